@@ -0,0 +1,244 @@
+//! Audio/video sync: decides when a frontend runs frames and nudges the
+//! audio rate so the host's audio queue neither runs dry nor piles up.
+//!
+//! A frontend is clocked by one of two things:
+//!
+//! - **Video** ([`SyncMode::Video`]): the display or a timer wakes the
+//!   frontend, which runs however many frames the elapsed wall time is
+//!   worth. The audio device's clock never quite matches, so its queue
+//!   drifts; dynamic rate control stretches or squeezes the APU output by
+//!   up to a fraction of a percent to hold the queue near its target. The
+//!   pitch change is far below what anyone can hear.
+//! - **Audio** ([`SyncMode::Audio`]): the frontend runs a frame whenever the
+//!   audio queue falls below its target and shows the newest frame when the
+//!   display asks. The audio is never resampled; the video repeats or drops
+//!   a frame now and then instead.
+//!
+//! ```ignore
+//! let mut sync = AvSync::new(SyncMode::Video, cpu.sample_rate());
+//! loop {
+//!     let (elapsed_us, queued) = host.wait_for_vsync();
+//!     for _ in 0..sync.frames_due(elapsed_us, queued) {
+//!         cpu.run_frame()?;
+//!         host.queue_audio(&cpu.drain_audio_samples());
+//!     }
+//!     cpu.set_audio_rate_skew(sync.rate_skew());
+//! }
+//! ```
+
+use crate::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+
+/// DMG T-cycle frequency.
+const CPU_FREQ: u64 = 4_194_304;
+const MICROS_PER_SECOND: u64 = 1_000_000;
+
+const DEFAULT_TARGET_LATENCY_MS: u32 = 50;
+const DEFAULT_MAX_SKEW: f32 = 0.005;
+/// Weight of each new reading in the smoothed queue fill. Hosts hand audio
+/// to the device in bursts, so single readings swing by a whole burst.
+const FILL_SMOOTHING: f32 = 1.0 / 32.0;
+/// After a stall (a hidden browser tab, a debugger pause) run at most this
+/// many frames at once and drop the rest of the backlog.
+const MAX_CATCHUP_FRAMES: u32 = 4;
+
+/// What paces the frontend; see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncMode {
+    #[default]
+    Video,
+    Audio,
+}
+
+/// Frame pacing and dynamic rate control for one audio output.
+///
+/// Queue fills are counted in stereo pairs at the output sample rate, the
+/// unit [`crate::cpu::sm83::Sm83::drain_audio_samples`] produces two floats
+/// of.
+pub struct AvSync {
+    mode: SyncMode,
+    sample_rate: u32,
+    target_latency_ms: u32,
+    /// Queue fill to aim for, in stereo pairs.
+    target: u32,
+    max_skew: f32,
+    /// Smoothed queue fill, in stereo pairs.
+    fill: f32,
+    /// Video mode: emulated time owed, in T-cycles × 10⁶ so elapsed
+    /// microseconds convert without rounding.
+    owed: u64,
+}
+
+impl AvSync {
+    pub fn new(mode: SyncMode, sample_rate: u32) -> Self {
+        let mut sync = AvSync {
+            mode,
+            sample_rate,
+            target_latency_ms: DEFAULT_TARGET_LATENCY_MS,
+            target: 0,
+            max_skew: DEFAULT_MAX_SKEW,
+            fill: 0.0,
+            owed: 0,
+        };
+        sync.set_sample_rate(sample_rate);
+        sync
+    }
+
+    /// Aim to keep `ms` milliseconds of audio queued (default 50). It must
+    /// cover the largest burst the host's audio device takes at once.
+    pub fn with_target_latency_ms(mut self, ms: u32) -> Self {
+        self.target_latency_ms = ms.max(1);
+        self.set_sample_rate(self.sample_rate);
+        self
+    }
+
+    /// Let rate control stretch or squeeze the audio by at most this
+    /// fraction (default 0.005, i.e. ±0.5%).
+    pub fn with_max_skew(mut self, skew: f32) -> Self {
+        self.max_skew = skew.clamp(0.0, crate::cpu::peripheral::apu::MAX_RATE_SKEW);
+        self
+    }
+
+    pub fn mode(&self) -> SyncMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: SyncMode) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    /// Follow a change of the output rate, e.g. after
+    /// [`crate::cpu::sm83::Sm83::set_sample_rate`].
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.max(1);
+        self.target = (self.sample_rate as u64 * self.target_latency_ms as u64 / 1000).max(1) as u32;
+        self.reset();
+    }
+
+    /// Queue fill the controller aims for, in stereo pairs.
+    pub fn target_fill(&self) -> u32 {
+        self.target
+    }
+
+    /// Forget the backlog and the fill history, e.g. after a pause or when
+    /// the audio device was reopened.
+    pub fn reset(&mut self) {
+        self.owed = 0;
+        self.fill = self.target as f32;
+    }
+
+    /// Record how many stereo pairs the host has queued but not yet played.
+    /// [`AvSync::frames_due`] does this itself; call it directly when the
+    /// frontend runs frames on a schedule of its own.
+    pub fn measure(&mut self, queued: u32) {
+        self.fill += (queued as f32 - self.fill) * FILL_SMOOTHING;
+    }
+
+    /// Frames to run now, `elapsed_us` after the previous call and with
+    /// `queued` stereo pairs waiting in the host's audio queue. Video mode
+    /// goes by the elapsed time, audio mode by the queue.
+    pub fn frames_due(&mut self, elapsed_us: u32, queued: u32) -> u32 {
+        self.measure(queued);
+        match self.mode {
+            SyncMode::Video => {
+                let frame = CYCLES_PER_FRAME * MICROS_PER_SECOND;
+                self.owed += elapsed_us as u64 * CPU_FREQ;
+                let frames = self.owed / frame;
+                if frames > MAX_CATCHUP_FRAMES as u64 {
+                    self.owed = 0;
+                    MAX_CATCHUP_FRAMES
+                } else {
+                    self.owed -= frames * frame;
+                    frames as u32
+                }
+            }
+            SyncMode::Audio => {
+                let per_frame = (self.sample_rate as u64 * CYCLES_PER_FRAME / CPU_FREQ).max(1) as u32;
+                let missing = self.target.saturating_sub(queued);
+                missing.div_ceil(per_frame).min(MAX_CATCHUP_FRAMES)
+            }
+        }
+    }
+
+    /// Skew for [`crate::cpu::sm83::Sm83::set_audio_rate_skew`]: positive
+    /// when the queue runs low, negative when it runs high, in proportion to
+    /// how far the smoothed fill is from the target. Always 0 in audio mode,
+    /// where the frame schedule already holds the queue at the target.
+    pub fn rate_skew(&self) -> f32 {
+        match self.mode {
+            SyncMode::Video => {
+                let error = (self.target as f32 - self.fill) / self.target as f32;
+                error.clamp(-1.0, 1.0) * self.max_skew
+            }
+            SyncMode::Audio => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One Game Boy frame, rounded to the microsecond.
+    const FRAME_US: u32 = 16_743;
+
+    #[test]
+    fn test_video_mode_paces_by_wall_time() {
+        for refresh_us in [16_667, 8_333, 6_944, 62_500] {
+            let mut sync = AvSync::new(SyncMode::Video, 48_000);
+            let ticks = 10_000_000 / refresh_us;
+            let frames: u32 = (0..ticks).map(|_| sync.frames_due(refresh_us, 0)).sum();
+            // 59.7275 frames per second of elapsed time.
+            let expected = (ticks * refresh_us) as f64 * 59.7275 / 1e6;
+            assert!((frames as f64 - expected).abs() <= 1.0, "{} us: {} frames", refresh_us, frames);
+        }
+    }
+
+    #[test]
+    fn test_video_mode_drops_long_stalls() {
+        let mut sync = AvSync::new(SyncMode::Video, 48_000);
+        assert_eq!(sync.frames_due(1_000_000, 0), MAX_CATCHUP_FRAMES);
+        assert_eq!(sync.frames_due(FRAME_US / 2, 0), 0);
+        sync.reset();
+        assert_eq!(sync.frames_due(FRAME_US, 0), 1);
+    }
+
+    #[test]
+    fn test_audio_mode_fills_queue_to_target() {
+        let mut sync = AvSync::new(SyncMode::Audio, 48_000).with_target_latency_ms(40);
+        assert_eq!(sync.target_fill(), 1920);
+        // About 804 pairs per frame.
+        assert_eq!(sync.frames_due(0, 0), 3);
+        assert_eq!(sync.frames_due(0, 1200), 1);
+        assert_eq!(sync.frames_due(0, 1920), 0);
+        assert_eq!(sync.frames_due(0, 100_000), 0);
+        assert_eq!(sync.rate_skew(), 0.0);
+    }
+
+    /// A video-clocked host whose audio device plays slightly fast or slow:
+    /// rate control settles on a skew that matches it, and the queue never
+    /// runs dry or grows without bound.
+    #[test]
+    fn test_rate_control_tracks_device_clock() {
+        for drift in [0.003f64, -0.003] {
+            let mut sync = AvSync::new(SyncMode::Video, 48_000);
+            let target = sync.target_fill() as f64;
+            let mut queued = target;
+            let (mut low, mut high) = (f64::MAX, 0.0f64);
+            for tick in 0..20_000 {
+                let elapsed = 6_944; // 144 Hz display
+                queued -= 48_000.0 * (1.0 + drift) * elapsed as f64 / 1e6;
+                let frames = sync.frames_due(elapsed, queued.max(0.0) as u32);
+                let skew = sync.rate_skew() as f64;
+                queued += frames as f64 * 70_224.0 * 48_000.0 * (1.0 + skew) / 4_194_304.0;
+                if tick > 5_000 {
+                    low = low.min(queued);
+                    high = high.max(queued);
+                }
+            }
+            let skew = sync.rate_skew() as f64;
+            assert!((skew - drift).abs() < 0.0005, "drift {}: skew {}", drift, skew);
+            assert!(low > 0.0 && high < 2.0 * target, "drift {}: queue {}..{}", drift, low, high);
+        }
+    }
+}
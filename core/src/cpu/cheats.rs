@@ -0,0 +1,319 @@
+//! GameShark and Game Genie cheat codes.
+//!
+//! A GameShark sits between the cartridge and the console and rewrites RAM
+//! once per frame from the VBlank interrupt. A Game Genie sits on the
+//! cartridge bus instead and substitutes bytes as ROM is read. Both are
+//! emulated the same way: [`Sm83`](super::sm83::Sm83) owns a [`CheatList`],
+//! applies the GameShark writes after the instruction during which the PPU
+//! enters VBlank and hands the Game Genie patches to the memory bus, which
+//! checks them on every ROM read.
+//!
+//! ```ignore
+//! let index = cpu.add_cheat("010F12C3")?; // GameShark: 0x0F -> 0xC312
+//! cpu.add_cheat("00A-17B-C49")?;          // Game Genie
+//! cpu.set_cheat_enabled(index, false);
+//! ```
+//!
+//! ## Code formats
+//!
+//! GameShark `ttvvaaaa`, 8 hex digits: write `vv` to address `aaaa` (low
+//! byte first) every frame. `tt` picks the external RAM bank for addresses
+//! in 0xA000–0xBFFF: `00` or `01` write through whatever bank is mapped,
+//! `80`–`8F` write bank `tt & 0x0F` directly.
+//!
+//! Game Genie `ABC-DEF` or `ABC-DEF-GHI`, hex digits: replace the byte at
+//! ROM address `(F ^ 0xF) C D E` with `AB`. The optional `GI` pair encodes
+//! a compare value, rotated right by two and XORed with 0xBA; the patch
+//! then only applies while the original byte matches, which is how a code
+//! targets one bank of the switchable window. `H` is not used.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::memory::memory::RomPatch;
+
+/// Reasons a code string is rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheatError {
+    /// Not shaped like either kind of code.
+    BadFormat,
+    /// A GameShark code type other than 00, 01 or 80–8F.
+    UnsupportedType(u8),
+    /// A GameShark code writing outside RAM, or a Game Genie code patching
+    /// outside ROM.
+    BadAddress(u16),
+    /// A GameShark code writing an external RAM bank the cartridge lacks.
+    NoSuchRamBank(u8),
+}
+
+impl fmt::Display for CheatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheatError::BadFormat => {
+                write!(f, "expected a GameShark code (ttvvaaaa) or a Game Genie code (ABC-DEF or ABC-DEF-GHI)")
+            }
+            CheatError::UnsupportedType(t) => write!(f, "unsupported GameShark code type {:02X}", t),
+            CheatError::BadAddress(a) => write!(f, "code targets address {:04X}, outside its memory region", a),
+            CheatError::NoSuchRamBank(b) => write!(f, "code writes RAM bank {:X}, which this cartridge doesn't have", b),
+        }
+    }
+}
+
+/// A parsed cheat code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheatCode {
+    /// Write `value` to `address` every frame. `bank` is the external RAM
+    /// bank to write, or `None` to write through the mapped bank.
+    GameShark { bank: Option<u8>, address: u16, value: u8 },
+    /// Patch ROM reads; see [`RomPatch`].
+    GameGenie(RomPatch),
+}
+
+impl CheatCode {
+    /// Parse a GameShark or Game Genie code. Surrounding whitespace and case
+    /// are ignored.
+    pub fn parse(code: &str) -> Result<Self, CheatError> {
+        let code = code.trim();
+        if code.contains('-') {
+            parse_game_genie(code)
+        } else if code.len() == 8 {
+            parse_game_shark(code)
+        } else {
+            Err(CheatError::BadFormat)
+        }
+    }
+}
+
+impl fmt::Display for CheatCode {
+    /// The canonical form of the code: upper case, dashes for Game Genie.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CheatCode::GameShark { bank, address, value } => {
+                let kind = bank.map_or(0x01, |b| 0x80 | b);
+                write!(f, "{:02X}{:02X}{:02X}{:02X}", kind, value, address as u8, address >> 8)
+            }
+            CheatCode::GameGenie(RomPatch { address, value, compare }) => {
+                let a = address ^ 0xF000;
+                write!(
+                    f,
+                    "{:02X}{:X}-{:X}{:X}{:X}",
+                    value,
+                    (a >> 8) & 0xF,
+                    (a >> 4) & 0xF,
+                    a & 0xF,
+                    a >> 12
+                )?;
+                if let Some(compare) = compare {
+                    let gi = (compare ^ 0xBA).rotate_left(2);
+                    // H carries nothing the emulator uses; write it as 0.
+                    write!(f, "-{:X}0{:X}", gi >> 4, gi & 0xF)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn hex_digits(s: &str) -> Result<Vec<u8>, CheatError> {
+    s.chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8).ok_or(CheatError::BadFormat))
+        .collect()
+}
+
+fn parse_game_shark(code: &str) -> Result<CheatCode, CheatError> {
+    let d = hex_digits(code)?;
+    let byte = |i: usize| (d[i] << 4) | d[i + 1];
+    let kind = byte(0);
+    let value = byte(2);
+    let address = u16::from_le_bytes([byte(4), byte(6)]);
+    let bank = match kind {
+        0x00 | 0x01 => None,
+        0x80..=0x8F => Some(kind & 0x0F),
+        _ => return Err(CheatError::UnsupportedType(kind)),
+    };
+    if !matches!(address, 0xA000..=0xDFFF | 0xFF80..=0xFFFE) {
+        return Err(CheatError::BadAddress(address));
+    }
+    Ok(CheatCode::GameShark { bank, address, value })
+}
+
+fn parse_game_genie(code: &str) -> Result<CheatCode, CheatError> {
+    let groups: Vec<&str> = code.split('-').collect();
+    if !matches!(groups.len(), 2 | 3) || groups.iter().any(|g| g.len() != 3) {
+        return Err(CheatError::BadFormat);
+    }
+    let d = hex_digits(&groups.concat())?;
+    let value = (d[0] << 4) | d[1];
+    let address = (((d[5] ^ 0xF) as u16) << 12) | ((d[2] as u16) << 8) | ((d[3] as u16) << 4) | d[4] as u16;
+    if address >= 0x8000 {
+        return Err(CheatError::BadAddress(address));
+    }
+    let compare = (d.len() == 9).then(|| ((d[6] << 4) | d[8]).rotate_right(2) ^ 0xBA);
+    Ok(CheatCode::GameGenie(RomPatch { address, value, compare }))
+}
+
+/// One entry in a [`CheatList`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cheat {
+    pub code: CheatCode,
+    /// The code as the user typed it, kept for display and storage.
+    pub text: String,
+    pub enabled: bool,
+}
+
+/// The cheats loaded for a game, each enabled or not.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheatList {
+    cheats: Vec<Cheat>,
+}
+
+impl CheatList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `text` and append it, enabled. Returns its index.
+    pub fn add(&mut self, text: &str) -> Result<usize, CheatError> {
+        let code = CheatCode::parse(text)?;
+        self.cheats.push(Cheat { code, text: String::from(text.trim()), enabled: true });
+        Ok(self.cheats.len() - 1)
+    }
+
+    /// Remove the cheat at `index`, shifting later ones down.
+    pub fn remove(&mut self, index: usize) -> Option<Cheat> {
+        (index < self.cheats.len()).then(|| self.cheats.remove(index))
+    }
+
+    /// Enable or disable the cheat at `index`. Returns `false` if there is
+    /// no such cheat.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) -> bool {
+        match self.cheats.get_mut(index) {
+            Some(cheat) => {
+                cheat.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cheats.clear();
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cheats.is_empty()
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &CheatCode> {
+        self.cheats.iter().filter(|c| c.enabled).map(|c| &c.code)
+    }
+
+    /// Patches for the enabled Game Genie codes.
+    pub fn rom_patches(&self) -> Vec<RomPatch> {
+        self.enabled()
+            .filter_map(|code| match code {
+                CheatCode::GameGenie(patch) => Some(*patch),
+                CheatCode::GameShark { .. } => None,
+            })
+            .collect()
+    }
+
+    /// RAM writes for the enabled GameShark codes, in list order:
+    /// `(bank, address, value)`.
+    pub fn ram_writes(&self) -> impl Iterator<Item = (Option<u8>, u16, u8)> + '_ {
+        self.enabled().filter_map(|code| match *code {
+            CheatCode::GameShark { bank, address, value } => Some((bank, address, value)),
+            CheatCode::GameGenie(_) => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn parses_game_shark_codes() {
+        assert_eq!(
+            CheatCode::parse("010F12C3"),
+            Ok(CheatCode::GameShark { bank: None, address: 0xC312, value: 0x0F })
+        );
+        assert_eq!(
+            CheatCode::parse(" 826304A0\n"),
+            Ok(CheatCode::GameShark { bank: Some(2), address: 0xA004, value: 0x63 })
+        );
+        assert_eq!(
+            CheatCode::parse("0199a0ff"),
+            Ok(CheatCode::GameShark { bank: None, address: 0xFFA0, value: 0x99 })
+        );
+    }
+
+    #[test]
+    fn rejects_bad_game_shark_codes() {
+        assert_eq!(CheatCode::parse("0101004"), Err(CheatError::BadFormat));
+        assert_eq!(CheatCode::parse("01010G40"), Err(CheatError::BadFormat));
+        assert_eq!(CheatCode::parse("42010040"), Err(CheatError::UnsupportedType(0x42)));
+        // 0x4000 is ROM; a write there would switch banks.
+        assert_eq!(CheatCode::parse("01010040"), Err(CheatError::BadAddress(0x4000)));
+        assert_eq!(CheatCode::parse("010100FF"), Err(CheatError::BadAddress(0xFF00)));
+    }
+
+    #[test]
+    fn parses_game_genie_codes() {
+        // Address digits F C D E = E 1 5 A, and E ^ 0xF = 1.
+        assert_eq!(
+            CheatCode::parse("3A1-5AE"),
+            Ok(CheatCode::GameGenie(RomPatch { address: 0x115A, value: 0x3A, compare: None }))
+        );
+        let CheatCode::GameGenie(patch) = CheatCode::parse("00a-17b-c49").unwrap() else {
+            panic!("not a Game Genie code");
+        };
+        assert_eq!(patch.address, 0x4A17);
+        assert_eq!(patch.value, 0x00);
+        assert_eq!(patch.compare, Some(0xC9u8.rotate_right(2) ^ 0xBA));
+    }
+
+    #[test]
+    fn rejects_bad_game_genie_codes() {
+        assert_eq!(CheatCode::parse("3A1-5A"), Err(CheatError::BadFormat));
+        assert_eq!(CheatCode::parse("3A1-5AE-"), Err(CheatError::BadFormat));
+        assert_eq!(CheatCode::parse("3A1-5AE-C4X"), Err(CheatError::BadFormat));
+        // F digit 7 gives an address of 0x8xxx, which is VRAM.
+        assert_eq!(CheatCode::parse("3A1-5A7"), Err(CheatError::BadAddress(0x815A)));
+    }
+
+    #[test]
+    fn display_is_canonical_and_reparses() {
+        for text in ["010F12C3", "826304A0", "3A1-5AE", "00A-17B-C49"] {
+            let code = CheatCode::parse(text).unwrap();
+            assert_eq!(CheatCode::parse(&code.to_string()), Ok(code), "{text}");
+        }
+        assert_eq!(CheatCode::parse("3a1-5ae").unwrap().to_string(), "3A1-5AE");
+    }
+
+    #[test]
+    fn list_filters_enabled_codes_by_kind() {
+        let mut list = CheatList::new();
+        let shark = list.add("010F12C3").unwrap();
+        let genie = list.add("3A1-5AE").unwrap();
+        assert_eq!(list.ram_writes().collect::<Vec<_>>(), [(None, 0xC312, 0x0F)]);
+        assert_eq!(list.rom_patches().len(), 1);
+
+        assert!(list.set_enabled(shark, false));
+        assert!(list.set_enabled(genie, false));
+        assert_eq!(list.ram_writes().count(), 0);
+        assert!(list.rom_patches().is_empty());
+        assert!(!list.set_enabled(2, true));
+
+        assert_eq!(list.remove(shark).map(|c| c.text), Some("010F12C3".to_string()));
+        assert_eq!(list.cheats().len(), 1);
+        assert!(list.add("nope").is_err());
+        assert_eq!(list.cheats().len(), 1);
+    }
+}
@@ -0,0 +1,447 @@
+//! Interactive debugger layered over [`Sm83`].
+//!
+//! Only compiled with the `debugger` feature; without it `Sm83` carries no
+//! debugger state and its bus paths have no hooks.
+//!
+//! The [`Debugger`] holds breakpoints, watchpoints and break conditions and
+//! drives a CPU it borrows for each call, so a frontend can keep owning its
+//! `Sm83` and hand it to the debugger only while stepping:
+//!
+//! ```ignore
+//! let mut dbg = Debugger::new();
+//! dbg.add_breakpoint(Breakpoint::at(0x0150));
+//! dbg.add_watchpoint(Watchpoint::new(0xC000..=0xC0FF, Access::WRITE));
+//! match dbg.run(&mut cpu, 10 * CYCLES_PER_FRAME)? {
+//!     StopReason::Breakpoint { .. } => dbg.step_over(&mut cpu, CYCLES_PER_FRAME)?,
+//!     other => other,
+//! };
+//! ```
+//!
+//! Breakpoints, execute watchpoints and conditions are checked before an
+//! instruction executes, so the CPU stops with PC on that instruction. Read
+//! and write watchpoints are reported by the bus itself (`bus_read`,
+//! `bus_write`, PC fetches and OAM DMA), so they see every access; the CPU
+//! stops once the instruction that made the access has finished.
+//!
+//! Watchpoints are only armed while a debugger call is running.
+
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use bitflags::bitflags;
+
+use crate::cpu::cpu::{Cpu, CpuError};
+use crate::cpu::peripheral::ppu::{CYCLES_PER_FRAME, LY_ADDR};
+use crate::cpu::registers::Registers;
+use crate::cpu::sm83::Sm83;
+
+bitflags! {
+    /// Kinds of bus access a [`Watchpoint`] triggers on.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Access: u8 {
+        /// Any read bus cycle, including opcode and operand fetches.
+        const READ = 0x01;
+        /// Any write bus cycle.
+        const WRITE = 0x02;
+        /// Execution of an instruction starting inside the range.
+        const EXECUTE = 0x04;
+    }
+}
+
+/// Handle returned when adding a breakpoint, watchpoint or condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugId(u32);
+
+impl DebugId {
+    /// Numeric form, for frontends that cannot hold the handle itself.
+    pub fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+}
+
+// ── Breakpoints ─────────────────────────────────────────────────────────────
+
+/// Stop before executing the instruction at `addr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    /// ROM bank the address must be mapped from. `None` matches any bank.
+    /// Addresses below 0x4000 are bank 0; outside ROM the bank is ignored.
+    pub bank: Option<usize>,
+    /// Only stop when this condition also holds.
+    pub condition: Option<Condition>,
+}
+
+impl Breakpoint {
+    /// Unconditional breakpoint at `addr` in any bank.
+    pub fn at(addr: u16) -> Self {
+        Self { addr, bank: None, condition: None }
+    }
+
+    /// Unconditional breakpoint at `addr` while `bank` is mapped there.
+    pub fn in_bank(bank: usize, addr: u16) -> Self {
+        Self { addr, bank: Some(bank), condition: None }
+    }
+
+    pub fn when(self, condition: Condition) -> Self {
+        Self { condition: Some(condition), ..self }
+    }
+
+    fn matches(&self, cpu: &Sm83, regs: &Registers) -> bool {
+        if regs.pc != self.addr {
+            return false;
+        }
+        let bank_ok = match (self.bank, self.addr) {
+            (None, _) => true,
+            (Some(bank), 0x0000..=0x3FFF) => bank == 0,
+            (Some(bank), 0x4000..=0x7FFF) => bank == cpu.current_rom_bank(),
+            (Some(_), _) => true,
+        };
+        bank_ok && self.condition.is_none_or(|c| c.holds(regs))
+    }
+}
+
+// ── Watchpoints ─────────────────────────────────────────────────────────────
+
+/// Stop when the bus accesses an address in `range` in one of the `access` ways.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<u16>,
+    pub access: Access,
+}
+
+impl Watchpoint {
+    pub fn new(range: RangeInclusive<u16>, access: Access) -> Self {
+        Self { range, access }
+    }
+}
+
+/// The access that tripped a read or write watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchHit {
+    pub id: DebugId,
+    pub addr: u16,
+    pub value: u8,
+    pub access: Access,
+    /// The access was made by OAM DMA rather than the CPU.
+    pub dma: bool,
+}
+
+/// Read/write watchpoints armed on the CPU's bus while a debugger call runs.
+#[derive(Default)]
+pub(crate) struct BusWatch {
+    watchpoints: Vec<(DebugId, Watchpoint)>,
+    /// First watchpoint hit since the last `take`.
+    hit: Option<WatchHit>,
+    /// Interrupt bit dispatched since the last `take`.
+    interrupt: Option<u8>,
+}
+
+impl BusWatch {
+    #[inline(always)]
+    pub(crate) fn access(&mut self, addr: u16, value: u8, access: Access, dma: bool) {
+        if self.watchpoints.is_empty() || self.hit.is_some() {
+            return;
+        }
+        if let Some((id, _)) = self
+            .watchpoints
+            .iter()
+            .find(|(_, wp)| wp.access.contains(access) && wp.range.contains(&addr))
+        {
+            self.hit = Some(WatchHit { id: *id, addr, value, access, dma });
+        }
+    }
+
+    pub(crate) fn interrupt(&mut self, bit: u8) {
+        self.interrupt = Some(bit);
+    }
+}
+
+// ── Conditions ──────────────────────────────────────────────────────────────
+
+/// A CPU register, as seen by a [`Condition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reg {
+    A, F, B, C, D, E, H, L,
+    AF, BC, DE, HL, SP, PC,
+}
+
+impl Reg {
+    fn read(self, r: &Registers) -> u16 {
+        let pair = |hi: u8, lo: u8| u16::from_be_bytes([hi, lo]);
+        match self {
+            Reg::A  => r.a as u16,
+            Reg::F  => r.f.bits() as u16,
+            Reg::B  => r.b as u16,
+            Reg::C  => r.c as u16,
+            Reg::D  => r.d as u16,
+            Reg::E  => r.e as u16,
+            Reg::H  => r.h as u16,
+            Reg::L  => r.l as u16,
+            Reg::AF => pair(r.a, r.f.bits()),
+            Reg::BC => pair(r.b, r.c),
+            Reg::DE => pair(r.d, r.e),
+            Reg::HL => pair(r.h, r.l),
+            Reg::SP => r.sp,
+            Reg::PC => r.pc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare {
+    Eq, Ne, Lt, Le, Gt, Ge,
+}
+
+/// `reg <compare> value`, evaluated before each instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub reg: Reg,
+    pub compare: Compare,
+    pub value: u16,
+}
+
+impl Condition {
+    pub fn new(reg: Reg, compare: Compare, value: u16) -> Self {
+        Self { reg, compare, value }
+    }
+
+    fn holds(&self, regs: &Registers) -> bool {
+        let lhs = self.reg.read(regs);
+        match self.compare {
+            Compare::Eq => lhs == self.value,
+            Compare::Ne => lhs != self.value,
+            Compare::Lt => lhs < self.value,
+            Compare::Le => lhs <= self.value,
+            Compare::Gt => lhs > self.value,
+            Compare::Ge => lhs >= self.value,
+        }
+    }
+}
+
+// ── Stop reasons ────────────────────────────────────────────────────────────
+
+/// Why a debugger call returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// PC reached a breakpoint; the instruction there has not executed.
+    Breakpoint { id: DebugId, pc: u16 },
+    /// An instruction starting inside an execute watchpoint is about to run.
+    Execute { id: DebugId, pc: u16 },
+    /// The instruction at `pc` (or DMA during it) hit a read/write watchpoint.
+    Watchpoint { hit: WatchHit, pc: u16 },
+    /// A standalone break condition holds at `pc`.
+    Condition { id: DebugId, pc: u16 },
+    /// An enabled interrupt was dispatched; PC is at its vector.
+    Interrupt { bit: u8 },
+    /// The requested step, step-over, step-out or run-to-scanline finished.
+    Step,
+    /// The PPU entered VBlank during [`Debugger::run_frame`].
+    Frame,
+    /// The cycle budget ran out first.
+    CycleLimit,
+}
+
+// ── Debugger ────────────────────────────────────────────────────────────────
+
+const CALL_LEN: u16 = 3;
+const RST_LEN: u16 = 1;
+
+/// Breakpoints, watchpoints and break conditions, plus the stepping
+/// operations that honour them.
+#[derive(Default)]
+pub struct Debugger {
+    next_id: u32,
+    breakpoints: Vec<(DebugId, Breakpoint)>,
+    watchpoints: Vec<(DebugId, Watchpoint)>,
+    conditions: Vec<(DebugId, Condition)>,
+    /// IF/IE bit mask of interrupts whose dispatch stops execution.
+    interrupt_mask: u8,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn alloc_id(&mut self) -> DebugId {
+        self.next_id += 1;
+        DebugId(self.next_id)
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> DebugId {
+        let id = self.alloc_id();
+        self.breakpoints.push((id, breakpoint));
+        id
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> DebugId {
+        let id = self.alloc_id();
+        self.watchpoints.push((id, watchpoint));
+        id
+    }
+
+    /// Stop before any instruction at which `condition` holds.
+    pub fn add_condition(&mut self, condition: Condition) -> DebugId {
+        let id = self.alloc_id();
+        self.conditions.push((id, condition));
+        id
+    }
+
+    /// Remove a breakpoint, watchpoint or condition. Returns false if `id`
+    /// is unknown.
+    pub fn remove(&mut self, id: DebugId) -> bool {
+        let before = self.breakpoints.len() + self.watchpoints.len() + self.conditions.len();
+        self.breakpoints.retain(|(i, _)| *i != id);
+        self.watchpoints.retain(|(i, _)| *i != id);
+        self.conditions.retain(|(i, _)| *i != id);
+        before != self.breakpoints.len() + self.watchpoints.len() + self.conditions.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.watchpoints.clear();
+        self.conditions.clear();
+        self.interrupt_mask = 0;
+    }
+
+    /// Stop right after dispatching any interrupt whose IF bit is in `mask`
+    /// (bit 0 = VBlank … bit 4 = joypad). Zero disables.
+    pub fn break_on_interrupts(&mut self, mask: u8) {
+        self.interrupt_mask = mask & 0x1F;
+    }
+
+    /// Run until something stops execution or `max_cycles` T-cycles pass.
+    /// Breakpoints at the current PC are skipped so a stopped CPU can resume.
+    pub fn run(&mut self, cpu: &mut Sm83, max_cycles: u64) -> Result<StopReason, CpuError> {
+        self.drive(cpu, max_cycles, |_, _| None)
+    }
+
+    /// Like [`Sm83::run_until_vblank`], but honouring breakpoints.
+    pub fn run_frame(&mut self, cpu: &mut Sm83) -> Result<StopReason, CpuError> {
+        cpu.clear_vblank_entered();
+        self.drive(cpu, CYCLES_PER_FRAME, |cpu, _| {
+            cpu.vblank_entered().then_some(StopReason::Frame)
+        })
+    }
+
+    /// Execute one instruction (or one idle M-cycle while halted or stopped).
+    pub fn step_in(&mut self, cpu: &mut Sm83) -> Result<StopReason, CpuError> {
+        self.drive(cpu, 1, |_, _| Some(StopReason::Step))
+    }
+
+    /// Step, treating a CALL or RST as one instruction: run until it returns
+    /// to the next instruction at the same stack depth.
+    pub fn step_over(&mut self, cpu: &mut Sm83, max_cycles: u64) -> Result<StopReason, CpuError> {
+        let regs = cpu.registers();
+        let len = match cpu.read_memory(regs.pc).unwrap_or(0) {
+            0xCD | 0xC4 | 0xCC | 0xD4 | 0xDC => CALL_LEN,
+            op if op & 0xC7 == 0xC7 => RST_LEN,
+            _ => return self.step_in(cpu),
+        };
+        let (ret, sp) = (regs.pc.wrapping_add(len), regs.sp);
+        self.drive(cpu, max_cycles, move |cpu, _| {
+            let now = cpu.registers();
+            (now.pc == ret && now.sp == sp).then_some(StopReason::Step)
+        })
+    }
+
+    /// Run until the current function returns to its caller.
+    pub fn step_out(&mut self, cpu: &mut Sm83, max_cycles: u64) -> Result<StopReason, CpuError> {
+        let sp = cpu.registers().sp;
+        self.drive(cpu, max_cycles, move |cpu, opcode| {
+            let is_ret = matches!(opcode, 0xC9 | 0xD9 | 0xC0 | 0xC8 | 0xD0 | 0xD8);
+            (is_ret && cpu.registers().sp > sp).then_some(StopReason::Step)
+        })
+    }
+
+    /// Run until LY changes to `ly`. If the PPU is already on that line this
+    /// runs to its next occurrence.
+    pub fn run_to_scanline(
+        &mut self,
+        cpu: &mut Sm83,
+        ly: u8,
+        max_cycles: u64,
+    ) -> Result<StopReason, CpuError> {
+        let mut prev = cpu.read_memory(LY_ADDR).unwrap_or(0);
+        self.drive(cpu, max_cycles, move |cpu, _| {
+            let now = cpu.read_memory(LY_ADDR).unwrap_or(0);
+            let reached = now == ly && prev != ly;
+            prev = now;
+            reached.then_some(StopReason::Step)
+        })
+    }
+
+    /// Core loop: check pre-execution breaks, tick, then check bus hits,
+    /// interrupts and `done` (given the opcode that just ran).
+    fn drive(
+        &mut self,
+        cpu: &mut Sm83,
+        max_cycles: u64,
+        mut done: impl FnMut(&Sm83, u8) -> Option<StopReason>,
+    ) -> Result<StopReason, CpuError> {
+        cpu.bus_watch_mut().watchpoints = self.watchpoints.clone();
+        let result = self.drive_armed(cpu, max_cycles, &mut done);
+        *cpu.bus_watch_mut() = BusWatch::default();
+        result
+    }
+
+    fn drive_armed(
+        &self,
+        cpu: &mut Sm83,
+        max_cycles: u64,
+        done: &mut impl FnMut(&Sm83, u8) -> Option<StopReason>,
+    ) -> Result<StopReason, CpuError> {
+        let start = cpu.cycle_counter();
+        let mut first = true;
+        while cpu.cycle_counter() - start < max_cycles {
+            let executing = cpu.will_execute();
+            if executing && !first {
+                if let Some(stop) = self.check_before(cpu) {
+                    return Ok(stop);
+                }
+            }
+            first = false;
+
+            let pc = cpu.registers().pc;
+            let opcode = if executing { cpu.read_memory(pc).unwrap_or(0) } else { 0 };
+            cpu.tick()?;
+
+            let watch = cpu.bus_watch_mut();
+            let (hit, interrupt) = (watch.hit.take(), watch.interrupt.take());
+            if let Some(hit) = hit {
+                return Ok(StopReason::Watchpoint { hit, pc });
+            }
+            if let Some(bit) = interrupt.filter(|b| self.interrupt_mask & (1 << b) != 0) {
+                return Ok(StopReason::Interrupt { bit });
+            }
+            if let Some(stop) = done(cpu, opcode) {
+                return Ok(stop);
+            }
+        }
+        Ok(StopReason::CycleLimit)
+    }
+
+    fn check_before(&self, cpu: &Sm83) -> Option<StopReason> {
+        let regs = cpu.registers();
+        let pc = regs.pc;
+        if let Some((id, _)) = self.breakpoints.iter().find(|(_, bp)| bp.matches(cpu, &regs)) {
+            return Some(StopReason::Breakpoint { id: *id, pc });
+        }
+        if let Some((id, _)) = self
+            .watchpoints
+            .iter()
+            .find(|(_, wp)| wp.access.contains(Access::EXECUTE) && wp.range.contains(&pc))
+        {
+            return Some(StopReason::Execute { id: *id, pc });
+        }
+        if let Some((id, _)) = self.conditions.iter().find(|(_, c)| c.holds(&regs)) {
+            return Some(StopReason::Condition { id: *id, pc });
+        }
+        None
+    }
+}
@@ -0,0 +1,960 @@
+//! SM83 disassembler built on the opcode decoders.
+//!
+//! Bytes are decoded through the same [`OpCodeTable`] the CPU executes from,
+//! then dispatched into a recording [`Instructions`] implementation instead of
+//! the CPU, so mnemonics, lengths and cycle counts always agree with what
+//! `Sm83` actually runs.
+//!
+//! ```ignore
+//! let dis = Disassembler::new().with_symbols(SymbolTable::parse_sym(&sym_file));
+//! let insn = dis.decode_cpu(&cpu, cpu.registers().pc)?;
+//! eprintln!("{:02X}:{:04X}  {}", insn.bank.unwrap_or(0), insn.addr, insn);
+//! ```
+//!
+//! Output uses RGBDS syntax: `LD A, [HL+]`, `LDH [$FF40], A`, `JR NZ, $0150`.
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::cpu::instructions::adc::opcode::Adc;
+use crate::cpu::instructions::add::opcode::{Add16, Add8, AddSP16};
+use crate::cpu::instructions::call::opcode::{Call, CallOp};
+use crate::cpu::instructions::cb::opcode::{CbInstruction, CbOp, CbTarget};
+use crate::cpu::instructions::cp::opcode::Cp8;
+use crate::cpu::instructions::inc_dec::opcode::{Dec16, Dec8, Inc16, Inc8};
+use crate::cpu::instructions::instructions::{Error as InstructionError, Instructions};
+use crate::cpu::instructions::jump::opcode::{Condition, Jump, JumpOp};
+use crate::cpu::instructions::ld::opcode::Ld8;
+use crate::cpu::instructions::ld16::opcode::{Ld16, Ld16Op};
+use crate::cpu::instructions::logic::opcode::{And8, Or8, Xor8};
+use crate::cpu::instructions::misc::opcode::{Misc, MiscOp};
+use crate::cpu::instructions::opcodes::{OpCodeDecoder, OpCodeTable};
+use crate::cpu::instructions::operand::{Memory, Operand, Register16, Register8};
+use crate::cpu::instructions::ret::opcode::{Ret, RetOp};
+use crate::cpu::instructions::rotate::opcode::{Rotate, RotateOp};
+use crate::cpu::instructions::rst::opcode::Rst;
+use crate::cpu::instructions::sbc::opcode::Sbc8;
+use crate::cpu::instructions::stack::opcode::{Pop16, Push16};
+use crate::cpu::instructions::sub::opcode::Sub8;
+use crate::cpu::sm83::Sm83;
+
+const CB_PREFIX: u8 = 0xCB;
+const ROM_BANK_SIZE: usize = 0x4000;
+
+// ── Errors ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// The byte at `addr` is not an SM83 opcode.
+    InvalidOpcode { addr: u16, opcode: u8 },
+    /// The instruction at `addr` runs past the end of the supplied bytes.
+    Truncated { addr: u16 },
+    /// `addr` is not in cartridge ROM, or the ROM image is too small for it.
+    OutsideRom { addr: u16 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidOpcode { addr, opcode } => {
+                write!(f, "Invalid opcode 0x{:02X} at 0x{:04X}", opcode, addr)
+            }
+            Error::Truncated { addr } => write!(f, "Instruction at 0x{:04X} is truncated", addr),
+            Error::OutsideRom { addr } => write!(f, "Address 0x{:04X} is outside the ROM", addr),
+        }
+    }
+}
+
+// ── Symbols ──────────────────────────────────────────────────────────────────
+
+/// Labels keyed by `(bank, address)`, as in RGBDS `.sym` files.
+///
+/// Addresses outside 0x4000–0x7FFF are looked up in bank 0 unless an exact
+/// `(bank, address)` entry exists, so WRAM/HRAM labels resolve regardless of
+/// which ROM bank is mapped.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+    labels: BTreeMap<(usize, u16), String>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse an RGBDS-style symbol file (`BB:AAAA Name` per line, `;` comments).
+    /// Lines that don't match the format are skipped.
+    pub fn parse_sym(text: &str) -> Self {
+        let mut table = Self::new();
+        for line in text.lines() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            let Some((location, name)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let Some((bank, addr)) = location.split_once(':') else {
+                continue;
+            };
+            if let (Ok(bank), Ok(addr)) = (
+                usize::from_str_radix(bank, 16),
+                u16::from_str_radix(addr, 16),
+            ) {
+                table.insert(bank, addr, name.trim());
+            }
+        }
+        table
+    }
+
+    pub fn insert(&mut self, bank: usize, addr: u16, name: &str) {
+        self.labels.insert((bank, addr), name.to_string());
+    }
+
+    /// Label for `addr`. `bank` is the ROM bank mapped at 0x4000–0x7FFF when
+    /// known; with `None`, switchable-bank addresses never match.
+    pub fn lookup(&self, bank: Option<usize>, addr: u16) -> Option<&str> {
+        let bank = match addr {
+            0x4000..=0x7FFF => bank?,
+            _ => bank.unwrap_or(0),
+        };
+        self.labels
+            .get(&(bank, addr))
+            .or_else(|| self.labels.get(&(0, addr)))
+            .map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+// ── Instruction ──────────────────────────────────────────────────────────────
+
+/// One decoded instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub addr: u16,
+    /// ROM bank mapped at 0x4000–0x7FFF when this was decoded, if known.
+    pub bank: Option<usize>,
+    /// Raw encoding; only the first `len` bytes are meaningful.
+    pub bytes: [u8; 3],
+    pub len: u8,
+    pub mnemonic: &'static str,
+    /// Formatted operands, with symbols substituted for addresses.
+    pub operands: Vec<String>,
+    /// T-cycles, counting the branch as taken for conditional instructions.
+    pub cycles: u8,
+    /// T-cycles when a conditional branch is not taken.
+    pub cycles_not_taken: Option<u8>,
+    /// Destination of a jump, call or RST, when it is statically known.
+    pub target: Option<u16>,
+}
+
+impl Instruction {
+    pub fn encoding(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// Address of the following instruction.
+    pub fn next_addr(&self) -> u16 {
+        self.addr.wrapping_add(self.len as u16)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mnemonic)?;
+        for (i, operand) in self.operands.iter().enumerate() {
+            f.write_str(if i == 0 { " " } else { ", " })?;
+            f.write_str(operand)?;
+        }
+        Ok(())
+    }
+}
+
+// ── Disassembler ─────────────────────────────────────────────────────────────
+
+pub struct Disassembler {
+    opcodes: OpCodeTable,
+    symbols: SymbolTable,
+}
+
+impl Default for Disassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Disassembler {
+    pub fn new() -> Self {
+        Self {
+            opcodes: OpCodeTable::from_decoder(&OpCodeDecoder::new()),
+            symbols: SymbolTable::new(),
+        }
+    }
+
+    pub fn with_symbols(mut self, symbols: SymbolTable) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Decode the instruction at `addr`, whose encoding starts at `bytes[0]`.
+    /// `bank` is only used for symbol lookup.
+    pub fn decode(
+        &self,
+        addr: u16,
+        bank: Option<usize>,
+        bytes: &[u8],
+    ) -> Result<Instruction, Error> {
+        let opcode = *bytes.first().ok_or(Error::Truncated { addr })?;
+        let mut recorder = Recorder::new(addr, bytes);
+        let op = if opcode == CB_PREFIX {
+            let cb_opcode = *bytes.get(1).ok_or(Error::Truncated { addr })?;
+            self.opcodes.get_cb(cb_opcode)
+        } else {
+            self.opcodes.get(opcode)
+        }
+        .map_err(|_| Error::InvalidOpcode { addr, opcode })?;
+        op.execute(&mut recorder)
+            .map_err(|_| Error::InvalidOpcode { addr, opcode })?;
+
+        let len = recorder.len;
+        if bytes.len() < len as usize {
+            return Err(Error::Truncated { addr });
+        }
+        let mut encoding = [0u8; 3];
+        encoding[..len as usize].copy_from_slice(&bytes[..len as usize]);
+        Ok(Instruction {
+            addr,
+            bank,
+            bytes: encoding,
+            len,
+            mnemonic: recorder.mnemonic,
+            operands: recorder
+                .args
+                .iter()
+                .map(|arg| arg.format(&self.symbols, bank))
+                .collect(),
+            cycles: recorder.cycles,
+            cycles_not_taken: recorder.cycles_not_taken,
+            target: recorder.target,
+        })
+    }
+
+    /// Decode from a cartridge ROM image as if `bank` were mapped at
+    /// 0x4000–0x7FFF. `addr` must be in 0x0000–0x7FFF.
+    pub fn decode_rom(&self, rom: &[u8], bank: usize, addr: u16) -> Result<Instruction, Error> {
+        let offset = match addr {
+            0x0000..=0x3FFF => addr as usize,
+            0x4000..=0x7FFF => bank * ROM_BANK_SIZE + (addr as usize - ROM_BANK_SIZE),
+            _ => return Err(Error::OutsideRom { addr }),
+        };
+        // Don't let an instruction at the end of the window read into the next bank.
+        let window_end = offset - (addr as usize % ROM_BANK_SIZE) + ROM_BANK_SIZE;
+        let bytes = rom
+            .get(offset..window_end.min(rom.len()))
+            .ok_or(Error::OutsideRom { addr })?;
+        self.decode(addr, Some(bank), &bytes[..bytes.len().min(3)])
+    }
+
+    /// Decode from the CPU's current memory map without side effects.
+    pub fn decode_cpu(&self, cpu: &Sm83, addr: u16) -> Result<Instruction, Error> {
+        let mut bytes = [0u8; 3];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = cpu.read_memory(addr.wrapping_add(i as u16)).unwrap_or(0xFF);
+        }
+        self.decode(addr, Some(cpu.current_rom_bank()), &bytes)
+    }
+
+    /// Decode up to `count` consecutive instructions from the CPU's memory
+    /// map, stopping at the first invalid opcode.
+    pub fn listing(&self, cpu: &Sm83, addr: u16, count: usize) -> Vec<Instruction> {
+        let mut out = Vec::with_capacity(count);
+        let mut addr = addr;
+        while out.len() < count {
+            match self.decode_cpu(cpu, addr) {
+                Ok(insn) => {
+                    addr = insn.next_addr();
+                    out.push(insn);
+                }
+                Err(_) => break,
+            }
+        }
+        out
+    }
+}
+
+// ── Recorder ─────────────────────────────────────────────────────────────────
+
+/// An operand before symbol substitution.
+enum Arg {
+    Text(&'static str),
+    /// Bit index of `BIT`/`RES`/`SET`.
+    Bit(u8),
+    Imm8(u8),
+    Imm16(u16),
+    /// Signed offset, as in `ADD SP, e8`.
+    Offset(i8),
+    /// `SP+e8` in `LD HL, SP+e8`.
+    SpOffset(i8),
+    /// A code address (jump, call or RST target).
+    Addr(u16),
+    /// A memory operand at a fixed address: `[n16]`.
+    Mem(u16),
+}
+
+impl Arg {
+    fn format(&self, symbols: &SymbolTable, bank: Option<usize>) -> String {
+        match *self {
+            Arg::Text(text) => text.to_string(),
+            Arg::Bit(bit) => format!("{}", bit),
+            Arg::Imm8(value) => format!("${:02X}", value),
+            Arg::Imm16(value) => format!("${:04X}", value),
+            Arg::Offset(e) => format!("{}", e),
+            Arg::SpOffset(e) => format!("SP{:+}", e),
+            Arg::Addr(addr) => match symbols.lookup(bank, addr) {
+                Some(name) => name.to_string(),
+                None => format!("${:04X}", addr),
+            },
+            Arg::Mem(addr) => match symbols.lookup(bank, addr) {
+                Some(name) => format!("[{}]", name),
+                None => format!("[${:04X}]", addr),
+            },
+        }
+    }
+}
+
+/// Receives the decoded opcode through [`Instructions`] double dispatch and
+/// records its text form instead of executing it.
+struct Recorder<'a> {
+    addr: u16,
+    bytes: &'a [u8],
+    mnemonic: &'static str,
+    args: Vec<Arg>,
+    len: u8,
+    cycles: u8,
+    cycles_not_taken: Option<u8>,
+    target: Option<u16>,
+}
+
+impl<'a> Recorder<'a> {
+    fn new(addr: u16, bytes: &'a [u8]) -> Self {
+        Self {
+            addr,
+            bytes,
+            mnemonic: "",
+            args: Vec::new(),
+            len: 1,
+            cycles: 0,
+            cycles_not_taken: None,
+            target: None,
+        }
+    }
+
+    fn byte(&self, index: usize) -> u8 {
+        self.bytes.get(index).copied().unwrap_or(0)
+    }
+
+    fn imm8(&mut self) -> u8 {
+        self.len = 2;
+        self.byte(1)
+    }
+
+    fn imm16(&mut self) -> u16 {
+        self.len = 3;
+        u16::from_le_bytes([self.byte(1), self.byte(2)])
+    }
+
+    fn operand(&mut self, operand: &Operand) -> Arg {
+        match operand {
+            Operand::Register8(reg) => Arg::Text(register8_name(*reg)),
+            Operand::Register16(reg) => Arg::Text(register16_name(*reg)),
+            Operand::Imm8 => Arg::Imm8(self.imm8()),
+            Operand::Imm16 => Arg::Imm16(self.imm16()),
+            Operand::ImmSigned8 => Arg::Offset(self.imm8() as i8),
+            Operand::Memory(mem) => Arg::Text(match mem {
+                Memory::HL => "[HL]",
+                Memory::BC => "[BC]",
+                Memory::DE => "[DE]",
+                Memory::HLI => "[HL+]",
+                Memory::HLD => "[HL-]",
+            }),
+        }
+    }
+
+    fn record(
+        &mut self,
+        mnemonic: &'static str,
+        args: Vec<Arg>,
+        cycles: u8,
+    ) -> Result<u8, InstructionError> {
+        self.mnemonic = mnemonic;
+        self.args = args;
+        self.cycles = cycles;
+        Ok(cycles)
+    }
+
+    fn alu(
+        &mut self,
+        mnemonic: &'static str,
+        operand: &Operand,
+        cycles: u8,
+    ) -> Result<u8, InstructionError> {
+        let arg = self.operand(operand);
+        self.record(mnemonic, alloc::vec![Arg::Text("A"), arg], cycles)
+    }
+
+    fn unary(
+        &mut self,
+        mnemonic: &'static str,
+        operand: &Operand,
+        cycles: u8,
+    ) -> Result<u8, InstructionError> {
+        let arg = self.operand(operand);
+        self.record(mnemonic, alloc::vec![arg], cycles)
+    }
+
+    /// Record a branch to `target`, with an optional condition.
+    fn branch(
+        &mut self,
+        mnemonic: &'static str,
+        condition: Option<Condition>,
+        target: u16,
+        cycles: u8,
+        cycles_not_taken: u8,
+    ) -> Result<u8, InstructionError> {
+        let mut args = Vec::with_capacity(2);
+        if let Some(condition) = condition {
+            args.push(Arg::Text(condition_name(condition)));
+            self.cycles_not_taken = Some(cycles_not_taken);
+        }
+        args.push(Arg::Addr(target));
+        self.target = Some(target);
+        self.record(mnemonic, args, cycles)
+    }
+}
+
+fn register8_name(reg: Register8) -> &'static str {
+    match reg {
+        Register8::A => "A",
+        Register8::B => "B",
+        Register8::C => "C",
+        Register8::D => "D",
+        Register8::E => "E",
+        Register8::H => "H",
+        Register8::L => "L",
+    }
+}
+
+fn register16_name(reg: Register16) -> &'static str {
+    match reg {
+        Register16::AF => "AF",
+        Register16::BC => "BC",
+        Register16::DE => "DE",
+        Register16::HL => "HL",
+        Register16::SP => "SP",
+    }
+}
+
+fn condition_name(condition: Condition) -> &'static str {
+    match condition {
+        Condition::NZ => "NZ",
+        Condition::Z => "Z",
+        Condition::NC => "NC",
+        Condition::C => "C",
+    }
+}
+
+impl Instructions for Recorder<'_> {
+    fn add8(&mut self, opcode: &Add8) -> Result<u8, InstructionError> {
+        self.alu("ADD", &opcode.operand, opcode.cycles)
+    }
+
+    fn add16(&mut self, opcode: &Add16) -> Result<u8, InstructionError> {
+        let arg = self.operand(&opcode.operand);
+        self.record("ADD", alloc::vec![Arg::Text("HL"), arg], opcode.cycles)
+    }
+
+    fn add_sp16(&mut self, opcode: &AddSP16) -> Result<u8, InstructionError> {
+        let arg = self.operand(&opcode.operand);
+        self.record("ADD", alloc::vec![Arg::Text("SP"), arg], opcode.cycles)
+    }
+
+    fn adc(&mut self, opcode: &Adc) -> Result<u8, InstructionError> {
+        self.alu("ADC", &opcode.operand, opcode.cycles)
+    }
+
+    fn sub8(&mut self, opcode: &Sub8) -> Result<u8, InstructionError> {
+        self.alu("SUB", &opcode.operand, opcode.cycles)
+    }
+
+    fn sbc8(&mut self, opcode: &Sbc8) -> Result<u8, InstructionError> {
+        self.alu("SBC", &opcode.operand, opcode.cycles)
+    }
+
+    fn cp8(&mut self, opcode: &Cp8) -> Result<u8, InstructionError> {
+        self.alu("CP", &opcode.operand, opcode.cycles)
+    }
+
+    fn ld8(&mut self, opcode: &Ld8) -> Result<u8, InstructionError> {
+        let dest = self.operand(&opcode.dest);
+        let src = self.operand(&opcode.src);
+        self.record("LD", alloc::vec![dest, src], opcode.cycles)
+    }
+
+    fn ld16(&mut self, opcode: &Ld16) -> Result<u8, InstructionError> {
+        let (mnemonic, args) = match opcode.op {
+            Ld16Op::RrImm16 { dest } => (
+                "LD",
+                alloc::vec![Arg::Text(register16_name(dest)), Arg::Imm16(self.imm16())],
+            ),
+            Ld16Op::NnSp => ("LD", alloc::vec![Arg::Mem(self.imm16()), Arg::Text("SP")]),
+            Ld16Op::SpHl => ("LD", alloc::vec![Arg::Text("SP"), Arg::Text("HL")]),
+            Ld16Op::HlSpE => (
+                "LD",
+                alloc::vec![Arg::Text("HL"), Arg::SpOffset(self.imm8() as i8)],
+            ),
+            Ld16Op::BcA => ("LD", alloc::vec![Arg::Text("[BC]"), Arg::Text("A")]),
+            Ld16Op::DeA => ("LD", alloc::vec![Arg::Text("[DE]"), Arg::Text("A")]),
+            Ld16Op::ABc => ("LD", alloc::vec![Arg::Text("A"), Arg::Text("[BC]")]),
+            Ld16Op::ADe => ("LD", alloc::vec![Arg::Text("A"), Arg::Text("[DE]")]),
+            Ld16Op::HliA => ("LD", alloc::vec![Arg::Text("[HL+]"), Arg::Text("A")]),
+            Ld16Op::HldA => ("LD", alloc::vec![Arg::Text("[HL-]"), Arg::Text("A")]),
+            Ld16Op::AHli => ("LD", alloc::vec![Arg::Text("A"), Arg::Text("[HL+]")]),
+            Ld16Op::AHld => ("LD", alloc::vec![Arg::Text("A"), Arg::Text("[HL-]")]),
+            Ld16Op::NnA => ("LD", alloc::vec![Arg::Mem(self.imm16()), Arg::Text("A")]),
+            Ld16Op::ANn => ("LD", alloc::vec![Arg::Text("A"), Arg::Mem(self.imm16())]),
+            Ld16Op::LdhNA => (
+                "LDH",
+                alloc::vec![Arg::Mem(0xFF00 | self.imm8() as u16), Arg::Text("A")],
+            ),
+            Ld16Op::LdhAN => (
+                "LDH",
+                alloc::vec![Arg::Text("A"), Arg::Mem(0xFF00 | self.imm8() as u16)],
+            ),
+            Ld16Op::LdCA => ("LDH", alloc::vec![Arg::Text("[C]"), Arg::Text("A")]),
+            Ld16Op::LdAC => ("LDH", alloc::vec![Arg::Text("A"), Arg::Text("[C]")]),
+        };
+        self.record(mnemonic, args, opcode.cycles)
+    }
+
+    fn inc8(&mut self, opcode: &Inc8) -> Result<u8, InstructionError> {
+        self.unary("INC", &opcode.operand, opcode.cycles)
+    }
+
+    fn dec8(&mut self, opcode: &Dec8) -> Result<u8, InstructionError> {
+        self.unary("DEC", &opcode.operand, opcode.cycles)
+    }
+
+    fn inc16(&mut self, opcode: &Inc16) -> Result<u8, InstructionError> {
+        self.record(
+            "INC",
+            alloc::vec![Arg::Text(register16_name(opcode.operand))],
+            opcode.cycles,
+        )
+    }
+
+    fn dec16(&mut self, opcode: &Dec16) -> Result<u8, InstructionError> {
+        self.record(
+            "DEC",
+            alloc::vec![Arg::Text(register16_name(opcode.operand))],
+            opcode.cycles,
+        )
+    }
+
+    fn rotate_accumulator(&mut self, opcode: &Rotate) -> Result<u8, InstructionError> {
+        let mnemonic = match opcode.op {
+            RotateOp::Rlca => "RLCA",
+            RotateOp::Rla => "RLA",
+            RotateOp::Rrca => "RRCA",
+            RotateOp::Rra => "RRA",
+        };
+        self.record(mnemonic, Vec::new(), opcode.cycles)
+    }
+
+    fn and8(&mut self, opcode: &And8) -> Result<u8, InstructionError> {
+        self.alu("AND", &opcode.operand, opcode.cycles)
+    }
+
+    fn or8(&mut self, opcode: &Or8) -> Result<u8, InstructionError> {
+        self.alu("OR", &opcode.operand, opcode.cycles)
+    }
+
+    fn xor8(&mut self, opcode: &Xor8) -> Result<u8, InstructionError> {
+        self.alu("XOR", &opcode.operand, opcode.cycles)
+    }
+
+    fn jump(&mut self, opcode: &Jump) -> Result<u8, InstructionError> {
+        match opcode.op {
+            JumpOp::Jp => {
+                let target = self.imm16();
+                self.branch("JP", None, target, opcode.cycles, opcode.cycles)
+            }
+            JumpOp::JpCc(condition) => {
+                let target = self.imm16();
+                self.branch("JP", Some(condition), target, opcode.cycles, 12)
+            }
+            JumpOp::JpHl => self.record("JP", alloc::vec![Arg::Text("HL")], opcode.cycles),
+            JumpOp::Jr | JumpOp::JrCc(_) => {
+                let offset = self.imm8() as i8;
+                let target = self.addr.wrapping_add(2).wrapping_add(offset as u16);
+                let condition = match opcode.op {
+                    JumpOp::JrCc(condition) => Some(condition),
+                    _ => None,
+                };
+                self.branch("JR", condition, target, opcode.cycles, 8)
+            }
+        }
+    }
+
+    fn misc(&mut self, opcode: &Misc) -> Result<u8, InstructionError> {
+        let mnemonic = match opcode.op {
+            MiscOp::Nop => "NOP",
+            MiscOp::Halt => "HALT",
+            MiscOp::Stop => {
+                // STOP is followed by a padding byte the CPU skips.
+                self.len = 2;
+                "STOP"
+            }
+            MiscOp::Daa => "DAA",
+            MiscOp::Cpl => "CPL",
+            MiscOp::Scf => "SCF",
+            MiscOp::Ccf => "CCF",
+            MiscOp::Di => "DI",
+            MiscOp::Ei => "EI",
+        };
+        self.record(mnemonic, Vec::new(), opcode.cycles)
+    }
+
+    fn push16(&mut self, opcode: &Push16) -> Result<u8, InstructionError> {
+        self.record(
+            "PUSH",
+            alloc::vec![Arg::Text(register16_name(opcode.operand))],
+            opcode.cycles,
+        )
+    }
+
+    fn pop16(&mut self, opcode: &Pop16) -> Result<u8, InstructionError> {
+        self.record(
+            "POP",
+            alloc::vec![Arg::Text(register16_name(opcode.operand))],
+            opcode.cycles,
+        )
+    }
+
+    fn call(&mut self, opcode: &Call) -> Result<u8, InstructionError> {
+        let target = self.imm16();
+        match opcode.op {
+            CallOp::Call => self.branch("CALL", None, target, opcode.cycles, opcode.cycles),
+            CallOp::CallCc(condition) => {
+                self.branch("CALL", Some(condition), target, opcode.cycles, 12)
+            }
+        }
+    }
+
+    fn ret(&mut self, opcode: &Ret) -> Result<u8, InstructionError> {
+        match opcode.op {
+            RetOp::Ret => self.record("RET", Vec::new(), opcode.cycles),
+            RetOp::Reti => self.record("RETI", Vec::new(), opcode.cycles),
+            RetOp::RetCc(condition) => {
+                self.cycles_not_taken = Some(8);
+                self.record(
+                    "RET",
+                    alloc::vec![Arg::Text(condition_name(condition))],
+                    opcode.cycles,
+                )
+            }
+        }
+    }
+
+    fn rst(&mut self, opcode: &Rst) -> Result<u8, InstructionError> {
+        let target = opcode.vector as u16;
+        self.target = Some(target);
+        self.record("RST", alloc::vec![Arg::Addr(target)], opcode.cycles)
+    }
+
+    fn cb(&mut self, opcode: &CbInstruction) -> Result<u8, InstructionError> {
+        self.len = 2;
+        let target = Arg::Text(match opcode.target {
+            CbTarget::Reg(reg) => register8_name(reg),
+            CbTarget::HLMem => "[HL]",
+        });
+        let (mnemonic, bit) = match opcode.op {
+            CbOp::Rlc => ("RLC", None),
+            CbOp::Rrc => ("RRC", None),
+            CbOp::Rl => ("RL", None),
+            CbOp::Rr => ("RR", None),
+            CbOp::Sla => ("SLA", None),
+            CbOp::Sra => ("SRA", None),
+            CbOp::Swap => ("SWAP", None),
+            CbOp::Srl => ("SRL", None),
+            CbOp::Bit(bit) => ("BIT", Some(bit)),
+            CbOp::Res(bit) => ("RES", Some(bit)),
+            CbOp::Set(bit) => ("SET", Some(bit)),
+        };
+        let args = match bit {
+            Some(bit) => alloc::vec![Arg::Bit(bit), target],
+            None => alloc::vec![target],
+        };
+        self.record(mnemonic, args, opcode.cycles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{boxed::Box, string::ToString, vec};
+
+    use crate::cpu::instructions::opcodes::OpCodeDecoder;
+    use crate::memory::cartridge::NoMbc;
+    use crate::memory::memory::GameBoyMemory;
+
+    /// Instruction lengths for every main opcode; 0 marks the 11 unused
+    /// opcodes and the CB prefix (handled separately).
+    #[rustfmt::skip]
+    const LENGTHS: [u8; 256] = [
+        1, 3, 1, 1, 1, 1, 2, 1, 3, 1, 1, 1, 1, 1, 2, 1, // 0x
+        2, 3, 1, 1, 1, 1, 2, 1, 2, 1, 1, 1, 1, 1, 2, 1, // 1x
+        2, 3, 1, 1, 1, 1, 2, 1, 2, 1, 1, 1, 1, 1, 2, 1, // 2x
+        2, 3, 1, 1, 1, 1, 2, 1, 2, 1, 1, 1, 1, 1, 2, 1, // 3x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 4x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 5x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 6x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 7x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 8x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // 9x
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // Ax
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, // Bx
+        1, 1, 3, 3, 3, 1, 2, 1, 1, 1, 3, 0, 3, 3, 2, 1, // Cx
+        1, 1, 3, 0, 3, 1, 2, 1, 1, 1, 3, 0, 3, 0, 2, 1, // Dx
+        2, 1, 1, 0, 0, 1, 2, 1, 2, 1, 3, 0, 0, 0, 2, 1, // Ex
+        2, 1, 1, 1, 0, 1, 2, 1, 2, 1, 3, 1, 0, 0, 2, 1, // Fx
+    ];
+
+    fn text(bytes: &[u8]) -> String {
+        Disassembler::new()
+            .decode(0x0150, None, bytes)
+            .unwrap()
+            .to_string()
+    }
+
+    fn cpu_with_rom(rom: Vec<u8>) -> Sm83 {
+        let memory = Box::new(GameBoyMemory::with_cartridge(Box::new(NoMbc::new(rom))));
+        Sm83::new(memory, Box::new(OpCodeDecoder::new()))
+    }
+
+    #[test]
+    fn test_every_main_opcode_has_expected_length() {
+        let dis = Disassembler::new();
+        for opcode in 0..=255u8 {
+            let result = dis.decode(0x0000, None, &[opcode, 0x00, 0x00]);
+            match LENGTHS[opcode as usize] {
+                0 if opcode == CB_PREFIX => assert_eq!(result.unwrap().len, 2),
+                0 => assert_eq!(
+                    result,
+                    Err(Error::InvalidOpcode {
+                        addr: 0x0000,
+                        opcode
+                    }),
+                    "opcode 0x{:02X}",
+                    opcode
+                ),
+                len => assert_eq!(result.unwrap().len, len, "opcode 0x{:02X}", opcode),
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_cb_opcode_decodes() {
+        let dis = Disassembler::new();
+        for opcode in 0..=255u8 {
+            let insn = dis.decode(0x0000, None, &[CB_PREFIX, opcode]).unwrap();
+            assert_eq!(insn.len, 2);
+            assert!(insn.cycles >= 8, "CB 0x{:02X}", opcode);
+        }
+    }
+
+    #[test]
+    fn test_main_opcode_text() {
+        assert_eq!(text(&[0x00]), "NOP");
+        assert_eq!(text(&[0x3E, 0x12]), "LD A, $12");
+        assert_eq!(text(&[0x21, 0x34, 0x12]), "LD HL, $1234");
+        assert_eq!(text(&[0x22]), "LD [HL+], A");
+        assert_eq!(text(&[0x3A]), "LD A, [HL-]");
+        assert_eq!(text(&[0x70]), "LD [HL], B");
+        assert_eq!(text(&[0x08, 0x00, 0xC0]), "LD [$C000], SP");
+        assert_eq!(text(&[0xEA, 0x00, 0xC0]), "LD [$C000], A");
+        assert_eq!(text(&[0xE0, 0x40]), "LDH [$FF40], A");
+        assert_eq!(text(&[0xF2]), "LDH A, [C]");
+        assert_eq!(text(&[0xF8, 0xFE]), "LD HL, SP-2");
+        assert_eq!(text(&[0xE8, 0x05]), "ADD SP, 5");
+        assert_eq!(text(&[0x09]), "ADD HL, BC");
+        assert_eq!(text(&[0xC6, 0x01]), "ADD A, $01");
+        assert_eq!(text(&[0xAF]), "XOR A, A");
+        assert_eq!(text(&[0xFE, 0x90]), "CP A, $90");
+        assert_eq!(text(&[0x34]), "INC [HL]");
+        assert_eq!(text(&[0x0B]), "DEC BC");
+        assert_eq!(text(&[0xF5]), "PUSH AF");
+        assert_eq!(text(&[0x07]), "RLCA");
+        assert_eq!(text(&[0x10, 0x00]), "STOP");
+        assert_eq!(text(&[0xE9]), "JP HL");
+        assert_eq!(text(&[0xD9]), "RETI");
+        assert_eq!(text(&[0xC0]), "RET NZ");
+        assert_eq!(text(&[0xFF]), "RST $0038");
+    }
+
+    #[test]
+    fn test_cb_opcode_text() {
+        assert_eq!(text(&[0xCB, 0x37]), "SWAP A");
+        assert_eq!(text(&[0xCB, 0x7E]), "BIT 7, [HL]");
+        assert_eq!(text(&[0xCB, 0x80]), "RES 0, B");
+        assert_eq!(text(&[0xCB, 0xFF]), "SET 7, A");
+    }
+
+    #[test]
+    fn test_relative_jump_target() {
+        let dis = Disassembler::new();
+        let insn = dis.decode(0x0150, None, &[0x20, 0xFE]).unwrap();
+        assert_eq!(insn.to_string(), "JR NZ, $0150");
+        assert_eq!(insn.target, Some(0x0150));
+
+        let insn = dis.decode(0x0150, None, &[0x18, 0x10]).unwrap();
+        assert_eq!(insn.target, Some(0x0162));
+    }
+
+    #[test]
+    fn test_cycle_counts() {
+        let dis = Disassembler::new();
+        let cycles = |bytes: &[u8]| {
+            let insn = dis.decode(0x0000, None, bytes).unwrap();
+            (insn.cycles, insn.cycles_not_taken)
+        };
+        assert_eq!(cycles(&[0x00]), (4, None));
+        assert_eq!(cycles(&[0xC3, 0x00, 0x01]), (16, None));
+        assert_eq!(cycles(&[0xC2, 0x00, 0x01]), (16, Some(12)));
+        assert_eq!(cycles(&[0x38, 0x00]), (12, Some(8)));
+        assert_eq!(cycles(&[0xCC, 0x00, 0x01]), (24, Some(12)));
+        assert_eq!(cycles(&[0xC8]), (20, Some(8)));
+        assert_eq!(cycles(&[0xCB, 0x46]), (12, None));
+        assert_eq!(cycles(&[0xCB, 0x06]), (16, None));
+    }
+
+    #[test]
+    fn test_truncated_and_invalid() {
+        let dis = Disassembler::new();
+        assert_eq!(
+            dis.decode(0x0100, None, &[0xC3, 0x50]),
+            Err(Error::Truncated { addr: 0x0100 })
+        );
+        assert_eq!(
+            dis.decode(0x0100, None, &[0xCB]),
+            Err(Error::Truncated { addr: 0x0100 })
+        );
+        assert_eq!(
+            dis.decode(0x0100, None, &[]),
+            Err(Error::Truncated { addr: 0x0100 })
+        );
+        assert_eq!(
+            dis.decode(0x0100, None, &[0xD3]),
+            Err(Error::InvalidOpcode {
+                addr: 0x0100,
+                opcode: 0xD3
+            })
+        );
+    }
+
+    #[test]
+    fn test_symbols_substitute_addresses() {
+        let symbols = SymbolTable::parse_sym(
+            "; comment line\n\
+             00:0150 Main\n\
+             00:c000 wBuffer ; trailing comment\n\
+             00:ff40 rLCDC\n\
+             02:4000 FarFunc\n\
+             not a symbol\n",
+        );
+        let dis = Disassembler::new().with_symbols(symbols);
+        let at = |bank, bytes: &[u8]| dis.decode(0x0200, bank, bytes).unwrap().to_string();
+
+        assert_eq!(at(None, &[0xC3, 0x50, 0x01]), "JP Main");
+        assert_eq!(at(None, &[0xEA, 0x00, 0xC0]), "LD [wBuffer], A");
+        assert_eq!(at(None, &[0xE0, 0x40]), "LDH [rLCDC], A");
+        // Banked labels only resolve when the mapped bank is known and matches.
+        assert_eq!(at(Some(2), &[0xCD, 0x00, 0x40]), "CALL FarFunc");
+        assert_eq!(at(Some(3), &[0xCD, 0x00, 0x40]), "CALL $4000");
+        assert_eq!(at(None, &[0xCD, 0x00, 0x40]), "CALL $4000");
+        // Immediate values are never treated as addresses.
+        assert_eq!(at(None, &[0x21, 0x50, 0x01]), "LD HL, $0150");
+    }
+
+    #[test]
+    fn test_decode_rom_reads_requested_bank() {
+        let mut rom = vec![0u8; 0x4000 * 4];
+        rom[0x0100] = 0x00;
+        rom[2 * 0x4000 + 0x10] = 0x3E; // bank 2, 0x4010: LD A, $99
+        rom[2 * 0x4000 + 0x11] = 0x99;
+        rom[3 * 0x4000 + 0x10] = 0xAF; // bank 3, 0x4010: XOR A, A
+
+        let dis = Disassembler::new();
+        assert_eq!(
+            dis.decode_rom(&rom, 2, 0x4010).unwrap().to_string(),
+            "LD A, $99"
+        );
+        assert_eq!(
+            dis.decode_rom(&rom, 3, 0x4010).unwrap().to_string(),
+            "XOR A, A"
+        );
+        assert_eq!(dis.decode_rom(&rom, 3, 0x4010).unwrap().bank, Some(3));
+        assert_eq!(
+            dis.decode_rom(&rom, 9, 0x4010),
+            Err(Error::OutsideRom { addr: 0x4010 })
+        );
+        assert_eq!(
+            dis.decode_rom(&rom, 1, 0xC000),
+            Err(Error::OutsideRom { addr: 0xC000 })
+        );
+    }
+
+    #[test]
+    fn test_decode_rom_does_not_cross_bank_boundary() {
+        let mut rom = vec![0u8; 0x4000 * 2];
+        rom[0x7FFF] = 0xC3; // JP nn with operands in the next (nonexistent) bank
+        let dis = Disassembler::new();
+        assert_eq!(
+            dis.decode_rom(&rom, 1, 0x7FFF),
+            Err(Error::Truncated { addr: 0x7FFF })
+        );
+    }
+
+    #[test]
+    fn test_listing_from_cpu_memory() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[..6].copy_from_slice(&[0x3E, 0x01, 0xCB, 0x37, 0xC3, 0x00]);
+        let cpu = cpu_with_rom(rom);
+
+        let dis = Disassembler::new();
+        let listing = dis.listing(&cpu, 0x0000, 3);
+        let text: Vec<String> = listing.iter().map(|insn| insn.to_string()).collect();
+        assert_eq!(text, ["LD A, $01", "SWAP A", "JP $0000"]);
+        assert_eq!(listing[1].addr, 0x0002);
+        assert_eq!(listing[2].encoding(), &[0xC3, 0x00, 0x00]);
+        assert_eq!(listing[0].bank, Some(cpu.current_rom_bank()));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = Error::InvalidOpcode {
+            addr: 0x1234,
+            opcode: 0xDD,
+        };
+        assert!(error.to_string().contains("0xDD"));
+        assert!(error.to_string().contains("0x1234"));
+    }
+}
@@ -1,9 +1,17 @@
+pub mod av_sync;
+pub mod cheats;
 pub mod cpu;
+#[cfg(feature = "debugger")]
+pub mod debugger;
+pub mod disassembler;
 pub mod instructions;
+pub mod movie;
 mod operations;
 pub mod peripheral;
 #[cfg(feature = "perf")]
 pub mod perf;
+pub mod recording;
 pub mod registers;
+pub mod rewind;
 pub mod save_state;
 pub mod sm83;
@@ -0,0 +1,270 @@
+//! Input movies: a starting state plus one joypad mask per frame.
+//!
+//! The emulator is deterministic, so replaying the same input from the same
+//! state reproduces every frame exactly. Input is applied at frame
+//! boundaries through [`Sm83::run_frame_with_input`]; a movie records the
+//! buttons held when each frame starts.
+//!
+//! ```ignore
+//! let mut movie = Movie::power_on(&rom);
+//! loop {
+//!     // ... host calls cpu.set_button() between frames ...
+//!     movie.record_frame(&mut cpu)?;
+//! }
+//! std::fs::write("session.rbmv", movie.to_bytes())?;
+//!
+//! let movie = Movie::from_bytes(&std::fs::read("session.rbmv")?)?;
+//! let mut cpu = movie.start(rom, Box::new(OpCodeDecoder::new()))?;
+//! movie.play(&mut cpu)?;
+//! ```
+//!
+//! ## Format
+//!
+//! All integers are little-endian.
+//!
+//! ```text
+//! magic "RBMV" | version u16
+//! ROM CRC-32 u32                       (IEEE 802.3 polynomial, as zip and PNG use)
+//! start u8: 0 = power-on, 1 = save state
+//!   save state only: length u32 | RBSS save state blob
+//! frame count u32 | one button mask u8 per frame
+//! ```
+//!
+//! Power-on means the post-boot state of the model the cartridge header asks
+//! for, as built by `Sm83::from_rom(..).with_post_boot_state()`. A button
+//! mask has bit `n` set when [`Button::ALL`]`[n]` is held: Right, Left, Up,
+//! Down, A, B, Select, Start from bit 0.
+//!
+//! [`Button::ALL`]: crate::cpu::peripheral::joypad::Button::ALL
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::cpu::cpu::CpuError;
+use crate::cpu::instructions::decoder::Decoder;
+use crate::cpu::save_state::SaveState;
+use crate::cpu::sm83::{RunResult, Sm83};
+use crate::memory::cartridge::CartridgeError;
+
+pub const MAGIC: &[u8; 4] = b"RBMV";
+pub const VERSION: u16 = 1;
+
+const START_POWER_ON: u8 = 0;
+const START_SAVE_STATE: u8 = 1;
+
+/// Where a movie starts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MovieStart {
+    /// Post-boot state of the cartridge's model.
+    PowerOn,
+    /// An RBSS save state blob.
+    SaveState(Vec<u8>),
+}
+
+/// Reasons a movie can't be loaded or started.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MovieError {
+    /// The data doesn't start with the `RBMV` magic.
+    BadMagic,
+    /// Written by a newer version of the format.
+    UnsupportedVersion(u16),
+    /// The data ends before the movie does, or the start kind is unknown.
+    Malformed,
+    /// The movie was recorded with a different ROM.
+    RomMismatch { expected_crc32: u32, actual_crc32: u32 },
+    Cartridge(CartridgeError),
+    SaveState(&'static str),
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovieError::BadMagic => write!(f, "not a movie file"),
+            MovieError::UnsupportedVersion(v) => write!(f, "unsupported movie version {}", v),
+            MovieError::Malformed => write!(f, "movie file is truncated or corrupt"),
+            MovieError::RomMismatch { expected_crc32, actual_crc32 } => write!(
+                f,
+                "movie was recorded with ROM CRC-32 {:08X}, not {:08X}",
+                expected_crc32, actual_crc32
+            ),
+            MovieError::Cartridge(e) => write!(f, "{}", e),
+            MovieError::SaveState(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A recorded session: ROM identity, starting state and per-frame input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Movie {
+    pub rom_crc32: u32,
+    pub start: MovieStart,
+    /// Button mask held during each frame.
+    pub frames: Vec<u8>,
+}
+
+impl Movie {
+    /// An empty movie starting from power-on with `rom`.
+    pub fn power_on(rom: &[u8]) -> Self {
+        Movie { rom_crc32: crc32(rom), start: MovieStart::PowerOn, frames: Vec::new() }
+    }
+
+    /// An empty movie starting from `cpu`'s current state.
+    pub fn from_state(rom: &[u8], cpu: &Sm83) -> Self {
+        Movie { rom_crc32: crc32(rom), start: MovieStart::SaveState(cpu.save_state()), frames: Vec::new() }
+    }
+
+    /// Run one frame of `cpu` with the buttons it currently has held, and
+    /// append them to the movie.
+    pub fn record_frame(&mut self, cpu: &mut Sm83) -> Result<RunResult, CpuError> {
+        let buttons = cpu.buttons();
+        self.frames.push(buttons);
+        cpu.run_frame_with_input(buttons)
+    }
+
+    /// Build an `Sm83` for `rom` at the movie's starting state.
+    pub fn start(&self, rom: Vec<u8>, decoder: Box<dyn Decoder>) -> Result<Sm83, MovieError> {
+        let actual_crc32 = crc32(&rom);
+        if actual_crc32 != self.rom_crc32 {
+            return Err(MovieError::RomMismatch { expected_crc32: self.rom_crc32, actual_crc32 });
+        }
+        let mut cpu = Sm83::from_rom(rom, decoder).map_err(MovieError::Cartridge)?.with_post_boot_state();
+        if let MovieStart::SaveState(blob) = &self.start {
+            let state = SaveState::from_blob(blob.clone()).map_err(MovieError::SaveState)?;
+            cpu.load_state(state).map_err(MovieError::SaveState)?;
+        }
+        Ok(cpu)
+    }
+
+    /// Run every frame of the movie on `cpu`, which should be at the
+    /// movie's starting state (see [`Movie::start`]).
+    pub fn play(&self, cpu: &mut Sm83) -> Result<(), CpuError> {
+        for &buttons in &self.frames {
+            cpu.run_frame_with_input(buttons)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&self.rom_crc32.to_le_bytes());
+        match &self.start {
+            MovieStart::PowerOn => out.push(START_POWER_ON),
+            MovieStart::SaveState(blob) => {
+                out.push(START_SAVE_STATE);
+                out.extend_from_slice(&(blob.len() as u32).to_le_bytes());
+                out.extend_from_slice(blob);
+            }
+        }
+        out.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.frames);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MovieError> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(MovieError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version > VERSION {
+            return Err(MovieError::UnsupportedVersion(version));
+        }
+        let rom_crc32 = reader.u32()?;
+        let start = match reader.u8()? {
+            START_POWER_ON => MovieStart::PowerOn,
+            START_SAVE_STATE => {
+                let len = reader.u32()? as usize;
+                MovieStart::SaveState(reader.take(len)?.to_vec())
+            }
+            _ => return Err(MovieError::Malformed),
+        };
+        let frame_count = reader.u32()? as usize;
+        let frames = reader.take(frame_count)?.to_vec();
+        Ok(Movie { rom_crc32, start, frames })
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MovieError> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len()).ok_or(MovieError::Malformed)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, MovieError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, MovieError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, MovieError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+/// CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320) of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn crc32_matches_reference_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn power_on_movie_roundtrips() {
+        let movie = Movie { rom_crc32: 0x1234_5678, start: MovieStart::PowerOn, frames: vec![0, 0x10, 0x11, 0x80] };
+        let bytes = movie.to_bytes();
+        assert_eq!(&bytes[..4], MAGIC);
+        assert_eq!(bytes.len(), 4 + 2 + 4 + 1 + 4 + 4);
+        assert_eq!(Movie::from_bytes(&bytes), Ok(movie));
+    }
+
+    #[test]
+    fn save_state_movie_roundtrips() {
+        let movie = Movie { rom_crc32: 1, start: MovieStart::SaveState(vec![1, 2, 3]), frames: vec![0xFF] };
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()), Ok(movie));
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_newer_versions() {
+        let mut bytes = Movie { rom_crc32: 0, start: MovieStart::PowerOn, frames: vec![] }.to_bytes();
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(Movie::from_bytes(&bytes), Err(MovieError::UnsupportedVersion(VERSION + 1)));
+        bytes[0] = b'X';
+        assert_eq!(Movie::from_bytes(&bytes), Err(MovieError::BadMagic));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = Movie { rom_crc32: 0, start: MovieStart::SaveState(vec![0; 8]), frames: vec![1, 2] }.to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Movie::from_bytes(&bytes[..len]).is_err(), "accepted {len} bytes");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Band-limited synthesis of the mixer output, after blargg's blip_buf.
+//!
+//! The mixer only changes level at discrete T-cycles, so rather than
+//! sampling it, every level change is recorded as a step: a windowed-sinc
+//! impulse, placed at the change's sub-sample position, is added to the
+//! derivative of the output. Integrating that derivative at the output rate
+//! gives a signal with nothing above the output Nyquist frequency, where
+//! point sampling folds high-pitched pulse harmonics back into the audible
+//! range.
+
+use crate::cpu::save_state::ApuOutputState;
+
+/// Sub-sample positions the kernel is tabulated for.
+pub(super) const BLIP_PHASES: usize = 32;
+/// Output samples each step is spread across.
+pub(crate) const BLIP_WIDTH: usize = 16;
+/// Fixed-point one: every kernel phase sums to this.
+pub(super) const BLIP_UNITY: i32 = 1 << 15;
+
+/// Blackman-windowed sinc, cut off at 0.45 of the output rate. Row `p` is a
+/// step `p / BLIP_PHASES` of the way through an output sample, centred on
+/// tap 7, so output lags the mixer by 7 samples. Generated offline and
+/// rounded so that each row sums to exactly `BLIP_UNITY`; see the
+/// `test_kernel_phases_sum_to_unity` test.
+#[rustfmt::skip]
+const BLIP_KERNEL: [[i16; BLIP_WIDTH]; BLIP_PHASES] = [
+    [18, -110, 359, -843, 1561, -2371, 3025, 29490, 3025, -2371, 1561, -843, 359, -110, 18, 0],
+    [17, -108, 347, -795, 1421, -2025, 2117, 29452, 3974, -2714, 1693, -887, 369, -111, 18, 0],
+    [17, -105, 332, -742, 1276, -1679, 1252, 29332, 4960, -3051, 1818, -925, 376, -110, 17, 0],
+    [16, -102, 315, -686, 1128, -1335, 434, 29131, 5981, -3378, 1932, -956, 380, -109, 17, 0],
+    [16, -98, 297, -627, 977, -997, -336, 28853, 7031, -3693, 2036, -982, 381, -106, 16, 0],
+    [15, -93, 277, -566, 824, -665, -1055, 28499, 8106, -3992, 2127, -999, 378, -103, 15, 0],
+    [14, -87, 256, -503, 672, -343, -1721, 28067, 9203, -4273, 2204, -1009, 372, -97, 13, 0],
+    [13, -82, 234, -439, 522, -34, -2334, 27565, 10317, -4531, 2266, -1011, 362, -91, 11, 0],
+    [12, -76, 211, -375, 374, 262, -2891, 26992, 11444, -4765, 2311, -1004, 348, -83, 8, 0],
+    [10, -69, 188, -311, 229, 543, -3394, 26350, 12577, -4970, 2339, -987, 330, -73, 6, 0],
+    [9, -63, 165, -248, 90, 807, -3840, 25646, 13712, -5144, 2348, -962, 308, -62, 2, 0],
+    [8, -56, 142, -186, -44, 1052, -4231, 24877, 14845, -5283, 2338, -926, 282, -50, -1, 1],
+    [7, -50, 119, -126, -171, 1277, -4566, 24057, 15970, -5386, 2307, -881, 251, -36, -5, 1],
+    [6, -44, 96, -68, -291, 1482, -4846, 23182, 17081, -5448, 2255, -825, 217, -21, -10, 2],
+    [5, -37, 74, -12, -403, 1666, -5072, 22257, 18174, -5467, 2182, -760, 178, -4, -15, 2],
+    [4, -31, 53, 41, -506, 1828, -5246, 21289, 19243, -5441, 2086, -685, 136, 14, -20, 3],
+    [3, -25, 33, 90, -600, 1968, -5368, 20283, 20283, -5368, 1968, -600, 90, 33, -25, 3],
+    [3, -20, 14, 136, -685, 2086, -5441, 19243, 21289, -5246, 1828, -506, 41, 53, -31, 4],
+    [2, -15, -4, 178, -760, 2182, -5467, 18174, 22257, -5072, 1666, -403, -12, 74, -37, 5],
+    [2, -10, -21, 217, -825, 2255, -5448, 17081, 23182, -4846, 1482, -291, -68, 96, -44, 6],
+    [1, -5, -36, 251, -881, 2307, -5386, 15970, 24057, -4566, 1277, -171, -126, 119, -50, 7],
+    [1, -1, -50, 282, -926, 2338, -5283, 14845, 24877, -4231, 1052, -44, -186, 142, -56, 8],
+    [0, 2, -62, 308, -962, 2348, -5144, 13712, 25646, -3840, 807, 90, -248, 165, -63, 9],
+    [0, 6, -73, 330, -987, 2339, -4970, 12577, 26350, -3394, 543, 229, -311, 188, -69, 10],
+    [0, 8, -83, 348, -1004, 2311, -4765, 11444, 26992, -2891, 262, 374, -375, 211, -76, 12],
+    [0, 11, -91, 362, -1011, 2266, -4531, 10317, 27565, -2334, -34, 522, -439, 234, -82, 13],
+    [0, 13, -97, 372, -1009, 2204, -4273, 9203, 28067, -1721, -343, 672, -503, 256, -87, 14],
+    [0, 15, -103, 378, -999, 2127, -3992, 8106, 28499, -1055, -665, 824, -566, 277, -93, 15],
+    [0, 16, -106, 381, -982, 2036, -3693, 7031, 28853, -336, -997, 977, -627, 297, -98, 16],
+    [0, 17, -109, 380, -956, 1932, -3378, 5981, 29131, 434, -1335, 1128, -686, 315, -102, 16],
+    [0, 17, -110, 376, -925, 1818, -3051, 4960, 29332, 1252, -1679, 1276, -742, 332, -105, 17],
+    [0, 18, -111, 369, -887, 1693, -2714, 3974, 29452, 2117, -2025, 1421, -795, 347, -108, 17],
+];
+
+/// Stereo step accumulator for [`Resampler::BandLimited`](super::Resampler).
+pub(super) struct BlipBuffer {
+    /// Derivative of the output for the next `BLIP_WIDTH` samples, starting
+    /// at `head`.
+    ring: [[i32; 2]; BLIP_WIDTH],
+    head: usize,
+    /// Running sum of the samples read so far, in mixer levels × `BLIP_UNITY`.
+    integrator: [i32; 2],
+    /// Mixer level the last step brought the output to.
+    level: [i32; 2],
+}
+
+impl BlipBuffer {
+    pub(super) fn new() -> Self {
+        Self { ring: [[0; 2]; BLIP_WIDTH], head: 0, integrator: [0; 2], level: [0; 2] }
+    }
+
+    pub(super) fn to_state(&self, state: &mut ApuOutputState) {
+        state.blip_ring = self.ring;
+        state.blip_head = self.head as u8;
+        state.integrator = self.integrator;
+        state.level = self.level;
+    }
+
+    pub(super) fn from_state(state: &ApuOutputState) -> Self {
+        Self {
+            ring: state.blip_ring,
+            head: state.blip_head as usize % BLIP_WIDTH,
+            integrator: state.integrator,
+            level: state.level,
+        }
+    }
+
+    /// Move the output to `level` at `phase` (0..`BLIP_PHASES`) of the way
+    /// through the current output sample.
+    pub(super) fn set_level(&mut self, level: [i32; 2], phase: usize) {
+        if level == self.level {
+            return;
+        }
+        let delta = [level[0] - self.level[0], level[1] - self.level[1]];
+        self.level = level;
+        for (i, &tap) in BLIP_KERNEL[phase].iter().enumerate() {
+            let slot = &mut self.ring[(self.head + i) % BLIP_WIDTH];
+            slot[0] += delta[0] * tap as i32;
+            slot[1] += delta[1] * tap as i32;
+        }
+    }
+
+    /// Finish the current output sample and return it, in mixer levels
+    /// scaled by `BLIP_UNITY`.
+    pub(super) fn read_sample(&mut self) -> [i32; 2] {
+        let slot = core::mem::take(&mut self.ring[self.head]);
+        self.head = (self.head + 1) % BLIP_WIDTH;
+        self.integrator[0] += slot[0];
+        self.integrator[1] += slot[1];
+        self.integrator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_phases_sum_to_unity() {
+        for row in BLIP_KERNEL.iter() {
+            assert_eq!(row.iter().map(|&tap| tap as i32).sum::<i32>(), BLIP_UNITY);
+        }
+    }
+
+    #[test]
+    fn test_step_settles_at_level() {
+        let mut blip = BlipBuffer::new();
+        blip.set_level([100, -40], 13);
+        let mut last = [0; 2];
+        for _ in 0..BLIP_WIDTH {
+            last = blip.read_sample();
+        }
+        assert_eq!(last, [100 * BLIP_UNITY, -40 * BLIP_UNITY]);
+        assert_eq!(blip.read_sample(), last);
+    }
+}
@@ -1,3 +1,12 @@
+use crate::cpu::save_state::{
+    ApuOutputState, ApuState, NoiseChannelState, SquareChannelState, SweepUnitState, WaveChannelState,
+};
+
+mod blip;
+
+use blip::{BlipBuffer, BLIP_PHASES, BLIP_UNITY};
+pub(crate) use blip::BLIP_WIDTH;
+
 /// APU register addresses.
 pub(crate) const NR10_ADDR: u16 = 0xFF10;
 pub(crate) const NR52_ADDR: u16 = 0xFF26;
@@ -45,22 +54,103 @@ const DUTY_TABLE: [[u8; 8]; 4] = [
 /// Noise channel divisor table.
 const NOISE_DIVISORS: [u16; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
 
-/// Target sample rate for PCM output (Hz).
-pub const SAMPLE_RATE: u32 = 48_000;
+/// PCM output rate (Hz) until [`ApuPeripheral::set_sample_rate`] says otherwise.
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+/// Output rates outside this range are clamped to it.
+pub const MIN_SAMPLE_RATE: u32 = 8_000;
+pub const MAX_SAMPLE_RATE: u32 = 192_000;
+/// Largest fraction by which [`ApuPeripheral::set_rate_skew`] may stretch or
+/// squeeze the output rate.
+pub const MAX_RATE_SKEW: f32 = 0.05;
 
 /// DMG T-cycle frequency.
 const CPU_FREQ: u32 = 4_194_304;
 
-/// Numerator/denominator for the downsampling accumulator.
-/// We emit one stereo sample every CPU_FREQ/SAMPLE_RATE T-cycles.
-/// Using fixed-point: accumulator counts in units of SAMPLE_RATE.
+/// One output sample is due every CPU_FREQ/sample_rate T-cycles. The
+/// downsampling accumulator counts in units of the sample rate and emits a
+/// sample each time it passes this.
 const SAMPLE_PERIOD_NUM: u32 = CPU_FREQ;
-const SAMPLE_PERIOD_DEN: u32 = SAMPLE_RATE;
 /// About 804 stereo pairs are produced per Game Boy frame at 48 kHz.
 /// Reserve some headroom so the hot audio path doesn't regrow this buffer.
 const SAMPLE_BUFFER_CAPACITY_HINT: usize = 2048;
 /// Max possible mixer output before normalization: 4 channels * level 15 * volume 8.
 const MIXER_MAX: u32 = 4 * 15 * 8;
+/// Fraction of the output capacitor's charge kept per T-cycle, as measured
+/// on a DMG. The capacitor blocks the mixer's DC offset, so silence settles
+/// at zero rather than at whatever level the channels were left on.
+const CAPACITOR_CHARGE_PER_CYCLE: f64 = 0.999958;
+
+/// The four sound channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundChannel {
+    /// Channel 1: pulse with frequency sweep.
+    Pulse1,
+    /// Channel 2: pulse.
+    Pulse2,
+    /// Channel 3: 4-bit samples from wave RAM.
+    Wave,
+    /// Channel 4: LFSR noise.
+    Noise,
+}
+
+impl SoundChannel {
+    /// All channels, in mask order: bit `n` of a channel mask is `ALL[n]`,
+    /// as in NR51 and NR52.
+    pub const ALL: [SoundChannel; 4] =
+        [SoundChannel::Pulse1, SoundChannel::Pulse2, SoundChannel::Wave, SoundChannel::Noise];
+
+    /// This channel's bit in a channel mask.
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// How the APU turns its 4 MiHz mixer output into samples at the output rate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Resampler {
+    /// Synthesize each mixer level change as a band-limited step. Free of
+    /// the aliasing that makes high-pitched pulse notes sound rough, at the
+    /// cost of 16 multiply-adds per level change.
+    #[default]
+    BandLimited,
+    /// Take the mixer level at each sample instant. Cheapest, so the Pico
+    /// uses it, but harmonics above the output Nyquist frequency alias.
+    Nearest,
+}
+
+/// DC-blocking high-pass filter modelling the output capacitor.
+struct HighPass {
+    /// Charge kept per output sample.
+    charge: f32,
+    capacitor: [f32; 2],
+}
+
+impl HighPass {
+    fn new(sample_rate: u32) -> Self {
+        // CAPACITOR_CHARGE_PER_CYCLE ^ (CPU_FREQ / sample_rate), as a series
+        // for e^-y since core has no `powf`. y stays under 0.011 over the
+        // supported rates, so three terms are exact to f32 precision.
+        let y = -ln_near_one(CAPACITOR_CHARGE_PER_CYCLE) * CPU_FREQ as f64 / sample_rate as f64;
+        let charge = 1.0 - y + y * y / 2.0 - y * y * y / 6.0;
+        Self { charge: charge as f32, capacitor: [0.0; 2] }
+    }
+
+    fn apply(&mut self, input: [f32; 2]) -> [i16; 2] {
+        let mut out = [0; 2];
+        for ((input, capacitor), out) in input.into_iter().zip(&mut self.capacitor).zip(&mut out) {
+            let filtered = input - *capacitor;
+            *capacitor = input - filtered * self.charge;
+            *out = filtered.clamp(-32767.0, 32767.0) as i16;
+        }
+        out
+    }
+}
+
+/// ln(x) for x just below 1, from the series for ln(1 - d).
+fn ln_near_one(x: f64) -> f64 {
+    let d = 1.0 - x;
+    -(d + d * d / 2.0 + d * d * d / 3.0)
+}
 
 /// Result of an APU tick.
 pub struct ApuOutput {
@@ -111,6 +201,44 @@ struct SquareChannel {
 }
 
 impl SquareChannel {
+    fn to_state(&self) -> SquareChannelState {
+        SquareChannelState {
+            enabled:          self.enabled,
+            dac_enabled:      self.dac_enabled,
+            length_counter:   self.length_counter,
+            length_enabled:   self.length_enabled,
+            volume:           self.volume,
+            volume_initial:   self.volume_initial,
+            envelope_add:     self.envelope_add,
+            envelope_period:  self.envelope_period,
+            envelope_timer:   self.envelope_timer,
+            frequency:        self.frequency,
+            frequency_period: self.frequency_period,
+            frequency_timer:  self.frequency_timer,
+            duty:             self.duty,
+            duty_position:    self.duty_position,
+        }
+    }
+
+    fn from_state(s: SquareChannelState) -> Self {
+        Self {
+            enabled:          s.enabled,
+            dac_enabled:      s.dac_enabled,
+            length_counter:   s.length_counter,
+            length_enabled:   s.length_enabled,
+            volume:           s.volume,
+            volume_initial:   s.volume_initial,
+            envelope_add:     s.envelope_add,
+            envelope_period:  s.envelope_period,
+            envelope_timer:   s.envelope_timer,
+            frequency:        s.frequency,
+            frequency_period: s.frequency_period,
+            frequency_timer:  s.frequency_timer,
+            duty:             s.duty,
+            duty_position:    s.duty_position,
+        }
+    }
+
     fn sync_frequency_period(&mut self) {
         self.frequency_period = (2048 - self.frequency) * 4;
     }
@@ -214,6 +342,30 @@ struct SweepState {
 }
 
 impl SweepState {
+    fn to_state(&self) -> SweepUnitState {
+        SweepUnitState {
+            enabled:          self.enabled,
+            period:           self.period,
+            timer:            self.timer,
+            negate:           self.negate,
+            shift:            self.shift,
+            shadow_frequency: self.shadow_frequency,
+            negate_used:      self.negate_used,
+        }
+    }
+
+    fn from_state(s: SweepUnitState) -> Self {
+        Self {
+            enabled:          s.enabled,
+            period:           s.period,
+            timer:            s.timer,
+            negate:           s.negate,
+            shift:            s.shift,
+            shadow_frequency: s.shadow_frequency,
+            negate_used:      s.negate_used,
+        }
+    }
+
     fn trigger(&mut self, channel: &SquareChannel) {
         self.shadow_frequency = channel.frequency;
         self.timer = if self.period == 0 { 8 } else { self.period };
@@ -298,6 +450,40 @@ struct WaveChannel {
 }
 
 impl WaveChannel {
+    fn to_state(&self) -> WaveChannelState {
+        WaveChannelState {
+            enabled:          self.enabled,
+            dac_enabled:      self.dac_enabled,
+            length_counter:   self.length_counter,
+            length_enabled:   self.length_enabled,
+            volume_code:      self.volume_code,
+            frequency:        self.frequency,
+            frequency_period: self.frequency_period,
+            frequency_timer:  self.frequency_timer,
+            position:         self.position,
+            sample_buffer:    self.sample_buffer,
+            wave_ram:         self.wave_ram,
+            just_read:        self.just_read,
+        }
+    }
+
+    fn from_state(s: WaveChannelState) -> Self {
+        Self {
+            enabled:          s.enabled,
+            dac_enabled:      s.dac_enabled,
+            length_counter:   s.length_counter,
+            length_enabled:   s.length_enabled,
+            volume_code:      s.volume_code,
+            frequency:        s.frequency,
+            frequency_period: s.frequency_period,
+            frequency_timer:  s.frequency_timer,
+            position:         s.position,
+            sample_buffer:    s.sample_buffer,
+            wave_ram:         s.wave_ram,
+            just_read:        s.just_read,
+        }
+    }
+
     fn sync_frequency_period(&mut self) {
         self.frequency_period = 2048 - self.frequency;
     }
@@ -417,6 +603,46 @@ struct NoiseChannel {
 }
 
 impl NoiseChannel {
+    fn to_state(&self) -> NoiseChannelState {
+        NoiseChannelState {
+            enabled:          self.enabled,
+            dac_enabled:      self.dac_enabled,
+            length_counter:   self.length_counter,
+            length_enabled:   self.length_enabled,
+            volume:           self.volume,
+            volume_initial:   self.volume_initial,
+            envelope_add:     self.envelope_add,
+            envelope_period:  self.envelope_period,
+            envelope_timer:   self.envelope_timer,
+            clock_shift:      self.clock_shift,
+            width_mode:       self.width_mode,
+            divisor_code:     self.divisor_code,
+            frequency_period: self.frequency_period,
+            frequency_timer:  self.frequency_timer,
+            lfsr:             self.lfsr,
+        }
+    }
+
+    fn from_state(s: NoiseChannelState) -> Self {
+        Self {
+            enabled:          s.enabled,
+            dac_enabled:      s.dac_enabled,
+            length_counter:   s.length_counter,
+            length_enabled:   s.length_enabled,
+            volume:           s.volume,
+            volume_initial:   s.volume_initial,
+            envelope_add:     s.envelope_add,
+            envelope_period:  s.envelope_period,
+            envelope_timer:   s.envelope_timer,
+            clock_shift:      s.clock_shift,
+            width_mode:       s.width_mode,
+            divisor_code:     s.divisor_code,
+            frequency_period: s.frequency_period,
+            frequency_timer:  s.frequency_timer,
+            lfsr:             s.lfsr,
+        }
+    }
+
     fn sync_frequency_period(&mut self) {
         self.frequency_period = NOISE_DIVISORS[self.divisor_code as usize] << self.clock_shift;
     }
@@ -547,18 +773,41 @@ pub struct ApuPeripheral {
     /// Read-back ORs these with `READ_MASKS` to expose write-only bits as 1.
     regs: [u8; 23], // NR10 (0xFF10) through NR52 (0xFF26)
 
-    /// Downsampling accumulator. Incremented by SAMPLE_PERIOD_DEN each T-cycle;
+    /// Output rate in Hz.
+    sample_rate: u32,
+    /// Fraction the sample clock runs fast (positive) or slow by; see
+    /// [`ApuPeripheral::set_rate_skew`].
+    rate_skew: f32,
+    /// `sample_rate` with `rate_skew` applied: samples actually produced
+    /// per emulated second.
+    clock_rate: u32,
+    resampler: Resampler,
+    /// Downsampling accumulator. Incremented by `clock_rate` each T-cycle;
     /// when it reaches SAMPLE_PERIOD_NUM a stereo sample is emitted and the
-    /// remainder is kept to avoid pitch drift.
+    /// remainder is kept to avoid pitch drift. Between samples it is also the
+    /// sub-sample position of band-limited steps.
     sample_acc: u32,
+    blip: BlipBuffer,
+    high_pass: HighPass,
     /// Interleaved stereo PCM output buffer: [L, R, L, R, ...], i16 PCM words.
     sample_buffer: alloc::vec::Vec<i16>,
     /// Cached NR50 master-volume gains scaled for direct integer PCM output.
     left_scale: u16,
     right_scale: u16,
-    /// Cached NR51 routing bits normalized to channel bits 0..3.
+    /// Cached NR50 master volumes (1–8) for band-limited mixer levels.
+    left_volume: u16,
+    right_volume: u16,
+    /// Cached NR51 routing bits normalized to channel bits 0..3, with
+    /// muted channels removed.
     left_routes: u8,
     right_routes: u8,
+    /// Channel masks set by the host; see [`ApuPeripheral::set_muted_channels`].
+    muted: u8,
+    soloed: u8,
+    /// Per-channel levels (0–15) at each output sample, indexed by
+    /// [`SoundChannel`]. `None` unless enabled with
+    /// [`ApuPeripheral::set_channel_capture`].
+    channel_samples: Option<[alloc::vec::Vec<u8>; 4]>,
     #[cfg(feature = "perf")]
     perf_profile: ApuPerfProfile,
 }
@@ -576,12 +825,23 @@ impl ApuPeripheral {
             channel3: WaveChannel::default(),
             channel4: NoiseChannel::default(),
             regs: [0u8; 23],
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            rate_skew: 0.0,
+            clock_rate: DEFAULT_SAMPLE_RATE,
+            resampler: Resampler::default(),
             sample_acc: 0,
+            blip: BlipBuffer::new(),
+            high_pass: HighPass::new(DEFAULT_SAMPLE_RATE),
             sample_buffer: alloc::vec::Vec::with_capacity(SAMPLE_BUFFER_CAPACITY_HINT),
             left_scale: 0,
             right_scale: 0,
+            left_volume: 0,
+            right_volume: 0,
             left_routes: 0,
             right_routes: 0,
+            muted: 0,
+            soloed: 0,
+            channel_samples: None,
             #[cfg(feature = "perf")]
             perf_profile: ApuPerfProfile::default(),
         }
@@ -610,9 +870,159 @@ impl ApuPeripheral {
         core::mem::swap(out, &mut self.sample_buffer);
     }
 
+    /// Start or stop recording each channel's level alongside the mixed
+    /// output; see [`ApuPeripheral::drain_channel_samples`]. Costs an extra
+    /// four bytes per output sample, so it is off by default.
+    pub fn set_channel_capture(&mut self, enabled: bool) {
+        if enabled != self.channel_samples.is_some() {
+            self.channel_samples = enabled.then(Default::default);
+        }
+    }
+
+    /// Drain the per-channel streams recorded since the last call, indexed
+    /// by [`SoundChannel`]: each channel's digital output (0–15, after its
+    /// envelope or wave volume shift, before NR50/NR51 mixing) at the instant
+    /// of every output sample. One entry per stereo pair drained from
+    /// [`ApuPeripheral::drain_samples`], although the band-limited mix lags
+    /// them by a few samples. Muting doesn't affect these streams. Empty
+    /// unless capture is enabled.
+    pub fn drain_channel_samples(&mut self) -> [alloc::vec::Vec<u8>; 4] {
+        match &mut self.channel_samples {
+            Some(streams) => streams.each_mut().map(core::mem::take),
+            None => Default::default(),
+        }
+    }
+
+    /// Silence the channels in `mask` (see [`SoundChannel::mask`]) in the
+    /// mixed output, whatever NR51 says. Games can't observe this.
+    pub fn set_muted_channels(&mut self, mask: u8) {
+        self.muted = mask & 0x0F;
+        self.sync_mixer_cache();
+    }
+
+    pub fn muted_channels(&self) -> u8 {
+        self.muted
+    }
+
+    /// Play only the channels in `mask`, overriding the mute mask while any
+    /// channel is soloed. 0 turns solo off.
+    pub fn set_soloed_channels(&mut self, mask: u8) {
+        self.soloed = mask & 0x0F;
+        self.sync_mixer_cache();
+    }
+
+    pub fn soloed_channels(&self) -> u8 {
+        self.soloed
+    }
+
     pub fn clear_samples(&mut self) {
         self.sample_buffer.clear();
+        if let Some(streams) = &mut self.channel_samples {
+            streams.iter_mut().for_each(alloc::vec::Vec::clear);
+        }
         self.sample_acc = 0;
+        self.reset_output_filters();
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Change the PCM output rate, clamped to
+    /// [`MIN_SAMPLE_RATE`]..=[`MAX_SAMPLE_RATE`]. Samples already produced
+    /// stay at the old rate; drain them first.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.clamp(MIN_SAMPLE_RATE, MAX_SAMPLE_RATE);
+        self.high_pass = HighPass::new(self.sample_rate);
+        self.set_rate_skew(self.rate_skew);
+    }
+
+    pub fn rate_skew(&self) -> f32 {
+        self.rate_skew
+    }
+
+    /// Produce `1 + skew` times as many samples per emulated second as the
+    /// sample rate asks for, with `skew` clamped to ±[`MAX_RATE_SKEW`].
+    /// Unlike [`ApuPeripheral::set_sample_rate`] this leaves the output
+    /// filters alone, so it can be nudged every frame to keep a host's audio
+    /// queue from draining or piling up; see [`crate::cpu::av_sync`].
+    pub fn set_rate_skew(&mut self, skew: f32) {
+        self.rate_skew = skew.clamp(-MAX_RATE_SKEW, MAX_RATE_SKEW);
+        self.clock_rate = (self.sample_rate as f32 * (1.0 + self.rate_skew) + 0.5) as u32;
+    }
+
+    pub fn resampler(&self) -> Resampler {
+        self.resampler
+    }
+
+    pub fn set_resampler(&mut self, resampler: Resampler) {
+        self.resampler = resampler;
+        self.reset_output_filters();
+    }
+
+    /// Forget the resampler's and capacitor's history, e.g. after a jump in
+    /// emulated time.
+    fn reset_output_filters(&mut self) {
+        self.blip = BlipBuffer::new();
+        self.high_pass = HighPass::new(self.sample_rate);
+    }
+
+    /// Extract APU state into an [`ApuState`] for serialization.
+    pub fn to_save_state(&self) -> ApuState {
+        ApuState {
+            powered:              self.powered,
+            prev_div_bit:         self.prev_div_bit,
+            frame_sequencer_step: self.frame_sequencer_step,
+            wave_2mhz_phase:      self.wave_2mhz_phase,
+            channel1:             self.channel1.to_state(),
+            sweep:                self.sweep.to_state(),
+            channel2:             self.channel2.to_state(),
+            channel3:             self.channel3.to_state(),
+            channel4:             self.channel4.to_state(),
+            regs:                 self.regs,
+            sample_acc:           self.sample_acc,
+        }
+    }
+
+    /// Apply APU state from a parsed [`ApuState`]. Samples produced before the
+    /// load are discarded.
+    pub fn load_state(&mut self, state: ApuState) {
+        self.powered              = state.powered;
+        self.prev_div_bit         = state.prev_div_bit;
+        self.frame_sequencer_step = state.frame_sequencer_step;
+        self.wave_2mhz_phase      = state.wave_2mhz_phase;
+        self.channel1             = SquareChannel::from_state(state.channel1);
+        self.sweep                = SweepState::from_state(state.sweep);
+        self.channel2             = SquareChannel::from_state(state.channel2);
+        self.channel3             = WaveChannel::from_state(state.channel3);
+        self.channel4             = NoiseChannel::from_state(state.channel4);
+        self.regs                 = state.regs;
+        self.sample_acc           = state.sample_acc % SAMPLE_PERIOD_NUM;
+        self.sample_buffer.clear();
+        if let Some(streams) = &mut self.channel_samples {
+            streams.iter_mut().for_each(alloc::vec::Vec::clear);
+        }
+        self.reset_output_filters();
+        self.sync_mixer_cache();
+    }
+
+    /// Extract the resampler and output capacitor history.
+    pub fn to_output_save_state(&self) -> ApuOutputState {
+        let mut state = ApuOutputState { capacitor: self.high_pass.capacitor, ..Default::default() };
+        self.blip.to_state(&mut state);
+        state
+    }
+
+    /// Apply resampler and capacitor history saved alongside the [`ApuState`]
+    /// just loaded. States without it start the output filters from rest.
+    pub fn load_output_state(&mut self, state: Option<&ApuOutputState>) {
+        match state {
+            Some(state) => {
+                self.blip = BlipBuffer::from_state(state);
+                self.high_pass.capacitor = state.capacitor;
+            }
+            None => self.reset_output_filters(),
+        }
     }
 
     #[cfg(feature = "perf")]
@@ -772,44 +1182,16 @@ impl ApuPeripheral {
             self.perf_profile.noise = self.perf_profile.noise.wrapping_add(dt);
         }
 
-        // Downsample to 48 kHz. The Pico runtime almost always calls this with
-        // cycles=1/3/4, so a single-sample fast path avoids a 64-bit divide/mod
-        // in the common case while keeping the generic batch path for tests and
-        // any larger callers.
-        let sample_inc = cycles as u32 * SAMPLE_PERIOD_DEN;
-        if cycles <= 4 {
-            self.sample_acc += sample_inc;
-            if self.sample_acc >= SAMPLE_PERIOD_NUM {
-                self.sample_acc -= SAMPLE_PERIOD_NUM;
-                #[cfg(feature = "perf")]
-                let t0 = crate::cpu::perf::cyccnt();
-                let (left, right) = self.mix_sample();
-                self.sample_buffer.push(left);
-                self.sample_buffer.push(right);
-                #[cfg(feature = "perf")]
-                {
-                    let dt = crate::cpu::perf::cyccnt().wrapping_sub(t0);
-                    self.perf_profile.mix = self.perf_profile.mix.wrapping_add(dt);
-                }
-            }
-        } else {
-            let acc = self.sample_acc as u64 + sample_inc as u64;
-            let n_samples = acc / SAMPLE_PERIOD_NUM as u64;
-            self.sample_acc = (acc % SAMPLE_PERIOD_NUM as u64) as u32;
-            if n_samples != 0 {
-                #[cfg(feature = "perf")]
-                let t0 = crate::cpu::perf::cyccnt();
-                for _ in 0..n_samples {
-                    let (left, right) = self.mix_sample();
-                    self.sample_buffer.push(left);
-                    self.sample_buffer.push(right);
-                }
-                #[cfg(feature = "perf")]
-                {
-                    let dt = crate::cpu::perf::cyccnt().wrapping_sub(t0);
-                    self.perf_profile.mix = self.perf_profile.mix.wrapping_add(dt);
-                }
-            }
+        #[cfg(feature = "perf")]
+        let t0 = crate::cpu::perf::cyccnt();
+        match self.resampler {
+            Resampler::Nearest => self.downsample_nearest(cycles),
+            Resampler::BandLimited => self.downsample_band_limited(cycles),
+        }
+        #[cfg(feature = "perf")]
+        {
+            let dt = crate::cpu::perf::cyccnt().wrapping_sub(t0);
+            self.perf_profile.mix = self.perf_profile.mix.wrapping_add(dt);
         }
 
         ApuOutput { nr52: self.build_nr52() }
@@ -842,10 +1224,71 @@ impl ApuPeripheral {
         self.channel4.clock_length();
     }
 
-    /// Mix all four channels into a stereo sample pair using NR50/NR51.
-    /// Returns signed 16-bit PCM values packed as an interleaved stereo pair.
+    /// Emit the mixer level at each sample instant. The Pico runtime almost
+    /// always calls this with cycles=1/3/4, so a single-sample fast path
+    /// avoids a 64-bit divide/mod in the common case while keeping the
+    /// generic batch path for tests and any larger callers.
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
-    fn mix_sample(&self) -> (i16, i16) {
+    fn downsample_nearest(&mut self, cycles: u16) {
+        if cycles <= 4 {
+            self.sample_acc += cycles as u32 * self.clock_rate;
+            if self.sample_acc >= SAMPLE_PERIOD_NUM {
+                self.sample_acc -= SAMPLE_PERIOD_NUM;
+                let (left, right) = self.mix_sample();
+                self.push_sample([left as f32, right as f32]);
+            }
+        } else {
+            for _ in 0..self.advance_sample_clock(cycles) {
+                let (left, right) = self.mix_sample();
+                self.push_sample([left as f32, right as f32]);
+            }
+        }
+    }
+
+    /// Finish the output samples that end within these cycles, then record
+    /// the mixer's new level as a step at the current sub-sample position.
+    /// Level changes are observed at batch granularity, which is at most an
+    /// instruction's worth of T-cycles.
+    fn downsample_band_limited(&mut self, cycles: u16) {
+        const TO_PCM: f32 = 32767.0 / (MIXER_MAX as f32 * BLIP_UNITY as f32);
+        for _ in 0..self.advance_sample_clock(cycles) {
+            let [left, right] = self.blip.read_sample();
+            self.push_sample([left as f32 * TO_PCM, right as f32 * TO_PCM]);
+        }
+        let (left, right) = self.mix_levels();
+        let level = [
+            (left * self.left_volume) as i32,
+            (right * self.right_volume) as i32,
+        ];
+        let phase = self.sample_acc as usize * BLIP_PHASES / SAMPLE_PERIOD_NUM as usize;
+        self.blip.set_level(level, phase);
+    }
+
+    /// Advance the downsampling accumulator and return how many samples
+    /// fell due.
+    fn advance_sample_clock(&mut self, cycles: u16) -> u32 {
+        let acc = self.sample_acc as u64 + cycles as u64 * self.clock_rate as u64;
+        self.sample_acc = (acc % SAMPLE_PERIOD_NUM as u64) as u32;
+        (acc / SAMPLE_PERIOD_NUM as u64) as u32
+    }
+
+    /// Pass a stereo pair of PCM values through the output capacitor and
+    /// into the sample buffer.
+    fn push_sample(&mut self, pcm: [f32; 2]) {
+        let [left, right] = self.high_pass.apply(pcm);
+        self.sample_buffer.push(left);
+        self.sample_buffer.push(right);
+        if let Some(streams) = &mut self.channel_samples {
+            streams[0].push(self.channel1.digital_output());
+            streams[1].push(self.channel2.digital_output());
+            streams[2].push(self.channel3.digital_output());
+            streams[3].push(self.channel4.digital_output());
+        }
+    }
+
+    /// Sum the channels routed to each side by NR51, before master volume.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn mix_levels(&self) -> (u16, u16) {
         let ch1 = self.channel1.digital_output() as u16;
         let ch2 = self.channel2.digital_output() as u16;
         let ch3 = self.channel3.digital_output() as u16;
@@ -864,6 +1307,14 @@ impl ApuPeripheral {
         if self.right_routes & 0x04 != 0 { right += ch3; }
         if self.right_routes & 0x08 != 0 { right += ch4; }
 
+        (left, right)
+    }
+
+    /// Mix all four channels into a stereo sample pair using NR50/NR51.
+    /// Returns signed 16-bit PCM values packed as an interleaved stereo pair.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn mix_sample(&self) -> (i16, i16) {
+        let (left, right) = self.mix_levels();
         (
             (left as u32 * self.left_scale as u32) as i16,
             (right as u32 * self.right_scale as u32) as i16,
@@ -926,10 +1377,13 @@ impl ApuPeripheral {
         let right_vol = (nr50 & 0x07) as u32 + 1;
         self.left_scale = ((left_vol * 32767) / MIXER_MAX) as u16;
         self.right_scale = ((right_vol * 32767) / MIXER_MAX) as u16;
+        self.left_volume = left_vol as u16;
+        self.right_volume = right_vol as u16;
 
+        let audible = if self.soloed != 0 { self.soloed } else { !self.muted & 0x0F };
         let nr51 = self.regs[21];
-        self.left_routes = (nr51 >> 4) & 0x0F;
-        self.right_routes = nr51 & 0x0F;
+        self.left_routes = (nr51 >> 4) & audible;
+        self.right_routes = nr51 & audible;
     }
 
     fn apply_register_write(&mut self, address: u16, value: u8) {
@@ -1680,4 +2134,149 @@ mod tests {
         assert!(nonzero, "all samples zero: max={} nr50={:#04x} nr51={:#04x}",
             max, apu.regs[20], apu.regs[21]);
     }
+
+    /// An APU playing a 2048 Hz, 50% duty pulse on ch1 at full volume.
+    fn pulse_2048hz(sample_rate: u32, resampler: Resampler) -> ApuPeripheral {
+        let mut apu = ApuPeripheral::new();
+        apu.set_sample_rate(sample_rate);
+        apu.set_resampler(resampler);
+        apu.write_register(0xFF26, 0x80); // NR52: APU on
+        apu.write_register(0xFF25, 0x11); // NR51: ch1 both sides
+        apu.write_register(0xFF24, 0x77); // NR50: max volume
+        apu.write_register(0xFF12, 0xF0); // NR12: volume=15, no envelope
+        apu.write_register(0xFF11, 0x80); // NR11: duty=2 (50%)
+        apu.write_register(0xFF13, 0xC0); // NR13: period 2048-1984=64
+        apu.write_register(0xFF14, 0x87); // NR14: trigger
+        apu
+    }
+
+    fn run_cycles(apu: &mut ApuPeripheral, cycles: u32) -> alloc::vec::Vec<f32> {
+        let mut div: u16 = 0;
+        for _ in 0..cycles / 4 {
+            div = div.wrapping_add(4);
+            apu.tick(4, div);
+        }
+        apu.drain_samples()
+    }
+
+    #[test]
+    fn test_sample_count_follows_sample_rate() {
+        for resampler in [Resampler::Nearest, Resampler::BandLimited] {
+            for rate in [32_000, 44_100, 48_000, 96_000] {
+                let mut apu = pulse_2048hz(rate, resampler);
+                let samples = run_cycles(&mut apu, CPU_FREQ);
+                assert_eq!(samples.len(), 2 * rate as usize, "{:?} at {} Hz", resampler, rate);
+            }
+        }
+        let mut apu = ApuPeripheral::new();
+        apu.set_sample_rate(1);
+        assert_eq!(apu.sample_rate(), MIN_SAMPLE_RATE);
+    }
+
+    #[test]
+    fn test_rate_skew_stretches_output_without_resetting_filters() {
+        let mut apu = pulse_2048hz(48_000, Resampler::BandLimited);
+        run_cycles(&mut apu, CPU_FREQ / 8);
+        let capacitor = apu.high_pass.capacitor;
+        apu.set_rate_skew(0.01);
+        assert_eq!(apu.high_pass.capacitor, capacitor);
+        assert_eq!(run_cycles(&mut apu, CPU_FREQ).len(), 2 * 48_480);
+
+        apu.set_rate_skew(-1.0);
+        assert_eq!(apu.rate_skew(), -MAX_RATE_SKEW);
+        assert_eq!(run_cycles(&mut apu, CPU_FREQ).len(), 2 * 45_600);
+        // The skew follows a new sample rate.
+        apu.set_sample_rate(32_000);
+        assert_eq!(run_cycles(&mut apu, CPU_FREQ).len(), 2 * 30_400);
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc() {
+        let mut high_pass = HighPass::new(DEFAULT_SAMPLE_RATE);
+        assert_eq!(high_pass.apply([10_000.0, 0.0]), [10_000, 0]);
+        let mut last = [0; 2];
+        for _ in 0..DEFAULT_SAMPLE_RATE / 10 {
+            last = high_pass.apply([10_000.0, 0.0]);
+        }
+        assert!(last[0].abs() < 10, "DC left after 100 ms: {:?}", last);
+
+        // A held pulse level decays too, so the output is centred on zero.
+        let mut apu = pulse_2048hz(DEFAULT_SAMPLE_RATE, Resampler::BandLimited);
+        let samples = run_cycles(&mut apu, CPU_FREQ);
+        let tail = &samples[samples.len() / 2..];
+        let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+        assert!(mean.abs() < 0.01, "mean {}", mean);
+    }
+
+    #[test]
+    fn test_channel_capture_records_levels_per_sample() {
+        let mut apu = pulse_2048hz(DEFAULT_SAMPLE_RATE, Resampler::Nearest);
+        assert!(apu.drain_channel_samples().iter().all(|s| s.is_empty()), "capture is off by default");
+        apu.set_channel_capture(true);
+        let samples = run_cycles(&mut apu, CPU_FREQ / 10);
+        let [pulse1, pulse2, wave, noise] = apu.drain_channel_samples();
+        assert_eq!(pulse1.len(), samples.len() / 2);
+        assert!(pulse1.iter().all(|&level| level == 0 || level == 15));
+        assert!(pulse1.contains(&0) && pulse1.contains(&15));
+        for silent in [pulse2, wave, noise] {
+            assert_eq!(silent.len(), pulse1.len());
+            assert!(silent.iter().all(|&level| level == 0));
+        }
+        assert!(apu.drain_channel_samples().iter().all(|s| s.is_empty()));
+    }
+
+    #[test]
+    fn test_mute_and_solo_masks() {
+        let peak = |apu: &mut ApuPeripheral| {
+            run_cycles(apu, CPU_FREQ / 10).iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+        };
+        let mut apu = pulse_2048hz(DEFAULT_SAMPLE_RATE, Resampler::BandLimited);
+        apu.set_channel_capture(true);
+        assert!(peak(&mut apu) > 0.1);
+
+        apu.set_muted_channels(SoundChannel::Pulse1.mask());
+        run_cycles(&mut apu, CPU_FREQ / 10); // let the capacitor discharge
+        assert!(peak(&mut apu) < 0.001);
+        assert!(apu.drain_channel_samples()[0].contains(&15), "muting is post-capture");
+        assert_eq!(apu.read_register(0xFF25), 0x11, "NR51 reads back unchanged");
+
+        // Solo overrides mute; soloing another channel silences ch1 again.
+        apu.set_soloed_channels(SoundChannel::Pulse1.mask() | SoundChannel::Noise.mask());
+        assert!(peak(&mut apu) > 0.1);
+        apu.set_soloed_channels(SoundChannel::Wave.mask());
+        run_cycles(&mut apu, CPU_FREQ / 10);
+        assert!(peak(&mut apu) < 0.001);
+        apu.set_soloed_channels(0);
+        apu.set_muted_channels(0);
+        assert!(peak(&mut apu) > 0.1);
+    }
+
+    /// Amplitude of the `freq` Hz component of the left channel.
+    fn tone_amplitude(samples: &[f32], sample_rate: u32, freq: f64) -> f64 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        let left: alloc::vec::Vec<f32> = samples.iter().step_by(2).copied().collect();
+        for (n, &x) in left.iter().enumerate() {
+            let angle = 2.0 * core::f64::consts::PI * freq * n as f64 / sample_rate as f64;
+            re += x as f64 * angle.cos();
+            im += x as f64 * angle.sin();
+        }
+        2.0 * (re * re + im * im).sqrt() / left.len() as f64
+    }
+
+    #[test]
+    fn test_band_limited_removes_aliases() {
+        // At 30 kHz the pulse's 13th harmonic, 26624 Hz, folds down to 3376 Hz,
+        // which is not a harmonic of 2048 Hz.
+        let rate = 30_000;
+        let amplitudes = [Resampler::Nearest, Resampler::BandLimited].map(|resampler| {
+            let mut apu = pulse_2048hz(rate, resampler);
+            run_cycles(&mut apu, CPU_FREQ / 4);
+            let samples = run_cycles(&mut apu, CPU_FREQ);
+            (tone_amplitude(&samples, rate, 2048.0), tone_amplitude(&samples, rate, 3376.0))
+        });
+        let [(nearest_fundamental, nearest_alias), (band_limited_fundamental, band_limited_alias)] = amplitudes;
+        assert!((band_limited_fundamental / nearest_fundamental - 1.0).abs() < 0.05, "{:?}", amplitudes);
+        assert!(nearest_alias > 0.01, "{:?}", amplitudes);
+        assert!(band_limited_alias * 20.0 < nearest_alias, "{:?}", amplitudes);
+    }
 }
@@ -0,0 +1,168 @@
+pub(crate) const HDMA1_ADDR: u16 = 0xFF51;
+pub(crate) const HDMA2_ADDR: u16 = 0xFF52;
+pub(crate) const HDMA3_ADDR: u16 = 0xFF53;
+pub(crate) const HDMA4_ADDR: u16 = 0xFF54;
+pub(crate) const HDMA5_ADDR: u16 = 0xFF55;
+
+/// Bytes copied per HDMA block.
+pub const HDMA_BLOCK_SIZE: u16 = 0x10;
+
+/// CGB VRAM DMA (HDMA1–HDMA5).
+///
+/// HDMA1/2 hold the source address and HDMA3/4 the VRAM destination, both
+/// aligned to 16 bytes. Writing HDMA5 starts a transfer of `(value & 0x7F) + 1`
+/// blocks: with bit 7 clear a general-purpose transfer copies everything at
+/// once; with bit 7 set an HBlank transfer copies one block per HBlank.
+///
+/// Only owns the transfer state. The CPU does the copying, one
+/// [`Self::next_block`] at a time, and stalls itself while it happens.
+#[derive(Default)]
+pub struct HdmaPeripheral {
+    source: u16,
+    /// Offset into VRAM, 0x0000–0x1FF0.
+    destination: u16,
+    /// Blocks left to copy.
+    remaining: u8,
+    /// An HBlank transfer is in progress.
+    hblank_active: bool,
+}
+
+impl HdmaPeripheral {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle a CPU write to HDMA1–HDMA5. Returns true if the write started a
+    /// general-purpose transfer, which the caller must run to completion now.
+    pub fn write_register(&mut self, addr: u16, value: u8) -> bool {
+        match addr {
+            HDMA1_ADDR => self.source = (self.source & 0x00FF) | (value as u16) << 8,
+            HDMA2_ADDR => self.source = (self.source & 0xFF00) | (value & 0xF0) as u16,
+            HDMA3_ADDR => {
+                self.destination = (self.destination & 0x00FF) | ((value & 0x1F) as u16) << 8
+            }
+            HDMA4_ADDR => self.destination = (self.destination & 0xFF00) | (value & 0xF0) as u16,
+            HDMA5_ADDR => {
+                if self.hblank_active && value & 0x80 == 0 {
+                    // Writing bit 7 clear during an HBlank transfer cancels it.
+                    self.hblank_active = false;
+                    return false;
+                }
+                self.remaining = (value & 0x7F) + 1;
+                self.hblank_active = value & 0x80 != 0;
+                return !self.hblank_active;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// HDMA5 read-back: blocks left minus one, with bit 7 clear while an
+    /// HBlank transfer is active. Reads 0xFF once a transfer has finished.
+    pub fn read_hdma5(&self) -> u8 {
+        let blocks = self.remaining.wrapping_sub(1) & 0x7F;
+        if self.hblank_active {
+            blocks
+        } else {
+            0x80 | blocks
+        }
+    }
+
+    /// Whether an HBlank transfer is waiting for the next HBlank.
+    pub fn hblank_active(&self) -> bool {
+        self.hblank_active
+    }
+
+    /// Claim the next block: returns its source address and VRAM destination
+    /// address (0x8000–0x9FF0), then advances both.
+    pub fn next_block(&mut self) -> (u16, u16) {
+        let block = (self.source, 0x8000 | self.destination);
+        self.source = self.source.wrapping_add(HDMA_BLOCK_SIZE);
+        self.destination = (self.destination + HDMA_BLOCK_SIZE) & 0x1FF0;
+        self.remaining = self.remaining.saturating_sub(1);
+        if self.remaining == 0 {
+            self.hblank_active = false;
+        }
+        block
+    }
+
+    /// Extract HDMA state into an [`HdmaState`](crate::cpu::save_state::HdmaState) for serialization.
+    pub fn to_save_state(&self) -> crate::cpu::save_state::HdmaState {
+        crate::cpu::save_state::HdmaState {
+            source: self.source,
+            destination: self.destination,
+            remaining: self.remaining,
+            hblank_active: self.hblank_active,
+        }
+    }
+
+    /// Apply HDMA state from a parsed [`HdmaState`](crate::cpu::save_state::HdmaState).
+    pub fn load_state(&mut self, state: crate::cpu::save_state::HdmaState) {
+        self.source = state.source;
+        self.destination = state.destination;
+        self.remaining = state.remaining;
+        self.hblank_active = state.hblank_active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_addresses(hdma: &mut HdmaPeripheral, source: u16, destination: u16) {
+        hdma.write_register(HDMA1_ADDR, (source >> 8) as u8);
+        hdma.write_register(HDMA2_ADDR, source as u8);
+        hdma.write_register(HDMA3_ADDR, (destination >> 8) as u8);
+        hdma.write_register(HDMA4_ADDR, destination as u8);
+    }
+
+    #[test]
+    fn addresses_are_block_aligned_and_destination_stays_in_vram() {
+        let mut hdma = HdmaPeripheral::new();
+        set_addresses(&mut hdma, 0xC12F, 0xF34F);
+        assert_eq!(hdma.next_block(), (0xC120, 0x9340));
+    }
+
+    #[test]
+    fn general_purpose_transfer_counts_down_to_ff() {
+        let mut hdma = HdmaPeripheral::new();
+        set_addresses(&mut hdma, 0xC000, 0x8000);
+        assert!(
+            hdma.write_register(HDMA5_ADDR, 0x01),
+            "bit 7 clear starts a GDMA"
+        );
+        assert!(!hdma.hblank_active());
+        assert_eq!(hdma.next_block(), (0xC000, 0x8000));
+        assert_eq!(hdma.next_block(), (0xC010, 0x8010));
+        assert_eq!(hdma.read_hdma5(), 0xFF);
+    }
+
+    #[test]
+    fn hblank_transfer_reports_remaining_blocks() {
+        let mut hdma = HdmaPeripheral::new();
+        set_addresses(&mut hdma, 0x4000, 0x9000);
+        assert!(!hdma.write_register(HDMA5_ADDR, 0x82));
+        assert!(hdma.hblank_active());
+        assert_eq!(hdma.read_hdma5(), 0x02);
+
+        hdma.next_block();
+        assert_eq!(hdma.read_hdma5(), 0x01);
+        hdma.next_block();
+        hdma.next_block();
+        assert!(!hdma.hblank_active());
+        assert_eq!(hdma.read_hdma5(), 0xFF);
+    }
+
+    #[test]
+    fn clearing_bit_7_cancels_an_hblank_transfer() {
+        let mut hdma = HdmaPeripheral::new();
+        hdma.write_register(HDMA5_ADDR, 0x83);
+        hdma.next_block();
+        assert!(
+            !hdma.write_register(HDMA5_ADDR, 0x00),
+            "cancelling does not start a GDMA"
+        );
+        assert!(!hdma.hblank_active());
+        assert_eq!(hdma.read_hdma5(), 0x82);
+    }
+}
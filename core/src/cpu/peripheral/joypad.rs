@@ -33,6 +33,23 @@ pub enum Button {
     Start,
 }
 
+impl Button {
+    /// All buttons, in bitmask order: bit `n` of a button mask is `ALL[n]`.
+    pub const ALL: [Button; 8] = [
+        Button::Right, Button::Left, Button::Up, Button::Down,
+        Button::A, Button::B, Button::Select, Button::Start,
+    ];
+
+    /// This button's bit in a button mask.
+    pub fn mask(self) -> u8 {
+        let (group, bit) = button_to_group_bit(self);
+        match group {
+            Group::Directions => 1 << bit,
+            Group::Actions    => 1 << (bit + 4),
+        }
+    }
+}
+
 /// Interrupt bit for joypad in the IF register (bit 4).
 pub(crate) const JOYPAD_INTERRUPT_BIT: u8 = 4;
 /// JOYP register address.
@@ -83,6 +100,22 @@ impl JoypadPeripheral {
         }
     }
 
+    /// Pressed buttons as a mask (see [`Button::ALL`]).
+    pub fn pressed(&self) -> u8 {
+        self.directions | (self.actions << 4)
+    }
+
+    /// Extract joypad state into a [`JoypadState`] for serialization.
+    pub fn to_save_state(&self) -> crate::cpu::save_state::JoypadState {
+        crate::cpu::save_state::JoypadState { select: self.select }
+    }
+
+    /// Apply joypad state from a parsed [`JoypadState`]. Pressed buttons are
+    /// live host input and are not restored.
+    pub fn load_state(&mut self, state: crate::cpu::save_state::JoypadState) {
+        self.select = state.select & 0x30;
+    }
+
     /// Handle a write to JOYP — update the stored select bits.
     pub fn write(&mut self, value: u8) {
         self.select = value & 0x30;
@@ -259,4 +292,19 @@ mod tests {
         assert_eq!(low & 0x04, 4); // Select released
         assert_eq!(low & 0x08, 0); // Start pressed
     }
+
+    #[test]
+    fn button_masks_follow_all_order() {
+        for (n, button) in Button::ALL.into_iter().enumerate() {
+            assert_eq!(button.mask(), 1 << n, "{button:?}");
+        }
+    }
+
+    #[test]
+    fn pressed_reports_both_groups() {
+        let mut joypad = JoypadPeripheral::new();
+        joypad.set_button(Button::Left, true);
+        joypad.set_button(Button::Start, true);
+        assert_eq!(joypad.pressed(), Button::Left.mask() | Button::Start.mask());
+    }
 }
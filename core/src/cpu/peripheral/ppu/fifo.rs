@@ -0,0 +1,538 @@
+//! Dot-accurate pixel FIFO renderer.
+//!
+//! The scanline renderer draws a whole line when mode 3 ends, so a register
+//! write during mode 3 either lands for the entire line or not at all. This
+//! renderer instead steps the background fetcher and the BG and OBJ pixel
+//! FIFOs one dot at a time, reading SCX, SCY, LCDC, WX and the palettes as
+//! tiles are fetched and pixels are shifted out. Mode 3 then lasts as long as
+//! it takes: 172 dots, plus SCX fine scroll, plus 6 dots when the window
+//! starts, plus 6–11 dots per object fetch.
+//!
+//! Simplifications: the OAM scan happens in one go at the start of mode 3, and
+//! an object fetch pauses the background fetcher for a penalty computed from
+//! the object's position in its BG tile, rather than modelling how the two
+//! fetchers share VRAM dot by dot.
+
+use crate::cpu::save_state::PixelFifoState;
+
+use super::{
+    apply_palette, decode_2bpp_pixel, palette::{set_pixel_layer, Layer}, palette_color, sprite_tile_row_address,
+    tile_data_address, Lcdc, PpuInput, PpuPeripheral, SCREEN_WIDTH,
+};
+
+/// Dots spent on the first tile fetch of a line, which is thrown away.
+const STARTUP_DOTS: u8 = 6;
+/// Dots an object fetch takes once the background fetcher has finished its
+/// current tile.
+const OBJ_FETCH_DOTS: u8 = 6;
+/// `obj_fetch_slot` when no object fetch is in progress.
+const NO_OBJ_FETCH: u8 = 0xFF;
+
+// Background fetcher steps. All but the push take two dots; the push retries
+// every dot until the BG FIFO is empty.
+const STEP_TILE: u8 = 0;
+const STEP_DATA_LOW: u8 = 1;
+const STEP_DATA_HIGH: u8 = 2;
+const STEP_PUSH: u8 = 3;
+
+impl PixelFifoState {
+    /// Reset for a new line at the start of mode 3 and select its objects.
+    pub(super) fn start_line(&mut self, ly: u8, input: &PpuInput) {
+        let wy_matched = ly != 0 && self.wy_matched;
+        *self = PixelFifoState {
+            discard: input.scx & 0x07,
+            startup: STARTUP_DOTS,
+            // WY is compared at the start of each line; one match enables the
+            // window for the rest of the frame.
+            wy_matched: wy_matched || input.wy == ly,
+            ..PixelFifoState::idle()
+        };
+
+        let height: i16 = if Lcdc(input.lcdc).obj_tall() { 16 } else { 8 };
+        for i in 0..40 {
+            if self.object_count as usize == self.objects.len() {
+                break;
+            }
+            let y = input.oam[i * 4] as i16 - 16;
+            if (y..y + height).contains(&(ly as i16)) {
+                self.objects[self.object_count as usize] = i as u8;
+                self.object_x[self.object_count as usize] = input.oam[i * 4 + 1];
+                self.object_count += 1;
+            }
+        }
+    }
+
+    /// Compare WY against a line drawn without mode 3: line 0 after the LCD
+    /// is switched on still enables the window for the frame.
+    pub(super) fn match_wy(&mut self, ly: u8, wy: u8) {
+        self.wy_matched |= wy == ly;
+    }
+
+    /// State with no line in progress. If mode 3 is entered without
+    /// [`Self::start_line`] (after switching renderer or loading a state
+    /// without FIFO data) the line is drawn from its first pixel, with no
+    /// objects.
+    pub(super) fn idle() -> Self {
+        PixelFifoState { obj_fetch_slot: NO_OBJ_FETCH, ..PixelFifoState::default() }
+    }
+
+    /// Whether the renderer can resume from this state, e.g. one read from a
+    /// save state blob: every FIFO length, index and step in range.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.lx as usize <= SCREEN_WIDTH
+            && self.discard <= 7
+            && self.startup <= STARTUP_DOTS
+            && self.fetch_step <= STEP_PUSH
+            && self.bg_len <= 8
+            && self.obj_head <= 7
+            && self.object_count as usize <= self.objects.len()
+            && (self.obj_fetch_slot == NO_OBJ_FETCH
+                || (self.obj_fetch_slot < self.object_count && self.obj_fetch_dots > 0))
+    }
+
+    /// The not-yet-fetched object with the lowest X (then OAM index) that
+    /// starts at or before screen X `lx`.
+    fn object_at(&self, lx: u8) -> Option<usize> {
+        (0..self.object_count as usize)
+            .filter(|&slot| self.fetched & (1 << slot) == 0 && self.object_x[slot] <= lx + 8)
+            .min_by_key(|&slot| (self.object_x[slot], self.objects[slot]))
+    }
+
+    /// Extra dots an object fetch at the current pixel waits for the BG
+    /// fetcher: `5 - min(5, x)` where `x` is the object's offset into its BG
+    /// tile. Only the first object in a tile pays it.
+    fn tile_penalty(&mut self) -> u8 {
+        let x = (8 - self.bg_len + self.discard) & 0x07;
+        let tile_end = self.lx + 8 - x;
+        if self.penalty_tile_end == tile_end {
+            return 0;
+        }
+        self.penalty_tile_end = tile_end;
+        5 - x.min(5)
+    }
+
+    fn pop_bg(&mut self) -> (u8, u8) {
+        let i = 8 - self.bg_len as usize;
+        self.bg_len -= 1;
+        (self.bg_colors[i], self.bg_attrs[i])
+    }
+
+    fn pop_obj(&mut self) -> (u8, u8) {
+        let head = self.obj_head as usize;
+        let pixel = (self.obj_colors[head], self.obj_attrs[head]);
+        self.obj_colors[head] = 0;
+        self.obj_head = (self.obj_head + 1) & 0x07;
+        pixel
+    }
+}
+
+impl PpuPeripheral {
+    /// Run mode 3 for up to `*remaining` dots. Returns true once the last
+    /// pixel of the line is out and the PPU should enter HBlank.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    pub(super) fn run_fifo(
+        &mut self,
+        fifo: &mut PixelFifoState,
+        input: &PpuInput,
+        remaining: &mut u16,
+    ) -> bool {
+        while *remaining > 0 {
+            *remaining -= 1;
+            self.dot += 1;
+            if self.fifo_dot(fifo, input) {
+                if fifo.window {
+                    self.window_line_counter += 1;
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn cgb_mode(&self) -> bool {
+        self.color.as_deref().is_some_and(|color| color.cgb_mode)
+    }
+
+    /// One dot of mode 3. Returns true when the line is finished.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn fifo_dot(&mut self, fifo: &mut PixelFifoState, input: &PpuInput) -> bool {
+        let lcdc = Lcdc(input.lcdc);
+        if fifo.startup > 0 {
+            fifo.startup -= 1;
+            return false;
+        }
+
+        if !fifo.window && lcdc.window_enabled() && fifo.wy_matched && fifo.lx + 7 >= input.wx {
+            // The fetcher restarts on the window's first tile and the pixels
+            // already in the BG FIFO are dropped.
+            fifo.window = true;
+            fifo.penalty_tile_end = 0;
+            fifo.discard = 7u8.saturating_sub(input.wx);
+            fifo.bg_len = 0;
+            fifo.fetch_x = 0;
+            fifo.fetch_step = STEP_TILE;
+            fifo.fetch_half = false;
+        }
+
+        if fifo.obj_fetch_slot == NO_OBJ_FETCH && lcdc.obj_enabled() {
+            if let Some(slot) = fifo.object_at(fifo.lx) {
+                fifo.fetched |= 1 << slot;
+                fifo.obj_fetch_slot = slot as u8;
+                fifo.obj_fetch_dots = OBJ_FETCH_DOTS + fifo.tile_penalty();
+            }
+        }
+
+        if fifo.obj_fetch_slot != NO_OBJ_FETCH {
+            // Pixel output and the background fetcher pause for the object fetch.
+            fifo.obj_fetch_dots -= 1;
+            if fifo.obj_fetch_dots == 0 {
+                self.fetch_object(fifo, input, lcdc, fifo.obj_fetch_slot as usize);
+                fifo.obj_fetch_slot = NO_OBJ_FETCH;
+            }
+            return false;
+        }
+
+        self.step_fetcher(fifo, input, lcdc);
+        if fifo.bg_len == 0 {
+            return false;
+        }
+        let bg = fifo.pop_bg();
+        if fifo.discard > 0 {
+            fifo.discard -= 1;
+            return false;
+        }
+        let obj = fifo.pop_obj();
+        self.output_fifo_pixel(fifo.lx, bg, obj, input, lcdc);
+        fifo.lx += 1;
+        fifo.lx as usize == SCREEN_WIDTH
+    }
+
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn step_fetcher(&self, fifo: &mut PixelFifoState, input: &PpuInput, lcdc: Lcdc) {
+        if fifo.fetch_step != STEP_PUSH {
+            fifo.fetch_half = !fifo.fetch_half;
+            if fifo.fetch_half {
+                return;
+            }
+        }
+        match fifo.fetch_step {
+            STEP_TILE => {
+                let map_addr = self.fetcher_map_address(fifo, input, lcdc);
+                fifo.tile = input.vram[map_addr];
+                fifo.tile_attrs = if self.cgb_mode() { input.vram_bank1[map_addr] } else { 0 };
+                fifo.fetch_step = STEP_DATA_LOW;
+            }
+            STEP_DATA_LOW => {
+                fifo.tile_lo = self.fetcher_tile_data(fifo, input, lcdc, 0);
+                fifo.fetch_step = STEP_DATA_HIGH;
+            }
+            STEP_DATA_HIGH => {
+                fifo.tile_hi = self.fetcher_tile_data(fifo, input, lcdc, 1);
+                fifo.fetch_step = STEP_PUSH;
+            }
+            _ => {
+                if fifo.bg_len > 0 {
+                    return;
+                }
+                for i in 0..8u8 {
+                    let bit = if fifo.tile_attrs & 0x20 != 0 { i } else { 7 - i };
+                    fifo.bg_colors[i as usize] = decode_2bpp_pixel(fifo.tile_lo, fifo.tile_hi, bit);
+                    fifo.bg_attrs[i as usize] = fifo.tile_attrs;
+                }
+                fifo.bg_len = 8;
+                fifo.fetch_x = fifo.fetch_x.wrapping_add(1);
+                fifo.fetch_step = STEP_TILE;
+            }
+        }
+    }
+
+    /// Tilemap offset of the tile the fetcher is on. SCX and SCY are read
+    /// live, so mid-line writes take effect from the next tile.
+    fn fetcher_map_address(&self, fifo: &PixelFifoState, input: &PpuInput, lcdc: Lcdc) -> usize {
+        if fifo.window {
+            let base = if lcdc.window_tilemap_high() { 0x1C00 } else { 0x1800 };
+            let row = (self.window_line_counter / 8) as usize;
+            base + row * 32 + (fifo.fetch_x & 0x1F) as usize
+        } else {
+            let base = if lcdc.bg_tilemap_high() { 0x1C00 } else { 0x1800 };
+            let row = (input.scy.wrapping_add(self.ly) / 8) as usize;
+            let col = (input.scx / 8).wrapping_add(fifo.fetch_x) & 0x1F;
+            base + row * 32 + col as usize
+        }
+    }
+
+    fn fetcher_tile_data(&self, fifo: &PixelFifoState, input: &PpuInput, lcdc: Lcdc, byte: usize) -> u8 {
+        let y = if fifo.window { self.window_line_counter } else { input.scy.wrapping_add(self.ly) };
+        let fine_y = if fifo.tile_attrs & 0x40 != 0 { 7 - y % 8 } else { y % 8 };
+        let bank = if fifo.tile_attrs & 0x08 != 0 { input.vram_bank1 } else { input.vram };
+        bank[tile_data_address(lcdc, fifo.tile, fine_y as usize) + byte]
+    }
+
+    /// Fetch the tile row of the object in `slot` and merge it into the OBJ
+    /// FIFO. Opaque pixels already there keep priority, except in CGB mode
+    /// when OPRI selects priority by OAM index.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn fetch_object(&self, fifo: &mut PixelFifoState, input: &PpuInput, lcdc: Lcdc, slot: usize) {
+        let oam_index = fifo.objects[slot];
+        let oam_addr = oam_index as usize * 4;
+        let height: u8 = if lcdc.obj_tall() { 16 } else { 8 };
+        let row = (self.ly as i16) - (input.oam[oam_addr] as i16 - 16);
+        if !(0..height as i16).contains(&row) {
+            // OAM changed since the scan; the object no longer covers this line.
+            return;
+        }
+        let tile = input.oam[oam_addr + 2];
+        let attrs = input.oam[oam_addr + 3];
+        let cgb_mode = self.cgb_mode();
+        let by_oam_index = self.color.as_deref().is_some_and(|color| !color.obj_priority_by_x);
+
+        let tile_addr = sprite_tile_row_address(lcdc, height, tile, attrs, row as u8);
+        let bank = if cgb_mode && attrs & 0x08 != 0 { input.vram_bank1 } else { input.vram };
+        let (lo, hi) = (bank[tile_addr], bank[tile_addr + 1]);
+
+        let left = fifo.object_x[slot] as i16 - 8;
+        for pixel in 0..8u8 {
+            let offset = left + pixel as i16 - fifo.lx as i16;
+            if offset < 0 {
+                continue;
+            }
+            let bit = if attrs & 0x20 != 0 { pixel } else { 7 - pixel };
+            let color = decode_2bpp_pixel(lo, hi, bit);
+            let i = (fifo.obj_head as usize + offset as usize) & 0x07;
+            let replace = fifo.obj_colors[i] == 0 || (by_oam_index && oam_index < fifo.obj_oam[i]);
+            if color != 0 && replace {
+                fifo.obj_colors[i] = color;
+                fifo.obj_attrs[i] = attrs;
+                fifo.obj_oam[i] = oam_index;
+            }
+        }
+    }
+
+    /// Mix one BG and OBJ pixel and write it at screen X `x`. LCDC and the
+    /// palettes are read at this point, so mid-line writes split the line.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn output_fifo_pixel(&mut self, x: u8, bg: (u8, u8), obj: (u8, u8), input: &PpuInput, lcdc: Lcdc) {
+        let offset = self.ly as usize * SCREEN_WIDTH + x as usize;
+        let (bg_index, bg_attrs) = bg;
+        let (obj_index, obj_attrs) = obj;
+        let obj_index = if lcdc.obj_enabled() { obj_index } else { 0 };
+
+        match self.color.as_deref_mut() {
+            Some(color) if color.cgb_mode => {
+                // LCDC bit 0 is the BG's master priority over objects in CGB mode.
+                let obj_wins = obj_index != 0
+                    && (!lcdc.bg_enabled() || bg_index == 0 || (obj_attrs | bg_attrs) & 0x80 == 0);
+                if obj_wins {
+                    self.framebuffer[offset] = obj_index;
+                    color.framebuffer[offset] = palette_color(&color.obj_palettes, obj_attrs & 0x07, obj_index);
+                } else {
+                    self.framebuffer[offset] = bg_index;
+                    color.framebuffer[offset] = palette_color(&color.bg_palettes, bg_attrs & 0x07, bg_index);
+                }
+            }
+            color => {
+                let bg_index = if lcdc.bg_enabled() { bg_index } else { 0 };
+                let obj_wins = obj_index != 0 && (obj_attrs & 0x80 == 0 || bg_index == 0);
+                let (shade, obj_palette) = if obj_wins {
+                    let obp = if obj_attrs & 0x10 != 0 { input.obp1 } else { input.obp0 };
+                    (apply_palette(obp, obj_index), Some((obj_attrs >> 4) & 0x01))
+                } else if lcdc.bg_enabled() {
+                    (apply_palette(input.bgp, bg_index), None)
+                } else {
+                    (0, None)
+                };
+                self.framebuffer[offset] = shade;
+                match color {
+                    Some(color) => {
+                        color.framebuffer[offset] = match obj_palette {
+                            Some(palette) => palette_color(&color.obj_palettes, palette, shade),
+                            None => palette_color(&color.bg_palettes, 0, shade),
+                        };
+                    }
+                    None => {
+                        let layer = match obj_palette {
+                            Some(0) => Layer::Obj0,
+                            Some(_) => Layer::Obj1,
+                            None => Layer::Background,
+                        };
+                        set_pixel_layer(&mut self.layers, offset, layer);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{PpuRenderer, CYCLES_PER_FRAME, SCREEN_HEIGHT};
+    use super::*;
+
+    struct Scene {
+        vram: [u8; 0x2000],
+        oam: [u8; 0xA0],
+        lcdc: u8,
+        scx: u8,
+        bgp: u8,
+        wy: u8,
+        wx: u8,
+    }
+
+    impl Scene {
+        fn new() -> Self {
+            Scene { vram: [0; 0x2000], oam: [0; 0xA0], lcdc: 0x93, scx: 0, bgp: 0xE4, wy: 0, wx: 0xFF }
+        }
+
+        fn input(&self) -> PpuInput<'_> {
+            PpuInput {
+                lcdc: self.lcdc,
+                stat: 0x00,
+                scy: 0,
+                scx: self.scx,
+                lyc: 0xFF,
+                bgp: self.bgp,
+                obp0: 0xE4,
+                obp1: 0x1B,
+                wy: self.wy,
+                wx: self.wx,
+                vram: &self.vram,
+                vram_bank1: &[],
+                oam: &self.oam,
+            }
+        }
+
+        fn set_object(&mut self, index: usize, y: u8, x: u8, tile: u8, attrs: u8) {
+            self.oam[index * 4..index * 4 + 4].copy_from_slice(&[y, x, tile, attrs]);
+        }
+    }
+
+    fn fifo_ppu() -> PpuPeripheral {
+        let mut ppu = PpuPeripheral::new();
+        ppu.set_renderer(PpuRenderer::Fifo);
+        ppu
+    }
+
+    /// Dots the first line spends in mode 3.
+    fn mode3_length(scene: &Scene) -> u32 {
+        let mut ppu = fifo_ppu();
+        let mut dots = 0;
+        for _ in 0..456 {
+            if ppu.tick(1, scene.input()).stat & 0x03 == 3 {
+                dots += 1;
+            }
+        }
+        dots
+    }
+
+    #[test]
+    fn test_mode3_is_172_dots_without_scroll_or_objects() {
+        assert_eq!(mode3_length(&Scene::new()), 172);
+    }
+
+    #[test]
+    fn test_scx_fine_scroll_lengthens_mode3() {
+        let mut scene = Scene::new();
+        scene.scx = 3;
+        assert_eq!(mode3_length(&scene), 175);
+        scene.scx = 8;
+        assert_eq!(mode3_length(&scene), 172);
+    }
+
+    #[test]
+    fn test_window_lengthens_mode3_by_six() {
+        let mut scene = Scene::new();
+        scene.lcdc |= 0x20;
+        scene.wx = 87;
+        assert_eq!(mode3_length(&scene), 178);
+    }
+
+    #[test]
+    fn test_object_penalty_depends_on_tile_alignment() {
+        let mut scene = Scene::new();
+        scene.set_object(0, 16, 8, 0, 0);
+        assert_eq!(mode3_length(&scene), 172 + 11);
+
+        // A second object in the same tile only pays the fetch itself.
+        scene.set_object(1, 16, 8, 0, 0);
+        assert_eq!(mode3_length(&scene), 172 + 11 + 6);
+
+        let mut scene = Scene::new();
+        scene.set_object(0, 16, 8 + 5, 0, 0);
+        assert_eq!(mode3_length(&scene), 172 + 6);
+    }
+
+    #[test]
+    fn test_objects_ignored_when_disabled() {
+        let mut scene = Scene::new();
+        scene.lcdc &= !0x02;
+        scene.set_object(0, 16, 8, 0, 0);
+        assert_eq!(mode3_length(&scene), 172);
+    }
+
+    #[test]
+    fn test_mid_line_bgp_write_splits_the_line() {
+        let mut scene = Scene::new();
+        // Tile 0 is solid color 3.
+        scene.vram[..16].fill(0xFF);
+        let mut ppu = fifo_ppu();
+        // OAM scan, the discarded fetch and the first 40 pixels.
+        for _ in 0..80 + 12 + 40 {
+            ppu.tick(1, scene.input());
+        }
+        scene.bgp = 0x1B;
+        for _ in 0..456 {
+            ppu.tick(1, scene.input());
+        }
+
+        let line = &ppu.framebuffer()[..160];
+        assert!(line[..40].iter().all(|&shade| shade == 3));
+        assert!(line[40..].iter().all(|&shade| shade == 0));
+    }
+
+    #[test]
+    fn test_matches_scanline_renderer_on_static_scene() {
+        let mut scene = Scene::new();
+        scene.lcdc |= 0x20;
+        scene.scx = 13;
+        scene.wy = 60;
+        scene.wx = 107;
+        // Tiles 1-3 hold distinct patterns; the BG map and window map use them.
+        for tile in 1..4usize {
+            for row in 0..8 {
+                scene.vram[tile * 16 + row * 2] = 0xF0 >> ((tile + row) % 4);
+                scene.vram[tile * 16 + row * 2 + 1] = (0x3C << tile) as u8 ^ row as u8;
+            }
+        }
+        for (i, entry) in scene.vram[0x1800..0x1C00].iter_mut().enumerate() {
+            *entry = (i % 7 % 4) as u8;
+        }
+        scene.set_object(0, 20, 4, 1, 0x00);
+        scene.set_object(1, 30, 50, 2, 0x20);
+        scene.set_object(2, 34, 52, 3, 0x90);
+        scene.set_object(3, 90, 120, 1, 0x40);
+
+        let mut scanline = PpuPeripheral::new();
+        let mut fifo = fifo_ppu();
+        for _ in 0..CYCLES_PER_FRAME / 4 {
+            scanline.tick(4, scene.input());
+            fifo.tick(4, scene.input());
+        }
+        for ly in 0..SCREEN_HEIGHT {
+            let row = ly * SCREEN_WIDTH..(ly + 1) * SCREEN_WIDTH;
+            assert_eq!(fifo.framebuffer()[row.clone()], scanline.framebuffer()[row], "line {ly}");
+        }
+    }
+
+    #[test]
+    fn test_line_timing_unchanged_by_mode3_length() {
+        let mut scene = Scene::new();
+        scene.scx = 7;
+        scene.set_object(0, 16, 8, 0, 0);
+        let mut ppu = fifo_ppu();
+        let mut ly = 0;
+        for _ in 0..456 {
+            ly = ppu.tick(1, scene.input()).ly;
+        }
+        assert_eq!(ly, 1);
+    }
+}
@@ -0,0 +1,162 @@
+//! DMG OAM corruption bug.
+//!
+//! During mode 2 the PPU reads OAM one 8-byte row per M-cycle. If the CPU
+//! puts an address in 0xFE00–0xFEFF on the bus in the same M-cycle, whether
+//! to read, write, or just to increment or decrement a register pair, the row
+//! being read is mangled: its first word is replaced by a bitwise mix of
+//! itself and the previous row, and the rest of it by a copy of the previous
+//! row. The first row is never corrupted. The CGB fixed this.
+
+use super::{PpuMode, PpuPeripheral};
+
+/// What the CPU did with an OAM-range address on the bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OamCorruption {
+    /// A write, or a 16-bit INC/DEC of a pair holding the address.
+    Write,
+    /// A read.
+    Read,
+    /// A read that also increments or decrements the pair holding the
+    /// address (`LD A,(HL+)`, `LD A,(HL-)`, the first read of `POP`). This
+    /// corrupts the two rows before the current one and is then followed by
+    /// a [`Read`](Self::Read) corruption.
+    ReadIncrease,
+}
+
+const ROW_SIZE: usize = 8;
+const ROWS: usize = 20;
+
+impl PpuPeripheral {
+    /// OAM row the PPU reads in the current M-cycle, if it is in mode 2.
+    fn oam_scan_row(&self) -> Option<usize> {
+        (self.mode == PpuMode::OamScan).then_some(self.dot as usize / 4).filter(|&row| row < ROWS)
+    }
+
+    /// Apply the corruption `kind` to `oam` if the PPU is scanning OAM.
+    pub fn corrupt_oam(&self, oam: &mut [u8], kind: OamCorruption) {
+        let Some(row) = self.oam_scan_row() else { return };
+        match kind {
+            OamCorruption::Write => {
+                if row > 0 {
+                    let (a, b, c) = (word(oam, row, 0), word(oam, row - 1, 0), word(oam, row - 1, 2));
+                    set_word(oam, row, 0, ((a ^ c) & (b ^ c)) ^ c);
+                    copy_tail(oam, row - 1, row);
+                }
+            }
+            OamCorruption::Read => {
+                if row > 0 {
+                    let (a, b, c) = (word(oam, row, 0), word(oam, row - 1, 0), word(oam, row - 1, 2));
+                    let glitched = b | (a & c);
+                    set_word(oam, row - 1, 0, glitched);
+                    set_word(oam, row, 0, glitched);
+                    copy_tail(oam, row - 1, row);
+                }
+            }
+            OamCorruption::ReadIncrease => {
+                if (4..ROWS - 1).contains(&row) {
+                    let a = word(oam, row - 2, 0);
+                    let b = word(oam, row - 1, 0);
+                    let c = word(oam, row, 0);
+                    let d = word(oam, row - 1, 2);
+                    set_word(oam, row - 1, 0, (b & (a | c | d)) | (a & c & d));
+                    let previous = row - 1;
+                    oam.copy_within(previous * ROW_SIZE..row * ROW_SIZE, (row - 2) * ROW_SIZE);
+                    oam.copy_within(previous * ROW_SIZE..row * ROW_SIZE, row * ROW_SIZE);
+                }
+                self.corrupt_oam(oam, OamCorruption::Read);
+            }
+        }
+    }
+}
+
+fn word(oam: &[u8], row: usize, index: usize) -> u16 {
+    let offset = row * ROW_SIZE + index * 2;
+    u16::from_le_bytes([oam[offset], oam[offset + 1]])
+}
+
+fn set_word(oam: &mut [u8], row: usize, index: usize, value: u16) {
+    let offset = row * ROW_SIZE + index * 2;
+    oam[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Copy words 1-3 of row `from` over those of row `to`.
+fn copy_tail(oam: &mut [u8], from: usize, to: usize) {
+    oam.copy_within(from * ROW_SIZE + 2..(from + 1) * ROW_SIZE, to * ROW_SIZE + 2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// OAM where every byte holds its own row number in the high nibble and
+    /// its offset in the row in the low nibble.
+    fn numbered_oam() -> [u8; 0xA0] {
+        let mut oam = [0; 0xA0];
+        for (i, byte) in oam.iter_mut().enumerate() {
+            *byte = (((i / ROW_SIZE) << 4) | (i % ROW_SIZE)) as u8;
+        }
+        oam
+    }
+
+    fn scanning_row(row: u16) -> PpuPeripheral {
+        let mut ppu = PpuPeripheral::new();
+        ppu.mode = PpuMode::OamScan;
+        ppu.dot = row * 4;
+        ppu
+    }
+
+    #[test]
+    fn test_no_corruption_outside_oam_scan() {
+        let mut ppu = scanning_row(5);
+        ppu.mode = PpuMode::HBlank;
+        let mut oam = numbered_oam();
+        ppu.corrupt_oam(&mut oam, OamCorruption::Write);
+        assert_eq!(oam, numbered_oam());
+    }
+
+    #[test]
+    fn test_first_row_is_never_corrupted() {
+        let ppu = scanning_row(0);
+        let mut oam = numbered_oam();
+        ppu.corrupt_oam(&mut oam, OamCorruption::Write);
+        ppu.corrupt_oam(&mut oam, OamCorruption::Read);
+        assert_eq!(oam, numbered_oam());
+    }
+
+    #[test]
+    fn test_write_corrupts_current_row_from_previous_row() {
+        let ppu = scanning_row(3);
+        let mut oam = numbered_oam();
+        ppu.corrupt_oam(&mut oam, OamCorruption::Write);
+
+        let (a, b, c) = (0x3130u16, 0x2120u16, 0x2524u16);
+        assert_eq!(word(&oam, 3, 0), ((a ^ c) & (b ^ c)) ^ c);
+        assert_eq!(oam[26..32], oam[18..24]);
+        assert_eq!(oam[..24], numbered_oam()[..24]);
+        assert_eq!(oam[32..], numbered_oam()[32..]);
+    }
+
+    #[test]
+    fn test_read_glitches_first_word_of_both_rows() {
+        let ppu = scanning_row(3);
+        let mut oam = numbered_oam();
+        ppu.corrupt_oam(&mut oam, OamCorruption::Read);
+
+        let glitched = 0x2120 | (0x3130 & 0x2524);
+        assert_eq!(word(&oam, 2, 0), glitched);
+        assert_eq!(word(&oam, 3, 0), glitched);
+        assert_eq!(oam[26..32], oam[18..24]);
+    }
+
+    #[test]
+    fn test_read_increase_copies_previous_row_around_it() {
+        let ppu = scanning_row(6);
+        let mut oam = numbered_oam();
+        ppu.corrupt_oam(&mut oam, OamCorruption::ReadIncrease);
+
+        assert_eq!(oam[32..40], oam[40..48]);
+        assert_eq!(oam[50..56], oam[42..48]);
+        assert_eq!(oam[..32], numbered_oam()[..32]);
+        assert_eq!(oam[56..], numbered_oam()[56..]);
+    }
+}
@@ -0,0 +1,471 @@
+//! Output palettes: turning the 2-bit shade framebuffer into display colors.
+//!
+//! On DMG the PPU only produces shades 0–3 after BGP/OBP0/OBP1; what color a
+//! shade is depends on the screen. A [`Palette`] gives each of the three
+//! layers its own four colors, the way a CGB colors DMG games. Which layer a
+//! pixel came from is recorded in a packed 2-bit plane next to the shade
+//! framebuffer (see [`pixel_layer`]).
+//!
+//! CGB color output is already RGB555; [`ColorCorrection`] optionally
+//! approximates the washed-out look of the CGB LCD when converting it.
+//!
+//! Pan Docs reference: <https://gbdev.io/pandocs/Power_Up_Sequence.html#compatibility-palettes>
+
+use super::{CompatPalette, FRAMEBUFFER_SIZE};
+
+/// Bytes in a layer plane: 2 bits per pixel.
+pub const LAYER_PLANE_SIZE: usize = FRAMEBUFFER_SIZE / 4;
+
+/// The layer a DMG pixel was drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Layer {
+    /// Background or window.
+    Background = 0,
+    /// An object using OBP0.
+    Obj0 = 1,
+    /// An object using OBP1.
+    Obj1 = 2,
+}
+
+/// Layer of pixel `index` in a packed layer plane.
+#[inline]
+pub fn pixel_layer(plane: &[u8; LAYER_PLANE_SIZE], index: usize) -> Layer {
+    match (plane[index / 4] >> ((index % 4) * 2)) & 0x03 {
+        1 => Layer::Obj0,
+        2 => Layer::Obj1,
+        _ => Layer::Background,
+    }
+}
+
+/// Record `layer` for pixel `index` in a packed layer plane.
+#[inline]
+pub(crate) fn set_pixel_layer(plane: &mut [u8; LAYER_PLANE_SIZE], index: usize, layer: Layer) {
+    let shift = (index % 4) * 2;
+    let byte = &mut plane[index / 4];
+    *byte = (*byte & !(0x03 << shift)) | (layer as u8) << shift;
+}
+
+// ── Palette ──────────────────────────────────────────────────────────────────
+
+/// Display colors for DMG shades 0–3 (lightest to darkest), as 0xRRGGBB, per
+/// layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub bg: [u32; 4],
+    pub obj0: [u32; 4],
+    pub obj1: [u32; 4],
+}
+
+impl Palette {
+    /// The pea-green original DMG screen.
+    pub const DMG_GREEN: Self = Self::uniform([0xE0F8D0, 0x88C070, 0x346856, 0x081820]);
+    /// The Game Boy Pocket's grey screen.
+    pub const POCKET: Self = Self::uniform([0xE0DBCD, 0xA89F94, 0x706B66, 0x2B2B26]);
+    /// The backlit blue-green Game Boy Light screen.
+    pub const LIGHT: Self = Self::uniform([0x00B581, 0x009A71, 0x00694A, 0x004F3B]);
+    /// Pure white to pure black, with the middle shades pushed apart.
+    pub const HIGH_CONTRAST: Self = Self::uniform([0xFFFFFF, 0xB0B0B0, 0x505050, 0x000000]);
+
+    pub const fn uniform(colors: [u32; 4]) -> Self {
+        Self { bg: colors, obj0: colors, obj1: colors }
+    }
+
+    /// The colors of `layer`.
+    pub fn layer(&self, layer: Layer) -> &[u32; 4] {
+        match layer {
+            Layer::Background => &self.bg,
+            Layer::Obj0 => &self.obj0,
+            Layer::Obj1 => &self.obj1,
+        }
+    }
+
+    /// Lookup table of RGBA8888 bytes, indexed by `[layer as usize][shade]`.
+    pub fn rgba8888_table(&self) -> [[[u8; 4]; 4]; 3] {
+        self.table(rgb888_to_rgba8888)
+    }
+
+    /// Lookup table of RGB565 values, indexed by `[layer as usize][shade]`.
+    pub fn rgb565_table(&self) -> [[u16; 4]; 3] {
+        self.table(rgb888_to_rgb565)
+    }
+
+    fn table<T>(&self, convert: fn(u32) -> T) -> [[T; 4]; 3] {
+        [&self.bg, &self.obj0, &self.obj1].map(|colors| colors.map(convert))
+    }
+
+    /// Colorize a whole DMG frame into RGBA8888 bytes. `out` holds at least
+    /// `4 * FRAMEBUFFER_SIZE` bytes.
+    pub fn write_rgba8888(&self, shades: &[u8; FRAMEBUFFER_SIZE], layers: &[u8; LAYER_PLANE_SIZE], out: &mut [u8]) {
+        let table = self.rgba8888_table();
+        for (i, (&shade, pixel)) in shades.iter().zip(out.chunks_exact_mut(4)).enumerate() {
+            pixel.copy_from_slice(&table[pixel_layer(layers, i) as usize][(shade & 0x03) as usize]);
+        }
+    }
+}
+
+impl From<CompatPalette> for Palette {
+    fn from(palette: CompatPalette) -> Self {
+        Self { bg: palette.bg, obj0: palette.obj0, obj1: palette.obj1 }
+    }
+}
+
+// ── Presets ──────────────────────────────────────────────────────────────────
+
+/// A user-selectable palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PalettePreset {
+    #[default]
+    DmgGreen,
+    Pocket,
+    Light,
+    HighContrast,
+    /// What a CGB shows for the cartridge: the boot ROM's per-title palette
+    /// for Nintendo games, [`CompatPalette::DEFAULT`] for everything else.
+    CgbTitle,
+}
+
+impl PalettePreset {
+    pub const ALL: [PalettePreset; 5] = [
+        PalettePreset::DmgGreen,
+        PalettePreset::Pocket,
+        PalettePreset::Light,
+        PalettePreset::HighContrast,
+        PalettePreset::CgbTitle,
+    ];
+
+    /// Stable name for settings files and UIs.
+    pub fn name(self) -> &'static str {
+        match self {
+            PalettePreset::DmgGreen => "dmg-green",
+            PalettePreset::Pocket => "pocket",
+            PalettePreset::Light => "light",
+            PalettePreset::HighContrast => "high-contrast",
+            PalettePreset::CgbTitle => "cgb",
+        }
+    }
+
+    /// Inverse of [`Self::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    /// The preset after this one in [`Self::ALL`], wrapping around.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&preset| preset == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The colors for `rom`, which only [`PalettePreset::CgbTitle`] looks at.
+    pub fn palette(self, rom: &[u8]) -> Palette {
+        match self {
+            PalettePreset::DmgGreen => Palette::DMG_GREEN,
+            PalettePreset::Pocket => Palette::POCKET,
+            PalettePreset::Light => Palette::LIGHT,
+            PalettePreset::HighContrast => Palette::HIGH_CONTRAST,
+            PalettePreset::CgbTitle => CompatPalette::for_title(rom).into(),
+        }
+    }
+}
+
+// ── CGB boot ROM title palettes ─────────────────────────────────────────────
+
+/// Licensee the boot ROM requires before looking a title up: Nintendo, as
+/// either the old or the new licensee code.
+const NINTENDO_LICENSEE: u8 = 0x01;
+const OLD_LICENSEE_ADDR: usize = 0x014B;
+const NEW_LICENSEE_ADDR: usize = 0x0144;
+/// The 4th title letter separates games whose title checksums collide.
+const DISAMBIGUATION_LETTER_ADDR: usize = 0x0137;
+
+/// The boot ROM's palette data: 30 palettes of 4 RGB555 colors, read as one
+/// flat run of colors by [`PALETTE_COMBINATIONS`].
+const PALETTE_DATA: [u16; 120] = [
+    0x7FFF, 0x32BF, 0x00D0, 0x0000, 0x639F, 0x4279, 0x15B0, 0x04CB,
+    0x7FFF, 0x6E31, 0x454A, 0x0000, 0x7FFF, 0x1BEF, 0x0200, 0x0000,
+    0x7FFF, 0x421F, 0x1CF2, 0x0000, 0x7FFF, 0x5294, 0x294A, 0x0000,
+    0x7FFF, 0x03FF, 0x012F, 0x0000, 0x7FFF, 0x03EF, 0x01D6, 0x0000,
+    0x7FFF, 0x42B5, 0x3DC8, 0x0000, 0x7E74, 0x03FF, 0x0180, 0x0000,
+    0x67FF, 0x77AC, 0x1A13, 0x2D6B, 0x7ED6, 0x4BFF, 0x2175, 0x0000,
+    0x53FF, 0x4A5F, 0x7E52, 0x0000, 0x4FFF, 0x7ED2, 0x3A4C, 0x1CE0,
+    0x03ED, 0x7FFF, 0x255F, 0x0000, 0x036A, 0x021F, 0x03FF, 0x7FFF,
+    0x7FFF, 0x01DF, 0x0112, 0x0000, 0x231F, 0x035F, 0x00F2, 0x0009,
+    0x7FFF, 0x03EA, 0x011F, 0x0000, 0x299F, 0x001A, 0x000C, 0x0000,
+    0x7FFF, 0x027F, 0x001F, 0x0000, 0x7FFF, 0x03E0, 0x0206, 0x0120,
+    0x7FFF, 0x7EEB, 0x001F, 0x7C00, 0x7FFF, 0x3FFF, 0x7E00, 0x001F,
+    0x7FFF, 0x03FF, 0x001F, 0x0000, 0x03FF, 0x001F, 0x000C, 0x0000,
+    0x7FFF, 0x033F, 0x0193, 0x0000, 0x0000, 0x4200, 0x037F, 0x7FFF,
+    0x7FFF, 0x7E8C, 0x7C00, 0x0000, 0x7FFF, 0x1BEF, 0x6180, 0x0000,
+];
+
+/// OBJ0, OBJ1 and BG palettes as offsets into [`PALETTE_DATA`]. A few start
+/// mid-palette, as the boot ROM's own table does. Combination 0 is the
+/// default, [`CompatPalette::DEFAULT`].
+const PALETTE_COMBINATIONS: [[u8; 3]; 51] = [
+    [16, 16, 116], [72, 72, 72], [80, 80, 80], [96, 96, 96], [36, 36, 36],
+    [0, 0, 0], [108, 108, 108], [20, 20, 20], [48, 48, 48], [104, 104, 104],
+    [64, 32, 32], [16, 112, 112], [16, 8, 8], [12, 16, 16], [16, 116, 116],
+    [112, 16, 112], [8, 68, 8], [64, 64, 32], [16, 16, 28], [16, 16, 72],
+    [16, 16, 80], [76, 76, 36], [15, 15, 44], [68, 68, 8], [16, 16, 8],
+    [16, 16, 12], [112, 112, 0], [12, 12, 0], [0, 0, 4], [72, 88, 72],
+    [80, 88, 80], [96, 88, 96], [64, 88, 32], [68, 16, 52], [111, 0, 56],
+    [111, 16, 60], [76, 88, 36], [64, 112, 40], [16, 92, 112], [68, 88, 8],
+    [16, 0, 8], [16, 112, 12], [112, 12, 0], [12, 112, 16], [84, 112, 16],
+    [12, 112, 0], [100, 12, 112], [0, 112, 32], [16, 12, 112], [112, 12, 24],
+    [16, 112, 116],
+];
+
+/// Title checksums the boot ROM recognises without looking further, and the
+/// palette combination each one gets.
+const TITLE_CHECKSUMS: [(u8, u8); 64] = [
+    (0x88, 4), (0x16, 5), (0x36, 35), (0xD1, 34), (0xDB, 3), (0xF2, 31),
+    (0x3C, 15), (0x8C, 10), (0x92, 5), (0x3D, 19), (0x5C, 36), (0x58, 7),
+    (0xC9, 37), (0x3E, 30), (0x70, 44), (0x1D, 21), (0x59, 32), (0x69, 31),
+    (0x19, 20), (0x35, 5), (0xA8, 33), (0x14, 13), (0xAA, 14), (0x75, 5),
+    (0x95, 29), (0x99, 5), (0x34, 18), (0x6F, 9), (0x15, 3), (0xFF, 2),
+    (0x97, 26), (0x4B, 25), (0x90, 25), (0x17, 41), (0x10, 42), (0x39, 26),
+    (0xF7, 45), (0xF6, 42), (0xA2, 45), (0x49, 36), (0x4E, 38), (0x43, 26),
+    (0x68, 42), (0xE0, 30), (0x8B, 41), (0xF0, 34), (0xCE, 34), (0x0C, 5),
+    (0x29, 42), (0xE8, 6), (0xB7, 5), (0x86, 33), (0x9A, 25), (0x52, 42),
+    (0x01, 42), (0x9D, 40), (0x71, 2), (0x9C, 16), (0xBD, 25), (0x5D, 42),
+    (0x6D, 42), (0x67, 5), (0x3F, 0), (0x6B, 39),
+];
+
+/// Title checksums shared by several games, told apart by the 4th title
+/// letter: (checksum, letter, palette combination).
+const AMBIGUOUS_TITLE_CHECKSUMS: [(u8, u8, u8); 29] = [
+    (0xB3, b'B', 36), (0x46, b'E', 22), (0x28, b'F', 25), (0xA5, b'A', 6),
+    (0xC6, b'A', 32), (0xD3, b'R', 12), (0x27, b'B', 36), (0x61, b'E', 11),
+    (0x18, b'K', 39), (0x66, b'E', 18), (0x6A, b'K', 39), (0xBF, b' ', 24),
+    (0x0D, b'R', 31), (0xF4, b'-', 50), (0xB3, b'U', 17), (0x46, b'R', 46),
+    (0x28, b'A', 6), (0xA5, b'R', 27), (0xC6, b' ', 0), (0xD3, b'I', 47),
+    (0x27, b'N', 41), (0x61, b'A', 41), (0x18, b'I', 0), (0x66, b'L', 0),
+    (0x6A, b'I', 19), (0xBF, b'C', 34), (0x0D, b'E', 23), (0xF4, b' ', 18),
+    (0xB3, b'R', 29),
+];
+
+/// The sum of the 16 title bytes at 0x0134–0x0143, which the CGB boot ROM
+/// keys its per-title palettes off.
+///
+/// `rom` must be at least 0x0144 bytes long.
+pub fn title_checksum(rom: &[u8]) -> u8 {
+    rom[0x0134..0x0144].iter().fold(0u8, |sum, &b| sum.wrapping_add(b))
+}
+
+impl CompatPalette {
+    /// The palette the CGB boot ROM picks for a DMG cartridge with no button
+    /// held: a per-title palette for Nintendo-published games,
+    /// [`CompatPalette::DEFAULT`] otherwise.
+    ///
+    /// Only the header is read; a `rom` shorter than 0x0150 bytes gets the
+    /// default.
+    pub fn for_title(rom: &[u8]) -> Self {
+        Self::from_combination(title_combination(rom).unwrap_or(0))
+    }
+
+    fn from_combination(index: u8) -> Self {
+        let [obj0, obj1, bg] = PALETTE_COMBINATIONS[index as usize].map(|offset| {
+            let offset = offset as usize;
+            let mut colors = [0u32; 4];
+            for (color, &rgb555) in colors.iter_mut().zip(&PALETTE_DATA[offset..offset + 4]) {
+                *color = rgb555_to_rgb888_rounded(rgb555);
+            }
+            colors
+        });
+        Self { bg, obj0, obj1 }
+    }
+}
+
+/// The palette combination for a Nintendo title the boot ROM knows.
+fn title_combination(rom: &[u8]) -> Option<u8> {
+    let header = rom.get(..0x0150)?;
+    let nintendo = match header[OLD_LICENSEE_ADDR] {
+        NINTENDO_LICENSEE => true,
+        0x33 => header[NEW_LICENSEE_ADDR..NEW_LICENSEE_ADDR + 2] == *b"01",
+        _ => false,
+    };
+    if !nintendo {
+        return None;
+    }
+    let checksum = title_checksum(header);
+    if let Some(&(_, combination)) = TITLE_CHECKSUMS.iter().find(|&&(c, _)| c == checksum) {
+        return Some(combination);
+    }
+    let letter = header[DISAMBIGUATION_LETTER_ADDR];
+    AMBIGUOUS_TITLE_CHECKSUMS
+        .iter()
+        .find(|&&(c, l, _)| c == checksum && l == letter)
+        .map(|&(_, _, combination)| combination)
+}
+
+// ── Color conversion ─────────────────────────────────────────────────────────
+
+/// How CGB RGB555 colors are turned into RGB888.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorCorrection {
+    /// Scale each channel linearly. Saturated and bright, as on a modern
+    /// display.
+    #[default]
+    None,
+    /// Mix the channels and darken the way the CGB's LCD does, so colors
+    /// chosen for that screen look as intended.
+    Lcd,
+}
+
+impl ColorCorrection {
+    /// Convert one RGB555 color (red in the low bits) to 0xRRGGBB.
+    pub fn rgb555_to_rgb888(self, color: u16) -> u32 {
+        let r = (color & 0x1F) as u32;
+        let g = ((color >> 5) & 0x1F) as u32;
+        let b = ((color >> 10) & 0x1F) as u32;
+        match self {
+            ColorCorrection::None => {
+                let expand = |c: u32| (c << 3) | (c >> 2);
+                expand(r) << 16 | expand(g) << 8 | expand(b)
+            }
+            ColorCorrection::Lcd => {
+                // Each output is at most 31 * 16 / 2 = 248.
+                let red = (r * 13 + g * 2 + b) >> 1;
+                let green = (g * 3 + b) << 1;
+                let blue = (r * 3 + g * 2 + b * 11) >> 1;
+                red << 16 | green << 8 | blue
+            }
+        }
+    }
+
+    /// Convert a CGB frame into RGBA8888 bytes. `out` holds at least
+    /// `4 * FRAMEBUFFER_SIZE` bytes.
+    pub fn write_rgba8888(self, frame: &[u16; FRAMEBUFFER_SIZE], out: &mut [u8]) {
+        for (&color, pixel) in frame.iter().zip(out.chunks_exact_mut(4)) {
+            pixel.copy_from_slice(&rgb888_to_rgba8888(self.rgb555_to_rgb888(color)));
+        }
+    }
+}
+
+/// RGB555 to 0xRRGGBB rounded to nearest, matching the values Pan Docs lists
+/// for the compatibility palettes.
+fn rgb555_to_rgb888_rounded(color: u16) -> u32 {
+    let channel = |shift: u16| (((color >> shift) & 0x1F) as u32 * 255 + 15) / 31;
+    channel(0) << 16 | channel(5) << 8 | channel(10)
+}
+
+/// 0xRRGGBB to opaque RGBA8888 bytes.
+pub fn rgb888_to_rgba8888(rgb: u32) -> [u8; 4] {
+    [(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 0xFF]
+}
+
+/// 0xRRGGBB to RGB565, truncating the low bits of each channel.
+pub fn rgb888_to_rgb565(rgb: u32) -> u16 {
+    let r = (rgb >> 19) & 0x1F;
+    let g = (rgb >> 10) & 0x3F;
+    let b = (rgb >> 3) & 0x1F;
+    (r << 11 | g << 5 | b) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A header for `title` with the given old licensee code.
+    fn header(title: &[u8], old_licensee: u8) -> [u8; 0x150] {
+        let mut rom = [0u8; 0x150];
+        rom[0x0134..0x0134 + title.len()].copy_from_slice(title);
+        rom[OLD_LICENSEE_ADDR] = old_licensee;
+        rom
+    }
+
+    #[test]
+    fn test_layer_plane_round_trip() {
+        let mut plane = [0u8; LAYER_PLANE_SIZE];
+        set_pixel_layer(&mut plane, 5, Layer::Obj1);
+        set_pixel_layer(&mut plane, 6, Layer::Obj0);
+        assert_eq!(pixel_layer(&plane, 4), Layer::Background);
+        assert_eq!(pixel_layer(&plane, 5), Layer::Obj1);
+        assert_eq!(pixel_layer(&plane, 6), Layer::Obj0);
+        set_pixel_layer(&mut plane, 5, Layer::Background);
+        assert_eq!(pixel_layer(&plane, 5), Layer::Background);
+        assert_eq!(pixel_layer(&plane, 6), Layer::Obj0);
+    }
+
+    #[test]
+    fn test_write_rgba8888_colors_each_layer() {
+        let palette = Palette {
+            bg: [0x000001, 0x000002, 0x000003, 0x000004],
+            obj0: [0x000100, 0x000200, 0x000300, 0x000400],
+            obj1: [0x010000, 0x020000, 0x030000, 0x040000],
+        };
+        let mut shades = [0u8; FRAMEBUFFER_SIZE];
+        shades[..3].copy_from_slice(&[1, 2, 3]);
+        let mut layers = [0u8; LAYER_PLANE_SIZE];
+        set_pixel_layer(&mut layers, 1, Layer::Obj0);
+        set_pixel_layer(&mut layers, 2, Layer::Obj1);
+        let mut out = [0u8; FRAMEBUFFER_SIZE * 4];
+        palette.write_rgba8888(&shades, &layers, &mut out);
+        assert_eq!(out[..12], [0, 0, 2, 0xFF, 0, 3, 0, 0xFF, 4, 0, 0, 0xFF]);
+    }
+
+    #[test]
+    fn test_rgb565_table() {
+        let table = Palette::HIGH_CONTRAST.rgb565_table();
+        assert_eq!(table[Layer::Background as usize][0], 0xFFFF);
+        assert_eq!(table[Layer::Obj1 as usize][3], 0x0000);
+        assert_eq!(rgb888_to_rgb565(0xE0F8D0), 0xE7DA);
+    }
+
+    #[test]
+    fn test_preset_names_round_trip() {
+        for preset in PalettePreset::ALL {
+            assert_eq!(PalettePreset::from_name(preset.name()), Some(preset));
+        }
+        assert_eq!(PalettePreset::from_name("sepia"), None);
+        assert_eq!(PalettePreset::CgbTitle.next(), PalettePreset::DmgGreen);
+    }
+
+    #[test]
+    fn test_default_combination_matches_compat_default() {
+        assert_eq!(CompatPalette::from_combination(0), CompatPalette::DEFAULT);
+        assert_eq!(CompatPalette::from_combination(3), CompatPalette::DOWN_A);
+        assert_eq!(CompatPalette::from_combination(48), CompatPalette::LEFT);
+        assert_eq!(CompatPalette::from_combination(49), CompatPalette::DOWN_B);
+    }
+
+    #[test]
+    fn test_title_lookup_needs_nintendo_licensee() {
+        // "TETRIS" sums to 0xDB.
+        assert_eq!(title_checksum(&header(b"TETRIS", 0x01)), 0xDB);
+        assert_eq!(CompatPalette::for_title(&header(b"TETRIS", 0x01)), CompatPalette::from_combination(3));
+        assert_eq!(CompatPalette::for_title(&header(b"TETRIS", 0x00)), CompatPalette::DEFAULT);
+
+        let mut new_licensee = header(b"TETRIS", 0x33);
+        new_licensee[NEW_LICENSEE_ADDR..NEW_LICENSEE_ADDR + 2].copy_from_slice(b"01");
+        assert_eq!(CompatPalette::for_title(&new_licensee), CompatPalette::from_combination(3));
+        assert_eq!(CompatPalette::for_title(&[0u8; 0x100]), CompatPalette::DEFAULT);
+    }
+
+    #[test]
+    fn test_title_lookup_uses_fourth_letter_for_collisions() {
+        // "KID ICARUS" and "SOCCER" both sum to 0xBF.
+        let kid_icarus = header(b"KID ICARUS", 0x01);
+        let soccer = header(b"SOCCER", 0x01);
+        assert_eq!(title_checksum(&kid_icarus), 0xBF);
+        assert_eq!(title_checksum(&soccer), 0xBF);
+        assert_eq!(CompatPalette::for_title(&kid_icarus), CompatPalette::from_combination(24));
+        assert_eq!(CompatPalette::for_title(&soccer), CompatPalette::from_combination(34));
+
+        let mut unknown = soccer;
+        unknown[DISAMBIGUATION_LETTER_ADDR] = b'Z';
+        unknown[0x0143] = 0u8.wrapping_sub(b'Z' - b'C');
+        assert_eq!(title_checksum(&unknown), 0xBF);
+        assert_eq!(CompatPalette::for_title(&unknown), CompatPalette::DEFAULT);
+    }
+
+    #[test]
+    fn test_color_correction() {
+        assert_eq!(ColorCorrection::None.rgb555_to_rgb888(0x7FFF), 0xFFFFFF);
+        assert_eq!(ColorCorrection::None.rgb555_to_rgb888(0x001F), 0xFF0000);
+        assert_eq!(ColorCorrection::Lcd.rgb555_to_rgb888(0x0000), 0x000000);
+        // White comes out slightly dim; pure red bleeds into blue.
+        assert_eq!(ColorCorrection::Lcd.rgb555_to_rgb888(0x7FFF), 0xF8F8F8);
+        assert_eq!(ColorCorrection::Lcd.rgb555_to_rgb888(0x001F), 0xC9002E);
+    }
+}
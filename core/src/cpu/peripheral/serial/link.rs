@@ -0,0 +1,234 @@
+//! Link cable transports.
+//!
+//! A transfer is driven by whichever Game Boy selected the internal clock.
+//! When its eight bits have shifted out it calls [`LinkCable::transfer`],
+//! which hands its byte to the other end and returns what that end had
+//! waiting in SB. The other Game Boy arms an external-clock transfer and polls
+//! [`LinkCable::receive`] every M-cycle until a byte has been clocked in.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use super::DISCONNECTED;
+
+/// One end of a link cable.
+pub trait LinkCable {
+    /// Clock side: send `byte` and return the byte shifted in from the other
+    /// Game Boy, or 0xFF if it isn't waiting on a transfer.
+    fn transfer(&mut self, byte: u8) -> u8;
+
+    /// External-clock side: offer `byte` to the other Game Boy and return the
+    /// byte it clocked in, once it has.
+    fn receive(&mut self, byte: u8) -> Option<u8>;
+
+    /// External-clock side: the transfer offered through `receive` was
+    /// abandoned before the other end clocked it.
+    fn cancel(&mut self) {}
+}
+
+/// Lets the host keep a handle on a cable it has plugged into an `Sm83`,
+/// e.g. to pump a [`RemoteLink`] from a WebSocket.
+impl<C: LinkCable + ?Sized> LinkCable for Rc<RefCell<C>> {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.borrow_mut().transfer(byte)
+    }
+
+    fn receive(&mut self, byte: u8) -> Option<u8> {
+        self.borrow_mut().receive(byte)
+    }
+
+    fn cancel(&mut self) {
+        self.borrow_mut().cancel()
+    }
+}
+
+#[derive(Default)]
+struct Wire {
+    /// Byte each end has armed for an external-clock transfer.
+    offered: [Option<u8>; 2],
+    /// Byte clocked in to each end, not yet picked up by its `receive`.
+    delivered: [Option<u8>; 2],
+}
+
+/// A cable between two `Sm83` instances in the same process.
+///
+/// The exchange happens the moment the clock side's transfer completes, so
+/// as long as both instances are stepped together (see `Sm83::run_linked`)
+/// the external-clock side sees it within one instruction.
+pub struct InProcessCable {
+    wire: Rc<RefCell<Wire>>,
+    end: usize,
+}
+
+impl InProcessCable {
+    /// Both ends of a new cable.
+    pub fn pair() -> (Self, Self) {
+        let wire = Rc::new(RefCell::new(Wire::default()));
+        (InProcessCable { wire: wire.clone(), end: 0 }, InProcessCable { wire, end: 1 })
+    }
+}
+
+impl LinkCable for InProcessCable {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        let mut wire = self.wire.borrow_mut();
+        let peer = 1 - self.end;
+        match wire.offered[peer].take() {
+            Some(reply) => {
+                wire.delivered[peer] = Some(byte);
+                reply
+            }
+            None => DISCONNECTED,
+        }
+    }
+
+    fn receive(&mut self, byte: u8) -> Option<u8> {
+        let mut wire = self.wire.borrow_mut();
+        if let Some(received) = wire.delivered[self.end].take() {
+            return Some(received);
+        }
+        wire.offered[self.end] = Some(byte);
+        None
+    }
+
+    fn cancel(&mut self) {
+        self.wire.borrow_mut().offered[self.end] = None;
+    }
+}
+
+/// Message tags of the two-byte `[tag, value]` protocol spoken by [`RemoteLink`].
+const MSG_OFFER: u8 = 0x01;
+const MSG_CLOCK: u8 = 0x02;
+const MSG_CANCEL: u8 = 0x03;
+
+/// Cable whose other end is reached over a byte stream (a socket, a WebSocket
+/// relay). The host moves bytes with [`take_outgoing`](Self::take_outgoing)
+/// and [`push_incoming`](Self::push_incoming).
+///
+/// The external-clock side announces its byte as soon as it arms, and the
+/// clock side answers with its own when its transfer completes. A clock side
+/// that hasn't heard an offer yet shifts in 0xFF, as if the other Game Boy
+/// weren't listening, so the link tolerates latency but isn't cycle-exact.
+#[derive(Default)]
+pub struct RemoteLink {
+    outgoing: Vec<u8>,
+    /// First byte of a message split across `push_incoming` calls.
+    partial: Option<u8>,
+    peer_offer: Option<u8>,
+    clocked: Option<u8>,
+    offered: bool,
+}
+
+impl RemoteLink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes to send to the other end, oldest first.
+    pub fn take_outgoing(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.outgoing)
+    }
+
+    /// Feed bytes received from the other end. A message may be split across
+    /// calls.
+    pub fn push_incoming(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match self.partial.take() {
+                None => self.partial = Some(byte),
+                Some(MSG_OFFER) => self.peer_offer = Some(byte),
+                Some(MSG_CLOCK) => self.clocked = Some(byte),
+                Some(MSG_CANCEL) => self.peer_offer = None,
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn send(&mut self, tag: u8, value: u8) {
+        self.outgoing.extend_from_slice(&[tag, value]);
+    }
+}
+
+impl LinkCable for RemoteLink {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        match self.peer_offer.take() {
+            Some(reply) => {
+                self.send(MSG_CLOCK, byte);
+                reply
+            }
+            None => DISCONNECTED,
+        }
+    }
+
+    fn receive(&mut self, byte: u8) -> Option<u8> {
+        if let Some(received) = self.clocked.take() {
+            self.offered = false;
+            return Some(received);
+        }
+        if !self.offered {
+            self.offered = true;
+            self.send(MSG_OFFER, byte);
+        }
+        None
+    }
+
+    fn cancel(&mut self) {
+        if self.offered {
+            self.offered = false;
+            self.send(MSG_CANCEL, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_process_transfer_without_offer_reads_disconnected() {
+        let (mut a, _b) = InProcessCable::pair();
+        assert_eq!(a.transfer(0x42), 0xFF);
+    }
+
+    #[test]
+    fn test_in_process_exchange_swaps_bytes() {
+        let (mut clock, mut external) = InProcessCable::pair();
+        assert_eq!(external.receive(0x99), None);
+        assert_eq!(clock.transfer(0x42), 0x99);
+        assert_eq!(external.receive(0x99), Some(0x42));
+        // The offer was consumed: the next transfer finds nobody waiting.
+        assert_eq!(clock.transfer(0x43), 0xFF);
+    }
+
+    #[test]
+    fn test_in_process_cancel_withdraws_offer() {
+        let (mut clock, mut external) = InProcessCable::pair();
+        external.receive(0x99);
+        external.cancel();
+        assert_eq!(clock.transfer(0x42), 0xFF);
+    }
+
+    #[test]
+    fn test_remote_links_exchange_over_byte_stream() {
+        let (mut clock, mut external) = (RemoteLink::new(), RemoteLink::new());
+        assert_eq!(external.receive(0x99), None);
+        // Offer is sent once, not on every poll.
+        assert_eq!(external.receive(0x99), None);
+        let offer = external.take_outgoing();
+        assert_eq!(offer.len(), 2);
+
+        // Deliver it one byte at a time to exercise split messages.
+        clock.push_incoming(&offer[..1]);
+        clock.push_incoming(&offer[1..]);
+        assert_eq!(clock.transfer(0x42), 0x99);
+
+        external.push_incoming(&clock.take_outgoing());
+        assert_eq!(external.receive(0x99), Some(0x42));
+    }
+
+    #[test]
+    fn test_remote_transfer_before_offer_arrives_reads_disconnected() {
+        let mut clock = RemoteLink::new();
+        assert_eq!(clock.transfer(0x42), 0xFF);
+        assert!(clock.take_outgoing().is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+mod link;
+mod printer;
+#[cfg(feature = "std")]
+mod tcp;
+
+pub use link::{InProcessCable, LinkCable, RemoteLink};
+pub use printer::{GameBoyPrinter, PrintedPage, PRINTER_WIDTH};
+#[cfg(feature = "std")]
+pub use tcp::TcpLinkCable;
+
+const SC_TRANSFER_BIT: u8 = 0x80;
+const SC_INTERNAL_CLOCK_BIT: u8 = 0x01;
+
+/// T-cycles for one serial transfer using the internal clock (8 bits × 64 T-cycles/bit).
+const SERIAL_TRANSFER_CYCLES: u16 = 512;
+
+/// Byte shifted in when nothing drives the other end of the cable.
+const DISCONNECTED: u8 = 0xFF;
+
+pub(crate) const SERIAL_INTERRUPT_BIT: u8 = 3;
+
+/// Serial port peripheral. Captures bytes transferred via the Game Boy serial link.
+///
+/// When the ROM writes to SC (0xFF02) with bit 7 (transfer start) and bit 0
+/// (internal clock) set, a transfer begins. After 512 T-cycles the transfer
+/// completes: SB is set to the byte received over the [`LinkCable`] (0xFF
+/// with none connected), SC bit 7 is cleared, and the serial interrupt
+/// (IF bit 3) is fired.
+///
+/// External-clock transfers (bit 0 = 0) wait for the other Game Boy to drive
+/// the clock. Without a cable they never complete, just like on hardware with
+/// nothing plugged in.
+pub struct SerialPort {
+    output: Vec<u8>,
+    /// Remaining T-cycles until the in-progress internal-clock transfer completes.
+    /// `None` means no transfer is in progress.
+    cycles_remaining: Option<u16>,
+    /// SB as it was when the current transfer started.
+    outgoing: u8,
+    /// An external-clock transfer is armed and waiting on the cable.
+    external: bool,
+    cable: Option<Box<dyn LinkCable>>,
+}
+
+/// Result of a serial tick.
+pub struct SerialOutput {
+    /// Whether the transfer completed this tick and the interrupt should fire.
+    pub interrupt: bool,
+    /// New value for SB (the received byte on transfer complete, unchanged otherwise).
+    pub sb: Option<u8>,
+    /// New value for SC (transfer bit cleared on complete, unchanged otherwise).
+    pub sc: Option<u8>,
+}
+
+impl SerialPort {
+    pub fn new() -> Self {
+        Self { output: Vec::new(), cycles_remaining: None, outgoing: 0, external: false, cable: None }
+    }
+
+    /// Plug in (or with `None`, unplug) the link cable, returning the old one.
+    /// `sc` and `sb` are the current register values, so a transfer already
+    /// waiting on the external clock picks up the new cable.
+    pub fn set_cable(&mut self, cable: Option<Box<dyn LinkCable>>, sc: u8, sb: u8) -> Option<Box<dyn LinkCable>> {
+        let old = core::mem::replace(&mut self.cable, cable);
+        self.rearm(sc, sb);
+        old
+    }
+
+    /// Re-derive the armed external-clock transfer from SC after the registers
+    /// were replaced wholesale (loading a save state, swapping cables).
+    pub fn rearm(&mut self, sc: u8, sb: u8) {
+        self.external = self.cable.is_some()
+            && sc & SC_TRANSFER_BIT != 0
+            && sc & SC_INTERNAL_CLOCK_BIT == 0
+            && self.cycles_remaining.is_none();
+        self.outgoing = sb;
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Extract serial state into a [`SerialState`] for serialization.
+    pub fn to_save_state(&self) -> crate::cpu::save_state::SerialState {
+        crate::cpu::save_state::SerialState { cycles_remaining: self.cycles_remaining }
+    }
+
+    /// Apply serial state from a parsed [`SerialState`]. Captured output is
+    /// host-side and is left untouched.
+    pub fn load_state(&mut self, state: crate::cpu::save_state::SerialState) {
+        self.cycles_remaining = state.cycles_remaining;
+    }
+
+    #[inline(always)]
+    pub fn is_idle(&self) -> bool {
+        self.cycles_remaining.is_none() && !self.external
+    }
+
+    /// Called when SC (0xFF02) is written. Captures `sb` and starts a timed
+    /// transfer if the internal clock bit is set; with the external clock the
+    /// transfer waits on the link cable, if there is one.
+    pub fn handle_sc_write(&mut self, sc_value: u8, sb: u8) {
+        if sc_value & SC_TRANSFER_BIT == 0 {
+            if self.external {
+                self.external = false;
+                if let Some(cable) = self.cable.as_deref_mut() {
+                    cable.cancel();
+                }
+            }
+            return;
+        }
+        self.output.push(sb);
+        if sc_value & SC_INTERNAL_CLOCK_BIT != 0 {
+            if self.cycles_remaining.is_none() {
+                // Internal clock: time the transfer over 512 T-cycles.
+                // Only start if no transfer is already in progress — games that
+                // poll by re-writing SC=0x81 must not reset the countdown.
+                self.cycles_remaining = Some(SERIAL_TRANSFER_CYCLES);
+                self.outgoing = sb;
+                if self.external {
+                    self.external = false;
+                    if let Some(cable) = self.cable.as_deref_mut() {
+                        cable.cancel();
+                    }
+                }
+            }
+        } else if self.cable.is_some() && self.cycles_remaining.is_none() {
+            // External clock: offer SB on the cable from the next tick on.
+            // Without a cable the transfer is left pending forever.
+            self.external = true;
+            self.outgoing = sb;
+        }
+    }
+
+    /// Advance the serial port by `cycles` T-cycles.
+    ///
+    /// Returns a `SerialOutput` describing any state changes from a completed transfer.
+    pub fn tick(&mut self, cycles: u16) -> SerialOutput {
+        if self.external {
+            let received = self.cable.as_deref_mut().and_then(|cable| cable.receive(self.outgoing));
+            return match received {
+                Some(byte) => {
+                    self.external = false;
+                    Self::complete(byte)
+                }
+                None => SerialOutput { interrupt: false, sb: None, sc: None },
+            };
+        }
+
+        let remaining = match self.cycles_remaining {
+            Some(r) => r,
+            None => return SerialOutput { interrupt: false, sb: None, sc: None },
+        };
+
+        if remaining > cycles {
+            self.cycles_remaining = Some(remaining - cycles);
+            SerialOutput { interrupt: false, sb: None, sc: None }
+        } else {
+            self.cycles_remaining = None;
+            let received = match self.cable.as_deref_mut() {
+                Some(cable) => cable.transfer(self.outgoing),
+                None => DISCONNECTED,
+            };
+            Self::complete(received)
+        }
+    }
+
+    fn complete(received: u8) -> SerialOutput {
+        SerialOutput {
+            interrupt: true,
+            sb: Some(received),
+            sc: Some(0x00),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serial_transfer_captures_sb_byte() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x81, b'H'); // internal clock transfer
+        assert_eq!(port.output(), b"H");
+    }
+
+    #[test]
+    fn test_serial_transfer_without_start_bit_does_not_capture() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x01, b'X'); // bit 7 NOT set
+        assert_eq!(port.output(), b"");
+    }
+
+    #[test]
+    fn test_serial_captures_multiple_bytes_in_order() {
+        let mut port = SerialPort::new();
+        // Internal-clock transfer: start, tick to complete, then start next
+        port.handle_sc_write(0x81, b'H');
+        port.tick(512); // complete first transfer
+        port.handle_sc_write(0x81, b'i');
+        assert_eq!(port.output(), b"Hi");
+    }
+
+    #[test]
+    fn test_serial_output_starts_empty() {
+        let port = SerialPort::new();
+        assert_eq!(port.output(), b"");
+    }
+
+    #[test]
+    fn test_write_to_sb_alone_does_not_capture() {
+        // Only an SC write triggers capture; an SB write alone does nothing.
+        let port = SerialPort::new();
+        assert_eq!(port.output(), b"");
+    }
+
+    // ── Internal-clock transfer timing ────────────────────────────────────────
+
+    #[test]
+    fn internal_clock_no_interrupt_before_512_cycles() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x81, b'A'); // internal clock
+        let out = port.tick(511);
+        assert!(!out.interrupt);
+    }
+
+    #[test]
+    fn internal_clock_interrupt_fires_at_512_cycles() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x81, b'A'); // internal clock
+        let out = port.tick(512);
+        assert!(out.interrupt);
+        assert_eq!(out.sb, Some(0xFF));
+        assert_eq!(out.sc, Some(0x00));
+    }
+
+    #[test]
+    fn internal_clock_interrupt_fires_across_multiple_ticks() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x81, b'A');
+        let out1 = port.tick(256);
+        assert!(!out1.interrupt);
+        let out2 = port.tick(256);
+        assert!(out2.interrupt);
+    }
+
+    #[test]
+    fn external_clock_transfer_never_fires_interrupt() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x80, b'A'); // external clock (no internal clock bit)
+        let out = port.tick(512);
+        assert!(!out.interrupt);
+    }
+
+    #[test]
+    fn no_transfer_tick_returns_no_interrupt() {
+        let mut port = SerialPort::new();
+        let out = port.tick(512);
+        assert!(!out.interrupt);
+        assert!(out.sb.is_none());
+        assert!(out.sc.is_none());
+    }
+
+    // ── Link cable ────────────────────────────────────────────────────────────
+
+    fn linked_ports() -> (SerialPort, SerialPort) {
+        let (a, b) = InProcessCable::pair();
+        let (mut clock, mut external) = (SerialPort::new(), SerialPort::new());
+        clock.set_cable(Some(Box::new(a)), 0x00, 0x00);
+        external.set_cable(Some(Box::new(b)), 0x00, 0x00);
+        (clock, external)
+    }
+
+    #[test]
+    fn external_clock_with_cable_is_not_idle() {
+        let (_, mut external) = linked_ports();
+        external.handle_sc_write(0x80, 0x99);
+        assert!(!external.is_idle());
+    }
+
+    #[test]
+    fn linked_transfer_swaps_sb_and_interrupts_both_sides() {
+        let (mut clock, mut external) = linked_ports();
+        external.handle_sc_write(0x80, 0x99);
+        assert!(!external.tick(4).interrupt);
+        clock.handle_sc_write(0x81, 0x42);
+
+        let out = clock.tick(512);
+        assert!(out.interrupt);
+        assert_eq!(out.sb, Some(0x99));
+
+        let out = external.tick(4);
+        assert!(out.interrupt);
+        assert_eq!(out.sb, Some(0x42));
+        assert!(external.is_idle());
+    }
+
+    #[test]
+    fn clearing_sc_start_bit_cancels_external_transfer() {
+        let (mut clock, mut external) = linked_ports();
+        external.handle_sc_write(0x80, 0x99);
+        external.tick(4);
+        external.handle_sc_write(0x00, 0x99);
+        assert!(external.is_idle());
+
+        clock.handle_sc_write(0x81, 0x42);
+        assert_eq!(clock.tick(512).sb, Some(0xFF));
+    }
+
+    #[test]
+    fn rearm_picks_up_transfer_armed_before_cable_was_plugged_in() {
+        let mut port = SerialPort::new();
+        port.handle_sc_write(0x80, 0x99);
+        assert!(port.is_idle());
+        let (a, _b) = InProcessCable::pair();
+        port.set_cable(Some(Box::new(a)), 0x80, 0x99);
+        assert!(!port.is_idle());
+    }
+}
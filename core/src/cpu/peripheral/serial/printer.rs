@@ -0,0 +1,450 @@
+//! Game Boy Printer, plugged into the serial port as a [`LinkCable`].
+//!
+//! The Game Boy drives the clock and sends packets of the form
+//!
+//! ```text
+//! 0x88 0x33 | command | compression | length (LE u16) | data | checksum (LE u16) | 0x00 0x00
+//! ```
+//!
+//! where the checksum is the 16-bit sum of the command through the data. The
+//! printer shifts out 0x00 for every byte until the two trailing ones, where
+//! it answers 0x81 ("alive") and then its status byte.
+//!
+//! Image data arrives as rows of 20 2bpp tiles (160 pixels wide), usually two
+//! tile rows per data packet. A print command renders everything received
+//! since the last print onto the paper, which frontends collect as
+//! [`PrintedPage`]s.
+
+use alloc::vec::Vec;
+
+use super::LinkCable;
+
+/// Width of the printer paper in pixels.
+pub const PRINTER_WIDTH: usize = 160;
+
+const TILES_PER_ROW: usize = PRINTER_WIDTH / 8;
+const BYTES_PER_TILE: usize = 16;
+const BYTES_PER_TILE_ROW: usize = TILES_PER_ROW * BYTES_PER_TILE;
+/// Image data the printer accepts before a print command: 0x1680 bytes of
+/// its 8 KiB RAM, 18 tile rows or a whole 160×144 screen.
+const IMAGE_BUFFER_SIZE: usize = BYTES_PER_TILE_ROW * 18;
+
+const MAGIC_1: u8 = 0x88;
+const MAGIC_2: u8 = 0x33;
+const ALIVE: u8 = 0x81;
+
+const CMD_INIT: u8 = 0x01;
+const CMD_PRINT: u8 = 0x02;
+const CMD_DATA: u8 = 0x04;
+const CMD_STATUS: u8 = 0x0F;
+
+const STATUS_CHECKSUM_ERROR: u8 = 0x01;
+const STATUS_PRINTING: u8 = 0x02;
+const STATUS_IMAGE_FULL: u8 = 0x04;
+const STATUS_UNPROCESSED: u8 = 0x08;
+
+/// Status polls the printer reports itself busy for after a print command.
+/// Games wait for the busy bit to clear before sending the next image.
+const BUSY_STATUS_POLLS: u8 = 4;
+
+/// Palette printed with when the print command leaves it at 0.
+const DEFAULT_PALETTE: u8 = 0xE4;
+
+/// Where the printer is within a packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PacketState {
+    Magic1,
+    Magic2,
+    Command,
+    Compression,
+    LengthLo,
+    LengthHi,
+    Data,
+    ChecksumLo,
+    ChecksumHi,
+    Alive,
+    Status,
+}
+
+/// A printed strip of paper.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PrintedPage {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major shades, one byte per pixel: 0 is white, 3 is black.
+    pub pixels: Vec<u8>,
+}
+
+/// Emulated Game Boy Printer. Plug it in with `Sm83::set_link_cable`, keeping
+/// an `Rc<RefCell<GameBoyPrinter>>` handle to collect the pages.
+pub struct GameBoyPrinter {
+    state: PacketState,
+    command: u8,
+    compressed: bool,
+    length: u16,
+    /// Packet payload as sent, before decompression.
+    data: Vec<u8>,
+    checksum: u16,
+    received_checksum: u16,
+    status: u8,
+    busy_polls: u8,
+    /// Tile data received since the last print.
+    image: Vec<u8>,
+    pages: Vec<PrintedPage>,
+    /// The last page had no feed after it, so the next print continues it.
+    page_open: bool,
+}
+
+impl Default for GameBoyPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameBoyPrinter {
+    pub fn new() -> Self {
+        GameBoyPrinter {
+            state: PacketState::Magic1,
+            command: 0,
+            compressed: false,
+            length: 0,
+            data: Vec::new(),
+            checksum: 0,
+            received_checksum: 0,
+            status: 0,
+            busy_polls: 0,
+            image: Vec::new(),
+            pages: Vec::new(),
+            page_open: false,
+        }
+    }
+
+    /// Pages printed so far, oldest first.
+    pub fn pages(&self) -> &[PrintedPage] {
+        &self.pages
+    }
+
+    /// Remove and return the oldest page the printer has fed out, if any. The
+    /// last page isn't finished while the next print could still continue it.
+    pub fn take_finished_page(&mut self) -> Option<PrintedPage> {
+        let finished = self.pages.len() - usize::from(self.page_open);
+        (finished > 0).then(|| self.pages.remove(0))
+    }
+
+    /// Remove and return the printed pages. A page still open for more
+    /// strips is returned too; the next print starts a new one.
+    pub fn take_pages(&mut self) -> Vec<PrintedPage> {
+        self.page_open = false;
+        core::mem::take(&mut self.pages)
+    }
+
+    fn add_to_checksum(&mut self, byte: u8) {
+        self.checksum = self.checksum.wrapping_add(byte as u16);
+    }
+
+    /// Advance the packet state machine past `byte`.
+    fn receive_byte(&mut self, byte: u8) {
+        self.state = match self.state {
+            PacketState::Magic1 if byte == MAGIC_1 => PacketState::Magic2,
+            PacketState::Magic1 => PacketState::Magic1,
+            PacketState::Magic2 if byte == MAGIC_2 => PacketState::Command,
+            PacketState::Magic2 if byte == MAGIC_1 => PacketState::Magic2,
+            PacketState::Magic2 => PacketState::Magic1,
+            PacketState::Command => {
+                self.command = byte;
+                self.checksum = 0;
+                self.data.clear();
+                self.add_to_checksum(byte);
+                PacketState::Compression
+            }
+            PacketState::Compression => {
+                self.compressed = byte & 0x01 != 0;
+                self.add_to_checksum(byte);
+                PacketState::LengthLo
+            }
+            PacketState::LengthLo => {
+                self.length = byte as u16;
+                self.add_to_checksum(byte);
+                PacketState::LengthHi
+            }
+            PacketState::LengthHi => {
+                self.length |= (byte as u16) << 8;
+                self.add_to_checksum(byte);
+                if self.length == 0 { PacketState::ChecksumLo } else { PacketState::Data }
+            }
+            PacketState::Data => {
+                self.data.push(byte);
+                self.add_to_checksum(byte);
+                if self.data.len() == self.length as usize { PacketState::ChecksumLo } else { PacketState::Data }
+            }
+            PacketState::ChecksumLo => {
+                self.received_checksum = byte as u16;
+                PacketState::ChecksumHi
+            }
+            PacketState::ChecksumHi => {
+                self.received_checksum |= (byte as u16) << 8;
+                if self.received_checksum == self.checksum {
+                    self.status &= !STATUS_CHECKSUM_ERROR;
+                    self.execute();
+                } else {
+                    self.status |= STATUS_CHECKSUM_ERROR;
+                }
+                PacketState::Alive
+            }
+            PacketState::Alive => PacketState::Status,
+            PacketState::Status => PacketState::Magic1,
+        };
+    }
+
+    fn execute(&mut self) {
+        match self.command {
+            CMD_INIT => {
+                self.image.clear();
+                self.status = 0;
+                self.busy_polls = 0;
+            }
+            CMD_DATA if !self.data.is_empty() => {
+                let data = core::mem::take(&mut self.data);
+                if self.compressed {
+                    decompress_into(&data, &mut self.image);
+                } else {
+                    self.image.extend_from_slice(&data);
+                }
+                self.image.truncate(IMAGE_BUFFER_SIZE);
+                self.status |= STATUS_UNPROCESSED;
+            }
+            // An empty data packet marks the end of the image.
+            CMD_DATA => {}
+            CMD_PRINT if self.data.len() >= 4 => {
+                let (sheets, margins, palette) = (self.data[0], self.data[1], self.data[2]);
+                if sheets > 0 {
+                    self.print(margins >> 4, margins & 0x0F, palette);
+                }
+                self.image.clear();
+                self.status = (self.status & !STATUS_UNPROCESSED) | STATUS_PRINTING | STATUS_IMAGE_FULL;
+                self.busy_polls = BUSY_STATUS_POLLS;
+            }
+            CMD_STATUS if self.busy_polls > 0 => {
+                self.busy_polls -= 1;
+                if self.busy_polls == 0 {
+                    self.status &= !(STATUS_PRINTING | STATUS_IMAGE_FULL);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Render the image buffer onto the paper. A zero top margin continues
+    /// the previous page when it was left without a feed.
+    fn print(&mut self, margin_before: u8, margin_after: u8, palette: u8) {
+        let palette = if palette == 0 { DEFAULT_PALETTE } else { palette };
+        let pixels = render_tiles(&self.image, palette);
+        let continues = self.page_open && margin_before == 0;
+        match self.pages.last_mut() {
+            Some(page) if continues => {
+                page.height += pixels.len() / PRINTER_WIDTH;
+                page.pixels.extend_from_slice(&pixels);
+            }
+            _ => self.pages.push(PrintedPage { width: PRINTER_WIDTH, height: pixels.len() / PRINTER_WIDTH, pixels }),
+        }
+        self.page_open = margin_after == 0;
+    }
+}
+
+impl LinkCable for GameBoyPrinter {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        // The reply is loaded into the printer's shift register before the
+        // byte it's exchanged with has arrived.
+        let reply = match self.state {
+            PacketState::Alive => ALIVE,
+            PacketState::Status => self.status,
+            _ => 0x00,
+        };
+        self.receive_byte(byte);
+        reply
+    }
+
+    /// The printer never drives the clock.
+    fn receive(&mut self, _byte: u8) -> Option<u8> {
+        None
+    }
+}
+
+/// Expand the printer's run-length encoding: a control byte with bit 7 set is
+/// followed by one byte repeated `(control & 0x7F) + 2` times, otherwise by
+/// `control + 1` literal bytes.
+fn decompress_into(data: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < data.len() {
+        let control = data[i];
+        i += 1;
+        if control & 0x80 != 0 {
+            let Some(&byte) = data.get(i) else { break };
+            out.extend(core::iter::repeat_n(byte, (control & 0x7F) as usize + 2));
+            i += 1;
+        } else {
+            let end = (i + control as usize + 1).min(data.len());
+            out.extend_from_slice(&data[i..end]);
+            i = end;
+        }
+    }
+}
+
+/// Decode whole tile rows of 2bpp tile data into shades through `palette`.
+fn render_tiles(image: &[u8], palette: u8) -> Vec<u8> {
+    let tile_rows = image.len() / BYTES_PER_TILE_ROW;
+    let mut pixels = Vec::with_capacity(tile_rows * 8 * PRINTER_WIDTH);
+    for y in 0..tile_rows * 8 {
+        let row = &image[(y / 8) * BYTES_PER_TILE_ROW..];
+        for x in 0..PRINTER_WIDTH {
+            let tile = &row[(x / 8) * BYTES_PER_TILE..];
+            let (lo, hi) = (tile[(y % 8) * 2], tile[(y % 8) * 2 + 1]);
+            let bit = 7 - (x % 8);
+            let color = (((hi >> bit) & 1) << 1) | ((lo >> bit) & 1);
+            pixels.push((palette >> (color * 2)) & 0x03);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// Frame a packet and send it, returning the printer's replies to the
+    /// two trailing bytes (alive, status).
+    fn send_packet(printer: &mut GameBoyPrinter, command: u8, compressed: bool, data: &[u8]) -> (u8, u8) {
+        let len = data.len() as u16;
+        let mut body = vec![command, compressed as u8, len as u8, (len >> 8) as u8];
+        body.extend_from_slice(data);
+        let checksum = body.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+        let mut packet = vec![MAGIC_1, MAGIC_2];
+        packet.extend_from_slice(&body);
+        packet.extend_from_slice(&checksum.to_le_bytes());
+        for &byte in &packet {
+            assert_eq!(printer.transfer(byte), 0x00);
+        }
+        (printer.transfer(0x00), printer.transfer(0x00))
+    }
+
+    /// One tile row where every pixel uses `color`.
+    fn solid_tile_row(color: u8) -> Vec<u8> {
+        let lo = if color & 1 != 0 { 0xFF } else { 0x00 };
+        let hi = if color & 2 != 0 { 0xFF } else { 0x00 };
+        [lo, hi].repeat(BYTES_PER_TILE_ROW / 2)
+    }
+
+    #[test]
+    fn init_replies_alive_and_ready() {
+        let mut printer = GameBoyPrinter::new();
+        assert_eq!(send_packet(&mut printer, CMD_INIT, false, &[]), (ALIVE, 0x00));
+    }
+
+    #[test]
+    fn bad_checksum_sets_status_bit() {
+        let mut printer = GameBoyPrinter::new();
+        for byte in [MAGIC_1, MAGIC_2, CMD_INIT, 0, 0, 0, 0xFF, 0xFF] {
+            printer.transfer(byte);
+        }
+        assert_eq!(printer.transfer(0), ALIVE);
+        assert_eq!(printer.transfer(0), STATUS_CHECKSUM_ERROR);
+    }
+
+    #[test]
+    fn data_then_print_produces_page() {
+        let mut printer = GameBoyPrinter::new();
+        send_packet(&mut printer, CMD_INIT, false, &[]);
+        let mut data = solid_tile_row(3);
+        data.extend(solid_tile_row(1));
+        assert_eq!(send_packet(&mut printer, CMD_DATA, false, &data).1, STATUS_UNPROCESSED);
+        send_packet(&mut printer, CMD_DATA, false, &[]);
+
+        let status = send_packet(&mut printer, CMD_PRINT, false, &[1, 0x13, 0xE4, 0x40]).1;
+        assert_eq!(status, STATUS_PRINTING | STATUS_IMAGE_FULL);
+
+        let page = &printer.pages()[0];
+        assert_eq!((page.width, page.height), (160, 16));
+        assert!(page.pixels[..160 * 8].iter().all(|&p| p == 3));
+        assert!(page.pixels[160 * 8..].iter().all(|&p| p == 1));
+    }
+
+    #[test]
+    fn printing_status_clears_after_polls() {
+        let mut printer = GameBoyPrinter::new();
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(0));
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x00, 0xE4, 0x40]);
+        for _ in 1..BUSY_STATUS_POLLS {
+            assert_ne!(send_packet(&mut printer, CMD_STATUS, false, &[]).1 & STATUS_PRINTING, 0);
+        }
+        assert_eq!(send_packet(&mut printer, CMD_STATUS, false, &[]).1, 0x00);
+    }
+
+    #[test]
+    fn print_applies_palette() {
+        let mut printer = GameBoyPrinter::new();
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(1));
+        // Color 1 → shade 2.
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x00, 0b0000_1000, 0x40]);
+        assert!(printer.pages()[0].pixels.iter().all(|&p| p == 2));
+    }
+
+    #[test]
+    fn compressed_data_is_expanded() {
+        let mut printer = GameBoyPrinter::new();
+        // Whole row of 0xFF via runs of 129, plus literals for the remainder.
+        let mut data = Vec::new();
+        let mut remaining = BYTES_PER_TILE_ROW;
+        while remaining >= 129 {
+            data.extend_from_slice(&[0xFF, 0xFF]);
+            remaining -= 129;
+        }
+        data.push(remaining as u8 - 1);
+        data.extend(core::iter::repeat_n(0xFF, remaining));
+        send_packet(&mut printer, CMD_DATA, true, &data);
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x00, 0xE4, 0x40]);
+        let page = &printer.pages()[0];
+        assert_eq!(page.height, 8);
+        assert!(page.pixels.iter().all(|&p| p == 3));
+    }
+
+    #[test]
+    fn zero_margins_continue_the_page() {
+        let mut printer = GameBoyPrinter::new();
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(0));
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x10, 0xE4, 0x40]);
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(3));
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x03, 0xE4, 0x40]);
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(3));
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x00, 0xE4, 0x40]);
+
+        let pages = printer.take_pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].height, 16);
+        assert_eq!(pages[1].height, 8);
+        assert!(printer.pages().is_empty());
+    }
+
+    #[test]
+    fn open_page_is_not_finished() {
+        let mut printer = GameBoyPrinter::new();
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(0));
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x10, 0xE4, 0x40]);
+        assert_eq!(printer.take_finished_page(), None);
+
+        send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(0));
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x03, 0xE4, 0x40]);
+        assert_eq!(printer.take_finished_page().map(|page| page.height), Some(16));
+        assert_eq!(printer.take_finished_page(), None);
+    }
+
+    #[test]
+    fn image_buffer_is_capped() {
+        let mut printer = GameBoyPrinter::new();
+        for _ in 0..20 {
+            send_packet(&mut printer, CMD_DATA, false, &solid_tile_row(2));
+        }
+        send_packet(&mut printer, CMD_PRINT, false, &[1, 0x00, 0xE4, 0x40]);
+        assert_eq!(printer.pages()[0].height, 18 * 8);
+    }
+}
@@ -0,0 +1,123 @@
+//! Link cable over a TCP socket, for linking emulators in separate processes
+//! or on separate machines. Requires the `std` feature.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::vec::Vec;
+
+use super::{LinkCable, RemoteLink};
+
+/// A [`RemoteLink`] pumped through a non-blocking `TcpStream`.
+///
+/// Socket errors are not reported: a broken connection behaves like an
+/// unplugged cable and the clock side reads 0xFF.
+pub struct TcpLinkCable {
+    stream: TcpStream,
+    link: RemoteLink,
+    /// Outgoing bytes the socket didn't accept yet.
+    unsent: Vec<u8>,
+}
+
+impl TcpLinkCable {
+    /// Connect to another emulator waiting in [`TcpLinkCable::accept`].
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::from_stream(TcpStream::connect(addr)?)
+    }
+
+    /// Block until another emulator connects to `listener`.
+    pub fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        Self::from_stream(stream)
+    }
+
+    pub fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        Ok(TcpLinkCable { stream, link: RemoteLink::new(), unsent: Vec::new() })
+    }
+
+    /// Read whatever the peer has sent so far.
+    fn poll(&mut self) {
+        let mut buf = [0u8; 64];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => self.link.push_incoming(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.unsent.extend(self.link.take_outgoing());
+        while !self.unsent.is_empty() {
+            match self.stream.write(&self.unsent) {
+                Ok(0) => break,
+                Ok(n) => {
+                    self.unsent.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl LinkCable for TcpLinkCable {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.poll();
+        let received = self.link.transfer(byte);
+        self.flush();
+        received
+    }
+
+    fn receive(&mut self, byte: u8) -> Option<u8> {
+        self.poll();
+        let received = self.link.receive(byte);
+        self.flush();
+        received
+    }
+
+    fn cancel(&mut self) {
+        self.link.cancel();
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn connected_pair() -> (TcpLinkCable, TcpLinkCable) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || TcpLinkCable::connect(addr).unwrap());
+        let server = TcpLinkCable::accept(&listener).unwrap();
+        (server, client.join().unwrap())
+    }
+
+    /// Poll `f` until it yields a value; loopback delivery isn't instant.
+    fn wait_for<T>(mut f: impl FnMut() -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(value) = f() {
+                return value;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting on the socket");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_tcp_cables_exchange_bytes() {
+        let (mut clock, mut external) = connected_pair();
+        assert_eq!(external.receive(0x99), None);
+
+        let reply = wait_for(|| Some(clock.transfer(0x42)).filter(|&b| b != 0xFF));
+        assert_eq!(reply, 0x99);
+        assert_eq!(wait_for(|| external.receive(0x99)), 0x42);
+    }
+}
@@ -0,0 +1,399 @@
+//! Animated GIF encoder.
+//!
+//! Each frame gets its own color table built from the colors it uses, so
+//! frames are lossless as long as they use at most 256 colors — every DMG
+//! frame and nearly every CGB frame. Frames with more colors have their low
+//! color bits dropped until they fit.
+//!
+//! GIF delays are whole hundredths of a second and players treat anything
+//! under 2 as 10, so recordings keep every other Game Boy frame; see
+//! [`delay_cs`] for spreading the ~3.35 cs between them.
+
+use alloc::vec::Vec;
+
+use crate::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+
+/// DMG T-cycle frequency.
+const CPU_FREQ: u64 = 4_194_304;
+/// LZW codes are at most 12 bits wide.
+const MAX_CODES: usize = 4096;
+/// Largest data sub-block.
+const SUB_BLOCK: usize = 255;
+
+/// How long to show a frame that starts `from_frame` Game Boy frames into a
+/// recording and lasts until `to_frame`, in hundredths of a second. Both
+/// ends are rounded to the nearest hundredth, so delays alternate (e.g.
+/// 3, 4, 3, …) and never drift from real time.
+pub fn delay_cs(from_frame: u64, to_frame: u64) -> u16 {
+    let cs = |frame: u64| (frame * CYCLES_PER_FRAME * 100 + CPU_FREQ / 2) / CPU_FREQ;
+    (cs(to_frame) - cs(from_frame)) as u16
+}
+
+/// Writes GIF blocks into a caller-owned buffer, so a frontend can stream
+/// them to a file or keep them in memory.
+pub struct GifEncoder {
+    width: u16,
+    height: u16,
+    /// Color table for the frame being encoded.
+    palette: Vec<[u8; 3]>,
+    /// The frame as indices into `palette`.
+    indices: Vec<u8>,
+    /// LZW dictionary: `table[prefix * alphabet + index]` is the code for
+    /// that string extended by one pixel, or 0 if it has none yet.
+    table: Vec<u16>,
+}
+
+impl GifEncoder {
+    pub fn new(width: u16, height: u16) -> Self {
+        GifEncoder {
+            width,
+            height,
+            palette: Vec::new(),
+            indices: Vec::new(),
+            table: Vec::new(),
+        }
+    }
+
+    /// The file header, with the animation set to loop forever.
+    pub fn write_header(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b"GIF89a");
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+        // No global color table; every frame brings its own.
+        out.extend_from_slice(&[0x00, 0x00, 0x00]);
+        // NETSCAPE2.0 application extension: loop count 0 (forever).
+        out.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+        out.extend_from_slice(b"NETSCAPE2.0");
+        out.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+    }
+
+    /// One RGBA8 frame, shown for `delay_cs` hundredths of a second.
+    pub fn write_frame(&mut self, rgba: &[u8], delay_cs: u16, out: &mut Vec<u8>) {
+        write_delay(delay_cs, out);
+        self.write_image(rgba, out);
+    }
+
+    /// The image block for one RGBA8 frame, without its delay: a frontend
+    /// that buffers frames can pair it with [`write_delay`] later.
+    pub fn write_image(&mut self, rgba: &[u8], out: &mut Vec<u8>) {
+        self.index_colors(rgba);
+        let bits = (usize::BITS - (self.palette.len() - 1).leading_zeros()).max(2) as u8;
+
+        out.push(0x2C);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+        // Local color table of 2^bits entries, not interlaced.
+        out.push(0x80 | (bits - 1));
+        for n in 0..1usize << bits {
+            out.extend_from_slice(self.palette.get(n).unwrap_or(&[0, 0, 0]));
+        }
+        out.push(bits);
+        self.compress(bits, out);
+    }
+
+    /// Build `palette` and `indices` for a frame, dropping low color bits
+    /// until at most 256 colors remain.
+    fn index_colors(&mut self, rgba: &[u8]) {
+        for mask in [0xFF, 0xF8, 0xF0, 0xE0, 0xC0] {
+            self.palette.clear();
+            self.indices.clear();
+            let mut last: Option<([u8; 3], u8)> = None;
+            let fits = rgba.chunks_exact(4).all(|pixel| {
+                let color = [pixel[0] & mask, pixel[1] & mask, pixel[2] & mask];
+                let index = match last {
+                    Some((c, i)) if c == color => i,
+                    _ => match self.palette.iter().position(|&c| c == color) {
+                        Some(i) => i as u8,
+                        None if self.palette.len() < 256 => {
+                            self.palette.push(color);
+                            (self.palette.len() - 1) as u8
+                        }
+                        None => return false,
+                    },
+                };
+                last = Some((color, index));
+                self.indices.push(index);
+                true
+            });
+            if fits {
+                break;
+            }
+        }
+        if self.palette.is_empty() {
+            self.palette.push([0, 0, 0]);
+        }
+    }
+
+    /// LZW-compress `indices` as GIF image data: variable-width codes,
+    /// packed LSB first into length-prefixed sub-blocks.
+    fn compress(&mut self, min_code_size: u8, out: &mut Vec<u8>) {
+        let alphabet = 1usize << min_code_size;
+        let clear = alphabet as u16;
+        let end = clear + 1;
+        self.table.clear();
+        self.table.resize(MAX_CODES * alphabet, 0);
+
+        let mut bits = BitWriter::new(out);
+        let mut code_size = min_code_size + 1;
+        let mut next = end + 1;
+        bits.write(clear, code_size);
+
+        let mut pixels = self.indices.iter();
+        let Some(&first) = pixels.next() else {
+            bits.write(end, code_size);
+            bits.finish();
+            return;
+        };
+        let mut prefix = first as u16;
+        for &index in pixels {
+            let slot = prefix as usize * alphabet + index as usize;
+            if self.table[slot] != 0 {
+                prefix = self.table[slot];
+                continue;
+            }
+            bits.write(prefix, code_size);
+            // The decoder adds its entry one code later, so widen codes
+            // only once the table has outgrown the current width.
+            if next as usize >= 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+            if (next as usize) < MAX_CODES - 1 {
+                self.table[slot] = next;
+                next += 1;
+            } else {
+                bits.write(clear, code_size);
+                self.table.fill(0);
+                code_size = min_code_size + 1;
+                next = end + 1;
+            }
+            prefix = index as u16;
+        }
+        bits.write(prefix, code_size);
+        if next as usize >= 1 << code_size && code_size < 12 {
+            code_size += 1;
+        }
+        bits.write(end, code_size);
+        bits.finish();
+    }
+}
+
+/// A Graphics Control Extension giving the next image's delay.
+pub fn write_delay(delay_cs: u16, out: &mut Vec<u8>) {
+    out.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+    out.extend_from_slice(&delay_cs.to_le_bytes());
+    out.extend_from_slice(&[0x00, 0x00]);
+}
+
+/// Ends the file.
+pub fn write_trailer(out: &mut Vec<u8>) {
+    out.push(0x3B);
+}
+
+/// Packs codes LSB first and splits the bytes into sub-blocks.
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    block: [u8; SUB_BLOCK],
+    len: usize,
+    acc: u32,
+    acc_bits: u8,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        BitWriter { out, block: [0; SUB_BLOCK], len: 0, acc: 0, acc_bits: 0 }
+    }
+
+    fn write(&mut self, code: u16, size: u8) {
+        self.acc |= (code as u32) << self.acc_bits;
+        self.acc_bits += size;
+        while self.acc_bits >= 8 {
+            self.push(self.acc as u8);
+            self.acc >>= 8;
+            self.acc_bits -= 8;
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.block[self.len] = byte;
+        self.len += 1;
+        if self.len == SUB_BLOCK {
+            self.flush_block();
+        }
+    }
+
+    fn flush_block(&mut self) {
+        self.out.push(self.len as u8);
+        self.out.extend_from_slice(&self.block[..self.len]);
+        self.len = 0;
+    }
+
+    /// Write out the partial byte and block, then the block terminator.
+    fn finish(mut self) {
+        if self.acc_bits > 0 {
+            self.push(self.acc as u8);
+        }
+        if self.len > 0 {
+            self.flush_block();
+        }
+        self.out.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode the image data that starts at `data[0]` (the minimum code
+    /// size), returning the indices and the bytes consumed.
+    fn lzw_decode(data: &[u8]) -> (Vec<u8>, usize) {
+        let min = data[0];
+        let mut bytes = Vec::new();
+        let mut pos = 1;
+        while data[pos] != 0 {
+            let len = data[pos] as usize;
+            bytes.extend_from_slice(&data[pos + 1..pos + 1 + len]);
+            pos += 1 + len;
+        }
+        let clear = 1u16 << min;
+        let end = clear + 1;
+        let mut dict: Vec<Vec<u8>> = Vec::new();
+        let reset = |dict: &mut Vec<Vec<u8>>| {
+            dict.clear();
+            dict.extend((0..clear).map(|i| alloc::vec![i as u8]));
+            dict.push(Vec::new());
+            dict.push(Vec::new());
+        };
+        reset(&mut dict);
+        let (mut size, mut bit, mut prev): (u8, usize, Option<Vec<u8>>) = (min + 1, 0, None);
+        let mut indices = Vec::new();
+        loop {
+            let mut code = 0u16;
+            for i in 0..size as usize {
+                let b = bit + i;
+                code |= (((bytes[b / 8] >> (b % 8)) & 1) as u16) << i;
+            }
+            bit += size as usize;
+            if code == clear {
+                reset(&mut dict);
+                size = min + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                break;
+            }
+            let entry = match (dict.get(code as usize), &prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) => {
+                    let mut e = p.clone();
+                    e.push(p[0]);
+                    e
+                }
+                (None, None) => panic!("bad first code {}", code),
+            };
+            if let Some(p) = prev {
+                let mut added = p;
+                added.push(entry[0]);
+                dict.push(added);
+                if dict.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            indices.extend_from_slice(&entry);
+            prev = Some(entry);
+        }
+        (indices, pos + 1)
+    }
+
+    fn frame(width: usize, height: usize, color: impl Fn(usize, usize) -> [u8; 3]) -> Vec<u8> {
+        let mut rgba = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                rgba.extend_from_slice(&color(x, y));
+                rgba.push(0xFF);
+            }
+        }
+        rgba
+    }
+
+    /// Encode one frame, then decode it and look each pixel up in the
+    /// frame's color table.
+    fn round_trip(width: usize, height: usize, rgba: &[u8]) -> Vec<[u8; 3]> {
+        let mut encoder = GifEncoder::new(width as u16, height as u16);
+        let mut out = Vec::new();
+        encoder.write_image(rgba, &mut out);
+        assert_eq!(out[0], 0x2C);
+        let table_len = 3 << ((out[9] & 0x07) + 1);
+        let table = &out[10..10 + table_len];
+        let (indices, used) = lzw_decode(&out[10 + table_len..]);
+        assert_eq!(10 + table_len + used, out.len());
+        assert_eq!(indices.len(), width * height);
+        indices.iter().map(|&i| {
+            let i = i as usize * 3;
+            [table[i], table[i + 1], table[i + 2]]
+        }).collect()
+    }
+
+    fn pixels(rgba: &[u8]) -> Vec<[u8; 3]> {
+        rgba.chunks_exact(4).map(|p| [p[0], p[1], p[2]]).collect()
+    }
+
+    #[test]
+    fn test_frames_round_trip_losslessly() {
+        let shades = [[0xE0, 0xF8, 0xD0], [0x88, 0xC0, 0x70], [0x34, 0x68, 0x56], [0x08, 0x18, 0x20]];
+        // Long runs, noise-like patterns that fill the dictionary and force
+        // clear codes, and a 256-color frame that needs 8-bit codes.
+        let cases: [&dyn Fn(usize, usize) -> [u8; 3]; 4] = [
+            &|_, _| shades[0],
+            &|x, y| shades[(x / 8 + y / 8) % 4],
+            &|x, y| shades[(x * 7 + y * 13 + (x * y) % 5) % 4],
+            &|x, y| {
+                let i = (x * 7 + y * 3) % 256;
+                [i as u8, (i * 3) as u8, (i * 7) as u8]
+            },
+        ];
+        for (n, color) in cases.iter().enumerate() {
+            let rgba = frame(160, 144, color);
+            assert_eq!(round_trip(160, 144, &rgba), pixels(&rgba), "case {}", n);
+        }
+    }
+
+    #[test]
+    fn test_too_many_colors_are_quantized() {
+        let rgba = frame(160, 144, |x, y| [x as u8, y as u8, (x + y) as u8]);
+        let decoded = round_trip(160, 144, &rgba);
+        for (got, want) in decoded.iter().zip(pixels(&rgba)) {
+            for c in 0..3 {
+                // Low bits dropped, high bits kept.
+                assert_eq!(want[c] & got[c], got[c]);
+                assert!(want[c] - got[c] < 0x40);
+            }
+        }
+    }
+
+    #[test]
+    fn test_delays_track_real_time() {
+        // Every other frame: 33.49 ms apart.
+        let delays: Vec<u16> = (0..6).map(|n| delay_cs(n * 2, n * 2 + 2)).collect();
+        assert_eq!(delays, [3, 4, 3, 3, 4, 3]);
+        let total: u32 = (0..300).map(|n| delay_cs(n * 2, n * 2 + 2) as u32).sum();
+        assert_eq!(total, delay_cs(0, 600) as u32);
+        assert_eq!(total, 1005);
+    }
+
+    #[test]
+    fn test_file_structure() {
+        let mut encoder = GifEncoder::new(2, 1);
+        let mut out = Vec::new();
+        encoder.write_header(&mut out);
+        encoder.write_frame(&[0, 0, 0, 255, 255, 255, 255, 255], 3, &mut out);
+        write_trailer(&mut out);
+        assert_eq!(&out[..6], b"GIF89a");
+        assert_eq!(&out[6..10], &[2, 0, 1, 0]);
+        assert_eq!(&out[13..16], &[0x21, 0xFF, 0x0B]);
+        assert_eq!(&out[32..40], &[0x21, 0xF9, 0x04, 0x00, 3, 0, 0, 0]);
+        assert_eq!(out[40], 0x2C);
+        assert_eq!(*out.last().unwrap(), 0x3B);
+    }
+}
@@ -0,0 +1,304 @@
+//! Gameplay recording: encoders for the frames and audio a frontend already
+//! has, and a ring buffer that keeps the last few seconds of both.
+//!
+//! Frames go in as 160×144 RGBA8, as produced by
+//! [`Palette::write_rgba8888`](crate::cpu::peripheral::ppu::palette::Palette::write_rgba8888)
+//! or [`ColorCorrection::write_rgba8888`](crate::cpu::peripheral::ppu::palette::ColorCorrection::write_rgba8888),
+//! and audio as the interleaved stereo samples from
+//! [`Sm83::drain_audio_samples`](crate::cpu::sm83::Sm83::drain_audio_samples).
+//! Encoders append to a caller-owned `Vec<u8>`; frontends write that to a
+//! file (and clear it) as they go, or hand it to the browser.
+//!
+//! | Format | Good for | Audio |
+//! |---|---|---|
+//! | [`VideoFormat::Gif`] | Short clips for bug reports; lossless, every other frame | None |
+//! | [`VideoFormat::Y4m`] | Long recordings to edit or transcode; every frame | [`wav`] next to it |
+//! | [`VideoFormat::RawRgb`] | The same as bare RGB24 frames, for tools that want them | [`wav`] next to it |
+//!
+//! ```ignore
+//! let mut video = VideoEncoder::new(VideoFormat::Y4m);
+//! let mut out = Vec::new();
+//! video.write_header(&mut out);
+//! loop {
+//!     cpu.run_frame()?;
+//!     palette.write_rgba8888(cpu.framebuffer(), cpu.framebuffer_layers(), &mut rgba);
+//!     video.push_frame(&rgba, &mut out);
+//!     file.write_all(&out)?;
+//!     out.clear();
+//! }
+//! ```
+
+pub mod gif;
+pub mod wav;
+pub mod y4m;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+
+pub const FRAME_WIDTH: usize = 160;
+pub const FRAME_HEIGHT: usize = 144;
+/// Bytes in one RGBA8 frame.
+pub const FRAME_RGBA_SIZE: usize = FRAME_WIDTH * FRAME_HEIGHT * 4;
+/// GIFs keep every this many frames; see [`gif`].
+pub const GIF_FRAME_STEP: u64 = 2;
+
+/// DMG T-cycle frequency.
+const CPU_FREQ: u64 = 4_194_304;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    Gif,
+    Y4m,
+    RawRgb,
+}
+
+impl VideoFormat {
+    /// The format for a file extension: `gif`, `y4m` or `rgb`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "gif" => Some(VideoFormat::Gif),
+            "y4m" => Some(VideoFormat::Y4m),
+            "rgb" => Some(VideoFormat::RawRgb),
+            _ => None,
+        }
+    }
+}
+
+/// Streams frames in one of the [`VideoFormat`]s.
+pub struct VideoEncoder {
+    format: VideoFormat,
+    gif: gif::GifEncoder,
+    /// Frames pushed so far.
+    frames: u64,
+}
+
+impl VideoEncoder {
+    pub fn new(format: VideoFormat) -> Self {
+        VideoEncoder {
+            format,
+            gif: gif::GifEncoder::new(FRAME_WIDTH as u16, FRAME_HEIGHT as u16),
+            frames: 0,
+        }
+    }
+
+    pub fn format(&self) -> VideoFormat {
+        self.format
+    }
+
+    /// Frames pushed so far, including those a GIF skips.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// What goes before the first frame; nothing for raw RGB.
+    pub fn write_header(&self, out: &mut Vec<u8>) {
+        match self.format {
+            VideoFormat::Gif => self.gif.write_header(out),
+            VideoFormat::Y4m => y4m::write_header(FRAME_WIDTH as u16, FRAME_HEIGHT as u16, out),
+            VideoFormat::RawRgb => {}
+        }
+    }
+
+    /// Encode the next Game Boy frame.
+    pub fn push_frame(&mut self, rgba: &[u8], out: &mut Vec<u8>) {
+        let frame = self.frames;
+        self.frames += 1;
+        match self.format {
+            VideoFormat::Gif if frame.is_multiple_of(GIF_FRAME_STEP) => {
+                let delay = gif::delay_cs(frame, frame + GIF_FRAME_STEP);
+                self.gif.write_frame(rgba, delay, out);
+            }
+            VideoFormat::Gif => {}
+            VideoFormat::Y4m => y4m::write_frame(rgba, out),
+            VideoFormat::RawRgb => {
+                out.extend(rgba.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]));
+            }
+        }
+    }
+
+    /// What goes after the last frame; only GIFs need anything.
+    pub fn finish(&self, out: &mut Vec<u8>) {
+        if self.format == VideoFormat::Gif {
+            gif::write_trailer(out);
+        }
+    }
+}
+
+/// One GIF image and the audio that plays while it's shown.
+struct ClipFrame {
+    /// Game Boy frames since the buffer was created.
+    number: u64,
+    image: Vec<u8>,
+    audio: Vec<i16>,
+}
+
+/// The last few seconds of gameplay, ready to save as a GIF and a WAV file
+/// at any moment. Frames are kept already GIF-compressed, typically a few
+/// KiB each, and audio as 16-bit PCM; 30 seconds take around 10 MiB.
+pub struct ClipBuffer {
+    sample_rate: u32,
+    /// GIF images to keep: the buffer's length in seconds, at
+    /// 1/[`GIF_FRAME_STEP`] of the frame rate.
+    capacity: usize,
+    gif: gif::GifEncoder,
+    frames: VecDeque<ClipFrame>,
+    /// Frames pushed, including those a GIF skips.
+    frames_seen: u64,
+}
+
+impl ClipBuffer {
+    /// A buffer holding the last `seconds` of frames and of audio at
+    /// `sample_rate` Hz.
+    pub fn new(seconds: u32, sample_rate: u32) -> Self {
+        let frames = seconds as u64 * CPU_FREQ / CYCLES_PER_FRAME;
+        ClipBuffer {
+            sample_rate,
+            capacity: frames.div_ceil(GIF_FRAME_STEP).max(1) as usize,
+            gif: gif::GifEncoder::new(FRAME_WIDTH as u16, FRAME_HEIGHT as u16),
+            frames: VecDeque::new(),
+            frames_seen: 0,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Record audio at a new rate from now on. What was buffered at the old
+    /// rate is dropped.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            self.clear();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Game Boy frames covered by the buffer.
+    pub fn frames(&self) -> u64 {
+        self.frames.len() as u64 * GIF_FRAME_STEP
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Add a frame and the audio produced while it ran, dropping the oldest
+    /// once the buffer is full.
+    pub fn push_frame(&mut self, rgba: &[u8], audio: &[f32]) {
+        let number = self.frames_seen;
+        self.frames_seen += 1;
+        if number.is_multiple_of(GIF_FRAME_STEP) || self.frames.is_empty() {
+            // Reuse the evicted frame's buffers.
+            let mut entry = if self.frames.len() >= self.capacity {
+                self.frames.pop_front().unwrap()
+            } else {
+                ClipFrame { number, image: Vec::new(), audio: Vec::new() }
+            };
+            entry.number = number;
+            entry.image.clear();
+            entry.audio.clear();
+            self.gif.write_image(rgba, &mut entry.image);
+            self.frames.push_back(entry);
+        }
+        if let Some(entry) = self.frames.back_mut() {
+            entry.audio.extend(audio.iter().map(|&s| wav::to_pcm(s)));
+        }
+    }
+
+    /// The buffered frames as an animated GIF.
+    pub fn to_gif(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.gif.write_header(&mut out);
+        for entry in &self.frames {
+            gif::write_delay(gif::delay_cs(entry.number, entry.number + GIF_FRAME_STEP), &mut out);
+            out.extend_from_slice(&entry.image);
+        }
+        gif::write_trailer(&mut out);
+        out
+    }
+
+    /// The buffered audio as a WAV file, lined up with [`ClipBuffer::to_gif`].
+    pub fn to_wav(&self) -> Vec<u8> {
+        let samples: usize = self.frames.iter().map(|entry| entry.audio.len()).sum();
+        let mut out = Vec::with_capacity(wav::HEADER_SIZE + samples * 2);
+        out.extend_from_slice(&wav::header(self.sample_rate, (samples / 2) as u32));
+        for entry in &self.frames {
+            for sample in &entry.audio {
+                out.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(shade: u8) -> Vec<u8> {
+        [shade, shade, shade, 0xFF].repeat(FRAME_WIDTH * FRAME_HEIGHT)
+    }
+
+    #[test]
+    fn test_video_encoder_formats() {
+        let frame = solid(0x80);
+        let mut out = Vec::new();
+        let mut raw = VideoEncoder::new(VideoFormat::RawRgb);
+        raw.write_header(&mut out);
+        raw.push_frame(&frame, &mut out);
+        raw.finish(&mut out);
+        assert_eq!(out, [0x80; FRAME_WIDTH * FRAME_HEIGHT * 3]);
+
+        out.clear();
+        let mut y4m = VideoEncoder::new(VideoFormat::Y4m);
+        y4m.write_header(&mut out);
+        let header = out.len();
+        y4m.push_frame(&frame, &mut out);
+        assert_eq!(out.len(), header + 6 + FRAME_WIDTH * FRAME_HEIGHT * 3);
+
+        // GIFs keep every other frame.
+        out.clear();
+        let mut gif = VideoEncoder::new(VideoFormat::Gif);
+        for _ in 0..4 {
+            gif.push_frame(&frame, &mut out);
+        }
+        assert_eq!(out.windows(2).filter(|w| w == &[0x21, 0xF9]).count(), 2);
+        assert_eq!(gif.frames(), 4);
+
+        assert_eq!(VideoFormat::from_extension("GIF"), Some(VideoFormat::Gif));
+        assert_eq!(VideoFormat::from_extension("mp4"), None);
+    }
+
+    #[test]
+    fn test_clip_buffer_keeps_last_seconds() {
+        let mut clip = ClipBuffer::new(1, 48_000);
+        assert!(clip.is_empty());
+        // 1 s is 59 frames, so 30 GIF images.
+        for n in 0..200u32 {
+            clip.push_frame(&solid(n as u8), &[0.5; 1606]);
+        }
+        assert_eq!(clip.frames(), 60);
+
+        let wav = clip.to_wav();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), wav::HEADER_SIZE + 60 * 1606 * 2);
+        assert_eq!(i16::from_le_bytes([wav[44], wav[45]]), i16::MAX / 2);
+
+        let gif = clip.to_gif();
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(gif.windows(4).filter(|w| w == &[0x21, 0xF9, 0x04, 0x00]).count(), 30);
+        // The oldest image kept is from frame 140: shade 140 in its color table.
+        let first = gif.windows(2).position(|w| w == [0x00, 0x2C]).unwrap() + 1;
+        assert_eq!(&gif[first + 10..first + 13], &[140, 140, 140]);
+        assert_eq!(*gif.last().unwrap(), 0x3B);
+
+        clip.set_sample_rate(44_100);
+        assert!(clip.is_empty());
+    }
+}
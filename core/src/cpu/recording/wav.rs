@@ -0,0 +1,49 @@
+//! RIFF/WAVE output for the APU's interleaved stereo samples, as 16-bit PCM.
+
+use alloc::vec::Vec;
+
+pub const HEADER_SIZE: usize = 44;
+const CHANNELS: u16 = 2;
+const BITS_PER_SAMPLE: u16 = 16;
+
+/// Header for `pairs` stereo sample pairs. A frontend streaming to a file
+/// can write it with a count of 0 and rewrite it once the count is known.
+pub fn header(sample_rate: u32, pairs: u32) -> [u8; HEADER_SIZE] {
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    let data_len = pairs.saturating_mul(block_align as u32);
+    let mut header = [0u8; HEADER_SIZE];
+    let fields: [&[u8]; 13] = [
+        b"RIFF",
+        &data_len.saturating_add(36).to_le_bytes(),
+        b"WAVE",
+        b"fmt ",
+        &16u32.to_le_bytes(),
+        &1u16.to_le_bytes(), // PCM
+        &CHANNELS.to_le_bytes(),
+        &sample_rate.to_le_bytes(),
+        &(sample_rate * block_align as u32).to_le_bytes(),
+        &block_align.to_le_bytes(),
+        &BITS_PER_SAMPLE.to_le_bytes(),
+        b"data",
+        &data_len.to_le_bytes(),
+    ];
+    let mut at = 0;
+    for field in fields {
+        header[at..at + field.len()].copy_from_slice(field);
+        at += field.len();
+    }
+    header
+}
+
+/// Convert interleaved f32 samples to 16-bit PCM and append them.
+pub fn write_samples(samples: &[f32], out: &mut Vec<u8>) {
+    out.reserve(samples.len() * 2);
+    for &sample in samples {
+        out.extend_from_slice(&to_pcm(sample).to_le_bytes());
+    }
+}
+
+/// A sample in [-1, 1] as 16-bit PCM, clamped.
+pub fn to_pcm(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
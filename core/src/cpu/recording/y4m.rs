@@ -0,0 +1,57 @@
+//! YUV4MPEG2 (`.y4m`) encoder, the uncompressed format ffmpeg, mpv and
+//! most video tools read directly.
+//!
+//! Frames are stored full-range BT.601 4:4:4, so no chroma is thrown away.
+//! The frame rate in the header is the exact 4194304/70224 Hz.
+
+use alloc::vec::Vec;
+
+use crate::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+
+/// DMG T-cycle frequency.
+const CPU_FREQ: u64 = 4_194_304;
+
+/// The stream header.
+pub fn write_header(width: u16, height: u16, out: &mut Vec<u8>) {
+    let header = alloc::format!(
+        "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444 XCOLORRANGE=FULL\n",
+        width,
+        height,
+        CPU_FREQ,
+        CYCLES_PER_FRAME
+    );
+    out.extend_from_slice(header.as_bytes());
+}
+
+/// One RGBA8 frame as Y, Cb and Cr planes.
+pub fn write_frame(rgba: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(b"FRAME\n");
+    // BT.601 coefficients in 16.16 fixed point.
+    let plane = |out: &mut Vec<u8>, [r, g, b, offset]: [i32; 4]| {
+        out.extend(rgba.chunks_exact(4).map(|p| {
+            let v = r * p[0] as i32 + g * p[1] as i32 + b * p[2] as i32;
+            ((v + (offset << 16) + 0x8000) >> 16).clamp(0, 255) as u8
+        }));
+    };
+    plane(out, [19595, 38470, 7471, 0]);
+    plane(out, [-11059, -21709, 32768, 128]);
+    plane(out, [32768, -27439, -5329, 128]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_and_planes() {
+        let mut out = Vec::new();
+        write_header(2, 1, &mut out);
+        assert_eq!(out, b"YUV4MPEG2 W2 H1 F4194304:70224 Ip A1:1 C444 XCOLORRANGE=FULL\n");
+
+        out.clear();
+        write_frame(&[255, 255, 255, 255, 255, 0, 0, 255], &mut out);
+        assert_eq!(&out[..6], b"FRAME\n");
+        // White: full luma, neutral chroma. Red: luma 76, Cb 85, Cr 255.
+        assert_eq!(&out[6..], &[255, 76, 128, 85, 128, 255]);
+    }
+}
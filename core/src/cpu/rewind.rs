@@ -0,0 +1,320 @@
+//! Rewind buffer: a ring of save states taken every few frames.
+//!
+//! Snapshots are grouped behind keyframes. A keyframe stores a whole
+//! `Sm83::save_state` blob; the snapshots after it store only the XOR against
+//! that keyframe, run-length encoded. WRAM, VRAM and cartridge RAM barely
+//! change from one frame to the next, so a delta is a small fraction of a
+//! full state. Keyframes are run-length encoded too (against zeros), which
+//! mostly squeezes out empty RAM and blank framebuffer rows.
+//!
+//! The buffer stays within a byte budget by evicting the oldest keyframe
+//! together with the deltas that depend on it.
+//!
+//! ```ignore
+//! let mut rewind = RewindBuffer::new(16 * 1024 * 1024);
+//! loop {
+//!     if rewind_held {
+//!         rewind.rewind(&mut cpu, 2)?;
+//!     } else {
+//!         cpu.run_frame()?;
+//!         rewind.record(&cpu);
+//!     }
+//! }
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::cpu::save_state::SaveState;
+use crate::cpu::sm83::Sm83;
+
+const DEFAULT_FRAMES_PER_SNAPSHOT: u32 = 2;
+const DEFAULT_SNAPSHOTS_PER_KEYFRAME: u32 = 30;
+
+struct Snapshot {
+    keyframe: bool,
+    /// Length of the decoded save state.
+    len: usize,
+    /// Encoded against zeros for a keyframe, else against its keyframe.
+    data: Vec<u8>,
+}
+
+/// Ring of compressed snapshots for stepping an `Sm83` back in time.
+pub struct RewindBuffer {
+    budget: usize,
+    frames_per_snapshot: u32,
+    snapshots_per_keyframe: u32,
+    snapshots: VecDeque<Snapshot>,
+    /// Decoded newest keyframe, the reference for new deltas.
+    keyframe: Vec<u8>,
+    /// Deltas recorded since the newest keyframe.
+    deltas_since_keyframe: u32,
+    /// Frames recorded since the newest snapshot was taken.
+    frames_since_snapshot: u32,
+    /// Encoded bytes held by `snapshots`.
+    stored: usize,
+}
+
+impl RewindBuffer {
+    /// An empty buffer that keeps at most `budget` bytes, counting the
+    /// decoded keyframe it encodes deltas against. A DMG save state is
+    /// around 64 KiB uncompressed, so the budget should leave room for at
+    /// least that plus a few compressed snapshots.
+    pub fn new(budget: usize) -> Self {
+        RewindBuffer {
+            budget,
+            frames_per_snapshot: DEFAULT_FRAMES_PER_SNAPSHOT,
+            snapshots_per_keyframe: DEFAULT_SNAPSHOTS_PER_KEYFRAME,
+            snapshots: VecDeque::new(),
+            keyframe: Vec::new(),
+            deltas_since_keyframe: 0,
+            frames_since_snapshot: 0,
+            stored: 0,
+        }
+    }
+
+    /// Take a snapshot every `frames` recorded frames (default 2). Fewer
+    /// snapshots reach further back on the same budget but rewind in
+    /// coarser steps.
+    pub fn with_frames_per_snapshot(mut self, frames: u32) -> Self {
+        self.frames_per_snapshot = frames.max(1);
+        self
+    }
+
+    /// Start a new keyframe every `snapshots` snapshots (default 30). Deltas
+    /// grow as the state drifts from its keyframe; keyframes cost a full
+    /// (compressed) state but let old history be evicted in smaller groups.
+    pub fn with_snapshots_per_keyframe(mut self, snapshots: u32) -> Self {
+        self.snapshots_per_keyframe = snapshots.max(1);
+        self
+    }
+
+    /// Number of snapshots held.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Bytes held, as counted against the budget.
+    pub fn bytes_used(&self) -> usize {
+        self.stored + self.keyframe.len()
+    }
+
+    /// How many frames back the oldest snapshot is.
+    pub fn frames_available(&self) -> u32 {
+        match self.snapshots.len() {
+            0 => 0,
+            n => self.frames_since_snapshot + (n as u32 - 1) * self.frames_per_snapshot,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.keyframe.clear();
+        self.deltas_since_keyframe = 0;
+        self.frames_since_snapshot = 0;
+        self.stored = 0;
+    }
+
+    /// Call once per emulated frame; takes a snapshot every
+    /// `frames_per_snapshot` calls.
+    pub fn record(&mut self, cpu: &Sm83) {
+        self.frames_since_snapshot += 1;
+        if !self.snapshots.is_empty() && self.frames_since_snapshot < self.frames_per_snapshot {
+            return;
+        }
+        self.frames_since_snapshot = 0;
+        self.push(cpu.save_state());
+    }
+
+    fn push(&mut self, state: Vec<u8>) {
+        let new_keyframe = self.snapshots.is_empty()
+            || self.deltas_since_keyframe + 1 >= self.snapshots_per_keyframe
+            || state.len() != self.keyframe.len();
+        let snapshot = if new_keyframe {
+            let snapshot = Snapshot { keyframe: true, len: state.len(), data: encode(None, &state) };
+            self.keyframe = state;
+            self.deltas_since_keyframe = 0;
+            snapshot
+        } else {
+            self.deltas_since_keyframe += 1;
+            Snapshot { keyframe: false, len: state.len(), data: encode(Some(&self.keyframe), &state) }
+        };
+        self.stored += snapshot.data.len();
+        self.snapshots.push_back(snapshot);
+        self.evict();
+    }
+
+    /// Drop the oldest keyframe group while over budget, always keeping the
+    /// newest group. A lone group outgrowing the budget starts a new
+    /// keyframe so it can be dropped at the next snapshot.
+    fn evict(&mut self) {
+        while self.bytes_used() > self.budget {
+            let Some(next_group) = self.snapshots.iter().skip(1).position(|s| s.keyframe) else {
+                self.deltas_since_keyframe = self.snapshots_per_keyframe;
+                return;
+            };
+            for snapshot in self.snapshots.drain(..=next_group) {
+                self.stored -= snapshot.data.len();
+            }
+        }
+    }
+
+    /// Restore `cpu` to the newest snapshot at least `frames` frames old, or
+    /// to the oldest one held if history doesn't reach that far. Newer
+    /// snapshots are discarded; recording resumes from the restored state.
+    /// Returns how many frames back the restored state is (0 if the buffer
+    /// is empty).
+    pub fn rewind(&mut self, cpu: &mut Sm83, frames: u32) -> Result<u32, &'static str> {
+        if self.snapshots.is_empty() {
+            return Ok(0);
+        }
+        let steps = frames.saturating_sub(self.frames_since_snapshot).div_ceil(self.frames_per_snapshot);
+        let index = self.snapshots.len() - 1 - (steps as usize).min(self.snapshots.len() - 1);
+        let rewound = self.frames_since_snapshot + (self.snapshots.len() - 1 - index) as u32 * self.frames_per_snapshot;
+
+        for snapshot in self.snapshots.drain(index + 1..) {
+            self.stored -= snapshot.data.len();
+        }
+        let key_index = self.snapshots.iter().rposition(|s| s.keyframe).unwrap_or(0);
+        let key = &self.snapshots[key_index];
+        self.keyframe = decode(None, &key.data, key.len);
+        self.deltas_since_keyframe = (index - key_index) as u32;
+        self.frames_since_snapshot = 0;
+
+        let target = &self.snapshots[index];
+        let state = if target.keyframe {
+            self.keyframe.clone()
+        } else {
+            decode(Some(&self.keyframe), &target.data, target.len)
+        };
+        cpu.load_state(SaveState::from_blob(state)?)?;
+        Ok(rewound)
+    }
+}
+
+// ── Encoding ─────────────────────────────────────────────────────────────────
+//
+// `state XOR reference` as alternating runs: a varint count of zero bytes,
+// then a varint count of literal bytes followed by those bytes. With no
+// reference the state itself is encoded.
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some(&byte) = data.get(*pos) {
+        *pos += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    value
+}
+
+/// Shortest zero run worth ending a literal run for; shorter runs cost more
+/// in headers than they save.
+const MIN_ZERO_RUN: usize = 4;
+
+fn encode(reference: Option<&[u8]>, state: &[u8]) -> Vec<u8> {
+    let diff = |i: usize| state[i] ^ reference.map_or(0, |r| r[i]);
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < state.len() {
+        let zeros_start = i;
+        while i < state.len() && diff(i) == 0 {
+            i += 1;
+        }
+        write_varint(&mut out, i - zeros_start);
+
+        let literal_start = i;
+        let mut zero_run = 0;
+        while i < state.len() && zero_run < MIN_ZERO_RUN {
+            zero_run = if diff(i) == 0 { zero_run + 1 } else { 0 };
+            i += 1;
+        }
+        if zero_run == MIN_ZERO_RUN {
+            i -= zero_run;
+        }
+        write_varint(&mut out, i - literal_start);
+        out.extend((literal_start..i).map(diff));
+    }
+    out
+}
+
+fn decode(reference: Option<&[u8]>, data: &[u8], len: usize) -> Vec<u8> {
+    let mut state = match reference {
+        Some(reference) => reference[..len].to_vec(),
+        None => alloc::vec![0u8; len],
+    };
+    let mut pos = 0;
+    let mut i = 0;
+    while pos < data.len() && i < len {
+        i += read_varint(data, &mut pos);
+        let literals = read_varint(data, &mut pos);
+        for byte in state.iter_mut().skip(i).take(literals) {
+            *byte ^= data[pos];
+            pos += 1;
+        }
+        i += literals;
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn keyframe_roundtrips() {
+        let state: Vec<u8> = (0..1000u32).map(|i| if i % 100 < 50 { 0 } else { i as u8 }).collect();
+        let encoded = encode(None, &state);
+        assert!(encoded.len() < state.len());
+        assert_eq!(decode(None, &encoded, state.len()), state);
+    }
+
+    #[test]
+    fn delta_roundtrips_and_is_small() {
+        let reference: Vec<u8> = (0..4096u32).map(|i| (i * 7) as u8).collect();
+        let mut state = reference.clone();
+        state[10] ^= 0xFF;
+        state[11] = 0;
+        state[3000] = 0x42;
+        let encoded = encode(Some(&reference), &state);
+        assert!(encoded.len() < 16, "delta is {} bytes", encoded.len());
+        assert_eq!(decode(Some(&reference), &encoded, state.len()), state);
+    }
+
+    #[test]
+    fn short_zero_runs_stay_in_literals() {
+        let state = vec![1, 0, 1, 0, 0, 1, 0, 0, 0, 0, 0, 1];
+        let encoded = encode(None, &state);
+        // zeros 0, literals 6, zeros 5, literals 1
+        assert_eq!(encoded, [0, 6, 1, 0, 1, 0, 0, 1, 5, 1, 1]);
+        assert_eq!(decode(None, &encoded, state.len()), state);
+    }
+
+    #[test]
+    fn varints_roundtrip() {
+        for value in [0, 1, 0x7F, 0x80, 0x3FFF, 0x4000, 1 << 20] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            let mut pos = 0;
+            assert_eq!(read_varint(&out, &mut pos), value);
+            assert_eq!(pos, out.len());
+        }
+    }
+}
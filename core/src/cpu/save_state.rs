@@ -1,4 +1,4 @@
-//! Typed save state representation for the RBSS v1 format.
+//! Typed save state representation for the RBSS format.
 //!
 //! On the **save path**, `Sm83::save_state()` writes directly to a `Vec<u8>` —
 //! no `SaveState` instance is created.
@@ -8,20 +8,49 @@
 //! emulator state is touched. The struct owns the blob and exposes zero-copy
 //! slice accessors for large memory regions. Each component's state struct is
 //! copied out at parse time and applied via that component's `load_state`.
+//!
+//! The cartridge section is the exception: its MBC register layout depends on
+//! the cartridge type, so it is kept as a raw slice and the cartridge reports
+//! how many bytes it consumed when the section is applied.
+//!
+//! ## Layout
+//!
+//! ```text
+//! magic "RBSS" | version u16
+//! CPU | timer | PPU                              (v1 and v2)
+//! IO regs | IE | WRAM | HRAM | VRAM | OAM        (v1 and v2)
+//! PPU back buffer | front buffer | APU | serial  (v2 and later)
+//!   | joypad | OAM DMA | pending IO writes
+//! model (0 = DMG, 1 = CGB)                       (v3 and later)
+//!   CGB only: CGB state | color back buffer | color front buffer
+//!   CGB mode only: VBK | SVBK | unmapped VRAM bank | WRAM banks 1-7
+//! pixel FIFO present | pixel FIFO state          (v3 and later)
+//! APU output filters                             (v3 and later)
+//! MBC registers | cart RAM length | cart RAM     (length is u16 in v1, u32 since v2)
+//! ```
+//!
+//! Version 1 blobs are still accepted. They carry no peripheral state beyond
+//! the timer and PPU counters, so those peripherals keep whatever state the
+//! emulator already had when a v1 blob is applied. v1 and v2 blobs predate
+//! CGB support and are DMG states.
 
 use alloc::vec::Vec;
 use core::mem::size_of;
 use core::ops::Range;
 
-use crate::cpu::peripheral::ppu::PpuMode;
+use crate::cpu::peripheral::apu::BLIP_WIDTH;
+use crate::cpu::peripheral::ppu::{PpuMode, FRAMEBUFFER_SIZE, PALETTE_RAM_SIZE};
 use crate::cpu::registers::{Flags, Registers};
 use crate::cpu::sm83::ImeState;
-use crate::memory::memory::GameBoyMemory;
+use crate::memory::memory::{GameBoyMemory, CGB_BANKS_STATE_SIZE};
 
 // ── Format constants ──────────────────────────────────────────────────────────
 
 pub const MAGIC: &[u8; 4] = b"RBSS";
-pub const VERSION: u16     = 1;
+pub const VERSION: u16     = 3;
+
+/// Oldest version `from_blob` still accepts.
+pub const MIN_VERSION: u16 = 1;
 
 const MAGIC_SIZE:         usize = 4;
 const VERSION_SIZE:       usize = size_of::<u16>();
@@ -49,16 +78,103 @@ const WRAM_SIZE:          usize = 0x2000;
 const HRAM_SIZE:          usize = 0x7F;
 const VRAM_SIZE:          usize = 0x2000;
 const OAM_SIZE:           usize = 0xA0;
-const MBC_SIZE:           usize = 4 * size_of::<u8>(); // rom_bank_lo upper_bits ram_mode ram_enabled
-const CART_RAM_LEN_SIZE:  usize = size_of::<u16>();
 
 /// Minimum valid blob length: everything up through OAM, without optional MBC/cart RAM.
 pub const MIN_BLOB_SIZE: usize = HEADER_SIZE + CPU_STATE_SIZE + TIMER_STATE_SIZE
     + PPU_STATE_SIZE + IO_REGS_SIZE + IE_SIZE + WRAM_SIZE + HRAM_SIZE + VRAM_SIZE + OAM_SIZE;
 
+const SQUARE_STATE_SIZE:  usize = 2 * size_of::<u8>()  // enabled, dac_enabled
+                                + size_of::<u16>()      // length_counter
+                                + 6 * size_of::<u8>()   // length_enabled .. envelope_timer
+                                + 3 * size_of::<u16>()  // frequency, period, timer
+                                + 2 * size_of::<u8>();  // duty, duty_position
+const SWEEP_STATE_SIZE:   usize = 5 * size_of::<u8>()  // enabled .. shift
+                                + size_of::<u16>()      // shadow_frequency
+                                + size_of::<u8>();      // negate_used
+const WAVE_STATE_SIZE:    usize = 2 * size_of::<u8>()  // enabled, dac_enabled
+                                + size_of::<u16>()      // length_counter
+                                + 2 * size_of::<u8>()   // length_enabled, volume_code
+                                + 3 * size_of::<u16>()  // frequency, period, timer
+                                + 2 * size_of::<u8>()   // position, sample_buffer
+                                + WAVE_RAM_SIZE
+                                + size_of::<u8>();      // just_read
+const NOISE_STATE_SIZE:   usize = 2 * size_of::<u8>()  // enabled, dac_enabled
+                                + size_of::<u16>()      // length_counter
+                                + 9 * size_of::<u8>()   // length_enabled .. divisor_code
+                                + 3 * size_of::<u16>(); // period, timer, lfsr
+const APU_STATE_SIZE:     usize = 4 * size_of::<u8>()  // powered .. wave_2mhz_phase
+                                + 2 * SQUARE_STATE_SIZE + SWEEP_STATE_SIZE
+                                + WAVE_STATE_SIZE + NOISE_STATE_SIZE
+                                + APU_REGS_SIZE
+                                + size_of::<u32>();     // sample_acc
+
+const SERIAL_STATE_SIZE:  usize = size_of::<u8>()       // transfer in progress
+                                + size_of::<u16>();     // cycles_remaining
+const JOYPAD_STATE_SIZE:  usize = size_of::<u8>();      // select
+const DMA_STATE_SIZE:     usize = size_of::<u8>()       // active
+                                + size_of::<u16>()      // source
+                                + size_of::<u8>();      // progress
+const IO_WRITE_SIZE:      usize = size_of::<u16>() + size_of::<u8>();
+
+const HDMA_STATE_SIZE:    usize = 2 * size_of::<u16>()  // source, destination
+                                + size_of::<u8>()       // remaining
+                                + size_of::<u8>();      // hblank_active
+const COLOR_PPU_STATE_SIZE: usize = size_of::<u8>()     // cgb_mode
+                                + 2 * PALETTE_RAM_SIZE
+                                + 2 * size_of::<u8>()   // bcps, ocps
+                                + size_of::<u8>();      // obj_priority_by_x
+const CGB_STATE_SIZE:     usize = size_of::<u8>()       // double_speed | speed_switch_armed
+                                + size_of::<u16>()      // stall_m_cycles
+                                + HDMA_STATE_SIZE + COLOR_PPU_STATE_SIZE;
+const COLOR_FRAMEBUFFER_SIZE: usize = FRAMEBUFFER_SIZE * size_of::<u16>();
+
+const PIXEL_FIFO_STATE_SIZE: usize = 10 * size_of::<u8>() // lx .. tile_hi
+                                + 2 * 8 + size_of::<u8>()   // BG FIFO, bg_len
+                                + 3 * 8 + size_of::<u8>()   // OBJ FIFO, obj_head
+                                + 2 * size_of::<u8>()       // window, wy_matched
+                                + 2 * 10 + size_of::<u8>()  // objects, object_x, object_count
+                                + size_of::<u16>()          // fetched
+                                + 3 * size_of::<u8>();      // obj_fetch_slot .. penalty_tile_end
+
+const APU_OUTPUT_STATE_SIZE: usize = 2 * BLIP_WIDTH * size_of::<i32>() // blip_ring
+                                + size_of::<u8>()           // blip_head
+                                + 4 * size_of::<i32>()      // integrator, level
+                                + 2 * size_of::<f32>();     // capacitor
+
+/// Fixed-size v2 sections between OAM and the pending IO write list.
+const V2_FIXED_SIZE: usize = size_of::<u8>()            // PPU prev_stat_line
+    + 2 * FRAMEBUFFER_SIZE + APU_STATE_SIZE + SERIAL_STATE_SIZE + JOYPAD_STATE_SIZE
+    + DMA_STATE_SIZE + size_of::<u8>();                 // pending IO write count
+
+pub const WAVE_RAM_SIZE: usize = 16;
+pub const APU_REGS_SIZE: usize = 23;
+
+// ── Byte cursor ───────────────────────────────────────────────────────────────
+
+/// Little-endian reader over a region `from_blob` has already bounds-checked.
+struct Cursor<'a> {
+    b: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(blob: &'a [u8], offset: usize) -> Self { Cursor { b: blob, pos: offset } }
+
+    fn u8(&mut self) -> u8     { self.pos += 1; self.b[self.pos - 1] }
+    fn bool(&mut self) -> bool { self.u8() != 0 }
+    fn u16(&mut self) -> u16   { u16::from_le_bytes(self.array()) }
+    fn u32(&mut self) -> u32   { u32::from_le_bytes(self.array()) }
+    fn i32(&mut self) -> i32   { i32::from_le_bytes(self.array()) }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        self.pos += N;
+        self.b[self.pos - N..self.pos].try_into().unwrap()
+    }
+}
+
 // ── Component state structs ───────────────────────────────────────────────────
 
-/// Full CPU state: registers + IME + halted + cycle counter.
+/// Full CPU state: registers + IME + halted/stopped + cycle counter.
 #[derive(Debug, Clone, Copy)]
 pub struct CpuState {
     pub a: u8,
@@ -73,6 +189,8 @@ pub struct CpuState {
     pub pc: u16,
     pub ime: ImeState,
     pub halted: bool,
+    /// STOP mode. Shares the halted byte as bit 1, so v1 readers see it as halted.
+    pub stopped: bool,
     pub cycle_counter: u64,
 }
 
@@ -88,7 +206,7 @@ impl CpuState {
             ImeState::Pending  => 1,
             ImeState::Enabled  => 2,
         });
-        out.push(self.halted as u8);
+        out.push(self.halted as u8 | (self.stopped as u8) << 1);
         out.extend_from_slice(&self.cycle_counter.to_le_bytes());
     }
 
@@ -106,7 +224,8 @@ impl CpuState {
             sp: u16::from_le_bytes([b[8],  b[9]]),
             pc: u16::from_le_bytes([b[10], b[11]]),
             ime,
-            halted:        b[CPU_REGS_SIZE + IME_SIZE] != 0,
+            halted:        b[CPU_REGS_SIZE + IME_SIZE] & 0x01 != 0,
+            stopped:       b[CPU_REGS_SIZE + IME_SIZE] & 0x02 != 0,
             cycle_counter: u64::from_le_bytes(
                 b[CPU_REGS_SIZE + IME_SIZE + HALTED_SIZE
                 ..CPU_REGS_SIZE + IME_SIZE + HALTED_SIZE + CYCLE_COUNTER_SIZE]
@@ -145,12 +264,16 @@ impl TimerState {
 }
 
 /// PPU peripheral state.
+///
+/// `prev_stat_line` is stored in the v2 section after OAM so the v1 prefix
+/// stays unchanged; v1 blobs load it as `false`.
 #[derive(Debug, Clone, Copy)]
 pub struct PpuState {
     pub dot: u16,
     pub ly: u8,
     pub mode: PpuMode,
     pub window_line_counter: u8,
+    pub prev_stat_line: bool,
 }
 
 impl PpuState {
@@ -173,36 +296,621 @@ impl PpuState {
                 _ => PpuMode::PixelTransfer,
             },
             window_line_counter: b[4],
+            prev_stat_line:      false,
         };
         (state, PPU_STATE_SIZE)
     }
 }
 
-/// MBC register state (covers MBC1 and MBC1Multicart layouts).
+/// Pulse channel state (ch1 and ch2).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SquareChannelState {
+    pub enabled: bool,
+    pub dac_enabled: bool,
+    pub length_counter: u16,
+    pub length_enabled: bool,
+    pub volume: u8,
+    pub volume_initial: u8,
+    pub envelope_add: bool,
+    pub envelope_period: u8,
+    pub envelope_timer: u8,
+    pub frequency: u16,
+    pub frequency_period: u16,
+    pub frequency_timer: u16,
+    pub duty: u8,
+    pub duty_position: u8,
+}
+
+impl SquareChannelState {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.enabled as u8);
+        out.push(self.dac_enabled as u8);
+        out.extend_from_slice(&self.length_counter.to_le_bytes());
+        out.push(self.length_enabled as u8);
+        out.push(self.volume);
+        out.push(self.volume_initial);
+        out.push(self.envelope_add as u8);
+        out.push(self.envelope_period);
+        out.push(self.envelope_timer);
+        out.extend_from_slice(&self.frequency.to_le_bytes());
+        out.extend_from_slice(&self.frequency_period.to_le_bytes());
+        out.extend_from_slice(&self.frequency_timer.to_le_bytes());
+        out.push(self.duty);
+        out.push(self.duty_position);
+    }
+
+    fn parse(c: &mut Cursor) -> Self {
+        SquareChannelState {
+            enabled:          c.bool(),
+            dac_enabled:      c.bool(),
+            length_counter:   c.u16(),
+            length_enabled:   c.bool(),
+            volume:           c.u8(),
+            volume_initial:   c.u8(),
+            envelope_add:     c.bool(),
+            envelope_period:  c.u8(),
+            envelope_timer:   c.u8(),
+            frequency:        c.u16(),
+            frequency_period: c.u16(),
+            frequency_timer:  c.u16(),
+            duty:             c.u8(),
+            duty_position:    c.u8(),
+        }
+    }
+}
+
+/// Ch1 frequency sweep unit state.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SweepUnitState {
+    pub enabled: bool,
+    pub period: u8,
+    pub timer: u8,
+    pub negate: bool,
+    pub shift: u8,
+    pub shadow_frequency: u16,
+    pub negate_used: bool,
+}
+
+impl SweepUnitState {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.enabled as u8);
+        out.push(self.period);
+        out.push(self.timer);
+        out.push(self.negate as u8);
+        out.push(self.shift);
+        out.extend_from_slice(&self.shadow_frequency.to_le_bytes());
+        out.push(self.negate_used as u8);
+    }
+
+    fn parse(c: &mut Cursor) -> Self {
+        SweepUnitState {
+            enabled:          c.bool(),
+            period:           c.u8(),
+            timer:            c.u8(),
+            negate:           c.bool(),
+            shift:            c.u8(),
+            shadow_frequency: c.u16(),
+            negate_used:      c.bool(),
+        }
+    }
+}
+
+/// Wave channel state (ch3), including wave RAM and the sample position.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WaveChannelState {
+    pub enabled: bool,
+    pub dac_enabled: bool,
+    pub length_counter: u16,
+    pub length_enabled: bool,
+    pub volume_code: u8,
+    pub frequency: u16,
+    pub frequency_period: u16,
+    pub frequency_timer: u16,
+    pub position: u8,
+    pub sample_buffer: u8,
+    pub wave_ram: [u8; WAVE_RAM_SIZE],
+    pub just_read: bool,
+}
+
+impl WaveChannelState {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.enabled as u8);
+        out.push(self.dac_enabled as u8);
+        out.extend_from_slice(&self.length_counter.to_le_bytes());
+        out.push(self.length_enabled as u8);
+        out.push(self.volume_code);
+        out.extend_from_slice(&self.frequency.to_le_bytes());
+        out.extend_from_slice(&self.frequency_period.to_le_bytes());
+        out.extend_from_slice(&self.frequency_timer.to_le_bytes());
+        out.push(self.position);
+        out.push(self.sample_buffer);
+        out.extend_from_slice(&self.wave_ram);
+        out.push(self.just_read as u8);
+    }
+
+    fn parse(c: &mut Cursor) -> Self {
+        WaveChannelState {
+            enabled:          c.bool(),
+            dac_enabled:      c.bool(),
+            length_counter:   c.u16(),
+            length_enabled:   c.bool(),
+            volume_code:      c.u8(),
+            frequency:        c.u16(),
+            frequency_period: c.u16(),
+            frequency_timer:  c.u16(),
+            position:         c.u8(),
+            sample_buffer:    c.u8(),
+            wave_ram:         c.array(),
+            just_read:        c.bool(),
+        }
+    }
+}
+
+/// Noise channel state (ch4), including the LFSR.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoiseChannelState {
+    pub enabled: bool,
+    pub dac_enabled: bool,
+    pub length_counter: u16,
+    pub length_enabled: bool,
+    pub volume: u8,
+    pub volume_initial: u8,
+    pub envelope_add: bool,
+    pub envelope_period: u8,
+    pub envelope_timer: u8,
+    pub clock_shift: u8,
+    pub width_mode: bool,
+    pub divisor_code: u8,
+    pub frequency_period: u16,
+    pub frequency_timer: u16,
+    pub lfsr: u16,
+}
+
+impl NoiseChannelState {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.enabled as u8);
+        out.push(self.dac_enabled as u8);
+        out.extend_from_slice(&self.length_counter.to_le_bytes());
+        out.push(self.length_enabled as u8);
+        out.push(self.volume);
+        out.push(self.volume_initial);
+        out.push(self.envelope_add as u8);
+        out.push(self.envelope_period);
+        out.push(self.envelope_timer);
+        out.push(self.clock_shift);
+        out.push(self.width_mode as u8);
+        out.push(self.divisor_code);
+        out.extend_from_slice(&self.frequency_period.to_le_bytes());
+        out.extend_from_slice(&self.frequency_timer.to_le_bytes());
+        out.extend_from_slice(&self.lfsr.to_le_bytes());
+    }
+
+    fn parse(c: &mut Cursor) -> Self {
+        NoiseChannelState {
+            enabled:          c.bool(),
+            dac_enabled:      c.bool(),
+            length_counter:   c.u16(),
+            length_enabled:   c.bool(),
+            volume:           c.u8(),
+            volume_initial:   c.u8(),
+            envelope_add:     c.bool(),
+            envelope_period:  c.u8(),
+            envelope_timer:   c.u8(),
+            clock_shift:      c.u8(),
+            width_mode:       c.bool(),
+            divisor_code:     c.u8(),
+            frequency_period: c.u16(),
+            frequency_timer:  c.u16(),
+            lfsr:             c.u16(),
+        }
+    }
+}
+
+/// APU peripheral state: frame sequencer, all four channels, raw register
+/// bytes and the downsampling accumulator. The mixer caches are derived from
+/// NR50/NR51 and rebuilt on load.
 #[derive(Debug, Clone, Copy)]
-pub struct MbcState {
-    pub rom_bank_lo: u8,
-    pub upper_bits: u8,
-    pub ram_mode: bool,
-    pub ram_enabled: bool,
+pub struct ApuState {
+    pub powered: bool,
+    pub prev_div_bit: bool,
+    pub frame_sequencer_step: u8,
+    pub wave_2mhz_phase: bool,
+    pub channel1: SquareChannelState,
+    pub sweep: SweepUnitState,
+    pub channel2: SquareChannelState,
+    pub channel3: WaveChannelState,
+    pub channel4: NoiseChannelState,
+    pub regs: [u8; APU_REGS_SIZE],
+    pub sample_acc: u32,
 }
 
-impl MbcState {
+impl ApuState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.powered as u8);
+        out.push(self.prev_div_bit as u8);
+        out.push(self.frame_sequencer_step);
+        out.push(self.wave_2mhz_phase as u8);
+        self.channel1.serialize(out);
+        self.sweep.serialize(out);
+        self.channel2.serialize(out);
+        self.channel3.serialize(out);
+        self.channel4.serialize(out);
+        out.extend_from_slice(&self.regs);
+        out.extend_from_slice(&self.sample_acc.to_le_bytes());
+    }
+
     fn parse(blob: &[u8], offset: usize) -> (Self, usize) {
-        let b = &blob[offset..];
-        let state = MbcState {
-            rom_bank_lo: b[0].max(1),
-            upper_bits:  b[1] & 0x03,
-            ram_mode:    b[2] != 0,
-            ram_enabled: b[3] != 0,
+        let mut c = Cursor::new(blob, offset);
+        let state = ApuState {
+            powered:              c.bool(),
+            prev_div_bit:         c.bool(),
+            frame_sequencer_step: c.u8(),
+            wave_2mhz_phase:      c.bool(),
+            channel1:             SquareChannelState::parse(&mut c),
+            sweep:                SweepUnitState::parse(&mut c),
+            channel2:             SquareChannelState::parse(&mut c),
+            channel3:             WaveChannelState::parse(&mut c),
+            channel4:             NoiseChannelState::parse(&mut c),
+            regs:                 c.array(),
+            sample_acc:           c.u32(),
         };
-        (state, MBC_SIZE)
+        (state, APU_STATE_SIZE)
+    }
+}
+
+/// Serial port state. `cycles_remaining` is `Some` while an internal-clock
+/// transfer is in flight.
+#[derive(Debug, Clone, Copy)]
+pub struct SerialState {
+    pub cycles_remaining: Option<u16>,
+}
+
+impl SerialState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.cycles_remaining.is_some() as u8);
+        out.extend_from_slice(&self.cycles_remaining.unwrap_or(0).to_le_bytes());
+    }
+
+    fn parse(blob: &[u8], offset: usize) -> (Self, usize) {
+        let mut c = Cursor::new(blob, offset);
+        let active = c.bool();
+        let cycles = c.u16();
+        (SerialState { cycles_remaining: active.then_some(cycles) }, SERIAL_STATE_SIZE)
+    }
+}
+
+/// Joypad state: the JOYP select lines. Button state is host input and is
+/// deliberately not saved.
+#[derive(Debug, Clone, Copy)]
+pub struct JoypadState {
+    pub select: u8,
+}
+
+impl JoypadState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.select);
+    }
+
+    fn parse(blob: &[u8], offset: usize) -> (Self, usize) {
+        (JoypadState { select: blob[offset] }, JOYPAD_STATE_SIZE)
+    }
+}
+
+/// In-flight OAM DMA transfer: source base address and bytes copied so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OamDmaState {
+    pub source: u16,
+    pub progress: u8,
+}
+
+impl OamDmaState {
+    pub fn serialize(state: Option<Self>, out: &mut Vec<u8>) {
+        let dma = state.unwrap_or(OamDmaState { source: 0, progress: 0 });
+        out.push(state.is_some() as u8);
+        out.extend_from_slice(&dma.source.to_le_bytes());
+        out.push(dma.progress);
+    }
+
+    fn parse(blob: &[u8], offset: usize) -> (Option<Self>, usize) {
+        let mut c = Cursor::new(blob, offset);
+        let active = c.bool();
+        let state = OamDmaState { source: c.u16(), progress: c.u8() };
+        (active.then_some(state), DMA_STATE_SIZE)
+    }
+}
+
+/// CGB VRAM DMA state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HdmaState {
+    pub source: u16,
+    pub destination: u16,
+    pub remaining: u8,
+    pub hblank_active: bool,
+}
+
+impl HdmaState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.source.to_le_bytes());
+        out.extend_from_slice(&self.destination.to_le_bytes());
+        out.push(self.remaining);
+        out.push(self.hblank_active as u8);
+    }
+
+    fn parse(c: &mut Cursor) -> Self {
+        HdmaState {
+            source:        c.u16(),
+            destination:   c.u16(),
+            remaining:     c.u8(),
+            hblank_active: c.bool(),
+        }
+    }
+}
+
+/// CGB palette RAM and object priority mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorPpuState {
+    /// False for a DMG cartridge in compatibility mode.
+    pub cgb_mode: bool,
+    pub bg_palettes: [u8; PALETTE_RAM_SIZE],
+    pub obj_palettes: [u8; PALETTE_RAM_SIZE],
+    pub bcps: u8,
+    pub ocps: u8,
+    pub obj_priority_by_x: bool,
+}
+
+impl ColorPpuState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.cgb_mode as u8);
+        out.extend_from_slice(&self.bg_palettes);
+        out.extend_from_slice(&self.obj_palettes);
+        out.push(self.bcps);
+        out.push(self.ocps);
+        out.push(self.obj_priority_by_x as u8);
+    }
+
+    fn parse(c: &mut Cursor) -> Self {
+        ColorPpuState {
+            cgb_mode:          c.bool(),
+            bg_palettes:       c.array(),
+            obj_palettes:      c.array(),
+            bcps:              c.u8(),
+            ocps:              c.u8(),
+            obj_priority_by_x: c.bool(),
+        }
     }
 }
 
+/// State only a CGB has: speed switching, VRAM DMA and palette RAM (v3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CgbState {
+    pub double_speed: bool,
+    pub speed_switch_armed: bool,
+    /// M-cycles the CPU still sits out for an HDMA transfer or speed switch.
+    pub stall_m_cycles: u16,
+    pub hdma: HdmaState,
+    pub ppu: ColorPpuState,
+}
+
+impl CgbState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.double_speed as u8 | (self.speed_switch_armed as u8) << 1);
+        out.extend_from_slice(&self.stall_m_cycles.to_le_bytes());
+        self.hdma.serialize(out);
+        self.ppu.serialize(out);
+    }
+
+    fn parse(blob: &[u8], offset: usize) -> (Self, usize) {
+        let mut c = Cursor::new(blob, offset);
+        let speed = c.u8();
+        let state = CgbState {
+            double_speed:       speed & 0x01 != 0,
+            speed_switch_armed: speed & 0x02 != 0,
+            stall_m_cycles:     c.u16(),
+            hdma:               HdmaState::parse(&mut c),
+            ppu:                ColorPpuState::parse(&mut c),
+        };
+        (state, CGB_STATE_SIZE)
+    }
+}
+
+/// Mode 3 progress of the pixel FIFO renderer (v3). Also the renderer's own
+/// working state, so saving it is a plain copy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PixelFifoState {
+    /// Screen X of the next pixel to output.
+    pub lx: u8,
+    /// BG FIFO pixels still to drop: SCX fine scroll, or 7 - WX for WX < 7.
+    pub discard: u8,
+    /// Dots left of the discarded first tile fetch.
+    pub startup: u8,
+    pub fetch_step: u8,
+    /// Set on the first dot of a two-dot fetcher step.
+    pub fetch_half: bool,
+    /// Tiles pushed since the line or window started.
+    pub fetch_x: u8,
+    pub tile: u8,
+    /// CGB BG map attributes of `tile`; 0 outside CGB mode.
+    pub tile_attrs: u8,
+    pub tile_lo: u8,
+    pub tile_hi: u8,
+    /// BG FIFO; the `bg_len` pixels left are at the end.
+    pub bg_colors: [u8; 8],
+    pub bg_attrs: [u8; 8],
+    pub bg_len: u8,
+    /// OBJ FIFO as a ring starting at `obj_head`; color 0 is an empty slot.
+    pub obj_colors: [u8; 8],
+    pub obj_attrs: [u8; 8],
+    pub obj_oam: [u8; 8],
+    pub obj_head: u8,
+    /// The fetcher has switched to the window on this line.
+    pub window: bool,
+    /// LY has equalled WY at the start of a line this frame.
+    pub wy_matched: bool,
+    /// OAM indices and X of the objects on this line, in OAM order.
+    pub objects: [u8; 10],
+    pub object_x: [u8; 10],
+    pub object_count: u8,
+    /// Bit per `objects` slot already fetched.
+    pub fetched: u16,
+    /// Slot being fetched, or 0xFF.
+    pub obj_fetch_slot: u8,
+    /// Dots left until the object being fetched is merged.
+    pub obj_fetch_dots: u8,
+    /// One past the screen X of the last BG tile an object fetch waited on;
+    /// 0 if none yet.
+    pub penalty_tile_end: u8,
+}
+
+impl PixelFifoState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[
+            self.lx, self.discard, self.startup, self.fetch_step, self.fetch_half as u8,
+            self.fetch_x, self.tile, self.tile_attrs, self.tile_lo, self.tile_hi,
+        ]);
+        out.extend_from_slice(&self.bg_colors);
+        out.extend_from_slice(&self.bg_attrs);
+        out.push(self.bg_len);
+        out.extend_from_slice(&self.obj_colors);
+        out.extend_from_slice(&self.obj_attrs);
+        out.extend_from_slice(&self.obj_oam);
+        out.push(self.obj_head);
+        out.push(self.window as u8);
+        out.push(self.wy_matched as u8);
+        out.extend_from_slice(&self.objects);
+        out.extend_from_slice(&self.object_x);
+        out.push(self.object_count);
+        out.extend_from_slice(&self.fetched.to_le_bytes());
+        out.push(self.obj_fetch_slot);
+        out.push(self.obj_fetch_dots);
+        out.push(self.penalty_tile_end);
+    }
+
+    /// Returns `Err` for a state the renderer can't resume from, such as a
+    /// FIFO length or index out of range.
+    fn parse(blob: &[u8], offset: usize) -> Result<(Self, usize), &'static str> {
+        let mut c = Cursor::new(blob, offset);
+        let state = PixelFifoState {
+            lx:               c.u8(),
+            discard:          c.u8(),
+            startup:          c.u8(),
+            fetch_step:       c.u8(),
+            fetch_half:       c.bool(),
+            fetch_x:          c.u8(),
+            tile:             c.u8(),
+            tile_attrs:       c.u8(),
+            tile_lo:          c.u8(),
+            tile_hi:          c.u8(),
+            bg_colors:        c.array(),
+            bg_attrs:         c.array(),
+            bg_len:           c.u8(),
+            obj_colors:       c.array(),
+            obj_attrs:        c.array(),
+            obj_oam:          c.array(),
+            obj_head:         c.u8(),
+            window:           c.bool(),
+            wy_matched:       c.bool(),
+            objects:          c.array(),
+            object_x:         c.array(),
+            object_count:     c.u8(),
+            fetched:          c.u16(),
+            obj_fetch_slot:   c.u8(),
+            obj_fetch_dots:   c.u8(),
+            penalty_tile_end: c.u8(),
+        };
+        if !state.is_valid() {
+            return Err("invalid pixel FIFO state");
+        }
+        Ok((state, PIXEL_FIFO_STATE_SIZE))
+    }
+}
+
+/// History of the APU's resampler and output capacitor (v3), so a loaded
+/// state produces the same samples as the run it was saved from. Also the
+/// state a [`Resampler::Nearest`](crate::cpu::peripheral::apu::Resampler)
+/// APU saves, with an idle band-limited resampler.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ApuOutputState {
+    /// Pending band-limited steps, for the `BLIP_WIDTH` samples from
+    /// `blip_head` on.
+    pub blip_ring: [[i32; 2]; BLIP_WIDTH],
+    pub blip_head: u8,
+    pub integrator: [i32; 2],
+    pub level: [i32; 2],
+    pub capacitor: [f32; 2],
+}
+
+impl ApuOutputState {
+    pub fn serialize(&self, out: &mut Vec<u8>) {
+        for value in self.blip_ring.iter().flatten() {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.push(self.blip_head);
+        for value in self.integrator.iter().chain(&self.level) {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in self.capacitor {
+            out.extend_from_slice(&value.to_bits().to_le_bytes());
+        }
+    }
+
+    fn parse(blob: &[u8], offset: usize) -> (Self, usize) {
+        let mut c = Cursor::new(blob, offset);
+        let mut state = ApuOutputState::default();
+        for value in state.blip_ring.iter_mut().flatten() {
+            *value = c.i32();
+        }
+        state.blip_head = c.u8();
+        for value in state.integrator.iter_mut().chain(&mut state.level) {
+            *value = c.i32();
+        }
+        for value in &mut state.capacitor {
+            *value = f32::from_bits(c.u32());
+        }
+        (state, APU_OUTPUT_STATE_SIZE)
+    }
+}
+
+/// The CGB part of [`SaveStateSource`].
+pub struct CgbSource<'a> {
+    pub state: CgbState,
+    /// Color frame the PPU is currently drawing into.
+    pub ppu_framebuffer: &'a [u16; FRAMEBUFFER_SIZE],
+    /// Last completed color frame, as returned by `Sm83::color_framebuffer`.
+    pub front_buffer:    &'a [u16; FRAMEBUFFER_SIZE],
+}
+
+/// Peripheral state captured by RBSS v2. Absent when loading a v1 blob.
+#[derive(Debug, Clone, Copy)]
+pub struct PeripheralState {
+    pub apu:    ApuState,
+    pub serial: SerialState,
+    pub joypad: JoypadState,
+    pub dma:    Option<OamDmaState>,
+}
+
+/// Everything `SaveState::serialize` writes, gathered by `Sm83::save_state`.
+pub struct SaveStateSource<'a> {
+    pub cpu:         CpuState,
+    pub timer:       TimerState,
+    pub ppu:         PpuState,
+    pub peripherals: PeripheralState,
+    /// Frame the PPU is currently drawing into.
+    pub ppu_framebuffer: &'a [u8; FRAMEBUFFER_SIZE],
+    /// Last completed frame, as returned by `Sm83::framebuffer`.
+    pub front_buffer:    &'a [u8; FRAMEBUFFER_SIZE],
+    /// IO writes already applied to memory but not yet routed to peripherals.
+    pub pending_io_writes: &'a [(u16, u8)],
+    /// `None` on DMG.
+    pub cgb:         Option<CgbSource<'a>>,
+    /// `None` with the scanline renderer.
+    pub fifo:        Option<PixelFifoState>,
+    pub apu_output:  ApuOutputState,
+    pub memory:      &'a GameBoyMemory,
+}
+
 // ── SaveState ─────────────────────────────────────────────────────────────────
 
-/// A parsed, validated RBSS v1 save state blob.
+/// A parsed, validated RBSS save state blob (v1 to v3).
 ///
 /// Owns the blob. Large memory regions are zero-copy slices via range indices.
 /// Each component's state is a typed struct applied via that component's
@@ -210,10 +918,18 @@ impl MbcState {
 pub struct SaveState {
     blob: Vec<u8>,
 
+    pub version: u16,
     pub cpu:   CpuState,
     pub timer: TimerState,
     pub ppu:   PpuState,
-    mbc:       Option<MbcState>,
+    /// `None` for v1 blobs.
+    pub peripherals: Option<PeripheralState>,
+    /// `None` for DMG states, including all v1 and v2 blobs.
+    pub cgb: Option<CgbState>,
+    /// `None` unless saved by the pixel FIFO renderer (v3 and later).
+    pub fifo: Option<PixelFifoState>,
+    /// `None` before v3.
+    pub apu_output: Option<ApuOutputState>,
 
     io_range:       Range<usize>,
     ie_offset:      usize,
@@ -221,26 +937,65 @@ pub struct SaveState {
     hram_range:     Range<usize>,
     vram_range:     Range<usize>,
     oam_range:      Range<usize>,
-    cart_ram_range: Option<Range<usize>>,
+    ppu_fb_range:   Option<Range<usize>>,
+    front_fb_range: Option<Range<usize>>,
+    io_write_range: Range<usize>,
+    color_fb_range:    Option<Range<usize>>,
+    color_front_range: Option<Range<usize>>,
+    cgb_banks_range:   Option<Range<usize>>,
+    cart_range:     Range<usize>,
 }
 
 impl SaveState {
-    /// Serialize emulator state into an RBSS v1 blob.
+    /// Serialize emulator state into an RBSS v3 blob.
     ///
     /// Called by `Sm83::save_state` which constructs the typed state structs
     /// from its own fields and passes them here. This function owns the format.
-    pub fn serialize(cpu: CpuState, timer: TimerState, ppu: PpuState, memory: &GameBoyMemory) -> Vec<u8> {
-        let mut out = Vec::with_capacity(MIN_BLOB_SIZE);
+    pub fn serialize(src: SaveStateSource<'_>) -> Vec<u8> {
+        let mut out = Vec::with_capacity(MIN_BLOB_SIZE + V2_FIXED_SIZE);
         out.extend_from_slice(MAGIC);
         out.extend_from_slice(&VERSION.to_le_bytes());
-        cpu.serialize(&mut out);
-        timer.serialize(&mut out);
-        ppu.serialize(&mut out);
-        memory.save_state(&mut out);
+        src.cpu.serialize(&mut out);
+        src.timer.serialize(&mut out);
+        src.ppu.serialize(&mut out);
+        src.memory.save_state(&mut out);
+
+        out.push(src.ppu.prev_stat_line as u8);
+        out.extend_from_slice(src.ppu_framebuffer);
+        out.extend_from_slice(src.front_buffer);
+        src.peripherals.apu.serialize(&mut out);
+        src.peripherals.serial.serialize(&mut out);
+        src.peripherals.joypad.serialize(&mut out);
+        OamDmaState::serialize(src.peripherals.dma, &mut out);
+        out.push(src.pending_io_writes.len() as u8);
+        for &(address, value) in src.pending_io_writes {
+            out.extend_from_slice(&address.to_le_bytes());
+            out.push(value);
+        }
+
+        out.push(src.cgb.is_some() as u8);
+        if let Some(cgb) = src.cgb {
+            cgb.state.serialize(&mut out);
+            for framebuffer in [cgb.ppu_framebuffer, cgb.front_buffer] {
+                for pixel in framebuffer {
+                    out.extend_from_slice(&pixel.to_le_bytes());
+                }
+            }
+            src.memory.save_cgb_state(&mut out);
+        }
+
+        out.push(src.fifo.is_some() as u8);
+        if let Some(fifo) = src.fifo {
+            fifo.serialize(&mut out);
+        }
+
+        src.apu_output.serialize(&mut out);
+
+        src.memory.save_cartridge_state(&mut out);
         out
     }
 
-    /// Parse and validate a raw RBSS v1 blob.
+    /// Parse and validate a raw RBSS v1 to v3 blob.
     ///
     /// Returns `Err` if the blob is too short, has a bad magic, or has an
     /// unsupported version. No emulator state is modified.
@@ -252,15 +1007,15 @@ impl SaveState {
             return Err("invalid save state magic");
         }
         let version = u16::from_le_bytes([blob[MAGIC_SIZE], blob[MAGIC_SIZE + 1]]);
-        if version != VERSION {
+        if !(MIN_VERSION..=VERSION).contains(&version) {
             return Err("unsupported save state version");
         }
 
         let mut cur = HEADER_SIZE;
 
-        let (cpu,   n) = CpuState::parse(&blob, cur);   cur += n;
-        let (timer, n) = TimerState::parse(&blob, cur);  cur += n;
-        let (ppu,   n) = PpuState::parse(&blob, cur);    cur += n;
+        let (cpu,     n) = CpuState::parse(&blob, cur);   cur += n;
+        let (timer,   n) = TimerState::parse(&blob, cur);  cur += n;
+        let (mut ppu, n) = PpuState::parse(&blob, cur);    cur += n;
 
         let io_range  = cur..cur + IO_REGS_SIZE;          cur += IO_REGS_SIZE;
         let ie_offset = cur;                              cur += IE_SIZE;
@@ -269,43 +1024,270 @@ impl SaveState {
         let vram_range = cur..cur + VRAM_SIZE;            cur += VRAM_SIZE;
         let oam_range  = cur..cur + OAM_SIZE;             cur += OAM_SIZE;
 
-        let mbc = if cur + MBC_SIZE <= blob.len() {
-            let (m, n) = MbcState::parse(&blob, cur);    cur += n;
-            Some(m)
-        } else {
-            None
-        };
+        let mut peripherals    = None;
+        let mut ppu_fb_range   = None;
+        let mut front_fb_range = None;
+        let mut io_write_range = cur..cur;
 
-        let cart_ram_range = if cur + CART_RAM_LEN_SIZE <= blob.len() {
-            let ram_len = u16::from_le_bytes([blob[cur], blob[cur + 1]]) as usize;
-            cur += CART_RAM_LEN_SIZE;
-            if ram_len > 0 && cur + ram_len <= blob.len() {
-                Some(cur..cur + ram_len)
-            } else {
-                None
+        if version >= 2 {
+            if blob.len() < cur + V2_FIXED_SIZE {
+                return Err("save state blob too short");
             }
-        } else {
-            None
-        };
+            ppu.prev_stat_line = blob[cur] != 0;          cur += 1;
+            ppu_fb_range   = Some(cur..cur + FRAMEBUFFER_SIZE); cur += FRAMEBUFFER_SIZE;
+            front_fb_range = Some(cur..cur + FRAMEBUFFER_SIZE); cur += FRAMEBUFFER_SIZE;
+
+            let (apu,    n) = ApuState::parse(&blob, cur);     cur += n;
+            let (serial, n) = SerialState::parse(&blob, cur);  cur += n;
+            let (joypad, n) = JoypadState::parse(&blob, cur);  cur += n;
+            let (dma,    n) = OamDmaState::parse(&blob, cur);  cur += n;
+            peripherals = Some(PeripheralState { apu, serial, joypad, dma });
+
+            let writes = blob[cur] as usize;              cur += 1;
+            if blob.len() < cur + writes * IO_WRITE_SIZE {
+                return Err("save state blob too short");
+            }
+            io_write_range = cur..cur + writes * IO_WRITE_SIZE;
+            cur += writes * IO_WRITE_SIZE;
+        }
+
+        let mut cgb               = None;
+        let mut color_fb_range    = None;
+        let mut color_front_range = None;
+        let mut cgb_banks_range   = None;
+
+        if version >= 3 {
+            let is_cgb = *blob.get(cur).ok_or("save state blob too short")? != 0;
+            cur += 1;
+            if is_cgb {
+                if blob.len() < cur + CGB_STATE_SIZE + 2 * COLOR_FRAMEBUFFER_SIZE {
+                    return Err("save state blob too short");
+                }
+                let (state, n) = CgbState::parse(&blob, cur);  cur += n;
+                color_fb_range    = Some(cur..cur + COLOR_FRAMEBUFFER_SIZE); cur += COLOR_FRAMEBUFFER_SIZE;
+                color_front_range = Some(cur..cur + COLOR_FRAMEBUFFER_SIZE); cur += COLOR_FRAMEBUFFER_SIZE;
+                if state.ppu.cgb_mode {
+                    if blob.len() < cur + CGB_BANKS_STATE_SIZE {
+                        return Err("save state blob too short");
+                    }
+                    cgb_banks_range = Some(cur..cur + CGB_BANKS_STATE_SIZE);
+                    cur += CGB_BANKS_STATE_SIZE;
+                }
+                cgb = Some(state);
+            }
+        }
+
+        let mut fifo = None;
+        if version >= 3 {
+            let has_fifo = *blob.get(cur).ok_or("save state blob too short")? != 0;
+            cur += 1;
+            if has_fifo {
+                if blob.len() < cur + PIXEL_FIFO_STATE_SIZE {
+                    return Err("save state blob too short");
+                }
+                let (state, n) = PixelFifoState::parse(&blob, cur)?;  cur += n;
+                fifo = Some(state);
+            }
+        }
+
+        let mut apu_output = None;
+        if version >= 3 {
+            if blob.len() < cur + APU_OUTPUT_STATE_SIZE {
+                return Err("save state blob too short");
+            }
+            let (state, n) = ApuOutputState::parse(&blob, cur);  cur += n;
+            apu_output = Some(state);
+        }
+
+        // MBC registers (cartridge-specific length) + cart RAM length + cart RAM
+        let cart_range = cur..blob.len();
 
         Ok(SaveState {
-            blob, cpu, timer, ppu, mbc,
+            blob, version, cpu, timer, ppu, peripherals, cgb, fifo, apu_output,
             io_range, ie_offset, wram_range, hram_range, vram_range, oam_range,
-            cart_ram_range,
+            ppu_fb_range, front_fb_range, io_write_range,
+            color_fb_range, color_front_range, cgb_banks_range, cart_range,
         })
     }
 
     // ── Accessors ─────────────────────────────────────────────────────────────
 
-    pub fn mbc(&self) -> Option<&MbcState>  { self.mbc.as_ref() }
-
     pub fn io_registers(&self) -> &[u8]     { &self.blob[self.io_range.clone()] }
     pub fn ie(&self) -> u8                  { self.blob[self.ie_offset] }
     pub fn wram(&self) -> &[u8]             { &self.blob[self.wram_range.clone()] }
     pub fn hram(&self) -> &[u8]             { &self.blob[self.hram_range.clone()] }
     pub fn vram(&self) -> &[u8]             { &self.blob[self.vram_range.clone()] }
     pub fn oam(&self)  -> &[u8]             { &self.blob[self.oam_range.clone()] }
-    pub fn cart_ram(&self) -> Option<&[u8]> {
-        self.cart_ram_range.as_ref().map(|r| &self.blob[r.clone()])
+
+    /// Frame the PPU was drawing into when the state was saved (v2 only).
+    pub fn ppu_framebuffer(&self) -> Option<&[u8]> {
+        self.ppu_fb_range.clone().map(|r| &self.blob[r])
+    }
+
+    /// Last completed frame when the state was saved (v2 only).
+    pub fn front_buffer(&self) -> Option<&[u8]> {
+        self.front_fb_range.clone().map(|r| &self.blob[r])
+    }
+
+    /// IO writes the CPU issued but peripherals had not yet observed (v2 only;
+    /// empty for v1).
+    pub fn pending_io_writes(&self) -> impl Iterator<Item = (u16, u8)> + '_ {
+        self.blob[self.io_write_range.clone()]
+            .chunks_exact(IO_WRITE_SIZE)
+            .map(|w| (u16::from_le_bytes([w[0], w[1]]), w[2]))
+    }
+
+    /// Color frame the PPU was drawing into, as little-endian RGB555 (CGB only).
+    pub fn color_framebuffer(&self) -> Option<&[u8]> {
+        self.color_fb_range.clone().map(|r| &self.blob[r])
+    }
+
+    /// Last completed color frame, as little-endian RGB555 (CGB only).
+    pub fn color_front_buffer(&self) -> Option<&[u8]> {
+        self.color_front_range.clone().map(|r| &self.blob[r])
+    }
+
+    /// VRAM/WRAM bank section as written by `GameBoyMemory::save_cgb_state`
+    /// (CGB mode only).
+    pub fn cgb_banks(&self) -> Option<&[u8]> {
+        self.cgb_banks_range.clone().map(|r| &self.blob[r])
+    }
+
+    /// Raw cartridge section: MBC registers as written by
+    /// `Cartridge::save_mbc_state`, then the length-prefixed cart RAM.
+    pub fn cartridge(&self) -> &[u8]        { &self.blob[self.cart_range.clone()] }
+
+    /// Width of the cart RAM length prefix in the cartridge section.
+    /// v1 used a u16, which can't describe 64 KiB or 128 KiB of RAM.
+    pub fn cart_ram_len_size(&self) -> usize {
+        if self.version >= 2 { size_of::<u32>() } else { size_of::<u16>() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apu_state_size_matches_serialized_length() {
+        let state = ApuState {
+            powered: true,
+            prev_div_bit: false,
+            frame_sequencer_step: 3,
+            wave_2mhz_phase: true,
+            channel1: SquareChannelState::default(),
+            sweep: SweepUnitState::default(),
+            channel2: SquareChannelState::default(),
+            channel3: WaveChannelState::default(),
+            channel4: NoiseChannelState::default(),
+            regs: [0; APU_REGS_SIZE],
+            sample_acc: 0,
+        };
+        let mut out = Vec::new();
+        state.serialize(&mut out);
+        assert_eq!(out.len(), APU_STATE_SIZE);
+    }
+
+    #[test]
+    fn apu_state_roundtrips_through_bytes() {
+        let mut wave_ram = [0u8; WAVE_RAM_SIZE];
+        wave_ram[5] = 0xA5;
+        let state = ApuState {
+            powered: true,
+            prev_div_bit: true,
+            frame_sequencer_step: 6,
+            wave_2mhz_phase: false,
+            channel1: SquareChannelState { length_counter: 42, envelope_timer: 3, ..Default::default() },
+            sweep: SweepUnitState { shadow_frequency: 0x6FF, negate_used: true, ..Default::default() },
+            channel2: SquareChannelState { duty_position: 5, ..Default::default() },
+            channel3: WaveChannelState { wave_ram, position: 17, ..Default::default() },
+            channel4: NoiseChannelState { lfsr: 0x7ABC, ..Default::default() },
+            regs: [0x11; APU_REGS_SIZE],
+            sample_acc: 0x0001_2345,
+        };
+        let mut out = Vec::new();
+        state.serialize(&mut out);
+        let (parsed, n) = ApuState::parse(&out, 0);
+        assert_eq!(n, out.len());
+        assert_eq!(parsed.frame_sequencer_step, 6);
+        assert_eq!(parsed.channel1.length_counter, 42);
+        assert_eq!(parsed.channel1.envelope_timer, 3);
+        assert_eq!(parsed.sweep.shadow_frequency, 0x6FF);
+        assert!(parsed.sweep.negate_used);
+        assert_eq!(parsed.channel2.duty_position, 5);
+        assert_eq!(parsed.channel3.wave_ram[5], 0xA5);
+        assert_eq!(parsed.channel3.position, 17);
+        assert_eq!(parsed.channel4.lfsr, 0x7ABC);
+        assert_eq!(parsed.sample_acc, 0x0001_2345);
+    }
+
+    #[test]
+    fn pixel_fifo_state_roundtrips_through_bytes() {
+        let state = PixelFifoState {
+            lx: 93,
+            fetch_half: true,
+            bg_colors: [0, 1, 2, 3, 3, 2, 1, 0],
+            bg_len: 5,
+            obj_oam: [39; 8],
+            wy_matched: true,
+            object_x: [168; 10],
+            object_count: 10,
+            fetched: 0x02FF,
+            obj_fetch_slot: 9,
+            obj_fetch_dots: 4,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        state.serialize(&mut out);
+        assert_eq!(out.len(), PIXEL_FIFO_STATE_SIZE);
+        assert_eq!(PixelFifoState::parse(&out, 0), Ok((state, PIXEL_FIFO_STATE_SIZE)));
+    }
+
+    #[test]
+    fn pixel_fifo_state_rejects_out_of_range_fields() {
+        let valid = PixelFifoState { lx: 40, bg_len: 8, object_count: 3, obj_fetch_slot: 2, obj_fetch_dots: 6, ..Default::default() };
+        let corrupt = [
+            PixelFifoState { bg_len: 0xFF, ..valid },
+            PixelFifoState { obj_head: 8, ..valid },
+            PixelFifoState { object_count: 11, ..valid },
+            PixelFifoState { obj_fetch_slot: 3, ..valid },
+            PixelFifoState { obj_fetch_dots: 0, ..valid },
+            PixelFifoState { fetch_step: 4, ..valid },
+            PixelFifoState { discard: 8, ..valid },
+            PixelFifoState { lx: 161, ..valid },
+        ];
+        let mut out = Vec::new();
+        valid.serialize(&mut out);
+        assert!(PixelFifoState::parse(&out, 0).is_ok());
+        for state in corrupt {
+            out.clear();
+            state.serialize(&mut out);
+            assert_eq!(PixelFifoState::parse(&out, 0), Err("invalid pixel FIFO state"), "{state:?}");
+        }
+    }
+
+    #[test]
+    fn apu_output_state_roundtrips_through_bytes() {
+        let mut state = ApuOutputState { blip_head: 11, integrator: [-5, 1 << 24], ..Default::default() };
+        state.blip_ring[3] = [i32::MIN, 77];
+        state.level = [480, 0];
+        state.capacitor = [-1234.5, 0.25];
+        let mut out = Vec::new();
+        state.serialize(&mut out);
+        assert_eq!(out.len(), APU_OUTPUT_STATE_SIZE);
+        assert_eq!(ApuOutputState::parse(&out, 0), (state, APU_OUTPUT_STATE_SIZE));
+    }
+
+    #[test]
+    fn serial_and_dma_encode_absence() {
+        let mut out = Vec::new();
+        SerialState { cycles_remaining: None }.serialize(&mut out);
+        OamDmaState::serialize(Some(OamDmaState { source: 0xC100, progress: 37 }), &mut out);
+        assert_eq!(out.len(), SERIAL_STATE_SIZE + DMA_STATE_SIZE);
+        assert_eq!(SerialState::parse(&out, 0).0.cycles_remaining, None);
+        assert_eq!(
+            OamDmaState::parse(&out, SERIAL_STATE_SIZE).0,
+            Some(OamDmaState { source: 0xC100, progress: 37 }),
+        );
     }
 }
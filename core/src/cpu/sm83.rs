@@ -1,5 +1,6 @@
 use alloc::{boxed::Box, format, vec::Vec};
 
+use super::cheats::{Cheat, CheatCode, CheatError, CheatList};
 use super::cpu::{Cpu, CpuError};
 use super::instructions::adc::opcode::Adc;
 use super::instructions::add::opcode::{Add16, Add8, AddSP16};
@@ -30,25 +31,37 @@ use super::operations::inc_dec::{dec_u8, inc_u8};
 use super::operations::logic::{and_u8, or_u8, xor_u8};
 use super::operations::misc::daa_u8;
 use super::operations::sub::*;
+use super::peripheral::hdma::{
+    HdmaPeripheral, HDMA1_ADDR, HDMA4_ADDR, HDMA5_ADDR, HDMA_BLOCK_SIZE,
+};
 use super::peripheral::apu::{
-    ApuPeripheral, NR10_ADDR, NR52_ADDR, WAVE_RAM_START, WAVE_RAM_END,
+    ApuPeripheral, Resampler, NR10_ADDR, NR52_ADDR, WAVE_RAM_START, WAVE_RAM_END,
 };
 use super::peripheral::joypad::{Button, JoypadPeripheral, JOYP_ADDR, JOYPAD_INTERRUPT_BIT};
-use super::peripheral::serial::{SerialPort, SERIAL_INTERRUPT_BIT};
+use super::peripheral::serial::{LinkCable, SerialPort, SERIAL_INTERRUPT_BIT};
 use super::peripheral::ppu::{
-    PpuInput, PpuPeripheral, FRAMEBUFFER_SIZE, LCDC_ADDR, STAT_ADDR, SCY_ADDR, SCX_ADDR,
-    LY_ADDR, LYC_ADDR, BGP_ADDR, OBP0_ADDR, OBP1_ADDR, WY_ADDR, WX_ADDR,
-    VBLANK_INTERRUPT_BIT, STAT_INTERRUPT_BIT,
+    CompatPalette, OamCorruption, PpuInput, PpuPeripheral, PpuRenderer, CYCLES_PER_FRAME, FRAMEBUFFER_SIZE, LCDC_ADDR, STAT_ADDR,
+    SCY_ADDR, SCX_ADDR, LY_ADDR, LYC_ADDR, BGP_ADDR, OBP0_ADDR, OBP1_ADDR, WY_ADDR, WX_ADDR,
+    BCPS_ADDR, OPRI_ADDR, VBLANK_INTERRUPT_BIT, STAT_INTERRUPT_BIT, palette::LAYER_PLANE_SIZE,
 };
 use super::peripheral::timer::{
     TimerInput, TimerPeripheral, DIV_ADDR, TIMA_ADDR, TIMER_INTERRUPT_BIT, TMA_ADDR, TAC_ADDR,
 };
+#[cfg(feature = "debugger")]
+use super::debugger::{Access, BusWatch};
 #[cfg(feature = "perf")]
 use super::perf::{cyccnt, Sm83PerfRecorder};
 use super::registers::{Flags, Registers};
-use super::save_state::{CpuState, SaveState};
+use super::save_state::{
+    CgbSource, CgbState, CpuState, OamDmaState, PeripheralState, SaveState, SaveStateSource,
+};
 
-use crate::memory::memory::{BusEvent, Error as MemoryError, GameBoyMemory, Memory as MemoryBus};
+use crate::memory::cartridge::CartridgeError;
+use crate::memory::header::{CgbFlag, CGB_FLAG_ADDR};
+use crate::memory::memory::{
+    BusEvent, Error as MemoryError, GameBoyMemory, Memory as MemoryBus, BOOT_ROM_DISABLE_ADDR,
+    BOOT_ROM_SIZE, SVBK_ADDR, VBK_ADDR,
+};
 #[cfg(feature = "perf")]
 pub use super::perf::Sm83PerfProfile;
 
@@ -72,6 +85,47 @@ const DMA_ADDR: u16 = 0xFF46;
 const IE_ADDR: u16 = 0xFFFF;
 const SB_ADDR: u16 = 0xFF01;
 const SC_ADDR: u16 = 0xFF02;
+/// CGB speed switch register.
+const KEY1_ADDR: u16 = 0xFF4D;
+
+/// CGB IO registers, handled by [`Sm83::write_cgb_register`].
+const CGB_REGISTERS: [u16; 13] = [
+    KEY1_ADDR, VBK_ADDR, HDMA1_ADDR, HDMA1_ADDR + 1, HDMA1_ADDR + 2, HDMA4_ADDR, HDMA5_ADDR,
+    BCPS_ADDR, BCPS_ADDR + 1, BCPS_ADDR + 2, BCPS_ADDR + 3, OPRI_ADDR, SVBK_ADDR,
+];
+
+/// M-cycles the CPU is stopped for while switching speed (Pan Docs: 2050).
+const SPEED_SWITCH_M_CYCLES: u16 = 2050;
+
+/// Hardware model being emulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// Original Game Boy.
+    Dmg,
+    /// Game Boy Color. Runs DMG-only cartridges in DMG-compatibility mode.
+    Cgb,
+}
+
+impl Model {
+    /// The model a cartridge asks for: CGB for CGB-enhanced and CGB-only
+    /// cartridges, DMG for everything else.
+    pub fn for_cgb_flag(flag: CgbFlag) -> Self {
+        match flag {
+            CgbFlag::DmgOnly => Model::Dmg,
+            CgbFlag::CgbEnhanced | CgbFlag::CgbOnly => Model::Cgb,
+        }
+    }
+}
+
+/// Outcome of [`Sm83::run_cycles`], [`Sm83::run_until_vblank`] and [`Sm83::run_frame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunResult {
+    /// T-cycles executed. Instructions always run to completion, so this can
+    /// exceed the requested budget by up to one instruction.
+    pub cycles: u64,
+    /// Whether a new frame reached [`Sm83::framebuffer`] during the run.
+    pub frame: bool,
+}
 
 /// Snapshot of CPU and key hardware state passed to trace hooks.
 #[cfg(feature = "trace")]
@@ -184,23 +238,49 @@ pub struct Sm83 {
     joypad: JoypadPeripheral,
     ime: ImeState,
     halted: bool,
+    /// STOP mode: the system clock is stopped until a selected joypad line goes low.
+    stopped: bool,
     /// Cycle counter incremented by 4 on each M-cycle (bus_read/bus_write/tick_cycle).
     cycle_counter: u64,
     /// Pending OAM DMA transfer. When Some, holds the source page and number of
     /// bytes already copied. Each M-cycle advances the transfer by one byte.
     dma: Option<DmaState>,
+    /// Set when the PPU enters VBlank; cleared at the start of each `run_*` call.
+    vblank_entered: bool,
+    /// Set when the PPU enters VBlank; GameShark writes are applied and this
+    /// cleared once the current instruction finishes.
+    ram_cheats_due: bool,
     /// Stable front buffer: snapshotted from the PPU at VBlank so callers always
     /// read a fully-rendered frame rather than one mid-render.
     front_buffer: [u8; FRAMEBUFFER_SIZE],
+    /// Layer plane snapshotted alongside `front_buffer`.
+    front_layers: [u8; LAYER_PLANE_SIZE],
     /// Accumulates APU T-cycles between timing-sensitive boundaries so normal
     /// M-cycles can batch one `apu.tick(...)` per instruction instead of per cycle.
     pending_apu_cycles: PendingApuCycles,
     /// CPU MMIO writes are routed here and applied on the next M-cycle.
     pending_bus_events: Vec<BusEvent>,
+    model: Model,
+    /// CGB double-speed mode: the CPU, timer and serial port run at twice the
+    /// rate of the PPU and APU.
+    double_speed: bool,
+    /// KEY1 bit 0: the next STOP switches speed instead of stopping.
+    speed_switch_armed: bool,
+    hdma: HdmaPeripheral,
+    /// M-cycles the CPU sits out while HDMA copies or the speed switch settles.
+    /// The rest of the system keeps running.
+    stall_m_cycles: u16,
+    /// Color counterpart of `front_buffer`; `Some` only on CGB.
+    front_color_buffer: Option<Box<[u16; FRAMEBUFFER_SIZE]>>,
+    /// GameShark and Game Genie codes; see [`super::cheats`].
+    cheats: CheatList,
     pub cache: Sm83Cache,
     /// Per-instruction trace hook, enabled by the `trace` feature.
     #[cfg(feature = "trace")]
     trace_hook: Option<Box<dyn FnMut(TraceEvent<'_>)>>,
+    /// Watchpoints armed by the debugger, checked on every bus access.
+    #[cfg(feature = "debugger")]
+    bus_watch: BusWatch,
     #[cfg(feature = "perf")]
     perf: Sm83PerfRecorder,
 }
@@ -228,19 +308,34 @@ impl Sm83 {
             joypad,
             ime: ImeState::Disabled,
             halted: false,
+            stopped: false,
             cycle_counter: 0,
             dma: None,
+            vblank_entered: false,
+            ram_cheats_due: false,
             front_buffer: [0u8; FRAMEBUFFER_SIZE],
+            front_layers: [0u8; LAYER_PLANE_SIZE],
             pending_apu_cycles: PendingApuCycles::default(),
             pending_bus_events: Vec::with_capacity(4),
+            model: Model::Dmg,
+            double_speed: false,
+            speed_switch_armed: false,
+            hdma: HdmaPeripheral::new(),
+            stall_m_cycles: 0,
+            front_color_buffer: None,
+            cheats: CheatList::new(),
             cache: Sm83Cache::default(),
             #[cfg(feature = "trace")]
             trace_hook: None,
+            #[cfg(feature = "debugger")]
+            bus_watch: BusWatch::default(),
             #[cfg(feature = "perf")]
             perf: Sm83PerfRecorder::default(),
         };
         // Seed JOYP with no buttons pressed (all lines high).
         sm83.memory.write_io(JOYP_ADDR, sm83.joypad.read());
+        // No boot ROM is mapped unless `with_boot_rom` supplies one.
+        sm83.memory.write_io(BOOT_ROM_DISABLE_ADDR, 0xFF);
         // Seed IO memory with initial APU register read values so games
         // reading registers before any write see correct masked values.
         for addr in NR10_ADDR..=NR52_ADDR {
@@ -258,23 +353,129 @@ impl Sm83 {
         sm83
     }
 
+    /// Build a CPU around a cartridge ROM image, detecting the MBC and the
+    /// hardware model (see [`Model::for_cgb_flag`]) from its header.
+    ///
+    /// Fails without touching any emulator state if the ROM is not a cartridge
+    /// this emulator can run.
+    pub fn from_rom(rom: Vec<u8>, opcode_decoder: Box<dyn Decoder>) -> Result<Self, CartridgeError> {
+        let flag = CgbFlag::from_byte(rom.get(CGB_FLAG_ADDR).copied().unwrap_or(0));
+        let memory = GameBoyMemory::with_rom(rom)?;
+        Ok(Self::new(Box::new(memory), opcode_decoder).with_model(Model::for_cgb_flag(flag)))
+    }
+
+    /// Builder method to select the hardware model. A CGB runs cartridges
+    /// whose header sets the CGB flag in CGB mode and anything else in
+    /// DMG-compatibility mode, colored with [`CompatPalette::DEFAULT`].
+    pub fn with_model(mut self, model: Model) -> Self {
+        self.model = model;
+        if model == Model::Cgb {
+            let flag = CgbFlag::from_byte(self.memory.read_fast(CGB_FLAG_ADDR as u16));
+            let cgb_mode = flag != CgbFlag::DmgOnly;
+            if cgb_mode {
+                self.memory.enable_cgb_mode();
+            }
+            self.ppu.enable_color(cgb_mode);
+            self.front_color_buffer = Some(Box::new([0x7FFF; FRAMEBUFFER_SIZE]));
+            self.sync_cgb_registers();
+        }
+        self
+    }
+
+    /// Builder method to replace the DMG-compatibility palette. The CGB boot
+    /// ROM picks one from the title of Nintendo-published games, or the user
+    /// from a button combination at boot. No-op unless a DMG cartridge is
+    /// running on a CGB.
+    pub fn with_compat_palette(mut self, palette: &CompatPalette) -> Self {
+        if self.model == Model::Cgb && !self.cgb_mode() {
+            self.ppu.load_compat_palette(palette);
+        }
+        self
+    }
+
+    /// Builder method to select the PPU renderer. The default scanline
+    /// renderer is the fast one; [`PpuRenderer::Fifo`] draws dot by dot for
+    /// games and demos that change registers mid-line.
+    pub fn with_ppu_renderer(mut self, renderer: PpuRenderer) -> Self {
+        self.ppu.set_renderer(renderer);
+        self
+    }
+
+    /// Builder method to set the audio output rate in Hz; see
+    /// [`Sm83::set_sample_rate`].
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.apu.set_sample_rate(sample_rate);
+        self
+    }
+
+    /// Builder method to select the audio resampler. The default
+    /// band-limited one avoids aliasing; [`Resampler::Nearest`] is cheaper
+    /// for slow hosts.
+    pub fn with_resampler(mut self, resampler: Resampler) -> Self {
+        self.apu.set_resampler(resampler);
+        self
+    }
+
+    /// Audio output rate in Hz, 48,000 unless changed.
+    pub fn sample_rate(&self) -> u32 {
+        self.apu.sample_rate()
+    }
+
+    /// Change the audio output rate to match the host's audio device.
+    /// Clamped to 8–192 kHz; samples not yet drained keep the old rate.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.apu.set_sample_rate(sample_rate);
+    }
+
+    /// Stretch (positive) or squeeze the audio by a small fraction, e.g.
+    /// 0.002 for 0.2% more samples per emulated second. Used by
+    /// [`AvSync`](super::av_sync::AvSync) for dynamic rate control; clamped
+    /// to ±5%.
+    pub fn set_audio_rate_skew(&mut self, skew: f32) {
+        self.apu.set_rate_skew(skew);
+    }
+
     /// Press or release a button. Fires the joypad interrupt if the button is
     /// newly pressed and its select line is active.
     pub fn set_button(&mut self, button: Button, pressed: bool) {
         let interrupt = self.joypad.set_button(button, pressed);
         self.memory.write_io(JOYP_ADDR, self.joypad.read());
         if interrupt {
+            // The same high-to-low transition that requests the interrupt
+            // restarts the clock after STOP, regardless of IE and IME.
+            self.stopped = false;
             let if_val = self.memory.read_io(IF_ADDR);
             self.memory.write_io(IF_ADDR, if_val | (1 << JOYPAD_INTERRUPT_BIT));
         }
     }
 
+    /// Pressed buttons as a mask, bit `n` for [`Button::ALL`]`[n]`.
+    pub fn buttons(&self) -> u8 {
+        self.joypad.pressed()
+    }
+
+    /// Press exactly the buttons in `mask` (see [`Sm83::buttons`]) and
+    /// release the rest.
+    pub fn set_buttons(&mut self, mask: u8) {
+        for button in Button::ALL {
+            self.set_button(button, mask & button.mask() != 0);
+        }
+    }
+
     /// Subscribe a peripheral to receive bus events for the given address range.
     /// Returns all bytes captured by the serial port (SB transfers via SC).
     pub fn serial_output(&self) -> &[u8] {
         self.serial.output()
     }
 
+    /// Plug a link cable into the serial port, or unplug it with `None`.
+    /// Returns the cable that was connected before.
+    pub fn set_link_cable(&mut self, cable: Option<Box<dyn LinkCable>>) -> Option<Box<dyn LinkCable>> {
+        let sc = self.memory.read_io(SC_ADDR);
+        let sb = self.memory.read_io(SB_ADDR);
+        self.serial.set_cable(cable, sc, sb)
+    }
+
     // Retrieve a copy of the CPU registers.
     pub fn registers(&self) -> Registers {
         self.registers.clone()
@@ -290,6 +491,27 @@ impl Sm83 {
         self.halted
     }
 
+    /// Returns true if the CPU is in STOP mode (waiting for a joypad press).
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// The hardware model being emulated.
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Returns true when running a CGB cartridge on a CGB, as opposed to DMG
+    /// hardware or DMG-compatibility mode.
+    pub fn cgb_mode(&self) -> bool {
+        self.memory.cgb_mode()
+    }
+
+    /// Returns true if the CGB is in double-speed mode.
+    pub fn double_speed(&self) -> bool {
+        self.double_speed
+    }
+
     /// Install a per-instruction trace hook (only available with `--features trace`).
     /// The hook is called after every instruction with a snapshot of CPU and hardware state.
     #[cfg(feature = "trace")]
@@ -300,6 +522,30 @@ impl Sm83 {
         self.trace_hook = Some(Box::new(hook));
     }
 
+    #[cfg(feature = "debugger")]
+    pub(crate) fn bus_watch_mut(&mut self) -> &mut BusWatch {
+        &mut self.bus_watch
+    }
+
+    /// Whether the next `tick` will execute an instruction, rather than idle
+    /// in HALT/STOP or only dispatch an interrupt.
+    #[cfg(feature = "debugger")]
+    pub(crate) fn will_execute(&self) -> bool {
+        !self.stopped
+            && self.stall_m_cycles == 0
+            && (!self.halted || (self.has_pending_interrupt() && self.ime != ImeState::Enabled))
+    }
+
+    #[cfg(feature = "debugger")]
+    pub(crate) fn vblank_entered(&self) -> bool {
+        self.vblank_entered
+    }
+
+    #[cfg(feature = "debugger")]
+    pub(crate) fn clear_vblank_entered(&mut self) {
+        self.vblank_entered = false;
+    }
+
     /// Read a byte from the memory bus (for test/debug access).
     pub fn read_memory(&self, address: u16) -> Result<u8, MemoryError> {
         self.memory.read(address)
@@ -310,6 +556,11 @@ impl Sm83 {
         self.memory.current_rom_bank()
     }
 
+    /// Returns whether the cartridge rumble motor is currently driven.
+    pub fn rumble_active(&self) -> bool {
+        self.memory.rumble_active()
+    }
+
     /// Returns a reference to the PPU framebuffer (160x144 pixels, 2-bit shade per pixel).
     pub fn cycle_counter(&self) -> u64 {
         self.cycle_counter
@@ -319,12 +570,189 @@ impl Sm83 {
         &self.front_buffer
     }
 
+    /// Which layer each pixel of [`Self::framebuffer`] came from, packed 2
+    /// bits per pixel, so a [`Palette`] can color objects separately. Only
+    /// kept on DMG; save states don't carry it, so it reads as all
+    /// background until the first frame after a load.
+    ///
+    /// [`Palette`]: super::peripheral::ppu::palette::Palette
+    pub fn framebuffer_layers(&self) -> &[u8; LAYER_PLANE_SIZE] {
+        &self.front_layers
+    }
+
+    /// The last completed frame as RGB555 (bits 0–4 red, 5–9 green, 10–14
+    /// blue), or `None` on DMG. Compatibility mode colors DMG games through
+    /// the compatibility palette, so this is what a CGB would display.
+    pub fn color_framebuffer(&self) -> Option<&[u16; FRAMEBUFFER_SIZE]> {
+        self.front_color_buffer.as_deref()
+    }
+
+    // ── Frame stepping ──────────────────────────────────────────────────────────
+
+    /// Run whole instructions until at least `cycles` T-cycles have elapsed.
+    pub fn run_cycles(&mut self, cycles: u64) -> Result<RunResult, CpuError> {
+        self.run_for(cycles, false)
+    }
+
+    /// Run until the PPU enters VBlank, stopping after the instruction during
+    /// which it happened. With the LCD off (or the CPU in STOP) there is no
+    /// VBlank, so this gives up after one frame period with `frame == false`.
+    pub fn run_until_vblank(&mut self) -> Result<RunResult, CpuError> {
+        self.run_for(CYCLES_PER_FRAME, true)
+    }
+
+    /// Run one displayable frame. Like [`Self::run_until_vblank`], except a
+    /// frame period with the LCD off also counts as a frame: the front buffer
+    /// is blanked, matching the white screen of a DMG with its LCD disabled.
+    pub fn run_frame(&mut self) -> Result<RunResult, CpuError> {
+        let result = self.run_until_vblank()?;
+        if !result.frame && self.cache.lcdc & 0x80 == 0 {
+            self.front_buffer.fill(0);
+            self.front_layers.fill(0);
+            if let Some(front) = self.front_color_buffer.as_deref_mut() {
+                front.fill(0x7FFF);
+            }
+            return Ok(RunResult { frame: true, ..result });
+        }
+        Ok(result)
+    }
+
+    /// Apply `buttons` (see [`Sm83::buttons`]) and run one frame. This is
+    /// the point where input movies apply each frame's joypad state: input
+    /// that only changes between frames replays exactly from the same
+    /// starting state.
+    pub fn run_frame_with_input(&mut self, buttons: u8) -> Result<RunResult, CpuError> {
+        self.set_buttons(buttons);
+        self.run_frame()
+    }
+
+    /// Run `self` and `other` side by side until each has run at least
+    /// `cycles` T-cycles, always stepping whichever is behind. Two Game Boys
+    /// joined by an [`InProcessCable`](super::peripheral::serial::InProcessCable)
+    /// then see each transfer within one instruction of each other.
+    pub fn run_linked(&mut self, other: &mut Sm83, cycles: u64) -> Result<(), CpuError> {
+        let (start, other_start) = (self.cycle_counter, other.cycle_counter);
+        loop {
+            let ran = self.cycle_counter - start;
+            let other_ran = other.cycle_counter - other_start;
+            if ran >= cycles && other_ran >= cycles {
+                return Ok(());
+            }
+            if ran <= other_ran {
+                self.tick()?;
+            } else {
+                other.tick()?;
+            }
+        }
+    }
+
+    fn run_for(&mut self, budget: u64, stop_at_vblank: bool) -> Result<RunResult, CpuError> {
+        let start = self.cycle_counter;
+        self.vblank_entered = false;
+        while self.cycle_counter - start < budget {
+            self.tick()?;
+            if stop_at_vblank && self.vblank_entered {
+                break;
+            }
+        }
+        Ok(RunResult { cycles: self.cycle_counter - start, frame: self.vblank_entered })
+    }
+
+    // ── Cheats ──────────────────────────────────────────────────────────────────
+
+    /// Parse and enable a GameShark or Game Genie code. Returns its index.
+    ///
+    /// GameShark codes write RAM at the end of the instruction during which
+    /// the PPU enters VBlank, as the real device does from the VBlank
+    /// interrupt, however the CPU is being stepped. With the LCD off there is
+    /// no VBlank, so they are not applied. A code for an external RAM bank
+    /// the cartridge doesn't have is rejected. Game Genie codes patch ROM
+    /// reads immediately.
+    pub fn add_cheat(&mut self, code: &str) -> Result<usize, CheatError> {
+        if let CheatCode::GameShark { bank: Some(bank), address: 0xA000..=0xBFFF, .. } = CheatCode::parse(code)? {
+            let ram_len = self.memory.external_ram().map_or(0, <[u8]>::len);
+            if bank as usize * 0x2000 >= ram_len {
+                return Err(CheatError::NoSuchRamBank(bank));
+            }
+        }
+        let index = self.cheats.add(code)?;
+        self.sync_rom_patches();
+        Ok(index)
+    }
+
+    /// Remove the cheat at `index`; later cheats move down by one.
+    pub fn remove_cheat(&mut self, index: usize) -> Option<Cheat> {
+        let removed = self.cheats.remove(index);
+        self.sync_rom_patches();
+        removed
+    }
+
+    /// Enable or disable the cheat at `index`. Returns `false` if there is
+    /// no such cheat.
+    pub fn set_cheat_enabled(&mut self, index: usize, enabled: bool) -> bool {
+        let found = self.cheats.set_enabled(index, enabled);
+        self.sync_rom_patches();
+        found
+    }
+
+    pub fn clear_cheats(&mut self) {
+        self.cheats.clear();
+        self.sync_rom_patches();
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        self.cheats.cheats()
+    }
+
+    fn sync_rom_patches(&mut self) {
+        self.memory.set_rom_patches(self.cheats.rom_patches());
+    }
+
+    fn apply_ram_cheats(&mut self) {
+        for (bank, address, value) in self.cheats.ram_writes() {
+            match (bank, address) {
+                (Some(bank), 0xA000..=0xBFFF) => {
+                    self.memory.poke_external_ram(bank as usize, address - 0xA000, value)
+                }
+                _ => self.memory.write_fast(address, value),
+            }
+        }
+    }
+
     /// Drain accumulated PCM audio samples since the last call.
-    /// Returns interleaved stereo f32 samples [L, R, L, R, ...] at 48,000 Hz.
+    /// Returns interleaved stereo f32 samples [L, R, L, R, ...] at
+    /// [`Sm83::sample_rate`].
     pub fn drain_audio_samples(&mut self) -> alloc::vec::Vec<f32> {
         self.apu.drain_samples()
     }
 
+    /// Start or stop recording each sound channel's level alongside the
+    /// mixed audio; see [`ApuPeripheral::drain_channel_samples`].
+    pub fn set_channel_capture(&mut self, enabled: bool) {
+        self.apu.set_channel_capture(enabled);
+    }
+
+    /// Per-channel levels since the last call, indexed by
+    /// [`SoundChannel`](super::peripheral::apu::SoundChannel),
+    /// one per stereo pair of [`Sm83::drain_audio_samples`]. Empty unless
+    /// capture is enabled.
+    pub fn drain_channel_samples(&mut self) -> [alloc::vec::Vec<u8>; 4] {
+        self.apu.drain_channel_samples()
+    }
+
+    /// Silence the channels in a
+    /// [`SoundChannel::mask`](super::peripheral::apu::SoundChannel::mask) mask.
+    pub fn set_muted_channels(&mut self, mask: u8) {
+        self.apu.set_muted_channels(mask);
+    }
+
+    /// Play only the channels in a
+    /// [`SoundChannel::mask`](super::peripheral::apu::SoundChannel::mask)
+    /// mask; 0 plays all unmuted channels.
+    pub fn set_soloed_channels(&mut self, mask: u8) {
+        self.apu.set_soloed_channels(mask);
+    }
+
     #[cfg(feature = "perf")]
     pub fn take_perf_profile(&mut self) -> Sm83PerfProfile {
         self.perf.take_profile()
@@ -357,16 +785,53 @@ impl Sm83 {
         self.memory.set_external_ram(data);
     }
 
-    /// Serialize the full emulator state to an RBSS v1 blob.
+    /// Serialize the full emulator state to an RBSS v3 blob.
     pub fn save_state(&self) -> alloc::vec::Vec<u8> {
         let cpu = CpuState {
             a: self.registers.a, b: self.registers.b, c: self.registers.c,
             d: self.registers.d, e: self.registers.e, h: self.registers.h,
             l: self.registers.l, f: self.registers.f,
             sp: self.registers.sp, pc: self.registers.pc,
-            ime: self.ime, halted: self.halted, cycle_counter: self.cycle_counter,
+            ime: self.ime, halted: self.halted, stopped: self.stopped,
+            cycle_counter: self.cycle_counter,
+        };
+        let peripherals = PeripheralState {
+            apu:    self.apu.to_save_state(),
+            serial: self.serial.to_save_state(),
+            joypad: self.joypad.to_save_state(),
+            dma:    self.dma.as_ref().map(|d| OamDmaState { source: d.source, progress: d.progress }),
+        };
+        let pending_io_writes: Vec<(u16, u8)> = self.pending_bus_events
+            .iter()
+            .map(|e| (e.address, e.value))
+            .collect();
+        let cgb = match (self.ppu.to_color_save_state(), self.ppu.color_framebuffer(), &self.front_color_buffer) {
+            (Some(ppu), Some(ppu_framebuffer), Some(front_buffer)) => Some(CgbSource {
+                state: CgbState {
+                    double_speed: self.double_speed,
+                    speed_switch_armed: self.speed_switch_armed,
+                    stall_m_cycles: self.stall_m_cycles,
+                    hdma: self.hdma.to_save_state(),
+                    ppu,
+                },
+                ppu_framebuffer,
+                front_buffer,
+            }),
+            _ => None,
         };
-        SaveState::serialize(cpu, self.timer.to_save_state(), self.ppu.to_save_state(), &self.memory)
+        SaveState::serialize(SaveStateSource {
+            cpu,
+            timer: self.timer.to_save_state(),
+            ppu: self.ppu.to_save_state(),
+            peripherals,
+            ppu_framebuffer: self.ppu.framebuffer(),
+            front_buffer: &self.front_buffer,
+            pending_io_writes: &pending_io_writes,
+            cgb,
+            fifo: self.ppu.to_fifo_save_state(),
+            apu_output: self.apu.to_output_save_state(),
+            memory: &self.memory,
+        })
     }
 
     /// Restore emulator state from a parsed [`SaveState`].
@@ -375,14 +840,74 @@ impl Sm83 {
     /// [`SaveState::from_blob`]), so applying here is infallible given a
     /// well-formed `SaveState`. Returns `Ok(())` always; kept as `Result` for
     /// call-site symmetry and future extensibility.
+    ///
+    /// v1 blobs don't carry APU, serial or joypad state, so those peripherals
+    /// keep their current state. Any in-flight DMA or IO write belongs to the
+    /// state being replaced and is dropped.
+    ///
+    /// Fails, leaving the emulator untouched, if the state was saved on a
+    /// different hardware model or, on CGB, in a different mode.
     pub fn load_state(&mut self, state: SaveState) -> Result<(), &'static str> {
+        let state_model = if state.cgb.is_some() { Model::Cgb } else { Model::Dmg };
+        let mode_matches = state.cgb.is_none_or(|cgb| cgb.ppu.cgb_mode == self.cgb_mode());
+        if state_model != self.model || !mode_matches {
+            return Err("save state is for a different hardware model");
+        }
+
         self.registers     = state.cpu.to_registers();
         self.ime           = state.cpu.ime;
         self.halted        = state.cpu.halted;
+        self.stopped       = state.cpu.stopped;
         self.cycle_counter = state.cpu.cycle_counter;
         self.timer.load_state(state.timer);
         self.ppu.load_state(state.ppu);
+        self.ppu.load_fifo_state(state.fifo.as_ref());
         self.memory.load_state(&state);
+
+        if let Some(framebuffer) = state.ppu_framebuffer() {
+            self.ppu.load_framebuffer(framebuffer);
+        }
+        if let Some(front) = state.front_buffer() {
+            self.front_buffer.copy_from_slice(front);
+        }
+        self.front_layers.fill(0);
+        self.dma = None;
+        if let Some(peripherals) = state.peripherals {
+            self.apu.load_state(peripherals.apu);
+            self.apu.load_output_state(state.apu_output.as_ref());
+            self.serial.load_state(peripherals.serial);
+            self.joypad.load_state(peripherals.joypad);
+            self.memory.write_io(JOYP_ADDR, self.joypad.read());
+            self.dma = peripherals.dma.map(|d| DmaState { source: d.source, progress: d.progress });
+        }
+        // A transfer armed in the loaded SC waits on whatever cable is plugged in now.
+        self.serial.rearm(self.memory.read_io(SC_ADDR), self.memory.read_io(SB_ADDR));
+        if let Some(cgb) = state.cgb {
+            self.double_speed       = cgb.double_speed;
+            self.speed_switch_armed = cgb.speed_switch_armed;
+            self.stall_m_cycles     = cgb.stall_m_cycles;
+            self.hdma.load_state(cgb.hdma);
+            self.ppu.load_color_state(&cgb.ppu);
+            if let Some(banks) = state.cgb_banks() {
+                self.memory.load_cgb_state(banks);
+            }
+            if let Some(framebuffer) = state.color_framebuffer() {
+                self.ppu.load_color_framebuffer(framebuffer);
+            }
+            if let (Some(front), Some(saved)) =
+                (self.front_color_buffer.as_deref_mut(), state.color_front_buffer())
+            {
+                for (pixel, bytes) in front.iter_mut().zip(saved.chunks_exact(2)) {
+                    *pixel = u16::from_le_bytes([bytes[0], bytes[1]]);
+                }
+            }
+            self.sync_cgb_registers();
+        }
+        self.pending_apu_cycles = PendingApuCycles::default();
+        self.pending_bus_events.clear();
+        self.pending_bus_events.extend(
+            state.pending_io_writes().map(|(address, value)| BusEvent { address, value }),
+        );
         self.cache.sync(&self.memory);
         Ok(())
     }
@@ -394,8 +919,30 @@ impl Sm83 {
         self
     }
 
+    /// Start from the state the boot ROM for the selected model leaves behind:
+    /// the post-boot registers with PC = 0x0100 and SP = 0xFFFE, plus
+    /// [`Self::with_dmg_state`]'s IO registers.
+    pub fn with_post_boot_state(self) -> Self {
+        let (af, bc, de, hl) = match (self.model, self.cgb_mode()) {
+            (Model::Dmg, _)     => (0x01B0, 0x0013, 0x00D8, 0x014D),
+            (Model::Cgb, true)  => (0x1180, 0x0000, 0xFF56, 0x000D),
+            // B and HL depend on the title checksum the boot ROM uses to pick
+            // a compatibility palette; these are the values for most titles.
+            (Model::Cgb, false) => (0x1180, 0x0000, 0x0008, 0x007C),
+        };
+        let registers = Registers {
+            a: (af >> 8) as u8, f: Flags::from_bits_truncate(af as u8),
+            b: (bc >> 8) as u8, c: bc as u8,
+            d: (de >> 8) as u8, e: de as u8,
+            h: (hl >> 8) as u8, l: hl as u8,
+            sp: 0xFFFE, pc: 0x0100,
+        };
+        self.with_registers(registers).with_dmg_state()
+    }
+
     /// Seed IO registers to their DMG post-boot-ROM state so games that poll
     /// LY or check LCDC before enabling the LCD work correctly without a boot ROM.
+    /// The CGB boot ROM leaves the same values behind.
     pub fn with_dmg_state(mut self) -> Self {
         // Post-boot IO register values (DMG, SGB, MGB verified against Pan Docs)
         self.memory.write_io(LCDC_ADDR, 0x91); // LCD on, BG on, sprites off, window off
@@ -411,6 +958,27 @@ impl Sm83 {
         self
     }
 
+    /// Map a DMG boot ROM over 0x0000–0x00FF and start from the power-on state
+    /// (PC = 0x0000, LCD and APU off). The boot ROM unmaps itself by writing
+    /// 0xFF50 just before handing off at 0x0100.
+    ///
+    /// Use this instead of `with_registers` + `with_dmg_state`: the boot ROM
+    /// produces the post-boot register and IO state itself. CGB boot ROMs are
+    /// larger and not supported; use [`Self::with_post_boot_state`] on CGB.
+    pub fn with_boot_rom(mut self, image: [u8; BOOT_ROM_SIZE]) -> Self {
+        self.memory.map_boot_rom(image);
+        self.memory.write_io(BOOT_ROM_DISABLE_ADDR, 0xFE);
+        self.registers = Registers::default();
+        self.write_apu_register(NR52_ADDR, 0x00);
+        self.cache.sync(&self.memory);
+        self
+    }
+
+    /// Whether the boot ROM is still overlaid on the cartridge.
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.memory.boot_rom_mapped()
+    }
+
     // ── M-cycle–accurate bus access ─────────────────────────────────────────
 
     /// Perform a bus read: advance all peripherals by one M-cycle (4 T-cycles),
@@ -419,6 +987,19 @@ impl Sm83 {
     /// reads while ch3 is on return the current sample buffer.
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn bus_read(&mut self, addr: u16) -> Result<u8, MemoryError> {
+        self.bus_read_with(addr, OamCorruption::Read)
+    }
+
+    /// Bus read that also increments or decrements the register pair holding
+    /// `addr`, which corrupts OAM differently during mode 2.
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    fn bus_read_increasing(&mut self, addr: u16) -> Result<u8, MemoryError> {
+        self.bus_read_with(addr, OamCorruption::ReadIncrease)
+    }
+
+    #[cfg_attr(target_arch = "arm", link_section = ".data")]
+    #[inline(always)]
+    fn bus_read_with(&mut self, addr: u16, oam_bug: OamCorruption) -> Result<u8, MemoryError> {
         if (WAVE_RAM_START..=WAVE_RAM_END).contains(&addr) {
             // Wave RAM reads require T-cycle precision. The sample is latched
             // at T3 within the M-cycle on real hardware.
@@ -427,6 +1008,8 @@ impl Sm83 {
             let value = self.apu.read_wave_ram(offset);
             self.advance_timer(1);
             self.queue_apu_cycles(1);
+            #[cfg(feature = "debugger")]
+            self.bus_watch.access(addr, value, Access::READ, false);
             return Ok(value);
         }
         #[cfg(feature = "perf")]
@@ -434,9 +1017,12 @@ impl Sm83 {
         #[cfg(feature = "perf")]
         let t0 = cyccnt();
         self.tick_cycle();
+        self.trigger_oam_bug(addr, oam_bug);
         #[cfg(feature = "perf")]
         let t_read = cyccnt();
         let value = self.memory.read_fast(addr);
+        #[cfg(feature = "debugger")]
+        self.bus_watch.access(addr, value, Access::READ, false);
         #[cfg(feature = "perf")]
         {
             let t1 = cyccnt();
@@ -496,6 +1082,8 @@ impl Sm83 {
     /// for T-cycle accurate timing.
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn bus_write(&mut self, addr: u16, value: u8) -> Result<(), MemoryError> {
+        #[cfg(feature = "debugger")]
+        self.bus_watch.access(addr, value, Access::WRITE, false);
         if (NR10_ADDR..=NR52_ADDR).contains(&addr) {
             self.tick_cycle_to_t3();
             self.write_apu_register(addr, value);
@@ -511,6 +1099,7 @@ impl Sm83 {
             return Ok(());
         }
         self.tick_cycle();
+        self.trigger_oam_bug(addr, OamCorruption::Write);
         #[cfg(feature = "perf")]
         let t0 = cyccnt();
         let result = match addr {
@@ -535,7 +1124,6 @@ impl Sm83 {
                 }
                 Ok(())
             }
-            0xE000..=0xFDFF => Err(MemoryError::ReadOnly(addr)),
             _ => {
                 #[cfg(feature = "perf")]
                 let t_fast = cyccnt();
@@ -554,6 +1142,21 @@ impl Sm83 {
         result
     }
 
+    /// DMG OAM corruption bug: an address in 0xFE00–0xFEFF on the bus while
+    /// the PPU scans OAM mangles the row it is reading.
+    #[inline(always)]
+    fn trigger_oam_bug(&mut self, addr: u16, kind: OamCorruption) {
+        if (0xFE00..=0xFEFF).contains(&addr) && self.model == Model::Dmg {
+            self.ppu.corrupt_oam(self.memory.oam_mut(), kind);
+        }
+    }
+
+    /// Dots (4.19 MHz PPU clocks) per M-cycle: 4, or 2 in double speed.
+    #[inline(always)]
+    fn dots_per_m_cycle(&self) -> u16 {
+        if self.double_speed { 2 } else { 4 }
+    }
+
     /// Advance peripherals by one M-cycle (4 T-cycles) without a bus access.
     /// Used for internal M-cycles (e.g. ALU operations, SP adjustment).
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
@@ -575,6 +1178,11 @@ impl Sm83 {
         };
         let byte = self.memory.read_fast(source + progress as u16);
         self.memory.write_fast(0xFE00 + progress as u16, byte);
+        #[cfg(feature = "debugger")]
+        {
+            self.bus_watch.access(source + progress as u16, byte, Access::READ, true);
+            self.bus_watch.access(0xFE00 + progress as u16, byte, Access::WRITE, true);
+        }
         let next = progress + 1;
         self.dma = if next < 160 {
             Some(DmaState { source, progress: next })
@@ -583,13 +1191,16 @@ impl Sm83 {
         };
     }
 
+    /// `cycles` are CPU clocks. The PPU and RTC run off the dot clock, which
+    /// double speed leaves alone.
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn advance_peripherals(&mut self, cycles: u16) {
-        self.advance_ppu(cycles);
+        let dots = if self.double_speed { cycles / 2 } else { cycles };
+        self.advance_ppu(dots);
         self.advance_timer(cycles);
         self.queue_apu_cycles(cycles);
         if self.memory.has_rtc() {
-            self.memory.tick_rtc(cycles as u32);
+            self.memory.tick_rtc(dots as u32);
         }
         if !self.serial.is_idle() {
             self.advance_serial(cycles);
@@ -618,7 +1229,7 @@ impl Sm83 {
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn tick_cycle_to_t3(&mut self) {
         self.begin_t3_sensitive_m_cycle();
-        self.advance_ppu(4);
+        self.advance_ppu(self.dots_per_m_cycle());
         self.advance_timer(3);
         self.queue_apu_cycles(3);
     }
@@ -627,7 +1238,7 @@ impl Sm83 {
 
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn begin_m_cycle(&mut self) {
-        self.cycle_counter += 4;
+        self.cycle_counter += self.dots_per_m_cycle() as u64;
         self.flush_apu_before_bus_events();
         self.route_bus_events();
         self.advance_dma();
@@ -645,20 +1256,33 @@ impl Sm83 {
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     #[inline(always)]
     fn run_idle_m_cycle(&mut self) {
-        self.cycle_counter += 4;
-        self.advance_ppu(4);
+        let dots = self.dots_per_m_cycle();
+        self.cycle_counter += dots as u64;
+        self.advance_ppu(dots);
         self.advance_timer(4);
         self.queue_apu_cycles(4);
     }
 
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn begin_t3_sensitive_m_cycle(&mut self) {
-        self.cycle_counter += 4;
+        self.cycle_counter += self.dots_per_m_cycle() as u64;
         self.flush_pending_apu_cycles();
         self.route_bus_events();
         self.advance_dma();
     }
 
+    /// STOP resets DIV as it executes rather than through the bus event
+    /// queue: the timer is frozen until wakeup either way, and a save state
+    /// taken while stopped then has no queued write to carry. The APU is
+    /// flushed first since it clocks off DIV, and the register is synced
+    /// here because no timer step follows until wakeup.
+    fn reset_div_for_stop(&mut self) {
+        self.flush_pending_apu_cycles();
+        self.timer.reset_div();
+        self.cache.div = 0;
+        self.memory.write_io(DIV_ADDR, 0);
+    }
+
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn advance_ime(&mut self) {
         if self.ime == ImeState::Pending {
@@ -727,6 +1351,14 @@ impl Sm83 {
             a if a == DMA_ADDR => {
                 self.dma = Some(DmaState { source: (value as u16) << 8, progress: 0 });
             }
+            a if a == BOOT_ROM_DISABLE_ADDR => {
+                // Once unmapped the boot ROM stays unmapped until power-off.
+                if value & 0x01 != 0 {
+                    self.memory.unmap_boot_rom();
+                }
+                let mapped = self.memory.boot_rom_mapped();
+                self.memory.write_io(BOOT_ROM_DISABLE_ADDR, if mapped { 0xFE } else { 0xFF });
+            }
             a if (NR10_ADDR..=NR52_ADDR).contains(&a) => self.write_apu_register(a, value),
             // Unused APU addresses 0xFF27-0xFF2F always read as 0xFF
             a if (0xFF27u16..WAVE_RAM_START).contains(&a) => self.memory.write_io(a, 0xFF),
@@ -744,10 +1376,79 @@ impl Sm83 {
             a if a == OBP1_ADDR => self.cache.obp1 = value,
             a if a == WY_ADDR   => self.cache.wy   = value,
             a if a == WX_ADDR   => self.cache.wx   = value,
+            a if CGB_REGISTERS.contains(&a) => self.write_cgb_register(a, value),
             _ => {}
         }
     }
 
+    /// Apply a write to a CGB register, then refresh the read-back values.
+    /// On DMG these addresses stay plain IO memory; in DMG-compatibility mode
+    /// the boot ROM has locked them.
+    fn write_cgb_register(&mut self, addr: u16, value: u8) {
+        if self.model != Model::Cgb {
+            return;
+        }
+        if self.cgb_mode() {
+            match addr {
+                KEY1_ADDR => self.speed_switch_armed = value & 0x01 != 0,
+                VBK_ADDR  => self.memory.set_vram_bank(value),
+                SVBK_ADDR => self.memory.set_wram_bank(value),
+                HDMA1_ADDR..=HDMA5_ADDR => self.write_hdma_register(addr, value),
+                _ => self.ppu.write_color_register(addr, value),
+            }
+        }
+        self.sync_cgb_registers();
+    }
+
+    fn read_cgb_register(&self, addr: u16) -> u8 {
+        if !self.cgb_mode() {
+            return 0xFF;
+        }
+        match addr {
+            KEY1_ADDR  => 0x7E | (self.double_speed as u8) << 7 | self.speed_switch_armed as u8,
+            VBK_ADDR   => 0xFE | self.memory.vram_bank(),
+            SVBK_ADDR  => 0xF8 | self.memory.wram_bank(),
+            HDMA5_ADDR => self.hdma.read_hdma5(),
+            HDMA1_ADDR..=HDMA4_ADDR => 0xFF,
+            _ => self.ppu.read_color_register(addr),
+        }
+    }
+
+    /// Write every CGB register's read-back value to IO memory.
+    fn sync_cgb_registers(&mut self) {
+        for addr in CGB_REGISTERS {
+            let value = self.read_cgb_register(addr);
+            self.memory.write_io(addr, value);
+        }
+    }
+
+    fn write_hdma_register(&mut self, addr: u16, value: u8) {
+        if self.hdma.write_register(addr, value) {
+            // General-purpose DMA copies everything before the CPU resumes.
+            for _ in 0..=(value & 0x7F) {
+                self.run_hdma_block();
+            }
+        } else if addr == HDMA5_ADDR
+            && self.hdma.hblank_active()
+            && (self.cache.lcdc & 0x80 == 0 || self.cache.stat & 0x03 == 0)
+        {
+            // Started during HBlank or with the LCD off: the first block
+            // does not wait for the next HBlank.
+            self.run_hdma_block();
+        }
+    }
+
+    /// Copy one HDMA block into the mapped VRAM bank and stall the CPU for it.
+    fn run_hdma_block(&mut self) {
+        let (source, destination) = self.hdma.next_block();
+        for i in 0..HDMA_BLOCK_SIZE {
+            let byte = self.memory.read_fast(source.wrapping_add(i));
+            self.memory.write_fast(destination + i, byte);
+        }
+        self.stall_m_cycles += if self.double_speed { 16 } else { 8 };
+        self.memory.write_io(HDMA5_ADDR, self.hdma.read_hdma5());
+    }
+
     /// Write an APU register and sync the masked read-back value to IO memory.
     /// NR52 writes may power off all channels, so all registers are resynced.
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
@@ -774,6 +1475,7 @@ impl Sm83 {
     fn advance_ppu(&mut self, cycles: u16) {
         #[cfg(feature = "perf")]
         let t0 = cyccnt();
+        let (vram, vram_bank1) = self.memory.vram_banks();
         let output = self.ppu.tick(
             cycles,
             PpuInput {
@@ -787,7 +1489,8 @@ impl Sm83 {
                 obp1: self.cache.obp1,
                 wy: self.cache.wy,
                 wx: self.cache.wx,
-                vram: self.memory.vram(),
+                vram,
+                vram_bank1,
                 oam: self.memory.oam(),
             },
         );
@@ -803,6 +1506,14 @@ impl Sm83 {
             // Snapshot the completed frame into the front buffer before the PPU
             // starts overwriting scanlines for the next frame.
             self.front_buffer.copy_from_slice(self.ppu.framebuffer());
+            self.front_layers.copy_from_slice(self.ppu.layers());
+            if let (Some(front), Some(color)) =
+                (self.front_color_buffer.as_deref_mut(), self.ppu.color_framebuffer())
+            {
+                front.copy_from_slice(color);
+            }
+            self.vblank_entered = true;
+            self.ram_cheats_due = true;
             let if_val = self.memory.read_io(IF_ADDR);
             self.memory.write_io(IF_ADDR, if_val | (1 << VBLANK_INTERRUPT_BIT));
         }
@@ -810,6 +1521,9 @@ impl Sm83 {
             let if_val = self.memory.read_io(IF_ADDR);
             self.memory.write_io(IF_ADDR, if_val | (1 << STAT_INTERRUPT_BIT));
         }
+        if output.hblank && self.hdma.hblank_active() {
+            self.run_hdma_block();
+        }
         #[cfg(feature = "perf")]
         {
             self.perf.record_ppu(cyccnt().wrapping_sub(t0));
@@ -864,7 +1578,19 @@ impl Sm83 {
     fn tick_apu(&mut self, cycles: u16) {
         #[cfg(feature = "perf")]
         let t0 = cyccnt();
-        let output = self.apu.tick(cycles, self.timer.internal_counter());
+        // The APU runs off the dot clock. In double speed, both the CPU-clock
+        // batch and DIV (whose bit 13 then drives the frame sequencer) tick
+        // twice as fast. Halve the batch by counting the edges of DIV's
+        // bit 0 it crossed, so a batch with an odd cycle count doesn't drop
+        // a dot: the half left over carries into the next batch through DIV.
+        let div = self.timer.internal_counter();
+        let output = if self.double_speed {
+            let start = div.wrapping_sub(cycles);
+            let dots = (div >> 1).wrapping_sub(start >> 1) & 0x7FFF;
+            self.apu.tick(dots, div >> 1)
+        } else {
+            self.apu.tick(cycles, div)
+        };
         if output.nr52 != self.cache.nr52 {
             self.cache.nr52 = output.nr52;
             self.memory.write_io(NR52_ADDR, output.nr52);
@@ -878,6 +1604,10 @@ impl Sm83 {
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn finish_tick(&mut self, start_cycles: u64) -> u8 {
         self.flush_pending_apu_cycles();
+        if self.ram_cheats_due {
+            self.ram_cheats_due = false;
+            self.apply_ram_cheats();
+        }
         (self.cycle_counter - start_cycles) as u8
     }
 
@@ -913,6 +1643,8 @@ impl Sm83 {
         self.bus_write(self.registers.sp, self.registers.pc as u8)?;
         self.tick_cycle(); // internal — load ISR address
         self.registers.pc = 0x0040 + (bit as u16) * 8;
+        #[cfg(feature = "debugger")]
+        self.bus_watch.interrupt(bit);
         Ok(())
     }
 
@@ -924,7 +1656,10 @@ impl Sm83 {
         #[cfg(feature = "perf")]
         let t0 = cyccnt();
         let byte = if addr <= 0x7FFF {
-            self.read_pc_rom_fast(addr)
+            let byte = self.read_pc_rom_fast(addr);
+            #[cfg(feature = "debugger")]
+            self.bus_watch.access(addr, byte, Access::READ, false);
+            byte
         } else {
             self.bus_read(addr)?
         };
@@ -1102,6 +1837,24 @@ impl Sm83 {
     fn tick_impl(&mut self) -> Result<u8, CpuError> {
         let start_cycles = self.cycle_counter;
 
+        if self.stopped {
+            // Nothing is clocked in STOP mode: the PPU, timer and APU all freeze.
+            // The cycle counter still advances so frame-paced callers keep running.
+            self.cycle_counter += 4;
+            return Ok(self.finish_tick(start_cycles));
+        }
+
+        if self.stall_m_cycles > 0 {
+            // HDMA and the speed switch stop the CPU, not the rest of the
+            // system. Sit out a bounded slice per tick so the result fits a u8.
+            let m_cycles = self.stall_m_cycles.min(16);
+            self.stall_m_cycles -= m_cycles;
+            for _ in 0..m_cycles {
+                self.tick_cycle();
+            }
+            return Ok(self.finish_tick(start_cycles));
+        }
+
         if self.halted {
             self.tick_cycle(); // 1 M-cycle while halted
             if self.has_pending_interrupt() {
@@ -1329,6 +2082,7 @@ impl Instructions for Sm83 {
         let val = self.get_register16_operand(opcode.operand);
         self.set_register16_operand(opcode.operand, val.wrapping_add(1));
         self.tick_cycle(); // internal
+        self.trigger_oam_bug(val, OamCorruption::Write);
         Ok(opcode.cycles)
     }
 
@@ -1337,6 +2091,7 @@ impl Instructions for Sm83 {
         let val = self.get_register16_operand(opcode.operand);
         self.set_register16_operand(opcode.operand, val.wrapping_sub(1));
         self.tick_cycle(); // internal
+        self.trigger_oam_bug(val, OamCorruption::Write);
         Ok(opcode.cycles)
     }
 
@@ -1433,12 +2188,12 @@ impl Instructions for Sm83 {
             }
             Ld16Op::AHli => {
                 let addr = self.registers.hl();
-                self.registers.a = self.bus_read(addr)?;
+                self.registers.a = self.bus_read_increasing(addr)?;
                 self.registers.set_hl(addr.wrapping_add(1));
             }
             Ld16Op::AHld => {
                 let addr = self.registers.hl();
-                self.registers.a = self.bus_read(addr)?;
+                self.registers.a = self.bus_read_increasing(addr)?;
                 self.registers.set_hl(addr.wrapping_sub(1));
             }
             Ld16Op::NnA => {
@@ -1556,9 +2311,33 @@ impl Instructions for Sm83 {
             MiscOp::Halt => {
                 self.halted = true;
             }
-            MiscOp::Stop => {
-                // Consume the next byte (should be 0x00)
+            MiscOp::Stop if self.speed_switch_armed && self.cgb_mode() => {
+                // CGB speed switch: STOP consumes its operand, resets DIV and
+                // resumes at the new speed once the clock has settled.
                 let _ = self.read_next_pc()?;
+                self.double_speed = !self.double_speed;
+                self.speed_switch_armed = false;
+                self.reset_div_for_stop();
+                self.stall_m_cycles += SPEED_SWITCH_M_CYCLES;
+                self.sync_cgb_registers();
+            }
+            MiscOp::Stop => {
+                // DMG behaviour from Pan Docs, "Using the STOP Instruction":
+                //   button held, interrupt pending: 1-byte opcode, nothing happens
+                //   button held, none pending:      2-byte opcode, enters HALT
+                //   no button, interrupt pending:   1-byte opcode, enters STOP, resets DIV
+                //   no button, none pending:        2-byte opcode, enters STOP, resets DIV
+                let button_held = self.joypad.read() & 0x0F != 0x0F;
+                let interrupt_pending = self.has_pending_interrupt();
+                if !interrupt_pending {
+                    let _ = self.read_next_pc()?;
+                }
+                if button_held {
+                    self.halted = !interrupt_pending;
+                } else {
+                    self.reset_div_for_stop();
+                    self.stopped = true;
+                }
             }
             MiscOp::Daa => {
                 (self.registers.a, self.registers.f) = daa_u8(self.registers.a, self.registers.f);
@@ -1597,6 +2376,7 @@ impl Instructions for Sm83 {
         // fetch + internal + write hi + write lo = 4 M-cycles
         let value = self.get_register16_operand(opcode.operand);
         self.tick_cycle(); // internal
+        self.trigger_oam_bug(self.registers.sp, OamCorruption::Write);
         self.registers.sp = self.registers.sp.wrapping_sub(1);
         self.bus_write(self.registers.sp, (value >> 8) as u8)?;
         self.registers.sp = self.registers.sp.wrapping_sub(1);
@@ -1607,7 +2387,7 @@ impl Instructions for Sm83 {
     #[cfg_attr(target_arch = "arm", link_section = ".data")]
     fn pop16(&mut self, opcode: &Pop16) -> Result<u8, InstructionError> {
         // fetch + read lo + read hi = 3 M-cycles
-        let lo = self.bus_read(self.registers.sp)? as u16;
+        let lo = self.bus_read_increasing(self.registers.sp)? as u16;
         self.registers.sp = self.registers.sp.wrapping_add(1);
         let hi = self.bus_read(self.registers.sp)? as u16;
         self.registers.sp = self.registers.sp.wrapping_add(1);
@@ -1729,10 +2509,17 @@ mod tests {
     use crate::cpu::instructions::opcodes::OpCodeDecoder;
     use crate::cpu::registers::Flags;
 
+    use crate::memory::cartridge::NoMbc;
     use crate::memory::memory::GameBoyMemory;
 
+    /// Memory backed by a header-less flat ROM, for tests that only need a
+    /// few opcode bytes at 0x0000.
+    fn flat_rom_memory(rom_data: Vec<u8>) -> GameBoyMemory {
+        GameBoyMemory::with_cartridge(Box::new(NoMbc::new(rom_data)))
+    }
+
     pub fn make_test_cpu(rom_data: Vec<u8>) -> Sm83 {
-        let memory: Box<GameBoyMemory> = Box::new(GameBoyMemory::with_rom(rom_data));
+        let memory: Box<GameBoyMemory> = Box::new(flat_rom_memory(rom_data));
         let decoder = Box::new(OpCodeDecoder::new());
 
         Sm83::new(memory, decoder)
@@ -1742,12 +2529,21 @@ mod tests {
         setup: impl FnOnce(&mut GameBoyMemory),
         rom_data: Vec<u8>,
     ) -> Sm83 {
-        let mut mem = GameBoyMemory::with_rom(rom_data);
+        let mut mem = flat_rom_memory(rom_data);
         setup(&mut mem);
         let decoder = Box::new(OpCodeDecoder::new());
         Sm83::new(Box::new(mem), decoder)
     }
 
+    #[test]
+    fn test_from_rom_returns_cartridge_error() {
+        let result = Sm83::from_rom(vec![0x00; 0x100], Box::new(OpCodeDecoder::new()));
+        assert!(matches!(
+            result,
+            Err(CartridgeError::RomTooSmall { expected_bytes: 0x150, actual_bytes: 0x100 })
+        ));
+    }
+
     /// Add a constant to the accumulator register and expect the register's value to be the
     /// appropriate value.
     #[test]
@@ -2260,7 +3056,7 @@ mod tests {
         // ROM: store A to (HL), then load A from (HL); HL=0xC000, A=0xCD
         // After tick 1 (LD (HL),A): memory[0xC000]=0xCD, cycles=8
         // After tick 2 (LD A,(HL)): A=0xCD, cycles=8
-        let memory = GameBoyMemory::with_rom(vec![0x77, 0x7E]);
+        let memory = flat_rom_memory(vec![0x77, 0x7E]);
         let decoder = Box::new(OpCodeDecoder::new());
         let mut cpu = Sm83::new(Box::new(memory), decoder).with_registers(Registers {
             a: 0xCD,
@@ -2286,6 +3082,20 @@ mod tests {
         assert_eq!(cpu.registers().a, 0xCD);
     }
 
+    /// LD (HL), A with HL in echo RAM — the store lands in the mirrored WRAM byte.
+    #[test]
+    fn test_ld8_mem_hl_from_a_echo_ram_mirrors_into_wram() {
+        let mut cpu = make_test_cpu(vec![0x77]).with_registers(Registers {
+            a: 0x3C,
+            h: 0xE1,
+            l: 0x23,
+            ..Default::default()
+        });
+
+        cpu.tick().unwrap();
+        assert_eq!(cpu.read_memory(0xC123).unwrap(), 0x3C);
+    }
+
     /// LD B, n — load immediate byte into B.
     /// ROM: [0x06, 0x7F], expect B=0x7F, 8 cycles.
     #[test]
@@ -2303,7 +3113,7 @@ mod tests {
     /// Tick 2: load A from (HL), expect A=0x99, 8 cycles.
     #[test]
     fn test_ld8_mem_hl_imm8() {
-        let memory = GameBoyMemory::with_rom(vec![0x36, 0x99, 0x7E]);
+        let memory = flat_rom_memory(vec![0x36, 0x99, 0x7E]);
         let decoder = Box::new(OpCodeDecoder::new());
         let mut cpu = Sm83::new(Box::new(memory), decoder).with_registers(Registers {
             h: 0xC0,
@@ -2329,7 +3139,7 @@ mod tests {
     /// Expected: A = 0x10, H flag set (lower nibble overflow 1+F=10).
     #[test]
     fn test_integration_add8_memory_hl_gameboy_memory() {
-        let mut memory = GameBoyMemory::with_rom(vec![0x86]);
+        let mut memory = flat_rom_memory(vec![0x86]);
         // Pre-populate the memory location HL will point to
         memory.write(0xC010, 0x0F).unwrap();
 
@@ -2954,7 +3764,8 @@ mod tests {
         assert_eq!(cycles, 4);
     }
 
-    /// STOP (0x10): next byte (0x00) is consumed, so PC advances by 2.
+    /// STOP (0x10) with no button held and no interrupt pending: the next
+    /// byte (0x00) is consumed, so PC advances by 2.
     #[test]
     fn test_stop_consumes_next_byte() {
         let mut cpu = make_test_cpu(vec![0x10, 0x00]);
@@ -3184,7 +3995,7 @@ mod tests {
     /// Verify by reading back with LD A,(HL).
     #[test]
     fn test_inc8_mem_hl() {
-        let mut memory = GameBoyMemory::with_rom(vec![0x34, 0x7E]);
+        let mut memory = flat_rom_memory(vec![0x34, 0x7E]);
         memory.write(0xC000, 0x07).unwrap();
         let decoder = Box::new(OpCodeDecoder::new());
         let mut cpu = Sm83::new(Box::new(memory), decoder).with_registers(Registers {
@@ -3209,7 +4020,7 @@ mod tests {
     /// HL=0xC000, memory[0xC000]=0x07. Expected: memory[0xC000]=0x06, 12 cycles.
     #[test]
     fn test_dec8_mem_hl() {
-        let mut memory = GameBoyMemory::with_rom(vec![0x35, 0x7E]);
+        let mut memory = flat_rom_memory(vec![0x35, 0x7E]);
         memory.write(0xC000, 0x07).unwrap();
         let decoder = Box::new(OpCodeDecoder::new());
         let mut cpu = Sm83::new(Box::new(memory), decoder).with_registers(Registers {
@@ -3629,6 +4440,35 @@ mod tests {
         assert!(!cpu.registers().f.contains(Flags::Z));
     }
 
+    // --- Boot ROM ---
+
+    #[test]
+    fn test_boot_rom_starts_at_power_on_state() {
+        let cpu = make_test_cpu(vec![0x00; 0x8000]).with_boot_rom([0x00; BOOT_ROM_SIZE]);
+        assert!(cpu.boot_rom_mapped());
+        assert_eq!(cpu.registers().pc, 0x0000);
+        assert_eq!(cpu.read_memory(LCDC_ADDR).unwrap(), 0x00, "LCD off at power-on");
+        assert_eq!(cpu.read_memory(NR52_ADDR).unwrap() & 0x80, 0x00, "APU off at power-on");
+    }
+
+    #[test]
+    fn test_boot_rom_unmaps_on_ff50_write_with_bit0_set() {
+        let mut boot = [0x00; BOOT_ROM_SIZE];
+        // LD A,0x02; LDH (0x50),A; LD A,0x01; LDH (0x50),A
+        boot[..8].copy_from_slice(&[0x3E, 0x02, 0xE0, 0x50, 0x3E, 0x01, 0xE0, 0x50]);
+        let mut cpu = make_test_cpu(vec![0x00; 0x8000]).with_boot_rom(boot);
+
+        cpu.tick().unwrap();
+        cpu.tick().unwrap();
+        cpu.tick().unwrap(); // next fetch routes the 0x02 write
+        assert!(cpu.boot_rom_mapped(), "bit 0 clear must not unmap");
+        cpu.tick().unwrap();
+        cpu.tick().unwrap();
+        assert!(!cpu.boot_rom_mapped());
+        assert_eq!(cpu.read_memory(0x0000).unwrap(), 0x00);
+        assert_eq!(cpu.read_memory(BOOT_ROM_DISABLE_ADDR).unwrap(), 0xFF);
+    }
+
     // --- IME and HALT ---
 
     #[test]
@@ -3724,6 +4564,236 @@ mod tests {
         assert_eq!(cycles, 8); // halted M-cycle + NOP
     }
 
+    // --- STOP ---
+
+    /// Run `n` NOPs from a ROM of NOPs followed by STOP 0x00 and a NOP.
+    fn make_stop_cpu(nops_before_stop: usize) -> Sm83 {
+        let mut rom = vec![0x00; nops_before_stop];
+        rom.extend_from_slice(&[0x10, 0x00, 0x00]);
+        let mut cpu = make_test_cpu(rom);
+        cpu.handle_bus_event(JOYP_ADDR, 0x20); // select the d-pad lines
+        for _ in 0..nops_before_stop {
+            cpu.tick().unwrap();
+        }
+        cpu
+    }
+
+    #[test]
+    fn test_stop_enters_stop_mode() {
+        let mut cpu = make_stop_cpu(0);
+        cpu.tick().unwrap();
+        assert!(cpu.is_stopped());
+        assert!(!cpu.is_halted());
+        assert_eq!(cpu.registers().pc, 2);
+    }
+
+    #[test]
+    fn test_stopped_cpu_does_not_advance_pc_or_peripherals() {
+        let mut cpu = make_stop_cpu(100).with_dmg_state(); // LCD on
+        cpu.tick().unwrap(); // STOP
+        let pc = cpu.registers().pc;
+        let div = cpu.read_memory(DIV_ADDR).unwrap();
+        let ly = cpu.read_memory(LY_ADDR).unwrap();
+        let start = cpu.cycle_counter();
+
+        for _ in 0..1000 {
+            assert_eq!(cpu.tick().unwrap(), 4);
+        }
+
+        assert!(cpu.is_stopped());
+        assert_eq!(cpu.registers().pc, pc);
+        assert_eq!(cpu.read_memory(DIV_ADDR).unwrap(), div, "DIV frozen");
+        assert_eq!(cpu.read_memory(LY_ADDR).unwrap(), ly, "PPU frozen");
+        assert_eq!(cpu.cycle_counter() - start, 4000, "cycle counter keeps pace");
+    }
+
+    #[test]
+    fn test_stop_resets_div() {
+        // 100 NOPs is 400 T-cycles, so DIV has ticked past 0 before STOP.
+        let mut cpu = make_stop_cpu(100);
+        assert_ne!(cpu.read_memory(DIV_ADDR).unwrap(), 0);
+        cpu.tick().unwrap(); // STOP
+        assert_eq!(cpu.read_memory(DIV_ADDR).unwrap(), 0);
+        assert!(cpu.pending_bus_events.is_empty());
+        cpu.set_button(Button::Up, true);
+        cpu.tick().unwrap(); // NOP after wakeup
+
+        assert_eq!(cpu.read_memory(DIV_ADDR).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_stop_div_reset_survives_save_state() {
+        let mut cpu = make_stop_cpu(100);
+        cpu.tick().unwrap(); // STOP
+        let blob = cpu.save_state();
+
+        let mut restored = make_stop_cpu(100);
+        assert_ne!(restored.read_memory(DIV_ADDR).unwrap(), 0);
+        restored.load_state(SaveState::from_blob(blob).unwrap()).unwrap();
+        assert_eq!(restored.timer.internal_counter(), 0);
+        restored.set_button(Button::Up, true);
+        restored.tick().unwrap(); // NOP after wakeup
+
+        assert_eq!(restored.read_memory(DIV_ADDR).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_stop_wakes_on_selected_joypad_press() {
+        let mut cpu = make_stop_cpu(0);
+        cpu.tick().unwrap(); // STOP
+
+        cpu.set_button(Button::Up, true);
+        assert!(!cpu.is_stopped());
+        cpu.tick().unwrap(); // NOP
+        assert_eq!(cpu.registers().pc, 3);
+        assert_ne!(cpu.memory.read_io(IF_ADDR) & (1 << JOYPAD_INTERRUPT_BIT), 0);
+    }
+
+    #[test]
+    fn test_stop_ignores_unselected_buttons_and_other_interrupts() {
+        let mut cpu = make_stop_cpu(0);
+        cpu.tick().unwrap(); // STOP
+
+        cpu.set_button(Button::A, true); // action lines are not selected
+        cpu.memory.write_io(IE_ADDR, 0x01);
+        cpu.memory.write_io(IF_ADDR, 0x01); // VBlank pending
+        for _ in 0..10 {
+            cpu.tick().unwrap();
+        }
+
+        assert!(cpu.is_stopped());
+        assert_eq!(cpu.registers().pc, 2);
+    }
+
+    #[test]
+    fn test_stop_with_interrupt_pending_is_one_byte() {
+        let mut cpu = make_stop_cpu(0);
+        cpu.memory.write_io(IE_ADDR, 0x01);
+        cpu.memory.write_io(IF_ADDR, 0x01);
+
+        assert_eq!(cpu.tick().unwrap(), 4);
+        assert!(cpu.is_stopped());
+        assert_eq!(cpu.registers().pc, 1);
+    }
+
+    #[test]
+    fn test_stop_with_button_held_enters_halt_without_resetting_div() {
+        let mut cpu = make_stop_cpu(100);
+        cpu.set_button(Button::Up, true);
+        cpu.memory.write_io(IF_ADDR, 0x00); // discard the joypad request
+        let div = cpu.read_memory(DIV_ADDR).unwrap();
+
+        cpu.tick().unwrap();
+
+        assert!(cpu.is_halted());
+        assert!(!cpu.is_stopped());
+        assert_eq!(cpu.registers().pc, 102);
+        assert!(cpu.pending_bus_events.is_empty());
+        assert!(cpu.read_memory(DIV_ADDR).unwrap() >= div);
+    }
+
+    #[test]
+    fn test_stop_with_button_held_and_interrupt_pending_does_nothing() {
+        let mut cpu = make_stop_cpu(0);
+        cpu.set_button(Button::Up, true);
+        cpu.memory.write_io(IE_ADDR, 0x10); // joypad request stays pending
+
+        assert_eq!(cpu.tick().unwrap(), 4);
+        assert!(!cpu.is_halted());
+        assert!(!cpu.is_stopped());
+        assert_eq!(cpu.registers().pc, 1);
+    }
+
+    #[test]
+    fn test_stopped_flag_survives_save_state() {
+        let mut cpu = make_stop_cpu(0);
+        cpu.tick().unwrap(); // STOP
+        let blob = cpu.save_state();
+
+        let mut restored = make_stop_cpu(0);
+        restored.load_state(SaveState::from_blob(blob).unwrap()).unwrap();
+
+        assert!(restored.is_stopped());
+        assert!(!restored.is_halted());
+    }
+
+    // --- Frame stepping ---
+
+    #[test]
+    fn test_run_until_vblank_stops_at_vblank_entry() {
+        let mut cpu = make_test_cpu(vec![0x00; 0x8000]).with_dmg_state(); // LCD on, LY=0
+
+        let first = cpu.run_until_vblank().unwrap();
+        assert!(first.frame);
+        assert!(first.cycles < CYCLES_PER_FRAME);
+        assert_eq!(cpu.read_memory(LY_ADDR).unwrap(), 144);
+
+        // From one VBlank entry to the next is exactly one frame of NOPs.
+        let second = cpu.run_until_vblank().unwrap();
+        assert_eq!(second, RunResult { cycles: CYCLES_PER_FRAME, frame: true });
+        assert_eq!(cpu.read_memory(LY_ADDR).unwrap(), 144);
+    }
+
+    #[test]
+    fn test_run_until_vblank_gives_up_after_one_frame_with_lcd_off() {
+        let mut cpu = make_test_cpu(vec![0x00; 0x8000]); // LCDC=0
+
+        let result = cpu.run_until_vblank().unwrap();
+
+        assert_eq!(result, RunResult { cycles: CYCLES_PER_FRAME, frame: false });
+    }
+
+    #[test]
+    fn test_run_frame_blanks_screen_with_lcd_off() {
+        let mut cpu = make_test_cpu(vec![0x00; 0x8000]);
+        cpu.front_buffer.fill(3);
+
+        let result = cpu.run_frame().unwrap();
+
+        assert_eq!(result, RunResult { cycles: CYCLES_PER_FRAME, frame: true });
+        assert!(cpu.framebuffer().iter().all(|&p| p == 0));
+    }
+
+    #[test]
+    fn test_run_frame_keeps_last_frame_in_stop_mode() {
+        let mut cpu = make_stop_cpu(0).with_dmg_state();
+        cpu.front_buffer.fill(3);
+
+        let result = cpu.run_frame().unwrap();
+
+        assert!(cpu.is_stopped());
+        assert!(!result.frame);
+        assert!(cpu.framebuffer().iter().all(|&p| p == 3));
+    }
+
+    #[test]
+    fn test_run_cycles_runs_whole_instructions() {
+        // LD BC,d16 takes 12 cycles, so 16 cycles needs two of them.
+        let mut cpu = make_test_cpu(vec![0x01, 0x00, 0x00, 0x01, 0x00, 0x00]);
+
+        let result = cpu.run_cycles(16).unwrap();
+
+        assert_eq!(result, RunResult { cycles: 24, frame: false });
+        assert_eq!(cpu.registers().pc, 6);
+    }
+
+    #[test]
+    fn test_run_cycles_reports_vblank_without_stopping() {
+        let mut cpu = make_test_cpu(vec![0x00; 0x8000]).with_dmg_state();
+
+        let result = cpu.run_cycles(2 * CYCLES_PER_FRAME).unwrap();
+
+        assert_eq!(result, RunResult { cycles: 2 * CYCLES_PER_FRAME, frame: true });
+    }
+
+    #[test]
+    fn test_run_frame_surfaces_cpu_errors() {
+        let mut cpu = make_test_cpu(vec![0x00, 0x00, 0xD3]); // 0xD3 is unused
+
+        assert!(cpu.run_frame().is_err());
+        assert_eq!(cpu.registers().pc, 3);
+    }
+
     #[test]
     fn test_tick_returns_with_no_pending_apu_cycles() {
         let mut cpu = make_test_cpu(vec![0x00]);
@@ -3766,4 +4836,185 @@ mod tests {
 
         assert_eq!(cpu.pending_apu_cycles.cycles, 3);
     }
+
+    // --- CGB ---
+
+    /// A CGB-mode CPU running `program` from 0x0000 of a ROM whose header
+    /// sets the CGB flag.
+    fn make_cgb_cpu(program: &[u8]) -> Sm83 {
+        let mut rom = vec![0x00; 0x8000];
+        rom[..program.len()].copy_from_slice(program);
+        rom[CGB_FLAG_ADDR] = 0x80;
+        make_test_cpu(rom).with_model(Model::Cgb)
+    }
+
+    #[test]
+    fn test_model_follows_cgb_flag() {
+        assert_eq!(Model::for_cgb_flag(CgbFlag::DmgOnly), Model::Dmg);
+        assert_eq!(Model::for_cgb_flag(CgbFlag::CgbEnhanced), Model::Cgb);
+        assert_eq!(Model::for_cgb_flag(CgbFlag::CgbOnly), Model::Cgb);
+    }
+
+    #[test]
+    fn test_dmg_cart_on_cgb_runs_in_compat_mode() {
+        let cpu = make_test_cpu(vec![0x00; 0x8000]).with_model(Model::Cgb);
+
+        assert_eq!(cpu.model(), Model::Cgb);
+        assert!(!cpu.cgb_mode());
+        assert!(cpu.color_framebuffer().is_some());
+        assert_eq!(cpu.read_memory(KEY1_ADDR).unwrap(), 0xFF);
+        assert_eq!(cpu.read_memory(VBK_ADDR).unwrap(), 0xFF);
+    }
+
+    #[test]
+    fn test_post_boot_registers_depend_on_model() {
+        let dmg = make_test_cpu(vec![0x00; 0x8000]).with_post_boot_state();
+        assert_eq!(dmg.registers().a, 0x01);
+
+        let cgb = make_cgb_cpu(&[]).with_post_boot_state();
+        let registers = cgb.registers();
+        assert_eq!((registers.a, registers.d, registers.e), (0x11, 0xFF, 0x56));
+        assert_eq!((registers.pc, registers.sp), (0x0100, 0xFFFE));
+    }
+
+    #[test]
+    fn test_vbk_switches_vram_bank() {
+        let mut cpu = make_cgb_cpu(&[
+            0x3E, 0x01,       // LD A,1
+            0xE0, 0x4F,       // LDH (VBK),A
+            0x3E, 0x42,       // LD A,0x42
+            0xEA, 0x00, 0x80, // LD (0x8000),A
+            0xAF,             // XOR A
+            0xE0, 0x4F,       // LDH (VBK),A
+            0x00,             // NOP
+        ]);
+        for _ in 0..7 {
+            cpu.tick().unwrap();
+        }
+
+        assert_eq!(cpu.read_memory(VBK_ADDR).unwrap(), 0xFE);
+        assert_eq!(cpu.read_memory(0x8000).unwrap(), 0x00);
+        let (_, bank1) = cpu.memory.vram_banks();
+        assert_eq!(bank1[0], 0x42);
+    }
+
+    #[test]
+    fn test_vbk_is_plain_io_on_dmg() {
+        let mut cpu = make_test_cpu(vec![0x3E, 0x01, 0xE0, 0x4F, 0x00]);
+        for _ in 0..3 {
+            cpu.tick().unwrap();
+        }
+
+        assert_eq!(cpu.read_memory(VBK_ADDR).unwrap(), 0x01);
+        assert_eq!(cpu.memory.vram_bank(), 0);
+    }
+
+    #[test]
+    fn test_stop_with_key1_armed_switches_speed() {
+        let mut cpu = make_cgb_cpu(&[
+            0x3E, 0x01, // LD A,1
+            0xE0, 0x4D, // LDH (KEY1),A
+            0x10, 0x00, // STOP
+            0x00,       // NOP
+        ]);
+        for _ in 0..3 {
+            cpu.tick().unwrap();
+        }
+
+        assert!(cpu.double_speed());
+        assert!(!cpu.is_stopped());
+        assert_eq!(cpu.registers().pc, 6);
+        assert_eq!(cpu.read_memory(KEY1_ADDR).unwrap(), 0xFE);
+
+        while cpu.stall_m_cycles > 0 {
+            cpu.tick().unwrap();
+        }
+        // An M-cycle now takes two dots.
+        assert_eq!(cpu.tick().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_double_speed_apu_keeps_odd_cycles() {
+        let mut batched = make_cgb_cpu(&[]);
+        let mut single = make_cgb_cpu(&[]);
+        for cpu in [&mut batched, &mut single] {
+            cpu.double_speed = true;
+            cpu.write_apu_register(NR52_ADDR, 0x80);
+            cpu.drain_audio_samples();
+        }
+
+        // The same CPU clocks in double speed, in batches of two and in
+        // batches of one, which halving with `>> 1` would zero.
+        for _ in 0..CYCLES_PER_FRAME as u32 {
+            batched.advance_timer(2);
+            batched.tick_apu(2);
+            for _ in 0..2 {
+                single.advance_timer(1);
+                single.tick_apu(1);
+            }
+        }
+
+        let samples = batched.drain_audio_samples().len();
+        assert!(samples > 0);
+        assert_eq!(single.drain_audio_samples().len(), samples);
+    }
+
+    #[test]
+    fn test_general_purpose_hdma_copies_into_vram() {
+        let mut cpu = make_cgb_cpu(&[
+            0x3E, 0xC0, // LD A,0xC0
+            0xE0, 0x51, // LDH (HDMA1),A
+            0xAF,       // XOR A
+            0xE0, 0x52, // LDH (HDMA2),A
+            0xE0, 0x53, // LDH (HDMA3),A
+            0xE0, 0x54, // LDH (HDMA4),A
+            0x3E, 0x01, // LD A,1 (two blocks)
+            0xE0, 0x55, // LDH (HDMA5),A
+            0x00,       // NOP
+        ]);
+        for i in 0..0x20u16 {
+            cpu.memory.write_fast(0xC000 + i, i as u8 + 1);
+        }
+        for _ in 0..9 {
+            cpu.tick().unwrap();
+        }
+
+        for i in 0..0x20u16 {
+            assert_eq!(cpu.read_memory(0x8000 + i).unwrap(), i as u8 + 1);
+        }
+        assert_eq!(cpu.read_memory(HDMA5_ADDR).unwrap(), 0xFF);
+        assert_eq!(cpu.stall_m_cycles, 16);
+    }
+
+    #[test]
+    fn test_cgb_save_state_round_trips_banks_and_speed() {
+        let mut cpu = make_cgb_cpu(&[]);
+        cpu.memory.set_wram_bank(3);
+        cpu.memory.write_fast(0xD000, 0x33);
+        cpu.memory.set_vram_bank(1);
+        cpu.memory.write_fast(0x9800, 0x07);
+        cpu.double_speed = true;
+        let blob = cpu.save_state();
+
+        let mut restored = make_cgb_cpu(&[]);
+        restored.load_state(SaveState::from_blob(blob).unwrap()).unwrap();
+
+        assert!(restored.double_speed());
+        assert_eq!(restored.memory.wram_bank(), 3);
+        assert_eq!(restored.read_memory(0xD000).unwrap(), 0x33);
+        assert_eq!(restored.read_memory(0x9800).unwrap(), 0x07);
+        assert_eq!(restored.read_memory(SVBK_ADDR).unwrap(), 0xFB);
+    }
+
+    #[test]
+    fn test_load_state_rejects_other_model() {
+        let blob = make_test_cpu(vec![0x00; 0x8000]).save_state();
+        let mut cpu = make_cgb_cpu(&[]);
+        cpu.memory.write_fast(0xC000, 0x5A);
+
+        let result = cpu.load_state(SaveState::from_blob(blob).unwrap());
+
+        assert_eq!(result, Err("save state is for a different hardware model"));
+        assert_eq!(cpu.read_memory(0xC000).unwrap(), 0x5A);
+    }
 }
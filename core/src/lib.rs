@@ -1,5 +1,7 @@
 #![no_std]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 pub mod cpu;
 pub mod memory;
@@ -6,6 +6,9 @@
 ///
 /// Writes to 0x0000–0x7FFF are intercepted by the MBC (not stored in ROM).
 use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt;
+
+use super::header::{CartridgeType, RomHeader, ROM_SIZE_ADDR};
 
 // ── Cartridge trait ──────────────────────────────────────────────────────────
 
@@ -51,11 +54,26 @@ pub trait Cartridge {
     fn current_rom_bank(&self) -> usize { 1 }
     /// Returns whether this cartridge needs per-M-cycle RTC ticking.
     fn has_rtc(&self) -> bool { false }
+    /// Returns whether the rumble motor is currently driven. Only MBC5 rumble
+    /// carts ever report `true`.
+    fn rumble_active(&self) -> bool { false }
     /// Advance the cartridge clock by `cycles` T-cycles (4 MHz). Only meaningful for
     /// MBC3 carts with an RTC; other implementations ignore this.
     fn tick_rtc(&mut self, _cycles: u32) {}
     /// Returns the full external RAM contents, or `None` if this cart has no battery-backed RAM.
     fn external_ram(&self) -> Option<&[u8]> { None }
+    /// Mutable view of the full external RAM, laid out like
+    /// [`Self::external_ram`]. `None` if the cart has no RAM.
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> { None }
+    /// Write `value` to RAM `bank` at `offset` (0x0000–0x1FFF), whatever
+    /// bank is mapped and whether RAM is enabled, for cheats that target a
+    /// specific bank. Writes past the end of RAM are dropped.
+    fn poke_external_ram(&mut self, bank: usize, offset: u16, value: u8) {
+        let index = bank * 0x2000 + offset as usize;
+        if let Some(byte) = self.external_ram_mut().and_then(|ram| ram.get_mut(index)) {
+            *byte = value;
+        }
+    }
     /// Overwrites the full external RAM from the given bytes. No-op if cart has no external RAM.
     fn set_external_ram(&mut self, _data: &[u8]) {}
     /// Serialize MBC register state (bank numbers, mode bits, etc.) into `out`.
@@ -70,14 +88,62 @@ pub trait Cartridge {
     }
 }
 
-// ── Header helpers ───────────────────────────────────────────────────────────
+// ── Errors ───────────────────────────────────────────────────────────────────
+
+/// Reasons a ROM image cannot be turned into a `Cartridge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CartridgeError {
+    /// Header byte 0x0147 names a cartridge type this emulator does not implement.
+    UnsupportedCartType(u8),
+    /// Header byte 0x0148 is not a known ROM size code.
+    UnsupportedRomSize(u8),
+    /// The image is too short to hold the cartridge header.
+    RomTooSmall {
+        expected_bytes: usize,
+        actual_bytes: usize,
+    },
+    /// The image length disagrees with the ROM size declared in the header.
+    RomSizeMismatch {
+        expected_bytes: usize,
+        actual_bytes: usize,
+    },
+    /// The header checksum at 0x014D does not match bytes 0x0134–0x014C.
+    /// Real hardware refuses to boot such a cartridge.
+    BadHeaderChecksum {
+        expected: u8,
+        actual: u8,
+    },
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CartridgeError::UnsupportedCartType(t) => {
+                write!(f, "unsupported cartridge type 0x{:02X}", t)
+            }
+            CartridgeError::UnsupportedRomSize(code) => {
+                write!(f, "unsupported ROM size code 0x{:02X}", code)
+            }
+            CartridgeError::RomTooSmall { expected_bytes, actual_bytes } => write!(
+                f,
+                "ROM is {} bytes, too small for a cartridge header ({} bytes)",
+                actual_bytes, expected_bytes
+            ),
+            CartridgeError::RomSizeMismatch { expected_bytes, actual_bytes } => write!(
+                f,
+                "ROM is {} bytes but its header declares {} bytes",
+                actual_bytes, expected_bytes
+            ),
+            CartridgeError::BadHeaderChecksum { expected, actual } => write!(
+                f,
+                "bad header checksum: computed 0x{:02X}, header has 0x{:02X}",
+                expected, actual
+            ),
+        }
+    }
+}
 
-/// ROM header byte 0x0147: cartridge type (MBC variant + peripherals).
-const CART_TYPE_ADDR: usize = 0x0147;
-/// ROM header byte 0x0148: ROM size code.
-const ROM_SIZE_ADDR: usize = 0x0148;
-/// ROM header byte 0x0149: RAM size code.
-const RAM_SIZE_ADDR: usize = 0x0149;
+// ── Header helpers ───────────────────────────────────────────────────────────
 
 /// Nintendo logo bytes stored at 0x0104 in the ROM header.
 const NINTENDO_LOGO: [u8; 48] = [
@@ -111,49 +177,52 @@ fn is_mbc1_multicart(data: &[u8], rom_bank_count: usize) -> bool {
 
 /// Construct the appropriate `Cartridge` impl from a ROM image.
 ///
-/// Reads the cartridge type, ROM size, and RAM size from the header and
-/// returns a `NoMbc`, `Mbc1`, `Mbc1Multicart`, or `Mbc3` accordingly.
-/// Panics on unsupported types.
+/// Parses the header with [`RomHeader::parse`] and returns a `NoMbc`, `Mbc1`,
+/// `Mbc1Multicart`, `Mbc2`, `Mbc3`, or `Mbc5` accordingly. Besides the header
+/// checks, the image length must match the ROM size the header declares.
 ///
 /// MBC1 multicart mode is detected heuristically: a 64-bank MBC1 ROM with
 /// the Nintendo logo present in banks 0x10, 0x20, and 0x30.
-pub fn from_rom(data: Vec<u8>) -> Box<dyn Cartridge> {
-    let cart_type = *data.get(CART_TYPE_ADDR).unwrap_or(&0);
-    let rom_size_code = *data.get(ROM_SIZE_ADDR).unwrap_or(&0);
-    let ram_size_code = *data.get(RAM_SIZE_ADDR).unwrap_or(&0);
-    let ram_bytes = decode_ram_size(ram_size_code);
-    let rom_bank_count = 2usize << rom_size_code;
-
-    match cart_type {
-        // ROM only (no MBC)
-        0x00 => Box::new(NoMbc::new(data)),
-        // MBC1, MBC1+RAM, MBC1+RAM+BATTERY
-        0x01 | 0x02 | 0x03 => {
-            if is_mbc1_multicart(&data, rom_bank_count) {
+pub fn from_rom(data: Vec<u8>) -> Result<Box<dyn Cartridge>, CartridgeError> {
+    let header = RomHeader::parse(&data)?;
+    if data.len() != header.rom_size {
+        return Err(CartridgeError::RomSizeMismatch {
+            expected_bytes: header.rom_size,
+            actual_bytes: data.len(),
+        });
+    }
+    let ram_bytes = header.ram_size;
+    let cart_type = header.cartridge_type;
+
+    let cart: Box<dyn Cartridge> = match cart_type {
+        CartridgeType::RomOnly => Box::new(NoMbc::new(data)),
+        CartridgeType::Mbc1 | CartridgeType::Mbc1Ram | CartridgeType::Mbc1RamBattery => {
+            if is_mbc1_multicart(&data, header.rom_bank_count()) {
                 Box::new(Mbc1Multicart::new(data, ram_bytes))
             } else {
                 Box::new(Mbc1::new(data, ram_bytes))
             }
         }
-        // MBC3+TIMER+BATTERY, MBC3+TIMER+RAM+BATTERY, MBC3, MBC3+RAM, MBC3+RAM+BATTERY
-        0x0F | 0x10 | 0x11 | 0x12 | 0x13 => {
-            let has_timer = matches!(cart_type, 0x0F | 0x10);
-            Box::new(Mbc3::new(data, ram_bytes, has_timer))
+        // RAM is built in; the header RAM size is 0
+        CartridgeType::Mbc2 | CartridgeType::Mbc2Battery => Box::new(Mbc2::new(data)),
+        CartridgeType::Mbc3TimerBattery
+        | CartridgeType::Mbc3TimerRamBattery
+        | CartridgeType::Mbc3
+        | CartridgeType::Mbc3Ram
+        | CartridgeType::Mbc3RamBattery => {
+            Box::new(Mbc3::new(data, ram_bytes, cart_type.has_timer()))
         }
-        other => panic!("Unsupported cartridge type: 0x{:02X}", other),
-    }
-}
-
-fn decode_ram_size(code: u8) -> usize {
-    match code {
-        0x00 => 0,
-        0x01 => 2 * 1024,        // 2 KiB (unofficial, treated as 8 KiB by some)
-        0x02 => 8 * 1024,        // 8 KiB — 1 bank
-        0x03 => 32 * 1024,       // 32 KiB — 4 banks
-        0x04 => 128 * 1024,      // 128 KiB — 16 banks
-        0x05 => 64 * 1024,       // 64 KiB — 8 banks
-        _ => 0,
-    }
+        CartridgeType::Mbc5
+        | CartridgeType::Mbc5Ram
+        | CartridgeType::Mbc5RamBattery
+        | CartridgeType::Mbc5Rumble
+        | CartridgeType::Mbc5RumbleRam
+        | CartridgeType::Mbc5RumbleRamBattery => {
+            Box::new(Mbc5::new(data, ram_bytes, cart_type.has_rumble()))
+        }
+        other => return Err(CartridgeError::UnsupportedCartType(other.code())),
+    };
+    Ok(cart)
 }
 
 fn rom_window(rom: &[u8], base: usize) -> (*const u8, usize) {
@@ -310,6 +379,9 @@ impl Cartridge for Mbc1 {
     fn external_ram(&self) -> Option<&[u8]> {
         if self.ram.is_empty() { None } else { Some(&self.ram) }
     }
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.ram.is_empty() { None } else { Some(&mut self.ram) }
+    }
     fn set_external_ram(&mut self, data: &[u8]) {
         let len = self.ram.len().min(data.len());
         self.ram[..len].copy_from_slice(&data[..len]);
@@ -480,6 +552,9 @@ impl Cartridge for Mbc1Multicart {
     fn external_ram(&self) -> Option<&[u8]> {
         if self.ram.is_empty() { None } else { Some(&self.ram) }
     }
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.ram.is_empty() { None } else { Some(&mut self.ram) }
+    }
     fn set_external_ram(&mut self, data: &[u8]) {
         let len = self.ram.len().min(data.len());
         self.ram[..len].copy_from_slice(&data[..len]);
@@ -560,6 +635,134 @@ impl Cartridge for Mbc1Multicart {
     }
 }
 
+// ── MBC2 ─────────────────────────────────────────────────────────────────────
+
+/// Size of the MBC2 built-in RAM: 512 half-bytes, one per address.
+const MBC2_RAM_SIZE: usize = 512;
+
+/// MBC2 memory bank controller with built-in 512×4-bit RAM.
+///
+/// Register map (writes to 0x0000–0x3FFF; address bit 8 selects the register):
+///   bit 8 clear  RAM enable: lower 4 bits == 0x0A enables RAM
+///   bit 8 set    ROM bank number (4-bit, 0→1)
+///
+/// Writes to 0x4000–0x7FFF are ignored. The RAM only has 9 address lines, so
+/// 0xA000–0xA1FF is mirrored across the whole 0xA000–0xBFFF window. Only the
+/// lower nibble of each byte is stored; the upper nibble reads back as 1s.
+///
+/// Pan Docs reference: <https://gbdev.io/pandocs/MBC2.html>
+pub struct Mbc2 {
+    rom: Vec<u8>,
+    /// Built-in RAM, one nibble per byte (upper nibble always stored as 0).
+    ram: Vec<u8>,
+    /// Currently selected ROM bank (1–15).
+    rom_bank: u8,
+    ram_enabled: bool,
+    /// Number of ROM banks (derived from ROM size). Used for bank masking.
+    rom_bank_count: usize,
+}
+
+impl Mbc2 {
+    pub fn new(data: Vec<u8>) -> Self {
+        let rom_size_code = *data.get(ROM_SIZE_ADDR).unwrap_or(&0);
+        let rom_bank_count = 2usize << rom_size_code; // 2, 4, 8, 16
+
+        Self {
+            rom: data,
+            ram: vec![0u8; MBC2_RAM_SIZE],
+            rom_bank: 1,
+            ram_enabled: false,
+            rom_bank_count,
+        }
+    }
+
+    /// Effective ROM bank for the switchable region (0x4000–0x7FFF).
+    fn rom_bank(&self) -> usize {
+        self.rom_bank as usize % self.rom_bank_count
+    }
+}
+
+impl Cartridge for Mbc2 {
+    fn rom_windows(&self) -> Option<CartridgeRomWindows> {
+        let (fixed_ptr, fixed_len) = rom_window(&self.rom, 0);
+        let (banked_ptr, banked_len) = rom_window(&self.rom, self.rom_bank() * 0x4000);
+        Some(CartridgeRomWindows {
+            fixed_ptr,
+            fixed_len,
+            banked_ptr,
+            banked_len,
+        })
+    }
+
+    fn external_ram(&self) -> Option<&[u8]> {
+        Some(&self.ram)
+    }
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        Some(&mut self.ram)
+    }
+    /// Only bank 0 exists; `offset` mirrors every 512 bytes and only the
+    /// lower nibble is stored, as for a write through 0xA000–0xBFFF.
+    fn poke_external_ram(&mut self, bank: usize, offset: u16, value: u8) {
+        if bank == 0 {
+            self.ram[offset as usize % MBC2_RAM_SIZE] = value & 0x0F;
+        }
+    }
+    fn set_external_ram(&mut self, data: &[u8]) {
+        let len = self.ram.len().min(data.len());
+        for (dst, src) in self.ram[..len].iter_mut().zip(data) {
+            *dst = src & 0x0F;
+        }
+    }
+    fn current_rom_bank(&self) -> usize { self.rom_bank() }
+
+    fn read_rom(&self, addr: u16) -> u8 {
+        let physical = match addr {
+            0x0000..=0x3FFF => addr as usize,
+            0x4000..=0x7FFF => self.rom_bank() * 0x4000 + (addr as usize - 0x4000),
+            _ => return 0xFF,
+        };
+        self.rom.get(physical).copied().unwrap_or(0xFF)
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        self.ram[addr as usize % MBC2_RAM_SIZE] | 0xF0
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            // Address bit 8 clear: RAM enable
+            0x0000..=0x3FFF if addr & 0x0100 == 0 => {
+                self.ram_enabled = value & 0x0F == 0x0A;
+            }
+            // Address bit 8 set: ROM bank number (4-bit, 0→1)
+            0x0000..=0x3FFF => {
+                let bank = value & 0x0F;
+                self.rom_bank = if bank == 0 { 1 } else { bank };
+            }
+            // External RAM write (lower nibble only, mirrored every 512 bytes)
+            0xA000..=0xBFFF if self.ram_enabled => {
+                self.ram[(addr - 0xA000) as usize % MBC2_RAM_SIZE] = value & 0x0F;
+            }
+            _ => {}
+        }
+    }
+
+    fn save_mbc_state(&self, out: &mut Vec<u8>) {
+        out.push(self.rom_bank);
+        out.push(self.ram_enabled as u8);
+    }
+
+    fn load_mbc_state(&mut self, data: &[u8], offset: usize) -> usize {
+        if data.len() < offset + 2 { return 0; }
+        self.rom_bank    = (data[offset] & 0x0F).max(1);
+        self.ram_enabled = data[offset + 1] != 0;
+        2
+    }
+}
+
 // ── MBC3 ─────────────────────────────────────────────────────────────────────
 
 /// MBC3 real-time clock registers, latched on command.
@@ -716,6 +919,9 @@ impl Cartridge for Mbc3 {
     fn external_ram(&self) -> Option<&[u8]> {
         if self.ram.is_empty() { None } else { Some(&self.ram) }
     }
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.ram.is_empty() { None } else { Some(&mut self.ram) }
+    }
     fn set_external_ram(&mut self, data: &[u8]) {
         let len = self.ram.len().min(data.len());
         self.ram[..len].copy_from_slice(&data[..len]);
@@ -852,11 +1058,162 @@ impl Cartridge for Mbc3 {
     }
 }
 
+// ── MBC5 ─────────────────────────────────────────────────────────────────────
+
+/// MBC5 memory bank controller, with optional rumble motor.
+///
+/// Register map (writes to ROM space):
+///   0x0000–0x1FFF  RAM enable: exactly 0x0A enables, anything else disables
+///   0x2000–0x2FFF  ROM bank number, lower 8 bits
+///   0x3000–0x3FFF  ROM bank number, bit 8
+///   0x4000–0x5FFF  RAM bank number (4-bit). On rumble carts bit 3 drives the
+///                  motor instead, leaving 3 bits for the RAM bank.
+///
+/// Unlike MBC1/MBC3 there is no 0→1 quirk: bank 0 can be mapped into the
+/// switchable window.
+///
+/// Pan Docs reference: <https://gbdev.io/pandocs/MBC5.html>
+pub struct Mbc5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    /// 9-bit ROM bank register (0–511).
+    rom_bank: u16,
+    /// Raw RAM bank register, including the rumble bit on rumble carts.
+    ram_bank: u8,
+    ram_enabled: bool,
+    /// Whether bit 3 of the RAM bank register is wired to a rumble motor.
+    has_rumble: bool,
+    /// Number of ROM banks (derived from ROM size). Used for bank masking.
+    rom_bank_count: usize,
+    /// Number of RAM banks. Used for bank masking.
+    ram_bank_count: usize,
+}
+
+impl Mbc5 {
+    pub fn new(data: Vec<u8>, ram_bytes: usize, has_rumble: bool) -> Self {
+        let rom_size_code = *data.get(ROM_SIZE_ADDR).unwrap_or(&0);
+        let rom_bank_count = 2usize << rom_size_code; // 2, 4, 8, ..., 512
+        let ram_bank_count = (ram_bytes / 0x2000).max(1);
+
+        Self {
+            rom: data,
+            ram: vec![0u8; ram_bytes],
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: false,
+            has_rumble,
+            rom_bank_count,
+            ram_bank_count,
+        }
+    }
+
+    /// Effective ROM bank for the switchable region (0x4000–0x7FFF).
+    fn rom_bank(&self) -> usize {
+        self.rom_bank as usize % self.rom_bank_count
+    }
+
+    /// Effective RAM bank, with the rumble bit stripped on rumble carts.
+    fn ram_bank(&self) -> usize {
+        let mask = if self.has_rumble { 0x07 } else { 0x0F };
+        (self.ram_bank & mask) as usize % self.ram_bank_count
+    }
+}
+
+impl Cartridge for Mbc5 {
+    fn rom_windows(&self) -> Option<CartridgeRomWindows> {
+        let (fixed_ptr, fixed_len) = rom_window(&self.rom, 0);
+        let (banked_ptr, banked_len) = rom_window(&self.rom, self.rom_bank() * 0x4000);
+        Some(CartridgeRomWindows {
+            fixed_ptr,
+            fixed_len,
+            banked_ptr,
+            banked_len,
+        })
+    }
+
+    fn external_ram(&self) -> Option<&[u8]> {
+        if self.ram.is_empty() { None } else { Some(&self.ram) }
+    }
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.ram.is_empty() { None } else { Some(&mut self.ram) }
+    }
+    fn set_external_ram(&mut self, data: &[u8]) {
+        let len = self.ram.len().min(data.len());
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+    fn current_rom_bank(&self) -> usize { self.rom_bank() }
+
+    fn rumble_active(&self) -> bool {
+        self.has_rumble && self.ram_bank & 0x08 != 0
+    }
+
+    fn read_rom(&self, addr: u16) -> u8 {
+        let physical = match addr {
+            0x0000..=0x3FFF => addr as usize,
+            0x4000..=0x7FFF => self.rom_bank() * 0x4000 + (addr as usize - 0x4000),
+            _ => return 0xFF,
+        };
+        self.rom.get(physical).copied().unwrap_or(0xFF)
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled || self.ram.is_empty() {
+            return 0xFF;
+        }
+        let offset = self.ram_bank() * 0x2000 + addr as usize;
+        self.ram.get(offset).copied().unwrap_or(0xFF)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            // RAM enable: MBC5 decodes all 8 bits, not just the lower nibble
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            // ROM bank number, lower 8 bits
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            // ROM bank number, bit 8
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0xFF) | (((value & 0x01) as u16) << 8)
+            }
+            // RAM bank number (+ rumble motor)
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            // External RAM write
+            0xA000..=0xBFFF if self.ram_enabled => {
+                let offset = self.ram_bank() * 0x2000 + (addr - 0xA000) as usize;
+                if let Some(b) = self.ram.get_mut(offset) {
+                    *b = value;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn save_mbc_state(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.rom_bank.to_le_bytes());
+        out.push(self.ram_bank);
+        out.push(self.ram_enabled as u8);
+    }
+
+    fn load_mbc_state(&mut self, data: &[u8], offset: usize) -> usize {
+        if data.len() < offset + 4 { return 0; }
+        self.rom_bank    = u16::from_le_bytes([data[offset], data[offset + 1]]) & 0x1FF;
+        self.ram_bank    = data[offset + 2] & 0x0F;
+        self.ram_enabled = data[offset + 3] != 0;
+        4
+    }
+}
+
 // ── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::header::{header_checksum, CART_TYPE_ADDR, HEADER_CHECKSUM_ADDR, RAM_SIZE_ADDR};
+
+    /// Stamp a valid header checksum and build the cartridge via `from_rom`.
+    fn load_rom(mut data: Vec<u8>) -> Box<dyn Cartridge> {
+        data[HEADER_CHECKSUM_ADDR] = header_checksum(&data);
+        from_rom(data).expect("test ROM should be accepted")
+    }
 
     fn make_rom(size_kb: usize, cart_type: u8) -> Vec<u8> {
         let size = size_kb * 1024;
@@ -1044,7 +1401,7 @@ mod tests {
     fn from_rom_no_mbc_for_type_00() {
         let mut data = vec![0u8; 0x8000];
         data[CART_TYPE_ADDR] = 0x00;
-        let cart = from_rom(data);
+        let cart = load_rom(data);
         assert_eq!(cart.read_rom(0x0000), 0x00);
     }
 
@@ -1052,7 +1409,7 @@ mod tests {
     fn from_rom_mbc1_for_type_01() {
         let mut data = make_rom(64, 0x01);
         data[CART_TYPE_ADDR] = 0x01;
-        let cart = from_rom(data);
+        let cart = load_rom(data);
         assert_eq!(cart.read_rom(0x4000), 0x01); // default bank 1
     }
 
@@ -1060,7 +1417,7 @@ mod tests {
     fn from_rom_mbc1_ram_for_type_02() {
         let mut data = make_rom(64, 0x02);
         data[RAM_SIZE_ADDR] = 0x02; // 8 KiB RAM
-        let mut cart = from_rom(data);
+        let mut cart = load_rom(data);
         cart.write(0x0000, 0x0A); // enable RAM
         cart.write(0xA000, 0xCC);
         assert_eq!(cart.read_ram(0x0000), 0xCC);
@@ -1070,7 +1427,7 @@ mod tests {
     fn from_rom_mbc1_battery_for_type_03() {
         let mut data = make_rom(64, 0x03);
         data[RAM_SIZE_ADDR] = 0x02; // 8 KiB RAM
-        let mut cart = from_rom(data);
+        let mut cart = load_rom(data);
         cart.write(0x0000, 0x0A);
         cart.write(0xA000, 0xDD);
         assert_eq!(cart.read_ram(0x0000), 0xDD);
@@ -1091,7 +1448,7 @@ mod tests {
     #[test]
     fn from_rom_detects_multicart_by_logo_heuristic() {
         let data = make_multicart_rom();
-        let mut cart = from_rom(data);
+        let mut cart = load_rom(data);
         // Multicart: BANK2=1 shifts by 4, so bank = (1<<4)|1 = 17.
         cart.write(0x4000, 0x01); // upper = 1
         cart.write(0x2000, 0x01); // lower = 1
@@ -1102,7 +1459,7 @@ mod tests {
     fn from_rom_no_multicart_without_logos() {
         // 64-bank MBC1 but logos missing → normal MBC1 (upper << 5 shift).
         let data = make_rom(1024, 0x01);
-        let mut cart = from_rom(data);
+        let mut cart = load_rom(data);
         cart.write(0x4000, 0x01); // upper = 1
         cart.write(0x2000, 0x01); // lower = 1
         // Normal MBC1: bank = (1<<5)|1 = 33.
@@ -1119,13 +1476,152 @@ mod tests {
                 data[base..base + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
             }
         }
-        let mut cart = from_rom(data);
+        let mut cart = load_rom(data);
         cart.write(0x4000, 0x01);
         cart.write(0x2000, 0x01);
         // Normal MBC1: bank = (1<<5)|1 = 33, masked to 32 banks → 33 % 32 = 1.
         assert_eq!(cart.read_rom(0x4000), 1);
     }
 
+    // ── MBC2 ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn mbc2_default_reads_bank0_and_bank1() {
+        let cart = Mbc2::new(make_rom(256, 0x05));
+        assert_eq!(cart.read_rom(0x0000), 0x00);
+        assert_eq!(cart.read_rom(0x4000), 0x01);
+    }
+
+    #[test]
+    fn mbc2_address_bit8_selects_rom_bank_register() {
+        let mut cart = Mbc2::new(make_rom(256, 0x05));
+        cart.write(0x2100, 0x05);
+        assert_eq!(cart.read_rom(0x4000), 0x05);
+        // Bit 8 clear goes to RAM enable, leaving the bank untouched
+        cart.write(0x2000, 0x07);
+        assert_eq!(cart.read_rom(0x4000), 0x05);
+        // Any address in 0x0000–0x3FFF with bit 8 set works
+        cart.write(0x0100, 0x03);
+        assert_eq!(cart.read_rom(0x4000), 0x03);
+    }
+
+    #[test]
+    fn mbc2_bank_0_write_selects_bank1() {
+        let mut cart = Mbc2::new(make_rom(256, 0x05));
+        cart.write(0x2100, 0x00);
+        assert_eq!(cart.current_rom_bank(), 1);
+        cart.write(0x2100, 0x10); // only the lower 4 bits count
+        assert_eq!(cart.current_rom_bank(), 1);
+    }
+
+    #[test]
+    fn mbc2_bank_number_masked_to_rom_size() {
+        let mut cart = Mbc2::new(make_rom(64, 0x05)); // 4 banks
+        cart.write(0x2100, 0x07);
+        assert_eq!(cart.read_rom(0x4000), 0x03);
+    }
+
+    #[test]
+    fn mbc2_writes_to_upper_rom_half_are_ignored() {
+        let mut cart = Mbc2::new(make_rom(256, 0x05));
+        cart.write(0x4100, 0x05);
+        cart.write(0x6100, 0x05);
+        assert_eq!(cart.read_rom(0x4000), 0x01);
+    }
+
+    #[test]
+    fn mbc2_ram_enable_requires_bit8_clear() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.write(0x0100, 0x0A); // bit 8 set: ROM bank, not RAM enable
+        assert_eq!(cart.read_ram(0x0000), 0xFF);
+        cart.write(0x0000, 0x0A);
+        assert_eq!(cart.read_ram(0x0000), 0xF0);
+    }
+
+    #[test]
+    fn mbc2_ram_stores_lower_nibble_only() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA000, 0xA5);
+        assert_eq!(cart.read_ram(0x0000), 0xF5);
+    }
+
+    #[test]
+    fn mbc2_ram_mirrors_every_512_bytes() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA1FF, 0x0C);
+        assert_eq!(cart.read_ram(0x01FF), 0xFC);
+        assert_eq!(cart.read_ram(0x03FF), 0xFC);
+        assert_eq!(cart.read_ram(0x1FFF), 0xFC);
+        cart.write(0xBE00, 0x03); // mirror of 0xA000
+        assert_eq!(cart.read_ram(0x0000), 0xF3);
+    }
+
+    #[test]
+    fn mbc2_ram_disabled_write_is_ignored() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.write(0xA000, 0x0F);
+        cart.write(0x0000, 0x0A);
+        assert_eq!(cart.read_ram(0x0000), 0xF0);
+    }
+
+    #[test]
+    fn mbc2_external_ram_is_512_nibbles() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA010, 0x09);
+        let ram = cart.external_ram().expect("mbc2 should have built-in ram");
+        assert_eq!(ram.len(), MBC2_RAM_SIZE);
+        assert_eq!(ram[0x10], 0x09);
+    }
+
+    #[test]
+    fn mbc2_set_external_ram_masks_upper_nibble() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.set_external_ram(&[0xFF; 4]);
+        cart.write(0x0000, 0x0A);
+        assert_eq!(cart.external_ram().unwrap()[0], 0x0F);
+        assert_eq!(cart.read_ram(0x0000), 0xFF);
+    }
+
+    #[test]
+    fn mbc2_poke_stores_mirrored_nibble_in_bank0_only() {
+        let mut cart = Mbc2::new(make_rom(64, 0x06));
+        cart.poke_external_ram(0, 0x1234, 0xA5); // RAM disabled: poke ignores it
+        cart.poke_external_ram(1, 0x0000, 0xA5);
+        let ram = cart.external_ram().unwrap();
+        assert_eq!(ram[0x034], 0x05);
+        assert_eq!(ram[0], 0x00);
+    }
+
+    #[test]
+    fn mbc2_save_load_mbc_state_roundtrip() {
+        let data = make_rom(256, 0x06);
+        let mut cart = Mbc2::new(data.clone());
+        cart.write(0x2100, 0x0B);
+        cart.write(0x0000, 0x0A);
+
+        let mut blob = Vec::new();
+        cart.save_mbc_state(&mut blob);
+        assert_eq!(blob.len(), 2);
+
+        let mut cart2 = Mbc2::new(data);
+        assert_eq!(cart2.load_mbc_state(&blob, 0), 2);
+        assert_eq!(cart2.read_rom(0x4000), 0x0B);
+        assert_eq!(cart2.read_ram(0x0000), 0xF0); // RAM enabled
+    }
+
+    #[test]
+    fn from_rom_dispatches_mbc2_types() {
+        for &cart_type in &[0x05u8, 0x06] {
+            let mut cart = load_rom(make_rom(256, cart_type));
+            cart.write(0x2100, 0x02);
+            assert_eq!(cart.read_rom(0x4000), 0x02);
+            assert_eq!(cart.external_ram().map(|r| r.len()), Some(MBC2_RAM_SIZE));
+        }
+    }
+
     // ── MBC3 ─────────────────────────────────────────────────────────────────
 
     fn make_mbc3_rom(size_kb: usize, cart_type: u8) -> Vec<u8> {
@@ -1282,7 +1778,7 @@ mod tests {
     fn from_rom_dispatches_mbc3_types() {
         for &cart_type in &[0x0Fu8, 0x10, 0x11, 0x12, 0x13] {
             let data = make_mbc3_rom(1024, cart_type);
-            let mut cart = from_rom(data);
+            let mut cart = load_rom(data);
             // Basic sanity: bank switching works
             cart.write(0x2000, 0x02);
             assert_eq!(cart.read_rom(0x4000), 0x02);
@@ -1417,7 +1913,7 @@ mod tests {
     #[test]
     fn mbc1_multicart_save_load_mbc_state_roundtrip() {
         let data = make_multicart_rom();
-        let mut cart = from_rom(data.clone());
+        let mut cart = load_rom(data.clone());
         // Select sub-game 1 (upper=1) bank 3 (lower=3) → bank (1<<4)|3 = 19
         cart.write(0x4000, 0x01);
         cart.write(0x2000, 0x03);
@@ -1426,11 +1922,236 @@ mod tests {
         let mut blob = Vec::new();
         cart.save_mbc_state(&mut blob);
 
-        let mut cart2 = from_rom(data);
+        let mut cart2 = load_rom(data);
         cart2.load_mbc_state(&blob, 0);
         assert_eq!(cart2.read_rom(0x4000), 19);
     }
 
+    // ── MBC5 ─────────────────────────────────────────────────────────────────
+
+    /// Like `make_rom`, but stores the 16-bit bank number in the first two
+    /// bytes of every bank so banks above 255 stay distinguishable.
+    fn make_mbc5_rom(banks: usize, cart_type: u8) -> Vec<u8> {
+        let mut data = vec![0u8; banks * 0x4000];
+        for bank in 0..banks {
+            let base = bank * 0x4000;
+            data[base..base + 2].copy_from_slice(&(bank as u16).to_le_bytes());
+        }
+        data[CART_TYPE_ADDR] = cart_type;
+        data[ROM_SIZE_ADDR] = (banks / 2).trailing_zeros() as u8;
+        data
+    }
+
+    fn mbc5_bank_marker(cart: &dyn Cartridge) -> u16 {
+        u16::from_le_bytes([cart.read_rom(0x4000), cart.read_rom(0x4001)])
+    }
+
+    #[test]
+    fn mbc5_default_reads_bank0_and_bank1() {
+        let cart = Mbc5::new(make_mbc5_rom(4, 0x19), 0, false);
+        assert_eq!(cart.read_rom(0x0000), 0x00);
+        assert_eq!(mbc5_bank_marker(&cart), 1);
+    }
+
+    #[test]
+    fn mbc5_bank_0_is_selectable_in_switchable_window() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x19), 0, false);
+        cart.write(0x2000, 0x00);
+        assert_eq!(cart.current_rom_bank(), 0);
+        assert_eq!(mbc5_bank_marker(&cart), 0);
+    }
+
+    #[test]
+    fn mbc5_ninth_bank_bit_selects_upper_banks() {
+        let mut cart = Mbc5::new(make_mbc5_rom(512, 0x19), 0, false);
+        cart.write(0x2000, 0x23);
+        cart.write(0x3000, 0x01);
+        assert_eq!(mbc5_bank_marker(&cart), 0x123);
+        // Low byte writes preserve bit 8
+        cart.write(0x2FFF, 0xFF);
+        assert_eq!(mbc5_bank_marker(&cart), 0x1FF);
+        cart.write(0x3FFF, 0x00);
+        assert_eq!(mbc5_bank_marker(&cart), 0x0FF);
+    }
+
+    #[test]
+    fn mbc5_bank_number_masked_to_rom_size() {
+        let mut cart = Mbc5::new(make_mbc5_rom(8, 0x19), 0, false);
+        cart.write(0x2000, 0x0B); // 11 % 8 = 3
+        assert_eq!(mbc5_bank_marker(&cart), 3);
+    }
+
+    #[test]
+    fn mbc5_rom_windows_track_bank_switch() {
+        let mut cart = Mbc5::new(make_mbc5_rom(512, 0x19), 0, false);
+        cart.write(0x2000, 0x80);
+        cart.write(0x3000, 0x01);
+        let windows = cart.rom_windows().unwrap();
+        assert_eq!(windows.banked_len, 0x4000);
+        let marker = unsafe { [*windows.banked_ptr, *windows.banked_ptr.add(1)] };
+        assert_eq!(u16::from_le_bytes(marker), 0x180);
+    }
+
+    #[test]
+    fn mbc5_ram_enable_requires_exact_0x0a() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x1A), 8 * 1024, false);
+        cart.write(0x0000, 0x1A); // lower nibble matches but MBC5 checks all bits
+        cart.write(0xA000, 0x55);
+        assert_eq!(cart.read_ram(0x0000), 0xFF);
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA000, 0x55);
+        assert_eq!(cart.read_ram(0x0000), 0x55);
+    }
+
+    #[test]
+    fn mbc5_sixteen_ram_banks() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x1B), 128 * 1024, false);
+        cart.write(0x0000, 0x0A);
+        for bank in 0..16u8 {
+            cart.write(0x4000, bank);
+            cart.write(0xA000, bank + 0x10);
+        }
+        for bank in 0..16u8 {
+            cart.write(0x4000, bank);
+            assert_eq!(cart.read_ram(0x0000), bank + 0x10);
+        }
+    }
+
+    #[test]
+    fn mbc5_rumble_bit_drives_motor_not_ram_bank() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x1E), 32 * 1024, true);
+        cart.write(0x0000, 0x0A);
+        cart.write(0x4000, 0x01);
+        cart.write(0xA000, 0x77);
+        assert!(!cart.rumble_active());
+        cart.write(0x4000, 0x09); // motor on, still RAM bank 1
+        assert!(cart.rumble_active());
+        assert_eq!(cart.read_ram(0x0000), 0x77);
+        cart.write(0x4000, 0x01);
+        assert!(!cart.rumble_active());
+    }
+
+    #[test]
+    fn mbc5_without_rumble_never_reports_motor() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x1B), 128 * 1024, false);
+        cart.write(0x4000, 0x08);
+        assert!(!cart.rumble_active());
+    }
+
+    #[test]
+    fn mbc5_external_ram_roundtrips() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x1B), 32 * 1024, false);
+        let payload = vec![0x5A; 64];
+        cart.set_external_ram(&payload);
+        let ram = cart.external_ram().expect("mbc5 should have external ram");
+        assert_eq!(&ram[..payload.len()], &payload[..]);
+    }
+
+    #[test]
+    fn mbc5_no_ram_external_ram_is_none() {
+        let cart = Mbc5::new(make_mbc5_rom(4, 0x19), 0, false);
+        assert!(cart.external_ram().is_none());
+    }
+
+    #[test]
+    fn mbc5_save_load_mbc_state_roundtrip() {
+        let data = make_mbc5_rom(512, 0x1E);
+        let mut cart = Mbc5::new(data.clone(), 128 * 1024, true);
+        cart.write(0x2000, 0x42);
+        cart.write(0x3000, 0x01);
+        cart.write(0x4000, 0x0B); // RAM bank 3 + rumble
+        cart.write(0x0000, 0x0A);
+
+        let mut blob = Vec::new();
+        cart.save_mbc_state(&mut blob);
+        assert_eq!(blob.len(), 4);
+
+        let mut cart2 = Mbc5::new(data, 128 * 1024, true);
+        let consumed = cart2.load_mbc_state(&blob, 0);
+        assert_eq!(consumed, 4);
+        assert_eq!(mbc5_bank_marker(&cart2), 0x142);
+        assert!(cart2.rumble_active());
+    }
+
+    #[test]
+    fn mbc5_load_mbc_state_too_short_returns_zero() {
+        let mut cart = Mbc5::new(make_mbc5_rom(4, 0x19), 0, false);
+        assert_eq!(cart.load_mbc_state(&[0u8; 3], 0), 0);
+    }
+
+    #[test]
+    fn from_rom_dispatches_mbc5_types() {
+        for cart_type in 0x19u8..=0x1E {
+            let mut cart = load_rom(make_mbc5_rom(512, cart_type));
+            cart.write(0x2000, 0x00);
+            cart.write(0x3000, 0x01);
+            assert_eq!(mbc5_bank_marker(cart.as_ref()), 0x100);
+        }
+    }
+
+    // ── from_rom validation ──────────────────────────────────────────────────
+
+    fn with_checksum(mut data: Vec<u8>) -> Vec<u8> {
+        data[HEADER_CHECKSUM_ADDR] = header_checksum(&data);
+        data
+    }
+
+    #[test]
+    fn from_rom_rejects_rom_smaller_than_header() {
+        assert_eq!(
+            from_rom(vec![0u8; 0x14F]).err(),
+            Some(CartridgeError::RomTooSmall { expected_bytes: 0x150, actual_bytes: 0x14F })
+        );
+    }
+
+    #[test]
+    fn from_rom_rejects_bad_header_checksum() {
+        let mut data = with_checksum(make_rom(32, 0x00));
+        let expected = data[HEADER_CHECKSUM_ADDR];
+        data[HEADER_CHECKSUM_ADDR] = expected.wrapping_add(1);
+        assert_eq!(
+            from_rom(data).err(),
+            Some(CartridgeError::BadHeaderChecksum { expected, actual: expected.wrapping_add(1) })
+        );
+    }
+
+    #[test]
+    fn from_rom_rejects_unsupported_cart_type() {
+        // 0x22 = MBC7+SENSOR+RUMBLE+RAM+BATTERY
+        let data = with_checksum(make_rom(32, 0x22));
+        assert_eq!(from_rom(data).err(), Some(CartridgeError::UnsupportedCartType(0x22)));
+    }
+
+    #[test]
+    fn from_rom_rejects_unknown_rom_size_code() {
+        let mut data = make_rom(32, 0x00);
+        data[ROM_SIZE_ADDR] = 0x52;
+        assert_eq!(
+            from_rom(with_checksum(data)).err(),
+            Some(CartridgeError::UnsupportedRomSize(0x52))
+        );
+    }
+
+    #[test]
+    fn from_rom_rejects_truncated_rom() {
+        let mut data = make_rom(64, 0x01);
+        data.truncate(0x6000);
+        assert_eq!(
+            from_rom(with_checksum(data)).err(),
+            Some(CartridgeError::RomSizeMismatch { expected_bytes: 0x10000, actual_bytes: 0x6000 })
+        );
+    }
+
+    #[test]
+    fn from_rom_rejects_rom_larger_than_header_declares() {
+        let mut data = make_rom(64, 0x01);
+        data[ROM_SIZE_ADDR] = 0x00; // declares 32 KiB
+        assert_eq!(
+            from_rom(with_checksum(data)).err(),
+            Some(CartridgeError::RomSizeMismatch { expected_bytes: 0x8000, actual_bytes: 0x10000 })
+        );
+    }
+
     // ── NoMbc set_external_ram is no-op ──────────────────────────────────────
 
     #[test]
@@ -0,0 +1,556 @@
+//! Cartridge header parsing.
+//!
+//! Every Game Boy ROM carries a header at 0x0100–0x014F describing the
+//! cartridge: title, publisher, CGB/SGB support, MBC type, ROM and RAM sizes,
+//! and two checksums. The boot ROM refuses to start a cartridge whose header
+//! checksum is wrong; the global checksum is informational only.
+use alloc::string::String;
+
+use super::cartridge::CartridgeError;
+
+// ── Header offsets ───────────────────────────────────────────────────────────
+
+/// First byte past the cartridge header.
+pub(crate) const HEADER_END: usize = 0x0150;
+/// Title, upper-case ASCII padded with 0x00. Up to 16 bytes on DMG carts.
+const TITLE_ADDR: usize = 0x0134;
+/// 4-byte manufacturer code on later carts (overlaps the end of the title).
+const MANUFACTURER_ADDR: usize = 0x013F;
+/// CGB flag: 0x80 = CGB enhanced, 0xC0 = CGB only.
+pub(crate) const CGB_FLAG_ADDR: usize = 0x0143;
+/// 2-byte ASCII publisher code, used when the old licensee byte is 0x33.
+const NEW_LICENSEE_ADDR: usize = 0x0144;
+/// SGB flag: 0x03 = SGB functions supported.
+const SGB_FLAG_ADDR: usize = 0x0146;
+/// Cartridge type (MBC variant + peripherals).
+pub(crate) const CART_TYPE_ADDR: usize = 0x0147;
+/// ROM size code: 32 KiB << code.
+pub(crate) const ROM_SIZE_ADDR: usize = 0x0148;
+/// RAM size code.
+pub(crate) const RAM_SIZE_ADDR: usize = 0x0149;
+/// Old licensee code; 0x33 means "see the new licensee code".
+const OLD_LICENSEE_ADDR: usize = 0x014B;
+/// Mask ROM version number.
+const VERSION_ADDR: usize = 0x014C;
+/// Header checksum over 0x0134–0x014C.
+pub(crate) const HEADER_CHECKSUM_ADDR: usize = 0x014D;
+/// Big-endian sum of every other byte in the ROM.
+const GLOBAL_CHECKSUM_ADDR: usize = 0x014E;
+
+/// Old licensee value meaning the publisher is in the new licensee code and
+/// the SGB flag is honoured.
+const USE_NEW_LICENSEE: u8 = 0x33;
+
+// ── CartridgeType ────────────────────────────────────────────────────────────
+
+/// Header byte 0x0147: which MBC the cartridge uses and what hardware sits
+/// beside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CartridgeType {
+    RomOnly,
+    Mbc1,
+    Mbc1Ram,
+    Mbc1RamBattery,
+    Mbc2,
+    Mbc2Battery,
+    RomRam,
+    RomRamBattery,
+    Mmm01,
+    Mmm01Ram,
+    Mmm01RamBattery,
+    Mbc3TimerBattery,
+    Mbc3TimerRamBattery,
+    Mbc3,
+    Mbc3Ram,
+    Mbc3RamBattery,
+    Mbc5,
+    Mbc5Ram,
+    Mbc5RamBattery,
+    Mbc5Rumble,
+    Mbc5RumbleRam,
+    Mbc5RumbleRamBattery,
+    Mbc6,
+    Mbc7SensorRumbleRamBattery,
+    PocketCamera,
+    BandaiTama5,
+    HuC3,
+    HuC1RamBattery,
+    Unknown(u8),
+}
+
+impl CartridgeType {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x00 => CartridgeType::RomOnly,
+            0x01 => CartridgeType::Mbc1,
+            0x02 => CartridgeType::Mbc1Ram,
+            0x03 => CartridgeType::Mbc1RamBattery,
+            0x05 => CartridgeType::Mbc2,
+            0x06 => CartridgeType::Mbc2Battery,
+            0x08 => CartridgeType::RomRam,
+            0x09 => CartridgeType::RomRamBattery,
+            0x0B => CartridgeType::Mmm01,
+            0x0C => CartridgeType::Mmm01Ram,
+            0x0D => CartridgeType::Mmm01RamBattery,
+            0x0F => CartridgeType::Mbc3TimerBattery,
+            0x10 => CartridgeType::Mbc3TimerRamBattery,
+            0x11 => CartridgeType::Mbc3,
+            0x12 => CartridgeType::Mbc3Ram,
+            0x13 => CartridgeType::Mbc3RamBattery,
+            0x19 => CartridgeType::Mbc5,
+            0x1A => CartridgeType::Mbc5Ram,
+            0x1B => CartridgeType::Mbc5RamBattery,
+            0x1C => CartridgeType::Mbc5Rumble,
+            0x1D => CartridgeType::Mbc5RumbleRam,
+            0x1E => CartridgeType::Mbc5RumbleRamBattery,
+            0x20 => CartridgeType::Mbc6,
+            0x22 => CartridgeType::Mbc7SensorRumbleRamBattery,
+            0xFC => CartridgeType::PocketCamera,
+            0xFD => CartridgeType::BandaiTama5,
+            0xFE => CartridgeType::HuC3,
+            0xFF => CartridgeType::HuC1RamBattery,
+            other => CartridgeType::Unknown(other),
+        }
+    }
+
+    /// The raw header byte this type was decoded from.
+    pub fn code(self) -> u8 {
+        match self {
+            CartridgeType::RomOnly => 0x00,
+            CartridgeType::Mbc1 => 0x01,
+            CartridgeType::Mbc1Ram => 0x02,
+            CartridgeType::Mbc1RamBattery => 0x03,
+            CartridgeType::Mbc2 => 0x05,
+            CartridgeType::Mbc2Battery => 0x06,
+            CartridgeType::RomRam => 0x08,
+            CartridgeType::RomRamBattery => 0x09,
+            CartridgeType::Mmm01 => 0x0B,
+            CartridgeType::Mmm01Ram => 0x0C,
+            CartridgeType::Mmm01RamBattery => 0x0D,
+            CartridgeType::Mbc3TimerBattery => 0x0F,
+            CartridgeType::Mbc3TimerRamBattery => 0x10,
+            CartridgeType::Mbc3 => 0x11,
+            CartridgeType::Mbc3Ram => 0x12,
+            CartridgeType::Mbc3RamBattery => 0x13,
+            CartridgeType::Mbc5 => 0x19,
+            CartridgeType::Mbc5Ram => 0x1A,
+            CartridgeType::Mbc5RamBattery => 0x1B,
+            CartridgeType::Mbc5Rumble => 0x1C,
+            CartridgeType::Mbc5RumbleRam => 0x1D,
+            CartridgeType::Mbc5RumbleRamBattery => 0x1E,
+            CartridgeType::Mbc6 => 0x20,
+            CartridgeType::Mbc7SensorRumbleRamBattery => 0x22,
+            CartridgeType::PocketCamera => 0xFC,
+            CartridgeType::BandaiTama5 => 0xFD,
+            CartridgeType::HuC3 => 0xFE,
+            CartridgeType::HuC1RamBattery => 0xFF,
+            CartridgeType::Unknown(code) => code,
+        }
+    }
+
+    /// Whether the cartridge carries external RAM. MBC2's built-in RAM counts.
+    pub fn has_ram(self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc1Ram
+                | CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::RomRam
+                | CartridgeType::RomRamBattery
+                | CartridgeType::Mmm01Ram
+                | CartridgeType::Mmm01RamBattery
+                | CartridgeType::Mbc3TimerRamBattery
+                | CartridgeType::Mbc3Ram
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc5Ram
+                | CartridgeType::Mbc5RamBattery
+                | CartridgeType::Mbc5RumbleRam
+                | CartridgeType::Mbc5RumbleRamBattery
+                | CartridgeType::Mbc6
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+                | CartridgeType::PocketCamera
+                | CartridgeType::HuC3
+                | CartridgeType::HuC1RamBattery
+        )
+    }
+
+    /// Whether external RAM (or the RTC) is kept alive by a battery.
+    pub fn has_battery(self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::RomRamBattery
+                | CartridgeType::Mmm01RamBattery
+                | CartridgeType::Mbc3TimerBattery
+                | CartridgeType::Mbc3TimerRamBattery
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc5RamBattery
+                | CartridgeType::Mbc5RumbleRamBattery
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+                | CartridgeType::HuC1RamBattery
+        )
+    }
+
+    /// Whether the cartridge has a real-time clock.
+    pub fn has_timer(self) -> bool {
+        matches!(self, CartridgeType::Mbc3TimerBattery | CartridgeType::Mbc3TimerRamBattery)
+    }
+
+    /// Whether the cartridge has a rumble motor.
+    pub fn has_rumble(self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc5Rumble
+                | CartridgeType::Mbc5RumbleRam
+                | CartridgeType::Mbc5RumbleRamBattery
+                | CartridgeType::Mbc7SensorRumbleRamBattery
+        )
+    }
+
+    /// Whether this emulator implements the cartridge's MBC.
+    pub fn is_supported(self) -> bool {
+        matches!(
+            self,
+            CartridgeType::RomOnly
+                | CartridgeType::Mbc1
+                | CartridgeType::Mbc1Ram
+                | CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::Mbc3TimerBattery
+                | CartridgeType::Mbc3TimerRamBattery
+                | CartridgeType::Mbc3
+                | CartridgeType::Mbc3Ram
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc5
+                | CartridgeType::Mbc5Ram
+                | CartridgeType::Mbc5RamBattery
+                | CartridgeType::Mbc5Rumble
+                | CartridgeType::Mbc5RumbleRam
+                | CartridgeType::Mbc5RumbleRamBattery
+        )
+    }
+}
+
+// ── CgbFlag ──────────────────────────────────────────────────────────────────
+
+/// Header byte 0x0143: Game Boy Color support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgbFlag {
+    /// Original Game Boy cartridge (byte is part of the title).
+    DmgOnly,
+    /// 0x80: uses CGB features but also runs on DMG.
+    CgbEnhanced,
+    /// 0xC0: runs only on CGB.
+    CgbOnly,
+}
+
+impl CgbFlag {
+    pub(crate) fn from_byte(byte: u8) -> Self {
+        match byte {
+            0xC0 => CgbFlag::CgbOnly,
+            b if b & 0x80 != 0 => CgbFlag::CgbEnhanced,
+            _ => CgbFlag::DmgOnly,
+        }
+    }
+}
+
+// ── RomHeader ────────────────────────────────────────────────────────────────
+
+/// The parsed cartridge header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomHeader {
+    /// Game title with padding stripped.
+    pub title: String,
+    /// 4-character manufacturer code, present on some CGB-era carts.
+    pub manufacturer_code: Option<String>,
+    pub cgb_flag: CgbFlag,
+    /// 2-character publisher code; only meaningful when `old_licensee_code` is 0x33.
+    pub new_licensee_code: Option<String>,
+    pub old_licensee_code: u8,
+    /// True when the cart enables SGB functions (0x0146 = 0x03 and old licensee 0x33).
+    pub sgb_flag: bool,
+    pub cartridge_type: CartridgeType,
+    /// ROM size in bytes as declared by the header.
+    pub rom_size: usize,
+    /// External RAM size in bytes as declared by the header. MBC2 carts
+    /// declare 0 even though they carry 512 half-bytes on-chip.
+    pub ram_size: usize,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+}
+
+impl RomHeader {
+    /// Parse and validate the header at the start of `rom`.
+    ///
+    /// Only the first 0x0150 bytes are inspected, so bank 0 alone is enough.
+    /// Fails if the data is too short, the header checksum is wrong, or the
+    /// ROM size code is unknown. Unsupported cartridge types still parse;
+    /// check [`CartridgeType::is_supported`].
+    pub fn parse(rom: &[u8]) -> Result<Self, CartridgeError> {
+        if rom.len() < HEADER_END {
+            return Err(CartridgeError::RomTooSmall {
+                expected_bytes: HEADER_END,
+                actual_bytes: rom.len(),
+            });
+        }
+        let checksum = header_checksum(rom);
+        if checksum != rom[HEADER_CHECKSUM_ADDR] {
+            return Err(CartridgeError::BadHeaderChecksum {
+                expected: checksum,
+                actual: rom[HEADER_CHECKSUM_ADDR],
+            });
+        }
+        let rom_size_code = rom[ROM_SIZE_ADDR];
+        let rom_size = rom_size_from_code(rom_size_code)
+            .ok_or(CartridgeError::UnsupportedRomSize(rom_size_code))?;
+
+        let cgb_flag = CgbFlag::from_byte(rom[CGB_FLAG_ADDR]);
+        let manufacturer = &rom[MANUFACTURER_ADDR..CGB_FLAG_ADDR];
+        let manufacturer_code = (cgb_flag != CgbFlag::DmgOnly
+            && manufacturer.iter().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()))
+        .then(|| ascii_field(manufacturer));
+        let title_end = match (cgb_flag, &manufacturer_code) {
+            (CgbFlag::DmgOnly, _) => NEW_LICENSEE_ADDR,
+            (_, Some(_)) => MANUFACTURER_ADDR,
+            (_, None) => CGB_FLAG_ADDR,
+        };
+
+        let old_licensee_code = rom[OLD_LICENSEE_ADDR];
+        let new_licensee_code = (old_licensee_code == USE_NEW_LICENSEE)
+            .then(|| ascii_field(&rom[NEW_LICENSEE_ADDR..SGB_FLAG_ADDR]));
+
+        Ok(RomHeader {
+            title: ascii_field(&rom[TITLE_ADDR..title_end]),
+            manufacturer_code,
+            cgb_flag,
+            new_licensee_code,
+            old_licensee_code,
+            sgb_flag: rom[SGB_FLAG_ADDR] == 0x03 && old_licensee_code == USE_NEW_LICENSEE,
+            cartridge_type: CartridgeType::from_code(rom[CART_TYPE_ADDR]),
+            rom_size,
+            ram_size: ram_size_from_code(rom[RAM_SIZE_ADDR]),
+            version: rom[VERSION_ADDR],
+            header_checksum: rom[HEADER_CHECKSUM_ADDR],
+            global_checksum: u16::from_be_bytes([
+                rom[GLOBAL_CHECKSUM_ADDR],
+                rom[GLOBAL_CHECKSUM_ADDR + 1],
+            ]),
+        })
+    }
+
+    /// Number of 16 KiB ROM banks declared by the header.
+    pub fn rom_bank_count(&self) -> usize {
+        self.rom_size / 0x4000
+    }
+
+    /// Whether the global checksum matches the full ROM image. Real hardware
+    /// never checks this, so a mismatch is not fatal.
+    pub fn global_checksum_matches(&self, rom: &[u8]) -> bool {
+        global_checksum(rom) == self.global_checksum
+    }
+}
+
+// ── Checksums ────────────────────────────────────────────────────────────────
+
+/// Compute the header checksum over bytes 0x0134–0x014C (title through
+/// version), as the boot ROM does.
+///
+/// `rom` must be at least 0x014D bytes long.
+pub fn header_checksum(rom: &[u8]) -> u8 {
+    rom[TITLE_ADDR..HEADER_CHECKSUM_ADDR]
+        .iter()
+        .fold(0u8, |x, &b| x.wrapping_sub(b).wrapping_sub(1))
+}
+
+/// Compute the global checksum: the 16-bit sum of every ROM byte except the
+/// two checksum bytes themselves.
+pub fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|&(i, _)| i != GLOBAL_CHECKSUM_ADDR && i != GLOBAL_CHECKSUM_ADDR + 1)
+        .fold(0u16, |sum, (_, &b)| sum.wrapping_add(b as u16))
+}
+
+// ── Field decoders ───────────────────────────────────────────────────────────
+
+/// ROM size in bytes for a header size code (32 KiB << code), or `None` for
+/// codes outside 0x00–0x08.
+fn rom_size_from_code(code: u8) -> Option<usize> {
+    (code <= 0x08).then(|| 0x8000usize << code)
+}
+
+fn ram_size_from_code(code: u8) -> usize {
+    match code {
+        0x00 => 0,
+        0x01 => 2 * 1024,        // 2 KiB (unofficial, treated as 8 KiB by some)
+        0x02 => 8 * 1024,        // 8 KiB — 1 bank
+        0x03 => 32 * 1024,       // 32 KiB — 4 banks
+        0x04 => 128 * 1024,      // 128 KiB — 16 banks
+        0x05 => 64 * 1024,       // 64 KiB — 8 banks
+        _ => 0,
+    }
+}
+
+/// Decode a fixed-width ASCII header field, stopping at the first NUL or
+/// non-printable byte and dropping trailing spaces.
+fn ascii_field(bytes: &[u8]) -> String {
+    let text: String = bytes
+        .iter()
+        .take_while(|&&b| (0x20..0x7F).contains(&b))
+        .map(|&b| b as char)
+        .collect();
+    String::from(text.trim_end())
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn make_header(title: &[u8], cart_type: u8) -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[TITLE_ADDR..TITLE_ADDR + title.len()].copy_from_slice(title);
+        rom[CART_TYPE_ADDR] = cart_type;
+        rom
+    }
+
+    fn stamp(mut rom: Vec<u8>) -> Vec<u8> {
+        rom[HEADER_CHECKSUM_ADDR] = header_checksum(&rom);
+        let global = global_checksum(&rom).to_be_bytes();
+        rom[GLOBAL_CHECKSUM_ADDR..GLOBAL_CHECKSUM_ADDR + 2].copy_from_slice(&global);
+        rom
+    }
+
+    #[test]
+    fn parse_dmg_header() {
+        let mut rom = make_header(b"TETRIS", 0x00);
+        rom[OLD_LICENSEE_ADDR] = 0x01;
+        rom[VERSION_ADDR] = 0x01;
+        let rom = stamp(rom);
+        let header = RomHeader::parse(&rom).unwrap();
+        assert_eq!(header.title, "TETRIS");
+        assert_eq!(header.manufacturer_code, None);
+        assert_eq!(header.cgb_flag, CgbFlag::DmgOnly);
+        assert_eq!(header.old_licensee_code, 0x01);
+        assert_eq!(header.new_licensee_code, None);
+        assert!(!header.sgb_flag);
+        assert_eq!(header.cartridge_type, CartridgeType::RomOnly);
+        assert_eq!(header.rom_size, 0x8000);
+        assert_eq!(header.rom_bank_count(), 2);
+        assert_eq!(header.ram_size, 0);
+        assert_eq!(header.version, 0x01);
+        assert!(header.global_checksum_matches(&rom));
+    }
+
+    #[test]
+    fn parse_dmg_title_uses_all_sixteen_bytes() {
+        let rom = stamp(make_header(b"ABCDEFGHIJKLMNOP", 0x00));
+        assert_eq!(RomHeader::parse(&rom).unwrap().title, "ABCDEFGHIJKLMNOP");
+    }
+
+    #[test]
+    fn parse_cgb_header_with_manufacturer_code() {
+        let mut rom = make_header(b"PM_CRYSTAL\0BYTE", 0x10);
+        rom[CGB_FLAG_ADDR] = 0xC0;
+        rom[OLD_LICENSEE_ADDR] = 0x33;
+        rom[NEW_LICENSEE_ADDR..NEW_LICENSEE_ADDR + 2].copy_from_slice(b"01");
+        rom[SGB_FLAG_ADDR] = 0x03;
+        rom[ROM_SIZE_ADDR] = 0x06;
+        rom[RAM_SIZE_ADDR] = 0x03;
+        rom.resize(0x8000 << 6, 0);
+        let header = RomHeader::parse(&stamp(rom)).unwrap();
+        assert_eq!(header.title, "PM_CRYSTAL");
+        assert_eq!(header.manufacturer_code.as_deref(), Some("BYTE"));
+        assert_eq!(header.cgb_flag, CgbFlag::CgbOnly);
+        assert_eq!(header.new_licensee_code.as_deref(), Some("01"));
+        assert!(header.sgb_flag);
+        assert_eq!(header.cartridge_type, CartridgeType::Mbc3TimerRamBattery);
+        assert_eq!(header.rom_size, 2 * 1024 * 1024);
+        assert_eq!(header.ram_size, 32 * 1024);
+    }
+
+    #[test]
+    fn parse_cgb_enhanced_header_without_manufacturer_code() {
+        let mut rom = make_header(b"LONG CGB TITLE", 0x1B);
+        rom[CGB_FLAG_ADDR] = 0x80;
+        let header = RomHeader::parse(&stamp(rom)).unwrap();
+        assert_eq!(header.title, "LONG CGB TITLE");
+        assert_eq!(header.manufacturer_code, None);
+        assert_eq!(header.cgb_flag, CgbFlag::CgbEnhanced);
+    }
+
+    #[test]
+    fn sgb_flag_requires_new_licensee_marker() {
+        let mut rom = make_header(b"SGB", 0x00);
+        rom[SGB_FLAG_ADDR] = 0x03;
+        rom[OLD_LICENSEE_ADDR] = 0x01;
+        assert!(!RomHeader::parse(&stamp(rom)).unwrap().sgb_flag);
+    }
+
+    #[test]
+    fn header_checksum_matches_boot_rom_algorithm() {
+        let mut data = vec![0u8; HEADER_END];
+        // 25 zero bytes: x = x - 0 - 1 each step → -25
+        assert_eq!(header_checksum(&data), 0u8.wrapping_sub(25));
+        data[CART_TYPE_ADDR] = 0x01;
+        assert_eq!(header_checksum(&data), 0u8.wrapping_sub(26));
+    }
+
+    #[test]
+    fn parse_rejects_short_data() {
+        assert_eq!(
+            RomHeader::parse(&[0u8; 0x100]),
+            Err(CartridgeError::RomTooSmall { expected_bytes: 0x150, actual_bytes: 0x100 })
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_header_checksum() {
+        let mut rom = stamp(make_header(b"BAD", 0x00));
+        rom[HEADER_CHECKSUM_ADDR] ^= 0xFF;
+        assert!(matches!(
+            RomHeader::parse(&rom),
+            Err(CartridgeError::BadHeaderChecksum { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_accepts_unsupported_cart_type() {
+        let rom = stamp(make_header(b"CAMERA", 0xFC));
+        let header = RomHeader::parse(&rom).unwrap();
+        assert_eq!(header.cartridge_type, CartridgeType::PocketCamera);
+        assert!(!header.cartridge_type.is_supported());
+    }
+
+    #[test]
+    fn global_checksum_mismatch_is_detected_but_not_fatal() {
+        let mut rom = stamp(make_header(b"GLOBAL", 0x00));
+        rom[0x4000] = 0x55;
+        let header = RomHeader::parse(&rom).unwrap();
+        assert!(!header.global_checksum_matches(&rom));
+    }
+
+    #[test]
+    fn cartridge_type_codes_round_trip() {
+        for code in 0..=0xFFu8 {
+            assert_eq!(CartridgeType::from_code(code).code(), code);
+        }
+    }
+
+    #[test]
+    fn cartridge_type_peripherals() {
+        let t = CartridgeType::Mbc3TimerRamBattery;
+        assert!(t.has_ram() && t.has_battery() && t.has_timer() && !t.has_rumble());
+        let t = CartridgeType::Mbc5RumbleRam;
+        assert!(t.has_ram() && !t.has_battery() && !t.has_timer() && t.has_rumble());
+        assert!(CartridgeType::Mbc2.has_ram());
+        assert!(!CartridgeType::RomOnly.has_ram());
+    }
+}
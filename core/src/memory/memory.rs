@@ -3,7 +3,7 @@ use alloc::collections::VecDeque;
 use alloc::{vec, vec::Vec};
 use core::fmt;
 
-use super::cartridge::{self, Cartridge, CartridgeRomWindows, NoMbc};
+use super::cartridge::{self, Cartridge, CartridgeError, CartridgeRomWindows, NoMbc};
 use crate::cpu::save_state::SaveState;
 
 /// An event produced when a write occurs to an I/O or IE register address.
@@ -38,13 +38,57 @@ pub trait Memory {
     }
 }
 
+/// Size of the DMG boot ROM image overlaid at 0x0000–0x00FF.
+pub const BOOT_ROM_SIZE: usize = 0x100;
+
+/// Boot ROM disable register. Writing a value with bit 0 set unmaps the boot ROM.
+pub(crate) const BOOT_ROM_DISABLE_ADDR: u16 = 0xFF50;
+
+/// CGB VRAM bank select register.
+pub(crate) const VBK_ADDR: u16 = 0xFF4F;
+/// CGB WRAM bank select register.
+pub(crate) const SVBK_ADDR: u16 = 0xFF70;
+
+const VRAM_BANK_SIZE: usize = 0x2000;
+const WRAM_BANK_SIZE: usize = 0x1000;
+/// Switchable WRAM banks 1–7 at 0xD000–0xDFFF in CGB mode.
+const WRAM_SWITCHABLE_BANKS: usize = 7;
+/// Bytes written by [`GameBoyMemory::save_cgb_state`].
+pub(crate) const CGB_BANKS_STATE_SIZE: usize =
+    2 + VRAM_BANK_SIZE + WRAM_SWITCHABLE_BANKS * WRAM_BANK_SIZE;
+
+/// A Game Genie patch: reads of cartridge ROM at `address` return `value`
+/// instead, provided the ROM byte there equals `compare` (if any). The
+/// compare value is what lets one code target a single ROM bank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RomPatch {
+    pub address: u16,
+    pub value: u8,
+    pub compare: Option<u8>,
+}
+
+/// VRAM bank 1 and WRAM banks 2–7, present only in CGB mode.
+///
+/// `GameBoyMemory::vram` and the upper half of `wram` always hold the
+/// *mapped* banks so the hot paths keep indexing flat arrays; switching banks
+/// swaps the mapped window with its stored copy here.
+struct CgbBanks {
+    vram_bank: u8,
+    /// Whichever VRAM bank is not currently mapped.
+    vram_unmapped: [u8; VRAM_BANK_SIZE],
+    wram_bank: u8,
+    /// WRAM banks 1–7, indexed by `bank - 1`. The slot for the mapped bank is
+    /// stale until the bank is switched out again.
+    wram_banks: [[u8; WRAM_BANK_SIZE]; WRAM_SWITCHABLE_BANKS],
+}
+
 /// Resolved mapping for a given address: which region and the offset within it.
 enum RegionMapping {
     Rom,
     Vram(u16),
     ExternalRam(u16),
     Wram(u16),
-    /// Echo RAM: mirrors WRAM on reads, but is not writable.
+    /// Echo RAM: mirrors WRAM for both reads and writes.
     EchoRam(u16),
     Oam(u16),
     /// I/O registers: 0xFF00–0xFF7F
@@ -75,11 +119,12 @@ impl RegionMapping {
 /// Game Boy memory map dispatching reads/writes to the appropriate region.
 ///
 /// Address map:
-///   0x0000–0x7FFF  ROM (cartridge, may be bank-switched by MBC)
-///   0x8000–0x9FFF  VRAM
+///   0x0000–0x7FFF  ROM (cartridge, may be bank-switched by MBC; 0x0000–0x00FF
+///                  shows the boot ROM while one is mapped)
+///   0x8000–0x9FFF  VRAM (bank selected by VBK in CGB mode)
 ///   0xA000–0xBFFF  External RAM (cartridge, may be bank-switched by MBC)
-///   0xC000–0xDFFF  Work RAM (WRAM)
-///   0xE000–0xFDFF  Echo RAM (mirrors WRAM reads, writes are read-only)
+///   0xC000–0xDFFF  Work RAM (WRAM; 0xD000–0xDFFF selected by SVBK in CGB mode)
+///   0xE000–0xFDFF  Echo RAM (mirrors WRAM reads and writes)
 ///   0xFE00–0xFE9F  OAM
 ///   0xFF00–0xFF7F  I/O registers
 ///   0xFF80–0xFFFE  High RAM (HRAM)
@@ -99,6 +144,12 @@ pub struct GameBoyMemory {
     hram: [u8; 0x7F],
     ie: u8,
     events: VecDeque<BusEvent>,
+    /// Boot ROM overlay; `None` once unmapped via 0xFF50 or if never supplied.
+    boot_rom: Option<Box<[u8; BOOT_ROM_SIZE]>>,
+    /// CGB VRAM/WRAM banks; `None` on DMG and in DMG-compatibility mode.
+    cgb: Option<Box<CgbBanks>>,
+    /// Active Game Genie patches, checked on every cartridge ROM read.
+    rom_patches: Vec<RomPatch>,
 }
 
 impl GameBoyMemory {
@@ -119,6 +170,9 @@ impl GameBoyMemory {
             hram: [0; 0x7F],
             ie: 0,
             events: VecDeque::with_capacity(8),
+            boot_rom: None,
+            cgb: None,
+            rom_patches: Vec::new(),
         }
     }
 
@@ -140,29 +194,18 @@ impl GameBoyMemory {
             hram: [0; 0x7F],
             ie: 0,
             events: VecDeque::with_capacity(8),
+            boot_rom: None,
+            cgb: None,
+            rom_patches: Vec::new(),
         }
     }
 
     /// Construct memory with a cartridge ROM. The cartridge type is auto-detected
     /// from the ROM header (byte 0x0147) to select the correct MBC.
-    pub fn with_rom(data: Vec<u8>) -> Self {
-        let cartridge = cartridge::from_rom(data);
-        let rom_windows = cartridge.rom_windows().unwrap_or(CartridgeRomWindows::EMPTY);
-        Self {
-            cartridge_has_rtc: cartridge.has_rtc(),
-            rom_fixed_ptr: rom_windows.fixed_ptr,
-            rom_fixed_len: rom_windows.fixed_len,
-            rom_banked_ptr: rom_windows.banked_ptr,
-            rom_banked_len: rom_windows.banked_len,
-            cartridge,
-            vram: [0; 0x2000],
-            wram: [0; 0x2000],
-            oam: [0; 0xA0],
-            io: [0; 0x80],
-            hram: [0; 0x7F],
-            ie: 0,
-            events: VecDeque::with_capacity(8),
-        }
+    ///
+    /// Returns an error if the header is invalid or names an unsupported MBC.
+    pub fn with_rom(data: Vec<u8>) -> Result<Self, CartridgeError> {
+        Ok(Self::with_cartridge(cartridge::from_rom(data)?))
     }
 
     #[inline(always)]
@@ -198,11 +241,93 @@ impl GameBoyMemory {
         unsafe { *ptr.add(offset) }
     }
 
+    /// Overlay a boot ROM image at 0x0000–0x00FF until it is unmapped.
+    pub fn map_boot_rom(&mut self, image: [u8; BOOT_ROM_SIZE]) {
+        self.boot_rom = Some(Box::new(image));
+    }
+
+    /// Remove the boot ROM overlay, exposing the cartridge at 0x0000–0x00FF.
+    pub fn unmap_boot_rom(&mut self) {
+        self.boot_rom = None;
+    }
+
+    /// Whether the boot ROM is currently overlaid on the cartridge.
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some()
+    }
+
+    /// Switch on CGB mode: VRAM bank 1 behind VBK (0xFF4F) and WRAM banks
+    /// 1–7 at 0xD000–0xDFFF behind SVBK (0xFF70). Starts with VRAM bank 0
+    /// and WRAM bank 1 mapped.
+    pub fn enable_cgb_mode(&mut self) {
+        if self.cgb.is_none() {
+            self.cgb = Some(Box::new(CgbBanks {
+                vram_bank: 0,
+                vram_unmapped: [0; VRAM_BANK_SIZE],
+                wram_bank: 1,
+                wram_banks: [[0; WRAM_BANK_SIZE]; WRAM_SWITCHABLE_BANKS],
+            }));
+        }
+    }
+
+    /// Whether CGB banking is enabled.
+    pub fn cgb_mode(&self) -> bool {
+        self.cgb.is_some()
+    }
+
+    /// The VRAM bank mapped at 0x8000–0x9FFF (always 0 outside CGB mode).
+    pub fn vram_bank(&self) -> u8 {
+        self.cgb.as_ref().map_or(0, |cgb| cgb.vram_bank)
+    }
+
+    /// The WRAM bank mapped at 0xD000–0xDFFF (always 1 outside CGB mode).
+    pub fn wram_bank(&self) -> u8 {
+        self.cgb.as_ref().map_or(1, |cgb| cgb.wram_bank)
+    }
+
+    /// Map VRAM bank `bank & 1`. No-op outside CGB mode.
+    pub fn set_vram_bank(&mut self, bank: u8) {
+        let Some(cgb) = self.cgb.as_deref_mut() else { return };
+        let bank = bank & 0x01;
+        if bank != cgb.vram_bank {
+            core::mem::swap(&mut self.vram, &mut cgb.vram_unmapped);
+            cgb.vram_bank = bank;
+        }
+    }
+
+    /// Map WRAM bank `bank & 7` at 0xD000, where bank 0 selects bank 1.
+    /// No-op outside CGB mode.
+    pub fn set_wram_bank(&mut self, bank: u8) {
+        let Some(cgb) = self.cgb.as_deref_mut() else { return };
+        let bank = (bank & 0x07).max(1);
+        if bank != cgb.wram_bank {
+            let window = &mut self.wram[WRAM_BANK_SIZE..];
+            cgb.wram_banks[cgb.wram_bank as usize - 1].copy_from_slice(window);
+            window.copy_from_slice(&cgb.wram_banks[bank as usize - 1]);
+            cgb.wram_bank = bank;
+        }
+    }
+
+    /// VRAM banks 0 and 1 as the PPU sees them, regardless of which one the
+    /// CPU has mapped. Bank 1 is empty outside CGB mode.
+    pub fn vram_banks(&self) -> (&[u8], &[u8]) {
+        match self.cgb.as_deref() {
+            None => (&self.vram, &[]),
+            Some(cgb) if cgb.vram_bank == 0 => (&self.vram, &cgb.vram_unmapped),
+            Some(cgb) => (&cgb.vram_unmapped, &self.vram),
+        }
+    }
+
     /// Returns the currently mapped ROM bank number for the switchable window.
     pub fn current_rom_bank(&self) -> usize {
         self.cartridge.current_rom_bank()
     }
 
+    /// Returns whether the cartridge rumble motor is currently driven.
+    pub fn rumble_active(&self) -> bool {
+        self.cartridge.rumble_active()
+    }
+
     #[inline(always)]
     pub fn has_rtc(&self) -> bool {
         self.cartridge_has_rtc
@@ -240,8 +365,13 @@ impl GameBoyMemory {
     /// address is in `0x0000..=0x7FFF`.
     #[inline(always)]
     pub fn read_rom_fast(&self, address: u16) -> u8 {
-        match address {
+        let value = match address {
             0x0000..=0x3FFF => {
+                if let Some(boot) = self.boot_rom.as_deref() {
+                    if (address as usize) < BOOT_ROM_SIZE {
+                        return boot[address as usize];
+                    }
+                }
                 if self.rom_fixed_len != 0 {
                     Self::read_cached_rom(self.rom_fixed_ptr, self.rom_fixed_len, address as usize)
                 } else {
@@ -260,9 +390,35 @@ impl GameBoyMemory {
                 }
             }
             _ => 0xFF,
+        };
+        if self.rom_patches.is_empty() {
+            value
+        } else {
+            self.patch_rom_read(address, value)
         }
     }
 
+    #[cold]
+    fn patch_rom_read(&self, address: u16, value: u8) -> u8 {
+        self.rom_patches
+            .iter()
+            .find(|p| p.address == address && p.compare.is_none_or(|c| c == value))
+            .map_or(value, |p| p.value)
+    }
+
+    /// Replace the Game Genie patches applied to cartridge ROM reads. The
+    /// boot ROM overlay is never patched.
+    pub fn set_rom_patches(&mut self, patches: Vec<RomPatch>) {
+        self.rom_patches = patches;
+    }
+
+    /// Write `value` to external RAM `bank` at `offset` (0x0000–0x1FFF)
+    /// whatever bank is mapped and whether RAM is enabled. Out-of-range
+    /// writes are dropped.
+    pub fn poke_external_ram(&mut self, bank: usize, offset: u16, value: u8) {
+        self.cartridge.poke_external_ram(bank, offset, value);
+    }
+
     /// Fast infallible memory write used by hot non-IO paths.
     #[inline(always)]
     pub fn write_fast(&mut self, address: u16, value: u8) {
@@ -274,7 +430,7 @@ impl GameBoyMemory {
             0xA000..=0xBFFF => self.cartridge.write(address, value),
             0x8000..=0x9FFF => Self::write_region_fast(&mut self.vram, address - 0x8000, value),
             0xC000..=0xDFFF => Self::write_region_fast(&mut self.wram, address - 0xC000, value),
-            0xE000..=0xFDFF => {}
+            0xE000..=0xFDFF => Self::write_region_fast(&mut self.wram, address - 0xE000, value),
             0xFE00..=0xFE9F => Self::write_region_fast(&mut self.oam, address - 0xFE00, value),
             0xFF00..=0xFF7F => Self::write_region_fast(&mut self.io, address - 0xFF00, value),
             0xFF80..=0xFFFE => Self::write_region_fast(&mut self.hram, address - 0xFF80, value),
@@ -301,6 +457,10 @@ impl GameBoyMemory {
         &self.oam
     }
 
+    pub fn oam_mut(&mut self) -> &mut [u8] {
+        &mut self.oam
+    }
+
     pub fn wram(&self) -> &[u8] {
         &self.wram
     }
@@ -348,20 +508,51 @@ impl GameBoyMemory {
         out.extend_from_slice(self.hram());
         out.extend_from_slice(self.vram());
         out.extend_from_slice(self.oam());
-        self.cartridge.save_mbc_state(out);
-        // External RAM (cart SRAM): prefix with u16 LE length so load_state
-        // can handle carts with no RAM (len=0) and varying RAM sizes.
-        match self.cartridge.external_ram() {
-            Some(ram) => {
-                out.extend_from_slice(&(ram.len() as u16).to_le_bytes());
-                out.extend_from_slice(ram);
-            }
-            None => {
-                out.extend_from_slice(&0u16.to_le_bytes());
+    }
+
+    /// Serialize the CGB banks into `out`: VBK, SVBK, the unmapped VRAM bank,
+    /// then WRAM banks 1–7. Writes nothing outside CGB mode.
+    ///
+    /// The mapped VRAM bank and WRAM window are already covered by
+    /// [`Self::save_state`].
+    pub fn save_cgb_state(&self, out: &mut alloc::vec::Vec<u8>) {
+        let Some(cgb) = self.cgb.as_deref() else { return };
+        out.push(cgb.vram_bank);
+        out.push(cgb.wram_bank);
+        out.extend_from_slice(&cgb.vram_unmapped);
+        for bank in 1..=WRAM_SWITCHABLE_BANKS {
+            if bank == cgb.wram_bank as usize {
+                out.extend_from_slice(&self.wram[WRAM_BANK_SIZE..]);
+            } else {
+                out.extend_from_slice(&cgb.wram_banks[bank - 1]);
             }
         }
     }
 
+    /// Restore the CGB banks written by [`Self::save_cgb_state`]. Must run
+    /// after [`Self::load_state`] has restored the mapped windows.
+    pub fn load_cgb_state(&mut self, data: &[u8]) {
+        self.enable_cgb_mode();
+        let Some(cgb) = self.cgb.as_deref_mut() else { return };
+        cgb.vram_bank = data[0] & 0x01;
+        cgb.wram_bank = (data[1] & 0x07).max(1);
+        cgb.vram_unmapped.copy_from_slice(&data[2..2 + VRAM_BANK_SIZE]);
+        let banks = &data[2 + VRAM_BANK_SIZE..];
+        for (slot, bank) in cgb.wram_banks.iter_mut().zip(banks.chunks_exact(WRAM_BANK_SIZE)) {
+            slot.copy_from_slice(bank);
+        }
+    }
+
+    /// Serialize the cartridge section into `out`: MBC registers, then cart RAM.
+    pub fn save_cartridge_state(&self, out: &mut alloc::vec::Vec<u8>) {
+        self.cartridge.save_mbc_state(out);
+        // External RAM (cart SRAM): prefix with u32 LE length so load_state
+        // can handle carts with no RAM (len=0) and varying RAM sizes.
+        let ram = self.cartridge.external_ram().unwrap_or(&[]);
+        out.extend_from_slice(&(ram.len() as u32).to_le_bytes());
+        out.extend_from_slice(ram);
+    }
+
     /// Apply memory state from a parsed [`SaveState`]. Zero-copy for large regions.
     pub fn load_state(&mut self, state: &SaveState) {
         let io = state.io_registers();
@@ -373,15 +564,24 @@ impl GameBoyMemory {
         self.set_hram(state.hram());
         self.set_vram(state.vram());
         self.set_oam(state.oam());
-        if let Some(mbc) = state.mbc() {
-            // Reconstruct MBC register state via the existing load path.
-            // We build a minimal 4-byte buffer and reuse load_mbc_state.
-            let buf = [mbc.rom_bank_lo, mbc.upper_bits, mbc.ram_mode as u8, mbc.ram_enabled as u8];
-            self.cartridge.load_mbc_state(&buf, 0);
-            self.refresh_rom_windows();
+        // v1 predates boot ROM support, so its states were always taken post-boot.
+        if state.version < 2 || io[(BOOT_ROM_DISABLE_ADDR - 0xFF00) as usize] & 0x01 != 0 {
+            self.unmap_boot_rom();
         }
-        if let Some(ram) = state.cart_ram() {
-            self.cartridge.set_external_ram(ram);
+        // The MBC register layout is cartridge-specific, so let the cartridge
+        // tell us where its registers end and the cart RAM section begins.
+        let cart = state.cartridge();
+        let mbc_len = self.cartridge.load_mbc_state(cart, 0);
+        self.refresh_rom_windows();
+        let len_size = state.cart_ram_len_size();
+        if let Some(len) = cart.get(mbc_len..mbc_len + len_size) {
+            let mut len_bytes = [0u8; 4];
+            len_bytes[..len_size].copy_from_slice(len);
+            let ram_len = u32::from_le_bytes(len_bytes) as usize;
+            let ram_start = mbc_len + len_size;
+            if let Some(ram) = cart.get(ram_start..ram_start + ram_len).filter(|r| !r.is_empty()) {
+                self.cartridge.set_external_ram(ram);
+            }
         }
     }
 
@@ -461,7 +661,10 @@ impl Memory for GameBoyMemory {
                 self.wram[offset as usize] = value;
                 Ok(())
             }
-            RegionMapping::EchoRam(_) => Err(Error::ReadOnly(address)),
+            RegionMapping::EchoRam(offset) => {
+                self.wram[offset as usize] = value;
+                Ok(())
+            }
             RegionMapping::Oam(offset) => {
                 self.oam[offset as usize] = value;
                 Ok(())
@@ -492,6 +695,7 @@ impl Memory for GameBoyMemory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::header::header_checksum;
     use alloc::format;
 
     fn make_mbc1_rom(size_kb: usize) -> Vec<u8> {
@@ -504,14 +708,19 @@ mod tests {
         }
         data[0x0147] = 0x01;
         data[0x0148] = (size / (32 * 1024)).trailing_zeros() as u8;
+        data[0x014D] = header_checksum(&data);
         data
     }
 
+    fn with_flat_rom(data: Vec<u8>) -> GameBoyMemory {
+        GameBoyMemory::with_cartridge(Box::new(NoMbc::new(data)))
+    }
+
     // --- ROM region (read-only) ---
 
     #[test]
     fn test_rom_region_reads_loaded_data() {
-        let mem = GameBoyMemory::with_rom(vec![0x11, 0x22, 0x33]);
+        let mem = with_flat_rom(vec![0x11, 0x22, 0x33]);
         assert_eq!(mem.read(0x0000).unwrap(), 0x11);
         assert_eq!(mem.read(0x0001).unwrap(), 0x22);
         assert_eq!(mem.read(0x0002).unwrap(), 0x33);
@@ -521,16 +730,45 @@ mod tests {
 
     #[test]
     fn test_rom_region_write_is_silently_ignored() {
-        let mem_with_rom = GameBoyMemory::with_rom(vec![0x11, 0x22]);
+        let mem_with_rom = with_flat_rom(vec![0x11, 0x22]);
         let mut mem = mem_with_rom;
         assert!(mem.write(0x0000, 0xFF).is_ok());
         // ROM data should be unchanged
         assert_eq!(mem.read(0x0000).unwrap(), 0x11);
     }
 
+    #[test]
+    fn test_with_rom_rejects_unsupported_cartridge() {
+        let mut rom = make_mbc1_rom(64);
+        rom[0x0147] = 0xFC; // POCKET CAMERA
+        rom[0x014D] = header_checksum(&rom);
+        assert!(matches!(
+            GameBoyMemory::with_rom(rom),
+            Err(CartridgeError::UnsupportedCartType(0xFC))
+        ));
+    }
+
+    #[test]
+    fn test_boot_rom_overlays_first_256_bytes_until_unmapped() {
+        let mut rom = vec![0x11u8; 0x8000];
+        rom[0x0100] = 0x22;
+        let mut mem = with_flat_rom(rom);
+        mem.map_boot_rom([0xBB; BOOT_ROM_SIZE]);
+
+        assert!(mem.boot_rom_mapped());
+        assert_eq!(mem.read(0x0000).unwrap(), 0xBB);
+        assert_eq!(mem.read_fast(0x00FF), 0xBB);
+        assert_eq!(mem.read_rom_fast(0x0100), 0x22, "cartridge visible past the boot ROM");
+
+        mem.unmap_boot_rom();
+        assert!(!mem.boot_rom_mapped());
+        assert_eq!(mem.read(0x0000).unwrap(), 0x11);
+        assert_eq!(mem.read_rom_fast(0x00FF), 0x11);
+    }
+
     #[test]
     fn test_read_fast_rom_cache_tracks_bank_switches() {
-        let mut mem = GameBoyMemory::with_rom(make_mbc1_rom(128));
+        let mut mem = GameBoyMemory::with_rom(make_mbc1_rom(128)).unwrap();
 
         assert_eq!(mem.read_fast(0x0000), 0x00);
         assert_eq!(mem.read_fast(0x4000), 0x01);
@@ -594,9 +832,20 @@ mod tests {
     }
 
     #[test]
-    fn test_echo_ram_write_returns_readonly_error() {
+    fn test_echo_ram_write_mirrors_into_wram() {
         let mut mem = GameBoyMemory::new();
-        assert!(matches!(mem.write(0xE000, 0xFF), Err(Error::ReadOnly(_))));
+        mem.write(0xE000, 0xFF).unwrap();
+        mem.write(0xFDFF, 0x42).unwrap();
+        assert_eq!(mem.read(0xC000).unwrap(), 0xFF);
+        assert_eq!(mem.read(0xDDFF).unwrap(), 0x42);
+    }
+
+    #[test]
+    fn test_echo_ram_write_fast_mirrors_into_wram() {
+        let mut mem = GameBoyMemory::new();
+        mem.write_fast(0xE123, 0x5A);
+        assert_eq!(mem.read(0xC123).unwrap(), 0x5A);
+        assert_eq!(mem.read_fast(0xE123), 0x5A);
     }
 
     // --- OAM (0xFE00–0xFE9F) ---
@@ -760,4 +1009,44 @@ mod tests {
         mem.write_io(0xFFFF, 0x1F);
         assert_eq!(mem.read(0xFFFF).unwrap(), 0x1F);
     }
+
+    #[test]
+    fn test_wram_banks_keep_their_contents() {
+        let mut mem = GameBoyMemory::new();
+        mem.enable_cgb_mode();
+        mem.write(0xD000, 0x11).unwrap();
+        mem.set_wram_bank(5);
+        assert_eq!(mem.read(0xD000).unwrap(), 0x00);
+        mem.write(0xD000, 0x55).unwrap();
+
+        mem.set_wram_bank(0); // selects bank 1
+        assert_eq!(mem.wram_bank(), 1);
+        assert_eq!(mem.read(0xD000).unwrap(), 0x11);
+        mem.set_wram_bank(5);
+        assert_eq!(mem.read(0xD000).unwrap(), 0x55);
+    }
+
+    #[test]
+    fn test_vram_banks_report_both_banks_regardless_of_mapping() {
+        let mut mem = GameBoyMemory::new();
+        mem.enable_cgb_mode();
+        mem.write(0x8000, 0xB0).unwrap();
+        mem.set_vram_bank(1);
+        mem.write(0x8000, 0xB1).unwrap();
+
+        let (bank0, bank1) = mem.vram_banks();
+        assert_eq!((bank0[0], bank1[0]), (0xB0, 0xB1));
+    }
+
+    #[test]
+    fn test_bank_switching_is_ignored_outside_cgb_mode() {
+        let mut mem = GameBoyMemory::new();
+        mem.write(0x8000, 0xB0).unwrap();
+        mem.set_vram_bank(1);
+        mem.set_wram_bank(3);
+
+        assert_eq!(mem.read(0x8000).unwrap(), 0xB0);
+        assert_eq!((mem.vram_bank(), mem.wram_bank()), (0, 1));
+        assert!(mem.vram_banks().1.is_empty());
+    }
 }
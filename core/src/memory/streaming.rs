@@ -1,7 +1,8 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use super::cartridge::Cartridge;
+use super::cartridge::{Cartridge, CartridgeError};
+use super::header::{CartridgeType, RomHeader};
 #[cfg(feature = "perf")]
 use super::cartridge::CartridgePerfProfile;
 
@@ -12,11 +13,8 @@ pub trait RomReader {
     fn read_bank(&mut self, bank: usize, buf: &mut [u8; 0x4000]) -> Result<(), Self::Error>;
 }
 
-// ── Header offsets ───────────────────────────────────────────────────────────
-
-const CART_TYPE: usize = 0x0147;
-const ROM_SIZE:  usize = 0x0148;
-const RAM_SIZE:  usize = 0x0149;
+/// MBC2 carts report no RAM in the header but carry 512 half-bytes on-chip.
+const MBC2_RAM_BYTES: usize = 512;
 
 // ── MBC state ────────────────────────────────────────────────────────────────
 
@@ -29,11 +27,22 @@ enum MbcState {
         ram_enabled:    bool,
         ram_bank_count: usize,
     },
+    Mbc2 {
+        rom_bank:    u8,
+        ram_enabled: bool,
+    },
     Mbc3 {
         rom_bank:        u8,
         bank_or_rtc:     u8,
         ram_rtc_enabled: bool,
     },
+    Mbc5 {
+        rom_bank:       u16,
+        ram_bank:       u8,
+        ram_enabled:    bool,
+        has_rumble:     bool,
+        ram_bank_count: usize,
+    },
 }
 
 // ── StreamingCartridge ───────────────────────────────────────────────────────
@@ -41,6 +50,7 @@ enum MbcState {
 #[derive(Debug)]
 pub enum StreamingError<E> {
     Reader(E),
+    InvalidHeader(CartridgeError),
     UnsupportedCartType(u8),
 }
 
@@ -62,11 +72,16 @@ impl<R: RomReader> StreamingCartridge<R> {
         let mut bank0_cache = [0u8; 0x4000];
         reader.read_bank(0, &mut bank0_cache).map_err(StreamingError::Reader)?;
 
-        let cart_type      = bank0_cache[CART_TYPE];
-        let rom_bank_count = rom_bank_count_from_code(bank0_cache[ROM_SIZE]);
-        let ram_bytes      = ram_bytes_from_code(bank0_cache[RAM_SIZE]);
+        let header = RomHeader::parse(&bank0_cache).map_err(StreamingError::InvalidHeader)?;
+        let cart_type      = header.cartridge_type;
+        let rom_bank_count = header.rom_bank_count();
+        let ram_bytes      = if matches!(cart_type, CartridgeType::Mbc2 | CartridgeType::Mbc2Battery) {
+            MBC2_RAM_BYTES
+        } else {
+            header.ram_size
+        };
         let mbc = mbc_state_from_header(cart_type, ram_bytes)
-            .ok_or(StreamingError::UnsupportedCartType(cart_type))?;
+            .ok_or(StreamingError::UnsupportedCartType(cart_type.code()))?;
 
         let mut banked_cache = [0u8; 0x4000];
         reader.read_bank(1, &mut banked_cache).map_err(StreamingError::Reader)?;
@@ -95,7 +110,9 @@ impl<R: RomReader> StreamingCartridge<R> {
                     0
                 }
             }
+            MbcState::Mbc2 { .. } => 0,
             MbcState::Mbc3 { .. } => 0,
+            MbcState::Mbc5 { .. } => 0,
         }
     }
 
@@ -107,7 +124,9 @@ impl<R: RomReader> StreamingCartridge<R> {
                 let bank = if bank == 0 { 1 } else { bank };
                 bank % self.rom_bank_count
             }
+            MbcState::Mbc2 { rom_bank, .. } => *rom_bank as usize % self.rom_bank_count,
             MbcState::Mbc3 { rom_bank, .. } => *rom_bank as usize,
+            MbcState::Mbc5 { rom_bank, .. } => *rom_bank as usize % self.rom_bank_count,
         }
     }
 
@@ -214,6 +233,24 @@ impl<R: RomReader> StreamingCartridge<R> {
                     _ => false,
                 }
             }
+            MbcState::Mbc2 { rom_bank, ram_enabled } => {
+                match addr {
+                    0x0000..=0x3FFF if addr & 0x0100 == 0 => {
+                        *ram_enabled = value & 0x0F == 0x0A;
+                        false
+                    }
+                    0x0000..=0x3FFF => {
+                        let bank = if value & 0x0F == 0 { 1 } else { value & 0x0F };
+                        if *rom_bank == bank {
+                            false
+                        } else {
+                            *rom_bank = bank;
+                            true
+                        }
+                    }
+                    _ => false,
+                }
+            }
             MbcState::Mbc3 { rom_bank, bank_or_rtc, ram_rtc_enabled } => {
                 match addr {
                     0x0000..=0x1FFF => {
@@ -236,14 +273,39 @@ impl<R: RomReader> StreamingCartridge<R> {
                     _ => false,
                 }
             }
+            MbcState::Mbc5 { rom_bank, ram_bank, ram_enabled, .. } => {
+                let new_bank = match addr {
+                    0x0000..=0x1FFF => {
+                        *ram_enabled = value == 0x0A;
+                        return false;
+                    }
+                    0x2000..=0x2FFF => (*rom_bank & 0x100) | value as u16,
+                    0x3000..=0x3FFF => (*rom_bank & 0xFF) | (((value & 0x01) as u16) << 8),
+                    0x4000..=0x5FFF => {
+                        *ram_bank = value & 0x0F;
+                        return false;
+                    }
+                    _ => return false,
+                };
+                if *rom_bank == new_bank {
+                    false
+                } else {
+                    *rom_bank = new_bank;
+                    true
+                }
+            }
         }
     }
 
-    fn mbc1_ram_bank(&self) -> usize {
+    fn ram_bank(&self) -> usize {
         match &self.mbc {
             MbcState::Mbc1 { upper_bits, ram_mode: true, ram_bank_count, .. } => {
                 (*upper_bits as usize) % (*ram_bank_count).max(1)
             }
+            MbcState::Mbc5 { ram_bank, has_rumble, ram_bank_count, .. } => {
+                let mask = if *has_rumble { 0x07 } else { 0x0F };
+                (*ram_bank & mask) as usize % (*ram_bank_count).max(1)
+            }
             _ => 0,
         }
     }
@@ -252,9 +314,11 @@ impl<R: RomReader> StreamingCartridge<R> {
         match &self.mbc {
             MbcState::NoMbc => false,
             MbcState::Mbc1 { ram_enabled, .. } => *ram_enabled,
+            MbcState::Mbc2 { ram_enabled, .. } => *ram_enabled,
             MbcState::Mbc3 { ram_rtc_enabled, bank_or_rtc, .. } => {
                 *ram_rtc_enabled && !matches!(bank_or_rtc, 0x08..=0x0C)
             }
+            MbcState::Mbc5 { ram_enabled, .. } => *ram_enabled,
         }
     }
 }
@@ -270,7 +334,10 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
 
     fn read_ram(&self, addr: u16) -> u8 {
         if !self.is_ram_enabled() || self.ram.is_empty() { return 0xFF; }
-        let offset = self.mbc1_ram_bank() * 0x2000 + addr as usize;
+        if let MbcState::Mbc2 { .. } = self.mbc {
+            return self.ram[addr as usize % MBC2_RAM_BYTES] | 0xF0;
+        }
+        let offset = self.ram_bank() * 0x2000 + addr as usize;
         self.ram.get(offset).copied().unwrap_or(0xFF)
     }
 
@@ -279,8 +346,12 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
             #[cfg(feature = "perf")]
             let t_ram = crate::cpu::perf::cyccnt();
             if self.is_ram_enabled() && !self.ram.is_empty() {
-                let offset = self.mbc1_ram_bank() * 0x2000 + (addr - 0xA000) as usize;
-                if let Some(b) = self.ram.get_mut(offset) { *b = value; }
+                if let MbcState::Mbc2 { .. } = self.mbc {
+                    self.ram[(addr - 0xA000) as usize % MBC2_RAM_BYTES] = value & 0x0F;
+                } else {
+                    let offset = self.ram_bank() * 0x2000 + (addr - 0xA000) as usize;
+                    if let Some(b) = self.ram.get_mut(offset) { *b = value; }
+                }
             }
             #[cfg(feature = "perf")]
             {
@@ -324,10 +395,31 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
 
     fn current_rom_bank(&self) -> usize { self.current_bank_num }
 
+    fn rumble_active(&self) -> bool {
+        matches!(self.mbc, MbcState::Mbc5 { has_rumble: true, ram_bank, .. } if ram_bank & 0x08 != 0)
+    }
+
     fn external_ram(&self) -> Option<&[u8]> {
         if self.ram.is_empty() { None } else { Some(&self.ram) }
     }
 
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.ram.is_empty() { None } else { Some(&mut self.ram) }
+    }
+
+    fn poke_external_ram(&mut self, bank: usize, offset: u16, value: u8) {
+        if let MbcState::Mbc2 { .. } = self.mbc {
+            if bank == 0 {
+                self.ram[offset as usize % MBC2_RAM_BYTES] = value & 0x0F;
+            }
+            return;
+        }
+        let index = bank * 0x2000 + offset as usize;
+        if let Some(b) = self.ram.get_mut(index) {
+            *b = value;
+        }
+    }
+
     fn set_external_ram(&mut self, data: &[u8]) {
         let len = self.ram.len().min(data.len());
         self.ram[..len].copy_from_slice(&data[..len]);
@@ -339,9 +431,16 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
             MbcState::Mbc1 { rom_bank_lo, upper_bits, ram_mode, ram_enabled, .. } => {
                 out.extend_from_slice(&[*rom_bank_lo, *upper_bits, *ram_mode as u8, *ram_enabled as u8]);
             }
+            MbcState::Mbc2 { rom_bank, ram_enabled } => {
+                out.extend_from_slice(&[*rom_bank, *ram_enabled as u8]);
+            }
             MbcState::Mbc3 { rom_bank, bank_or_rtc, ram_rtc_enabled } => {
                 out.extend_from_slice(&[*rom_bank, *bank_or_rtc, *ram_rtc_enabled as u8]);
             }
+            MbcState::Mbc5 { rom_bank, ram_bank, ram_enabled, .. } => {
+                out.extend_from_slice(&rom_bank.to_le_bytes());
+                out.extend_from_slice(&[*ram_bank, *ram_enabled as u8]);
+            }
         }
     }
 
@@ -356,6 +455,12 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
                 *ram_enabled = data[offset + 3] != 0;
                 4
             }
+            MbcState::Mbc2 { rom_bank, ram_enabled } => {
+                if data.len() < offset + 2 { return 0; }
+                *rom_bank    = (data[offset] & 0x0F).max(1);
+                *ram_enabled = data[offset + 1] != 0;
+                2
+            }
             MbcState::Mbc3 { rom_bank, bank_or_rtc, ram_rtc_enabled } => {
                 if data.len() < offset + 3 { return 0; }
                 *rom_bank        = data[offset].max(1);
@@ -363,6 +468,13 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
                 *ram_rtc_enabled = data[offset + 2] != 0;
                 3
             }
+            MbcState::Mbc5 { rom_bank, ram_bank, ram_enabled, .. } => {
+                if data.len() < offset + 4 { return 0; }
+                *rom_bank    = u16::from_le_bytes([data[offset], data[offset + 1]]) & 0x1FF;
+                *ram_bank    = data[offset + 2] & 0x0F;
+                *ram_enabled = data[offset + 3] != 0;
+                4
+            }
         };
         if consumed > 0 {
             self.fixed_bank_num   = usize::MAX;
@@ -380,37 +492,44 @@ impl<R: RomReader> Cartridge for StreamingCartridge<R> {
 
 // ── Header decoders ──────────────────────────────────────────────────────────
 
-fn rom_bank_count_from_code(code: u8) -> usize {
-    2usize << code
-}
-
-fn ram_bytes_from_code(code: u8) -> usize {
-    match code {
-        0x01 => 2 * 1024,
-        0x02 => 8 * 1024,
-        0x03 => 32 * 1024,
-        0x04 => 128 * 1024,
-        0x05 => 64 * 1024,
-        _ => 0,
-    }
-}
-
-fn mbc_state_from_header(cart_type: u8, ram_bytes: usize) -> Option<MbcState> {
+fn mbc_state_from_header(cart_type: CartridgeType, ram_bytes: usize) -> Option<MbcState> {
     let ram_bank_count = if ram_bytes == 0 { 0 } else { (ram_bytes / 0x2000).max(1) };
     match cart_type {
-        0x00 => Some(MbcState::NoMbc),
-        0x01 | 0x02 | 0x03 => Some(MbcState::Mbc1 {
+        CartridgeType::RomOnly => Some(MbcState::NoMbc),
+        CartridgeType::Mbc1
+        | CartridgeType::Mbc1Ram
+        | CartridgeType::Mbc1RamBattery => Some(MbcState::Mbc1 {
             rom_bank_lo: 1,
             upper_bits: 0,
             ram_mode: false,
             ram_enabled: false,
             ram_bank_count,
         }),
-        0x0F | 0x10 | 0x11 | 0x12 | 0x13 => Some(MbcState::Mbc3 {
+        CartridgeType::Mbc2 | CartridgeType::Mbc2Battery => Some(MbcState::Mbc2 {
+            rom_bank: 1,
+            ram_enabled: false,
+        }),
+        CartridgeType::Mbc3TimerBattery
+        | CartridgeType::Mbc3TimerRamBattery
+        | CartridgeType::Mbc3
+        | CartridgeType::Mbc3Ram
+        | CartridgeType::Mbc3RamBattery => Some(MbcState::Mbc3 {
             rom_bank: 1,
             bank_or_rtc: 0,
             ram_rtc_enabled: false,
         }),
+        CartridgeType::Mbc5
+        | CartridgeType::Mbc5Ram
+        | CartridgeType::Mbc5RamBattery
+        | CartridgeType::Mbc5Rumble
+        | CartridgeType::Mbc5RumbleRam
+        | CartridgeType::Mbc5RumbleRamBattery => Some(MbcState::Mbc5 {
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: false,
+            has_rumble: cart_type.has_rumble(),
+            ram_bank_count,
+        }),
         _ => None,
     }
 }
@@ -420,6 +539,7 @@ fn mbc_state_from_header(cart_type: u8, ram_bytes: usize) -> Option<MbcState> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::header::{header_checksum, CART_TYPE_ADDR, HEADER_CHECKSUM_ADDR, RAM_SIZE_ADDR, ROM_SIZE_ADDR};
 
     struct MockRomReader {
         banks:    Vec<[u8; 0x4000]>,
@@ -432,9 +552,10 @@ mod tests {
             for (i, bank) in banks.iter_mut().enumerate() {
                 bank.fill(i as u8);
             }
-            banks[0][CART_TYPE] = cart_type;
-            banks[0][ROM_SIZE]  = rom_size_code_for(num_banks);
-            banks[0][RAM_SIZE]  = ram_size_code;
+            banks[0][CART_TYPE_ADDR]       = cart_type;
+            banks[0][ROM_SIZE_ADDR]        = rom_size_code_for(num_banks);
+            banks[0][RAM_SIZE_ADDR]        = ram_size_code;
+            banks[0][HEADER_CHECKSUM_ADDR] = header_checksum(&banks[0]);
             Self { banks, read_log: Vec::new() }
         }
     }
@@ -494,6 +615,16 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn new_bad_header_checksum_returns_error() {
+        let mut reader = MockRomReader::new(2, 0x01, 0x00);
+        reader.banks[0][HEADER_CHECKSUM_ADDR] ^= 0xFF;
+        assert!(matches!(
+            StreamingCartridge::new(reader),
+            Err(StreamingError::InvalidHeader(CartridgeError::BadHeaderChecksum { .. }))
+        ));
+    }
+
     // ── read_rom ──────────────────────────────────────────────────────────────
 
     #[test]
@@ -586,6 +717,105 @@ mod tests {
         assert_eq!(cart.read_rom(0x0000), 0x00);
     }
 
+    // ── MBC2 ──────────────────────────────────────────────────────────────────
+
+    fn mbc2(num_banks: usize) -> StreamingCartridge<MockRomReader> {
+        // cart type 0x06 = MBC2+BATTERY; RAM size code is always 0 for MBC2
+        StreamingCartridge::new(MockRomReader::new(num_banks, 0x06, 0x00)).unwrap()
+    }
+
+    #[test]
+    fn mbc2_bank_register_selected_by_address_bit8() {
+        let mut cart = mbc2(16);
+        cart.write(0x2100, 0x09);
+        assert_eq!(cart.current_rom_bank(), 9);
+        assert_eq!(cart.read_rom(0x4000), 0x09);
+        cart.reader.read_log.clear();
+        cart.write(0x2000, 0x0A); // RAM enable, no reload
+        assert!(cart.reader.read_log.is_empty());
+    }
+
+    #[test]
+    fn mbc2_ram_is_mirrored_nibble_storage() {
+        let mut cart = mbc2(4);
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA1FF, 0x3E);
+        assert_eq!(cart.read_ram(0x01FF), 0xFE);
+        assert_eq!(cart.read_ram(0x1FFF), 0xFE);
+        assert_eq!(cart.external_ram().map(|r| r.len()), Some(MBC2_RAM_BYTES));
+    }
+
+    #[test]
+    fn mbc2_poke_stores_mirrored_nibble_in_bank0_only() {
+        let mut cart = mbc2(4);
+        cart.poke_external_ram(0, 0x03FF, 0x3E);
+        cart.poke_external_ram(1, 0x0000, 0x3E);
+        let ram = cart.external_ram().unwrap();
+        assert_eq!(ram[0x1FF], 0x0E);
+        assert_eq!(ram[0], 0x00);
+    }
+
+    // ── MBC5 ──────────────────────────────────────────────────────────────────
+
+    fn mbc5(num_banks: usize) -> StreamingCartridge<MockRomReader> {
+        // cart type 0x1B = MBC5+RAM+BATTERY, ram_size_code 0x04 = 128 KiB
+        StreamingCartridge::new(MockRomReader::new(num_banks, 0x1B, 0x04)).unwrap()
+    }
+
+    #[test]
+    fn mbc5_bank_switch_loads_new_bank() {
+        let mut cart = mbc5(8);
+        cart.write(0x2000, 0x05);
+        assert_eq!(cart.current_rom_bank(), 5);
+        assert_eq!(cart.read_rom(0x4000), 0x05);
+    }
+
+    #[test]
+    fn mbc5_writing_zero_selects_bank0() {
+        let mut cart = mbc5(8);
+        cart.write(0x2000, 0x00);
+        assert_eq!(cart.current_rom_bank(), 0);
+        assert_eq!(cart.read_rom(0x4000), 0x00);
+    }
+
+    #[test]
+    fn mbc5_high_bit_wraps_to_rom_size() {
+        let mut cart = mbc5(64);
+        cart.write(0x2000, 0x03);
+        cart.write(0x3000, 0x01); // 0x103 % 64 = 3
+        assert_eq!(cart.current_rom_bank(), 3);
+    }
+
+    #[test]
+    fn mbc5_ram_bank_select_skips_reload() {
+        let mut cart = mbc5(8);
+        cart.reader.read_log.clear();
+        cart.write(0x4000, 0x03);
+        assert!(cart.reader.read_log.is_empty());
+    }
+
+    #[test]
+    fn mbc5_ram_banking() {
+        let mut cart = mbc5(8);
+        cart.write(0x0000, 0x0A);
+        cart.write(0x4000, 0x0F);
+        cart.write(0xA000, 0x99);
+        cart.write(0x4000, 0x00);
+        assert_eq!(cart.read_ram(0x0000), 0x00);
+        cart.write(0x4000, 0x0F);
+        assert_eq!(cart.read_ram(0x0000), 0x99);
+    }
+
+    #[test]
+    fn mbc5_rumble_bit_reported() {
+        // cart type 0x1C = MBC5+RUMBLE
+        let mut cart = StreamingCartridge::new(MockRomReader::new(8, 0x1C, 0x00)).unwrap();
+        cart.write(0x4000, 0x08);
+        assert!(cart.rumble_active());
+        cart.write(0x4000, 0x00);
+        assert!(!cart.rumble_active());
+    }
+
     // ── External RAM ──────────────────────────────────────────────────────────
 
     #[test]
@@ -641,6 +871,19 @@ mod tests {
         assert_eq!(cart2.current_rom_bank(), 3);
     }
 
+    #[test]
+    fn mbc5_save_load_state_restores_bank() {
+        let mut cart = mbc5(64);
+        cart.write(0x2000, 0x2A);
+        let mut blob = Vec::new();
+        cart.save_mbc_state(&mut blob);
+
+        let mut cart2 = mbc5(64);
+        assert_eq!(cart2.load_mbc_state(&blob, 0), 4);
+        assert_eq!(cart2.current_rom_bank(), 0x2A);
+        assert_eq!(cart2.read_rom(0x4000), 0x2A);
+    }
+
     #[test]
     fn mbc3_save_load_state_restores_bank() {
         let mut cart = mbc3(8);
@@ -5,6 +5,7 @@ mod common;
 use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
 use rustyboy_core::cpu::registers::{Flags, Registers};
 use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
 use rustyboy_core::memory::memory::GameBoyMemory;
 
 /// Build a minimal in-memory ROM with a NOP + JR -2 loop at 0x0100.
@@ -21,8 +22,10 @@ fn make_rom(cart_type: u8, rom_size_code: u8, ram_size_code: u8) -> Vec<u8> {
 }
 
 /// Create a fresh Sm83 with DMG post-boot register state.
-fn make_emulator(rom: Vec<u8>) -> Sm83 {
-    let memory = Box::new(GameBoyMemory::with_rom(rom));
+fn make_emulator(mut rom: Vec<u8>) -> Sm83 {
+    // Tests tweak header bytes after `make_rom`, so stamp the checksum last.
+    rom[0x014D] = header_checksum(&rom);
+    let memory = Box::new(GameBoyMemory::with_rom(rom).expect("test ROM should load"));
     let decoder = Box::new(OpCodeDecoder::new());
     Sm83::new(memory, decoder)
         .with_registers(Registers {
@@ -126,6 +129,29 @@ fn test_mbc3_external_ram_roundtrip() {
     }
 }
 
+// ── MBC2 built-in RAM roundtrip ──────────────────────────────────────────────
+
+#[test]
+fn test_mbc2_external_ram_roundtrip() {
+    // Cart type 0x06 = MBC2+BATTERY; the header RAM size is 0 for MBC2
+    let rom = make_rom(0x06, 0, 0x00);
+    let mut cpu = make_emulator(rom);
+
+    let ram_len = cpu.external_ram().expect("MBC2 cart should expose built-in RAM").len();
+    assert_eq!(ram_len, 512, "MBC2 RAM should be 512 half-bytes");
+
+    let data: Vec<u8> = (0..ram_len).map(|i| (i % 16) as u8).collect();
+    cpu.set_external_ram(&data);
+    assert_eq!(cpu.external_ram().unwrap(), &data[..]);
+
+    // Only the lower nibble of each byte is backed by the chip
+    cpu.set_external_ram(&vec![0xFF; ram_len]);
+    assert!(
+        cpu.external_ram().unwrap().iter().all(|&b| b == 0x0F),
+        "MBC2 RAM should drop the upper nibble of restored save data"
+    );
+}
+
 // ── Partial write does not panic ──────────────────────────────────────────────
 
 #[test]
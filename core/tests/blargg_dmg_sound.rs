@@ -8,6 +8,11 @@
 mod common;
 
 use common::assert_blargg_mem_passed;
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::peripheral::apu::SoundChannel;
+use rustyboy_core::cpu::registers::Registers;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::memory::GameBoyMemory;
 
 #[test]
 fn test_dmg_sound_01_registers() {
@@ -104,3 +109,57 @@ fn test_dmg_sound_12_wave_write_while_on() {
         "12-wave write while on",
     );
 }
+
+/// Run a dmg_sound ROM until it reports a result, with `muted` channels
+/// silenced, returning the mixed audio and the per-channel streams.
+fn run_with_channel_capture(path: &str, muted: u8) -> (String, Vec<f32>, [Vec<u8>; 4]) {
+    let memory = Box::new(GameBoyMemory::with_rom(common::load_rom(path)).expect("ROM should load"));
+    let mut cpu = Sm83::new(memory, Box::new(OpCodeDecoder::new()))
+        .with_registers(Registers { pc: 0x0100, sp: 0xFFFE, ..Default::default() });
+    cpu.set_channel_capture(true);
+    cpu.set_muted_channels(muted);
+
+    let mut mixed = Vec::new();
+    let mut channels: [Vec<u8>; 4] = Default::default();
+    for _ in 0..1200 {
+        cpu.run_frame().unwrap();
+        mixed.extend(cpu.drain_audio_samples());
+        for (all, frame) in channels.iter_mut().zip(cpu.drain_channel_samples()) {
+            all.extend(frame);
+        }
+        let signed = cpu.read_memory(0xA001).unwrap_or(0) == 0xDE;
+        if signed && cpu.read_memory(0xA000).unwrap_or(0x80) != 0x80 {
+            break;
+        }
+    }
+    let text = (0xA004u16..)
+        .map(|addr| cpu.read_memory(addr).unwrap_or(0))
+        .take_while(|&b| b != 0)
+        .map(char::from)
+        .collect();
+    (text, mixed, channels)
+}
+
+#[test]
+fn test_dmg_sound_01_registers_channel_output() {
+    // The register test leaves both pulse channels audible for a while.
+    let path = "roms/blargg/dmg_sound/individual/01-registers.gb";
+    let (text, mixed, channels) = run_with_channel_capture(path, 0);
+    assert!(text.contains("Passed"), "01-registers: {text:?}");
+    for (channel, stream) in SoundChannel::ALL.iter().zip(&channels) {
+        assert_eq!(stream.len(), mixed.len() / 2, "{channel:?} stream length");
+    }
+    for channel in [SoundChannel::Pulse1, SoundChannel::Pulse2] {
+        let stream = &channels[channel as usize];
+        assert!(stream.iter().any(|&level| level != 0), "{channel:?} never sounded");
+    }
+    assert!(mixed.iter().any(|&s| s != 0.0));
+
+    // Muting every channel silences the mix but not the channel streams,
+    // and doesn't change what the ROM observes.
+    let all = SoundChannel::ALL.iter().fold(0, |mask, c| mask | c.mask());
+    let (muted_text, muted_mix, muted_channels) = run_with_channel_capture(path, all);
+    assert_eq!(muted_text, text);
+    assert_eq!(muted_channels, channels);
+    assert!(muted_mix.iter().all(|&s| s == 0.0), "muted mix is not silent");
+}
@@ -1,79 +1,79 @@
 //! Integration tests using Blargg's oam_bug test ROMs.
 //!
 //! Tests OAM corruption behavior on DMG when 16-bit inc/dec
-//! targets the FE00-FEFF range during visible scanlines.
+//! targets the FE00-FEFF range during visible scanlines. The ROMs report
+//! their result through cartridge RAM rather than the serial port.
 
 mod common;
 
-use common::assert_blargg_passed;
+use common::{assert_blargg_mem_passed, load_rom, run_blargg_mem_rom_data};
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_01_lcd_sync() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/1-lcd_sync.gb",
         "1-lcd_sync",
     );
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_02_causes() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/2-causes.gb",
         "2-causes",
     );
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_03_non_causes() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/3-non_causes.gb",
         "3-non_causes",
     );
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_04_scanline_timing() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/4-scanline_timing.gb",
         "4-scanline_timing",
     );
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_05_timing_bug() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/5-timing_bug.gb",
         "5-timing_bug",
     );
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_06_timing_no_bug() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/6-timing_no_bug.gb",
         "6-timing_no_bug",
     );
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_07_timing_effect() {
-    assert_blargg_passed(
-        "roms/blargg/oam_bug/individual/7-timing_effect.gb",
-        "7-timing_effect",
-    );
+    // The ROM prints a 525-byte OAM dump for each of the 20 delays that
+    // corrupt OAM. Its text log at 0xA004 outgrows the 8 KiB of cartridge
+    // RAM and runs on into the code it executes from in WRAM, so stub out
+    // the log append (0xC3E7, copied from ROM offset 0x43E7) and go by the
+    // result code. The text still reaches the screen, and the ROM checks
+    // its CRC either way.
+    let mut rom = load_rom("roms/blargg/oam_bug/individual/7-timing_effect.gb");
+    assert_eq!(rom[0x43E7..0x43EA], [0xE5, 0xF5, 0xFA], "log append moved");
+    rom[0x43E7] = 0xC9; // RET
+    let (status, _) = run_blargg_mem_rom_data(rom);
+    assert_eq!(status, 0, "7-timing_effect: expected result code 0");
 }
 
 #[test]
-#[ignore] // Requires PPU implementation
 fn test_oam_bug_08_instr_effect() {
-    assert_blargg_passed(
+    assert_blargg_mem_passed(
         "roms/blargg/oam_bug/individual/8-instr_effect.gb",
         "8-instr_effect",
     );
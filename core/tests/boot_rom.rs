@@ -0,0 +1,187 @@
+//! Integration tests for running a DMG boot ROM before the cartridge.
+//!
+//! The real DMG boot ROM can't be distributed, so the main test uses a small
+//! stand-in image that leaves the machine in the documented post-boot state
+//! and hands off at 0x0100 the same way. To check against the real image, put
+//! it at `roms/boot/dmg_boot.bin` (or point `RUSTYBOY_DMG_BOOT_ROM` at it) and
+//! run: cargo test --test boot_rom -- --ignored
+
+mod common;
+
+use rustyboy_core::cpu::cpu::Cpu;
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::registers::{Flags, Registers};
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+use rustyboy_core::memory::memory::{GameBoyMemory, BOOT_ROM_SIZE};
+
+/// IO registers `with_dmg_state` seeds, compared after the boot ROM hands off.
+const POST_BOOT_IO: [(u16, &str); 7] = [
+    (0xFF40, "LCDC"),
+    (0xFF47, "BGP"),
+    (0xFF48, "OBP0"),
+    (0xFF49, "OBP1"),
+    (0xFF24, "NR50"),
+    (0xFF25, "NR51"),
+    (0xFF26, "NR52"),
+];
+
+/// Upper bound on instructions for a boot sequence (the real one takes ~2.5 s).
+const MAX_BOOT_TICKS: u32 = 5_000_000;
+
+fn make_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0000] = 0xAA; // distinguishes cartridge from boot ROM at 0x0000
+    rom[0x0100] = 0x00; // NOP
+    rom[0x0101] = 0x18; // JR -2
+    rom[0x0102] = 0xFE;
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+fn post_boot_reference(rom: Vec<u8>) -> Sm83 {
+    let memory = Box::new(GameBoyMemory::with_rom(rom).expect("ROM should load"));
+    Sm83::new(memory, Box::new(OpCodeDecoder::new()))
+        .with_registers(Registers {
+            a: 0x01,
+            f: Flags::from_bits_truncate(0xB0),
+            b: 0x00,
+            c: 0x13,
+            d: 0x00,
+            e: 0xD8,
+            h: 0x01,
+            l: 0x4D,
+            pc: 0x0100,
+            sp: 0xFFFE,
+        })
+        .with_dmg_state()
+}
+
+fn booting(rom: Vec<u8>, boot: [u8; BOOT_ROM_SIZE]) -> Sm83 {
+    let memory = Box::new(GameBoyMemory::with_rom(rom).expect("ROM should load"));
+    Sm83::new(memory, Box::new(OpCodeDecoder::new())).with_boot_rom(boot)
+}
+
+/// Stand-in boot ROM: sets up the APU, palettes and LCD, loads the post-boot
+/// registers, then unmaps itself with `LDH (0x50),A` at 0x00FE.
+fn stand_in_boot_rom() -> [u8; BOOT_ROM_SIZE] {
+    let mut code = vec![0x31, 0xFE, 0xFF]; // LD SP,0xFFFE
+    for (reg, value) in [
+        (0x26, 0x80), // NR52: APU on
+        (0x25, 0xF3), // NR51
+        (0x24, 0x77), // NR50
+        (0x47, 0xFC), // BGP
+        (0x48, 0xFF), // OBP0
+        (0x49, 0xFF), // OBP1
+        (0x40, 0x91), // LCDC: LCD on, BG on
+    ] {
+        code.extend_from_slice(&[0x3E, value, 0xE0, reg]); // LD A,n; LDH (reg),A
+    }
+    code.extend_from_slice(&[0x01, 0xB0, 0x01, 0xC5, 0xF1]); // LD BC,0x01B0; PUSH BC; POP AF
+    code.extend_from_slice(&[0x01, 0x13, 0x00]); // LD BC,0x0013
+    code.extend_from_slice(&[0x11, 0xD8, 0x00]); // LD DE,0x00D8
+    code.extend_from_slice(&[0x21, 0x4D, 0x01]); // LD HL,0x014D
+
+    let mut boot = [0x00; BOOT_ROM_SIZE]; // NOP sled up to the hand-off
+    boot[..code.len()].copy_from_slice(&code);
+    boot[0xFC..].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]); // LD A,1; LDH (0x50),A
+    boot
+}
+
+/// Tick until PC first reaches 0x0100. The 0xFF50 write lands on the next
+/// M-cycle, so the boot ROM is only unmapped after one more tick.
+/// Returns whether any frame during boot had a non-blank pixel.
+fn run_until_handoff(cpu: &mut Sm83) -> bool {
+    let mut drew_something = false;
+    for i in 0..MAX_BOOT_TICKS {
+        if cpu.registers().pc == 0x0100 {
+            return drew_something;
+        }
+        cpu.tick().unwrap();
+        if i % 1024 == 0 {
+            drew_something |= cpu.framebuffer().iter().any(|&p| p != 0);
+        }
+    }
+    panic!("boot ROM did not hand off (PC = {:#06x})", cpu.registers().pc);
+}
+
+fn assert_matches_post_boot_state(cpu: &Sm83, reference: &Sm83) {
+    let (got, want) = (cpu.registers(), reference.registers());
+    assert_eq!((got.a, got.f), (want.a, want.f), "AF after boot");
+    assert_eq!((got.b, got.c), (want.b, want.c), "BC after boot");
+    assert_eq!((got.d, got.e), (want.d, want.e), "DE after boot");
+    assert_eq!((got.h, got.l), (want.h, want.l), "HL after boot");
+    assert_eq!(got.sp, want.sp, "SP after boot");
+    assert_eq!(got.pc, want.pc, "PC after boot");
+    for (addr, name) in POST_BOOT_IO {
+        assert_eq!(
+            cpu.read_memory(addr).unwrap(),
+            reference.read_memory(addr).unwrap(),
+            "{name} after boot",
+        );
+    }
+}
+
+#[test]
+fn test_boot_rom_is_mapped_at_power_on() {
+    let boot = stand_in_boot_rom();
+    let cpu = booting(make_rom(), boot);
+    assert!(cpu.boot_rom_mapped());
+    assert_eq!(cpu.registers().pc, 0x0000);
+    assert_eq!(cpu.read_memory(0x0000).unwrap(), boot[0]);
+    assert_eq!(cpu.read_memory(0x0101).unwrap(), 0x18, "cartridge visible above 0x00FF");
+}
+
+#[test]
+fn test_boot_rom_hands_off_in_post_boot_state() {
+    let rom = make_rom();
+    let mut cpu = booting(rom.clone(), stand_in_boot_rom());
+    run_until_handoff(&mut cpu);
+    assert_matches_post_boot_state(&cpu, &post_boot_reference(rom));
+
+    cpu.tick().unwrap();
+    assert!(!cpu.boot_rom_mapped());
+    assert_eq!(cpu.read_memory(0x0000).unwrap(), 0xAA, "boot ROM should be unmapped");
+
+    // The cartridge keeps running once the boot ROM is gone.
+    for _ in 0..1000 {
+        cpu.tick().unwrap();
+    }
+    assert!((0x0100..=0x0102).contains(&cpu.registers().pc));
+}
+
+#[test]
+fn test_boot_rom_cannot_be_remapped() {
+    // Cartridge writes 0 to 0xFF50 right after the hand-off; it must not remap.
+    let mut rom = make_rom();
+    rom[0x0100..0x0105].copy_from_slice(&[0xAF, 0xE0, 0x50, 0x18, 0xFE]); // XOR A; LDH (0x50),A; JR -2
+    rom[0x014D] = header_checksum(&rom);
+    let mut cpu = booting(rom, stand_in_boot_rom());
+    run_until_handoff(&mut cpu);
+    for _ in 0..3 {
+        cpu.tick().unwrap();
+    }
+    assert!(!cpu.boot_rom_mapped());
+    assert_eq!(cpu.read_memory(0x0000).unwrap(), 0xAA);
+}
+
+#[test]
+#[ignore = "requires a DMG boot ROM image, which is not distributed"]
+fn test_real_dmg_boot_rom_hands_off_in_post_boot_state() {
+    let path = std::env::var("RUSTYBOY_DMG_BOOT_ROM")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| common::rom_path("roms/boot/dmg_boot.bin"));
+    let image = std::fs::read(&path)
+        .unwrap_or_else(|_| panic!("boot ROM not found: {}", path.display()));
+    let boot: [u8; BOOT_ROM_SIZE] = image.try_into().expect("DMG boot ROM is 256 bytes");
+
+    // Any cartridge with a valid logo and header checksum will do.
+    let rom = common::load_rom("roms/blargg/cpu_instrs/individual/01-special.gb");
+    let mut cpu = booting(rom.clone(), boot);
+    let drew_logo = run_until_handoff(&mut cpu);
+
+    assert!(drew_logo, "boot ROM should draw the scrolling logo");
+    assert_matches_post_boot_state(&cpu, &post_boot_reference(rom));
+    cpu.tick().unwrap();
+    assert!(!cpu.boot_rom_mapped());
+}
@@ -0,0 +1,47 @@
+//! Integration test using the cgb-acid2 PPU test ROM.
+//!
+//! This test renders the ROM on a CGB and compares the RGB555 framebuffer
+//! against the suite's reference image (roms/cgb-acid2/reference.png).
+//! The ROM and image are not vendored yet.
+
+mod common;
+
+use common::{load_reference_png_rgb555, load_rom, run_color_rom_data_frames};
+
+#[test]
+#[ignore = "requires the cgb-acid2 ROM and reference image, which are not vendored yet"]
+fn test_cgb_acid2() {
+    // Same budget as dmg-acid2: the ROM draws its face within a few frames
+    let framebuffer = run_color_rom_data_frames(load_rom("roms/cgb-acid2/cgb-acid2.gbc"), 600);
+    let reference = load_reference_png_rgb555("roms/cgb-acid2/reference.png");
+
+    assert_eq!(
+        framebuffer.len(),
+        reference.len(),
+        "Framebuffer size mismatch: got {} expected {}",
+        framebuffer.len(),
+        reference.len()
+    );
+
+    let mut mismatches = 0;
+    for (i, (got, expected)) in framebuffer.iter().zip(reference.iter()).enumerate() {
+        if got != expected {
+            mismatches += 1;
+            if mismatches <= 10 {
+                let x = i % 160;
+                let y = i / 160;
+                eprintln!(
+                    "Pixel ({},{}) mismatch: got {:#06X} expected {:#06X}",
+                    x, y, got, expected
+                );
+            }
+        }
+    }
+
+    assert_eq!(
+        mismatches, 0,
+        "cgb-acid2: {} pixel mismatches out of {} total",
+        mismatches,
+        framebuffer.len()
+    );
+}
@@ -0,0 +1,157 @@
+//! CGB rendering checks on a ROM built in memory.
+//!
+//! The program draws one scene covering what cgb-acid2 exercises: BG map
+//! attributes (palette, VRAM bank, X/Y flip, BG-to-OBJ priority), object
+//! palettes and VRAM bank, the LCDC bit 0 master priority, and CGB object
+//! priority by OAM index. The test checks individual pixels of the RGB555
+//! framebuffer against the colors the program loaded into the palettes.
+
+mod common;
+
+use common::{mooneye_rom_template, run_color_rom_data_frames, MOONEYE_MAIN_ADDR};
+use rustyboy_core::memory::header::header_checksum;
+
+const WHITE: u16 = 0x7FFF;
+const RED: u16 = 0x001F;
+const GREEN: u16 = 0x03E0;
+const BLACK: u16 = 0x0000;
+const BLUE: u16 = 0x7C00;
+const YELLOW: u16 = 0x03FF;
+const MAGENTA: u16 = 0x7C1F;
+const DARK_RED: u16 = 0x0010;
+const CYAN: u16 = 0x7FE0;
+
+/// BG palettes 0 and 1, then OBJ palettes 0-2, four colors each.
+const BG_PALETTES: [u16; 8] = [WHITE, RED, GREEN, BLACK, BLUE, YELLOW, BLACK, BLACK];
+const OBJ_PALETTES: [u16; 12] =
+    [BLACK, MAGENTA, DARK_RED, BLACK, BLACK, BLACK, BLACK, BLACK, BLACK, CYAN, BLACK, BLACK];
+
+/// BG map row 0, as (tile, attributes). Tile 1 is a single color 1 pixel in
+/// its top-left corner in bank 0 and solid color 2 in bank 1; tile 2 is
+/// solid color 3.
+const BG_ROW: [(u8, u8); 7] = [
+    (1, 0x01), // palette 1
+    (1, 0x20), // X flip
+    (1, 0x40), // Y flip
+    (1, 0x08), // tile data from bank 1
+    (2, 0x80), // priority over objects
+    (2, 0x00),
+    (0, 0x80), // priority, but color 0 never hides objects
+];
+
+/// OAM entries as (y, x, tile, attributes). Tile 3 is solid color 1 in
+/// bank 0; tile 1 in bank 1 is solid color 2.
+const OBJECTS: [[u8; 4]; 6] = [
+    [16, 8 * 4 + 8, 3, 0x02], // over the priority tile: hidden
+    [16, 8 * 5 + 8, 3, 0x02], // over a plain tile: shown
+    [16, 8 * 6 + 8, 3, 0x00], // over priority color 0: shown
+    [16, 8 * 7 + 8, 1, 0x08], // tile data from bank 1
+    [16, 76 + 8, 3, 0x02],    // lower OAM index wins on CGB...
+    [16, 72 + 8, 3, 0x00],    // ...even against a smaller X
+];
+
+/// `LD A,value; LDH (reg),A`.
+fn ldh(code: &mut Vec<u8>, reg: u8, value: u8) {
+    code.extend_from_slice(&[0x3E, value, 0xE0, reg]);
+}
+
+/// `LD HL,address` followed by `LD A,byte; LD (HL+),A` for each byte.
+fn store(code: &mut Vec<u8>, address: u16, bytes: &[u8]) {
+    let [lo, hi] = address.to_le_bytes();
+    code.extend_from_slice(&[0x21, lo, hi]);
+    for &byte in bytes {
+        code.extend_from_slice(&[0x3E, byte, 0x22]);
+    }
+}
+
+fn scene_rom(lcdc: u8) -> Vec<u8> {
+    let mut code = Vec::new();
+    ldh(&mut code, 0x40, 0x00); // LCD off
+
+    ldh(&mut code, 0x68, 0x80); // BCPS: index 0, auto-increment
+    for byte in BG_PALETTES.iter().flat_map(|color| color.to_le_bytes()) {
+        ldh(&mut code, 0x69, byte);
+    }
+    ldh(&mut code, 0x6A, 0x80); // OCPS
+    for byte in OBJ_PALETTES.iter().flat_map(|color| color.to_le_bytes()) {
+        ldh(&mut code, 0x6B, byte);
+    }
+
+    let mut tiles = [0u8; 64];
+    tiles[16] = 0x80; // tile 1: top-left pixel, color 1
+    tiles[32..48].fill(0xFF); // tile 2: color 3
+    for row in tiles[48..64].chunks_exact_mut(2) {
+        row[0] = 0xFF; // tile 3: color 1
+    }
+    store(&mut code, 0x8000, &tiles);
+    store(&mut code, 0x9800, &BG_ROW.map(|(tile, _)| tile));
+    store(&mut code, 0xFE00, OBJECTS.as_flattened());
+
+    ldh(&mut code, 0x4F, 0x01); // VBK: bank 1
+    let mut bank1_tile = [0u8; 16];
+    for row in bank1_tile.chunks_exact_mut(2) {
+        row[1] = 0xFF; // tile 1: color 2
+    }
+    store(&mut code, 0x8010, &bank1_tile);
+    store(&mut code, 0x9800, &BG_ROW.map(|(_, attrs)| attrs));
+    ldh(&mut code, 0x4F, 0x00);
+
+    ldh(&mut code, 0x40, lcdc);
+    code.extend_from_slice(&[0x18, 0xFE]); // JR -2
+
+    let mut rom = mooneye_rom_template(0x00, 0x00);
+    rom[0x0143] = 0x80; // CGB enhanced
+    let main = MOONEYE_MAIN_ADDR as usize;
+    rom[main..main + code.len()].copy_from_slice(&code);
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+/// LCD on, tile data at 0x8000, objects on, BG master priority on.
+const LCDC_SCENE: u8 = 0x93;
+
+fn render(lcdc: u8) -> Vec<u16> {
+    run_color_rom_data_frames(scene_rom(lcdc), 3)
+}
+
+fn pixel(frame: &[u16], x: usize, y: usize) -> u16 {
+    frame[y * 160 + x]
+}
+
+#[test]
+fn test_cgb_bg_attributes() {
+    let frame = render(LCDC_SCENE);
+
+    assert_eq!(pixel(&frame, 0, 0), YELLOW, "palette 1, color 1");
+    assert_eq!(pixel(&frame, 1, 0), BLUE, "palette 1, color 0");
+    assert_eq!(pixel(&frame, 8, 0), WHITE, "X flip moves the pixel right");
+    assert_eq!(pixel(&frame, 15, 0), RED);
+    assert_eq!(pixel(&frame, 16, 0), WHITE, "Y flip moves the pixel down");
+    assert_eq!(pixel(&frame, 16, 7), RED);
+    assert!((24..32).all(|x| pixel(&frame, x, 3) == GREEN), "bank 1 tile data");
+}
+
+#[test]
+fn test_cgb_object_palettes_banks_and_bg_priority() {
+    let frame = render(LCDC_SCENE);
+
+    assert_eq!(pixel(&frame, 32, 0), BLACK, "hidden behind a priority tile");
+    assert_eq!(pixel(&frame, 40, 0), CYAN, "OBJ palette 2");
+    assert_eq!(pixel(&frame, 48, 0), MAGENTA, "BG color 0 never hides objects");
+    assert_eq!(pixel(&frame, 56, 0), DARK_RED, "bank 1 object tile, color 2");
+}
+
+#[test]
+fn test_cgb_lcdc_bit0_clears_bg_priority() {
+    let frame = render(LCDC_SCENE & !0x01);
+
+    assert_eq!(pixel(&frame, 32, 0), CYAN);
+}
+
+#[test]
+fn test_cgb_object_priority_follows_oam_order() {
+    let frame = render(LCDC_SCENE);
+
+    assert!((72..76).all(|x| pixel(&frame, x, 0) == MAGENTA));
+    assert!((76..84).all(|x| pixel(&frame, x, 0) == CYAN), "OAM index 4 beats index 5");
+}
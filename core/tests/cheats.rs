@@ -0,0 +1,167 @@
+//! GameShark RAM writes and Game Genie ROM patches on a running cartridge.
+
+use rustyboy_core::cpu::cheats::{CheatCode, CheatError};
+use rustyboy_core::cpu::cpu::Cpu;
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+use rustyboy_core::memory::memory::RomPatch;
+
+/// Enable cart RAM and map RAM bank 0, then loop forever copying the byte at
+/// 0x4000 to 0xC001 and decrementing 0xC000.
+const PROGRAM: &[u8] = &[
+    0x3E, 0x0A, 0xEA, 0x00, 0x00, // LD A,0x0A; LD (0x0000),A
+    0xAF, 0xEA, 0x00, 0x40, // XOR A; LD (0x4000),A
+    0xFA, 0x00, 0x40, // loop: LD A,(0x4000)
+    0xEA, 0x01, 0xC0, // LD (0xC001),A
+    0x21, 0x00, 0xC0, 0x35, // LD HL,0xC000; DEC (HL)
+    0x18, 0xF4, // JR loop
+];
+
+/// A 64 KiB MBC5 cart with 32 KiB of RAM. ROM bank `n` starts with `n * 0x11`.
+fn mbc5_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x10000];
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP 0x0150
+    rom[0x0150..0x0150 + PROGRAM.len()].copy_from_slice(PROGRAM);
+    rom[0x0147] = 0x1B; // MBC5+RAM+BATTERY
+    rom[0x0148] = 0x01; // 64 KiB
+    rom[0x0149] = 0x03; // 32 KiB
+    for bank in 1..4 {
+        rom[bank * 0x4000] = bank as u8 * 0x11;
+    }
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+fn power_on() -> Sm83 {
+    Sm83::from_rom(mbc5_rom(), Box::new(OpCodeDecoder::new()))
+        .unwrap()
+        .with_post_boot_state()
+}
+
+fn game_genie(address: u16, value: u8, compare: Option<u8>) -> String {
+    CheatCode::GameGenie(RomPatch { address, value, compare }).to_string()
+}
+
+#[test]
+fn test_game_shark_rewrites_wram_every_frame() {
+    let mut cpu = power_on();
+    let index = cpu.add_cheat("010900C0").unwrap();
+    for _ in 0..3 {
+        cpu.run_frame().unwrap();
+        assert_eq!(cpu.read_memory(0xC000).unwrap(), 0x09);
+    }
+
+    assert!(cpu.set_cheat_enabled(index, false));
+    cpu.run_frame().unwrap();
+    assert_ne!(cpu.read_memory(0xC000).unwrap(), 0x09);
+}
+
+#[test]
+fn test_game_shark_applies_at_vblank_however_the_cpu_is_stepped() {
+    // 0xC100 is untouched by the program, so only the cheat writes it.
+    let mut cpu = power_on();
+    cpu.add_cheat("01AA00C1").unwrap();
+    cpu.run_cycles(1000).unwrap();
+    assert_eq!(cpu.read_memory(0xC100).unwrap(), 0x00, "no VBlank yet");
+    let result = cpu.run_cycles(CYCLES_PER_FRAME).unwrap();
+    assert!(result.frame);
+    assert_eq!(cpu.read_memory(0xC100).unwrap(), 0xAA);
+
+    // Right after the VBlank instruction the write is the latest one, even
+    // to the byte the program keeps decrementing.
+    let mut cpu = power_on();
+    cpu.add_cheat("010900C0").unwrap();
+    assert!(cpu.run_until_vblank().unwrap().frame);
+    assert_eq!(cpu.read_memory(0xC000).unwrap(), 0x09);
+
+    let mut cpu = power_on();
+    cpu.add_cheat("01AA00C1").unwrap();
+    while cpu.read_memory(0xC100).unwrap() != 0xAA {
+        assert!(cpu.cycle_counter() < CYCLES_PER_FRAME, "never applied by tick");
+        cpu.tick().unwrap();
+    }
+}
+
+#[test]
+fn test_game_shark_bank_code_writes_unmapped_ram_bank() {
+    let mut cpu = power_on();
+    cpu.add_cheat("827700A0").unwrap();
+    cpu.add_cheat("015500A0").unwrap();
+    cpu.run_frame().unwrap();
+
+    let ram = cpu.external_ram().unwrap();
+    assert_eq!(ram[2 * 0x2000], 0x77, "bank 2 written directly");
+    assert_eq!(ram[0], 0x55, "mapped bank 0 written through the bus");
+    assert_eq!(cpu.read_memory(0xA000).unwrap(), 0x55);
+}
+
+#[test]
+fn test_game_shark_bank_code_rejects_missing_ram_bank() {
+    let mut cpu = power_on();
+    assert_eq!(cpu.add_cheat("847700A0"), Err(CheatError::NoSuchRamBank(4)));
+    assert!(cpu.cheats().is_empty());
+    // Without a bank, or outside cart RAM, the code goes through the bus.
+    assert!(cpu.add_cheat("017700A0").is_ok());
+    assert!(cpu.add_cheat("847700C0").is_ok());
+}
+
+#[test]
+fn test_game_shark_bank_code_stores_mbc2_nibbles() {
+    let mut rom = mbc5_rom();
+    rom[0x0147] = 0x06; // MBC2+BATTERY
+    rom[0x0149] = 0x00;
+    rom[0x014D] = header_checksum(&rom);
+    let mut cpu = Sm83::from_rom(rom, Box::new(OpCodeDecoder::new()))
+        .unwrap()
+        .with_post_boot_state();
+
+    assert_eq!(cpu.add_cheat("81F512A0"), Err(CheatError::NoSuchRamBank(1)));
+    cpu.add_cheat("80F512A0").unwrap();
+    cpu.add_cheat("8077FFA3").unwrap(); // 0xA3FF mirrors 0xA1FF
+    cpu.run_frame().unwrap();
+
+    let ram = cpu.external_ram().unwrap();
+    assert_eq!(ram[0x12], 0x05);
+    assert_eq!(ram[0x1FF], 0x07);
+    assert_eq!(cpu.read_memory(0xA012).unwrap(), 0xF5);
+}
+
+#[test]
+fn test_game_genie_patches_rom_reads_by_the_cpu() {
+    let mut cpu = power_on();
+    cpu.run_frame().unwrap();
+    assert_eq!(cpu.read_memory(0xC001).unwrap(), 0x11);
+
+    let index = cpu.add_cheat(&game_genie(0x4000, 0x99, None)).unwrap();
+    cpu.run_frame().unwrap();
+    assert_eq!(cpu.read_memory(0xC001).unwrap(), 0x99);
+    assert_eq!(cpu.read_memory(0x4000).unwrap(), 0x99);
+
+    cpu.remove_cheat(index).unwrap();
+    cpu.run_frame().unwrap();
+    assert_eq!(cpu.read_memory(0xC001).unwrap(), 0x11);
+}
+
+#[test]
+fn test_game_genie_compare_value_targets_one_bank() {
+    let mut cpu = power_on();
+    cpu.add_cheat(&game_genie(0x4000, 0x99, Some(0x22))).unwrap();
+    cpu.run_frame().unwrap();
+    assert_eq!(cpu.read_memory(0xC001).unwrap(), 0x11, "bank 1 is mapped, so the bank 2 code does nothing");
+
+    cpu.clear_cheats();
+    cpu.add_cheat(&game_genie(0x4000, 0x99, Some(0x11))).unwrap();
+    cpu.run_frame().unwrap();
+    assert_eq!(cpu.read_memory(0xC001).unwrap(), 0x99);
+}
+
+#[test]
+fn test_game_genie_patches_fixed_bank() {
+    let mut cpu = power_on();
+    // The entry point's JP opcode; patching it to HALT is visible but harmless.
+    cpu.add_cheat(&game_genie(0x0101, 0x76, Some(0xC3))).unwrap();
+    assert_eq!(cpu.read_memory(0x0101).unwrap(), 0x76);
+    assert_eq!(cpu.read_memory(0x0100).unwrap(), 0x00);
+}
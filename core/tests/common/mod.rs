@@ -3,8 +3,11 @@
 
 use rustyboy_core::cpu::cpu::Cpu;
 use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::movie::Movie;
+use rustyboy_core::cpu::peripheral::ppu::PpuRenderer;
 use rustyboy_core::cpu::registers::Registers;
 use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
 use rustyboy_core::memory::memory::GameBoyMemory;
 
 /// Resolve a ROM path relative to the workspace root.
@@ -26,7 +29,7 @@ pub fn load_rom(path: &str) -> Vec<u8> {
 /// Returns the serial output as a string.
 pub fn run_blargg_rom(path: &str) -> String {
     let rom_data = load_rom(path);
-    let memory = Box::new(GameBoyMemory::with_rom(rom_data));
+    let memory = Box::new(GameBoyMemory::with_rom(rom_data).expect("ROM should load"));
     let decoder = Box::new(OpCodeDecoder::new());
     let mut cpu = Sm83::new(memory, decoder).with_registers(Registers {
         pc: 0x0100,
@@ -69,8 +72,13 @@ pub fn assert_blargg_passed(path: &str, name: &str) {
 ///
 /// Returns the text output as a string.
 pub fn run_blargg_mem_rom(path: &str) -> String {
-    let rom_data = load_rom(path);
-    let memory = Box::new(GameBoyMemory::with_rom(rom_data));
+    run_blargg_mem_rom_data(load_rom(path)).1
+}
+
+/// Run Blargg memory-output ROM data. Returns the status byte at 0xA000
+/// and the text at 0xA004+.
+pub fn run_blargg_mem_rom_data(rom_data: Vec<u8>) -> (u8, String) {
+    let memory = Box::new(GameBoyMemory::with_rom(rom_data).expect("ROM should load"));
     let decoder = Box::new(OpCodeDecoder::new());
     let mut cpu = Sm83::new(memory, decoder).with_registers(Registers {
         pc: 0x0100,
@@ -105,7 +113,7 @@ pub fn run_blargg_mem_rom(path: &str) -> String {
             Ok(b) => text.push(b as char),
         }
     }
-    text
+    (cpu.read_memory(0xA000).unwrap_or(0x80), text)
 }
 
 /// Assert that a Blargg memory-output ROM contains "Passed".
@@ -126,15 +134,29 @@ pub fn assert_blargg_mem_passed(path: &str, name: &str) {
 /// Pass: B=3, C=5, D=8, E=13, H=21, L=34
 /// Fail: B=0x42, C=0x42, D=0x42, E=0x42, H=0x42, L=0x42
 pub fn run_mooneye_rom(path: &str) -> MooneyeResult {
-    let rom_data = load_rom(path);
-    let memory = Box::new(GameBoyMemory::with_rom(rom_data));
+    run_mooneye_rom_data(load_rom(path))
+}
+
+/// Like [`run_mooneye_rom`], but for a ROM image built in memory.
+pub fn run_mooneye_rom_data(rom_data: Vec<u8>) -> MooneyeResult {
+    run_mooneye_cpu(mooneye_cpu(rom_data, PpuRenderer::Scanline))
+}
+
+/// A CPU at the cartridge entry point with no boot ROM state, as the Mooneye
+/// harness runs ROMs, using the given PPU renderer.
+pub fn mooneye_cpu(rom_data: Vec<u8>, renderer: PpuRenderer) -> Sm83 {
+    let memory = Box::new(GameBoyMemory::with_rom(rom_data).expect("ROM should load"));
     let decoder = Box::new(OpCodeDecoder::new());
-    let mut cpu = Sm83::new(memory, decoder).with_registers(Registers {
-        pc: 0x0100,
-        sp: 0xFFFE,
-        ..Default::default()
-    });
+    Sm83::new(memory, decoder)
+        .with_registers(Registers {
+            pc: 0x0100,
+            sp: 0xFFFE,
+            ..Default::default()
+        })
+        .with_ppu_renderer(renderer)
+}
 
+fn run_mooneye_cpu(mut cpu: Sm83) -> MooneyeResult {
     // Mooneye ROMs signal completion with an infinite `JR -2` loop.
     // Most finish in well under 1 million ticks; cap at 10M to be safe.
     const MAX_TICKS: u64 = 10_000_000;
@@ -183,7 +205,56 @@ pub enum MooneyeResult {
 
 /// Assert that a Mooneye ROM passed.
 pub fn assert_mooneye_passed(path: &str, name: &str) {
-    let result = run_mooneye_rom(path);
+    assert_mooneye_result(run_mooneye_rom(path), name);
+}
+
+/// Like [`assert_mooneye_passed`], drawing with the given PPU renderer.
+pub fn assert_mooneye_passed_with(path: &str, name: &str, renderer: PpuRenderer) {
+    assert_mooneye_result(run_mooneye_cpu(mooneye_cpu(load_rom(path), renderer)), name);
+}
+
+/// Address of the Fibonacci "pass" routine planted by [`mooneye_rom_template`].
+pub const MOONEYE_PASS_ADDR: u16 = 0x0150;
+/// Address of the `0x42` "fail" routine planted by [`mooneye_rom_template`].
+pub const MOONEYE_FAIL_ADDR: u16 = 0x0160;
+/// Where the test program goes; the entry point at 0x0100 jumps here.
+pub const MOONEYE_MAIN_ADDR: u16 = 0x0200;
+
+/// Build a zeroed ROM image for an in-memory Mooneye-style test.
+///
+/// Sets the cartridge type and ROM size header bytes (with a valid header
+/// checksum), plants `JP main` at the entry point, and the pass/fail
+/// routines at [`MOONEYE_PASS_ADDR`] and [`MOONEYE_FAIL_ADDR`]. The caller
+/// writes its program at
+/// [`MOONEYE_MAIN_ADDR`] and jumps to one of the two routines when done.
+pub fn mooneye_rom_template(cart_type: u8, rom_size_code: u8) -> Vec<u8> {
+    let mut rom = vec![0u8; (2usize << rom_size_code) * 0x4000];
+    rom[0x0147] = cart_type;
+    rom[0x0148] = rom_size_code;
+
+    let [main_lo, main_hi] = MOONEYE_MAIN_ADDR.to_le_bytes();
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, main_lo, main_hi]); // NOP; JP main
+
+    // B,C,D,E,H,L = 3,5,8,13,21,34; JR -2
+    let pass = MOONEYE_PASS_ADDR as usize;
+    rom[pass..pass + 14].copy_from_slice(&[
+        0x06, 3, 0x0E, 5, 0x16, 8, 0x1E, 13, 0x26, 21, 0x2E, 34, 0x18, 0xFE,
+    ]);
+    // B,C,D,E,H,L = 0x42; JR -2
+    let fail = MOONEYE_FAIL_ADDR as usize;
+    rom[fail..fail + 14].copy_from_slice(&[
+        0x06, 0x42, 0x0E, 0x42, 0x16, 0x42, 0x1E, 0x42, 0x26, 0x42, 0x2E, 0x42, 0x18, 0xFE,
+    ]);
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+/// Assert that an in-memory ROM following the Mooneye pass/fail protocol passed.
+pub fn assert_mooneye_rom_data_passed(rom_data: Vec<u8>, name: &str) {
+    assert_mooneye_result(run_mooneye_rom_data(rom_data), name);
+}
+
+fn assert_mooneye_result(result: MooneyeResult, name: &str) {
     match result {
         MooneyeResult::Pass => {}
         MooneyeResult::Fail => panic!("{}: Mooneye test reported FAIL", name),
@@ -195,32 +266,41 @@ pub fn assert_mooneye_passed(path: &str, name: &str) {
 }
 
 /// Run a ROM for a given number of frames and return a copy of the framebuffer.
-/// One frame = 70,224 dots = ~17,556 CPU ticks (at 4 cycles per tick average).
 pub fn run_rom_frames(path: &str, frames: u32) -> Vec<u8> {
-    let rom_data = load_rom(path);
-    let memory = Box::new(GameBoyMemory::with_rom(rom_data));
-    let decoder = Box::new(OpCodeDecoder::new());
-    let mut cpu = Sm83::new(memory, decoder).with_registers(Registers {
-        pc: 0x0100,
-        sp: 0xFFFE,
-        ..Default::default()
-    });
+    run_rom_frames_with(path, frames, PpuRenderer::Scanline)
+}
 
-    // Each frame is 154 scanlines * 456 dots = 70,224 dots.
-    // CPU tick returns ~4-20 cycles per instruction.
-    // Run enough ticks to cover the requested frames.
-    let total_dots: u64 = frames as u64 * 70_224;
-    let mut dots_elapsed: u64 = 0;
-    while dots_elapsed < total_dots {
-        let cycles = cpu.tick().unwrap() as u64;
-        dots_elapsed += cycles;
+/// Like [`run_rom_frames`], drawing with the given PPU renderer.
+pub fn run_rom_frames_with(path: &str, frames: u32, renderer: PpuRenderer) -> Vec<u8> {
+    let mut cpu = mooneye_cpu(load_rom(path), renderer);
+
+    for _ in 0..frames {
+        cpu.run_frame().unwrap();
     }
 
     cpu.framebuffer().to_vec()
 }
 
+/// Run a CGB ROM image from its post-boot state for `frames` frames and
+/// return the final RGB555 frame.
+pub fn run_color_rom_data_frames(rom_data: Vec<u8>, frames: u32) -> Vec<u16> {
+    let decoder = Box::new(OpCodeDecoder::new());
+    let mut cpu = Sm83::from_rom(rom_data, decoder)
+        .expect("ROM should load")
+        .with_post_boot_state();
+
+    for _ in 0..frames {
+        cpu.run_frame().unwrap();
+    }
+
+    cpu.color_framebuffer()
+        .expect("CGB ROM should run on a CGB")
+        .to_vec()
+}
+
 /// Load a reference PNG image and return the pixels as 2-bit shade values.
-/// The reference image should be 160x144 with 4 shades of gray.
+/// The reference image should be 160x144 with 4 shades of gray, stored as
+/// grayscale or RGB.
 pub fn load_reference_png(path: &str) -> Vec<u8> {
     let full_path = rom_path(path);
     let file = std::fs::File::open(&full_path)
@@ -232,6 +312,8 @@ pub fn load_reference_png(path: &str) -> Vec<u8> {
     let mut buf = vec![0u8; reader.output_buffer_size()];
     let info = reader.next_frame(&mut buf).unwrap();
     let bytes = &buf[..info.buffer_size()];
+    // Gray RGB images are read through their first channel.
+    let channels = info.color_type.samples();
 
     // Map grayscale pixel values to 2-bit shade indices.
     // After EXPAND, 2-bit values become: 0x00, 0x55, 0xAA, 0xFF
@@ -240,7 +322,7 @@ pub fn load_reference_png(path: &str) -> Vec<u8> {
     let mut shades = Vec::with_capacity(total_pixels);
 
     for i in 0..total_pixels {
-        let gray = bytes[i];
+        let gray = bytes[i * channels];
         let shade = match gray {
             0xC0..=0xFF => 0, // white
             0x80..=0xBF => 1, // light gray
@@ -252,3 +334,69 @@ pub fn load_reference_png(path: &str) -> Vec<u8> {
 
     shades
 }
+
+/// Load an RGB reference PNG and return the pixels as RGB555, red in the
+/// low bits as in [`Sm83::color_framebuffer`]. Each 8-bit channel keeps its
+/// top five bits, undoing the `(c << 3) | (c >> 2)` expansion cgb-acid2's
+/// reference image was made with.
+pub fn load_reference_png_rgb555(path: &str) -> Vec<u16> {
+    let full_path = rom_path(path);
+    let file = std::fs::File::open(&full_path)
+        .unwrap_or_else(|_| panic!("Reference image not found: {}", full_path.display()));
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().unwrap();
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).unwrap();
+    let channels = info.color_type.samples();
+    assert!(channels >= 3, "reference image should be RGB: {}", full_path.display());
+
+    buf[..info.buffer_size()]
+        .chunks_exact(channels)
+        .map(|px| {
+            let [r, g, b] = [px[0], px[1], px[2]].map(|c| u16::from(c >> 3));
+            r | (g << 5) | (b << 10)
+        })
+        .collect()
+}
+
+/// Write 2-bit shades (0 = white, 3 = black) as a grayscale PNG under the
+/// cargo test temp directory, so a test's output can be inspected by eye.
+/// Returns the path written.
+pub fn write_shades_png(name: &str, width: usize, height: usize, shades: &[u8]) -> std::path::PathBuf {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let file = std::fs::File::create(&path)
+        .unwrap_or_else(|_| panic!("Can't create image: {}", path.display()));
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let gray: Vec<u8> = shades.iter().map(|&shade| 0xFF - shade * 0x55).collect();
+    encoder.write_header().unwrap().write_image_data(&gray).unwrap();
+    path
+}
+
+/// FNV-1a hash of the last completed frame (the color frame on a CGB), for
+/// comparing runs without keeping whole images around.
+pub fn framebuffer_hash(cpu: &Sm83) -> u64 {
+    let bytes: Vec<u8> = match cpu.color_framebuffer() {
+        Some(fb) => fb.iter().flat_map(|px| px.to_le_bytes()).collect(),
+        None => cpu.framebuffer().to_vec(),
+    };
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3))
+}
+
+/// Replay an RBMV movie of `rom` from its starting state and assert that the
+/// final frame hashes (see [`framebuffer_hash`]) to `expected`.
+pub fn assert_movie_framebuffer_hash(rom: Vec<u8>, movie: &[u8], expected: u64) {
+    let movie = Movie::from_bytes(movie).unwrap_or_else(|e| panic!("Bad movie: {e}"));
+    let mut cpu = movie
+        .start(rom, Box::new(OpCodeDecoder::new()))
+        .unwrap_or_else(|e| panic!("Can't start movie: {e}"));
+    movie.play(&mut cpu).unwrap();
+    assert_eq!(
+        framebuffer_hash(&cpu),
+        expected,
+        "final frame differs after replaying {} frames",
+        movie.frames.len()
+    );
+}
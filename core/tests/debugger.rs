@@ -0,0 +1,272 @@
+//! Integration tests for the `debugger` feature.
+//! Run with: cargo test --features debugger --test debugger
+#![cfg(feature = "debugger")]
+
+use rustyboy_core::cpu::debugger::{
+    Access, Breakpoint, Compare, Condition, Debugger, Reg, StopReason, Watchpoint,
+};
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+use rustyboy_core::cpu::registers::Registers;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+use rustyboy_core::memory::memory::GameBoyMemory;
+
+const BUDGET: u64 = CYCLES_PER_FRAME;
+
+/// 0x0100: LD SP,0xFFFE; CALL 0x0200; LD A,0x42; LD (0xC000),A; LD A,(0xC000); JR -2
+/// 0x0200: INC B; CALL 0x0300; RET
+/// 0x0300: INC C; RET
+const MAIN: &[(u16, &[u8])] = &[
+    (0x0100, &[0x31, 0xFE, 0xFF, 0xCD, 0x00, 0x02, 0x3E, 0x42, 0xEA, 0x00, 0xC0, 0xFA, 0x00, 0xC0, 0x18, 0xFE]),
+    (0x0200, &[0x04, 0xCD, 0x00, 0x03, 0xC9]),
+    (0x0300, &[0x0C, 0xC9]),
+];
+
+fn make_rom(cart_type: u8, rom_size_code: u8, code: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut rom = vec![0u8; (2usize << rom_size_code) * 0x4000];
+    rom[0x0147] = cart_type;
+    rom[0x0148] = rom_size_code;
+    for &(addr, bytes) in code {
+        let addr = addr as usize;
+        rom[addr..addr + bytes.len()].copy_from_slice(bytes);
+    }
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+fn cpu_for(rom: Vec<u8>) -> Sm83 {
+    let memory = Box::new(GameBoyMemory::with_rom(rom).expect("ROM should load"));
+    Sm83::new(memory, Box::new(OpCodeDecoder::new())).with_registers(Registers {
+        pc: 0x0100,
+        sp: 0xFFFE,
+        ..Default::default()
+    })
+}
+
+fn main_cpu() -> Sm83 {
+    cpu_for(make_rom(0x00, 0x00, MAIN))
+}
+
+// ── Breakpoints ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_breakpoint_stops_before_instruction() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    let id = dbg.add_breakpoint(Breakpoint::at(0x0106));
+
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::Breakpoint { id, pc: 0x0106 });
+    let regs = cpu.registers();
+    assert_eq!((regs.b, regs.c), (1, 1), "subroutines ran");
+    assert_ne!(regs.a, 0x42, "LD A,0x42 has not executed");
+}
+
+#[test]
+fn test_run_resumes_past_current_breakpoint() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.add_breakpoint(Breakpoint::at(0x0106));
+    dbg.run(&mut cpu, BUDGET).unwrap();
+
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::CycleLimit);
+    assert_eq!(cpu.registers().a, 0x42);
+}
+
+#[test]
+fn test_conditional_breakpoint() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    // 0x010E is the JR loop; A is 0x42 the first time it is reached.
+    dbg.add_breakpoint(Breakpoint::at(0x010E).when(Condition::new(Reg::A, Compare::Ne, 0x42)));
+
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::CycleLimit);
+}
+
+#[test]
+fn test_bank_qualified_breakpoint() {
+    // MBC1, 4 banks: select bank 2 and jump into the switchable window.
+    let mut rom = make_rom(0x01, 0x01, &[
+        (0x0100, &[0x3E, 0x02, 0xEA, 0x00, 0x20, 0xC3, 0x00, 0x40]), // LD A,2; LD (0x2000),A; JP 0x4000
+    ]);
+    for bank in 1..4 {
+        rom[bank * 0x4000..bank * 0x4000 + 2].copy_from_slice(&[0x18, 0xFE]); // JR -2
+    }
+    rom[0x014D] = header_checksum(&rom);
+
+    let mut cpu = cpu_for(rom.clone());
+    let mut dbg = Debugger::new();
+    dbg.add_breakpoint(Breakpoint::in_bank(1, 0x4000));
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::CycleLimit);
+
+    let mut cpu = cpu_for(rom);
+    let id = dbg.add_breakpoint(Breakpoint::in_bank(2, 0x4000));
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::Breakpoint { id, pc: 0x4000 });
+}
+
+#[test]
+fn test_removed_breakpoint_no_longer_stops() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    let id = dbg.add_breakpoint(Breakpoint::at(0x0106));
+
+    assert!(dbg.remove(id));
+    assert!(!dbg.remove(id));
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::CycleLimit);
+}
+
+// ── Watchpoints ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_write_watchpoint_stops_after_writing_instruction() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.add_watchpoint(Watchpoint::new(0xC000..=0xC000, Access::WRITE));
+
+    let StopReason::Watchpoint { hit, pc } = dbg.run(&mut cpu, BUDGET).unwrap() else {
+        panic!("expected a watchpoint stop");
+    };
+    assert_eq!(pc, 0x0108);
+    assert_eq!((hit.addr, hit.value, hit.access, hit.dma), (0xC000, 0x42, Access::WRITE, false));
+    assert_eq!(cpu.registers().pc, 0x010B);
+}
+
+#[test]
+fn test_read_watchpoint_ignores_writes() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.add_watchpoint(Watchpoint::new(0xC000..=0xC000, Access::READ));
+
+    let StopReason::Watchpoint { hit, pc } = dbg.run(&mut cpu, BUDGET).unwrap() else {
+        panic!("expected a watchpoint stop");
+    };
+    assert_eq!((pc, hit.access), (0x010B, Access::READ));
+}
+
+#[test]
+fn test_execute_watchpoint_stops_before_instruction() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    let id = dbg.add_watchpoint(Watchpoint::new(0x0300..=0x03FF, Access::EXECUTE));
+
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::Execute { id, pc: 0x0300 });
+    assert_eq!(cpu.registers().c, 0);
+}
+
+#[test]
+fn test_watchpoint_sees_oam_dma() {
+    // LD A,0xC0; LDH (0x46),A; JR -2
+    let mut cpu = cpu_for(make_rom(0x00, 0x00, &[(0x0100, &[0x3E, 0xC0, 0xE0, 0x46, 0x18, 0xFE])]));
+    let mut dbg = Debugger::new();
+    dbg.add_watchpoint(Watchpoint::new(0xFE10..=0xFE10, Access::WRITE));
+
+    let StopReason::Watchpoint { hit, .. } = dbg.run(&mut cpu, BUDGET).unwrap() else {
+        panic!("expected a watchpoint stop");
+    };
+    assert!(hit.dma);
+    assert_eq!(hit.addr, 0xFE10);
+}
+
+#[test]
+fn test_watchpoints_are_disarmed_outside_debugger_calls() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.add_watchpoint(Watchpoint::new(0xC000..=0xC000, Access::WRITE));
+    dbg.step_in(&mut cpu).unwrap();
+
+    // Runs straight through the write without a debugger in control.
+    cpu.run_cycles(BUDGET).unwrap();
+    assert_eq!(cpu.read_memory(0xC000).unwrap(), 0x42);
+}
+
+// ── Conditions and interrupts ────────────────────────────────────────────────
+
+#[test]
+fn test_condition_stops_where_it_first_holds() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    let id = dbg.add_condition(Condition::new(Reg::C, Compare::Eq, 1));
+
+    // INC C at 0x0300 makes the condition true before RET at 0x0301.
+    assert_eq!(dbg.run(&mut cpu, BUDGET).unwrap(), StopReason::Condition { id, pc: 0x0301 });
+}
+
+#[test]
+fn test_break_on_interrupt_dispatch() {
+    // LD A,1; LDH (0xFF),A; LD A,0x80; LDH (0x40),A; EI; JR -2, with RETI at 0x0040.
+    let mut cpu = cpu_for(make_rom(0x00, 0x00, &[
+        (0x0040, &[0xD9]),
+        (0x0100, &[0x3E, 0x01, 0xE0, 0xFF, 0x3E, 0x80, 0xE0, 0x40, 0xFB, 0x18, 0xFE]),
+    ]));
+    let mut dbg = Debugger::new();
+    dbg.break_on_interrupts(0x01);
+
+    assert_eq!(dbg.run(&mut cpu, 2 * BUDGET).unwrap(), StopReason::Interrupt { bit: 0 });
+    assert_eq!(cpu.registers().pc, 0x0040);
+}
+
+// ── Stepping ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_step_in_executes_one_instruction() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+
+    assert_eq!(dbg.step_in(&mut cpu).unwrap(), StopReason::Step);
+    assert_eq!(cpu.registers().pc, 0x0103);
+    dbg.step_in(&mut cpu).unwrap();
+    assert_eq!(cpu.registers().pc, 0x0200, "step in follows CALL");
+}
+
+#[test]
+fn test_step_over_runs_call_to_completion() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.step_in(&mut cpu).unwrap(); // LD SP
+
+    assert_eq!(dbg.step_over(&mut cpu, BUDGET).unwrap(), StopReason::Step);
+    let regs = cpu.registers();
+    assert_eq!(regs.pc, 0x0106);
+    assert_eq!((regs.b, regs.c), (1, 1));
+}
+
+#[test]
+fn test_step_over_still_honours_breakpoints() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.step_in(&mut cpu).unwrap(); // LD SP
+    let id = dbg.add_breakpoint(Breakpoint::at(0x0300));
+
+    assert_eq!(dbg.step_over(&mut cpu, BUDGET).unwrap(), StopReason::Breakpoint { id, pc: 0x0300 });
+}
+
+#[test]
+fn test_step_out_returns_to_caller() {
+    let mut cpu = main_cpu();
+    let mut dbg = Debugger::new();
+    dbg.add_breakpoint(Breakpoint::at(0x0300));
+    dbg.run(&mut cpu, BUDGET).unwrap();
+
+    assert_eq!(dbg.step_out(&mut cpu, BUDGET).unwrap(), StopReason::Step);
+    assert_eq!(cpu.registers().pc, 0x0204);
+    assert_eq!(dbg.step_out(&mut cpu, BUDGET).unwrap(), StopReason::Step);
+    assert_eq!(cpu.registers().pc, 0x0106);
+}
+
+#[test]
+fn test_run_to_scanline() {
+    let mut cpu = main_cpu().with_dmg_state();
+    let mut dbg = Debugger::new();
+
+    assert_eq!(dbg.run_to_scanline(&mut cpu, 100, BUDGET).unwrap(), StopReason::Step);
+    assert_eq!(cpu.read_memory(0xFF44).unwrap(), 100);
+}
+
+#[test]
+fn test_run_frame_stops_at_vblank() {
+    let mut cpu = main_cpu().with_dmg_state();
+    let mut dbg = Debugger::new();
+
+    assert_eq!(dbg.run_frame(&mut cpu).unwrap(), StopReason::Frame);
+    assert_eq!(cpu.read_memory(0xFF44).unwrap(), 144);
+}
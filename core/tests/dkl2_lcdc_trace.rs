@@ -4,6 +4,7 @@
 mod common;
 
 use rustyboy_core::cpu::cpu::Cpu;
+use rustyboy_core::cpu::disassembler::Disassembler;
 use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
 use rustyboy_core::cpu::peripheral::joypad::Button;
 use rustyboy_core::cpu::registers::{Flags, Registers};
@@ -23,7 +24,7 @@ const SCY_ADDR: u16 = 0xFF42;
 fn build_cpu() -> Sm83 {
     let rom_path = "/home/vbonduro/roms/extracted/Donkey Kong Land 2 (USA, Europe) (SGB Enhanced).gb";
     let rom_data = std::fs::read(rom_path).expect("DKL2 ROM not found");
-    let memory = Box::new(GameBoyMemory::with_rom(rom_data));
+    let memory = Box::new(GameBoyMemory::with_rom(rom_data).expect("DKL2 ROM should load"));
     let decoder = Box::new(OpCodeDecoder::new());
     Sm83::new(memory, decoder)
         .with_registers(Registers {
@@ -48,6 +49,14 @@ fn run_frame(cpu: &mut Sm83) {
     }
 }
 
+/// Instruction at `pc` as text, for annotating trace lines.
+fn disasm(dis: &Disassembler, cpu: &Sm83, pc: u16) -> String {
+    match dis.decode_cpu(cpu, pc) {
+        Ok(insn) => insn.to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
 fn dump_oam_summary(cpu: &Sm83) -> usize {
     let mut visible = 0;
     for i in 0..40 {
@@ -244,6 +253,7 @@ fn trace_dkl2_isr_pc_trace() {
 
     eprintln!("=== Tracing ISR PC values during level gameplay ===");
 
+    let dis = Disassembler::new();
     let mut prev_if: u8 = cpu.read_memory(IF_ADDR).unwrap_or(0);
     let mut logged_pcs: std::collections::HashSet<u16> = std::collections::HashSet::new();
 
@@ -264,8 +274,8 @@ fn trace_dkl2_isr_pc_trace() {
             let in_14720_region = pc >= 0x4720 && pc <= 0x4730; // bank-switched
 
             if (in_vblank_isr || in_stat_region || in_14720_region) && !logged_pcs.contains(&pc) {
-                eprintln!("  F+{} PC=0x{:04X} LY={:3} LCDC=0x{:02X}(OBJ={}) IF=0x{:02X} FFEB=0x{:02X}",
-                    frame_offset, pc, ly, lcdc, (lcdc>>1)&1, cur_if, ffeb);
+                eprintln!("  F+{} PC=0x{:04X} {:<16} LY={:3} LCDC=0x{:02X}(OBJ={}) IF=0x{:02X} FFEB=0x{:02X}",
+                    frame_offset, pc, disasm(&dis, &cpu, pc), ly, lcdc, (lcdc>>1)&1, cur_if, ffeb);
                 logged_pcs.insert(pc);
             }
 
@@ -0,0 +1,94 @@
+//! Two `Sm83` instances running the same ROM, joined by an in-process link
+//! cable. The instance with A held drives the clock; the other waits on the
+//! external clock. Each sends four bytes and records what it receives.
+
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::peripheral::joypad::Button;
+use rustyboy_core::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+use rustyboy_core::cpu::peripheral::serial::InProcessCable;
+use rustyboy_core::cpu::registers::Registers;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+use rustyboy_core::memory::memory::GameBoyMemory;
+
+const CLOCK_BYTES: [u8; 4] = [0x11, 0x22, 0x33, 0x44];
+const EXTERNAL_BYTES: [u8; 4] = [0xA1, 0xB2, 0xC3, 0xD4];
+
+/// 0x0150: select the action buttons; HL = 0xC000 (received bytes);
+///         A held → DE = 0x0200, SC = 0x81, else DE = 0x0210, SC = 0x80
+/// loop:   SB = (DE+); SC = B; wait for SC bit 7 to clear; (HL+) = SB;
+///         repeat 4 times, then JR -2
+const PROGRAM: &[u8] = &[
+    0x3E, 0x10, 0xE0, 0x00, // LD A,0x10; LDH (P1),A
+    0xF0, 0x00, // LDH A,(P1)
+    0x21, 0x00, 0xC0, // LD HL,0xC000
+    0x11, 0x00, 0x02, // LD DE,0x0200
+    0x06, 0x81, // LD B,0x81
+    0xCB, 0x47, // BIT 0,A
+    0x28, 0x05, // JR Z,+5
+    0x06, 0x80, // LD B,0x80
+    0x11, 0x10, 0x02, // LD DE,0x0210
+    0x0E, 0x04, // LD C,4
+    0x1A, 0x13, 0xE0, 0x01, // loop: LD A,(DE); INC DE; LDH (SB),A
+    0x78, 0xE0, 0x02, // LD A,B; LDH (SC),A
+    0xF0, 0x02, 0xCB, 0x7F, 0x20, 0xFA, // wait: LDH A,(SC); BIT 7,A; JR NZ,wait
+    0xF0, 0x01, 0x22, // LDH A,(SB); LD (HL+),A
+    0x0D, 0x20, 0xED, // DEC C; JR NZ,loop
+    0x18, 0xFE, // JR -2
+];
+
+fn link_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP 0x0150
+    rom[0x0150..0x0150 + PROGRAM.len()].copy_from_slice(PROGRAM);
+    rom[0x0200..0x0204].copy_from_slice(&CLOCK_BYTES);
+    rom[0x0210..0x0214].copy_from_slice(&EXTERNAL_BYTES);
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+fn cpu() -> Sm83 {
+    let memory = Box::new(GameBoyMemory::with_rom(link_rom()).expect("ROM should load"));
+    Sm83::new(memory, Box::new(OpCodeDecoder::new())).with_registers(Registers {
+        pc: 0x0100,
+        sp: 0xFFFE,
+        ..Default::default()
+    })
+}
+
+fn received(cpu: &Sm83) -> Vec<u8> {
+    (0xC000..0xC004).map(|addr| cpu.read_memory(addr).unwrap()).collect()
+}
+
+#[test]
+fn test_linked_instances_exchange_bytes() {
+    let (a, b) = InProcessCable::pair();
+    let mut clock = cpu();
+    let mut external = cpu();
+    clock.set_button(Button::A, true);
+    clock.set_link_cable(Some(Box::new(a)));
+    external.set_link_cable(Some(Box::new(b)));
+
+    clock.run_linked(&mut external, CYCLES_PER_FRAME).unwrap();
+
+    assert_eq!(received(&clock), EXTERNAL_BYTES);
+    assert_eq!(received(&external), CLOCK_BYTES);
+    assert_eq!(clock.serial_output(), CLOCK_BYTES);
+    assert_eq!(external.serial_output(), EXTERNAL_BYTES);
+}
+
+#[test]
+fn test_unlinked_clock_side_reads_disconnected() {
+    let mut clock = cpu();
+    clock.set_button(Button::A, true);
+    clock.run_cycles(CYCLES_PER_FRAME).unwrap();
+    assert_eq!(received(&clock), [0xFF; 4]);
+}
+
+#[test]
+fn test_unlinked_external_side_waits_forever() {
+    let mut external = cpu();
+    external.run_cycles(CYCLES_PER_FRAME).unwrap();
+    assert_eq!(received(&external), [0x00; 4]);
+    assert_eq!(external.read_memory(0xFF02).unwrap() & 0x80, 0x80);
+}
@@ -0,0 +1,177 @@
+//! Integration tests using the mealybug-tearoom mode 3 ROMs, drawn with the
+//! pixel FIFO renderer.
+//!
+//! Each ROM changes a PPU register partway through mode 3 and leaves the
+//! result on screen. The frame is compared against the DMG screenshot from
+//! the suite's `expected/DMG-blob` directory. The ROMs are expected under
+//! `roms/mealybug`, which is not vendored yet.
+
+mod common;
+
+use common::{load_reference_png, run_rom_frames_with};
+use rustyboy_core::cpu::peripheral::ppu::PpuRenderer;
+
+/// The ROMs draw their scene within a few frames and then halt.
+const FRAMES: u32 = 60;
+
+fn assert_mealybug_matches(name: &str) {
+    let framebuffer =
+        run_rom_frames_with(&format!("roms/mealybug/{name}.gb"), FRAMES, PpuRenderer::Fifo);
+    let reference = load_reference_png(&format!("roms/mealybug/expected/DMG-blob/{name}.png"));
+    assert_eq!(framebuffer.len(), reference.len(), "{name}: framebuffer size mismatch");
+
+    let mut mismatches = 0;
+    for (i, (got, expected)) in framebuffer.iter().zip(reference.iter()).enumerate() {
+        if got != expected {
+            mismatches += 1;
+            if mismatches <= 10 {
+                eprintln!(
+                    "{name}: pixel ({},{}) mismatch: got shade {} expected shade {}",
+                    i % 160,
+                    i / 160,
+                    got,
+                    expected
+                );
+            }
+        }
+    }
+    assert_eq!(mismatches, 0, "{name}: {mismatches} pixel mismatches");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_bgp_change() {
+    assert_mealybug_matches("m3_bgp_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_bgp_change_sprites() {
+    assert_mealybug_matches("m3_bgp_change_sprites");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_bg_en_change() {
+    assert_mealybug_matches("m3_lcdc_bg_en_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_bg_map_change() {
+    assert_mealybug_matches("m3_lcdc_bg_map_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_obj_en_change() {
+    assert_mealybug_matches("m3_lcdc_obj_en_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_obj_en_change_variant() {
+    assert_mealybug_matches("m3_lcdc_obj_en_change_variant");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_obj_size_change() {
+    assert_mealybug_matches("m3_lcdc_obj_size_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_obj_size_change_scx() {
+    assert_mealybug_matches("m3_lcdc_obj_size_change_scx");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_tile_sel_change() {
+    assert_mealybug_matches("m3_lcdc_tile_sel_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_tile_sel_win_change() {
+    assert_mealybug_matches("m3_lcdc_tile_sel_win_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_win_en_change_multiple() {
+    assert_mealybug_matches("m3_lcdc_win_en_change_multiple");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_win_en_change_multiple_wx() {
+    assert_mealybug_matches("m3_lcdc_win_en_change_multiple_wx");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_lcdc_win_map_change() {
+    assert_mealybug_matches("m3_lcdc_win_map_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_obp0_change() {
+    assert_mealybug_matches("m3_obp0_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_scx_high_5_bits() {
+    assert_mealybug_matches("m3_scx_high_5_bits");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_scx_low_3_bits() {
+    assert_mealybug_matches("m3_scx_low_3_bits");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_scy_change() {
+    assert_mealybug_matches("m3_scy_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_window_timing() {
+    assert_mealybug_matches("m3_window_timing");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_window_timing_wx_0() {
+    assert_mealybug_matches("m3_window_timing_wx_0");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_wx_4_change() {
+    assert_mealybug_matches("m3_wx_4_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_wx_4_change_sprites() {
+    assert_mealybug_matches("m3_wx_4_change_sprites");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_wx_5_change() {
+    assert_mealybug_matches("m3_wx_5_change");
+}
+
+#[test]
+#[ignore = "requires the mealybug-tearoom ROMs, which are not vendored yet"]
+fn test_mealybug_m3_wx_6_change() {
+    assert_mealybug_matches("m3_wx_6_change");
+}
@@ -0,0 +1,327 @@
+//! Mooneye-style MBC2 tests.
+//!
+//! Mirrors the `bits_*`, `rom_*` and `ram` cases from Mooneye's emulator-only
+//! MBC2 suite. Each ROM is assembled in memory and reports its result through
+//! the Mooneye Fibonacci register protocol. The real suite runs from
+//! `roms/mooneye/emulator-only/mbc2` once its ROMs are vendored there.
+
+mod common;
+
+use common::{
+    assert_mooneye_passed, assert_mooneye_rom_data_passed, mooneye_rom_template,
+    MOONEYE_FAIL_ADDR, MOONEYE_MAIN_ADDR, MOONEYE_PASS_ADDR,
+};
+
+/// An MBC2+BATTERY ROM of `2 << rom_size_code` banks, each holding its own
+/// number at its first byte, with `main` at the program address.
+fn mbc2_rom(rom_size_code: u8, main: &[u8]) -> Vec<u8> {
+    let mut rom = mooneye_rom_template(0x06, rom_size_code);
+    let banks = 2usize << rom_size_code;
+    for bank in 1..banks {
+        rom[bank * 0x4000] = bank as u8;
+    }
+    let main_addr = MOONEYE_MAIN_ADDR as usize;
+    rom[main_addr..main_addr + main.len()].copy_from_slice(main);
+    rom
+}
+
+/// Address of the next instruction appended to `main`.
+fn here(main: &[u8]) -> [u8; 2] {
+    (MOONEYE_MAIN_ADDR + main.len() as u16).to_le_bytes()
+}
+
+/// Write `(L & 0xF0) | 0x0A` to every address in 0x0000–0x3FFF. Only those
+/// with address bit 8 clear may enable RAM, whatever the data's upper
+/// nibble; a lower nibble other than 0x0A leaves it disabled.
+fn make_mbc2_bits_ramg_rom() -> Vec<u8> {
+    let [fail_lo, fail_hi] = MOONEYE_FAIL_ADDR.to_le_bytes();
+    let [pass_lo, pass_hi] = MOONEYE_PASS_ADDR.to_le_bytes();
+    let mut main = vec![
+        0x21, 0x00, 0x00, // LD HL, 0x0000
+    ];
+    let [loop_lo, loop_hi] = here(&main);
+    main.extend_from_slice(&[
+        // loop:
+        0x7D, 0xE6, 0xF0, 0xF6, 0x0A, // LD A, L; AND 0xF0; OR 0x0A
+        0x77, //             LD (HL), A
+        0x3E, 0x05, //       LD A, 5
+        0xEA, 0x00, 0xA0, // LD (0xA000), A
+        0xFA, 0x00, 0xA0, // LD A, (0xA000)
+        0x47, //             LD B, A
+        0xCB, 0x44, //       BIT 0, H           ; address bit 8
+        0x3E, 0xF5, //       LD A, 0xF5         ; enabled: nibble + 1s
+        0x28, 0x02, //       JR Z, +2
+        0x3E, 0xFF, //       LD A, 0xFF         ; disabled: open bus
+        0xB8, //             CP B
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0xAF, //             XOR A
+        0xEA, 0x00, 0x00, // LD (0x0000), A     ; RAMG: disable
+        0x3C, //             INC A
+        0xEA, 0x00, 0x01, // LD (0x0100), A     ; ROMB: back to 1
+        0x23, //             INC HL
+        0x7C, //             LD A, H
+        0xFE, 0x40, //       CP 0x40
+        0xC2, loop_lo, loop_hi, // JP NZ, loop
+        0x3E, 0x0B, //       LD A, 0x0B
+        0xEA, 0x00, 0x00, // LD (0x0000), A
+        0xFA, 0x00, 0xA0, // LD A, (0xA000)
+        0xFE, 0xFF, //       CP 0xFF
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0xC3, pass_lo, pass_hi, // JP pass
+    ]);
+    mbc2_rom(0x00, &main)
+}
+
+/// Write `(L & 0xF0) | 0x03` to every address in 0x0000–0x3FFF. Only those
+/// with address bit 8 set may switch to bank 3; the data's upper nibble is
+/// ignored.
+fn make_mbc2_bits_romb_rom() -> Vec<u8> {
+    let [fail_lo, fail_hi] = MOONEYE_FAIL_ADDR.to_le_bytes();
+    let [pass_lo, pass_hi] = MOONEYE_PASS_ADDR.to_le_bytes();
+    let mut main = vec![
+        0x21, 0x00, 0x00, // LD HL, 0x0000
+    ];
+    let [loop_lo, loop_hi] = here(&main);
+    main.extend_from_slice(&[
+        // loop:
+        0x7D, 0xE6, 0xF0, 0xF6, 0x03, // LD A, L; AND 0xF0; OR 0x03
+        0x77, //             LD (HL), A
+        0xFA, 0x00, 0x40, // LD A, (0x4000)
+        0x47, //             LD B, A
+        0xCB, 0x44, //       BIT 0, H           ; address bit 8
+        0x3E, 0x01, //       LD A, 1            ; RAMG write: still bank 1
+        0x28, 0x02, //       JR Z, +2
+        0x3E, 0x03, //       LD A, 3            ; ROMB write: bank 3
+        0xB8, //             CP B
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0x3E, 0x01, //       LD A, 1
+        0xEA, 0x00, 0x01, // LD (0x0100), A     ; ROMB: back to 1
+        0x23, //             INC HL
+        0x7C, //             LD A, H
+        0xFE, 0x40, //       CP 0x40
+        0xC2, loop_lo, loop_hi, // JP NZ, loop
+        0xC3, pass_lo, pass_hi, // JP pass
+    ]);
+    mbc2_rom(0x03, &main)
+}
+
+/// Writes to 0x4000–0x7FFF reach neither register, and the RAM's four
+/// missing data bits read back as 1s for every value written.
+fn make_mbc2_bits_unused_rom() -> Vec<u8> {
+    let [fail_lo, fail_hi] = MOONEYE_FAIL_ADDR.to_le_bytes();
+    let [pass_lo, pass_hi] = MOONEYE_PASS_ADDR.to_le_bytes();
+    let mut main = vec![
+        0x21, 0x00, 0x40, // LD HL, 0x4000
+    ];
+    let [rom_lo, rom_hi] = here(&main);
+    main.extend_from_slice(&[
+        // rom_writes:
+        0x3E, 0x0A, //       LD A, 0x0A
+        0x77, //             LD (HL), A         ; would enable RAM
+        0x3E, 0x03, //       LD A, 3
+        0x77, //             LD (HL), A         ; would select bank 3
+        0xFA, 0x00, 0xA0, // LD A, (0xA000)
+        0xFE, 0xFF, //       CP 0xFF
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0xFA, 0x00, 0x40, // LD A, (0x4000)
+        0xFE, 0x01, //       CP 1
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0x23, //             INC HL
+        0x7C, //             LD A, H
+        0xFE, 0x80, //       CP 0x80
+        0xC2, rom_lo, rom_hi, // JP NZ, rom_writes
+        0x3E, 0x0A, //       LD A, 0x0A
+        0xEA, 0x00, 0x00, // LD (0x0000), A     ; RAMG: enable
+        0x0E, 0x00, //       LD C, 0
+    ]);
+    let [ram_lo, ram_hi] = here(&main);
+    main.extend_from_slice(&[
+        // ram_values:
+        0x79, //             LD A, C
+        0xEA, 0x00, 0xA0, // LD (0xA000), A
+        0xFA, 0x00, 0xA0, // LD A, (0xA000)
+        0x47, //             LD B, A
+        0x79, //             LD A, C
+        0xF6, 0xF0, //       OR 0xF0
+        0xB8, //             CP B
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0x0C, //             INC C
+        0xC2, ram_lo, ram_hi, // JP NZ, ram_values
+        0xC3, pass_lo, pass_hi, // JP pass
+    ]);
+    mbc2_rom(0x03, &main)
+}
+
+/// Build an MBC2 ROM of `2 << rom_size_code` banks whose program selects
+/// every bank through the bit-8-set register at 0x2100, then checks the 0→1
+/// alias and that a bit-8-clear write leaves the bank alone.
+fn make_mbc2_bank_test_rom(rom_size_code: u8) -> Vec<u8> {
+    let banks = 2usize << rom_size_code;
+    let [pass_lo, pass_hi] = MOONEYE_PASS_ADDR.to_le_bytes();
+    let [fail_lo, fail_hi] = MOONEYE_FAIL_ADDR.to_le_bytes();
+    let [loop_lo, loop_hi] = (MOONEYE_MAIN_ADDR + 2).to_le_bytes();
+    let main = [
+        0x0E, 0x01, //       LD C, 1            ; C = bank under test
+        // loop:
+        0x79, //             LD A, C
+        0xEA, 0x00, 0x21, // LD (0x2100), A     ; ROMB (address bit 8 set)
+        0xFA, 0x00, 0x40, // LD A, (0x4000)
+        0xB9, //             CP C
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0x0C, //             INC C
+        0x79, //             LD A, C
+        0xFE, banks as u8, // CP banks
+        0xC2, loop_lo, loop_hi, // JP NZ, loop
+        // Bank 0 aliases to bank 1
+        0xAF, //             XOR A
+        0xEA, 0x00, 0x21, // LD (0x2100), A
+        0xFA, 0x00, 0x40, // LD A, (0x4000)
+        0xFE, 0x01, //       CP 1
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        // Address bit 8 clear hits RAMG, not ROMB
+        0x3E, 0x02, //       LD A, 2
+        0xEA, 0x00, 0x20, // LD (0x2000), A
+        0xFA, 0x00, 0x40, // LD A, (0x4000)
+        0xFE, 0x01, //       CP 1
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0xC3, pass_lo, pass_hi, // JP pass
+    ];
+    mbc2_rom(rom_size_code, &main)
+}
+
+/// Build an MBC2 ROM whose program fills all 512 RAM nibbles, reads them
+/// back through the 0xBE00 mirror expecting the upper nibble as 1s, and
+/// checks that disabled RAM reads 0xFF.
+fn make_mbc2_ram_test_rom() -> Vec<u8> {
+    let [pass_lo, pass_hi] = MOONEYE_PASS_ADDR.to_le_bytes();
+    let [fail_lo, fail_hi] = MOONEYE_FAIL_ADDR.to_le_bytes();
+    let main = [
+        0x3E, 0x0A, //       LD A, 0x0A
+        0xEA, 0x00, 0x00, // LD (0x0000), A     ; RAMG: enable
+        0x21, 0x00, 0xA0, // LD HL, 0xA000
+        // fill:
+        0x7D, //             LD A, L
+        0x22, //             LD (HL+), A
+        0x7C, //             LD A, H
+        0xFE, 0xA2, //       CP 0xA2
+        0x20, 0xF9, //       JR NZ, fill
+        0x21, 0x00, 0xBE, // LD HL, 0xBE00      ; last 512-byte mirror
+        // verify:
+        0x7D, //             LD A, L
+        0xE6, 0x0F, //       AND 0x0F
+        0xF6, 0xF0, //       OR 0xF0
+        0x47, //             LD B, A
+        0x2A, //             LD A, (HL+)
+        0xB8, //             CP B
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0x7C, //             LD A, H
+        0xFE, 0xC0, //       CP 0xC0
+        0x20, 0xF0, //       JR NZ, verify
+        // Disabled RAM reads open bus
+        0xAF, //             XOR A
+        0xEA, 0x00, 0x00, // LD (0x0000), A     ; RAMG: disable
+        0xFA, 0x00, 0xA0, // LD A, (0xA000)
+        0xFE, 0xFF, //       CP 0xFF
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0xC3, pass_lo, pass_hi, // JP pass
+    ];
+    mbc2_rom(0x00, &main)
+}
+
+#[test]
+fn test_mooneye_mbc2_bits_ramg() {
+    assert_mooneye_rom_data_passed(make_mbc2_bits_ramg_rom(), "mbc2/bits_ramg");
+}
+
+#[test]
+fn test_mooneye_mbc2_bits_romb() {
+    assert_mooneye_rom_data_passed(make_mbc2_bits_romb_rom(), "mbc2/bits_romb");
+}
+
+#[test]
+fn test_mooneye_mbc2_bits_unused() {
+    assert_mooneye_rom_data_passed(make_mbc2_bits_unused_rom(), "mbc2/bits_unused");
+}
+
+#[test]
+fn test_mooneye_mbc2_ram() {
+    assert_mooneye_rom_data_passed(make_mbc2_ram_test_rom(), "mbc2/ram");
+}
+
+#[test]
+fn test_mooneye_mbc2_rom_512kb() {
+    assert_mooneye_rom_data_passed(make_mbc2_bank_test_rom(0x01), "mbc2/rom_512kb");
+}
+
+#[test]
+fn test_mooneye_mbc2_rom_1mb() {
+    assert_mooneye_rom_data_passed(make_mbc2_bank_test_rom(0x02), "mbc2/rom_1Mb");
+}
+
+#[test]
+fn test_mooneye_mbc2_rom_2mb() {
+    assert_mooneye_rom_data_passed(make_mbc2_bank_test_rom(0x03), "mbc2/rom_2Mb");
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_bits_ramg_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/bits_ramg.gb",
+        "mbc2/bits_ramg",
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_bits_romb_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/bits_romb.gb",
+        "mbc2/bits_romb",
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_bits_unused_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/bits_unused.gb",
+        "mbc2/bits_unused",
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_ram_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/ram.gb",
+        "mbc2/ram",
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_rom_512kb_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/rom_512kb.gb",
+        "mbc2/rom_512kb",
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_rom_1mb_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/rom_1Mb.gb",
+        "mbc2/rom_1Mb",
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's mbc2 ROMs, which are not vendored yet"]
+fn test_mooneye_mbc2_rom_2mb_rom() {
+    assert_mooneye_passed(
+        "roms/mooneye/emulator-only/mbc2/rom_2Mb.gb",
+        "mbc2/rom_2Mb",
+    );
+}
@@ -0,0 +1,96 @@
+//! Mooneye-style MBC5 ROM banking tests.
+//!
+//! Mirrors the `rom_*` cases from Mooneye's emulator-only MBC5 suite. Each
+//! ROM is assembled in memory: every bank stores its own 16-bit bank number
+//! in its first two bytes, and a small SM83 program walks the full 9-bit
+//! bank register, reading the marker back through the 0x4000–0x7FFF window.
+//! Pass/fail uses the Mooneye Fibonacci register protocol.
+
+mod common;
+
+use common::{
+    assert_mooneye_rom_data_passed, mooneye_rom_template, MOONEYE_FAIL_ADDR, MOONEYE_MAIN_ADDR,
+    MOONEYE_PASS_ADDR,
+};
+
+/// Build an MBC5 ROM of `2 << rom_size_code` banks whose program checks that
+/// every bank number 0..N maps the matching bank into the switchable window.
+fn make_mbc5_bank_test_rom(rom_size_code: u8) -> Vec<u8> {
+    let mut rom = mooneye_rom_template(0x19, rom_size_code); // MBC5
+    let banks = 2usize << rom_size_code;
+    for bank in 1..banks {
+        let base = bank * 0x4000;
+        rom[base..base + 2].copy_from_slice(&(bank as u16).to_le_bytes());
+    }
+
+    let [pass_lo, pass_hi] = MOONEYE_PASS_ADDR.to_le_bytes();
+    let [fail_lo, fail_hi] = MOONEYE_FAIL_ADDR.to_le_bytes();
+    let [loop_lo, loop_hi] = (MOONEYE_MAIN_ADDR + 6).to_le_bytes();
+    let [end_lo, end_hi] = (banks as u16).to_le_bytes();
+    let main = [
+        0x31, 0xFE, 0xFF, // LD SP, 0xFFFE
+        0x01, 0x00, 0x00, // LD BC, 0x0000      ; BC = bank under test
+        // loop:
+        0x79, //             LD A, C
+        0xEA, 0x00, 0x20, // LD (0x2000), A     ; ROMB0
+        0x78, //             LD A, B
+        0xEA, 0x00, 0x30, // LD (0x3000), A     ; ROMB1
+        0xFA, 0x00, 0x40, // LD A, (0x4000)
+        0xB9, //             CP C
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0xFA, 0x01, 0x40, // LD A, (0x4001)
+        0xB8, //             CP B
+        0xC2, fail_lo, fail_hi, // JP NZ, fail
+        0x03, //             INC BC
+        0x78, //             LD A, B
+        0xFE, end_hi, //     CP hi(banks)
+        0xC2, loop_lo, loop_hi, // JP NZ, loop
+        0x79, //             LD A, C
+        0xFE, end_lo, //     CP lo(banks)
+        0xC2, loop_lo, loop_hi, // JP NZ, loop
+        0xC3, pass_lo, pass_hi, // JP pass
+    ];
+    let main_addr = MOONEYE_MAIN_ADDR as usize;
+    rom[main_addr..main_addr + main.len()].copy_from_slice(&main);
+    rom
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_512kb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x01), "mbc5/rom_512kb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_1mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x02), "mbc5/rom_1Mb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_2mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x03), "mbc5/rom_2Mb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_4mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x04), "mbc5/rom_4Mb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_8mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x05), "mbc5/rom_8Mb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_16mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x06), "mbc5/rom_16Mb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_32mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x07), "mbc5/rom_32Mb");
+}
+
+#[test]
+fn test_mooneye_mbc5_rom_64mb() {
+    assert_mooneye_rom_data_passed(make_mbc5_bank_test_rom(0x08), "mbc5/rom_64Mb");
+}
@@ -0,0 +1,131 @@
+//! Integration tests using Mooneye's PPU acceptance ROMs, drawn with the
+//! pixel FIFO renderer.
+//!
+//! The ROMs time STAT modes, STAT interrupts and LY against the CPU and
+//! report through the Fibonacci register protocol. They are expected under
+//! `roms/mooneye/acceptance/ppu`, which is not vendored yet.
+
+mod common;
+
+use common::assert_mooneye_passed_with;
+use rustyboy_core::cpu::peripheral::ppu::PpuRenderer;
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_hblank_ly_scx_timing_gs() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/hblank_ly_scx_timing-GS.gb",
+        "ppu/hblank_ly_scx_timing-GS",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_intr_1_2_timing_gs() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/intr_1_2_timing-GS.gb",
+        "ppu/intr_1_2_timing-GS",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_intr_2_0_timing() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/intr_2_0_timing.gb",
+        "ppu/intr_2_0_timing",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_intr_2_mode0_timing() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/intr_2_mode0_timing.gb",
+        "ppu/intr_2_mode0_timing",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_intr_2_mode0_timing_sprites() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/intr_2_mode0_timing_sprites.gb",
+        "ppu/intr_2_mode0_timing_sprites",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_intr_2_mode3_timing() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/intr_2_mode3_timing.gb",
+        "ppu/intr_2_mode3_timing",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_intr_2_oam_ok_timing() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/intr_2_oam_ok_timing.gb",
+        "ppu/intr_2_oam_ok_timing",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_lcdon_timing_gs() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/lcdon_timing-GS.gb",
+        "ppu/lcdon_timing-GS",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_lcdon_write_timing_gs() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/lcdon_write_timing-GS.gb",
+        "ppu/lcdon_write_timing-GS",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_stat_irq_blocking() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/stat_irq_blocking.gb",
+        "ppu/stat_irq_blocking",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_stat_lyc_onoff() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/stat_lyc_onoff.gb",
+        "ppu/stat_lyc_onoff",
+        PpuRenderer::Fifo,
+    );
+}
+
+#[test]
+#[ignore = "requires Mooneye's PPU acceptance ROMs, which are not vendored yet"]
+fn test_mooneye_ppu_vblank_stat_intr_gs() {
+    assert_mooneye_passed_with(
+        "roms/mooneye/acceptance/ppu/vblank_stat_intr-GS.gb",
+        "ppu/vblank_stat_intr-GS",
+        PpuRenderer::Fifo,
+    );
+}
@@ -0,0 +1,107 @@
+//! Recording input movies and replaying them frame for frame.
+
+mod common;
+
+use common::{assert_movie_framebuffer_hash, framebuffer_hash};
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::movie::{Movie, MovieError, MovieStart};
+use rustyboy_core::cpu::peripheral::joypad::Button;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+
+/// Once per frame at LY 144: read all eight buttons into A (pressed = 1,
+/// Right in bit 0), write it to BGP so the blank screen's shade follows
+/// Right/Left, and add it to a running sum at 0xC000.
+const PROGRAM: &[u8] = &[
+    0xF0, 0x44, 0xFE, 0x90, 0x20, 0xFA, // loop: LDH A,(LY); CP 144; JR NZ,loop
+    0x3E, 0x20, 0xE0, 0x00, 0xF0, 0x00, 0xF0, 0x00, // select d-pad; read P1 twice
+    0xE6, 0x0F, 0x47, // AND 0x0F; LD B,A
+    0x3E, 0x10, 0xE0, 0x00, 0xF0, 0x00, 0xF0, 0x00, // select buttons; read P1 twice
+    0xE6, 0x0F, 0xCB, 0x37, 0xB0, 0x2F, // AND 0x0F; SWAP A; OR B; CPL
+    0xE0, 0x47, // LDH (BGP),A
+    0x21, 0x00, 0xC0, 0x86, 0x77, // LD HL,0xC000; ADD A,(HL); LD (HL),A
+    0xF0, 0x44, 0xFE, 0x90, 0x28, 0xFA, // wait: LDH A,(LY); CP 144; JR Z,wait
+    0x18, 0xD2, // JR loop
+];
+
+fn input_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP 0x0150
+    rom[0x0150..0x0150 + PROGRAM.len()].copy_from_slice(PROGRAM);
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+fn power_on(rom: &[u8]) -> Sm83 {
+    Sm83::from_rom(rom.to_vec(), Box::new(OpCodeDecoder::new()))
+        .unwrap()
+        .with_post_boot_state()
+}
+
+/// Press and release buttons through `set_button` the way a frontend does,
+/// changing something every few frames.
+fn scripted_input(cpu: &mut Sm83, frame: usize) {
+    let button = Button::ALL[(frame / 3) % 8];
+    cpu.set_button(button, frame % 3 != 2);
+}
+
+fn record(cpu: &mut Sm83, movie: &mut Movie, frames: usize) {
+    for frame in 0..frames {
+        scripted_input(cpu, frame);
+        movie.record_frame(cpu).unwrap();
+    }
+}
+
+#[test]
+fn test_power_on_movie_replays_exact_framebuffer() {
+    let rom = input_rom();
+    let mut cpu = power_on(&rom);
+    let mut movie = Movie::power_on(&rom);
+    record(&mut cpu, &mut movie, 120);
+    assert_ne!(cpu.read_memory(0xC000).unwrap(), 0, "input should reach the ROM");
+
+    assert_movie_framebuffer_hash(rom, &movie.to_bytes(), framebuffer_hash(&cpu));
+}
+
+#[test]
+fn test_save_state_movie_replays_exact_memory() {
+    let rom = input_rom();
+    let mut cpu = power_on(&rom);
+    for _ in 0..30 {
+        cpu.run_frame_with_input(Button::A.mask()).unwrap();
+    }
+    let mut movie = Movie::from_state(&rom, &cpu);
+    record(&mut cpu, &mut movie, 90);
+
+    let movie = Movie::from_bytes(&movie.to_bytes()).unwrap();
+    assert!(matches!(movie.start, MovieStart::SaveState(_)));
+    let mut replay = movie.start(rom, Box::new(OpCodeDecoder::new())).unwrap();
+    movie.play(&mut replay).unwrap();
+    assert_eq!(replay.read_memory(0xC000).unwrap(), cpu.read_memory(0xC000).unwrap());
+    assert_eq!(replay.registers().pc, cpu.registers().pc);
+    assert_eq!(framebuffer_hash(&replay), framebuffer_hash(&cpu));
+}
+
+#[test]
+fn test_different_input_changes_the_result() {
+    let rom = input_rom();
+    let mut cpu = power_on(&rom);
+    let mut movie = Movie::power_on(&rom);
+    record(&mut cpu, &mut movie, 60);
+
+    let mut altered = movie.clone();
+    altered.frames[10] ^= Button::Right.mask();
+    let mut replay = altered.start(rom, Box::new(OpCodeDecoder::new())).unwrap();
+    altered.play(&mut replay).unwrap();
+    assert_ne!(replay.read_memory(0xC000).unwrap(), cpu.read_memory(0xC000).unwrap());
+}
+
+#[test]
+fn test_movie_refuses_other_rom() {
+    let rom = input_rom();
+    let movie = Movie::power_on(&rom);
+    let mut other = rom.clone();
+    other[0x0200] = 0x01;
+    let err = movie.start(other, Box::new(OpCodeDecoder::new())).err().unwrap();
+    assert!(matches!(err, MovieError::RomMismatch { .. }), "{err:?}");
+}
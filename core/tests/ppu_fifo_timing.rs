@@ -0,0 +1,152 @@
+//! Mode 3 timing of the pixel FIFO renderer, measured through the CPU.
+//!
+//! Each test builds a ROM in memory that sets up a scene with the LCD off,
+//! switches it on and then runs a long NOP sled. The harness samples STAT
+//! after every NOP, one sample per M-cycle, and counts the samples of line 2
+//! that read mode 3. A mode 3 of `n` dots spans `n / 4` samples, rounded up
+//! or down by where the line starts within an M-cycle; lengths that are a
+//! multiple of 4 are exact.
+
+mod common;
+
+use common::{mooneye_cpu, mooneye_rom_template, MOONEYE_MAIN_ADDR};
+use rustyboy_core::cpu::cpu::Cpu;
+use rustyboy_core::cpu::peripheral::ppu::PpuRenderer;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+
+const LCDC: u16 = 0xFF40;
+const STAT: u16 = 0xFF41;
+const LY: u16 = 0xFF44;
+
+/// LCD on, BG tile data at 0x8000, objects and BG on.
+const LCDC_BG_OBJ: u8 = 0x93;
+const LCDC_WINDOW: u8 = 0x20;
+
+struct Scene {
+    lcdc: u8,
+    scx: u8,
+    wx: u8,
+    /// OAM entries as `(y, x)`; all use tile 0 with no attributes.
+    objects: Vec<(u8, u8)>,
+}
+
+impl Scene {
+    fn new() -> Self {
+        Scene { lcdc: LCDC_BG_OBJ, scx: 0, wx: 0xFF, objects: Vec::new() }
+    }
+
+    /// Set up the registers and OAM with the LCD off, then switch it on.
+    /// Everything after the program is zero, so it falls into a NOP sled.
+    fn program(&self) -> Vec<u8> {
+        let mut code = vec![
+            0xAF, 0xE0, 0x40, // XOR A; LDH (LCDC),A
+            0x3E, self.scx, 0xE0, 0x43, // LD A,scx; LDH (SCX),A
+            0xAF, 0xE0, 0x4A, // XOR A; LDH (WY),A
+            0x3E, self.wx, 0xE0, 0x4B, // LD A,wx; LDH (WX),A
+            0x21, 0x00, 0xFE, // LD HL,0xFE00
+        ];
+        for &(y, x) in &self.objects {
+            for byte in [y, x, 0, 0] {
+                code.extend_from_slice(&[0x3E, byte, 0x22]); // LD A,byte; LD (HL+),A
+            }
+        }
+        code.extend_from_slice(&[0x3E, self.lcdc, 0xE0, 0x40]); // LD A,lcdc; LDH (LCDC),A
+        code
+    }
+
+    /// M-cycles of line 2 in which the CPU reads mode 3 from STAT.
+    fn mode3_cycles(&self) -> u32 {
+        let mut rom = mooneye_rom_template(0x00, 0x00);
+        let main = MOONEYE_MAIN_ADDR as usize;
+        let program = self.program();
+        rom[main..main + program.len()].copy_from_slice(&program);
+        rom[0x014D] = header_checksum(&rom);
+
+        let mut cpu = mooneye_cpu(rom, PpuRenderer::Fifo);
+
+        let read = |cpu: &Sm83, address| cpu.read_memory(address).unwrap();
+        // Skip the program and the short first line after LCD on.
+        while cpu.registers().pc < MOONEYE_MAIN_ADDR + program.len() as u16
+            || read(&cpu, LCDC) & 0x80 == 0
+            || read(&cpu, LY) != 2
+        {
+            cpu.tick().unwrap();
+        }
+        let mut cycles = 0;
+        while read(&cpu, LY) == 2 {
+            assert_eq!(cpu.tick().unwrap(), 4, "expected to be running NOPs");
+            if read(&cpu, LY) == 2 && read(&cpu, STAT) & 0x03 == 3 {
+                cycles += 1;
+            }
+        }
+        cycles
+    }
+}
+
+/// Samples a mode 3 of `dots` dots can span, depending on the line's phase.
+fn samples_for(dots: u32) -> [u32; 2] {
+    [dots / 4, dots.div_ceil(4)]
+}
+
+#[test]
+fn test_mode3_is_43_cycles_without_scroll_window_or_objects() {
+    assert_eq!(Scene::new().mode3_cycles(), 43);
+}
+
+#[test]
+fn test_scx_fine_scroll_lengthens_mode3() {
+    let lengths: Vec<u32> = (0..=8)
+        .map(|scx| Scene { scx, ..Scene::new() }.mode3_cycles())
+        .collect();
+
+    assert_eq!(lengths[0], 43);
+    assert_eq!(lengths[4], 44);
+    // Only SCX & 7 costs dots: a whole-tile scroll is free.
+    assert_eq!(lengths[8], 43);
+    for scx in 1..8 {
+        assert!(samples_for(172 + scx as u32).contains(&lengths[scx]), "SCX={}: {:?}", scx, lengths);
+    }
+    assert!(lengths.windows(2).take(7).all(|pair| pair[0] <= pair[1]), "{:?}", lengths);
+}
+
+#[test]
+fn test_window_lengthens_mode3_by_six() {
+    // WY is 0, so the window is enabled by line 0 even though the first line
+    // after LCD on has no mode 3.
+    let window = Scene { lcdc: LCDC_BG_OBJ | LCDC_WINDOW, wx: 87, ..Scene::new() };
+    assert!(samples_for(178).contains(&window.mode3_cycles()));
+
+    // 172 + 2 (SCX) + 6 (window) = 180 dots, exactly 45 M-cycles.
+    let scrolled = Scene { scx: 2, ..window };
+    assert_eq!(scrolled.mode3_cycles(), 45);
+
+    // A window enabled off the right edge is never fetched.
+    let offscreen = Scene { lcdc: LCDC_BG_OBJ | LCDC_WINDOW, wx: 0xFF, ..Scene::new() };
+    assert_eq!(offscreen.mode3_cycles(), 43);
+}
+
+#[test]
+fn test_object_penalty_through_the_cpu() {
+    // Objects on line 2 sit at OAM y 16..=18; x 8 is tile aligned.
+    let aligned = Scene { objects: vec![(16, 8)], ..Scene::new() };
+    assert!(samples_for(172 + 11).contains(&aligned.mode3_cycles()));
+
+    // An object 3 pixels into its tile costs 6 + (5 - 3) = 8 dots: 180 in
+    // all, exactly 45 M-cycles.
+    let unaligned = Scene { objects: vec![(16, 8 + 3)], ..Scene::new() };
+    assert_eq!(unaligned.mode3_cycles(), 45);
+
+    // A second object in an already-penalised tile only pays the fetch, and
+    // one 5 or more pixels in pays nothing beyond it: 172 + 8 + 6 + 6 = 192.
+    let three = Scene { objects: vec![(16, 8 + 3), (16, 8 + 3), (16, 8 + 13)], ..Scene::new() };
+    assert_eq!(three.mode3_cycles(), 48);
+
+    // Disabling objects drops the penalty.
+    let disabled = Scene { lcdc: LCDC_BG_OBJ & !0x02, ..aligned };
+    assert_eq!(disabled.mode3_cycles(), 43);
+
+    // Objects on other lines cost nothing.
+    let elsewhere = Scene { objects: vec![(40, 8)], ..Scene::new() };
+    assert_eq!(elsewhere.mode3_cycles(), 43);
+}
@@ -0,0 +1,142 @@
+//! A ROM sends Game Boy Printer packets over the serial port to an emulated
+//! printer; the printed page is written as a PNG for inspection.
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rustyboy_core::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+use rustyboy_core::cpu::peripheral::serial::{GameBoyPrinter, PRINTER_WIDTH};
+use rustyboy_core::cpu::registers::Registers;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::memory::header::header_checksum;
+use rustyboy_core::memory::memory::GameBoyMemory;
+
+const PACKETS_ADDR: usize = 0x0200;
+const REPLIES_ADDR: u16 = 0xC000;
+
+/// 0x0150: send BC bytes from 0x0200 with the internal clock, storing each
+///         reply from 0xC000 on; then JR -2
+const PROGRAM: &[u8] = &[
+    0x21, 0x00, 0x02, // LD HL,0x0200
+    0x11, 0x00, 0xC0, // LD DE,0xC000
+    0x01, 0x00, 0x00, // LD BC,<len>
+    0x2A, 0xE0, 0x01, // loop: LD A,(HL+); LDH (SB),A
+    0x3E, 0x81, 0xE0, 0x02, // LD A,0x81; LDH (SC),A
+    0xF0, 0x02, 0xCB, 0x7F, 0x20, 0xFA, // wait: LDH A,(SC); BIT 7,A; JR NZ,wait
+    0xF0, 0x01, 0x12, 0x13, // LDH A,(SB); LD (DE),A; INC DE
+    0x0B, 0x78, 0xB1, 0x20, 0xEA, // DEC BC; LD A,B; OR C; JR NZ,loop
+    0x18, 0xFE, // JR -2
+];
+
+fn packet(command: u8, data: &[u8]) -> Vec<u8> {
+    let len = data.len() as u16;
+    let mut body = vec![command, 0x00, len as u8, (len >> 8) as u8];
+    body.extend_from_slice(data);
+    let checksum = body.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+    let mut packet = vec![0x88, 0x33];
+    packet.extend(body);
+    packet.extend(checksum.to_le_bytes());
+    packet.extend([0x00, 0x00]);
+    packet
+}
+
+/// Two tile rows: 8-pixel stripes of all four colors, then alternating
+/// single-pixel columns of colors 1 and 2.
+fn image_data() -> Vec<u8> {
+    let stripes = (0..20).flat_map(|tile| {
+        let color = tile % 4;
+        let lo = if color & 1 != 0 { 0xFF } else { 0x00 };
+        let hi = if color & 2 != 0 { 0xFF } else { 0x00 };
+        [lo, hi].repeat(8)
+    });
+    let columns = (0..20).flat_map(|_| [0xAA, 0x55].repeat(8));
+    stripes.chain(columns).collect()
+}
+
+fn printer_rom(packets: &[u8]) -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP 0x0150
+    rom[0x0150..0x0150 + PROGRAM.len()].copy_from_slice(PROGRAM);
+    rom[0x0157..0x0159].copy_from_slice(&(packets.len() as u16).to_le_bytes());
+    rom[PACKETS_ADDR..PACKETS_ADDR + packets.len()].copy_from_slice(packets);
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+/// Run a ROM that sends `packets` to a printer for `frames` frames.
+fn run_printer_rom(packets: &[u8], frames: u64) -> (Sm83, Rc<RefCell<GameBoyPrinter>>) {
+    let memory = Box::new(GameBoyMemory::with_rom(printer_rom(packets)).expect("ROM should load"));
+    let mut cpu = Sm83::new(memory, Box::new(OpCodeDecoder::new())).with_registers(Registers {
+        pc: 0x0100,
+        sp: 0xFFFE,
+        ..Default::default()
+    });
+    let printer = Rc::new(RefCell::new(GameBoyPrinter::new()));
+    cpu.set_link_cable(Some(Box::new(printer.clone())));
+    cpu.run_cycles(frames * CYCLES_PER_FRAME).unwrap();
+    (cpu, printer)
+}
+
+#[test]
+fn test_rom_prints_page() {
+    let init = packet(0x01, &[]);
+    let data = packet(0x04, &image_data());
+    let end = packet(0x04, &[]);
+    let print = packet(0x02, &[0x01, 0x13, 0xE4, 0x40]);
+    let packets = [init.clone(), data.clone(), end.clone(), print.clone()].concat();
+
+    let (cpu, printer) = run_printer_rom(&packets, 20);
+
+    // Each packet is answered with 0x81 then the status after the command.
+    let reply = |offset: usize| cpu.read_memory(REPLIES_ADDR + offset as u16).unwrap();
+    let mut offset = 0;
+    for (packet, status) in [(&init, 0x00), (&data, 0x08), (&end, 0x08), (&print, 0x06)] {
+        offset += packet.len();
+        assert_eq!((reply(offset - 2), reply(offset - 1)), (0x81, status));
+    }
+
+    let printer = printer.borrow();
+    let page = &printer.pages()[0];
+    common::write_shades_png("printer_page.png", page.width, page.height, &page.pixels);
+    assert_eq!((page.width, page.height), (PRINTER_WIDTH, 16));
+    for x in 0..PRINTER_WIDTH {
+        assert_eq!(page.pixels[x], ((x / 8) % 4) as u8, "stripe at x={x}");
+        assert_eq!(page.pixels[8 * PRINTER_WIDTH + x], if x % 2 == 0 { 1 } else { 2 }, "column at x={x}");
+    }
+}
+
+#[test]
+fn test_rom_prints_full_screen() {
+    // A whole 160×144 screen, as games print photos: nine data packets of two
+    // tile rows each, then a single print command. Tile row r, tile t is
+    // solid color (r + t) % 4.
+    let tile_row = |row: usize| -> Vec<u8> {
+        (0..20).flat_map(|tile| {
+            let color = (row + tile) % 4;
+            let lo = if color & 1 != 0 { 0xFF } else { 0x00 };
+            let hi = if color & 2 != 0 { 0xFF } else { 0x00 };
+            [lo, hi].repeat(8)
+        }).collect()
+    };
+    let mut packets = packet(0x01, &[]);
+    for pair in 0..9 {
+        packets.extend(packet(0x04, &[tile_row(2 * pair), tile_row(2 * pair + 1)].concat()));
+    }
+    packets.extend(packet(0x04, &[]));
+    packets.extend(packet(0x02, &[0x01, 0x13, 0xE4, 0x40]));
+
+    let (_cpu, printer) = run_printer_rom(&packets, 400);
+
+    let printer = printer.borrow();
+    let page = &printer.pages()[0];
+    common::write_shades_png("printer_full_screen.png", page.width, page.height, &page.pixels);
+    assert_eq!((page.width, page.height), (PRINTER_WIDTH, 144));
+    for y in (0..144).step_by(8) {
+        for x in (0..PRINTER_WIDTH).step_by(8) {
+            assert_eq!(page.pixels[y * PRINTER_WIDTH + x], ((y / 8 + x / 8) % 4) as u8, "tile at x={x}, y={y}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Rewinding a running ROM with `RewindBuffer` and replaying forward.
+
+use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::registers::Registers;
+use rustyboy_core::cpu::rewind::RewindBuffer;
+use rustyboy_core::cpu::sm83::Sm83;
+use rustyboy_core::memory::header::header_checksum;
+use rustyboy_core::memory::memory::GameBoyMemory;
+
+const COUNTER: u16 = 0xC000;
+
+/// Counts in WRAM forever, spreading the count across a 256-byte page so
+/// each frame changes more than a single byte:
+/// `loop: INC (HL); INC L; JR loop` with HL = 0xC000.
+fn counter_rom() -> Vec<u8> {
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP 0x0150
+    rom[0x0150..0x0157].copy_from_slice(&[
+        0x21, 0x00, 0xC0, // LD HL,0xC000
+        0x34, // loop: INC (HL)
+        0x2C, // INC L
+        0x18, 0xFC, // JR loop
+    ]);
+    rom[0x014D] = header_checksum(&rom);
+    rom
+}
+
+fn cpu() -> Sm83 {
+    let memory = Box::new(GameBoyMemory::with_rom(counter_rom()).expect("ROM should load"));
+    Sm83::new(memory, Box::new(OpCodeDecoder::new())).with_registers(Registers {
+        pc: 0x0100,
+        sp: 0xFFFE,
+        ..Default::default()
+    })
+}
+
+/// Run `frames` frames, recording each, and return the counter page after
+/// every frame.
+fn run_recorded(cpu: &mut Sm83, rewind: &mut RewindBuffer, frames: usize) -> Vec<Vec<u8>> {
+    (0..frames)
+        .map(|_| {
+            cpu.run_frame().unwrap();
+            rewind.record(cpu);
+            (0..256).map(|i| cpu.read_memory(COUNTER + i).unwrap()).collect()
+        })
+        .collect()
+}
+
+fn page(cpu: &Sm83) -> Vec<u8> {
+    (0..256).map(|i| cpu.read_memory(COUNTER + i).unwrap()).collect()
+}
+
+#[test]
+fn test_rewind_restores_earlier_frame_and_replays_identically() {
+    let mut cpu = cpu();
+    let mut rewind = RewindBuffer::new(4 * 1024 * 1024);
+    let history = run_recorded(&mut cpu, &mut rewind, 60);
+
+    // Snapshots are taken at frames 0, 2, ..., 58, so the newest one at
+    // least 10 frames before frame 59 is frame 48.
+    assert_eq!(rewind.rewind(&mut cpu, 10).unwrap(), 11);
+    assert_eq!(page(&cpu), history[48]);
+
+    let replay = run_recorded(&mut cpu, &mut rewind, 11);
+    assert_eq!(replay, history[49..60]);
+}
+
+#[test]
+fn test_rewind_steps_back_repeatedly() {
+    let mut cpu = cpu();
+    let mut rewind = RewindBuffer::new(4 * 1024 * 1024);
+    let history = run_recorded(&mut cpu, &mut rewind, 40);
+
+    // The first step lands on the snapshot at frame 36, then each one goes
+    // back exactly one snapshot.
+    let mut frame = 39;
+    for _ in 0..5 {
+        frame -= rewind.rewind(&mut cpu, 2).unwrap() as usize;
+        assert_eq!(page(&cpu), history[frame]);
+    }
+    assert_eq!(frame, 28);
+}
+
+#[test]
+fn test_rewind_stops_at_oldest_snapshot() {
+    let mut cpu = cpu();
+    let mut rewind = RewindBuffer::new(4 * 1024 * 1024);
+    let history = run_recorded(&mut cpu, &mut rewind, 9);
+
+    // Snapshots at frames 0, 2, 4, 6, 8.
+    assert_eq!(rewind.frames_available(), 8);
+    assert_eq!(rewind.rewind(&mut cpu, 100).unwrap(), 8);
+    assert_eq!(page(&cpu), history[0]);
+    assert_eq!(rewind.len(), 1);
+}
+
+#[test]
+fn test_deltas_are_a_fraction_of_a_state() {
+    let mut cpu = cpu();
+    let mut rewind = RewindBuffer::new(4 * 1024 * 1024).with_frames_per_snapshot(1);
+    run_recorded(&mut cpu, &mut rewind, 1);
+    let keyframe = rewind.bytes_used();
+    run_recorded(&mut cpu, &mut rewind, 10);
+    let per_delta = (rewind.bytes_used() - keyframe) / 10;
+    assert!(per_delta * 4 < cpu.save_state().len(), "{per_delta} bytes per delta");
+}
+
+#[test]
+fn test_budget_evicts_oldest_history() {
+    let mut cpu = cpu();
+    let state_size = cpu.save_state().len();
+    // Room for the decoded keyframe plus a few dozen compressed snapshots.
+    let budget = state_size + 16 * 1024;
+    let mut rewind = RewindBuffer::new(budget).with_frames_per_snapshot(1).with_snapshots_per_keyframe(8);
+    let history = run_recorded(&mut cpu, &mut rewind, 200);
+
+    assert!(rewind.bytes_used() <= budget, "{} > {budget}", rewind.bytes_used());
+    let available = rewind.frames_available();
+    assert!(available > 0 && available < 199, "{available} frames available");
+    assert_eq!(rewind.rewind(&mut cpu, 1000).unwrap(), available);
+    assert_eq!(page(&cpu), history[199 - available as usize]);
+}
@@ -4,9 +4,11 @@ mod common;
 
 use rustyboy_core::cpu::cpu::Cpu;
 use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::peripheral::ppu::{PpuRenderer, CYCLES_PER_FRAME};
 use rustyboy_core::cpu::registers::{Flags, Registers};
-use rustyboy_core::cpu::sm83::Sm83;
-use rustyboy_core::cpu::save_state::SaveState;
+use rustyboy_core::cpu::sm83::{Model, Sm83};
+use rustyboy_core::cpu::save_state::{ApuOutputState, SaveState, MAGIC, MIN_BLOB_SIZE, VERSION};
+use rustyboy_core::memory::header::header_checksum;
 use rustyboy_core::memory::memory::GameBoyMemory;
 
 /// Build a minimal in-memory ROM with a NOP + JR -2 loop at 0x0100.
@@ -23,8 +25,10 @@ fn make_rom(cart_type: u8, rom_size_code: u8, ram_size_code: u8) -> Vec<u8> {
 }
 
 /// Create a fresh Sm83 with DMG post-boot register state.
-fn make_emulator(rom: Vec<u8>) -> Sm83 {
-    let memory = Box::new(GameBoyMemory::with_rom(rom));
+fn make_emulator(mut rom: Vec<u8>) -> Sm83 {
+    // Tests tweak header bytes after `make_rom`, so stamp the checksum last.
+    rom[0x014D] = header_checksum(&rom);
+    let memory = Box::new(GameBoyMemory::with_rom(rom).expect("test ROM should load"));
     let decoder = Box::new(OpCodeDecoder::new());
     Sm83::new(memory, decoder)
         .with_registers(Registers {
@@ -250,6 +254,48 @@ fn test_save_state_mbc1_cart_ram_preserved() {
     }
 }
 
+// ── MBC2 registers and built-in RAM preserved across save/load ───────────────
+
+#[test]
+fn test_save_state_mbc2_bank_and_ram_preserved() {
+    // MBC2+BATTERY, 256 KB ROM (16 banks); RAM is built in
+    let mut rom = vec![0u8; 16 * 0x4000];
+    rom[0x0147] = 0x06; // MBC2+BATTERY
+    rom[0x0148] = 0x03; // 16 banks
+    rom[0x0149] = 0x00; // header RAM size is always 0 for MBC2
+    rom[11 * 0x4000] = 0xCD;
+
+    // Program at 0x0100:
+    //   LD A, 0x0A      ; RAM enable value
+    //   LD (0x0000), A  ; address bit 8 clear → RAMG
+    //   LD A, 11
+    //   LD (0x0100), A  ; address bit 8 set → ROMB
+    //   NOP / JR -2
+    rom[0x0100..0x010C].copy_from_slice(&[
+        0x3E, 0x0A, 0xEA, 0x00, 0x00, 0x3E, 0x0B, 0xEA, 0x00, 0x01, 0x00, 0x18,
+    ]);
+    rom[0x010C] = 0xFE;
+
+    let mut cpu = make_emulator(rom.clone());
+    for _ in 0..40 {
+        cpu.tick().unwrap();
+    }
+    assert_eq!(cpu.current_rom_bank(), 11, "bank 11 should be active");
+
+    let mut ram = vec![0u8; 512];
+    ram[0x1FF] = 0x07;
+    cpu.set_external_ram(&ram);
+
+    let state = cpu.save_state();
+    let mut cpu2 = make_emulator(rom);
+    cpu2.load_state(SaveState::from_blob(state).expect("from_blob failed")).expect("load_state failed");
+
+    assert_eq!(cpu2.current_rom_bank(), 11, "MBC2 bank not restored across save/load");
+    assert_eq!(cpu2.read_memory(0x4000).unwrap(), 0xCD);
+    // RAM enable restored, and the nibble reads back with the upper bits set
+    assert_eq!(cpu2.read_memory(0xA1FF).unwrap(), 0xF7);
+}
+
 // ── SaveState: parse blob, inspect fields, then apply ────────────────────────
 
 #[test]
@@ -317,3 +363,298 @@ fn test_save_state_roundtrip_preserves_cycle_counter() {
 
     assert_eq!(cycles_before, cpu2.cycle_counter(), "cycle_counter not preserved across save/load");
 }
+
+// ── Save → load → run is bit-identical to an uninterrupted run ───────────────
+
+const DOTS_PER_FRAME: u64 = 70_224;
+
+/// Run `frames` frames, returning each completed frame and all audio produced.
+fn run_frames(cpu: &mut Sm83, frames: u32) -> (Vec<Vec<u8>>, Vec<f32>) {
+    let mut video = Vec::new();
+    let mut audio = Vec::new();
+    for _ in 0..frames {
+        let target = cpu.cycle_counter() + DOTS_PER_FRAME;
+        while cpu.cycle_counter() < target {
+            cpu.tick().unwrap();
+        }
+        video.push(cpu.framebuffer().to_vec());
+        audio.extend(cpu.drain_audio_samples());
+    }
+    (video, audio)
+}
+
+/// Save `cpu` after `warmup` frames, keep running it for `frames` frames, then
+/// load the state into a fresh emulator and check it produces the same output.
+fn assert_resume_is_bit_identical(rom: Vec<u8>, warmup: u32, frames: u32) -> Vec<f32> {
+    let mut cpu = make_emulator(rom.clone());
+    run_frames(&mut cpu, warmup);
+    let blob = cpu.save_state();
+    let (expected_video, expected_audio) = run_frames(&mut cpu, frames);
+
+    let mut resumed = make_emulator(rom);
+    resumed.load_state(SaveState::from_blob(blob).expect("from_blob failed")).expect("load_state failed");
+    let (video, audio) = run_frames(&mut resumed, frames);
+
+    assert_eq!(resumed.cycle_counter(), cpu.cycle_counter(), "cycle counters diverged");
+    for (i, (want, got)) in expected_video.iter().zip(&video).enumerate() {
+        assert!(want == got, "frame {i} after load differs from uninterrupted run");
+    }
+    assert_eq!(audio.len(), expected_audio.len(), "audio sample count diverged");
+    let first_diff = expected_audio.iter().zip(&audio).position(|(a, b)| a.to_bits() != b.to_bits());
+    assert_eq!(first_diff, None, "audio diverged after load");
+    expected_audio
+}
+
+/// ROM that keeps every peripheral busy: all four sound channels (with
+/// sweep, envelopes, length counters and the 7-bit LFSR), a scrolling
+/// background, a sprite moved through OAM DMA, and repeated serial transfers.
+fn make_busy_rom() -> Vec<u8> {
+    let mut rom = make_rom(0x00, 0, 0);
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP 0x0150
+
+    let mut code: Vec<u8> = Vec::new();
+    // LDH (reg), n for each APU register in init order
+    for (reg, value) in [
+        (0x26, 0x80), (0x24, 0x77), (0x25, 0xFF),               // power, volume, routing
+        (0x10, 0x16), (0x11, 0x90), (0x12, 0xF3), (0x13, 0x00), (0x14, 0xC6), // ch1
+        (0x16, 0x40), (0x17, 0xA1), (0x18, 0x80), (0x19, 0x87), // ch2
+    ] {
+        code.extend_from_slice(&[0x3E, value, 0xE0, reg]);
+    }
+    // Fill wave RAM: LD HL,0xFF30; LD B,16; loop: LD A,L; LD (HL+),A; DEC B; JR NZ loop
+    code.extend_from_slice(&[0x21, 0x30, 0xFF, 0x06, 0x10, 0x7D, 0x22, 0x05, 0x20, 0xFB]);
+    for (reg, value) in [
+        (0x1A, 0x80), (0x1C, 0x20), (0x1D, 0x00), (0x1E, 0x85), // ch3
+        (0x21, 0xF2), (0x22, 0x5B), (0x23, 0x80),               // ch4
+    ] {
+        code.extend_from_slice(&[0x3E, value, 0xE0, reg]);
+    }
+    // Tile 0: LD HL,0x8000; LD B,16; loop: LD A,L; XOR 0x5A; LD (HL+),A; DEC B; JR NZ loop
+    code.extend_from_slice(&[0x21, 0x00, 0x80, 0x06, 0x10, 0x7D, 0xEE, 0x5A, 0x22, 0x05, 0x20, 0xF9]);
+    // Sprite 0 at (0x40, 0x40) in WRAM for DMA; enable sprites
+    code.extend_from_slice(&[0x21, 0x00, 0xC0, 0x36, 0x40, 0x2C, 0x36, 0x40]);
+    code.extend_from_slice(&[0x3E, 0x93, 0xE0, 0x40]);
+
+    let main = code.len();
+    code.extend_from_slice(&[0x01, 0x00, 0x04]);                   // LD BC,0x0400
+    code.extend_from_slice(&[0x0B, 0x78, 0xB1, 0x20, 0xFB]);       // delay: DEC BC; LD A,B; OR C; JR NZ
+    code.extend_from_slice(&[0xF0, 0x43, 0x3C, 0xE0, 0x43]);       // SCX += 1
+    code.extend_from_slice(&[0x21, 0x01, 0xC0, 0x34]);             // sprite X += 1
+    code.extend_from_slice(&[0x3E, 0xC0, 0xE0, 0x46]);             // OAM DMA from 0xC000
+    code.extend_from_slice(&[0x3E, 0x81, 0xE0, 0x02]);             // serial transfer, internal clock
+    code.extend_from_slice(&[0x1C, 0x7B, 0xE0, 0x13]);             // ch1 frequency low = ++E
+    code.extend_from_slice(&[0x3E, 0xC6, 0xE0, 0x14]);             // retrigger ch1
+    code.extend_from_slice(&[0x3E, 0x80, 0xE0, 0x23]);             // retrigger ch4
+    let jr = main as isize - (code.len() as isize + 2);
+    code.extend_from_slice(&[0x18, jr as u8]);                     // JR main
+
+    rom[0x0150..0x0150 + code.len()].copy_from_slice(&code);
+    rom
+}
+
+#[test]
+fn test_save_state_resume_matches_uninterrupted_run() {
+    let audio = assert_resume_is_bit_identical(make_busy_rom(), 30, 60);
+    assert!(audio.iter().any(|&s| s != 0.0), "test ROM should produce audible output");
+}
+
+#[test]
+fn test_save_state_resume_matches_uninterrupted_run_at_every_phase() {
+    // Save at many different points within the main loop so the blob catches
+    // DMA, serial transfers and IO writes in flight.
+    let rom = make_busy_rom();
+    let mut cpu = make_emulator(rom.clone());
+    run_frames(&mut cpu, 2);
+    for _ in 0..40 {
+        for _ in 0..97 {
+            cpu.tick().unwrap();
+        }
+        cpu.drain_audio_samples();
+        let blob = cpu.save_state();
+        let mut resumed = make_emulator(rom.clone());
+        resumed.load_state(SaveState::from_blob(blob).expect("from_blob failed")).expect("load_state failed");
+        for _ in 0..2000 {
+            cpu.tick().unwrap();
+            resumed.tick().unwrap();
+        }
+        assert_eq!(resumed.registers().pc, cpu.registers().pc);
+        assert_eq!(resumed.cycle_counter(), cpu.cycle_counter());
+        assert_eq!(resumed.framebuffer(), cpu.framebuffer());
+        assert_eq!(resumed.drain_audio_samples(), cpu.drain_audio_samples());
+        for addr in 0xFE00..0xFEA0u16 {
+            assert_eq!(resumed.read_memory(addr).unwrap(), cpu.read_memory(addr).unwrap(), "OAM {addr:#06x}");
+        }
+        for addr in [0xFF01u16, 0xFF02, 0xFF0F, 0xFF26] {
+            assert_eq!(resumed.read_memory(addr).unwrap(), cpu.read_memory(addr).unwrap(), "IO {addr:#06x}");
+        }
+    }
+}
+
+#[test]
+fn test_fifo_save_state_resumes_mid_line() {
+    // Saves land at arbitrary dots, mostly mid-scanline and many in mode 3,
+    // so a resumed FIFO renderer has to pick up the line where it was.
+    let rom = make_busy_rom();
+    let fifo_emulator = || make_emulator(rom.clone()).with_ppu_renderer(PpuRenderer::Fifo);
+    let mut cpu = fifo_emulator();
+    run_frames(&mut cpu, 2);
+    for _ in 0..40 {
+        for _ in 0..89 {
+            cpu.tick().unwrap();
+        }
+        let state = SaveState::from_blob(cpu.save_state()).expect("from_blob failed");
+        assert!(state.fifo.is_some());
+        let mut resumed = fifo_emulator();
+        resumed.load_state(state).expect("load_state failed");
+        // Compare the completed frame that contains the save point.
+        let end = cpu.cycle_counter() + CYCLES_PER_FRAME + CYCLES_PER_FRAME / 2;
+        while cpu.cycle_counter() < end {
+            cpu.tick().unwrap();
+            resumed.tick().unwrap();
+        }
+        assert_eq!(resumed.cycle_counter(), cpu.cycle_counter());
+        assert_eq!(resumed.framebuffer(), cpu.framebuffer());
+    }
+}
+
+#[test]
+fn test_save_state_resume_matches_uninterrupted_run_dmg_sound() {
+    // Blargg's sound tests print results that depend on length counters,
+    // sweep and the frame sequencer, so any APU state lost on load shows up
+    // in the framebuffer.
+    let rom = common::load_rom("roms/blargg/dmg_sound/dmg_sound.gb");
+    assert_resume_is_bit_identical(rom, 90, 60);
+}
+
+// ── Version compatibility ─────────────────────────────────────────────────────
+
+/// Rewrite a current blob into the v1 layout: the shared prefix through OAM, then
+/// the cartridge section with a u16 cart RAM length.
+fn downgrade_to_v1(blob: &[u8], mbc_len: usize, ram_len: usize) -> Vec<u8> {
+    let mut v1 = blob[..MIN_BLOB_SIZE].to_vec();
+    v1[4..6].copy_from_slice(&1u16.to_le_bytes());
+    let cart = &blob[blob.len() - (mbc_len + 4 + ram_len)..];
+    v1.extend_from_slice(&cart[..mbc_len]);
+    v1.extend_from_slice(&(ram_len as u16).to_le_bytes());
+    v1.extend_from_slice(&cart[mbc_len + 4..]);
+    v1
+}
+
+#[test]
+fn test_save_state_writes_current_version() {
+    let cpu = make_emulator(make_rom(0x00, 0, 0));
+    let blob = cpu.save_state();
+    assert_eq!(&blob[0..4], MAGIC);
+    assert_eq!(u16::from_le_bytes([blob[4], blob[5]]), VERSION);
+    assert_eq!(SaveState::from_blob(blob).unwrap().version, 3);
+}
+
+#[test]
+fn test_save_state_loads_v1_blob() {
+    // MBC1+RAM+BATTERY, 8 KB RAM; program enables RAM then spins
+    let mut rom = make_rom(0x03, 0, 0x02);
+    rom[0x0100..0x0108].copy_from_slice(&[0x3E, 0x0A, 0xEA, 0x00, 0x00, 0x00, 0x18, 0xFE]);
+    let mut cpu = make_emulator(rom.clone());
+    for _ in 0..40 {
+        cpu.tick().unwrap();
+    }
+    let mut ram = vec![0u8; 0x2000];
+    ram[0x1FFF] = 0x5A;
+    cpu.set_external_ram(&ram);
+    let regs_before = cpu.registers();
+    let cycles_before = cpu.cycle_counter();
+
+    let v1 = downgrade_to_v1(&cpu.save_state(), 4, 0x2000);
+    let state = SaveState::from_blob(v1).expect("v1 blob should parse");
+    assert_eq!(state.version, 1);
+    assert!(state.peripherals.is_none(), "v1 carries no peripheral section");
+
+    let mut cpu2 = make_emulator(rom);
+    cpu2.load_state(state).expect("load_state failed");
+    assert_eq!(cpu2.registers().pc, regs_before.pc);
+    assert_eq!(cpu2.cycle_counter(), cycles_before);
+    assert_eq!(cpu2.read_memory(0xBFFF).unwrap(), 0x5A, "v1 cart RAM not restored");
+    // The restored emulator keeps running
+    for _ in 0..100 {
+        cpu2.tick().unwrap();
+    }
+}
+
+#[test]
+fn test_save_state_loads_v2_blob_as_dmg() {
+    let rom = make_rom(0x00, 0, 0);
+    let cpu = make_emulator(rom.clone());
+    let mut blob = cpu.save_state();
+    // A DMG v3 blob from the scanline renderer is a v2 blob plus zero model
+    // and pixel FIFO bytes and the APU output section before the cart
+    // section (no MBC registers, no RAM: just the u32 RAM length).
+    let mut apu_output = Vec::new();
+    ApuOutputState::default().serialize(&mut apu_output);
+    let model_byte = blob.len() - 4 - apu_output.len() - 2;
+    assert_eq!(blob[model_byte..model_byte + 2], [0, 0]);
+    blob.drain(model_byte..blob.len() - 4);
+    blob[4..6].copy_from_slice(&2u16.to_le_bytes());
+
+    let state = SaveState::from_blob(blob).expect("v2 blob should parse");
+    assert!(state.cgb.is_none());
+    make_emulator(rom).load_state(state).expect("v2 blob should load on DMG");
+}
+
+#[test]
+fn test_cgb_save_state_round_trip() {
+    let mut rom = make_rom(0x00, 0, 0);
+    rom[0x0143] = 0x80;
+    rom[0x014D] = header_checksum(&rom);
+    let new_cgb = || {
+        Sm83::from_rom(rom.clone(), Box::new(OpCodeDecoder::new())).unwrap().with_post_boot_state()
+    };
+    let mut cpu = new_cgb();
+    assert_eq!(cpu.model(), Model::Cgb);
+    cpu.run_frame().unwrap();
+
+    let mut cpu2 = new_cgb();
+    let state = SaveState::from_blob(cpu.save_state()).unwrap();
+    assert!(state.cgb.is_some_and(|cgb| cgb.ppu.cgb_mode));
+    cpu2.load_state(state).expect("load_state failed");
+    assert_eq!(cpu2.registers().pc, cpu.registers().pc);
+    assert_eq!(cpu2.cycle_counter(), cpu.cycle_counter());
+    assert_eq!(cpu2.color_framebuffer(), cpu.color_framebuffer());
+
+    let mut dmg = make_emulator(rom);
+    let err = dmg.load_state(SaveState::from_blob(cpu.save_state()).unwrap());
+    assert_eq!(err, Err("save state is for a different hardware model"));
+}
+
+#[test]
+fn test_save_state_rejects_unknown_version() {
+    let cpu = make_emulator(make_rom(0x00, 0, 0));
+    let mut blob = cpu.save_state();
+    blob[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    assert!(SaveState::from_blob(blob).is_err());
+}
+
+#[test]
+fn test_save_state_rejects_truncated_v2_blob() {
+    let cpu = make_emulator(make_rom(0x00, 0, 0));
+    let mut blob = cpu.save_state();
+    blob.truncate(MIN_BLOB_SIZE + 100);
+    assert!(SaveState::from_blob(blob).is_err());
+}
+
+#[test]
+fn test_save_state_128k_cart_ram_preserved() {
+    // MBC5+RAM+BATTERY with 128 KB RAM: too large for v1's u16 length prefix
+    let rom = make_rom(0x1B, 0, 0x04);
+    let mut cpu = make_emulator(rom.clone());
+    let mut ram = vec![0u8; 0x20000];
+    ram[0] = 0x11;
+    ram[0x1FFFF] = 0x22;
+    cpu.set_external_ram(&ram);
+
+    let mut cpu2 = make_emulator(rom);
+    cpu2.load_state(SaveState::from_blob(cpu.save_state()).unwrap()).unwrap();
+    let ram = cpu2.external_ram().expect("cart should have RAM");
+    assert_eq!(ram.len(), 0x20000);
+    assert_eq!(ram[0], 0x11);
+    assert_eq!(ram[0x1FFFF], 0x22);
+}
@@ -0,0 +1,262 @@
+//! Headless ROM runner for test suites and smoke tests.
+//!
+//! Usage:
+//!   rustyboy-cli --blargg "roms/blargg/cpu_instrs/individual/01-special.gb"
+//!   rustyboy-cli --mooneye roms/mooneye/acceptance/oam_dma/basic.gb
+//!   rustyboy-cli --frames 600 --input title.txt --png out.png game.gb
+//!
+//! Exit status: 0 when the run completes or the stop condition is met,
+//! 1 when a test reports failure or the CPU hits an invalid opcode, 2 for
+//! bad arguments or files, 3 when `--frames` runs out before the stop
+//! condition.
+
+mod record;
+mod runner;
+mod script;
+mod wav;
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use rustyboy_core::cpu::{
+    instructions::opcodes::OpCodeDecoder,
+    peripheral::{
+        apu::DEFAULT_SAMPLE_RATE,
+        ppu::{
+            palette::{ColorCorrection, Palette},
+            PpuRenderer,
+        },
+    },
+    sm83::Sm83,
+};
+use record::Recording;
+use runner::{Outcome, RunError, StopCondition};
+use script::InputScript;
+
+const USAGE: &str = "\
+usage: rustyboy-cli [options] <rom>
+
+  --frames N             run at most N frames (default 3600, one minute)
+  --input FILE           scripted joypad input (see README)
+  --fifo                 use the dot-accurate pixel FIFO renderer
+  --sample-rate HZ       audio rate for --wav (default 48000)
+
+Stop conditions (at most one):
+  --until-serial TEXT    serial output contains TEXT
+  --until-mem ADDR=VAL   byte at ADDR equals VAL at the end of a frame (hex)
+  --until-pc ADDR        PC reaches ADDR (hex)
+  --blargg               Blargg test finishes; fails if it reports Failed
+  --mooneye              Mooneye test finishes; fails on the 0x42 signature
+
+Output:
+  --png FILE             write the final frame
+  --png-every N          also write every Nth frame, numbered, next to FILE
+  --wav FILE             write the audio as 16-bit stereo WAV
+  --record FILE          write every frame as .gif, .apng, .y4m or .rgb
+  --serial FILE          write the serial output ('-' for stdout)";
+
+/// Shades 0–3 as white to black, matching the test suites' reference images.
+const GRAYSCALE: Palette = Palette::uniform([0xFFFFFF, 0xAAAAAA, 0x555555, 0x000000]);
+
+struct Args {
+    rom: PathBuf,
+    frames: u32,
+    input: Option<PathBuf>,
+    fifo: bool,
+    sample_rate: u32,
+    until: Option<StopCondition>,
+    png: Option<PathBuf>,
+    png_every: Option<u32>,
+    wav: Option<PathBuf>,
+    record: Option<PathBuf>,
+    serial: Option<PathBuf>,
+}
+
+fn parse_hex(flag: &str, text: &str) -> Result<u16, String> {
+    let digits = text.trim_start_matches("0x").trim_start_matches('$');
+    u16::from_str_radix(digits, 16).map_err(|_| format!("{flag}: {text:?} is not a hex number"))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut rom = None;
+    let mut parsed = Args {
+        rom: PathBuf::new(),
+        frames: 3600,
+        input: None,
+        fifo: false,
+        sample_rate: DEFAULT_SAMPLE_RATE,
+        until: None,
+        png: None,
+        png_every: None,
+        wav: None,
+        record: None,
+        serial: None,
+    };
+    let mut until = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--frames" => {
+                parsed.frames = value("--frames")?.parse().map_err(|_| "--frames needs a number")?
+            }
+            "--input" => parsed.input = Some(value("--input")?.into()),
+            "--fifo" => parsed.fifo = true,
+            "--sample-rate" => {
+                parsed.sample_rate = value("--sample-rate")?.parse().map_err(|_| "--sample-rate needs a number")?
+            }
+            "--until-serial" => until.push(StopCondition::Serial(value("--until-serial")?)),
+            "--until-mem" => {
+                let spec = value("--until-mem")?;
+                let (addr, val) = spec.split_once('=').ok_or("--until-mem needs ADDR=VALUE")?;
+                let addr = parse_hex("--until-mem", addr)?;
+                let value = u8::try_from(parse_hex("--until-mem", val)?)
+                    .map_err(|_| "--until-mem value must fit in a byte")?;
+                until.push(StopCondition::Memory { addr, value });
+            }
+            "--until-pc" => until.push(StopCondition::Pc(parse_hex("--until-pc", &value("--until-pc")?)?)),
+            "--blargg" => until.push(StopCondition::Blargg),
+            "--mooneye" => until.push(StopCondition::Mooneye),
+            "--png" => parsed.png = Some(value("--png")?.into()),
+            "--png-every" => {
+                let n: u32 = value("--png-every")?.parse().map_err(|_| "--png-every needs a number")?;
+                parsed.png_every = Some(n.max(1));
+            }
+            "--wav" => parsed.wav = Some(value("--wav")?.into()),
+            "--record" => {
+                let path = PathBuf::from(value("--record")?);
+                if !record::supported(&path) {
+                    return Err(format!("--record: {} is not a .gif, .apng, .y4m or .rgb file", path.display()));
+                }
+                parsed.record = Some(path);
+            }
+            "--serial" => parsed.serial = Some(value("--serial")?.into()),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}\n\n{USAGE}")),
+            path => rom = Some(PathBuf::from(path)),
+        }
+    }
+    if until.len() > 1 {
+        return Err("give at most one stop condition".to_string());
+    }
+    if parsed.png_every.is_some() && parsed.png.is_none() {
+        return Err("--png-every needs --png".to_string());
+    }
+    parsed.until = until.pop();
+    parsed.rom = rom.ok_or(USAGE)?;
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(Outcome::Completed | Outcome::Passed) => ExitCode::SUCCESS,
+        Ok(Outcome::Failed) => ExitCode::FAILURE,
+        Ok(Outcome::Timeout) => ExitCode::from(3),
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Errors are setup and output problems; a crashing ROM is a failed run.
+fn run(args: &Args) -> Result<Outcome, Box<dyn Error>> {
+    let rom = fs::read(&args.rom).map_err(|e| format!("{}: {e}", args.rom.display()))?;
+    let script = match &args.input {
+        Some(path) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            InputScript::parse(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        }
+        None => InputScript::default(),
+    };
+    let renderer = if args.fifo { PpuRenderer::Fifo } else { PpuRenderer::Scanline };
+    let mut cpu = Sm83::from_rom(rom, Box::new(OpCodeDecoder::new()))
+        .map_err(|e| format!("{}: {e}", args.rom.display()))?
+        .with_post_boot_state()
+        .with_ppu_renderer(renderer)
+        .with_sample_rate(args.sample_rate);
+
+    let mut recording = args.record.as_deref().map(Recording::create).transpose()?;
+    let mut rgba = vec![0u8; 160 * 144 * 4];
+    let result = runner::run(&mut cpu, args.frames, args.until.as_ref(), &script, |frame, cpu| {
+        if let Some(recording) = &mut recording {
+            frame_rgba(cpu, &mut rgba);
+            recording.push_frame(&rgba)?;
+        }
+        match (&args.png, args.png_every) {
+            (Some(png), Some(every)) if frame % every == 0 => write_png(&numbered(png, frame), cpu, &mut rgba),
+            _ => Ok(()),
+        }
+    });
+    // Keep the frames up to a crash; they're what a bug report needs.
+    if let Some(recording) = recording {
+        recording.finish()?;
+    }
+    let report = match result {
+        Ok(report) => report,
+        Err(RunError::Cpu(e)) => {
+            // Still write what the ROM printed; it often explains the crash.
+            write_serial(args, &cpu)?;
+            eprintln!("{e}");
+            return Ok(Outcome::Failed);
+        }
+        Err(RunError::Output(e)) => return Err(e),
+    };
+
+    if let Some(path) = &args.png {
+        write_png(path, &cpu, &mut rgba)?;
+    }
+    if let Some(path) = &args.wav {
+        let mut out = BufWriter::new(File::create(path)?);
+        wav::write_wav(&mut out, cpu.sample_rate(), &report.audio)?;
+        out.flush()?;
+    }
+    write_serial(args, &cpu)?;
+    if let Some(text) = runner::blargg_memory_text(&cpu).filter(|_| args.until == Some(StopCondition::Blargg)) {
+        eprint!("{text}");
+    }
+    eprintln!("{:?} after {} frame(s)", report.outcome, report.frames);
+    Ok(report.outcome)
+}
+
+fn write_serial(args: &Args, cpu: &Sm83) -> io::Result<()> {
+    match args.serial.as_deref() {
+        Some(path) if path == Path::new("-") => io::stdout().write_all(cpu.serial_output()),
+        Some(path) => fs::write(path, cpu.serial_output()),
+        None => Ok(()),
+    }
+}
+
+/// `out.png`, frame 42 → `out_000042.png`.
+fn numbered(path: &Path, frame: u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}_{frame:06}.png"))
+}
+
+fn frame_rgba(cpu: &Sm83, rgba: &mut [u8]) {
+    match cpu.color_framebuffer() {
+        Some(fb) => ColorCorrection::None.write_rgba8888(fb, rgba),
+        None => GRAYSCALE.write_rgba8888(cpu.framebuffer(), cpu.framebuffer_layers(), rgba),
+    }
+}
+
+fn write_png(path: &Path, cpu: &Sm83, rgba: &mut [u8]) -> Result<(), Box<dyn Error>> {
+    frame_rgba(cpu, rgba);
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), 160, 144);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(())
+}
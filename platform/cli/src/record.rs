@@ -0,0 +1,136 @@
+//! `--record`: every frame of the run written to a video file.
+//!
+//! GIF, Y4M and raw RGB stream to the file as the run goes. APNG needs its
+//! frame count before the first frame, so those frames are kept in memory
+//! and written at the end; use it for short clips.
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use rustyboy_core::cpu::recording::{VideoEncoder, VideoFormat, FRAME_HEIGHT, FRAME_WIDTH};
+
+/// One Game Boy frame, 70224/4194304 s, as the nearest fraction APNG can
+/// hold (off by about 10⁻⁸).
+const APNG_DELAY: (u16, u16) = (400, 23_891);
+
+pub enum Recording {
+    Stream {
+        video: VideoEncoder,
+        file: BufWriter<File>,
+        buf: Vec<u8>,
+    },
+    Apng {
+        path: PathBuf,
+        /// RGB8 frames, back to back.
+        frames: Vec<u8>,
+    },
+}
+
+/// Whether `path` names a format `--record` can write.
+pub fn supported(path: &Path) -> bool {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    extension.eq_ignore_ascii_case("apng") || VideoFormat::from_extension(extension).is_some()
+}
+
+impl Recording {
+    /// Start a recording in the format `path`'s extension names.
+    pub fn create(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if extension.eq_ignore_ascii_case("apng") {
+            return Ok(Recording::Apng { path: path.to_path_buf(), frames: Vec::new() });
+        }
+        let format = VideoFormat::from_extension(extension)
+            .ok_or_else(|| format!("{}: record to .gif, .apng, .y4m or .rgb", path.display()))?;
+        let video = VideoEncoder::new(format);
+        let mut buf = Vec::new();
+        video.write_header(&mut buf);
+        let mut file = BufWriter::new(File::create(path).map_err(|e| format!("{}: {e}", path.display()))?);
+        file.write_all(&buf)?;
+        buf.clear();
+        Ok(Recording::Stream { video, file, buf })
+    }
+
+    pub fn push_frame(&mut self, rgba: &[u8]) -> Result<(), Box<dyn Error>> {
+        match self {
+            Recording::Stream { video, file, buf } => {
+                video.push_frame(rgba, buf);
+                file.write_all(buf)?;
+                buf.clear();
+            }
+            Recording::Apng { frames, .. } => {
+                frames.extend(rgba.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write whatever ends the file.
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Recording::Stream { video, mut file, mut buf } => {
+                video.finish(&mut buf);
+                file.write_all(&buf)?;
+                file.flush()?;
+            }
+            Recording::Apng { path, frames } => {
+                let frame_size = FRAME_WIDTH * FRAME_HEIGHT * 3;
+                let count = frames.len() / frame_size;
+                if count == 0 {
+                    return Ok(());
+                }
+                let file = File::create(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+                let mut encoder = png::Encoder::new(BufWriter::new(file), FRAME_WIDTH as u32, FRAME_HEIGHT as u32);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(count as u32, 0)?;
+                encoder.set_frame_delay(APNG_DELAY.0, APNG_DELAY.1)?;
+                let mut writer = encoder.write_header()?;
+                for frame in frames.chunks_exact(frame_size) {
+                    writer.write_image_data(frame)?;
+                }
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_by_extension() {
+        for name in ["clip.gif", "clip.APNG", "clip.y4m", "clip.rgb"] {
+            assert!(supported(Path::new(name)), "{name}");
+        }
+        assert!(!supported(Path::new("clip.mp4")));
+        assert!(!supported(Path::new("clip")));
+    }
+
+    #[test]
+    fn test_apng_has_one_frame_per_push() {
+        let path = std::env::temp_dir().join(format!("rustyboy-cli-record-{}.apng", std::process::id()));
+        let mut recording = Recording::create(&path).unwrap();
+        for shade in [0u8, 0x55, 0xAA] {
+            recording.push_frame(&[shade, shade, shade, 0xFF].repeat(FRAME_WIDTH * FRAME_HEIGHT)).unwrap();
+        }
+        recording.finish().unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!(control.num_frames, 3);
+        let mut frame = vec![0; reader.output_buffer_size()];
+        for shade in [0u8, 0x55, 0xAA] {
+            reader.next_frame(&mut frame).unwrap();
+            assert_eq!(reader.info().frame_control.unwrap().delay_num, APNG_DELAY.0);
+            assert!(frame.iter().all(|&b| b == shade));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+//! The frame loop: apply scripted input, run a frame, collect output, and
+//! check the stop condition.
+
+use std::{error::Error, fmt};
+
+use rustyboy_core::cpu::{
+    cpu::CpuError,
+    debugger::{Breakpoint, Debugger, StopReason},
+    sm83::Sm83,
+};
+
+use crate::script::InputScript;
+
+/// Where Blargg tests that report through cartridge RAM put their status;
+/// the signature follows at +1..+4 and the text at +4.
+const BLARGG_STATUS_ADDR: u16 = 0xA000;
+const BLARGG_SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+/// Status byte while the test is still running.
+const BLARGG_RUNNING: u8 = 0x80;
+
+/// B, C, D, E, H, L of a passing Mooneye test.
+const MOONEYE_PASS: [u8; 6] = [3, 5, 8, 13, 21, 34];
+const MOONEYE_FAIL: [u8; 6] = [0x42; 6];
+
+/// When to stop before the frame limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopCondition {
+    /// The serial output contains this text.
+    Serial(String),
+    /// The byte at `addr` reads `value` at the end of a frame.
+    Memory { addr: u16, value: u8 },
+    /// PC reaches `addr`, checked before every instruction.
+    Pc(u16),
+    /// A Blargg test finishes, through a final "Passed…"/"Failed…" serial
+    /// line or the cartridge RAM protocol at 0xA000.
+    Blargg,
+    /// A Mooneye test finishes: B–L hold the Fibonacci pass signature or
+    /// the 0x42 fail signature.
+    Mooneye,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// All frames ran and there was no stop condition.
+    Completed,
+    /// The stop condition was met; for test protocols, the test passed.
+    Passed,
+    /// A test protocol reported failure.
+    Failed,
+    /// The frame limit was reached before the stop condition.
+    Timeout,
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    Cpu(CpuError),
+    /// `on_frame` failed to write its output.
+    Output(Box<dyn Error>),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Cpu(e) => write!(f, "{}", e),
+            RunError::Output(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+pub struct Report {
+    pub outcome: Outcome,
+    /// Frames run, counting a frame cut short by a PC stop.
+    pub frames: u32,
+    /// Interleaved stereo samples from the whole run.
+    pub audio: Vec<f32>,
+}
+
+/// Run `cpu` for up to `max_frames` frames. `on_frame` sees each frame as
+/// it completes, with its zero-based index.
+pub fn run(
+    cpu: &mut Sm83,
+    max_frames: u32,
+    until: Option<&StopCondition>,
+    script: &InputScript,
+    mut on_frame: impl FnMut(u32, &Sm83) -> Result<(), Box<dyn Error>>,
+) -> Result<Report, RunError> {
+    let mut debugger = Debugger::new();
+    if let Some(&StopCondition::Pc(addr)) = until {
+        debugger.add_breakpoint(Breakpoint::at(addr));
+    }
+    let mut audio = Vec::new();
+
+    for frame in 0..max_frames {
+        cpu.set_buttons(script.buttons_at(frame));
+        let stop = debugger.run_frame(cpu).map_err(RunError::Cpu)?;
+        audio.extend(cpu.drain_audio_samples());
+        on_frame(frame, cpu).map_err(RunError::Output)?;
+
+        let outcome = match until {
+            None => None,
+            Some(StopCondition::Pc(_)) => {
+                matches!(stop, StopReason::Breakpoint { .. }).then_some(Outcome::Passed)
+            }
+            Some(condition) => check(cpu, condition),
+        };
+        if let Some(outcome) = outcome {
+            return Ok(Report { outcome, frames: frame + 1, audio });
+        }
+    }
+
+    let outcome = if until.is_some() { Outcome::Timeout } else { Outcome::Completed };
+    Ok(Report { outcome, frames: max_frames, audio })
+}
+
+fn check(cpu: &Sm83, condition: &StopCondition) -> Option<Outcome> {
+    let read = |addr: u16| cpu.read_memory(addr).unwrap_or(0xFF);
+    match condition {
+        StopCondition::Serial(text) => {
+            let serial = String::from_utf8_lossy(cpu.serial_output());
+            serial.contains(text.as_str()).then_some(Outcome::Passed)
+        }
+        StopCondition::Memory { addr, value } => (read(*addr) == *value).then_some(Outcome::Passed),
+        StopCondition::Pc(_) => None,
+        StopCondition::Blargg => {
+            // Single tests end with "Passed" or "Failed", multi-test ROMs
+            // with "Passed all tests" or "Failed N tests".
+            let serial = String::from_utf8_lossy(cpu.serial_output());
+            let last_line = serial.strip_suffix('\n').map(|s| s.rsplit('\n').next().unwrap_or(""));
+            match last_line {
+                Some(line) if line.starts_with("Passed") => return Some(Outcome::Passed),
+                Some(line) if line.starts_with("Failed") => return Some(Outcome::Failed),
+                _ => {}
+            }
+            match read(BLARGG_STATUS_ADDR) {
+                _ if !blargg_signed(cpu) => None,
+                BLARGG_RUNNING => None,
+                0 => Some(Outcome::Passed),
+                _ => Some(Outcome::Failed),
+            }
+        }
+        StopCondition::Mooneye => {
+            let r = cpu.registers();
+            match [r.b, r.c, r.d, r.e, r.h, r.l] {
+                MOONEYE_PASS => Some(Outcome::Passed),
+                MOONEYE_FAIL => Some(Outcome::Failed),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// The text a Blargg test wrote to cartridge RAM, if it uses that protocol.
+pub fn blargg_memory_text(cpu: &Sm83) -> Option<String> {
+    if !blargg_signed(cpu) {
+        return None;
+    }
+    let mut text = String::new();
+    for addr in BLARGG_STATUS_ADDR + 4.. {
+        match cpu.read_memory(addr) {
+            Ok(0) | Err(_) => break,
+            Ok(b) => text.push(b as char),
+        }
+    }
+    Some(text)
+}
+
+fn blargg_signed(cpu: &Sm83) -> bool {
+    (BLARGG_STATUS_ADDR + 1..)
+        .zip(BLARGG_SIGNATURE)
+        .all(|(addr, byte)| cpu.read_memory(addr).ok() == Some(byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+    use rustyboy_core::memory::header::header_checksum;
+
+    const MAIN: usize = 0x0150;
+
+    /// A 32 KiB ROM that runs `program` from 0x0150 with the LCD on.
+    fn rom_with(program: &[u8]) -> Sm83 {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x104].copy_from_slice(&[0x00, 0xC3, MAIN as u8, (MAIN >> 8) as u8]);
+        rom[MAIN..MAIN + program.len()].copy_from_slice(program);
+        rom[0x14D] = header_checksum(&rom);
+        Sm83::from_rom(rom, Box::new(OpCodeDecoder::new()))
+            .unwrap()
+            .with_post_boot_state()
+    }
+
+    /// Load B–L with `regs`, then spin on `JR -2`.
+    fn mooneye_program(regs: [u8; 6]) -> Vec<u8> {
+        let mut program = Vec::new();
+        for (opcode, value) in [0x06, 0x0E, 0x16, 0x1E, 0x26, 0x2E].into_iter().zip(regs) {
+            program.extend([opcode, value]);
+        }
+        program.extend([0x18, 0xFE]);
+        program
+    }
+
+    /// A program that spins in place at `MAIN`.
+    const SPIN: [u8; 3] = [0xC3, MAIN as u8, (MAIN >> 8) as u8];
+
+    fn run_until(cpu: &mut Sm83, until: StopCondition) -> Report {
+        run(cpu, 10, Some(&until), &InputScript::default(), |_, _| Ok(())).unwrap()
+    }
+
+    #[test]
+    fn test_mooneye_signatures() {
+        let report = run_until(&mut rom_with(&mooneye_program(MOONEYE_PASS)), StopCondition::Mooneye);
+        assert_eq!((report.outcome, report.frames), (Outcome::Passed, 1));
+        let report = run_until(&mut rom_with(&mooneye_program(MOONEYE_FAIL)), StopCondition::Mooneye);
+        assert_eq!(report.outcome, Outcome::Failed);
+    }
+
+    #[test]
+    fn test_timeout_and_completed() {
+        assert_eq!(run_until(&mut rom_with(&SPIN), StopCondition::Mooneye).outcome, Outcome::Timeout);
+        let report = run(&mut rom_with(&SPIN), 3, None, &InputScript::default(), |_, _| Ok(())).unwrap();
+        assert_eq!((report.outcome, report.frames), (Outcome::Completed, 3));
+        assert!(!report.audio.is_empty());
+    }
+
+    #[test]
+    fn test_pc_and_memory_conditions() {
+        // ld a,0x2A; ld (0xC000),a; jp MAIN+5
+        let program = [0x3E, 0x2A, 0xEA, 0x00, 0xC0, 0xC3, MAIN as u8 + 5, (MAIN >> 8) as u8];
+        let mut cpu = rom_with(&program);
+        let report = run_until(&mut cpu, StopCondition::Pc(MAIN as u16 + 5));
+        assert_eq!((report.outcome, report.frames), (Outcome::Passed, 1));
+        assert_eq!(cpu.registers().pc, MAIN as u16 + 5);
+
+        let report = run_until(&mut rom_with(&program), StopCondition::Memory { addr: 0xC000, value: 0x2A });
+        assert_eq!(report.outcome, Outcome::Passed);
+        let report = run_until(&mut rom_with(&program), StopCondition::Memory { addr: 0xC000, value: 0x2B });
+        assert_eq!(report.outcome, Outcome::Timeout);
+    }
+
+    #[test]
+    fn test_script_reaches_joypad() {
+        let script = InputScript::parse("2 start").unwrap();
+        let mut held = Vec::new();
+        run(&mut rom_with(&SPIN), 4, None, &script, |_, cpu| {
+            held.push(cpu.buttons());
+            Ok(())
+        })
+        .unwrap();
+        let start = rustyboy_core::cpu::peripheral::joypad::Button::Start.mask();
+        assert_eq!(held, [0, 0, start, start]);
+    }
+}
@@ -0,0 +1,116 @@
+//! Scripted joypad input.
+//!
+//! One line per change of input: a frame number followed by the buttons
+//! held from that frame on, until the next line. `-` (or nothing) releases
+//! everything. `#` starts a comment.
+//!
+//! ```text
+//! # Skip the title screen.
+//! 120 start
+//! 125 -
+//! 200 right a
+//! ```
+
+use std::fmt;
+
+use rustyboy_core::cpu::peripheral::joypad::Button;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// The line doesn't start with a frame number.
+    BadFrame { line: usize },
+    UnknownButton { line: usize, name: String },
+    /// Frames must increase from one line to the next.
+    OutOfOrder { line: usize },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::BadFrame { line } => write!(f, "line {}: expected a frame number", line),
+            ScriptError::UnknownButton { line, name } => write!(f, "line {}: unknown button {:?}", line, name),
+            ScriptError::OutOfOrder { line } => write!(f, "line {}: frame is not after the previous line's", line),
+        }
+    }
+}
+
+/// Parsed input changes, in frame order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InputScript {
+    /// `(frame, buttons)`: from `frame` on, hold the [`Button::mask`] bits.
+    changes: Vec<(u32, u8)>,
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut changes: Vec<(u32, u8)> = Vec::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = index + 1;
+            let content = raw.split('#').next().unwrap_or("");
+            let mut words = content.split_whitespace();
+            let Some(frame) = words.next() else {
+                continue;
+            };
+            let frame: u32 = frame.parse().map_err(|_| ScriptError::BadFrame { line })?;
+            if changes.last().is_some_and(|&(last, _)| frame <= last) {
+                return Err(ScriptError::OutOfOrder { line });
+            }
+            let mut buttons = 0u8;
+            for word in words.filter(|&w| w != "-") {
+                let button = button_named(word).ok_or_else(|| ScriptError::UnknownButton {
+                    line,
+                    name: word.to_string(),
+                })?;
+                buttons |= button.mask();
+            }
+            changes.push((frame, buttons));
+        }
+        Ok(InputScript { changes })
+    }
+
+    /// The buttons held during `frame`.
+    pub fn buttons_at(&self, frame: u32) -> u8 {
+        let next = self.changes.partition_point(|&(start, _)| start <= frame);
+        next.checked_sub(1).map_or(0, |i| self.changes[i].1)
+    }
+}
+
+fn button_named(name: &str) -> Option<Button> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "right" => Button::Right,
+        "left" => Button::Left,
+        "up" => Button::Up,
+        "down" => Button::Down,
+        "a" => Button::A,
+        "b" => Button::B,
+        "select" => Button::Select,
+        "start" => Button::Start,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buttons_hold_until_next_line() {
+        let script = InputScript::parse("# title\n120 start\n125 -\n\n200 Right A  # run\n").unwrap();
+        assert_eq!(script.buttons_at(0), 0);
+        assert_eq!(script.buttons_at(119), 0);
+        assert_eq!(script.buttons_at(120), Button::Start.mask());
+        assert_eq!(script.buttons_at(124), Button::Start.mask());
+        assert_eq!(script.buttons_at(125), 0);
+        assert_eq!(script.buttons_at(5000), Button::Right.mask() | Button::A.mask());
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        assert_eq!(InputScript::parse("\nstart 10"), Err(ScriptError::BadFrame { line: 2 }));
+        assert_eq!(
+            InputScript::parse("10 jump"),
+            Err(ScriptError::UnknownButton { line: 1, name: "jump".into() })
+        );
+        assert_eq!(InputScript::parse("10 a\n10 b"), Err(ScriptError::OutOfOrder { line: 2 }));
+    }
+}
@@ -0,0 +1,35 @@
+//! Whole-run WAV output, using the core's PCM conversion.
+
+use std::io::{self, Write};
+
+use rustyboy_core::cpu::recording::wav;
+
+/// Write interleaved stereo f32 `samples` as 16-bit PCM.
+pub fn write_wav(out: &mut impl Write, sample_rate: u32, samples: &[f32]) -> io::Result<()> {
+    out.write_all(&wav::header(sample_rate, (samples.len() / 2) as u32))?;
+    let mut pcm = Vec::new();
+    wav::write_samples(samples, &mut pcm);
+    out.write_all(&pcm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_and_clamped_samples() {
+        let mut out = Vec::new();
+        write_wav(&mut out, 48_000, &[0.0, 1.0, -2.0, 0.5]).unwrap();
+        assert_eq!(out.len(), 44 + 8);
+        assert_eq!(&out[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(out[4..8].try_into().unwrap()), 36 + 8);
+        assert_eq!(u32::from_le_bytes(out[24..28].try_into().unwrap()), 48_000);
+        assert_eq!(u32::from_le_bytes(out[28..32].try_into().unwrap()), 48_000 * 4);
+        assert_eq!(u32::from_le_bytes(out[40..44].try_into().unwrap()), 8);
+        let pcm: Vec<i16> = out[44..]
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(pcm, [0, i16::MAX, -i16::MAX, i16::MAX / 2]);
+    }
+}
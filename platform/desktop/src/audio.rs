@@ -0,0 +1,127 @@
+//! Audio output through the default cpal device.
+//!
+//! The stream runs at the device's preferred rate unless one is asked for, and
+//! the APU is set to produce interleaved stereo f32 at the same rate. Each
+//! frame's samples are queued here and the device callback pulls from the
+//! queue, playing silence when it runs dry.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+const CHANNELS: u16 = 2;
+
+/// Samples waiting for the device, shared with the audio thread.
+#[derive(Clone)]
+pub struct AudioQueue {
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    /// Queued audio beyond this (~100 ms) is dropped, oldest first, so a
+    /// stall or clock drift between frame pacing and the sound card can't
+    /// build up lasting latency.
+    max_queued: usize,
+}
+
+impl AudioQueue {
+    fn new(sample_rate: u32) -> Self {
+        AudioQueue {
+            samples: Arc::default(),
+            max_queued: sample_rate as usize * CHANNELS as usize / 10,
+        }
+    }
+
+    pub fn push(&self, samples: &[f32]) {
+        let mut queue = self.samples.lock().unwrap();
+        queue.extend(samples);
+        if queue.len() > self.max_queued {
+            // Keep L/R pairs aligned.
+            let excess = (queue.len() - self.max_queued) & !1;
+            queue.drain(..excess);
+        }
+    }
+
+    /// Fill `out` from the queue, padding with silence.
+    fn pull(&self, out: &mut [f32]) {
+        let mut queue = self.samples.lock().unwrap();
+        for sample in out.iter_mut() {
+            *sample = queue.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.samples.lock().unwrap().clear();
+    }
+}
+
+/// Keeps the output stream playing; dropping it stops audio.
+pub struct AudioOutput {
+    _stream: cpal::Stream,
+    queue: AudioQueue,
+    sample_rate: u32,
+}
+
+impl AudioOutput {
+    /// Open the default device at `sample_rate`, or at its preferred rate.
+    pub fn open(sample_rate: Option<u32>) -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| "no audio output device".to_string())?;
+        let sample_rate = match sample_rate {
+            Some(rate) => rate,
+            None => device.default_output_config().map_err(|e| e.to_string())?.sample_rate().0,
+        };
+        let config = cpal::StreamConfig {
+            channels: CHANNELS,
+            sample_rate: cpal::SampleRate(sample_rate),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let queue = AudioQueue::new(sample_rate);
+        let source = queue.clone();
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| source.pull(data),
+                |e| eprintln!("audio stream error: {e}"),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        stream.play().map_err(|e| e.to_string())?;
+        Ok(AudioOutput { _stream: stream, queue, sample_rate })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn queue(&self) -> &AudioQueue {
+        &self.queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pull_pads_with_silence() {
+        let queue = AudioQueue::new(48_000);
+        queue.push(&[0.5, -0.5]);
+        let mut out = [1.0; 4];
+        queue.pull(&mut out);
+        assert_eq!(out, [0.5, -0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_push_drops_oldest_beyond_limit() {
+        let queue = AudioQueue::new(48_000);
+        assert_eq!(queue.max_queued, 9600);
+        let samples: Vec<f32> = (0..queue.max_queued + 10).map(|i| i as f32).collect();
+        queue.push(&samples);
+        let mut out = [0.0; 2];
+        queue.pull(&mut out);
+        assert_eq!(out, [10.0, 11.0]);
+        assert_eq!(queue.samples.lock().unwrap().len(), queue.max_queued - 2);
+    }
+}
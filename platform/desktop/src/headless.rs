@@ -0,0 +1,41 @@
+//! Run a ROM with no window or audio device, for CI smoke tests.
+
+use std::{error::Error, fs::File, io::BufWriter, path::Path};
+
+use crate::session::{Session, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Run `frames` frames with no buttons held, optionally recording them to
+/// `record` and writing the final frame to `screenshot` as a PNG, then
+/// flush the battery save.
+pub fn run(
+    mut session: Session,
+    frames: u32,
+    screenshot: Option<&Path>,
+    record: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = record {
+        session.start_recording(path)?;
+    }
+    for _ in 0..frames {
+        session.run_frame(0, 1)?;
+    }
+    for path in session.stop_recording()? {
+        println!("wrote {}", path.display());
+    }
+    if let Some(path) = screenshot {
+        write_png(path, session.frame_rgba())?;
+        println!("wrote {}", path.display());
+    }
+    session.save_battery()?;
+    println!("ran {frames} frame(s)");
+    Ok(())
+}
+
+fn write_png(path: &Path, rgba: &[u8]) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Keyboard and gamepad → joypad mask.
+//!
+//! | Button | Keyboard | Gamepad |
+//! |---|---|---|
+//! | D-pad | Arrow keys | D-pad / left stick |
+//! | A | Z | East (Xbox B, Nintendo A) |
+//! | B | X | South (Xbox A, Nintendo B) |
+//! | Start | Enter | Start |
+//! | Select | Shift | Select |
+//!
+//! Holding Tab or the right trigger fast-forwards.
+
+use gilrs::{Axis, Gilrs};
+use minifb::{Key, Window};
+use rustyboy_core::cpu::peripheral::joypad::Button;
+
+const KEYS: [(Key, Button); 9] = [
+    (Key::Right, Button::Right),
+    (Key::Left, Button::Left),
+    (Key::Up, Button::Up),
+    (Key::Down, Button::Down),
+    (Key::Z, Button::A),
+    (Key::X, Button::B),
+    (Key::LeftShift, Button::Select),
+    (Key::RightShift, Button::Select),
+    (Key::Enter, Button::Start),
+];
+
+const PAD_BUTTONS: [(gilrs::Button, Button); 8] = [
+    (gilrs::Button::DPadRight, Button::Right),
+    (gilrs::Button::DPadLeft, Button::Left),
+    (gilrs::Button::DPadUp, Button::Up),
+    (gilrs::Button::DPadDown, Button::Down),
+    (gilrs::Button::East, Button::A),
+    (gilrs::Button::South, Button::B),
+    (gilrs::Button::Select, Button::Select),
+    (gilrs::Button::Start, Button::Start),
+];
+
+/// How far a stick must lean to count as a D-pad press.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// What the player is holding this frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InputState {
+    /// Joypad mask for [`Sm83::run_frame_with_input`](rustyboy_core::cpu::sm83::Sm83::run_frame_with_input).
+    pub buttons: u8,
+    pub fast_forward: bool,
+}
+
+pub struct Input {
+    /// `None` when the platform has no gamepad support; the keyboard still works.
+    gilrs: Option<Gilrs>,
+}
+
+impl Input {
+    pub fn open() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                eprintln!("gamepads unavailable: {e}");
+                None
+            }
+        };
+        Input { gilrs }
+    }
+
+    pub fn poll(&mut self, window: &Window) -> InputState {
+        let mut state = InputState::default();
+        for (key, button) in KEYS {
+            if window.is_key_down(key) {
+                state.buttons |= button.mask();
+            }
+        }
+        state.fast_forward = window.is_key_down(Key::Tab);
+
+        let Some(gilrs) = &mut self.gilrs else {
+            return state;
+        };
+        // Drain events so gilrs updates the cached button and axis state.
+        while gilrs.next_event().is_some() {}
+        for (_, pad) in gilrs.gamepads() {
+            for (pad_button, button) in PAD_BUTTONS {
+                if pad.is_pressed(pad_button) {
+                    state.buttons |= button.mask();
+                }
+            }
+            let x = pad.value(Axis::LeftStickX);
+            let y = pad.value(Axis::LeftStickY);
+            if x > STICK_THRESHOLD {
+                state.buttons |= Button::Right.mask();
+            } else if x < -STICK_THRESHOLD {
+                state.buttons |= Button::Left.mask();
+            }
+            // gilrs reports up as positive.
+            if y > STICK_THRESHOLD {
+                state.buttons |= Button::Up.mask();
+            } else if y < -STICK_THRESHOLD {
+                state.buttons |= Button::Down.mask();
+            }
+            state.fast_forward |= pad.is_pressed(gilrs::Button::RightTrigger2);
+        }
+        state
+    }
+}
@@ -0,0 +1,160 @@
+//! Native desktop frontend.
+//!
+//! Usage:
+//!   cargo run -p rustyboy-desktop --release -- game.gb
+//!   cargo run -p rustyboy-desktop -- --headless --frames 600 --screenshot out.png game.gb
+//!
+//! See README.md for controls and options.
+
+// Slots, palette cycling and the like are only reachable from the window.
+#![cfg_attr(not(feature = "gui"), allow(dead_code))]
+
+#[cfg(feature = "gui")]
+mod audio;
+mod headless;
+#[cfg(feature = "gui")]
+mod input;
+mod record;
+mod session;
+#[cfg(feature = "gui")]
+mod window;
+
+use std::{path::PathBuf, process::ExitCode};
+
+use rustyboy_core::cpu::{peripheral::ppu::palette::PalettePreset, recording::VideoFormat};
+use session::Session;
+
+const USAGE: &str = "\
+usage: rustyboy-desktop [options] <rom>
+
+  --palette NAME       DMG colors: dmg-green, pocket, light, high-contrast, cgb
+  --lcd-colors         mimic the CGB LCD's muted colors
+  --scale N            window scale: 1, 2, 4 or 8 (default 4)
+  --fast-forward N     frames per displayed frame while fast-forwarding (default 4)
+  --sample-rate HZ     audio output rate (default: the device's preferred rate)
+  --headless           no window or audio; run --frames frames and exit
+  --frames N           frames to run headless (default 600)
+  --screenshot FILE    write the final headless frame as a PNG
+  --record FILE        record to FILE (.gif, .y4m or .rgb; .wav alongside)";
+
+struct Args {
+    rom: PathBuf,
+    palette: PalettePreset,
+    lcd_colors: bool,
+    scale: u32,
+    fast_forward: u32,
+    sample_rate: Option<u32>,
+    headless: bool,
+    frames: u32,
+    screenshot: Option<PathBuf>,
+    record: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut rom = None;
+    let mut parsed = Args {
+        rom: PathBuf::new(),
+        palette: PalettePreset::default(),
+        lcd_colors: false,
+        scale: 4,
+        fast_forward: 4,
+        sample_rate: None,
+        headless: false,
+        frames: 600,
+        screenshot: None,
+        record: None,
+    };
+    let number = |flag: &str, value: Option<String>| -> Result<u32, String> {
+        value
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| format!("{flag} needs a number"))
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--palette" => {
+                let name = args.next().unwrap_or_default();
+                parsed.palette =
+                    PalettePreset::from_name(&name).ok_or_else(|| format!("unknown palette: {name}"))?;
+            }
+            "--lcd-colors" => parsed.lcd_colors = true,
+            "--scale" => parsed.scale = number("--scale", args.next())?,
+            "--fast-forward" => parsed.fast_forward = number("--fast-forward", args.next())?.max(1),
+            "--sample-rate" => parsed.sample_rate = Some(number("--sample-rate", args.next())?),
+            "--headless" => parsed.headless = true,
+            "--frames" => parsed.frames = number("--frames", args.next())?,
+            "--screenshot" => {
+                parsed.screenshot = Some(args.next().ok_or("--screenshot needs a file name")?.into())
+            }
+            "--record" => {
+                let path = PathBuf::from(args.next().ok_or("--record needs a file name")?);
+                let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                if VideoFormat::from_extension(extension).is_none() {
+                    return Err(format!("{}: record to .gif, .y4m or .rgb", path.display()));
+                }
+                parsed.record = Some(path);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}\n\n{USAGE}")),
+            path => rom = Some(PathBuf::from(path)),
+        }
+    }
+    parsed.rom = rom.ok_or(USAGE)?;
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut session = match Session::open(&args.rom) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    session.set_palette(args.palette);
+    session.set_color_correction(args.lcd_colors);
+
+    // Builds without the `gui` feature are always headless.
+    #[cfg(feature = "gui")]
+    let result = if args.headless {
+        headless::run(session, args.frames, args.screenshot.as_deref(), args.record.as_deref())
+    } else {
+        run_window(session, &args)
+    };
+    #[cfg(not(feature = "gui"))]
+    let result = headless::run(session, args.frames, args.screenshot.as_deref(), args.record.as_deref());
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_window(session: Session, args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let scale = match args.scale {
+        1 => minifb::Scale::X1,
+        2 => minifb::Scale::X2,
+        4 => minifb::Scale::X4,
+        8 => minifb::Scale::X8,
+        n => return Err(format!("unsupported scale {n}; use 1, 2, 4 or 8").into()),
+    };
+    window::run(
+        session,
+        window::Options {
+            scale,
+            fast_forward: args.fast_forward,
+            sample_rate: args.sample_rate,
+            record: args.record.clone(),
+        },
+    )
+}
+
@@ -0,0 +1,84 @@
+//! Recording the session to a video file, plus a WAV file for formats
+//! that carry no sound.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use rustyboy_core::cpu::recording::{wav, VideoEncoder, VideoFormat};
+
+pub struct Recording {
+    video: VideoEncoder,
+    video_file: BufWriter<File>,
+    /// `None` for GIFs, which have no sound.
+    audio: Option<(PathBuf, BufWriter<File>)>,
+    sample_rate: u32,
+    /// Stereo pairs written to the WAV file.
+    pairs: u32,
+    buf: Vec<u8>,
+    path: PathBuf,
+}
+
+impl Recording {
+    /// Start recording to `path`, whose extension picks the format: `.gif`,
+    /// `.y4m` or `.rgb`. The last two get their audio in a `.wav` file next
+    /// to them.
+    pub fn create(path: &Path, sample_rate: u32) -> io::Result<Self> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let format = VideoFormat::from_extension(extension).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "record to a .gif, .y4m or .rgb file")
+        })?;
+        let video = VideoEncoder::new(format);
+        let mut buf = Vec::new();
+        video.write_header(&mut buf);
+        let mut video_file = BufWriter::new(File::create(path)?);
+        video_file.write_all(&buf)?;
+        buf.clear();
+
+        let audio = match format {
+            VideoFormat::Gif => None,
+            VideoFormat::Y4m | VideoFormat::RawRgb => {
+                let wav_path = path.with_extension("wav");
+                let mut file = BufWriter::new(File::create(&wav_path)?);
+                // The lengths are filled in by `finish`.
+                file.write_all(&wav::header(sample_rate, 0))?;
+                Some((wav_path, file))
+            }
+        };
+        Ok(Recording { video, video_file, audio, sample_rate, pairs: 0, buf, path: path.to_path_buf() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add one frame and the audio produced while it ran.
+    pub fn push_frame(&mut self, rgba: &[u8], samples: &[f32]) -> io::Result<()> {
+        self.video.push_frame(rgba, &mut self.buf);
+        self.video_file.write_all(&self.buf)?;
+        self.buf.clear();
+        if let Some((_, file)) = &mut self.audio {
+            wav::write_samples(samples, &mut self.buf);
+            file.write_all(&self.buf)?;
+            self.buf.clear();
+            self.pairs += (samples.len() / 2) as u32;
+        }
+        Ok(())
+    }
+
+    /// Close the files. Returns the WAV file's path, if there is one.
+    pub fn finish(mut self) -> io::Result<Option<PathBuf>> {
+        self.video.finish(&mut self.buf);
+        self.video_file.write_all(&self.buf)?;
+        self.video_file.flush()?;
+        let Some((wav_path, file)) = self.audio else {
+            return Ok(None);
+        };
+        let mut file = file.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&wav::header(self.sample_rate, self.pairs))?;
+        Ok(Some(wav_path))
+    }
+}
@@ -0,0 +1,373 @@
+//! A running cartridge plus the files that live next to its ROM.
+//!
+//! Both the window and headless mode drive the same loop: apply input and
+//! run one frame, then hand the framebuffer and audio to the host — the
+//! same order the web client and the Pico firmware use.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use rustyboy_core::cpu::{
+    cpu::CpuError,
+    instructions::opcodes::OpCodeDecoder,
+    peripheral::ppu::palette::{ColorCorrection, Palette, PalettePreset},
+    save_state::SaveState,
+    sm83::Sm83,
+};
+use rustyboy_core::memory::{cartridge::CartridgeError, header::RomHeader};
+
+use crate::record::Recording;
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+const RGBA_FRAMEBUFFER_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT * 4;
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(PathBuf, io::Error),
+    Cartridge(CartridgeError),
+    Cpu(CpuError),
+    /// The slot file exists but is not a usable save state for this ROM.
+    SaveState(PathBuf, &'static str),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            SessionError::Cartridge(e) => write!(f, "{}", e),
+            SessionError::Cpu(e) => write!(f, "{}", e),
+            SessionError::SaveState(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+pub struct Session {
+    cpu: Sm83,
+    rom_path: PathBuf,
+    /// Whether the cartridge keeps its RAM on a battery, i.e. has a `.sav`.
+    battery: bool,
+    palette_preset: PalettePreset,
+    palette: Palette,
+    /// What the `cgb` preset resolves to for this cartridge.
+    title_palette: Palette,
+    color_correction: ColorCorrection,
+    rgba_buf: Vec<u8>,
+    recording: Option<Recording>,
+}
+
+impl Session {
+    /// Load the ROM at `rom_path` and, if the cartridge has a battery, the
+    /// `.sav` file next to it.
+    pub fn open(rom_path: &Path) -> Result<Self, SessionError> {
+        let rom = fs::read(rom_path).map_err(|e| SessionError::Io(rom_path.to_path_buf(), e))?;
+        let header = RomHeader::parse(&rom).map_err(SessionError::Cartridge)?;
+        let title_palette = PalettePreset::CgbTitle.palette(&rom);
+        let cpu = Sm83::from_rom(rom, Box::new(OpCodeDecoder::new()))
+            .map_err(SessionError::Cartridge)?
+            .with_post_boot_state();
+        let mut session = Session {
+            cpu,
+            rom_path: rom_path.to_path_buf(),
+            battery: header.cartridge_type.has_battery(),
+            palette_preset: PalettePreset::default(),
+            palette: Palette::DMG_GREEN,
+            title_palette,
+            color_correction: ColorCorrection::None,
+            rgba_buf: vec![0u8; RGBA_FRAMEBUFFER_SIZE],
+            recording: None,
+        };
+        session.load_battery()?;
+        Ok(session)
+    }
+
+    pub fn cpu(&self) -> &Sm83 {
+        &self.cpu
+    }
+
+    pub fn rom_path(&self) -> &Path {
+        &self.rom_path
+    }
+
+    /// Apply `buttons` (a [`Sm83::buttons`] mask) and run one frame. With
+    /// `fast_forward > 1`, that many frames run and only the last frame's
+    /// audio is returned, so sound keeps up without piling up latency. A
+    /// recording still gets every frame and all of the audio.
+    pub fn run_frame(&mut self, buttons: u8, fast_forward: u32) -> Result<Vec<f32>, SessionError> {
+        for _ in 1..fast_forward {
+            self.cpu.run_frame_with_input(buttons).map_err(SessionError::Cpu)?;
+            let samples = self.cpu.drain_audio_samples();
+            self.record_frame(&samples)?;
+        }
+        self.cpu.run_frame_with_input(buttons).map_err(SessionError::Cpu)?;
+        let samples = self.cpu.drain_audio_samples();
+        self.record_frame(&samples)?;
+        Ok(samples)
+    }
+
+    /// The current frame as RGBA8, colored with the selected palette.
+    pub fn frame_rgba(&mut self) -> &[u8] {
+        self.render();
+        &self.rgba_buf
+    }
+
+    fn render(&mut self) {
+        match self.cpu.color_framebuffer() {
+            Some(fb) => self.color_correction.write_rgba8888(fb, &mut self.rgba_buf),
+            None => self.palette.write_rgba8888(
+                self.cpu.framebuffer(),
+                self.cpu.framebuffer_layers(),
+                &mut self.rgba_buf,
+            ),
+        }
+    }
+
+    /// Add the frame just run to the recording. A write error ends the
+    /// recording, so the session can carry on without it.
+    fn record_frame(&mut self, samples: &[f32]) -> Result<(), SessionError> {
+        if self.recording.is_none() {
+            return Ok(());
+        }
+        self.render();
+        let recording = self.recording.as_mut().unwrap();
+        if let Err(e) = recording.push_frame(&self.rgba_buf, samples) {
+            let path = recording.path().to_path_buf();
+            self.recording = None;
+            return Err(SessionError::Io(path, e));
+        }
+        Ok(())
+    }
+
+    /// Record every frame from now on to `path`; see [`Recording::create`].
+    /// Any recording already running is finished first.
+    pub fn start_recording(&mut self, path: &Path) -> Result<(), SessionError> {
+        self.stop_recording()?;
+        let recording = Recording::create(path, self.cpu.sample_rate())
+            .map_err(|e| SessionError::Io(path.to_path_buf(), e))?;
+        self.recording = Some(recording);
+        Ok(())
+    }
+
+    /// Finish the recording, if there is one. Returns the files written.
+    pub fn stop_recording(&mut self) -> Result<Vec<PathBuf>, SessionError> {
+        let Some(recording) = self.recording.take() else {
+            return Ok(Vec::new());
+        };
+        let path = recording.path().to_path_buf();
+        let wav = recording.finish().map_err(|e| SessionError::Io(path.clone(), e))?;
+        Ok([path].into_iter().chain(wav).collect())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn palette_preset(&self) -> PalettePreset {
+        self.palette_preset
+    }
+
+    /// Color DMG frames with `preset`; CGB frames are unaffected.
+    pub fn set_palette(&mut self, preset: PalettePreset) {
+        self.palette_preset = preset;
+        self.palette = match preset {
+            PalettePreset::CgbTitle => self.title_palette,
+            preset => preset.palette(&[]),
+        };
+    }
+
+    /// Produce audio at `sample_rate` Hz from the next frame on.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.cpu.set_sample_rate(sample_rate);
+    }
+
+    /// Mimic the CGB LCD's muted colors. DMG frames are unaffected.
+    pub fn set_color_correction(&mut self, enabled: bool) {
+        self.color_correction = if enabled { ColorCorrection::Lcd } else { ColorCorrection::None };
+    }
+
+    /// `game.gb` → `game.sav`, the name most emulators use.
+    pub fn battery_path(&self) -> PathBuf {
+        self.rom_path.with_extension("sav")
+    }
+
+    /// `game.gb` → `game.3.rbss` for slot 3.
+    pub fn slot_path(&self, slot: u8) -> PathBuf {
+        self.rom_path.with_extension(format!("{slot}.rbss"))
+    }
+
+    /// `game.gb` → `game.rec1.y4m`, or the first of `game.rec2.y4m`,
+    /// `game.rec3.y4m`, … not already taken by an earlier recording.
+    pub fn next_recording_path(&self) -> PathBuf {
+        (1..)
+            .map(|n| self.rom_path.with_extension(format!("rec{n}.y4m")))
+            .find(|path| !path.exists())
+            .unwrap()
+    }
+
+    fn load_battery(&mut self) -> Result<(), SessionError> {
+        if !self.battery {
+            return Ok(());
+        }
+        let path = self.battery_path();
+        match fs::read(&path) {
+            Ok(data) => {
+                self.cpu.set_external_ram(&data);
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(SessionError::Io(path, e)),
+        }
+    }
+
+    /// Write cartridge RAM to the `.sav` file. Does nothing for cartridges
+    /// without a battery.
+    pub fn save_battery(&self) -> Result<(), SessionError> {
+        let Some(ram) = self.cpu.external_ram().filter(|_| self.battery) else {
+            return Ok(());
+        };
+        let path = self.battery_path();
+        fs::write(&path, ram).map_err(|e| SessionError::Io(path, e))
+    }
+
+    pub fn save_slot(&self, slot: u8) -> Result<PathBuf, SessionError> {
+        let path = self.slot_path(slot);
+        fs::write(&path, self.cpu.save_state()).map_err(|e| SessionError::Io(path.clone(), e))?;
+        Ok(path)
+    }
+
+    pub fn load_slot(&mut self, slot: u8) -> Result<PathBuf, SessionError> {
+        let path = self.slot_path(slot);
+        let data = fs::read(&path).map_err(|e| SessionError::Io(path.clone(), e))?;
+        let state = SaveState::from_blob(data).map_err(|e| SessionError::SaveState(path.clone(), e))?;
+        self.cpu
+            .load_state(state)
+            .map_err(|e| SessionError::SaveState(path.clone(), e))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyboy_core::cpu::peripheral::joypad::Button;
+
+    /// A 32 KiB MBC1+RAM+BATTERY ROM that spins in place with the LCD on.
+    fn battery_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x100..0x104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // nop; jp 0x0150
+        rom[0x150..0x153].copy_from_slice(&[0xC3, 0x50, 0x01]); // jp 0x0150
+        rom[0x134..0x13C].copy_from_slice(b"DESKTOP\0");
+        rom[0x147] = 0x03; // MBC1+RAM+BATTERY
+        rom[0x149] = 0x02; // 8 KiB RAM
+        rom[0x14D] = rustyboy_core::memory::header::header_checksum(&rom);
+        rom
+    }
+
+    /// A fresh directory holding `game.gb`, removed again on drop.
+    struct TempRom(PathBuf);
+
+    impl TempRom {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("rustyboy-desktop-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("game.gb"), battery_rom()).unwrap();
+            TempRom(dir)
+        }
+
+        fn rom(&self) -> PathBuf {
+            self.0.join("game.gb")
+        }
+    }
+
+    impl Drop for TempRom {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_files_sit_next_to_rom() {
+        let tmp = TempRom::new("paths");
+        let session = Session::open(&tmp.rom()).unwrap();
+        assert_eq!(session.battery_path(), tmp.0.join("game.sav"));
+        assert_eq!(session.slot_path(3), tmp.0.join("game.3.rbss"));
+    }
+
+    #[test]
+    fn test_battery_round_trips_through_sav_file() {
+        let tmp = TempRom::new("battery");
+        let mut ram = vec![0u8; 0x2000];
+        ram[0] = 0x5A;
+        ram[0x1FFF] = 0xA5;
+        fs::write(tmp.0.join("game.sav"), &ram).unwrap();
+
+        let session = Session::open(&tmp.rom()).unwrap();
+        assert_eq!(session.cpu().external_ram(), Some(&ram[..]));
+
+        fs::remove_file(tmp.0.join("game.sav")).unwrap();
+        session.save_battery().unwrap();
+        assert_eq!(fs::read(tmp.0.join("game.sav")).unwrap(), ram);
+    }
+
+    #[test]
+    fn test_save_slot_restores_state() {
+        let tmp = TempRom::new("slots");
+        let mut session = Session::open(&tmp.rom()).unwrap();
+        session.run_frame(0, 1).unwrap();
+        session.save_slot(1).unwrap();
+        let saved = session.cpu().save_state();
+
+        session.run_frame(Button::A.mask(), 5).unwrap();
+        assert_ne!(session.cpu().save_state(), saved);
+        session.load_slot(1).unwrap();
+        assert_eq!(session.cpu().save_state(), saved);
+
+        assert!(matches!(session.load_slot(2), Err(SessionError::Io(..))));
+    }
+
+    #[test]
+    fn test_recording_gets_every_frame_and_all_audio() {
+        let tmp = TempRom::new("record");
+        let mut session = Session::open(&tmp.rom()).unwrap();
+        // The first frame after boot is partial.
+        session.run_frame(0, 1).unwrap();
+        let path = session.next_recording_path();
+        assert_eq!(path, tmp.0.join("game.rec1.y4m"));
+        session.start_recording(&path).unwrap();
+        assert!(session.is_recording());
+        for _ in 0..3 {
+            session.run_frame(0, 2).unwrap();
+        }
+        let files = session.stop_recording().unwrap();
+        assert_eq!(files, [path.clone(), tmp.0.join("game.rec1.wav")]);
+        assert!(!session.is_recording());
+        assert_eq!(session.next_recording_path(), tmp.0.join("game.rec2.y4m"));
+
+        let y4m = fs::read(&path).unwrap();
+        let frames = y4m.windows(6).filter(|w| w == b"FRAME\n").count();
+        assert_eq!(frames, 6);
+        let wav = fs::read(&files[1]).unwrap();
+        let data = u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize;
+        assert_eq!(data, wav.len() - 44);
+        // Six frames of stereo 16-bit audio, fast-forwarded or not.
+        let pairs = 6 * session.cpu().sample_rate() as usize / 60;
+        assert!((data / 4).abs_diff(pairs) < 50, "{} pairs", data / 4);
+    }
+
+    #[test]
+    fn test_fast_forward_keeps_one_frame_of_audio() {
+        let tmp = TempRom::new("ff");
+        let mut session = Session::open(&tmp.rom()).unwrap();
+        // The first frame after boot is partial.
+        session.run_frame(0, 1).unwrap();
+        let normal = session.run_frame(0, 1).unwrap().len();
+        let fast = session.run_frame(0, 4).unwrap().len();
+        assert!(normal > 0);
+        assert!(fast.abs_diff(normal) <= 2, "{fast} samples vs {normal}");
+    }
+}
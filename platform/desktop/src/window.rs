@@ -0,0 +1,163 @@
+//! The interactive frontend: a minifb window paced to the Game Boy's
+//! ~59.73 Hz frame rate.
+//!
+//! Hotkeys: 0–9 select a save-state slot, F5 saves to it, F8 loads it,
+//! P cycles the DMG palette, R starts and stops recording, Escape quits.
+
+use std::{
+    error::Error,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use minifb::{Key, KeyRepeat, Scale, ScaleMode, Window, WindowOptions};
+use rustyboy_core::cpu::peripheral::ppu::CYCLES_PER_FRAME;
+
+use crate::{
+    audio::AudioOutput,
+    input::Input,
+    session::{Session, SessionError, SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+
+/// T-cycles per second of the DMG master clock.
+const CLOCK_HZ: u64 = 4_194_304;
+
+const SLOT_KEYS: [Key; 10] = [
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4,
+    Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+];
+
+pub struct Options {
+    pub scale: Scale,
+    /// Frames run per displayed frame while fast-forward is held.
+    pub fast_forward: u32,
+    /// Audio output rate; `None` for the device's preferred rate.
+    pub sample_rate: Option<u32>,
+    /// Record from the first frame to this file.
+    pub record: Option<PathBuf>,
+}
+
+pub fn run(mut session: Session, options: Options) -> Result<(), Box<dyn Error>> {
+    let title = session
+        .rom_path()
+        .file_stem()
+        .map(|stem| format!("rustyboy — {}", stem.to_string_lossy()))
+        .unwrap_or_else(|| "rustyboy".to_string());
+    let mut window = Window::new(
+        &title,
+        SCREEN_WIDTH,
+        SCREEN_HEIGHT,
+        WindowOptions {
+            resize: true,
+            scale: options.scale,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        },
+    )?;
+
+    let audio = AudioOutput::open(options.sample_rate)
+        .map_err(|e| eprintln!("audio disabled: {e}"))
+        .ok();
+    if let Some(audio) = &audio {
+        session.set_sample_rate(audio.sample_rate());
+    }
+    // After the sample rate is settled, so the WAV header has the right one.
+    if let Some(path) = &options.record {
+        session.start_recording(path)?;
+    }
+    let mut input = Input::open();
+    let mut pixels = vec![0u32; SCREEN_WIDTH * SCREEN_HEIGHT];
+    let mut slot = 0u8;
+
+    let frame_time = Duration::from_nanos(CYCLES_PER_FRAME * 1_000_000_000 / CLOCK_HZ);
+    let mut deadline = Instant::now();
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let state = input.poll(&window);
+        let speed = if state.fast_forward { options.fast_forward } else { 1 };
+        let samples = match session.run_frame(state.buttons, speed) {
+            Ok(samples) => samples,
+            // Only a recording writes files mid-frame; it has stopped.
+            Err(e @ SessionError::Io(..)) => {
+                eprintln!("recording failed: {e}");
+                Vec::new()
+            }
+            Err(e) => {
+                // Keep the player's progress, and the recording that may
+                // show how it got here, even if the game crashed.
+                finish_recording(&mut session);
+                session.save_battery()?;
+                return Err(e.into());
+            }
+        };
+        if let Some(audio) = &audio {
+            audio.queue().push(&samples);
+        }
+
+        for (pixel, rgba) in pixels.iter_mut().zip(session.frame_rgba().chunks_exact(4)) {
+            *pixel = u32::from_be_bytes([0, rgba[0], rgba[1], rgba[2]]);
+        }
+        window.update_with_buffer(&pixels, SCREEN_WIDTH, SCREEN_HEIGHT)?;
+
+        for (n, key) in SLOT_KEYS.iter().enumerate() {
+            if window.is_key_pressed(*key, KeyRepeat::No) {
+                slot = n as u8;
+                window.set_title(&format!("{title} [slot {slot}]"));
+            }
+        }
+        if window.is_key_pressed(Key::F5, KeyRepeat::No) {
+            match session.save_slot(slot) {
+                Ok(path) => println!("saved {}", path.display()),
+                Err(e) => eprintln!("save failed: {e}"),
+            }
+        }
+        if window.is_key_pressed(Key::F8, KeyRepeat::No) {
+            match session.load_slot(slot) {
+                Ok(path) => {
+                    println!("loaded {}", path.display());
+                    if let Some(audio) = &audio {
+                        audio.queue().clear();
+                    }
+                }
+                Err(e) => eprintln!("load failed: {e}"),
+            }
+        }
+        if window.is_key_pressed(Key::P, KeyRepeat::No) {
+            session.set_palette(session.palette_preset().next());
+            println!("palette {}", session.palette_preset().name());
+        }
+        if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            if session.is_recording() {
+                finish_recording(&mut session);
+            } else {
+                let path = session.next_recording_path();
+                match session.start_recording(&path) {
+                    Ok(()) => println!("recording to {}", path.display()),
+                    Err(e) => eprintln!("recording failed: {e}"),
+                }
+            }
+        }
+
+        // Fixed-rate pacing. Falling more than a frame behind (a stall, the
+        // window being dragged) resets the schedule instead of racing to
+        // catch up.
+        deadline += frame_time;
+        let now = Instant::now();
+        if deadline > now {
+            std::thread::sleep(deadline - now);
+        } else if now - deadline > frame_time {
+            deadline = now;
+        }
+    }
+
+    finish_recording(&mut session);
+    session.save_battery()?;
+    Ok(())
+}
+
+fn finish_recording(session: &mut Session) {
+    match session.stop_recording() {
+        Ok(paths) => paths.iter().for_each(|path| println!("wrote {}", path.display())),
+        Err(e) => eprintln!("recording failed: {e}"),
+    }
+}
@@ -7,7 +7,7 @@
 
 use rustyboy_pico2w::display::{
     fb::FbDisplay,
-    Display, SCREEN_H, SCREEN_W,
+    Display, LAYER_PLANE_SIZE, SCREEN_H, SCREEN_W,
 };
 
 fn make_display() -> Display<FbDisplay> {
@@ -54,7 +54,7 @@ fn cmd_frame() {
         }
     }
 
-    disp.render_frame(&fb);
+    disp.render_frame(&fb, &[0; LAYER_PLANE_SIZE]);
     disp.save_png("/tmp/frame.png").expect("failed to write PNG");
     println!("wrote /tmp/frame.png");
 }
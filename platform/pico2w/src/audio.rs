@@ -6,6 +6,7 @@ const AUDIO_BUF_SIZE: usize = 1024;
 static mut AUDIO_BUF_A: [u32; AUDIO_BUF_SIZE] = [0u32; AUDIO_BUF_SIZE];
 static mut AUDIO_BUF_B: [u32; AUDIO_BUF_SIZE] = [0u32; AUDIO_BUF_SIZE];
 
+/// I2S output rate; the APU is set to produce samples at this rate.
 pub const SAMPLE_RATE: u32 = 48_000;
 
 pub struct AudioBuffers {
@@ -51,6 +52,13 @@ impl AudioBuffers {
         }
     }
 
+    /// Stereo pairs of the front buffer still to play `elapsed_us` after
+    /// its DMA started; 0 once it has run dry.
+    pub fn front_remaining(&self, elapsed_us: u64) -> u32 {
+        let played = elapsed_us * SAMPLE_RATE as u64 / 1_000_000;
+        (self.front_n as u64).saturating_sub(played) as u32
+    }
+
     pub fn queue_next_frame(&mut self, samples: &[f32], back_buf: &mut [u32]) {
         let back_n = samples_to_i2s(samples, back_buf);
         self.use_a_as_front = !self.use_a_as_front;
@@ -16,23 +16,27 @@ pub mod fb;
 
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::pixelcolor::raw::RawU16;
 use embedded_graphics::pixelcolor::Rgb565;
-use embedded_graphics::prelude::{IntoStorage, RgbColor};
+use embedded_graphics::prelude::RgbColor;
 use embedded_graphics::primitives::Rectangle;
+use rustyboy_core::cpu::peripheral::ppu::palette::pixel_layer;
+// Re-exported for display-viewer, which doesn't depend on the core directly.
+pub use rustyboy_core::cpu::peripheral::ppu::palette::{Palette, LAYER_PLANE_SIZE};
 
 // ---------------------------------------------------------------------------
-// DMG colour palette — matches the web platform exactly.
-// Source: platform/web/client/src/lib.rs PALETTE constant.
+// DMG green — the splash and letterbox colours, and the default game palette.
+// Source: rustyboy_core `Palette::DMG_GREEN`, shared with the web platform.
 // ---------------------------------------------------------------------------
 
 /// Lightest (colour 0) — #E0F8D0
-pub const C0: Rgb565 = Rgb565::new(0xE0 >> 3, 0xF8 >> 2, 0xD0 >> 3);
+pub const C0: Rgb565 = rgb565(Palette::DMG_GREEN.bg[0]);
 /// Light (colour 1) — #88C070
-pub const C1: Rgb565 = Rgb565::new(0x88 >> 3, 0xC0 >> 2, 0x70 >> 3);
+pub const C1: Rgb565 = rgb565(Palette::DMG_GREEN.bg[1]);
 /// Dark (colour 2) — #346856
-pub const C2: Rgb565 = Rgb565::new(0x34 >> 3, 0x68 >> 2, 0x56 >> 3);
+pub const C2: Rgb565 = rgb565(Palette::DMG_GREEN.bg[2]);
 /// Darkest (colour 3) — #081820
-pub const C3: Rgb565 = Rgb565::new(0x08 >> 3, 0x18 >> 2, 0x20 >> 3);
+pub const C3: Rgb565 = rgb565(Palette::DMG_GREEN.bg[3]);
 
 // ---------------------------------------------------------------------------
 // Geometry constants
@@ -61,40 +65,59 @@ const LOGO_SPEED: i32 = 4; // px per frame
 
 pub struct Display<D> {
     inner: D,
+    /// Game colours, indexed by `[layer][shade]`; see [`Self::set_palette`].
+    palette: [[Rgb565; 4]; 3],
 }
 
-/// Pre-scale a Game Boy framebuffer (160×144, palette indices) to 240×216 Rgb565.
+/// Pre-scale a Game Boy framebuffer (160×144, shades plus layer plane) to
+/// 240×216 Rgb565.
 ///
-/// Stores raw `u16` Rgb565 storage values. Pass the result to
-/// [`Display::render_game_only_scaled`] or, in Phase C, directly to DMA.
-pub fn scale_to_rgb565(src: &[u8; 23040], dst: &mut [u16; 51840]) {
+/// `colors` is a [`Palette::rgb565_table`]. Stores raw `u16` Rgb565 storage
+/// values. Pass the result to [`Display::render_game_only_scaled`] or, in
+/// Phase C, directly to DMA.
+pub fn scale_to_rgb565(
+    src: &[u8; 23040],
+    layers: &[u8; LAYER_PLANE_SIZE],
+    colors: &[[u16; 4]; 3],
+    dst: &mut [u16; 51840],
+) {
+    // Store big-endian so bytemuck::cast_slice in send_frame_raw gives the
+    // correct SPI byte order without an extra copy.
+    let colors = colors.map(|layer| layer.map(u16::swap_bytes));
     for sy in 0..216usize {
         let gy = sy * 2 / 3;
         let src_row = &src[gy * 160..(gy + 1) * 160];
         let dst_row = &mut dst[sy * 240..(sy + 1) * 240];
         for sx in 0..240usize {
-            // Store big-endian so bytemuck::cast_slice in send_frame_raw gives
-            // the correct SPI byte order without an extra copy.
-            dst_row[sx] = dmg_color(src_row[sx * 2 / 3]).into_storage().swap_bytes();
+            let gx = sx * 2 / 3;
+            let layer = pixel_layer(layers, gy * 160 + gx) as usize;
+            dst_row[sx] = colors[layer][(src_row[gx] & 3) as usize];
         }
     }
 }
 
 impl<D: DrawTarget<Color = Rgb565> + Dimensions> Display<D> {
     pub fn from_draw_target(inner: D) -> Self {
-        Self { inner }
+        let mut display = Self { inner, palette: [[C0; 4]; 3] };
+        display.set_palette(&Palette::DMG_GREEN);
+        display
+    }
+
+    /// Colours used by [`Self::render_frame`] and [`Self::render_game_only`].
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.palette = palette.rgb565_table().map(|layer| layer.map(|raw| Rgb565::from(RawU16::new(raw))));
     }
 
     // -----------------------------------------------------------------------
     // Frame rendering
     // -----------------------------------------------------------------------
 
-    /// Render a Game Boy framebuffer (160×144, palette indices 0–3).
+    /// Render a Game Boy framebuffer (160×144, shades 0–3) and its layer plane.
     ///
     /// Scales 1.5× to 240×216 via nearest-neighbour and fills letterbox bars.
-    pub fn render_frame(&mut self, fb: &[u8; 23040]) {
+    pub fn render_frame(&mut self, fb: &[u8; 23040], layers: &[u8; LAYER_PLANE_SIZE]) {
         self.fill_bar(0, Y_OFFSET, C3);
-        self.fill_scaled_frame(fb);
+        self.fill_scaled_frame(fb, layers);
         self.fill_bar(Y_OFFSET + SCALED_H, Y_OFFSET, Rgb565::BLACK);
     }
 
@@ -111,8 +134,8 @@ impl<D: DrawTarget<Color = Rgb565> + Dimensions> Display<D> {
     ///
     /// Saves ~6.4 ms per frame vs [`render_frame`] by not repainting the static
     /// top/bottom bars. Call [`draw_letterbox_bars`] once before the game loop.
-    pub fn render_game_only(&mut self, fb: &[u8; 23040]) {
-        self.fill_scaled_frame(fb);
+    pub fn render_game_only(&mut self, fb: &[u8; 23040], layers: &[u8; LAYER_PLANE_SIZE]) {
+        self.fill_scaled_frame(fb, layers);
     }
 
     /// Render a pre-scaled 240×216 frame from a [`scale_to_rgb565`] buffer.
@@ -120,7 +143,6 @@ impl<D: DrawTarget<Color = Rgb565> + Dimensions> Display<D> {
     /// Eliminates per-pixel scaling work during the SPI transfer. The buffer
     /// holds raw `Rgb565` storage values produced by [`scale_to_rgb565`].
     pub fn render_game_only_scaled(&mut self, buf: &[u16; 51840]) {
-        use embedded_graphics::pixelcolor::raw::RawU16;
         let rect = Rectangle::new(
             Point::new(0, Y_OFFSET),
             Size::new(SCALED_W as u32, SCALED_H as u32),
@@ -141,17 +163,19 @@ impl<D: DrawTarget<Color = Rgb565> + Dimensions> Display<D> {
         let _ = self.inner.fill_contiguous(&rect, core::iter::repeat(color));
     }
 
-    fn fill_scaled_frame(&mut self, fb: &[u8; 23040]) {
+    fn fill_scaled_frame(&mut self, fb: &[u8; 23040], layers: &[u8; LAYER_PLANE_SIZE]) {
         let rect = Rectangle::new(
             Point::new(0, Y_OFFSET),
             Size::new(SCALED_W as u32, SCALED_H as u32),
         );
+        let palette = &self.palette;
         let pixels = (0..SCALED_W * SCALED_H).map(|i| {
             let sx = i % SCALED_W;
             let sy = i / SCALED_W;
             let gx = (sx * 2 / 3) as usize;
             let gy = (sy * 2 / 3) as usize;
-            dmg_color(fb[gy * GB_W as usize + gx])
+            let index = gy * GB_W as usize + gx;
+            palette[pixel_layer(layers, index) as usize][(fb[index] & 3) as usize]
         });
         let _ = self.inner.fill_contiguous(&rect, pixels);
     }
@@ -275,6 +299,12 @@ impl<D: DrawTarget<Color = Rgb565> + Dimensions> Display<D> {
 // Palette
 // ---------------------------------------------------------------------------
 
+/// `const` counterpart of `rustyboy_core`'s `rgb888_to_rgb565`, for the
+/// fixed splash colours.
+const fn rgb565(rgb: u32) -> Rgb565 {
+    Rgb565::new((rgb >> 19) as u8 & 0x1F, (rgb >> 10) as u8 & 0x3F, (rgb >> 3) as u8 & 0x1F)
+}
+
 // ---------------------------------------------------------------------------
 // FbDisplay convenience — PNG export
 // ---------------------------------------------------------------------------
@@ -287,17 +317,6 @@ impl Display<fb::FbDisplay> {
     }
 }
 
-/// Map a DMG palette index (0–3) to an RGB565 colour.
-#[inline(always)]
-pub fn dmg_color(idx: u8) -> Rgb565 {
-    match idx {
-        0 => C0,
-        1 => C1,
-        2 => C2,
-        _ => C3,
-    }
-}
-
 // ---------------------------------------------------------------------------
 // Font bitmaps
 // ---------------------------------------------------------------------------
@@ -339,13 +358,32 @@ mod tests {
         Display::from_draw_target(FbDisplay::new(SCREEN_W as u32, SCREEN_H as u32))
     }
 
+    const NO_LAYERS: [u8; LAYER_PLANE_SIZE] = [0; LAYER_PLANE_SIZE];
+
+    #[test]
+    fn dmg_green_matches_core_palette() {
+        let table = Palette::DMG_GREEN.rgb565_table()[0];
+        for (c, raw) in [C0, C1, C2, C3].into_iter().zip(table) {
+            assert_eq!(c.into_storage(), raw);
+        }
+    }
+
     #[test]
-    fn dmg_color_palette() {
-        assert_eq!(dmg_color(0), C0);
-        assert_eq!(dmg_color(1), C1);
-        assert_eq!(dmg_color(2), C2);
-        assert_eq!(dmg_color(3), C3);
-        assert_eq!(dmg_color(4), C3); // out-of-range → darkest
+    fn scale_to_rgb565_colors_objects_by_layer() {
+        let palette = Palette {
+            bg: [0xFFFFFF; 4],
+            obj0: [0xFF0000; 4],
+            obj1: [0x0000FF; 4],
+        };
+        let fb = [0u8; 23040];
+        let mut layers = NO_LAYERS;
+        layers[0] = 0b10_01; // pixel 0 OBJ0, pixel 1 OBJ1
+        let mut dst = [0u16; 51840];
+        scale_to_rgb565(&fb, &layers, &palette.rgb565_table(), &mut dst);
+        // Screen pixels 0, 1 map to GB pixel 0; screen pixel 2 to GB pixel 1.
+        assert_eq!(dst[0], 0xF800u16.swap_bytes());
+        assert_eq!(dst[2], 0x001Fu16.swap_bytes());
+        assert_eq!(dst[3], 0xFFFF);
     }
 
     #[test]
@@ -353,7 +391,7 @@ mod tests {
         let mut disp = make_display();
         let fb = [0u8; 23040]; // all C0
 
-        disp.render_frame(&fb);
+        disp.render_frame(&fb, &NO_LAYERS);
 
         let fb_ref = disp.inner.as_ref();
         // Top bar should be C3
@@ -374,7 +412,7 @@ mod tests {
         for i in 0..23040 {
             fb[i] = (i / (23040 / 4)) as u8;
         }
-        disp.render_frame(&fb);
+        disp.render_frame(&fb, &NO_LAYERS);
         let pixels = disp.inner.as_ref();
         let colors_present: std::collections::HashSet<u16> =
             pixels.iter().map(|p| p.into_storage()).collect();
@@ -423,7 +461,7 @@ mod tests {
     fn render_game_only_updates_game_region() {
         let mut disp = make_display();
         let fb = [1u8; 23040]; // all palette index 1 → C1
-        disp.render_game_only(&fb);
+        disp.render_game_only(&fb, &NO_LAYERS);
         let fb_ref = disp.inner.as_ref();
         let mid = (Y_OFFSET * SCREEN_W) as usize;
         assert_eq!(fb_ref[mid], C1, "game region pixel should be C1");
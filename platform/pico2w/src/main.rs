@@ -22,7 +22,7 @@ use embedded_alloc::Heap;
 #[global_allocator]
 static HEAP: Heap = Heap::empty();
 
-use defmt::{error, info, warn};
+use defmt::{error, info};
 use embassy_executor::Spawner;
 use embassy_rp::gpio::{Level, Output};
 use embassy_rp::peripherals::{DMA_CH0, DMA_CH1, PIN_10, PIN_11, PIN_12, PIN_13, PIN_8, PIN_9, PIO0, SPI1};
@@ -31,13 +31,14 @@ use embassy_rp::pio_programs::i2s::{PioI2sOut, PioI2sOutProgram};
 use embassy_rp::spi::{self, Spi};
 use embassy_rp::watchdog::Watchdog;
 use embassy_rp::{bind_interrupts, dma};
-use embassy_time::{Delay, Duration, Timer};
+use embassy_time::{Delay, Duration, Instant, Timer};
 use embedded_hal_bus::spi::ExclusiveDevice;
 use embedded_sdmmc::{SdCard, VolumeManager};
 use {defmt_rtt as _, panic_probe as _};
 
-use rustyboy_core::cpu::cpu::Cpu;
+use rustyboy_core::cpu::av_sync::{AvSync, SyncMode};
 use rustyboy_core::cpu::instructions::opcodes::OpCodeDecoder;
+use rustyboy_core::cpu::peripheral::apu::Resampler;
 use rustyboy_core::cpu::peripheral::joypad::Button;
 use rustyboy_core::cpu::registers::{Flags, Registers};
 use rustyboy_core::cpu::sm83::Sm83;
@@ -49,7 +50,7 @@ use rustyboy_pico2w::flash_rom::{
     new_onboard_flash, probe_staged_rom, stage_rom_from_reader,
 };
 use rustyboy_pico2w::input::{ButtonState, InputHandler};
-use rustyboy_pico2w::sd::{DummyClock, SdRomReader};
+use rustyboy_pico2w::sd::{read_boot_rom, read_palette, DummyClock, SdRomReader, BOOT_ROM_FILE};
 use rustyboy_pico2w::stack_probe;
 use rustyboy_pico2w::xip_cartridge::XipCartridge;
 
@@ -62,7 +63,10 @@ const TARGET_CORE_VOLTAGE: embassy_rp::clocks::CoreVoltage =
     embassy_rp::clocks::CoreVoltage::V1_20;
 
 const FIRMWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
-const CYCLES_PER_FRAME: u64 = 70_224;
+
+/// Audio the front I2S buffer should still hold when the next frame's is
+/// queued; the slack for a frame that runs a little long.
+const AUDIO_MARGIN_MS: u32 = 4;
 
 bind_interrupts!(struct Irqs {
     PIO0_IRQ_0 => PioIrqHandler<PIO0>;
@@ -144,6 +148,24 @@ async fn main(_spawner: Spawner) {
         p.PIN_21, p.PIN_22, p.PIN_26, p.PIN_27, p.PIN_0, p.PIN_1, p.PIN_2, p.PIN_3,
     );
 
+    let mut spi_cfg = spi::Config::default();
+    spi_cfg.frequency = 400_000;
+    let spi_bus = Spi::new_blocking(p.SPI0, p.PIN_6, p.PIN_7, p.PIN_4, spi_cfg);
+    // SD card MISO (GP4) is open-collector — enable the internal pull-up.
+    rp_pac::PADS_BANK0.gpio(4).modify(|w| w.set_pue(true));
+    let spi_dev = ExclusiveDevice::new(spi_bus, Output::new(p.PIN_5, Level::High), Delay);
+    let sdcard = SdCard::new(spi_dev, Delay);
+    let mgr = VolumeManager::new(sdcard, DummyClock);
+
+    // Optional DMG boot ROM; absent (or no card) means start in post-boot state.
+    let boot_rom = read_boot_rom(&mgr);
+    if boot_rom.is_some() {
+        info!("{} found on SD; running boot ROM", BOOT_ROM_FILE);
+    }
+    // Optional palette choice; Start+Select cycles through the rest in game.
+    let mut palette_preset = read_palette(&mgr).unwrap_or_default();
+    info!("palette: {}", palette_preset.name());
+
     let mut onboard_flash = new_onboard_flash(p.FLASH);
     let flash_info = if let Some(info) = probe_staged_rom(&mut onboard_flash) {
         info!(
@@ -151,19 +173,11 @@ async fn main(_spawner: Spawner) {
             info.bank_count,
             info.size_bytes / 1024
         );
+        drop(mgr); // release the SD card; the ROM runs from flash
         info
     } else {
         info!("no staged ROM in flash; loading from SD");
 
-        let mut spi_cfg = spi::Config::default();
-        spi_cfg.frequency = 400_000;
-        let spi_bus = Spi::new_blocking(p.SPI0, p.PIN_6, p.PIN_7, p.PIN_4, spi_cfg);
-        // SD card MISO (GP4) is open-collector — enable the internal pull-up.
-        rp_pac::PADS_BANK0.gpio(4).modify(|w| w.set_pue(true));
-        let spi_dev = ExclusiveDevice::new(spi_bus, Output::new(p.PIN_5, Level::High), Delay);
-        let sdcard = SdCard::new(spi_dev, Delay);
-        let mgr = VolumeManager::new(sdcard, DummyClock);
-
         let mut reader = match SdRomReader::new(mgr) {
             Ok(r) => r,
             Err(e) => {
@@ -208,22 +222,34 @@ async fn main(_spawner: Spawner) {
     info!("building OpCodeDecoder");
     let decoder = alloc::boxed::Box::new(OpCodeDecoder::new());
     info!("building Sm83 CPU");
-    let mut cpu = Sm83::new(alloc::boxed::Box::new(memory), decoder)
-        .with_registers(Registers {
-            a: 0x01,
-            f: Flags::from_bits_truncate(0xB0),
-            b: 0x00,
-            c: 0x13,
-            d: 0x00,
-            e: 0xD8,
-            h: 0x01,
-            l: 0x4D,
-            pc: 0x0100,
-            sp: 0xFFFE,
-        })
-        .with_dmg_state();
+    // Nearest-sample resampling keeps the APU inside the frame budget; the
+    // band-limited one costs 16 multiply-adds per mixer level change.
+    let cpu = Sm83::new(alloc::boxed::Box::new(memory), decoder)
+        .with_sample_rate(SAMPLE_RATE)
+        .with_resampler(Resampler::Nearest);
+    let mut cpu = match boot_rom {
+        Some(image) => cpu.with_boot_rom(image),
+        None => cpu
+            .with_registers(Registers {
+                a: 0x01,
+                f: Flags::from_bits_truncate(0xB0),
+                b: 0x00,
+                c: 0x13,
+                d: 0x00,
+                e: 0xD8,
+                h: 0x01,
+                l: 0x4D,
+                pc: 0x0100,
+                sp: 0xFFFE,
+            })
+            .with_dmg_state(),
+    };
     info!("ROM loaded, starting peripheral init");
 
+    // The `cgb` preset picks a palette from the cartridge header.
+    let header: [u8; 0x150] = core::array::from_fn(|addr| cpu.read_memory(addr as u16).unwrap_or(0));
+    let mut palette = palette_preset.palette(&header).rgb565_table();
+
     // I2S audio: GP14=BCLK  GP15=LRCLK  GP16=DIN  GP17=SD_MODE (MAX98357A).
     // Drive SD_MODE high to enable the amplifier.
     let _sd_mode = Output::new(p.PIN_17, Level::High);
@@ -267,6 +293,11 @@ async fn main(_spawner: Spawner) {
     perf::init_dwt();
 
     let mut audio_buffers = AudioBuffers::new();
+    // The loop runs one frame per audio buffer, so only the rate-control
+    // half of AvSync is used: it stretches the audio slightly while frames
+    // run late, so the I2S DMA has queued a little more when the next one
+    // is due.
+    let mut av_sync = AvSync::new(SyncMode::Video, SAMPLE_RATE).with_target_latency_ms(AUDIO_MARGIN_MS);
     let mut prev_state = ButtonState::default();
 
     #[cfg(feature = "fps")]
@@ -278,7 +309,7 @@ async fn main(_spawner: Spawner) {
         // Pre-scale current frame into the buffer (~0.5 ms).
         #[cfg(feature = "perf")]
         let scale_start = perf::perf_cycle_read();
-        scale_to_rgb565(cpu.framebuffer(), frame_buf);
+        scale_to_rgb565(cpu.framebuffer(), cpu.framebuffer_layers(), &palette, frame_buf);
         #[cfg(feature = "perf")]
         tracker.record_scale(perf::perf_cycle_read().wrapping_sub(scale_start));
 
@@ -291,12 +322,12 @@ async fn main(_spawner: Spawner) {
         let (front_buf, back_buf) = audio_buffers.front_back_buffers();
         let mut audio_future = core::pin::pin!(i2s.write(front_buf));
         let _ = poll_once(audio_future.as_mut());
+        let audio_start = Instant::now();
 
-        // Run exactly one Game Boy frame (~16.74 ms).
+        // Run up to the next VBlank (one Game Boy frame, ~16.74 ms).
         // Both DMAs run while the CPU emulates — display finishes at ~13 ms.
-        let frame_start = cpu.cycle_counter();
-        while cpu.cycle_counter().wrapping_sub(frame_start) < CYCLES_PER_FRAME {
-            let _ = cpu.tick();
+        if let Err(e) = cpu.run_frame() {
+            error!("CPU error: {}", defmt::Display2Format(&e));
         }
 
         // Propagate button changes to the CPU.
@@ -311,11 +342,17 @@ async fn main(_spawner: Spawner) {
         }
         prev_state = state;
         if menu {
-            warn!("menu combo triggered");
+            // Until the in-game menu exists, the combo cycles the palette.
+            palette_preset = palette_preset.next();
+            palette = palette_preset.palette(&header).rgb565_table();
+            info!("menu combo: palette {}", palette_preset.name());
         }
 
-        // Fill audio back-buffer from APU output.
+        // Fill audio back-buffer from APU output, then size the next frame's
+        // audio by how much of the front buffer was still left to play.
         let samples = cpu.drain_audio_samples();
+        av_sync.measure(audio_buffers.front_remaining(audio_start.elapsed().as_micros()));
+        cpu.set_audio_rate_skew(av_sync.rate_skew());
         audio_buffers.queue_next_frame(&samples, back_buf);
 
         // Await display DMA — should already be done (~13 ms < ~16.7 ms emulation).
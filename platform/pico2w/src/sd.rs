@@ -4,8 +4,16 @@ use embedded_sdmmc::{
     VolumeManager,
 };
 
+use rustyboy_core::cpu::peripheral::ppu::palette::PalettePreset;
+use rustyboy_core::memory::memory::BOOT_ROM_SIZE;
 use rustyboy_core::memory::RomReader;
 
+/// Name of the optional DMG boot ROM image in the card's root directory.
+pub const BOOT_ROM_FILE: &str = "DMG_BOOT.BIN";
+/// Name of the optional palette setting in the card's root directory: one
+/// preset name such as `pocket`, see [`PalettePreset::name`].
+pub const PALETTE_FILE: &str = "PALETTE.TXT";
+
 // ── Time source ───────────────────────────────────────────────────────────────
 
 pub struct DummyClock;
@@ -98,6 +106,96 @@ where
     }
 }
 
+// ── Boot ROM ──────────────────────────────────────────────────────────────────
+
+/// Read `DMG_BOOT.BIN` from the root of the first partition.
+///
+/// Returns `None` when the card, the file, or a full 256-byte image is
+/// missing; the caller then falls back to the post-boot register state.
+pub fn read_boot_rom<D, T>(mgr: &VolumeManager<D, T>) -> Option<[u8; BOOT_ROM_SIZE]>
+where
+    D: BlockDevice,
+    <D as BlockDevice>::Error: core::fmt::Debug,
+    T: TimeSource,
+{
+    let volume = mgr.open_raw_volume(VolumeIdx(0)).ok()?;
+    let image = read_boot_rom_from_volume(mgr, volume);
+    let _ = mgr.close_volume(volume);
+    image
+}
+
+fn read_boot_rom_from_volume<D, T>(
+    mgr: &VolumeManager<D, T>,
+    volume: RawVolume,
+) -> Option<[u8; BOOT_ROM_SIZE]>
+where
+    D: BlockDevice,
+    <D as BlockDevice>::Error: core::fmt::Debug,
+    T: TimeSource,
+{
+    let root = mgr.open_root_dir(volume).ok()?;
+    let file = mgr.open_file_in_dir(root, BOOT_ROM_FILE, Mode::ReadOnly);
+    let _ = mgr.close_dir(root);
+    let file = file.ok()?;
+
+    let len = mgr.file_length(file).unwrap_or(0);
+    if len as usize != BOOT_ROM_SIZE {
+        warn!("{} is {} B, expected {} B; ignoring", BOOT_ROM_FILE, len, BOOT_ROM_SIZE);
+        let _ = mgr.close_file(file);
+        return None;
+    }
+
+    let mut image = [0u8; BOOT_ROM_SIZE];
+    let mut total = 0;
+    while total < BOOT_ROM_SIZE {
+        match mgr.read(file, &mut image[total..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => total += n,
+        }
+    }
+    let _ = mgr.close_file(file);
+
+    (total == BOOT_ROM_SIZE).then_some(image)
+}
+
+// ── Palette ───────────────────────────────────────────────────────────────────
+
+/// Read the palette preset named in `PALETTE.TXT`.
+///
+/// Returns `None` when the card or the file is missing, or the name is not
+/// a preset; the caller then uses the default.
+pub fn read_palette<D, T>(mgr: &VolumeManager<D, T>) -> Option<PalettePreset>
+where
+    D: BlockDevice,
+    <D as BlockDevice>::Error: core::fmt::Debug,
+    T: TimeSource,
+{
+    let volume = mgr.open_raw_volume(VolumeIdx(0)).ok()?;
+    let root = mgr.open_root_dir(volume);
+    let file = root.and_then(|root| {
+        let file = mgr.open_file_in_dir(root, PALETTE_FILE, Mode::ReadOnly);
+        let _ = mgr.close_dir(root);
+        file
+    });
+    let mut name = [0u8; 32];
+    let len = match file {
+        Ok(file) => {
+            let len = mgr.read(file, &mut name).unwrap_or(0);
+            let _ = mgr.close_file(file);
+            len
+        }
+        Err(_) => 0,
+    };
+    let _ = mgr.close_volume(volume);
+
+    let name = core::str::from_utf8(&name[..len]).ok()?.trim();
+    let preset = PalettePreset::from_name(name);
+    if preset.is_none() && !name.is_empty() {
+        warn!("{}: unknown palette {}; using the default", PALETTE_FILE, name);
+    }
+    preset
+}
+
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
 fn find_rom_in_dir<D, T>(
@@ -16,6 +16,8 @@ const ROM_BANK_BYTES: usize = 0x4000;
 const CART_TYPE: usize = 0x0147;
 const ROM_SIZE: usize = 0x0148;
 const RAM_SIZE: usize = 0x0149;
+/// MBC2 carts report no RAM in the header but carry 512 half-bytes on-chip.
+const MBC2_RAM_BYTES: usize = 512;
 
 enum MbcState {
     NoMbc,
@@ -26,11 +28,22 @@ enum MbcState {
         ram_enabled: bool,
         ram_bank_count: usize,
     },
+    Mbc2 {
+        rom_bank: u8,
+        ram_enabled: bool,
+    },
     Mbc3 {
         rom_bank: u8,
         bank_or_rtc: u8,
         ram_rtc_enabled: bool,
     },
+    Mbc5 {
+        rom_bank: u16,
+        ram_bank: u8,
+        ram_enabled: bool,
+        has_rumble: bool,
+        ram_bank_count: usize,
+    },
 }
 
 #[derive(Debug)]
@@ -62,7 +75,11 @@ impl XipCartridge {
         let cart_type = *rom.get(CART_TYPE).unwrap_or(&0);
         let rom_bank_count = rom_bank_count_from_code(*rom.get(ROM_SIZE).unwrap_or(&0))
             .ok_or(XipCartridgeError::UnsupportedCartType(cart_type))?;
-        let ram_bytes = ram_bytes_from_code(*rom.get(RAM_SIZE).unwrap_or(&0));
+        let ram_bytes = if matches!(cart_type, 0x05 | 0x06) {
+            MBC2_RAM_BYTES
+        } else {
+            ram_bytes_from_code(*rom.get(RAM_SIZE).unwrap_or(&0))
+        };
         let expected_bytes = rom_bank_count * ROM_BANK_BYTES;
         if rom.len() < expected_bytes {
             return Err(XipCartridgeError::RomTooSmall {
@@ -124,7 +141,9 @@ impl XipCartridge {
                 let bank = if bank == 0 { 1 } else { bank };
                 (fixed, bank % self.rom_bank_count)
             }
+            MbcState::Mbc2 { rom_bank, .. } => (0, *rom_bank as usize % self.rom_bank_count),
             MbcState::Mbc3 { rom_bank, .. } => (0, *rom_bank as usize),
+            MbcState::Mbc5 { rom_bank, .. } => (0, *rom_bank as usize % self.rom_bank_count),
         };
 
         self.fixed_bank_num = fixed_bank_num;
@@ -186,6 +205,25 @@ impl XipCartridge {
                 }
                 _ => false,
             },
+            MbcState::Mbc2 {
+                rom_bank,
+                ram_enabled,
+            } => match addr {
+                0x0000..=0x3FFF if addr & 0x0100 == 0 => {
+                    *ram_enabled = value & 0x0F == 0x0A;
+                    false
+                }
+                0x0000..=0x3FFF => {
+                    let bank = if value & 0x0F == 0 { 1 } else { value & 0x0F };
+                    if *rom_bank == bank {
+                        false
+                    } else {
+                        *rom_bank = bank;
+                        true
+                    }
+                }
+                _ => false,
+            },
             MbcState::Mbc3 {
                 rom_bank,
                 bank_or_rtc,
@@ -210,11 +248,37 @@ impl XipCartridge {
                 }
                 _ => false,
             },
+            MbcState::Mbc5 {
+                rom_bank,
+                ram_bank,
+                ram_enabled,
+                ..
+            } => {
+                let new_bank = match addr {
+                    0x0000..=0x1FFF => {
+                        *ram_enabled = value == 0x0A;
+                        return false;
+                    }
+                    0x2000..=0x2FFF => (*rom_bank & 0x100) | value as u16,
+                    0x3000..=0x3FFF => (*rom_bank & 0xFF) | (((value & 0x01) as u16) << 8),
+                    0x4000..=0x5FFF => {
+                        *ram_bank = value & 0x0F;
+                        return false;
+                    }
+                    _ => return false,
+                };
+                if *rom_bank == new_bank {
+                    false
+                } else {
+                    *rom_bank = new_bank;
+                    true
+                }
+            }
         }
     }
 
     #[inline]
-    fn mbc1_ram_bank(&self) -> usize {
+    fn ram_bank(&self) -> usize {
         match &self.mbc {
             MbcState::Mbc1 {
                 upper_bits,
@@ -222,6 +286,15 @@ impl XipCartridge {
                 ram_bank_count,
                 ..
             } => (*upper_bits as usize) % (*ram_bank_count).max(1),
+            MbcState::Mbc5 {
+                ram_bank,
+                has_rumble,
+                ram_bank_count,
+                ..
+            } => {
+                let mask = if *has_rumble { 0x07 } else { 0x0F };
+                (*ram_bank & mask) as usize % (*ram_bank_count).max(1)
+            }
             _ => 0,
         }
     }
@@ -231,11 +304,13 @@ impl XipCartridge {
         match &self.mbc {
             MbcState::NoMbc => false,
             MbcState::Mbc1 { ram_enabled, .. } => *ram_enabled,
+            MbcState::Mbc2 { ram_enabled, .. } => *ram_enabled,
             MbcState::Mbc3 {
                 ram_rtc_enabled,
                 bank_or_rtc,
                 ..
             } => *ram_rtc_enabled && !matches!(bank_or_rtc, 0x08..=0x0C),
+            MbcState::Mbc5 { ram_enabled, .. } => *ram_enabled,
         }
     }
 
@@ -291,7 +366,10 @@ impl Cartridge for XipCartridge {
         if !self.is_ram_enabled() || self.ram.is_empty() {
             return 0xFF;
         }
-        let offset = self.mbc1_ram_bank() * 0x2000 + addr as usize;
+        if let MbcState::Mbc2 { .. } = self.mbc {
+            return self.ram[addr as usize % MBC2_RAM_BYTES] | 0xF0;
+        }
+        let offset = self.ram_bank() * 0x2000 + addr as usize;
         self.ram.get(offset).copied().unwrap_or(0xFF)
     }
 
@@ -300,9 +378,13 @@ impl Cartridge for XipCartridge {
             #[cfg(feature = "perf")]
             let t_ram = cyccnt();
             if self.is_ram_enabled() && !self.ram.is_empty() {
-                let offset = self.mbc1_ram_bank() * 0x2000 + (addr - 0xA000) as usize;
-                if let Some(b) = self.ram.get_mut(offset) {
-                    *b = value;
+                if let MbcState::Mbc2 { .. } = self.mbc {
+                    self.ram[(addr - 0xA000) as usize % MBC2_RAM_BYTES] = value & 0x0F;
+                } else {
+                    let offset = self.ram_bank() * 0x2000 + (addr - 0xA000) as usize;
+                    if let Some(b) = self.ram.get_mut(offset) {
+                        *b = value;
+                    }
                 }
             }
             #[cfg(feature = "perf")]
@@ -343,6 +425,17 @@ impl Cartridge for XipCartridge {
         self.current_bank_num
     }
 
+    fn rumble_active(&self) -> bool {
+        matches!(
+            self.mbc,
+            MbcState::Mbc5 {
+                has_rumble: true,
+                ram_bank,
+                ..
+            } if ram_bank & 0x08 != 0
+        )
+    }
+
     fn external_ram(&self) -> Option<&[u8]> {
         if self.ram.is_empty() {
             None
@@ -351,6 +444,27 @@ impl Cartridge for XipCartridge {
         }
     }
 
+    fn external_ram_mut(&mut self) -> Option<&mut [u8]> {
+        if self.ram.is_empty() {
+            None
+        } else {
+            Some(&mut self.ram)
+        }
+    }
+
+    fn poke_external_ram(&mut self, bank: usize, offset: u16, value: u8) {
+        if let MbcState::Mbc2 { .. } = self.mbc {
+            if bank == 0 {
+                self.ram[offset as usize % MBC2_RAM_BYTES] = value & 0x0F;
+            }
+            return;
+        }
+        let index = bank * 0x2000 + offset as usize;
+        if let Some(b) = self.ram.get_mut(index) {
+            *b = value;
+        }
+    }
+
     fn set_external_ram(&mut self, data: &[u8]) {
         let len = self.ram.len().min(data.len());
         self.ram[..len].copy_from_slice(&data[..len]);
@@ -373,6 +487,12 @@ impl Cartridge for XipCartridge {
                     *ram_enabled as u8,
                 ]);
             }
+            MbcState::Mbc2 {
+                rom_bank,
+                ram_enabled,
+            } => {
+                out.extend_from_slice(&[*rom_bank, *ram_enabled as u8]);
+            }
             MbcState::Mbc3 {
                 rom_bank,
                 bank_or_rtc,
@@ -380,6 +500,15 @@ impl Cartridge for XipCartridge {
             } => {
                 out.extend_from_slice(&[*rom_bank, *bank_or_rtc, *ram_rtc_enabled as u8]);
             }
+            MbcState::Mbc5 {
+                rom_bank,
+                ram_bank,
+                ram_enabled,
+                ..
+            } => {
+                out.extend_from_slice(&rom_bank.to_le_bytes());
+                out.extend_from_slice(&[*ram_bank, *ram_enabled as u8]);
+            }
         }
     }
 
@@ -402,6 +531,17 @@ impl Cartridge for XipCartridge {
                 *ram_enabled = data[offset + 3] != 0;
                 4
             }
+            MbcState::Mbc2 {
+                rom_bank,
+                ram_enabled,
+            } => {
+                if data.len() < offset + 2 {
+                    return 0;
+                }
+                *rom_bank = (data[offset] & 0x0F).max(1);
+                *ram_enabled = data[offset + 1] != 0;
+                2
+            }
             MbcState::Mbc3 {
                 rom_bank,
                 bank_or_rtc,
@@ -415,6 +555,20 @@ impl Cartridge for XipCartridge {
                 *ram_rtc_enabled = data[offset + 2] != 0;
                 3
             }
+            MbcState::Mbc5 {
+                rom_bank,
+                ram_bank,
+                ram_enabled,
+                ..
+            } => {
+                if data.len() < offset + 4 {
+                    return 0;
+                }
+                *rom_bank = u16::from_le_bytes([data[offset], data[offset + 1]]) & 0x1FF;
+                *ram_bank = data[offset + 2] & 0x0F;
+                *ram_enabled = data[offset + 3] != 0;
+                4
+            }
         };
         if consumed > 0 {
             self.refresh_mappings();
@@ -464,11 +618,22 @@ fn mbc_state_from_header(cart_type: u8, ram_bytes: usize) -> Option<MbcState> {
             ram_enabled: false,
             ram_bank_count,
         }),
+        0x05 | 0x06 => Some(MbcState::Mbc2 {
+            rom_bank: 1,
+            ram_enabled: false,
+        }),
         0x0F | 0x10 | 0x11 | 0x12 | 0x13 => Some(MbcState::Mbc3 {
             rom_bank: 1,
             bank_or_rtc: 0,
             ram_rtc_enabled: false,
         }),
+        0x19..=0x1E => Some(MbcState::Mbc5 {
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enabled: false,
+            has_rumble: matches!(cart_type, 0x1C..=0x1E),
+            ram_bank_count,
+        }),
         _ => None,
     }
 }
@@ -525,6 +690,49 @@ mod tests {
         assert_eq!(cart.read_rom(0x4000), 0xFF);
     }
 
+    #[test]
+    fn mbc5_bank_zero_and_high_bit_map_through_xip_slice() {
+        let mut cart = XipCartridge::new(leak_rom(64, 0x19, 0x00)).unwrap();
+        cart.write(0x2000, 0x00);
+        assert_eq!(cart.current_rom_bank(), 0);
+        assert_eq!(cart.read_rom(0x4000), 0x00);
+        cart.write(0x2000, 0x05);
+        cart.write(0x3000, 0x01); // 0x105 % 64 = 5
+        assert_eq!(cart.current_rom_bank(), 5);
+        assert_eq!(cart.read_rom(0x4000), 0x05);
+    }
+
+    #[test]
+    fn mbc5_rumble_bit_does_not_select_ram_bank() {
+        // 0x1E = MBC5+RUMBLE+RAM+BATTERY, 0x03 = 32 KiB RAM
+        let mut cart = XipCartridge::new(leak_rom(4, 0x1E, 0x03)).unwrap();
+        cart.write(0x0000, 0x0A);
+        cart.write(0x4000, 0x02);
+        cart.write(0xA000, 0x33);
+        cart.write(0x4000, 0x0A);
+        assert!(cart.rumble_active());
+        assert_eq!(cart.read_ram(0x0000), 0x33);
+    }
+
+    #[test]
+    fn mbc2_bank_register_selected_by_address_bit8() {
+        let mut cart = XipCartridge::new(leak_rom(16, 0x06, 0x00)).unwrap();
+        cart.write(0x2100, 0x07);
+        assert_eq!(cart.read_rom(0x4000), 0x07);
+        cart.write(0x2000, 0x03); // RAM enable register, bank unchanged
+        assert_eq!(cart.read_rom(0x4000), 0x07);
+    }
+
+    #[test]
+    fn mbc2_ram_is_mirrored_nibble_storage() {
+        let mut cart = XipCartridge::new(leak_rom(4, 0x06, 0x00)).unwrap();
+        cart.write(0x0000, 0x0A);
+        cart.write(0xA005, 0x5C);
+        assert_eq!(cart.read_ram(0x0005), 0xFC);
+        assert_eq!(cart.read_ram(0x0205), 0xFC);
+        assert_eq!(cart.external_ram().map(|r| r.len()), Some(MBC2_RAM_BYTES));
+    }
+
     #[test]
     fn save_state_round_trip_restores_mbc1_mapping() {
         let mut cart = XipCartridge::new(leak_rom(64, 0x01, 0x00)).unwrap();
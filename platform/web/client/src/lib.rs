@@ -1,81 +1,321 @@
+use std::{cell::RefCell, rc::Rc};
 use wasm_bindgen::prelude::*;
 
-use rustyboy_core::{
-    cpu::{
-        cpu::Cpu,
-        instructions::opcodes::OpCodeDecoder,
-        peripheral::joypad::Button,
-        registers::{Flags, Registers},
-        save_state::SaveState,
-        sm83::Sm83,
+#[cfg(feature = "debugger")]
+use rustyboy_core::cpu::{
+    cpu::CpuError,
+    debugger::{Access, Breakpoint, DebugId, Debugger, StopReason, Watchpoint},
+    peripheral::ppu::CYCLES_PER_FRAME,
+};
+use rustyboy_core::cpu::{
+    av_sync::{AvSync, SyncMode},
+    disassembler::Disassembler,
+    instructions::opcodes::OpCodeDecoder,
+    peripheral::{
+        joypad::Button,
+        ppu::palette::{ColorCorrection, Palette, PalettePreset},
+        serial::{GameBoyPrinter, RemoteLink},
     },
-    memory::GameBoyMemory,
+    recording::ClipBuffer,
+    rewind::RewindBuffer,
+    save_state::SaveState,
+    sm83::Sm83,
 };
+use rustyboy_core::memory::memory::{GameBoyMemory, BOOT_ROM_SIZE};
 
-const CYCLES_PER_FRAME: u32 = 70224;
 const SCREEN_WIDTH: usize = 160;
 const SCREEN_HEIGHT: usize = 144;
 const RGBA_FRAMEBUFFER_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT * 4;
+/// Memory the rewind history may use; a few minutes of typical gameplay.
+const REWIND_BUDGET: usize = 16 * 1024 * 1024;
+/// Length of the clip `clip_gif` and `clip_wav` save.
+const CLIP_SECONDS: u32 = 30;
 
-// DMG green palette: palette index → RGBA
-const PALETTE: [[u8; 4]; 4] = [
-    [0xE0, 0xF8, 0xD0, 0xFF], // 0 - lightest green
-    [0x88, 0xC0, 0x70, 0xFF], // 1
-    [0x34, 0x68, 0x56, 0xFF], // 2
-    [0x08, 0x18, 0x20, 0xFF], // 3 - darkest
-];
+// Printer paper shades → grayscale, white paper to black ink
+const PAPER_SHADES: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
 
 #[wasm_bindgen]
 pub struct EmulatorHandle {
     cpu: Sm83,
     rgba_buf: Vec<u8>,
+    /// Colors for DMG frames; see `set_palette`.
+    palette: Palette,
+    /// What the `cgb` preset resolves to for this cartridge.
+    title_palette: Palette,
+    /// Applied to CGB frames; see `set_color_correction`.
+    color_correction: ColorCorrection,
+    disassembler: Disassembler,
+    rewind: RewindBuffer,
+    /// Link cable plugged in by `enable_link`; JS relays its bytes over a WebSocket.
+    link: Option<Rc<RefCell<RemoteLink>>>,
+    /// Game Boy Printer plugged in by `enable_printer`.
+    printer: Option<Rc<RefCell<GameBoyPrinter>>>,
+    /// Frame pacing and audio rate control; see `frames_due`.
+    sync: AvSync,
+    /// Pace by the audio queue whenever there is one; see `set_sync_to_audio`.
+    sync_to_audio: bool,
+    /// The last `CLIP_SECONDS` of gameplay; see `set_clip_recording`.
+    clip: Option<ClipBuffer>,
+    /// Audio drained into `clip` that `drain_audio_samples` has yet to return.
+    clip_audio: Vec<f32>,
+    #[cfg(feature = "debugger")]
+    debugger: Debugger,
+    /// Why the debugger last stopped; `run_frame` does nothing until `resume`.
+    #[cfg(feature = "debugger")]
+    paused: Option<StopReason>,
 }
 
-#[wasm_bindgen]
 impl EmulatorHandle {
-    #[wasm_bindgen(constructor)]
-    pub fn new(rom: Vec<u8>) -> EmulatorHandle {
-        let memory = GameBoyMemory::with_rom(rom);
-        let decoder = Box::new(OpCodeDecoder::new());
-        // Start at 0x100 with DMG post-boot-ROM state (skips boot ROM).
-        let cpu = Sm83::new(Box::new(memory), decoder)
-            .with_registers(Registers {
-                a: 0x01, f: Flags::from_bits_truncate(0xB0),
-                b: 0x00, c: 0x13,
-                d: 0x00, e: 0xD8,
-                h: 0x01, l: 0x4D,
-                pc: 0x0100,
-                sp: 0xFFFE,
-            })
-            .with_dmg_state();
+    fn from_cpu(cpu: Sm83, title_palette: Palette) -> Self {
+        let sync = AvSync::new(SyncMode::Video, cpu.sample_rate());
         EmulatorHandle {
             cpu,
             rgba_buf: vec![0u8; RGBA_FRAMEBUFFER_SIZE],
+            palette: Palette::DMG_GREEN,
+            title_palette,
+            color_correction: ColorCorrection::None,
+            disassembler: Disassembler::new(),
+            rewind: RewindBuffer::new(REWIND_BUDGET),
+            link: None,
+            printer: None,
+            sync,
+            sync_to_audio: false,
+            clip: None,
+            clip_audio: Vec::new(),
+            #[cfg(feature = "debugger")]
+            debugger: Debugger::new(),
+            #[cfg(feature = "debugger")]
+            paused: None,
         }
     }
 
-    pub fn run_frame(&mut self) {
-        let start = self.cpu.cycle_counter();
-        while self.cpu.cycle_counter().wrapping_sub(start) < CYCLES_PER_FRAME as u64 {
-            let _ = self.cpu.tick();
+    fn render(&mut self) {
+        match self.cpu.color_framebuffer() {
+            Some(fb) => self.color_correction.write_rgba8888(fb, &mut self.rgba_buf),
+            None => self.palette.write_rgba8888(
+                self.cpu.framebuffer(),
+                self.cpu.framebuffer_layers(),
+                &mut self.rgba_buf,
+            ),
+        }
+    }
+
+    /// Add the frame just run, and its audio, to the clip buffer.
+    fn record_clip(&mut self) {
+        if self.clip.is_none() {
+            return;
+        }
+        self.render();
+        let start = self.clip_audio.len();
+        self.clip_audio.extend(self.cpu.drain_audio_samples());
+        if let Some(clip) = &mut self.clip {
+            clip.push_frame(&self.rgba_buf, &self.clip_audio[start..]);
         }
     }
+}
+
+#[wasm_bindgen]
+impl EmulatorHandle {
+    /// Throws a JS error describing the problem if the ROM is not a cartridge
+    /// the emulator supports.
+    #[wasm_bindgen(constructor)]
+    pub fn new(rom: Vec<u8>) -> Result<EmulatorHandle, JsValue> {
+        let decoder = Box::new(OpCodeDecoder::new());
+        let title_palette = PalettePreset::CgbTitle.palette(&rom);
+        // Start at 0x100 with the post-boot-ROM state of whichever model the
+        // cartridge header asks for (skips boot ROM).
+        let cpu = Sm83::from_rom(rom, decoder)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?
+            .with_post_boot_state();
+        Ok(EmulatorHandle::from_cpu(cpu, title_palette))
+    }
+
+    /// Like the constructor, but starts at 0x0000 running the given DMG boot
+    /// ROM, which hands off to the cartridge at 0x0100. Throws if the image is
+    /// not exactly 256 bytes. Always emulates a DMG, even for CGB cartridges.
+    pub fn with_boot_rom(rom: Vec<u8>, boot_rom: Vec<u8>) -> Result<EmulatorHandle, JsValue> {
+        let boot_rom: [u8; BOOT_ROM_SIZE] = boot_rom
+            .try_into()
+            .map_err(|_| JsValue::from_str("boot ROM must be 256 bytes"))?;
+        let title_palette = PalettePreset::CgbTitle.palette(&rom);
+        let memory = GameBoyMemory::with_rom(rom).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let decoder = Box::new(OpCodeDecoder::new());
+        let cpu = Sm83::new(Box::new(memory), decoder).with_boot_rom(boot_rom);
+        Ok(EmulatorHandle::from_cpu(cpu, title_palette))
+    }
+
+    /// Run until the next VBlank. Throws if the CPU hits an invalid opcode.
+    #[cfg(not(feature = "debugger"))]
+    pub fn run_frame(&mut self) -> Result<(), JsValue> {
+        self.cpu
+            .run_frame()
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.rewind.record(&self.cpu);
+        self.record_clip();
+        Ok(())
+    }
+
+    /// Run until the next VBlank, or until a breakpoint or watchpoint stops
+    /// execution. While stopped this does nothing; see `stop_reason`.
+    #[cfg(feature = "debugger")]
+    pub fn run_frame(&mut self) -> Result<(), JsValue> {
+        if self.paused.is_some() {
+            return Ok(());
+        }
+        match self
+            .debugger
+            .run_frame(&mut self.cpu)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?
+        {
+            StopReason::Frame | StopReason::CycleLimit => {
+                self.rewind.record(&self.cpu);
+                self.record_clip();
+            }
+            reason => self.paused = Some(reason),
+        }
+        Ok(())
+    }
 
     /// Returns the framebuffer as an RGBA8 Vec for use in JS as Uint8ClampedArray.
     pub fn framebuffer_rgba(&mut self) -> Vec<u8> {
-        let fb = self.cpu.framebuffer();
-        for (i, &pixel) in fb.iter().enumerate() {
-            let color = PALETTE[(pixel & 3) as usize];
-            self.rgba_buf[i * 4..i * 4 + 4].copy_from_slice(&color);
-        }
+        self.render();
         self.rgba_buf.clone()
     }
 
+    /// Names of the palettes `set_palette` accepts, default first.
+    pub fn palette_names() -> Vec<String> {
+        PalettePreset::ALL.iter().map(|preset| preset.name().to_string()).collect()
+    }
+
+    /// Color DMG frames with a named palette from `palette_names`. `cgb` is
+    /// the palette a Game Boy Color would pick for this cartridge. Throws on
+    /// an unknown name. CGB frames are unaffected.
+    pub fn set_palette(&mut self, name: &str) -> Result<(), JsValue> {
+        let preset = PalettePreset::from_name(name)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown palette: {name}")))?;
+        self.palette = match preset {
+            PalettePreset::CgbTitle => self.title_palette,
+            preset => preset.palette(&[]),
+        };
+        Ok(())
+    }
+
+    /// Mimic the CGB LCD's muted colors instead of showing RGB555 at full
+    /// saturation. DMG frames are unaffected.
+    pub fn set_color_correction(&mut self, enabled: bool) {
+        self.color_correction = if enabled { ColorCorrection::Lcd } else { ColorCorrection::None };
+    }
+
+    /// Set the audio output rate in Hz, normally the AudioContext's
+    /// `sampleRate`. Clamped to 8–192 kHz.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.cpu.set_sample_rate(sample_rate);
+        self.sync.set_sample_rate(self.cpu.sample_rate());
+        if let Some(clip) = &mut self.clip {
+            clip.set_sample_rate(self.cpu.sample_rate());
+        }
+    }
+
+    /// How much audio to keep queued, in milliseconds. It must cover the
+    /// largest block the audio node takes at once.
+    pub fn set_audio_latency_ms(&mut self, ms: u32) {
+        self.sync = AvSync::new(self.sync.mode(), self.cpu.sample_rate()).with_target_latency_ms(ms);
+    }
+
+    /// Run frames when the audio queue runs low (true) instead of by elapsed
+    /// time (false, the default). Without an audio queue frames always go by
+    /// elapsed time.
+    pub fn set_sync_to_audio(&mut self, enabled: bool) {
+        self.sync_to_audio = enabled;
+    }
+
+    /// How many frames to run now, `elapsed_ms` after the previous call, with
+    /// `queued` stereo pairs waiting to be played (undefined when there is
+    /// no audio output). Also adjusts the audio rate so the queue holds
+    /// steady; call once per `requestAnimationFrame`.
+    pub fn frames_due(&mut self, elapsed_ms: f64, queued: Option<u32>) -> u32 {
+        let mode = if self.sync_to_audio && queued.is_some() { SyncMode::Audio } else { SyncMode::Video };
+        if self.sync.mode() != mode {
+            self.sync.set_mode(mode);
+        }
+        let queued = queued.unwrap_or(self.sync.target_fill());
+        let frames = self.sync.frames_due((elapsed_ms.max(0.0) * 1000.0) as u32, queued);
+        self.cpu.set_audio_rate_skew(self.sync.rate_skew());
+        frames
+    }
+
+    /// Forget the frame backlog and queue history, e.g. after a pause.
+    pub fn reset_sync(&mut self) {
+        self.sync.reset();
+    }
+
     /// Drain accumulated PCM audio samples since the last call.
-    /// Returns interleaved stereo f32 samples [L, R, L, R, ...] at 48,000 Hz.
+    /// Returns interleaved stereo f32 samples [L, R, L, R, ...] at the rate
+    /// given to `set_sample_rate`, 48,000 Hz by default.
     /// Pass to an AudioContext for playback.
     pub fn drain_audio_samples(&mut self) -> Vec<f32> {
-        self.cpu.drain_audio_samples()
+        let mut samples = std::mem::take(&mut self.clip_audio);
+        samples.extend(self.cpu.drain_audio_samples());
+        samples
+    }
+
+    /// Keep the last 30 seconds of frames and audio so `clip_gif` and
+    /// `clip_wav` can save them. Off by default; turning it off drops what
+    /// was kept. Costs a GIF encode every other frame.
+    pub fn set_clip_recording(&mut self, enabled: bool) {
+        if !enabled {
+            self.clip = None;
+        } else if self.clip.is_none() {
+            self.clip = Some(ClipBuffer::new(CLIP_SECONDS, self.cpu.sample_rate()));
+        }
+    }
+
+    /// Frames in the clip buffer, up to about 30 seconds' worth; 0 when
+    /// clip recording is off.
+    pub fn clip_frames(&self) -> u32 {
+        self.clip.as_ref().map_or(0, |clip| clip.frames() as u32)
+    }
+
+    /// The clip buffer as an animated GIF, every other frame. Empty when clip
+    /// recording is off.
+    pub fn clip_gif(&self) -> Vec<u8> {
+        self.clip.as_ref().map(ClipBuffer::to_gif).unwrap_or_default()
+    }
+
+    /// The clip buffer's audio as a 16-bit stereo WAV file the same length
+    /// as `clip_gif`. Empty when clip recording is off.
+    pub fn clip_wav(&self) -> Vec<u8> {
+        self.clip.as_ref().map(ClipBuffer::to_wav).unwrap_or_default()
+    }
+
+    /// Record each sound channel's level alongside the mixed audio, for the
+    /// channel scope. Off by default.
+    pub fn set_channel_capture(&mut self, enabled: bool) {
+        self.cpu.set_channel_capture(enabled);
+    }
+
+    /// Channel levels (0–15) since the last call, interleaved as
+    /// [pulse 1, pulse 2, wave, noise, pulse 1, ...], one group per stereo
+    /// pair of `drain_audio_samples`. Empty unless capture is on.
+    pub fn drain_channel_samples(&mut self) -> Vec<u8> {
+        let [pulse1, pulse2, wave, noise] = self.cpu.drain_channel_samples();
+        let mut out = Vec::with_capacity(pulse1.len() * 4);
+        for i in 0..pulse1.len() {
+            out.extend([pulse1[i], pulse2[i], wave[i], noise[i]]);
+        }
+        out
+    }
+
+    /// Silence channels in the mix: bit 0 pulse 1, bit 1 pulse 2, bit 2 wave,
+    /// bit 3 noise.
+    pub fn set_muted_channels(&mut self, mask: u8) {
+        self.cpu.set_muted_channels(mask);
+    }
+
+    /// Play only these channels (same bits as `set_muted_channels`); 0 turns
+    /// solo off.
+    pub fn set_soloed_channels(&mut self, mask: u8) {
+        self.cpu.set_soloed_channels(mask);
     }
 
     /// Returns a debug string with key PPU/interrupt state for on-screen display.
@@ -109,6 +349,94 @@ impl EmulatorHandle {
         )
     }
 
+    /// Disassemble `count` instructions starting at `addr` (or PC when `addr`
+    /// is undefined), one `BB:AAAA  bytes  text` line per instruction.
+    pub fn disassemble(&self, addr: Option<u16>, count: usize) -> String {
+        let addr = addr.unwrap_or(self.cpu.registers().pc);
+        let mut out = String::new();
+        for insn in self.disassembler.listing(&self.cpu, addr, count) {
+            let bytes: Vec<String> = insn.encoding().iter().map(|b| format!("{b:02X}")).collect();
+            out.push_str(&format!(
+                "{:02X}:{:04X}  {:<8}  {}\n",
+                insn.bank.unwrap_or(0),
+                insn.addr,
+                bytes.join(" "),
+                insn
+            ));
+        }
+        out
+    }
+
+    // ── Debugger (only with the `debugger` feature) ──────────────────────────
+
+    /// Break before executing `addr`. `bank` < 0 matches any ROM bank.
+    /// Returns an id for `remove_debug`.
+    #[cfg(feature = "debugger")]
+    pub fn add_breakpoint(&mut self, addr: u16, bank: i32) -> u32 {
+        let bp = match usize::try_from(bank) {
+            Ok(bank) => Breakpoint::in_bank(bank, addr),
+            Err(_) => Breakpoint::at(addr),
+        };
+        self.debugger.add_breakpoint(bp).raw()
+    }
+
+    /// Watch `start..=end`. `access` bits: 1 = read, 2 = write, 4 = execute.
+    #[cfg(feature = "debugger")]
+    pub fn add_watchpoint(&mut self, start: u16, end: u16, access: u8) -> u32 {
+        let access = Access::from_bits_truncate(access);
+        self.debugger
+            .add_watchpoint(Watchpoint::new(start..=end, access))
+            .raw()
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn remove_debug(&mut self, id: u32) -> bool {
+        self.debugger.remove(DebugId::from_raw(id))
+    }
+
+    /// Stop after dispatching an interrupt whose IF bit is in `mask`.
+    #[cfg(feature = "debugger")]
+    pub fn break_on_interrupts(&mut self, mask: u8) {
+        self.debugger.break_on_interrupts(mask);
+    }
+
+    /// Why execution is stopped, or undefined while running.
+    #[cfg(feature = "debugger")]
+    pub fn stop_reason(&self) -> Option<String> {
+        self.paused.map(|reason| format!("{reason:?}"))
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn resume(&mut self) {
+        self.paused = None;
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn step_in(&mut self) -> Result<String, JsValue> {
+        let reason = self.debugger.step_in(&mut self.cpu);
+        self.stopped_by(reason)
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn step_over(&mut self) -> Result<String, JsValue> {
+        let reason = self.debugger.step_over(&mut self.cpu, CYCLES_PER_FRAME);
+        self.stopped_by(reason)
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn step_out(&mut self) -> Result<String, JsValue> {
+        let reason = self.debugger.step_out(&mut self.cpu, CYCLES_PER_FRAME);
+        self.stopped_by(reason)
+    }
+
+    #[cfg(feature = "debugger")]
+    pub fn run_to_scanline(&mut self, ly: u8) -> Result<String, JsValue> {
+        let reason = self
+            .debugger
+            .run_to_scanline(&mut self.cpu, ly, CYCLES_PER_FRAME);
+        self.stopped_by(reason)
+    }
+
     /// Serialize the full emulator state to a byte blob (save state).
     pub fn save_state(&self) -> Vec<u8> {
         self.cpu.save_state()
@@ -116,8 +444,18 @@ impl EmulatorHandle {
 
     /// Restore emulator state from a blob produced by `save_state`.
     pub fn load_state(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
-        let state = SaveState::from_blob(data).map_err(|e| JsValue::from_str(e))?;
-        self.cpu.load_state(state).map_err(|e| JsValue::from_str(e))
+        let state = SaveState::from_blob(data).map_err(JsValue::from_str)?;
+        self.cpu.load_state(state).map_err(JsValue::from_str)?;
+        self.rewind.clear();
+        Ok(())
+    }
+
+    /// Step back at least `frames` frames (less if history runs out) and
+    /// return how many frames were rewound. Call `run_frame` to resume.
+    pub fn rewind(&mut self, frames: u32) -> Result<u32, JsValue> {
+        self.rewind
+            .rewind(&mut self.cpu, frames)
+            .map_err(JsValue::from_str)
     }
 
     /// Returns the cartridge external RAM (battery save) as bytes, or an empty Vec
@@ -147,4 +485,85 @@ impl EmulatorHandle {
         };
         self.cpu.set_button(btn, pressed);
     }
+
+    /// Parse and enable a GameShark or Game Genie code, returning its index.
+    /// Throws a message describing why the code was rejected.
+    pub fn add_cheat(&mut self, code: &str) -> Result<usize, JsValue> {
+        self.cpu
+            .add_cheat(code)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Enable or disable the cheat at `index`. Returns false if there is none.
+    pub fn set_cheat_enabled(&mut self, index: usize, enabled: bool) -> bool {
+        self.cpu.set_cheat_enabled(index, enabled)
+    }
+
+    /// Remove the cheat at `index`; later cheats move down by one.
+    pub fn remove_cheat(&mut self, index: usize) {
+        self.cpu.remove_cheat(index);
+    }
+
+    pub fn clear_cheats(&mut self) {
+        self.cpu.clear_cheats();
+    }
+
+    /// Plug in a link cable whose other end is reached through JS: send the
+    /// bytes from `link_take_outgoing` to the peer and feed the peer's bytes
+    /// to `link_push_incoming`.
+    pub fn enable_link(&mut self) {
+        let link = Rc::new(RefCell::new(RemoteLink::new()));
+        self.cpu.set_link_cable(Some(Box::new(link.clone())));
+        self.link = Some(link);
+        self.printer = None;
+    }
+
+    /// Unplug the link cable.
+    pub fn disable_link(&mut self) {
+        if self.link.take().is_some() {
+            self.cpu.set_link_cable(None);
+        }
+    }
+
+    /// Link protocol bytes to send to the peer since the last call.
+    pub fn link_take_outgoing(&mut self) -> Vec<u8> {
+        self.link.as_ref().map(|link| link.borrow_mut().take_outgoing()).unwrap_or_default()
+    }
+
+    /// Feed link protocol bytes received from the peer.
+    pub fn link_push_incoming(&mut self, bytes: Vec<u8>) {
+        if let Some(link) = &self.link {
+            link.borrow_mut().push_incoming(&bytes);
+        }
+    }
+
+    /// Plug a Game Boy Printer into the serial port in place of the link cable.
+    pub fn enable_printer(&mut self) {
+        let printer = Rc::new(RefCell::new(GameBoyPrinter::new()));
+        self.cpu.set_link_cable(Some(Box::new(printer.clone())));
+        self.printer = Some(printer);
+        self.link = None;
+    }
+
+    /// Returns the oldest finished page as RGBA8 pixels, 160 pixels wide,
+    /// or an empty Vec if none is ready. Call after each frame.
+    pub fn printer_take_page(&mut self) -> Vec<u8> {
+        let Some(page) = self.printer.as_ref().and_then(|p| p.borrow_mut().take_finished_page()) else {
+            return Vec::new();
+        };
+        page.pixels.iter().flat_map(|&shade| {
+            let gray = PAPER_SHADES[(shade & 3) as usize];
+            [gray, gray, gray, 0xFF]
+        }).collect()
+    }
+}
+
+#[cfg(feature = "debugger")]
+impl EmulatorHandle {
+    /// Record a stepping result as the pause reason and describe it for JS.
+    fn stopped_by(&mut self, reason: Result<StopReason, CpuError>) -> Result<String, JsValue> {
+        let reason = reason.map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.paused = Some(reason);
+        Ok(format!("{reason:?}"))
+    }
 }
@@ -76,7 +76,7 @@ function playBootJingle() {
 const state = {
   wasm:         null,   // wasm module (after init)
   emulator:     null,   // EmulatorHandle | null
-  roms:         [],     // string[]
+  roms:         [],     // /api/roms entries: {name, header, supported, error}
   selectedIdx:  0,
   lastRomName:  localStorage.getItem('lastRom') || null,
   running:      false,
@@ -91,18 +91,44 @@ const state = {
   paused:       false,  // true when emulation loop is suspended for in-game menu
   menuPending:  false,  // true while showInGameMenu fetch is in-flight; blocks re-entry
   menuGen:      0,      // incremented on every pause/resume; stale async callbacks self-cancel
+  bootRom:      undefined, // Uint8Array | null once fetched; null when the server has none
+  linkSocket:   null,   // WebSocket to the link cable relay | null
+  printer:      false,  // Game Boy Printer plugged in (?printer)
+  rewinding:    false,  // true while the rewind key is held
+  cheats:       [],     // {code, name, enabled} for the current ROM, as stored on the server
+  palette:      localStorage.getItem('palette') || 'dmg-green', // DMG palette name, see palette_names()
+  colorCorrection: localStorage.getItem('colorCorrection') === '1', // mimic the CGB LCD
+  mutedChannels: Number(localStorage.getItem('mutedChannels')) || 0, // bit per CHANNEL_NAMES entry
+  soloedChannels: 0,    // same bits; while non-zero only these channels play
+  channelScope: false,  // draw per-channel levels over the screen
+  scopeLevels:  null,   // last frame's drain_channel_samples() | null
+  syncToAudio:  localStorage.getItem('syncToAudio') === '1', // pace frames by the audio queue
+  lastFrameTime: null,  // requestAnimationFrame timestamp of the previous tick | null
 };
 
 // ── Audio ───────────────────────────────────────────────────────────────────
 
-const AUDIO_SAMPLE_RATE = 48000;
+// Samples the ScriptProcessorNode pulls from the ring at once.
+const AUDIO_BLOCK = 4096;
+
+// Match the emulator's output and queue target to the AudioContext: keep a
+// whole block plus two frames' worth queued so a pull never finds it empty.
+function configureAudioSync() {
+  const rate = state.audioCtx.sampleRate;
+  state.emulator.set_sample_rate(rate);
+  state.emulator.set_audio_latency_ms(Math.ceil(AUDIO_BLOCK * 1000 / rate) + 34);
+}
 
 async function initAudio() {
-  if (state.audioCtx) return;
+  if (state.audioCtx) {
+    configureAudioSync();
+    return;
+  }
   try {
-    state.audioCtx = new (window.AudioContext || window.webkitAudioContext)({
-      sampleRate: AUDIO_SAMPLE_RATE,
-    });
+    // Run at the device's native rate and have the APU resample to it,
+    // rather than making the browser resample a second time.
+    state.audioCtx = new (window.AudioContext || window.webkitAudioContext)();
+    configureAudioSync();
     await state.audioCtx.resume();
 
     // Ring buffer consumed by ScriptProcessorNode
@@ -111,7 +137,7 @@ async function initAudio() {
     state._ringTail = 0;
     state._ringSize = 0;
 
-    const node = state.audioCtx.createScriptProcessor(4096, 0, 2);
+    const node = state.audioCtx.createScriptProcessor(AUDIO_BLOCK, 0, 2);
     node.onaudioprocess = (e) => {
       const L = e.outputBuffer.getChannelData(0);
       const R = e.outputBuffer.getChannelData(1);
@@ -197,6 +223,220 @@ function stopBatterySaveTimer() {
   }
 }
 
+// ── Cheats ─────────────────────────────────────────────────────────────────
+
+async function loadCheats(romName) {
+  state.cheats = [];
+  try {
+    const res = await fetch(`/api/cheats/${encodeURIComponent(romName)}`);
+    if (res.ok) state.cheats = await res.json();
+  } catch (e) {
+    log.warn(`cheat list load failed: ${e}`);
+  }
+  applyCheats();
+}
+
+// Rebuild the emulator's cheat list from state.cheats.
+function applyCheats() {
+  if (!state.emulator) return;
+  state.emulator.clear_cheats();
+  for (const cheat of state.cheats) {
+    try {
+      const idx = state.emulator.add_cheat(cheat.code);
+      if (!cheat.enabled) state.emulator.set_cheat_enabled(idx, false);
+    } catch (e) {
+      log.warn(`cheat ${cheat.code} rejected: ${e}`);
+    }
+  }
+}
+
+async function uploadCheats(romName) {
+  try {
+    await fetch(`/api/cheats/${encodeURIComponent(romName)}`, {
+      method: 'PUT',
+      headers: { 'content-type': 'application/json' },
+      body: JSON.stringify(state.cheats),
+    });
+    log.debug(`cheat list uploaded: ${state.cheats.length} codes`);
+  } catch (e) {
+    log.warn(`cheat list upload failed: ${e}`);
+  }
+}
+
+// Ask for a code (and an optional name) and add it, enabled. Returns false
+// if the user cancelled or the code didn't parse.
+async function promptNewCheat(romName) {
+  const code = window.prompt('GameShark (01FF16D0) or Game Genie (ABC-DEF-GHI) code:');
+  if (!code || !code.trim()) return false;
+  try {
+    // Validate with the core's parser before storing anything.
+    state.emulator.add_cheat(code);
+  } catch (e) {
+    window.alert(String(e));
+    return false;
+  }
+  const name = (window.prompt('Name (optional):') || '').trim().slice(0, 64);
+  state.cheats.push({ code: code.trim(), name, enabled: true });
+  applyCheats();
+  await uploadCheats(romName);
+  return true;
+}
+
+function showCheatMenu(romName, onBack) {
+  const items = state.cheats.map((c, i) => ({
+    label: `${c.enabled ? '\u25a0' : '\u25a1'} ${c.name || c.code}`,
+    value: i,
+  }));
+  items.push({ label: '+ ADD CODE', value: 'add' });
+
+  const menu = new window.MenuRenderer(canvas);
+  state.activeMenu = menu;
+  menu.show({
+    title: 'CHEATS',
+    items,
+    footer: '\u25b2\u25bc MOVE  A TOGGLE  SEL DEL  B BACK',
+    onSelect: async (item) => {
+      state.activeMenu = null;
+      if (item.value === 'add') {
+        await promptNewCheat(romName);
+      } else {
+        const cheat = state.cheats[item.value];
+        cheat.enabled = !cheat.enabled;
+        applyCheats();
+        await uploadCheats(romName);
+      }
+      showCheatMenu(romName, onBack);
+    },
+    onBack: () => {
+      if (onBack) onBack();
+    },
+    onSelectBtn: async (selIdx) => {
+      // Select = delete the highlighted code
+      if (typeof items[selIdx]?.value !== 'number') return;
+      state.cheats.splice(items[selIdx].value, 1);
+      applyCheats();
+      await uploadCheats(romName);
+      showCheatMenu(romName, onBack);
+    },
+  });
+}
+
+// ── Display settings ───────────────────────────────────────────────────────
+
+// Push the stored palette and color correction choices into the emulator.
+function applyDisplaySettings() {
+  if (!state.emulator) return;
+  try {
+    state.emulator.set_palette(state.palette);
+  } catch (e) {
+    log.warn(`palette ${state.palette} rejected: ${e}`);
+    state.palette = EmulatorHandle.palette_names()[0];
+    localStorage.removeItem('palette');
+    state.emulator.set_palette(state.palette);
+  }
+  state.emulator.set_color_correction(state.colorCorrection);
+}
+
+function showPaletteMenu(onBack) {
+  const items = EmulatorHandle.palette_names().map(name => ({
+    label: `${name === state.palette ? '\u25a0' : '\u25a1'} ${name.toUpperCase()}`,
+    value: name,
+  }));
+  items.push({
+    label: `${state.colorCorrection ? '\u25a0' : '\u25a1'} CGB LCD COLORS`,
+    value: 'correction',
+  });
+
+  const menu = new window.MenuRenderer(canvas);
+  state.activeMenu = menu;
+  menu.show({
+    title: 'PALETTE',
+    items,
+    footer: '\u25b2\u25bc MOVE  A SELECT  B BACK',
+    onSelect: (item) => {
+      state.activeMenu = null;
+      if (item.value === 'correction') {
+        state.colorCorrection = !state.colorCorrection;
+        localStorage.setItem('colorCorrection', state.colorCorrection ? '1' : '0');
+      } else {
+        state.palette = item.value;
+        localStorage.setItem('palette', item.value);
+      }
+      applyDisplaySettings();
+      showPaletteMenu(onBack);
+    },
+    onBack: () => {
+      if (onBack) onBack();
+    },
+  });
+}
+
+// ── Sound settings ─────────────────────────────────────────────────────────
+
+const CHANNEL_NAMES = ['PULSE 1', 'PULSE 2', 'WAVE', 'NOISE'];
+
+// Push the channel mute/solo choices and the scope's capture into the emulator.
+function applySoundSettings() {
+  if (!state.emulator) return;
+  state.emulator.set_muted_channels(state.mutedChannels);
+  state.emulator.set_soloed_channels(state.soloedChannels);
+  state.emulator.set_channel_capture(state.channelScope);
+  state.emulator.set_sync_to_audio(state.syncToAudio);
+}
+
+function channelAudible(i) {
+  const bit = 1 << i;
+  return state.soloedChannels ? (state.soloedChannels & bit) !== 0 : (state.mutedChannels & bit) === 0;
+}
+
+function showSoundMenu(onBack) {
+  const items = CHANNEL_NAMES.map((name, i) => {
+    const bit = 1 << i;
+    const mode = state.soloedChannels & bit ? 'SOLO' : state.mutedChannels & bit ? 'MUTE' : 'ON';
+    return { label: `${name.padEnd(8)} ${mode}`, value: i };
+  });
+  items.push({
+    label: `${state.channelScope ? '\u25a0' : '\u25a1'} CHANNEL SCOPE`,
+    value: 'scope',
+  });
+  items.push({ label: `SYNC TO   ${state.syncToAudio ? 'AUDIO' : 'VIDEO'}`, value: 'sync' });
+
+  const menu = new window.MenuRenderer(canvas);
+  state.activeMenu = menu;
+  menu.show({
+    title: 'SOUND',
+    items,
+    footer: '\u25b2\u25bc MOVE  A SELECT  B BACK',
+    onSelect: (item) => {
+      state.activeMenu = null;
+      if (item.value === 'scope') {
+        state.channelScope = !state.channelScope;
+        state.scopeLevels = null;
+      } else if (item.value === 'sync') {
+        state.syncToAudio = !state.syncToAudio;
+        localStorage.setItem('syncToAudio', state.syncToAudio ? '1' : '0');
+      } else {
+        // Each press moves a channel on → mute → solo → on.
+        const bit = 1 << item.value;
+        if (state.soloedChannels & bit) {
+          state.soloedChannels &= ~bit;
+        } else if (state.mutedChannels & bit) {
+          state.mutedChannels &= ~bit;
+          state.soloedChannels |= bit;
+        } else {
+          state.mutedChannels |= bit;
+        }
+        localStorage.setItem('mutedChannels', String(state.mutedChannels));
+      }
+      applySoundSettings();
+      showSoundMenu(onBack);
+    },
+    onBack: () => {
+      if (onBack) onBack();
+    },
+  });
+}
+
 // ── DOM refs ───────────────────────────────────────────────────────────────
 
 const canvas      = document.getElementById('gameCanvas');
@@ -476,14 +716,14 @@ function showRomList() {
     return;
   }
 
-  const lastIdx = state.roms.indexOf(state.lastRomName);
+  const lastIdx = state.roms.findIndex(rom => rom.name === state.lastRomName);
   state.selectedIdx = lastIdx >= 0 ? lastIdx : 0;
 
   const menu = new window.MenuRenderer(canvas);
   state.activeMenu = menu;
   menu.show({
     title: 'SELECT GAME',
-    items: state.roms.map(name => ({ label: stripExtension(name), value: name })),
+    items: state.roms.map(rom => ({ label: romLabel(rom), value: rom.name })),
     footer: '\u25b2\u25bc MOVE  A SELECT  B BACK',
     onSelect: (item) => {
       state.activeMenu = null;
@@ -496,6 +736,11 @@ function showRomList() {
   });
 }
 
+// Prefer the cartridge header title; fall back to the file name.
+function romLabel(rom) {
+  return rom.header?.title || stripExtension(rom.name);
+}
+
 function stripExtension(name) {
   return name.replace(/\.(gb|gbc)$/i, '');
 }
@@ -530,6 +775,26 @@ async function launchRom(name) {
   await launchRomWithSaveState(name, saveStateId);
 }
 
+// ── Boot ROM ───────────────────────────────────────────────────────────────
+// If ROMS_DIR contains dmg_boot.bin, games start through the real boot ROM
+// (scrolling logo and chime) instead of jumping straight to 0x0100.
+
+const BOOT_ROM_NAME = 'dmg_boot.bin';
+
+async function fetchBootRom() {
+  if (state.bootRom !== undefined) return state.bootRom;
+  state.bootRom = null;
+  try {
+    const res = await fetch(`/roms/${BOOT_ROM_NAME}`);
+    if (res.ok) {
+      const bytes = new Uint8Array(await res.arrayBuffer());
+      if (bytes.length === 256) state.bootRom = bytes;
+      else log.warn(`${BOOT_ROM_NAME} is ${bytes.length} bytes, expected 256; skipping`);
+    }
+  } catch (_) {}
+  return state.bootRom;
+}
+
 async function launchRomWithSaveState(name, saveStateId) {
   // Fetch ROM bytes
   let bytes;
@@ -544,18 +809,28 @@ async function launchRomWithSaveState(name, saveStateId) {
     return;
   }
 
+  // A save state overwrites the boot sequence anyway, so only boot when starting fresh.
+  const bootRom = saveStateId ? null : await fetchBootRom();
+
   // Tear down previous
   await stopEmulation();
 
   // Create emulator
   try {
-    state.emulator = new EmulatorHandle(bytes);
+    state.emulator = bootRom
+      ? EmulatorHandle.with_boot_rom(bytes, bootRom)
+      : new EmulatorHandle(bytes);
   } catch (err) {
-    showCanvasError('ROM ERROR');
+    // The constructor throws the core's CartridgeError message.
+    showCanvasError(String(err).startsWith('unsupported') ? 'UNSUPPORTED ROM' : 'ROM ERROR');
     log.error(err);
     return;
   }
 
+  applyDisplaySettings();
+  applySoundSettings();
+  state.emulator.set_clip_recording(true);
+
   state.lastRomName = name;
   state.currentRomName = name;
   localStorage.setItem('lastRom', name);
@@ -577,10 +852,13 @@ async function launchRomWithSaveState(name, saveStateId) {
   } else {
     await loadBatterySave(name);
   }
+  await loadCheats(name);
   startBatterySaveTimer(name);
+  connectLink();
+  connectPrinter();
 
   initAudio();
-  playBootJingle();
+  if (!bootRom) playBootJingle(); // the real boot ROM plays its own chime
   menuOverlay.classList.add('hidden');
   screenInner.classList.add('booting');
   screenInner.classList.add('running');
@@ -601,6 +879,10 @@ async function stopEmulation() {
     state.rafId = null;
   }
   stopBatterySaveTimer();
+  disconnectLink();
+  state.printer = false;
+  state.rewinding = false;
+  state.cheats = [];
   if (state.emulator && state.currentRomName) {
     await uploadBatterySave(state.currentRomName);
   }
@@ -624,6 +906,7 @@ function pauseEmulation() {
   if (state.emulator) {
     for (let i = 0; i < 8; i++) state.emulator.set_button(i, false);
   }
+  state.rewinding = false;
   state.paused = true;
   state.menuGen++;
   if (state.rafId) {
@@ -662,6 +945,10 @@ function showPauseMenu(hasSaves, latestSaveId) {
     items.push({ label: 'QUICK LOAD', value: 'quickload' });
     items.push({ label: 'LOAD',       value: 'load' });
   }
+  items.push({ label: 'CHEATS', value: 'cheats' });
+  items.push({ label: 'PALETTE', value: 'palette' });
+  items.push({ label: 'SOUND', value: 'sound' });
+  if (state.emulator?.clip_frames()) items.push({ label: 'SAVE LAST 30 S', value: 'clip' });
   items.push({ label: 'RESET', value: 'reset' });
   items.push({ label: 'QUIT',  value: 'quit' });
 
@@ -701,6 +988,15 @@ function showPauseMenu(hasSaves, latestSaveId) {
           ]);
           showPauseMenu(hasSaves, latestSave);
         });
+      } else if (item.value === 'cheats') {
+        showCheatMenu(state.currentRomName, () => showPauseMenu(hasSaves, latestSaveId));
+      } else if (item.value === 'palette') {
+        showPaletteMenu(() => showPauseMenu(hasSaves, latestSaveId));
+      } else if (item.value === 'sound') {
+        showSoundMenu(() => showPauseMenu(hasSaves, latestSaveId));
+      } else if (item.value === 'clip') {
+        saveClip();
+        showPauseMenu(hasSaves, latestSaveId);
       } else if (item.value === 'reset') {
         const romName = state.currentRomName;
         await stopEmulation();
@@ -836,11 +1132,97 @@ function formatSaveSlotLabel(unixSecs) {
   return `${mon} ${day} ${h}:${m}`;
 }
 
+// ── Link cable ─────────────────────────────────────────────────────────────
+// Opening the page with ?link=<room> plugs a link cable into the emulator and
+// relays it through the server to whoever else joins the same room.
+
+function connectLink() {
+  const room = new URLSearchParams(window.location.search).get('link');
+  if (!room || !state.emulator) return;
+
+  const scheme = window.location.protocol === 'https:' ? 'wss' : 'ws';
+  const socket = new WebSocket(`${scheme}://${window.location.host}/api/link/${encodeURIComponent(room)}`);
+  socket.binaryType = 'arraybuffer';
+  socket.onmessage = (e) => {
+    if (state.linkSocket === socket && state.emulator) {
+      state.emulator.link_push_incoming(new Uint8Array(e.data));
+    }
+  };
+  socket.onopen  = () => log.debug(`link cable connected to room ${room}`);
+  socket.onclose = () => log.debug('link cable disconnected');
+  state.emulator.enable_link();
+  state.linkSocket = socket;
+}
+
+function disconnectLink() {
+  if (!state.linkSocket) return;
+  state.linkSocket.close();
+  state.linkSocket = null;
+  state.emulator?.disable_link();
+}
+
+function sendLinkBytes() {
+  if (state.linkSocket?.readyState !== WebSocket.OPEN) return;
+  const bytes = state.emulator.link_take_outgoing();
+  if (bytes.length) state.linkSocket.send(bytes);
+}
+
+// ── Game Boy Printer ───────────────────────────────────────────────────────
+// Opening the page with ?printer plugs a Game Boy Printer into the serial
+// port instead; every page it prints is downloaded as a PNG.
+
+const PRINTER_WIDTH = 160;
+
+function connectPrinter() {
+  if (state.linkSocket || !state.emulator) return;
+  if (!new URLSearchParams(window.location.search).has('printer')) return;
+  state.emulator.enable_printer();
+  state.printer = true;
+}
+
+function downloadPrintedPages() {
+  if (!state.printer) return;
+  let rgba;
+  while ((rgba = state.emulator.printer_take_page()).length) {
+    const height = rgba.length / (PRINTER_WIDTH * 4);
+    const pageCanvas = document.createElement('canvas');
+    pageCanvas.width = PRINTER_WIDTH;
+    pageCanvas.height = height;
+    pageCanvas.getContext('2d').putImageData(
+      new ImageData(new Uint8ClampedArray(rgba), PRINTER_WIDTH, height), 0, 0);
+    const name = `${stripExtension(state.currentRomName || 'rustyboy')}-print-${Date.now()}.png`;
+    pageCanvas.toBlob((blob) => downloadBlob(blob, name), 'image/png');
+    log.debug(`printed page ${PRINTER_WIDTH}x${height}`);
+  }
+}
+
+function downloadBlob(blob, name) {
+  const a = document.createElement('a');
+  a.href = URL.createObjectURL(blob);
+  a.download = name;
+  a.click();
+  setTimeout(() => URL.revokeObjectURL(a.href), 0);
+}
+
+// ── Clip recording ─────────────────────────────────────────────────────────
+// The emulator always keeps the last 30 seconds of play; SAVE LAST 30 S in
+// the pause menu downloads them as a GIF and a WAV of its sound.
+
+function saveClip() {
+  if (!state.emulator?.clip_frames()) return;
+  const name = `${stripExtension(state.currentRomName || 'rustyboy')}-clip-${Date.now()}`;
+  downloadBlob(new Blob([state.emulator.clip_gif()], { type: 'image/gif' }), `${name}.gif`);
+  downloadBlob(new Blob([state.emulator.clip_wav()], { type: 'audio/wav' }), `${name}.wav`);
+  log.debug(`saved clip: ${state.emulator.clip_frames()} frames`);
+}
+
 // ── Emulation loop ─────────────────────────────────────────────────────────
 
 let imageData = null;
 let offscreenCanvas = null;
 let offscreenCtx = null;
+// Rewinding plays back at twice real-time speed.
+const REWIND_FRAMES_PER_TICK = 2;
 let loopGeneration = 0; // incremented each time startLoop() is called; stale RAF callbacks self-cancel
 
 function startLoop() {
@@ -850,19 +1232,47 @@ function startLoop() {
   offscreenCtx = offscreenCanvas.getContext('2d');
   imageData = offscreenCtx.createImageData(160, 144);
   const myGen = ++loopGeneration;
+  state.lastFrameTime = null;
+  if (state.emulator) state.emulator.reset_sync();
 
   function frame(now) {
     if (!state.running || !state.emulator || loopGeneration !== myGen) return;
 
-    try {
-      state.emulator.run_frame();
-    } catch(e) {
-      log.error(`run_frame error: ${e}`);
+    const elapsed = state.lastFrameTime === null ? 0 : now - state.lastFrameTime;
+    state.lastFrameTime = now;
+
+    if (state.rewinding) {
+      try {
+        state.emulator.rewind(REWIND_FRAMES_PER_TICK);
+      } catch(e) {
+        log.error(`rewind error: ${e}`);
+      }
+      drawFrame();
+      state.rafId = requestAnimationFrame(frame);
       return;
     }
 
-    if (state.audioCtx) {
-      pushAudioSamples(state.emulator.drain_audio_samples());
+    // The core decides how many frames this tick is worth: by elapsed time
+    // on any refresh rate, or by the audio queue when synced to audio.
+    const queued = state.audioCtx ? state._ringSize : undefined;
+    const due = state.emulator.frames_due(elapsed, queued);
+    for (let i = 0; i < due; i++) {
+      try {
+        state.emulator.run_frame();
+      } catch(e) {
+        log.error(`run_frame error: ${e}`);
+        return;
+      }
+
+      sendLinkBytes();
+      downloadPrintedPages();
+
+      if (state.audioCtx) {
+        pushAudioSamples(state.emulator.drain_audio_samples());
+      }
+      if (state.channelScope) {
+        state.scopeLevels = state.emulator.drain_channel_samples();
+      }
     }
 
     drawFrame();
@@ -880,6 +1290,10 @@ function drawFrame() {
   ctx.imageSmoothingEnabled = false;
   ctx.drawImage(offscreenCanvas, 0, 0, canvas.width, canvas.height);
 
+  if (state.channelScope && state.scopeLevels) {
+    drawChannelScope(state.scopeLevels);
+  }
+
   if (state.debugOverlay && typeof state.emulator.debug_state === 'function') {
     const lines = state.emulator.debug_state().split('\n');
     const s = canvas.width / 160;
@@ -904,6 +1318,34 @@ function drawFrame() {
   }
 }
 
+// Draw one frame of channel levels as four oscilloscopes along the bottom of
+// the screen. Channels that aren't playing are dimmed.
+function drawChannelScope(levels) {
+  const count = levels.length >> 2;
+  if (count === 0) return;
+  const s = canvas.width / 160;
+  const w = 40, h = 24, top = 144 - h;
+  const points = Math.round(w * s);
+  ctx.save();
+  ctx.scale(s, s);
+  ctx.fillStyle = 'rgba(0,0,0,0.7)';
+  ctx.fillRect(0, top, 160, h);
+  ctx.lineWidth = 1 / s;
+  ctx.strokeStyle = '#9BBC0F';
+  for (let ch = 0; ch < 4; ch++) {
+    ctx.globalAlpha = channelAudible(ch) ? 1 : 0.35;
+    ctx.beginPath();
+    for (let p = 0; p < points; p++) {
+      const level = levels[Math.floor(p * count / points) * 4 + ch];
+      const x = ch * w + p / s;
+      const y = top + h - 2 - (level / 15) * (h - 4);
+      if (p === 0) ctx.moveTo(x, y); else ctx.lineTo(x, y);
+    }
+    ctx.stroke();
+  }
+  ctx.restore();
+}
+
 // ── Button handling ────────────────────────────────────────────────────────
 
 function sendButton(idx, pressed) {
@@ -1002,6 +1444,7 @@ const KEY_MAP = {
   'Shift': 6,        // Select
   'Enter': 7,        // Start
   'Backspace': -1,   // Power / menu
+  'r': -2, 'R': -2,  // Rewind (hold)
 };
 
 const heldKeys = new Set();
@@ -1041,7 +1484,9 @@ function bindKeyboard() {
     if (idx === undefined) return;
     e.preventDefault();
 
-    if (idx === -1) {
+    if (idx === -2) {
+      state.rewinding = true;
+    } else if (idx === -1) {
       if (state.menuPending) return;
       if (state.running && !state.paused) {
         showInGameMenu();
@@ -1057,7 +1502,8 @@ function bindKeyboard() {
     log.debug(`keyup key=${e.key}`);
     heldKeys.delete(e.key);
     const idx = KEY_MAP[e.key];
-    if (idx === undefined || idx === -1) return;
+    if (idx === -2) state.rewinding = false;
+    if (idx === undefined || idx < 0) return;
     e.preventDefault();
     sendButton(idx, false);
   });
@@ -1,12 +1,33 @@
 export default async function init() { return {}; }
 export class EmulatorHandle {
   constructor(rom) {}
+  static with_boot_rom(rom, bootRom) { return new EmulatorHandle(rom); }
   run_frame() {}
   framebuffer_rgba() { return new Uint8Array(160 * 144 * 4); }
+  set_sample_rate(rate) {}
   drain_audio_samples() { return new Float32Array(0); }
+  set_channel_capture(enabled) {}
+  drain_channel_samples() { return new Uint8Array(0); }
+  set_muted_channels(mask) {}
+  set_soloed_channels(mask) {}
+  set_audio_latency_ms(ms) {}
+  set_sync_to_audio(enabled) {}
+  frames_due(elapsedMs, queued) { return 1; }
+  reset_sync() {}
+  set_clip_recording(enabled) {}
+  clip_frames() { return 0; }
+  clip_gif() { return new Uint8Array(0); }
+  clip_wav() { return new Uint8Array(0); }
   set_button(btn, pressed) {}
   save_state() { return new Uint8Array(0); }
   load_state(data) {}
   get_battery_save() { return new Uint8Array(0); }
   set_battery_save(data) {}
+  add_cheat(code) { return 0; }
+  set_cheat_enabled(index, enabled) { return true; }
+  remove_cheat(index) {}
+  clear_cheats() {}
+  static palette_names() { return ['dmg-green']; }
+  set_palette(name) {}
+  set_color_correction(enabled) {}
 }
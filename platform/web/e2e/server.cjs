@@ -156,6 +156,12 @@ http.createServer((req, res) => {
     if (req.method === 'PUT') { res.writeHead(204); res.end(); return; }
   }
 
+  // /api/cheats/:rom — stub (always an empty list for get, 204 for put)
+  if (url.startsWith('/api/cheats/')) {
+    if (req.method === 'GET') { res.writeHead(200, { 'Content-Type': 'application/json' }); res.end('[]'); return; }
+    if (req.method === 'PUT') { res.writeHead(204); res.end(); return; }
+  }
+
   // /dev/log
   if (req.method === 'POST' && url === '/dev/log') {
     res.writeHead(204); res.end(); return;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS cheats (
+    id          TEXT    PRIMARY KEY NOT NULL,
+    user_id     TEXT    NOT NULL REFERENCES users(id),
+    rom_name    TEXT    NOT NULL,
+    position    INTEGER NOT NULL,
+    code        TEXT    NOT NULL,
+    name        TEXT    NOT NULL,
+    enabled     INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cheats_user_rom ON cheats(user_id, rom_name);
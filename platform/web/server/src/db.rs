@@ -42,6 +42,21 @@ pub struct BatterySave {
     pub updated_at: i64,
 }
 
+/// One entry in a user's cheat list for a ROM. `code` is a GameShark or
+/// Game Genie code string, validated by the caller.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Cheat {
+    pub code: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
 #[derive(Clone)]
 pub struct Database {
     pool: SqlitePool,
@@ -419,6 +434,61 @@ impl Database {
         })
     }
 
+    // --- Cheats ---
+
+    /// Returns the user's cheat list for a rom, in the order it was saved.
+    pub async fn list_cheats(&self, user_id: &str, rom_name: &str) -> Result<Vec<Cheat>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT code, name, enabled FROM cheats
+             WHERE user_id = ? AND rom_name = ?
+             ORDER BY position",
+        )
+        .bind(user_id)
+        .bind(rom_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Cheat {
+                code: r.get("code"),
+                name: r.get("name"),
+                enabled: r.get("enabled"),
+            })
+            .collect())
+    }
+
+    /// Replaces the user's whole cheat list for a rom.
+    pub async fn replace_cheats(
+        &self,
+        user_id: &str,
+        rom_name: &str,
+        cheats: &[Cheat],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM cheats WHERE user_id = ? AND rom_name = ?")
+            .bind(user_id)
+            .bind(rom_name)
+            .execute(&mut *tx)
+            .await?;
+        for (position, cheat) in cheats.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO cheats (id, user_id, rom_name, position, code, name, enabled)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(new_id())
+            .bind(user_id)
+            .bind(rom_name)
+            .bind(position as i64)
+            .bind(&cheat.code)
+            .bind(&cheat.name)
+            .bind(cheat.enabled)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
     // --- Revoked tokens ---
 
     pub async fn revoke_token(&self, jti: &str, expires_at: i64) -> Result<(), sqlx::Error> {
@@ -877,4 +947,27 @@ mod tests {
         // Verify it's gone
         assert!(db.get_save_state(&ss.id).await.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn test_replace_cheats_keeps_order_and_is_per_rom() {
+        let db = new_db().await;
+        let user = db
+            .upsert_user("sub_cheats", "cheats@example.com", "Cheats", None)
+            .await
+            .unwrap();
+        let cheat = |code: &str, enabled| Cheat { code: code.to_string(), name: String::new(), enabled };
+
+        db.replace_cheats(&user.id, "mario.gb", &[cheat("010900C0", true), cheat("3A1-5AE", false)])
+            .await
+            .unwrap();
+        db.replace_cheats(&user.id, "zelda.gb", &[cheat("01FF16D0", true)]).await.unwrap();
+        db.replace_cheats(&user.id, "mario.gb", &[cheat("3A1-5AE", true), cheat("010900C0", false)])
+            .await
+            .unwrap();
+
+        let mario = db.list_cheats(&user.id, "mario.gb").await.unwrap();
+        assert_eq!(mario, [cheat("3A1-5AE", true), cheat("010900C0", false)]);
+        assert_eq!(db.list_cheats(&user.id, "zelda.gb").await.unwrap().len(), 1);
+        assert!(db.list_cheats(&user.id, "tetris.gb").await.unwrap().is_empty());
+    }
 }
@@ -1,6 +1,8 @@
 pub mod auth;
 pub mod config;
 pub mod db;
+pub mod link;
+pub mod roms;
 
 use auth::{AuthUser, DbExt, JwtSecretExt};
 use axum::{
@@ -12,6 +14,7 @@ use axum::{
     response::{IntoResponse, Json, Response},
     routing::{delete, get, post},
 };
+use rustyboy_core::cpu::cheats::CheatCode;
 use std::{path::PathBuf, sync::Arc};
 use tower_http::services::ServeDir;
 
@@ -40,6 +43,7 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         .route("/api/save-states/:rom_name/latest", get(get_latest_save_state))
         .route("/api/save-states/by-id/:id/data", get(get_save_state_data))
         .route("/api/save-states/by-id/:id", delete(delete_save_state))
+        .route("/api/cheats/:rom_name", get(get_cheats).put(put_cheats))
         .route("/api/auth-method", get(api_auth_method))
         .route("/roms/:name", get(serve_rom))
         .route("/auth/google", get(auth::google_login))
@@ -47,6 +51,7 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         .route("/auth/cf-access", get(auth::cf_access_login))
         .route("/auth/logout", post(auth::logout))
         .route("/dev/log", post(dev_log))
+        .nest("/api/link", link::router())
         .nest_service("/static", ServeDir::new(&static_dir))
         .layer(middleware::from_fn_with_state(
             state.clone(),
@@ -100,19 +105,21 @@ async fn serve_index(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     }
 }
 
+/// GET /api/roms — list ROM files with their parsed cartridge headers
 async fn list_roms(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let mut names: Vec<String> = Vec::new();
+    let mut roms: Vec<roms::RomEntry> = Vec::new();
     if let Ok(mut entries) = tokio::fs::read_dir(&state.roms_dir).await {
         while let Ok(Some(entry)) = entries.next_entry().await {
             let name = entry.file_name();
             let s = name.to_string_lossy().into_owned();
             if s.ends_with(".gb") || s.ends_with(".gbc") {
-                names.push(s);
+                let data = tokio::fs::read(entry.path()).await.unwrap_or_default();
+                roms.push(roms::describe_rom(s, &data));
             }
         }
     }
-    names.sort();
-    Json(names)
+    roms.sort_by(|a, b| a.name.cmp(&b.name));
+    Json(roms)
 }
 
 async fn api_me(
@@ -310,6 +317,48 @@ async fn delete_save_state(
     }
 }
 
+// ── Cheat handlers ─────────────────────────────────────────────────────────
+
+const MAX_CHEATS_PER_ROM: usize = 256;
+const MAX_CHEAT_NAME_LEN: usize = 64;
+
+/// GET /api/cheats/:rom_name — the user's cheat list for a game (empty if none)
+async fn get_cheats(
+    auth: AuthUser,
+    Path(rom_name): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match state.db.list_cheats(&auth.user_id, &rom_name).await {
+        Ok(cheats) => Json(cheats).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// PUT /api/cheats/:rom_name — replace the cheat list; every code must parse
+async fn put_cheats(
+    auth: AuthUser,
+    Path(rom_name): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Json(mut cheats): Json<Vec<db::Cheat>>,
+) -> impl IntoResponse {
+    if cheats.len() > MAX_CHEATS_PER_ROM {
+        return (StatusCode::BAD_REQUEST, "too many cheats").into_response();
+    }
+    for cheat in &mut cheats {
+        if let Err(e) = CheatCode::parse(&cheat.code) {
+            return (StatusCode::BAD_REQUEST, format!("{}: {e}", cheat.code)).into_response();
+        }
+        if cheat.name.len() > MAX_CHEAT_NAME_LEN {
+            return (StatusCode::BAD_REQUEST, "cheat name too long").into_response();
+        }
+        cheat.code = cheat.code.trim().to_string();
+    }
+    match state.db.replace_cheats(&auth.user_id, &rom_name, &cheats).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
 fn now_unix_secs() -> i64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
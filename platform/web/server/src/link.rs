@@ -0,0 +1,191 @@
+//! WebSocket relay for link cable play between two browser sessions.
+//!
+//! Each client runs a `RemoteLink` from rustyboy-core and connects to
+//! `/api/link/:room`. The server doesn't interpret the link protocol; it
+//! forwards every binary message from one peer in a room to the other.
+
+use crate::auth::AuthUser;
+use axum::{
+    Router,
+    extract::{
+        Path, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::mpsc;
+
+/// A link cable has two ends.
+const PEERS_PER_ROOM: usize = 2;
+const MAX_ROOM_NAME_LEN: usize = 64;
+
+type PeerSender = mpsc::UnboundedSender<Vec<u8>>;
+
+/// Open rooms and the peers connected to each.
+#[derive(Default)]
+pub struct LinkRooms {
+    rooms: Mutex<HashMap<String, Vec<(u64, PeerSender)>>>,
+    next_id: AtomicU64,
+}
+
+impl LinkRooms {
+    /// Add a peer to `room`, returning its id, or `None` if the room already
+    /// has both ends plugged in.
+    fn join(&self, room: &str, peer: PeerSender) -> Option<u64> {
+        let mut rooms = self.rooms.lock().unwrap();
+        let peers = rooms.entry(room.to_string()).or_default();
+        // A peer whose upgrade never completed has dropped its receiver.
+        peers.retain(|(_, tx)| !tx.is_closed());
+        if peers.len() >= PEERS_PER_ROOM {
+            return None;
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        peers.push((id, peer));
+        Some(id)
+    }
+
+    fn leave(&self, room: &str, id: u64) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if let Some(peers) = rooms.get_mut(room) {
+            peers.retain(|(peer_id, _)| *peer_id != id);
+            if peers.is_empty() {
+                rooms.remove(room);
+            }
+        }
+    }
+
+    /// Send `bytes` to every other peer in `room`.
+    fn forward(&self, room: &str, from: u64, bytes: &[u8]) {
+        let rooms = self.rooms.lock().unwrap();
+        for (id, tx) in rooms.get(room).into_iter().flatten() {
+            if *id != from {
+                let _ = tx.send(bytes.to_vec());
+            }
+        }
+    }
+}
+
+/// Routes for the relay, with their own state so `AppState` stays unaware of
+/// link sessions.
+pub fn router<S: Clone + Send + Sync + 'static>() -> Router<S> {
+    Router::new()
+        .route("/:room", get(link_socket))
+        .with_state(Arc::new(LinkRooms::default()))
+}
+
+fn valid_room_name(room: &str) -> bool {
+    !room.is_empty()
+        && room.len() <= MAX_ROOM_NAME_LEN
+        && room.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+async fn link_socket(
+    _auth: AuthUser,
+    Path(room): Path<String>,
+    State(rooms): State<Arc<LinkRooms>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !valid_room_name(&room) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let (tx, rx) = mpsc::unbounded_channel();
+    let Some(id) = rooms.join(&room, tx) else {
+        return StatusCode::CONFLICT.into_response();
+    };
+    ws.on_upgrade(move |socket| relay(socket, rooms, room, id, rx))
+}
+
+async fn relay(
+    mut socket: WebSocket,
+    rooms: Arc<LinkRooms>,
+    room: String,
+    id: u64,
+    mut from_peer: mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    loop {
+        tokio::select! {
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Binary(bytes))) => rooms.forward(&room, id, &bytes),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            Some(bytes) = from_peer.recv() => {
+                if socket.send(Message::Binary(bytes)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    rooms.leave(&room, id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn room_holds_two_peers() {
+        let rooms = LinkRooms::default();
+        let (a, _ra) = mpsc::unbounded_channel();
+        let (b, _rb) = mpsc::unbounded_channel();
+        let (c, _rc) = mpsc::unbounded_channel();
+        assert!(rooms.join("tetris", a).is_some());
+        assert!(rooms.join("tetris", b).is_some());
+        assert!(rooms.join("tetris", c.clone()).is_none());
+        assert!(rooms.join("pokemon", c).is_some());
+    }
+
+    #[test]
+    fn forward_reaches_only_the_other_peer() {
+        let rooms = LinkRooms::default();
+        let (a, mut ra) = mpsc::unbounded_channel();
+        let (b, mut rb) = mpsc::unbounded_channel();
+        let id_a = rooms.join("room", a).unwrap();
+        rooms.join("room", b).unwrap();
+
+        rooms.forward("room", id_a, &[0x01, 0x42]);
+        assert_eq!(rb.try_recv().unwrap(), vec![0x01, 0x42]);
+        assert!(ra.try_recv().is_err());
+    }
+
+    #[test]
+    fn leaving_frees_the_slot() {
+        let rooms = LinkRooms::default();
+        let (a, _ra) = mpsc::unbounded_channel();
+        let (b, _rb) = mpsc::unbounded_channel();
+        let (c, _rc) = mpsc::unbounded_channel();
+        let id_a = rooms.join("room", a).unwrap();
+        rooms.join("room", b).unwrap();
+        rooms.leave("room", id_a);
+        assert!(rooms.join("room", c).is_some());
+    }
+
+    #[test]
+    fn abandoned_peer_is_pruned_on_join() {
+        let rooms = LinkRooms::default();
+        let (a, ra) = mpsc::unbounded_channel();
+        let (b, _rb) = mpsc::unbounded_channel();
+        let (c, _rc) = mpsc::unbounded_channel();
+        rooms.join("room", a).unwrap();
+        rooms.join("room", b).unwrap();
+        drop(ra);
+        assert!(rooms.join("room", c).is_some());
+    }
+
+    #[test]
+    fn room_names_are_restricted() {
+        assert!(valid_room_name("tetris-1_v2"));
+        assert!(!valid_room_name(""));
+        assert!(!valid_room_name("../etc"));
+        assert!(!valid_room_name(&"a".repeat(MAX_ROOM_NAME_LEN + 1)));
+    }
+}
@@ -0,0 +1,139 @@
+use rustyboy_core::memory::{cartridge::CartridgeError, CgbFlag, RomHeader};
+use serde::Serialize;
+
+/// One entry in the `/api/roms` listing.
+#[derive(Debug, Serialize)]
+pub struct RomEntry {
+    /// File name under the ROMs directory; used to fetch `/roms/:name`.
+    pub name: String,
+    /// Parsed cartridge header, or `None` if the file has no valid header.
+    pub header: Option<RomMetadata>,
+    /// Whether the emulator can run this ROM.
+    pub supported: bool,
+    /// Why the ROM can't be run, if it can't.
+    pub error: Option<String>,
+}
+
+/// Cartridge header fields exposed to the client.
+#[derive(Debug, Serialize)]
+pub struct RomMetadata {
+    pub title: String,
+    pub manufacturer_code: Option<String>,
+    pub old_licensee_code: u8,
+    pub new_licensee_code: Option<String>,
+    /// `"dmg"`, `"cgb_enhanced"` or `"cgb_only"`.
+    pub cgb: &'static str,
+    pub sgb: bool,
+    /// Cartridge type name, e.g. `"Mbc5RamBattery"`.
+    pub cartridge_type: String,
+    pub cartridge_type_code: u8,
+    pub rom_size: usize,
+    pub ram_size: usize,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    pub global_checksum_valid: bool,
+}
+
+/// Parse the header of a ROM image and describe it for the ROM listing.
+pub fn describe_rom(name: String, data: &[u8]) -> RomEntry {
+    let header = match RomHeader::parse(data) {
+        Ok(header) => header,
+        Err(e) => {
+            return RomEntry { name, header: None, supported: false, error: Some(e.to_string()) };
+        }
+    };
+
+    let error = if !header.cartridge_type.is_supported() {
+        Some(CartridgeError::UnsupportedCartType(header.cartridge_type.code()))
+    } else if data.len() != header.rom_size {
+        Some(CartridgeError::RomSizeMismatch {
+            expected_bytes: header.rom_size,
+            actual_bytes:   data.len(),
+        })
+    } else {
+        None
+    };
+
+    let metadata = RomMetadata {
+        title:                 header.title.clone(),
+        manufacturer_code:     header.manufacturer_code.clone(),
+        old_licensee_code:     header.old_licensee_code,
+        new_licensee_code:     header.new_licensee_code.clone(),
+        cgb: match header.cgb_flag {
+            CgbFlag::DmgOnly     => "dmg",
+            CgbFlag::CgbEnhanced => "cgb_enhanced",
+            CgbFlag::CgbOnly     => "cgb_only",
+        },
+        sgb:                   header.sgb_flag,
+        cartridge_type:        format!("{:?}", header.cartridge_type),
+        cartridge_type_code:   header.cartridge_type.code(),
+        rom_size:              header.rom_size,
+        ram_size:              header.ram_size,
+        version:               header.version,
+        header_checksum:       header.header_checksum,
+        global_checksum:       header.global_checksum,
+        global_checksum_valid: header.global_checksum_matches(data),
+    };
+
+    RomEntry {
+        name,
+        header: Some(metadata),
+        supported: error.is_none(),
+        error: error.map(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyboy_core::memory::header::{global_checksum, header_checksum};
+
+    fn make_rom(title: &[u8], cart_type: u8) -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0134..0x0134 + title.len()].copy_from_slice(title);
+        rom[0x0147] = cart_type;
+        rom[0x014D] = header_checksum(&rom);
+        let global = global_checksum(&rom).to_be_bytes();
+        rom[0x014E..0x0150].copy_from_slice(&global);
+        rom
+    }
+
+    #[test]
+    fn describes_supported_rom() {
+        let entry = describe_rom("tetris.gb".into(), &make_rom(b"TETRIS", 0x00));
+        assert!(entry.supported);
+        assert_eq!(entry.error, None);
+        let header = entry.header.unwrap();
+        assert_eq!(header.title, "TETRIS");
+        assert_eq!(header.cartridge_type, "RomOnly");
+        assert_eq!(header.cgb, "dmg");
+        assert_eq!(header.rom_size, 0x8000);
+        assert!(header.global_checksum_valid);
+    }
+
+    #[test]
+    fn describes_unsupported_cart_type_with_metadata() {
+        let entry = describe_rom("camera.gb".into(), &make_rom(b"CAMERA", 0xFC));
+        assert!(!entry.supported);
+        assert_eq!(entry.header.unwrap().cartridge_type, "PocketCamera");
+        assert_eq!(entry.error.as_deref(), Some("unsupported cartridge type 0xFC"));
+    }
+
+    #[test]
+    fn describes_truncated_rom_as_unsupported() {
+        let mut rom = make_rom(b"SHORT", 0x00);
+        rom.truncate(0x4000);
+        let entry = describe_rom("short.gb".into(), &rom);
+        assert!(!entry.supported);
+        assert!(entry.header.is_some());
+    }
+
+    #[test]
+    fn describes_garbage_without_header() {
+        let entry = describe_rom("junk.gb".into(), b"fake rom data");
+        assert!(!entry.supported);
+        assert!(entry.header.is_none());
+        assert!(entry.error.is_some());
+    }
+}
@@ -100,7 +100,8 @@ async fn test_list_roms_with_gb_files() {
     let body = axum::body::to_bytes(response.into_body(), usize::MAX)
         .await
         .unwrap();
-    let names: Vec<String> = serde_json::from_slice(&body).unwrap();
+    let roms: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    let names: Vec<&str> = roms.iter().map(|r| r["name"].as_str().unwrap()).collect();
     assert_eq!(names, vec!["a.gb", "b.gbc"]);
 }
 
@@ -116,10 +117,38 @@ async fn test_list_roms_ignores_non_gb() {
     let body = axum::body::to_bytes(response.into_body(), usize::MAX)
         .await
         .unwrap();
-    let names: Vec<String> = serde_json::from_slice(&body).unwrap();
+    let roms: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    let names: Vec<&str> = roms.iter().map(|r| r["name"].as_str().unwrap()).collect();
     assert_eq!(names, vec!["game.gb"]);
 }
 
+#[tokio::test]
+async fn test_list_roms_includes_header_metadata() {
+    let (app, roms_dir, _static) = test_app(&["junk.gb"]).await;
+    let mut rom = vec![0u8; 0x8000];
+    rom[0x0134..0x013A].copy_from_slice(b"TETRIS");
+    rom[0x014D] = rustyboy_core::memory::header::header_checksum(&rom);
+    std::fs::write(roms_dir.path().join("tetris.gb"), &rom).unwrap();
+
+    let req = Request::builder()
+        .uri("/api/roms")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let roms: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(roms[0]["name"], "junk.gb");
+    assert_eq!(roms[0]["header"], serde_json::Value::Null);
+    assert_eq!(roms[0]["supported"], false);
+    assert_eq!(roms[1]["name"], "tetris.gb");
+    assert_eq!(roms[1]["header"]["title"], "TETRIS");
+    assert_eq!(roms[1]["header"]["cartridge_type"], "RomOnly");
+    assert_eq!(roms[1]["supported"], true);
+}
+
 #[tokio::test]
 async fn test_serve_rom_not_found() {
     let (app, _roms, _static) = test_app(&[]).await;
@@ -180,6 +209,17 @@ async fn test_put_battery_save_unauthenticated() {
     assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 }
 
+#[tokio::test]
+async fn test_link_socket_unauthenticated() {
+    let (app, _roms, _static) = test_app(&[]).await;
+    let res = app.oneshot(
+        Request::builder()
+            .uri("/api/link/tetris")
+            .body(Body::empty()).unwrap()
+    ).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn test_get_battery_save_not_found() {
     let (app, cookie) = authed_app().await;
@@ -556,3 +596,70 @@ async fn test_battery_saves_are_per_user() {
     ).await.unwrap();
     assert_eq!(res.status(), StatusCode::NOT_FOUND);
 }
+
+// ── Cheat endpoint tests ──────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_get_cheats_unauthenticated() {
+    let (app, _r, _s) = test_app(&[]).await;
+    let res = app.oneshot(
+        Request::builder().uri("/api/cheats/pokemon.gb").body(Body::empty()).unwrap()
+    ).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_get_cheats_empty() {
+    let (app, cookie) = authed_app().await;
+    let res = app.oneshot(
+        Request::builder()
+            .uri("/api/cheats/pokemon.gb")
+            .header("cookie", &cookie)
+            .body(Body::empty()).unwrap()
+    ).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(body.as_ref(), b"[]");
+}
+
+#[tokio::test]
+async fn test_put_then_get_cheats() {
+    let (app, cookie) = authed_app().await;
+    let list = r#"[{"code":" 010900C0 ","name":"Lives","enabled":true},{"code":"3A1-5AE","enabled":false}]"#;
+
+    let put_res = app.clone().oneshot(
+        Request::builder()
+            .method("PUT").uri("/api/cheats/pokemon.gb")
+            .header("cookie", &cookie)
+            .header("content-type", "application/json")
+            .body(Body::from(list)).unwrap()
+    ).await.unwrap();
+    assert_eq!(put_res.status(), StatusCode::NO_CONTENT);
+
+    let get_res = app.oneshot(
+        Request::builder()
+            .uri("/api/cheats/pokemon.gb")
+            .header("cookie", &cookie)
+            .body(Body::empty()).unwrap()
+    ).await.unwrap();
+    assert_eq!(get_res.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(get_res.into_body(), usize::MAX).await.unwrap();
+    let cheats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(cheats, serde_json::json!([
+        { "code": "010900C0", "name": "Lives", "enabled": true },
+        { "code": "3A1-5AE", "name": "", "enabled": false },
+    ]));
+}
+
+#[tokio::test]
+async fn test_put_cheats_rejects_invalid_code() {
+    let (app, cookie) = authed_app().await;
+    let res = app.oneshot(
+        Request::builder()
+            .method("PUT").uri("/api/cheats/pokemon.gb")
+            .header("cookie", &cookie)
+            .header("content-type", "application/json")
+            .body(Body::from(r#"[{"code":"not a code"}]"#)).unwrap()
+    ).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}